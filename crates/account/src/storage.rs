@@ -120,3 +120,46 @@ where
     }
     Ok(())
 }
+
+/// Get the fee allowance granted by an account to another
+pub fn fee_allowance<S>(
+    storage: &S,
+    granter: &Address,
+    grantee: &Address,
+) -> Result<Option<FeeAllowance>>
+where
+    S: StorageRead,
+{
+    fee_allowances_handle(granter).get(storage, grantee)
+}
+
+/// Grant a fee allowance to an account, replacing any previous one
+pub fn grant_fee_allowance<S>(
+    storage: &mut S,
+    granter: &Address,
+    grantee: &Address,
+    allowance: FeeAllowance,
+) -> Result<()>
+where
+    S: StorageWrite + StorageRead,
+{
+    fee_allowances_handle(granter).insert(
+        storage,
+        grantee.clone(),
+        allowance,
+    )?;
+    Ok(())
+}
+
+/// Revoke the fee allowance granted to an account
+pub fn revoke_fee_allowance<S>(
+    storage: &mut S,
+    granter: &Address,
+    grantee: &Address,
+) -> Result<()>
+where
+    S: StorageWrite + StorageRead,
+{
+    fee_allowances_handle(granter).remove(storage, grantee)?;
+    Ok(())
+}
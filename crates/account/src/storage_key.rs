@@ -5,12 +5,15 @@ use namada_macros::StorageKeys;
 use namada_storage::collections::lazy_map::LazyMap;
 use namada_storage::collections::{LazyCollection, lazy_map};
 
+use crate::FeeAllowance;
+
 /// Storage keys for account.
 #[derive(StorageKeys)]
 struct Keys {
     public_keys: &'static str,
     threshold: &'static str,
     protocol_public_keys: &'static str,
+    fee_allowances: &'static str,
 }
 
 /// Obtain a storage key for user's public key.
@@ -89,3 +92,38 @@ pub fn is_protocol_pk_key(key: &storage::Key) -> Option<&Address> {
         _ => None,
     }
 }
+
+/// Obtain the storage key prefix of the fee allowances granted by an account
+pub fn fee_allowances_key_prefix(granter: &Address) -> storage::Key {
+    storage::Key {
+        segments: vec![
+            DbKeySeg::AddressSeg(granter.to_owned()),
+            DbKeySeg::StringSeg(Keys::VALUES.fee_allowances.to_string()),
+        ],
+    }
+}
+
+/// LazyMap handler for the fee allowances granted by an account, by grantee
+pub fn fee_allowances_handle(
+    granter: &Address,
+) -> LazyMap<Address, FeeAllowance> {
+    LazyMap::open(fee_allowances_key_prefix(granter))
+}
+
+/// Check if the given storage key is a fee allowance key. If it is, returns
+/// the granter and the grantee.
+pub fn is_fee_allowance_key(key: &storage::Key) -> Option<[&Address; 2]> {
+    match &key.segments[..] {
+        [
+            DbKeySeg::AddressSeg(granter),
+            DbKeySeg::StringSeg(prefix),
+            DbKeySeg::StringSeg(data),
+            DbKeySeg::AddressSeg(grantee),
+        ] if prefix.as_str() == Keys::VALUES.fee_allowances
+            && data.as_str() == lazy_map::DATA_SUBKEY =>
+        {
+            Some([granter, grantee])
+        }
+        _ => None,
+    }
+}
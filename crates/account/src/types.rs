@@ -1,7 +1,11 @@
+use std::collections::BTreeSet;
+
 use namada_core::address::Address;
 use namada_core::borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use namada_core::chain::Epoch;
 use namada_core::hash::Hash;
 use namada_core::key::common;
+use namada_core::token;
 use namada_macros::BorshDeserializer;
 #[cfg(feature = "migrations")]
 use namada_migrations::*;
@@ -57,6 +61,56 @@ pub struct UpdateAccount {
     pub threshold: Option<u8>,
 }
 
+/// An allowance granted by an account to pay the fees of the wrapper txs of
+/// another account
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshDeserializer,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub struct FeeAllowance {
+    /// The token in which the fees can be paid
+    pub token: Address,
+    /// The amount of fees that can still be paid with the allowance
+    pub spend_limit: token::Amount,
+    /// The first epoch in which the allowance can no longer be used
+    pub expiration: Option<Epoch>,
+    /// The code hashes of the txs whose fees can be paid with the allowance.
+    /// The fees of any tx can be paid if not set.
+    pub allowed_code_hashes: Option<BTreeSet<Hash>>,
+}
+
+/// A tx data type to grant or revoke a fee allowance
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshDeserializer,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub struct UpdateFeeAllowance {
+    /// The account paying the fees
+    pub granter: Address,
+    /// The account whose fees are paid
+    pub grantee: Address,
+    /// The new allowance, replacing any previous one. The allowance is
+    /// revoked if not set.
+    pub allowance: Option<FeeAllowance>,
+}
+
 #[allow(clippy::cast_possible_truncation)]
 #[cfg(any(test, feature = "testing"))]
 /// Tests and strategies for accounts
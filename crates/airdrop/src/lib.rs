@@ -1,5 +1,7 @@
 //! Airdrop functionality
 
+#[cfg(feature = "migrations")]
+mod schema;
 pub mod storage;
 pub mod storage_key;
 pub mod vp;
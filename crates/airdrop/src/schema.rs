@@ -0,0 +1,9 @@
+//! Storage schemas of the Borsh encoded airdrop keys. The Sapling verifying
+//! key and roots are stored as raw bytes and are not covered here.
+
+use namada_migrations::storage_schema;
+
+storage_schema!(NULLIFIER: "#Airdrop/nullifiers/<nullifier>" => ());
+storage_schema!(
+    VALUE_COMMITMENT_SCHEME: "#Airdrop/sapling/value_commitment_scheme" => u8
+);
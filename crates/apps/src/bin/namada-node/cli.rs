@@ -1,7 +1,7 @@
 //! Namada node CLI.
 
 use eyre::{Context, Result};
-use namada_apps_lib::cli::cmds::{DryRunProposal, TestGenesis};
+use namada_apps_lib::cli::cmds::{DryRunProposal, ExportGenesis, TestGenesis};
 use namada_apps_lib::cli::{self, cmds};
 use namada_apps_lib::config::{NodeLocalConfig, ValidatorLocalConfig};
 #[cfg(not(feature = "migrations"))]
@@ -57,11 +57,16 @@ pub fn main() -> Result<()> {
                 let chain_ctx = ctx.take_chain_or_exit();
                 node::dump_db(chain_ctx.config.ledger, args);
             }
-            cmds::Ledger::RollBack(_) => {
+            cmds::Ledger::RollBack(cmds::LedgerRollBack(args)) => {
                 let chain_ctx = ctx.take_chain_or_exit();
-                node::rollback(chain_ctx.config.ledger)
+                node::rollback(chain_ctx.config.ledger, args.to_height)
                     .wrap_err("Failed to rollback the Namada node")?;
             }
+            cmds::Ledger::VerifyDb(cmds::LedgerVerifyDb) => {
+                let chain_ctx = ctx.take_chain_or_exit();
+                node::verify_db(chain_ctx.config.ledger)
+                    .wrap_err("Failed to verify the Namada node's DB")?;
+            }
             cmds::Ledger::QueryDB(cmds::LedgerQueryDB(args)) => {
                 #[cfg(not(feature = "migrations"))]
                 {
@@ -79,6 +84,18 @@ pub fn main() -> Result<()> {
                     &args.cf,
                 );
             }
+            cmds::Ledger::Inspect(cmds::LedgerInspect(args)) => {
+                #[cfg(not(feature = "migrations"))]
+                {
+                    panic!(
+                        "This command is only available if built with the \
+                         \"migrations\" feature."
+                    )
+                }
+                let chain_ctx = ctx.take_chain_or_exit();
+                #[cfg(feature = "migrations")]
+                node::inspect_db(chain_ctx.config.ledger, &args.key);
+            }
         },
         cli::NamadaNode::Config(cmd, mut ctx) => match cmd {
             cmds::Config::Gen(cmds::ConfigGen) => {
@@ -145,6 +162,22 @@ pub fn main() -> Result<()> {
             cmds::NodeUtils::DryRunProposal(DryRunProposal(args)) => {
                 node::utils::dry_run_proposal(args, global_args)?;
             }
+            cmds::NodeUtils::ExportGenesis(ExportGenesis(args)) => {
+                node::utils::export_genesis(args, global_args)
+                    .wrap_err("Failed to export the genesis templates")?;
+            }
+            cmds::NodeUtils::WasmCache(cmds::WasmCache::Prune(
+                cmds::WasmCachePrune,
+            )) => {
+                node::utils::wasm_cache_prune(global_args)
+                    .wrap_err("Failed to prune the wasm caches")?;
+            }
+            cmds::NodeUtils::WasmCache(cmds::WasmCache::Verify(
+                cmds::WasmCacheVerify,
+            )) => {
+                node::utils::wasm_cache_verify(global_args)
+                    .wrap_err("Failed to verify the wasm caches")?;
+            }
         },
     }
     Ok(())
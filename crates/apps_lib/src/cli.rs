@@ -246,6 +246,7 @@ pub mod cmds {
                 .subcommand(TxUpdateAccount::def().display_order(1))
                 .subcommand(TxInitAccount::def().display_order(1))
                 .subcommand(TxRevealPk::def().display_order(1))
+                .subcommand(TxUpdateFeeAllowance::def().display_order(1))
                 // Governance transactions
                 .subcommand(TxInitProposal::def().display_order(1))
                 .subcommand(TxVoteProposal::def().display_order(1))
@@ -329,6 +330,8 @@ pub mod cmds {
             let tx_update_account =
                 Self::parse_with_ctx(matches, TxUpdateAccount);
             let tx_init_account = Self::parse_with_ctx(matches, TxInitAccount);
+            let tx_update_fee_allowance =
+                Self::parse_with_ctx(matches, TxUpdateFeeAllowance);
             let tx_become_validator =
                 Self::parse_with_ctx(matches, TxBecomeValidator);
             let tx_init_validator =
@@ -421,6 +424,7 @@ pub mod cmds {
                 .or(tx_update_account)
                 .or(tx_init_account)
                 .or(tx_reveal_pk)
+                .or(tx_update_fee_allowance)
                 .or(tx_init_proposal)
                 .or(tx_vote_proposal)
                 .or(tx_become_validator)
@@ -518,6 +522,7 @@ pub mod cmds {
         QueryResult(QueryResult),
         TxUpdateAccount(TxUpdateAccount),
         TxInitAccount(TxInitAccount),
+        TxUpdateFeeAllowance(TxUpdateFeeAllowance),
         TxBecomeValidator(TxBecomeValidator),
         TxInitValidator(TxInitValidator),
         TxCommissionRateChange(TxCommissionRateChange),
@@ -914,7 +919,9 @@ pub mod cmds {
         Reset(LedgerReset),
         DumpDb(LedgerDumpDb),
         QueryDB(LedgerQueryDB),
+        Inspect(LedgerInspect),
         RollBack(LedgerRollBack),
+        VerifyDb(LedgerVerifyDb),
     }
 
     impl SubCmd for Ledger {
@@ -926,12 +933,16 @@ pub mod cmds {
                 let reset = SubCmd::parse(matches).map(Self::Reset);
                 let dump_db = SubCmd::parse(matches).map(Self::DumpDb);
                 let query_db = SubCmd::parse(matches).map(Self::QueryDB);
+                let inspect = SubCmd::parse(matches).map(Self::Inspect);
                 let rollback = SubCmd::parse(matches).map(Self::RollBack);
+                let verify_db = SubCmd::parse(matches).map(Self::VerifyDb);
                 let run_until = SubCmd::parse(matches).map(Self::RunUntil);
                 run.or(reset)
                     .or(dump_db)
                     .or(query_db)
+                    .or(inspect)
                     .or(rollback)
+                    .or(verify_db)
                     .or(run_until)
                     // The `run` command is the default if no sub-command given
                     .or(Some(Self::Run(LedgerRun(args::LedgerRun {
@@ -954,7 +965,9 @@ pub mod cmds {
                 .subcommand(LedgerReset::def())
                 .subcommand(LedgerDumpDb::def())
                 .subcommand(LedgerQueryDB::def())
+                .subcommand(LedgerInspect::def())
                 .subcommand(LedgerRollBack::def())
+                .subcommand(LedgerVerifyDb::def())
         }
     }
 
@@ -1067,21 +1080,71 @@ pub mod cmds {
     }
 
     #[derive(Clone, Debug)]
-    pub struct LedgerRollBack;
+    pub struct LedgerInspect(pub args::LedgerInspect);
+
+    impl SubCmd for LedgerInspect {
+        const CMD: &'static str = "inspect";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches
+                .subcommand_matches(Self::CMD)
+                .map(|matches| Self(args::LedgerInspect::parse(matches)))
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(wrap!(
+                    "Decode the value of a key from the DB and print it as \
+                     JSON while the ledger is not running. The value's type \
+                     is found from the storage schemas registered for the \
+                     protocol's keys."
+                ))
+                .add_args::<args::LedgerInspect>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct LedgerRollBack(pub args::LedgerRollBack);
 
     impl SubCmd for LedgerRollBack {
         const CMD: &'static str = "rollback";
 
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches
+                .subcommand_matches(Self::CMD)
+                .map(|matches| Self(args::LedgerRollBack::parse(matches)))
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(wrap!(
+                    "Roll Namada state back to the previous height, or to the \
+                     given height within the node's rollback window. This \
+                     command does not create a backup of neither the Namada \
+                     nor the Tendermint state before execution: for extra \
+                     safety, it is recommended to make a backup in advance."
+                ))
+                .add_args::<args::LedgerRollBack>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct LedgerVerifyDb;
+
+    impl SubCmd for LedgerVerifyDb {
+        const CMD: &'static str = "verify-db";
+
         fn parse(matches: &ArgMatches) -> Option<Self> {
             matches.subcommand_matches(Self::CMD).map(|_matches| Self)
         }
 
         fn def() -> App {
             App::new(Self::CMD).about(wrap!(
-                "Roll Namada state back to the previous height. This command \
-                 does not create a backup of neither the Namada nor the \
-                 Tendermint state before execution: for extra safety, it is \
-                 recommended to make a backup in advance."
+                "Verify the integrity of Namada ledger node's DB while the \
+                 ledger is not running. The Merkle tree of the last block is \
+                 rebuilt from the stored key-vals and compared with the \
+                 committed one, reporting the first mismatching store and \
+                 keys, if any."
             ))
         }
     }
@@ -1476,6 +1539,30 @@ pub mod cmds {
         }
     }
 
+    #[derive(Clone, Debug)]
+    pub struct TxUpdateFeeAllowance(
+        pub args::TxUpdateFeeAllowance<args::CliTypes>,
+    );
+
+    impl SubCmd for TxUpdateFeeAllowance {
+        const CMD: &'static str = "update-fee-allowance";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches.subcommand_matches(Self::CMD).map(|matches| {
+                TxUpdateFeeAllowance(args::TxUpdateFeeAllowance::parse(matches))
+            })
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(wrap!(
+                    "Send a signed transaction to grant or revoke an \
+                     allowance to pay the fees of another account."
+                ))
+                .add_args::<args::TxUpdateFeeAllowance<args::CliTypes>>()
+        }
+    }
+
     #[derive(Clone, Debug)]
     pub struct TxInitAccount(pub args::TxInitAccount<args::CliTypes>);
 
@@ -2532,6 +2619,8 @@ pub mod cmds {
     pub enum NodeUtils {
         TestGenesis(TestGenesis),
         DryRunProposal(DryRunProposal),
+        ExportGenesis(ExportGenesis),
+        WasmCache(WasmCache),
     }
 
     impl SubCmd for NodeUtils {
@@ -2543,7 +2632,13 @@ pub mod cmds {
                     SubCmd::parse(matches).map(Self::TestGenesis);
                 let dry_run_proposal =
                     SubCmd::parse(matches).map(Self::DryRunProposal);
-                test_genesis.or(dry_run_proposal)
+                let export_genesis =
+                    SubCmd::parse(matches).map(Self::ExportGenesis);
+                let wasm_cache = SubCmd::parse(matches).map(Self::WasmCache);
+                test_genesis
+                    .or(dry_run_proposal)
+                    .or(export_genesis)
+                    .or(wasm_cache)
             })
         }
 
@@ -2552,6 +2647,8 @@ pub mod cmds {
                 .about(wrap!("Utilities."))
                 .subcommand(TestGenesis::def())
                 .subcommand(DryRunProposal::def())
+                .subcommand(ExportGenesis::def())
+                .subcommand(WasmCache::def())
                 .subcommand_required(true)
                 .arg_required_else_help(true)
         }
@@ -2908,6 +3005,99 @@ pub mod cmds {
         }
     }
 
+    #[derive(Clone, Debug)]
+    pub struct ExportGenesis(pub args::ExportGenesis);
+
+    impl SubCmd for ExportGenesis {
+        const CMD: &'static str = "export-genesis";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches
+                .subcommand_matches(Self::CMD)
+                .map(|matches| Self(args::ExportGenesis::parse(matches)))
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(wrap!(
+                    "Export the state of the chain as genesis templates. The \
+                     validator account and bond transactions are written \
+                     unsigned into a separate file and have to be signed with \
+                     `namada client utils sign-genesis-txs`."
+                ))
+                .add_args::<args::ExportGenesis>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub enum WasmCache {
+        Prune(WasmCachePrune),
+        Verify(WasmCacheVerify),
+    }
+
+    impl SubCmd for WasmCache {
+        const CMD: &'static str = "wasm-cache";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches.subcommand_matches(Self::CMD).and_then(|matches| {
+                let prune = SubCmd::parse(matches).map(Self::Prune);
+                let verify = SubCmd::parse(matches).map(Self::Verify);
+                prune.or(verify)
+            })
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(wrap!(
+                    "Manage the on-disk wasm compilation caches of the chain."
+                ))
+                .subcommand(WasmCachePrune::def())
+                .subcommand(WasmCacheVerify::def())
+                .subcommand_required(true)
+                .arg_required_else_help(true)
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct WasmCachePrune;
+
+    impl SubCmd for WasmCachePrune {
+        const CMD: &'static str = "prune";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches.subcommand_matches(Self::CMD).map(|_matches| Self)
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD).about(wrap!(
+                "Remove the wasm compiled by other versions of the node, the \
+                 wasm that cannot be loaded and the wasm that are not allowed \
+                 by the allowlists anymore from the on-disk compilation \
+                 caches. It is recommended to run it while the ledger is not \
+                 running."
+            ))
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct WasmCacheVerify;
+
+    impl SubCmd for WasmCacheVerify {
+        const CMD: &'static str = "verify";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches.subcommand_matches(Self::CMD).map(|_matches| Self)
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD).about(wrap!(
+                "Verify the on-disk wasm compilation caches by loading all \
+                 the compiled wasm, reporting those that cannot be loaded and \
+                 those compiled by other versions of the node."
+            ))
+        }
+    }
+
     #[derive(Clone, Debug)]
     pub struct SignGenesisTxs(pub args::SignGenesisTxs);
 
@@ -3452,8 +3642,8 @@ pub mod args {
         TX_INIT_PROPOSAL, TX_REACTIVATE_VALIDATOR_WASM, TX_REDELEGATE_WASM,
         TX_RESIGN_STEWARD, TX_REVEAL_PK, TX_TRANSFER_WASM, TX_UNBOND_WASM,
         TX_UNJAIL_VALIDATOR_WASM, TX_UPDATE_ACCOUNT_WASM,
-        TX_UPDATE_STEWARD_COMMISSION, TX_VOTE_PROPOSAL, TX_WITHDRAW_WASM,
-        VP_USER_WASM,
+        TX_UPDATE_FEE_ALLOWANCE_WASM, TX_UPDATE_STEWARD_COMMISSION,
+        TX_VOTE_PROPOSAL, TX_WITHDRAW_WASM, VP_USER_WASM,
     };
     use namada_sdk::{DEFAULT_GAS_LIMIT, token};
 
@@ -3474,8 +3664,11 @@ pub mod args {
     pub const ALIAS_FORCE: ArgFlag = flag("alias-force");
     pub const ALIAS_MANY: ArgMulti<String, GlobPlus> = arg_multi("aliases");
     pub const ALLOW_DUPLICATE_IP: ArgFlag = flag("allow-duplicate-ip");
+    pub const ALLOWED_TX_CODE_PATHS: ArgMulti<PathBuf, GlobStar> =
+        arg_multi("allowed-tx-code-paths");
     pub const AMOUNT: Arg<token::DenominatedAmount> = arg("amount");
     pub const ARCHIVE_DIR: ArgOpt<PathBuf> = arg_opt("archive-dir");
+    pub const AUTO_GAS: ArgFlag = flag("auto-gas");
     pub const AVATAR_OPT: ArgOpt<String> = arg_opt("avatar");
     pub const BALANCE_OWNER: Arg<WalletBalanceOwner> = arg("owner");
     pub const BASE_DIR: ArgDefault<PathBuf> = arg_default(
@@ -3569,6 +3762,11 @@ pub mod args {
     pub const EMAIL_OPT: ArgOpt<String> = EMAIL.opt();
     pub const FEE_AMOUNT_OPT: ArgOpt<token::DenominatedAmount> =
         arg_opt("gas-price");
+    pub const FEE_ALLOWANCE_EXPIRATION: ArgOpt<Epoch> =
+        arg_opt("expiration-epoch");
+    pub const FEE_ALLOWANCE_TOKEN: ArgDefaultFromCtx<WalletAddrOrNativeToken> =
+        arg_default_from_ctx("token", DefaultFn(|| "".parse().unwrap()));
+    pub const FEE_GRANTER_OPT: ArgOpt<WalletAddress> = arg_opt("fee-granter");
     pub const FEE_PAYER_OPT: ArgOpt<WalletPublicKey> = arg_opt("gas-payer");
     pub const FILE_PATH: Arg<String> = arg("file");
     pub const FORCE: ArgFlag = flag("force");
@@ -3577,6 +3775,11 @@ pub mod args {
         "gas-limit",
         DefaultFn(|| GasLimit::from(DEFAULT_GAS_LIMIT)),
     );
+    pub const GAS_MULTIPLIER: ArgDefault<Dec> = arg_default(
+        "gas-multiplier",
+        DefaultFn(|| GasEstimation::default().safety_multiplier),
+    );
+    pub const GAS_PROFILE: ArgFlag = flag("gas-profile");
     pub const GAS_SPENDING_KEY: ArgOpt<WalletSpendingKey> =
         arg_opt("gas-spending-key");
     pub const FEE_TOKEN: ArgDefaultFromCtx<WalletAddrOrNativeToken> =
@@ -3588,6 +3791,8 @@ pub mod args {
         arg("genesis-validator").opt();
     pub const GENESIS_VALIDATOR_ADDRESS: Arg<EstablishedAddress> =
         arg("validator");
+    pub const GRANTEE: Arg<WalletAddress> = arg("grantee");
+    pub const GRANTER: Arg<WalletAddress> = arg("granter");
     pub const HALT_ACTION: ArgFlag = flag("halt");
     pub const HASH: Arg<String> = arg("hash");
     pub const HASH_OPT: ArgOpt<String> = arg_opt("hash");
@@ -3695,6 +3900,7 @@ pub mod args {
         arg_opt("refund-target");
     pub const RELAYER: Arg<Address> = arg("relayer");
     pub const RETRIES: ArgOpt<u64> = arg_opt("retries");
+    pub const REVOKE: ArgFlag = flag("revoke");
     pub const SCHEME: ArgDefault<SchemeType> =
         arg_default("scheme", DefaultFn(|| SchemeType::Ed25519));
     pub const SHELL: Arg<Shell> = arg("shell");
@@ -3710,6 +3916,8 @@ pub mod args {
     pub const SOURCE: Arg<WalletAddress> = arg("source");
     pub const SOURCE_OPT: ArgOpt<WalletAddress> = SOURCE.opt();
     pub const SOURCE_VALIDATOR: Arg<WalletAddress> = arg("source-validator");
+    pub const SPEND_LIMIT_OPT: ArgOpt<token::DenominatedAmount> =
+        arg_opt("spend-limit");
     pub const SPENDING_KEY_SOURCE: Arg<WalletSpendingKey> = arg("source");
     pub const SPENDING_KEYS: ArgMulti<WalletSpendingKey, GlobStar> =
         arg_multi("spending-keys");
@@ -4015,6 +4223,27 @@ pub mod args {
         }
     }
 
+    #[derive(Clone, Debug)]
+    pub struct LedgerRollBack {
+        pub to_height: Option<BlockHeight>,
+    }
+
+    impl Args for LedgerRollBack {
+        fn parse(matches: &ArgMatches) -> Self {
+            let to_height = BLOCK_HEIGHT_TO_OPT.parse(matches);
+            Self { to_height }
+        }
+
+        fn def(app: App) -> App {
+            app.arg(BLOCK_HEIGHT_TO_OPT.def().help(wrap!(
+                "The block height to roll back to. The data needed to restore \
+                 the state is only kept for the number of blocks set in the \
+                 `rollback_window_blocks` config. Defaults to the previous \
+                 height."
+            )))
+        }
+    }
+
     #[derive(Clone, Debug)]
     pub struct LedgerQueryDb {
         pub key: storage::Key,
@@ -4050,6 +4279,22 @@ pub mod args {
         }
     }
 
+    #[derive(Clone, Debug)]
+    pub struct LedgerInspect {
+        pub key: storage::Key,
+    }
+
+    impl Args for LedgerInspect {
+        fn parse(matches: &ArgMatches) -> Self {
+            let key = storage::Key::parse(DB_KEY.parse(matches)).unwrap();
+            Self { key }
+        }
+
+        fn def(app: App) -> App {
+            app.arg(DB_KEY.def().help(wrap!("A database key to inspect")))
+        }
+    }
+
     #[derive(Clone, Debug)]
     pub struct UpdateValidatorLocalConfig {
         pub config_path: PathBuf,
@@ -5928,6 +6173,108 @@ pub mod args {
         }
     }
 
+    impl CliToSdk<TxUpdateFeeAllowance<SdkTypes>>
+        for TxUpdateFeeAllowance<CliTypes>
+    {
+        type Error = std::io::Error;
+
+        fn to_sdk(
+            self,
+            ctx: &mut Context,
+        ) -> Result<TxUpdateFeeAllowance<SdkTypes>, Self::Error> {
+            let tx = self.tx.to_sdk(ctx)?;
+            let chain_ctx = ctx.borrow_mut_chain_or_exit();
+
+            Ok(TxUpdateFeeAllowance::<SdkTypes> {
+                tx,
+                granter: chain_ctx.get(&self.granter),
+                grantee: chain_ctx.get(&self.grantee),
+                token: chain_ctx.get(&self.token).into(),
+                spend_limit: self.spend_limit,
+                expiration: self.expiration,
+                allowed_tx_code_paths: self.allowed_tx_code_paths,
+                revoke: self.revoke,
+                tx_code_path: self.tx_code_path,
+            })
+        }
+    }
+
+    impl Args for TxUpdateFeeAllowance<CliTypes> {
+        fn parse(matches: &ArgMatches) -> Self {
+            let tx = Tx::parse(matches);
+            let granter = GRANTER.parse(matches);
+            let grantee = GRANTEE.parse(matches);
+            let token = FEE_ALLOWANCE_TOKEN.parse(matches);
+            // The spend limit is only required to grant an allowance
+            let spend_limit = InputAmount::Unvalidated(
+                SPEND_LIMIT_OPT.parse(matches).unwrap_or_else(|| {
+                    token::DenominatedAmount::new(
+                        token::Amount::zero(),
+                        NATIVE_MAX_DECIMAL_PLACES.into(),
+                    )
+                }),
+            );
+            let expiration = FEE_ALLOWANCE_EXPIRATION.parse(matches);
+            let allowed_tx_code_paths = ALLOWED_TX_CODE_PATHS.parse(matches);
+            let revoke = REVOKE.parse(matches);
+            let tx_code_path = PathBuf::from(TX_UPDATE_FEE_ALLOWANCE_WASM);
+            Self {
+                tx,
+                granter,
+                grantee,
+                token,
+                spend_limit,
+                expiration,
+                allowed_tx_code_paths,
+                revoke,
+                tx_code_path,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Tx<CliTypes>>()
+                .arg(GRANTER.def().help(wrap!(
+                    "The address of the account paying the fees. Its key is \
+                     used to produce the signature."
+                )))
+                .arg(GRANTEE.def().help(wrap!(
+                    "The address of the account whose fees are paid."
+                )))
+                .arg(FEE_ALLOWANCE_TOKEN.def().help(wrap!(
+                    "The token in which the fees can be paid. Defaults to the \
+                     native token."
+                )))
+                .arg(
+                    SPEND_LIMIT_OPT
+                        .def()
+                        .help(wrap!(
+                            "The maximum amount of fees that can be paid with \
+                             the allowance."
+                        ))
+                        .required_unless_present(REVOKE.name),
+                )
+                .arg(FEE_ALLOWANCE_EXPIRATION.def().help(wrap!(
+                    "The epoch from which the allowance can no longer be used."
+                )))
+                .arg(ALLOWED_TX_CODE_PATHS.def().help(wrap!(
+                    "Restrict the allowance to transactions running only the \
+                     WASM codes at the given paths."
+                )))
+                .arg(
+                    REVOKE
+                        .def()
+                        .help(wrap!(
+                            "Revoke the allowance instead of granting it."
+                        ))
+                        .conflicts_with_all([
+                            SPEND_LIMIT_OPT.name,
+                            FEE_ALLOWANCE_EXPIRATION.name,
+                            ALLOWED_TX_CODE_PATHS.name,
+                        ]),
+                )
+        }
+    }
+
     impl CliToSdk<Bond<SdkTypes>> for Bond<CliTypes> {
         type Error = std::io::Error;
 
@@ -7897,10 +8244,13 @@ pub mod args {
                 wrapper_fee_payer: wrapper
                     .wrapper_fee_payer
                     .map(|x| ctx.get(&x)),
+                fee_granter: wrapper.fee_granter.map(|x| ctx.get(&x)),
+                gas_estimation: wrapper.gas_estimation,
             });
 
             Ok(Tx::<SdkTypes> {
                 dry_run: self.dry_run,
+                gas_profile: self.gas_profile,
                 dump_tx: self.dump_tx,
                 output_folder: self.output_folder,
                 force: self.force,
@@ -7944,6 +8294,11 @@ pub mod args {
                     ))
                     .conflicts_with(DRY_RUN_TX.name),
             )
+            .arg(GAS_PROFILE.def().help(wrap!(
+                "Display the breakdown of the gas consumed by the simulated \
+                 transaction, per host function, VP, wasm code and storage \
+                 access. Only used with --dry-run or --dry-run-wrapper."
+            )))
             .arg(
                 DUMP_TX
                     .def()
@@ -7984,6 +8339,15 @@ pub mod args {
             .arg(GAS_LIMIT.def().help(wrap!(
                 "The maximum amount of gas the transaction can use."
             )))
+            .arg(AUTO_GAS.def().help(wrap!(
+                "Estimate the gas limit of the transaction by dry-running it. \
+                 The gas limit provided with --gas-limit is then used as the \
+                 upper bound of the estimation."
+            )))
+            .arg(GAS_MULTIPLIER.def().help(wrap!(
+                "The multiplier applied to the gas consumed by the dry-run \
+                 when estimating the gas limit with --auto-gas."
+            )))
             .arg(WALLET_ALIAS_FORCE.def().help(wrap!(
                 "Override the alias without confirmation if it already exists."
             )))
@@ -8024,6 +8388,11 @@ pub mod args {
                  to pay fees via the MASP (recommended for transactions where \
                  the source is a shielded address)."
             )))
+            .arg(FEE_GRANTER_OPT.def().help(wrap!(
+                "The address of an account that granted a fee allowance to \
+                 the gas payer. The fees are then paid by this account \
+                 instead of the gas payer."
+            )))
             .arg(
                 USE_DEVICE
                     .def()
@@ -8052,6 +8421,7 @@ pub mod args {
             } else {
                 None
             };
+            let gas_profile = GAS_PROFILE.parse(matches);
             let dump_tx = if DUMP_TX.parse(matches) {
                 Some(DumpTx::Inner)
             } else if DUMP_WRAPPER_TX.parse(matches) {
@@ -8067,6 +8437,10 @@ pub mod args {
                 FEE_AMOUNT_OPT.parse(matches).map(InputAmount::Unvalidated);
             let fee_token = FEE_TOKEN.parse(matches);
             let gas_limit = GAS_LIMIT.parse(matches);
+            let gas_estimation =
+                AUTO_GAS.parse(matches).then(|| GasEstimation {
+                    safety_multiplier: GAS_MULTIPLIER.parse(matches),
+                });
             let wallet_alias_force = WALLET_ALIAS_FORCE.parse(matches);
             let expiration = EXPIRATION_OPT.parse(matches);
             let signing_keys = SIGNING_KEYS.parse(matches);
@@ -8075,6 +8449,7 @@ pub mod args {
             let password = None;
             let memo = MEMO_OPT.parse(matches).map(String::into_bytes);
             let wrapper_fee_payer = FEE_PAYER_OPT.parse(matches);
+            let fee_granter = FEE_GRANTER_OPT.parse(matches);
             let output_folder = OUTPUT_FOLDER_PATH.parse(matches);
             let use_device = USE_DEVICE.parse(matches);
             let no_expiration = NO_EXPIRATION.parse(matches);
@@ -8094,12 +8469,15 @@ pub mod args {
                     broadcast_only,
                     fee_amount,
                     wrapper_fee_payer,
+                    fee_granter,
                     fee_token,
                     gas_limit,
+                    gas_estimation,
                 }),
             };
             Self {
                 dry_run,
+                gas_profile,
                 dump_tx,
                 force,
                 ledger_address,
@@ -9238,6 +9616,35 @@ pub mod args {
         }
     }
 
+    #[derive(Clone, Debug)]
+    pub struct ExportGenesis {
+        /// The height of the state to export. Defaults to the last committed
+        /// height.
+        pub height: Option<BlockHeight>,
+        /// Output templates dir
+        pub path: PathBuf,
+    }
+
+    impl Args for ExportGenesis {
+        fn parse(matches: &ArgMatches) -> Self {
+            let height = BLOCK_HEIGHT_OPT.parse(matches);
+            let path = PATH.parse(matches);
+            Self { height, path }
+        }
+
+        fn def(app: App) -> App {
+            app.arg(BLOCK_HEIGHT_OPT.def().help(wrap!(
+                "The height of the state to export. Defaults to the last \
+                 committed height. The MASP keys don't keep the history of \
+                 their values, so the balances of the MASP are always \
+                 exported at the last committed height."
+            )))
+            .arg(PATH.def().help(wrap!(
+                "Path to the directory to write the template files into."
+            )))
+        }
+    }
+
     #[derive(Clone, Debug)]
     pub struct SignGenesisTxs {
         pub path: PathBuf,
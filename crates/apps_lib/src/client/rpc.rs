@@ -36,7 +36,7 @@ use namada_sdk::key::*;
 use namada_sdk::masp::MaspTokenRewardData;
 use namada_sdk::masp::shielded_wallet::ShieldedApi;
 use namada_sdk::parameters::{
-    EpochDuration, ProposalBytes, storage as param_storage,
+    EpochDuration, FeeMarketParams, ProposalBytes, storage as param_storage,
 };
 use namada_sdk::proof_of_stake::rewards::PosRewardsRates;
 use namada_sdk::proof_of_stake::types::{
@@ -892,6 +892,9 @@ pub async fn query_protocol_parameters(
     let epochs_per_year: u64 = query_storage_value(context.client(), &key)
         .await
         .expect("Parameter should be defined.");
+    let fee_market = rpc::query_fee_market_parameters(context.client())
+        .await
+        .expect("Fee market parameters should be readable.");
 
     // Reconstruct the Parameters type to ensure we retrieved all of them
     let parameters = namada_core::parameters::Parameters {
@@ -908,6 +911,7 @@ pub async fn query_protocol_parameters(
         gas_scale,
         minimum_gas_price,
         is_native_token_transferable,
+        fee_market,
     };
     // Deconstruct the type to ensure we display all the fields
     let namada_core::parameters::Parameters {
@@ -924,6 +928,7 @@ pub async fn query_protocol_parameters(
         gas_scale,
         minimum_gas_price,
         is_native_token_transferable,
+        fee_market,
     } = parameters;
 
     display_line!(
@@ -978,16 +983,16 @@ pub async fn query_protocol_parameters(
         masp_fee_payment_gas_limit
     );
     display_line!(context.io(), "{:4}Minimum gas costs:", "");
-    for (token, gas_cost) in minimum_gas_price {
-        let denom = rpc::query_denom(context.client(), &token)
+    for (token, gas_cost) in &minimum_gas_price {
+        let denom = rpc::query_denom(context.client(), token)
             .await
             .expect("Token should have denom");
-        let den_amt = DenominatedAmount::new(gas_cost, denom);
+        let den_amt = DenominatedAmount::new(*gas_cost, denom);
         display_line!(
             context.io(),
             "{:8}{}: {} per gas unit",
             "",
-            &token,
+            token,
             den_amt
         );
     }
@@ -998,6 +1003,55 @@ pub async fn query_protocol_parameters(
         "",
         is_native_token_transferable
     );
+    match fee_market {
+        Some(FeeMarketParams {
+            target_block_gas_fraction,
+            max_base_fee_change,
+            base_fee_destination,
+        }) => {
+            display_line!(context.io(), "{:4}Fee market:", "");
+            display_line!(
+                context.io(),
+                "{:8}Target block gas fraction: {}",
+                "",
+                target_block_gas_fraction
+            );
+            display_line!(
+                context.io(),
+                "{:8}Max. base fee change: {}",
+                "",
+                max_base_fee_change
+            );
+            display_line!(
+                context.io(),
+                "{:8}Base fee destination: {}",
+                "",
+                base_fee_destination
+            );
+            display_line!(context.io(), "{:8}Current base fees:", "");
+            for token in minimum_gas_price.keys() {
+                let Some(base_fee) =
+                    rpc::query_base_fee(context.client(), token)
+                        .await
+                        .expect("Base fee should be readable")
+                else {
+                    continue;
+                };
+                let denom = rpc::query_denom(context.client(), token)
+                    .await
+                    .expect("Token should have denom");
+                let den_amt = DenominatedAmount::new(base_fee, denom);
+                display_line!(
+                    context.io(),
+                    "{:12}{}: {} per gas unit",
+                    "",
+                    token,
+                    den_amt
+                );
+            }
+        }
+        None => display_line!(context.io(), "{:4}Fee market: disabled", ""),
+    }
 
     display_line!(context.io(), "\nProof of Stake parameters");
     let PosParams {
@@ -258,6 +258,14 @@ where
         // Sign the batch with the union of the signers required for each part
         match batched_signing_data {
             either::Either::Left(wrapper_sig_data) => {
+                // The batch kept the gas limit estimated for its first tx
+                tx::estimate_batch_gas_limit(
+                    namada,
+                    args,
+                    &mut batched_tx,
+                    &wrapper_sig_data,
+                )
+                .await?;
                 sign(
                     namada,
                     &mut batched_tx,
@@ -367,6 +375,26 @@ where
     Ok(())
 }
 
+pub async fn submit_update_fee_allowance<N: Namada>(
+    namada: &N,
+    args: args::TxUpdateFeeAllowance,
+) -> Result<(), error::Error>
+where
+    <N::Client as namada_sdk::io::Client>::Error: std::fmt::Display,
+{
+    let (mut tx, signing_data) = args.build(namada).await?;
+
+    if let Some(dump_tx) = args.tx.dump_tx {
+        tx::dump_tx(namada.io(), dump_tx, args.tx.output_folder, tx)?;
+    } else {
+        sign(namada, &mut tx, &args.tx, signing_data).await?;
+
+        namada.submit(tx, &args.tx).await?;
+    }
+
+    Ok(())
+}
+
 pub async fn submit_init_account<N: Namada>(
     namada: &N,
     args: args::TxInitAccount,
@@ -2,6 +2,7 @@
 //! The parameters used for the chain's genesis
 
 pub mod chain;
+pub mod export;
 pub mod templates;
 pub mod transactions;
 pub mod utils;
@@ -336,6 +336,7 @@ impl Finalized {
             minimum_gas_price,
             max_tx_bytes,
             is_native_token_transferable,
+            fee_market,
             ..
         } = self.parameters.parameters.clone();
 
@@ -385,6 +386,7 @@ impl Finalized {
                 })
                 .collect(),
             is_native_token_transferable,
+            fee_market,
         }
     }
 
@@ -0,0 +1,827 @@
+//! Export of a chain's state as genesis templates, e.g. to restart a chain from
+//! its state after a catastrophic halt or to spin up a test network from a
+//! live chain's state.
+//!
+//! The addresses of established accounts and tokens are derived from their
+//! genesis configuration, so they cannot be preserved by an export. Instead,
+//! every established account that owns some tokens, has some bonds, or is a
+//! validator or a PGF steward is re-created from its public keys and threshold
+//! and the new addresses are reported in [`ExportedTemplates::addresses`].
+//! Tokens are aliased with their old address, except for the native token.
+//! Their new addresses are derived from their alias and config and reported
+//! too. The addresses of IBC tokens are derived from their IBC trace instead,
+//! which genesis tokens cannot preserve, so the export fails if any account
+//! holds IBC tokens.
+//!
+//! The governance minimum proposal fund is a whole amount of native tokens in
+//! the templates, so a fractional amount is rounded up.
+//!
+//! Bonds are exported with their current amount net of slashes, while the
+//! unbonded tokens are credited back to their owners. The validator account
+//! and bond txs have to be signed by their owners, so they are written unsigned
+//! into a separate file (see [`UNSIGNED_TRANSACTIONS_FILE_NAME`]) that can be
+//! signed with `namada client utils sign-genesis-txs`. The CometBFT node key
+//! and the P2P address of a validator are not part of the chain's state, so the
+//! exported txs use the consensus key and a placeholder address instead, which
+//! the validator must replace before signing.
+//!
+//! Governance proposals, MASP notes and the IBC and Ethereum bridge states are
+//! not exported. The balances of internal addresses other than PoS are
+//! exported as they are.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::net::SocketAddr;
+use std::path::Path;
+
+use namada_sdk::address::{Address, EstablishedAddress, InternalAddress};
+use namada_sdk::governance::pgf::storage::{
+    get_parameters as get_pgf_parameters, get_stewards,
+};
+use namada_sdk::governance::storage::get_parameters as get_gov_parameters;
+use namada_sdk::ibc::parameters::IbcParameters;
+use namada_sdk::ibc::storage::{
+    ibc_trace_key_prefix, is_ibc_trace_key, params_key as ibc_params_key,
+};
+use namada_sdk::ibc::trace::ibc_token;
+use namada_sdk::key::common;
+use namada_sdk::proof_of_stake::PosParams;
+use namada_sdk::proof_of_stake::queries::bonds_and_unbonds;
+use namada_sdk::proof_of_stake::storage::{
+    read_all_validator_addresses, read_pos_params,
+    read_validator_max_commission_rate_change, read_validator_metadata,
+    validator_commission_rate_handle, validator_consensus_key_handle,
+    validator_eth_cold_key_handle, validator_eth_hot_key_handle,
+    validator_protocol_key_handle,
+};
+use namada_sdk::proof_of_stake::types::ValidatorMetaData;
+use namada_sdk::storage::{Key, StorageRead, iter_prefix_with_filter_map};
+use namada_sdk::string_encoding::StringEncoded;
+use namada_sdk::token::storage_key::is_any_token_balance_key;
+use namada_sdk::token::{
+    Amount, DenominatedAmount, Denomination, NATIVE_SCALE,
+};
+use namada_sdk::{account, governance, parameters, token};
+
+use super::GenesisAddress;
+use super::chain::DeriveEstablishedAddress;
+use super::templates::{
+    self, All, ChainParams, GovernanceParams, IbcParams, Parameters, PgfParams,
+    RawTokenBalances, TokenConfig, Tokens, UndenominatedBalances, Unvalidated,
+    ValidityPredicates, WasmVpConfig,
+};
+use super::transactions::{
+    BondTx, EstablishedAccountTx, Transactions, UnsignedTransactions,
+    UnsignedValidatorAccountTx,
+};
+use super::utils::{VP_USER, write_toml};
+use crate::wallet::Alias;
+
+pub const UNSIGNED_TRANSACTIONS_FILE_NAME: &str = "unsigned-transactions.toml";
+
+/// Validity predicate assigned to implicit accounts.
+const VP_IMPLICIT: &str = "vp_implicit";
+
+/// Alias of the native token.
+const NATIVE_TOKEN_ALIAS: &str = "nam";
+
+/// P2P address of the exported validators, to be replaced by the validators.
+const PLACEHOLDER_NET_ADDRESS: &str = "127.0.0.1:26656";
+
+/// Genesis templates exported from a chain's state
+#[derive(Clone, Debug)]
+pub struct ExportedTemplates {
+    /// The templates with the established account txs. The validator account
+    /// and bond txs have to be signed before they can be added to them.
+    pub templates: All<Unvalidated>,
+    /// The validator account and bond txs to be signed by their owners
+    pub unsigned_txs: UnsignedTransactions,
+    /// The new addresses of the exported established accounts and tokens,
+    /// keyed by their old addresses
+    pub addresses: BTreeMap<Address, Address>,
+    /// The parts of the state that could not be exported
+    pub warnings: Vec<String>,
+}
+
+impl ExportedTemplates {
+    /// Write the templates and the unsigned txs into the given directory.
+    pub fn write_toml_files(&self, output_dir: &Path) -> eyre::Result<()> {
+        self.templates.write_toml_files(output_dir)?;
+        write_toml(
+            &self.unsigned_txs,
+            &output_dir.join(UNSIGNED_TRANSACTIONS_FILE_NAME),
+            "Unsigned transactions",
+        )
+    }
+}
+
+/// Export the state of a chain as genesis templates.
+pub fn export_templates<S>(storage: &S) -> eyre::Result<ExportedTemplates>
+where
+    S: StorageRead,
+{
+    let mut warnings = vec![];
+    let native_token = storage.get_native_token()?;
+    let epoch = storage.get_block_epoch()?;
+    let pos_params = read_pos_params::<S, governance::Store<S>>(storage)?;
+
+    // Read all the balances and find the tokens
+    let mut balances: BTreeMap<Address, BTreeMap<Address, Amount>> =
+        BTreeMap::new();
+    let multitoken_prefix =
+        Key::from(Address::Internal(InternalAddress::Multitoken).to_db_key());
+    for balance in
+        iter_prefix_with_filter_map(storage, multitoken_prefix, |key| {
+            is_any_token_balance_key(key)
+                .map(|[token, owner]| (token.clone(), owner.clone()))
+        })?
+    {
+        let ((token, owner), amount): ((Address, Address), Amount) = balance?;
+        if amount.is_zero() {
+            continue;
+        }
+        balances.entry(token).or_default().insert(owner, amount);
+    }
+
+    // IBC tokens would lose their IBC trace
+    let ibc_tokens = balances
+        .keys()
+        .filter(|token| {
+            matches!(token, Address::Internal(InternalAddress::IbcToken(_)))
+        })
+        .collect::<BTreeSet<_>>();
+    if !ibc_tokens.is_empty() {
+        let mut ibc_traces = BTreeMap::new();
+        for trace in iter_prefix_with_filter_map(
+            storage,
+            ibc_trace_key_prefix(None),
+            |key| is_ibc_trace_key(key).map(|_| ()),
+        )? {
+            let ((), trace): ((), String) = trace?;
+            ibc_traces.insert(ibc_token(&trace), trace);
+        }
+        let ibc_tokens = ibc_tokens
+            .into_iter()
+            .map(|token| match ibc_traces.get(token) {
+                Some(trace) => format!("{token} ({trace})"),
+                None => token.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        eyre::bail!(
+            "The IBC tokens {ibc_tokens} cannot be exported, genesis tokens \
+             cannot preserve their IBC traces"
+        );
+    }
+
+    // The native token is always exported, even if it has no balances
+    let mut tokens = BTreeMap::new();
+    for token in balances.keys().chain([&native_token]) {
+        let alias = if *token == native_token {
+            Alias::from(NATIVE_TOKEN_ALIAS)
+        } else {
+            Alias::from(token.to_string())
+        };
+        let denom = token::read_denom(storage, token)?.ok_or_else(|| {
+            eyre::eyre!("The denomination of token {token} is missing")
+        })?;
+        let masp_params = token::read_params(storage, token, &denom)?;
+        tokens
+            .insert(token.clone(), (alias, TokenConfig { denom, masp_params }));
+    }
+    let native_denom = tokens
+        .get(&native_token)
+        .map(|(_, config)| config.denom)
+        .unwrap_or_else(|| token::NATIVE_MAX_DECIMAL_PLACES.into());
+
+    // Unbonded tokens are credited back to their owners. Bonded tokens are
+    // credited too, to be debited by the bond txs.
+    let mut bonds = BTreeMap::<(Address, Address), Amount>::new();
+    for (bond_id, details) in
+        bonds_and_unbonds::<S, governance::Store<S>>(storage, None, None)?
+    {
+        let mut bonded = Amount::zero();
+        for bond in details.bonds {
+            let amount = bond
+                .amount
+                .checked_sub(bond.slashed_amount.unwrap_or_default())
+                .unwrap_or_default();
+            bonded = checked_add(bonded, amount)?;
+        }
+        let mut unbonded = Amount::zero();
+        for unbond in details.unbonds {
+            let amount = unbond
+                .amount
+                .checked_sub(unbond.slashed_amount.unwrap_or_default())
+                .unwrap_or_default();
+            unbonded = checked_add(unbonded, amount)?;
+        }
+        let owner_balances = balances.entry(native_token.clone()).or_default();
+        let balance = owner_balances.entry(bond_id.source.clone()).or_default();
+        *balance = checked_add(*balance, checked_add(bonded, unbonded)?)?;
+        if !bonded.is_zero() {
+            bonds.insert((bond_id.source, bond_id.validator), bonded);
+        }
+    }
+    // The bonded tokens are re-created by the bond txs
+    if let Some(owner_balances) = balances.get_mut(&native_token) {
+        owner_balances.remove(&Address::Internal(InternalAddress::PoS));
+    }
+
+    let validators = read_all_validator_addresses(storage, epoch)?
+        .into_iter()
+        .collect::<BTreeSet<_>>();
+    let stewards = get_stewards(storage)?
+        .into_iter()
+        .map(|steward| steward.address)
+        .collect::<BTreeSet<_>>();
+
+    // Re-create the established accounts
+    let mut established_accounts = BTreeSet::new();
+    established_accounts.extend(
+        balances
+            .values()
+            .flat_map(|owner_balances| owner_balances.keys())
+            .chain(bonds.keys().map(|(source, _)| source))
+            .chain(validators.iter())
+            .chain(stewards.iter())
+            .filter(|address| matches!(address, Address::Established(_)))
+            .cloned(),
+    );
+    let mut addresses = BTreeMap::new();
+    let mut established_account_txs = BTreeMap::new();
+    for address in established_accounts {
+        let public_keys = account::public_keys(storage, &address)?;
+        let threshold = account::threshold(storage, &address)?;
+        let Some(threshold) = threshold.filter(|_| !public_keys.is_empty())
+        else {
+            warnings.push(format!(
+                "The established account {address} has no public keys, so it \
+                 was not exported"
+            ));
+            continue;
+        };
+        let tx = EstablishedAccountTx {
+            vp: VP_USER.to_string(),
+            threshold,
+            public_keys: public_keys
+                .into_iter()
+                .map(StringEncoded::new)
+                .collect(),
+        };
+        let new_address = tx.derive_address();
+        if established_account_txs
+            .insert(new_address.clone(), tx)
+            .is_some()
+        {
+            warnings.push(format!(
+                "The established account {address} has the same public keys \
+                 and threshold as another account, so they were merged into \
+                 {new_address}"
+            ));
+        }
+        addresses.insert(address, new_address);
+    }
+    let new_address = |address: &Address| match address {
+        Address::Established(_) => addresses.get(address).cloned(),
+        _ => Some(address.clone()),
+    };
+
+    // Export the validators
+    let mut validator_account_txs = vec![];
+    let mut exported_validators = BTreeSet::new();
+    for validator in validators {
+        let Some(Address::Established(address)) = new_address(&validator)
+        else {
+            warnings.push(format!(
+                "The validator {validator} has no established account, so it \
+                 was not exported"
+            ));
+            continue;
+        };
+        match export_validator(storage, &pos_params, &validator, address)? {
+            Ok(tx) => {
+                validator_account_txs.push(tx);
+                exported_validators.insert(validator);
+            }
+            Err(warning) => warnings.push(warning),
+        }
+    }
+
+    // Export the bonds to the exported validators
+    let mut bond_txs = vec![];
+    for ((source, validator), amount) in bonds {
+        let genesis_source = match new_address(&source) {
+            Some(Address::Established(address)) => {
+                Some(GenesisAddress::EstablishedAddress(address))
+            }
+            Some(Address::Implicit(_)) => {
+                account::public_keys(storage, &source)?
+                    .into_iter()
+                    .next()
+                    .map(|pk| GenesisAddress::PublicKey(StringEncoded::new(pk)))
+            }
+            _ => None,
+        };
+        match (genesis_source, exported_validators.contains(&validator)) {
+            (Some(genesis_source), true) => {
+                let Some(Address::Established(validator)) =
+                    new_address(&validator)
+                else {
+                    unreachable!("Exported validators have an address")
+                };
+                bond_txs.push(BondTx {
+                    source: genesis_source,
+                    validator: Address::Established(validator),
+                    amount: DenominatedAmount::new(amount, native_denom),
+                });
+            }
+            _ => warnings.push(format!(
+                "The bond of {} from {source} to {validator} was not exported \
+                 because either the source's public key is not revealed or \
+                 the validator was not exported. The tokens are credited to \
+                 the source instead.",
+                amount.to_string_native()
+            )),
+        }
+    }
+    bond_txs.sort();
+
+    // Export the balances of the accounts with their new addresses
+    let mut token_balances = BTreeMap::new();
+    for (token, owner_balances) in balances {
+        let Some((alias, config)) = tokens.get(&token) else {
+            continue;
+        };
+        let mut exported = BTreeMap::<Address, Amount>::new();
+        for (owner, amount) in owner_balances {
+            let Some(owner) = new_address(&owner) else {
+                warnings.push(format!(
+                    "The balance of {} of token {alias} of {owner} was not \
+                     exported",
+                    DenominatedAmount::new(amount, config.denom)
+                ));
+                continue;
+            };
+            let balance = exported.entry(owner).or_default();
+            *balance = checked_add(*balance, amount)?;
+        }
+        token_balances.insert(
+            alias.clone(),
+            RawTokenBalances(
+                exported
+                    .into_iter()
+                    .map(|(owner, amount)| {
+                        (owner, DenominatedAmount::new(amount, config.denom))
+                    })
+                    .collect(),
+            ),
+        );
+    }
+
+    let stewards = stewards
+        .iter()
+        .filter_map(|steward| {
+            // Only established accounts can be genesis stewards
+            let new_steward = addresses.get(steward).cloned();
+            if new_steward.is_none() {
+                warnings.push(format!(
+                    "The PGF steward {steward} was not exported"
+                ));
+            }
+            new_steward
+        })
+        .collect();
+
+    // The tokens' addresses are derived from their alias and config
+    for (token, (alias, config)) in &tokens {
+        let new_token =
+            Address::Established((alias, config).derive_established_address());
+        if *token != new_token {
+            addresses.insert(token.clone(), new_token);
+        }
+    }
+
+    let parameters =
+        export_parameters(storage, &tokens, stewards, &mut warnings)?;
+
+    Ok(ExportedTemplates {
+        templates: All {
+            vps: ValidityPredicates {
+                wasm: [VP_IMPLICIT, VP_USER]
+                    .into_iter()
+                    .map(|vp| {
+                        (
+                            vp.to_string(),
+                            WasmVpConfig {
+                                filename: format!("{vp}.wasm"),
+                            },
+                        )
+                    })
+                    .collect(),
+            },
+            tokens: Tokens {
+                token: tokens.into_values().collect(),
+            },
+            balances: UndenominatedBalances {
+                token: token_balances,
+            },
+            parameters,
+            transactions: Transactions {
+                established_account: Some(
+                    established_account_txs.into_values().collect(),
+                ),
+                ..Default::default()
+            },
+        },
+        unsigned_txs: UnsignedTransactions {
+            established_account: None,
+            validator_account: Some(validator_account_txs),
+            bond: Some(bond_txs),
+        },
+        addresses,
+        warnings,
+    })
+}
+
+/// Export the account tx of a validator with the given new address, or a
+/// warning if it cannot be exported.
+fn export_validator<S>(
+    storage: &S,
+    pos_params: &PosParams,
+    validator: &Address,
+    address: EstablishedAddress,
+) -> eyre::Result<Result<UnsignedValidatorAccountTx, String>>
+where
+    S: StorageRead,
+{
+    let epoch = storage.get_block_epoch()?;
+    if !account::public_keys(storage, validator)?
+        .iter()
+        .all(|pk| matches!(pk, common::PublicKey::Ed25519(_)))
+    {
+        return Ok(Err(format!(
+            "Not all the account keys of the validator {validator} are \
+             Ed25519 keys, so it was not exported"
+        )));
+    }
+    let consensus_key = validator_consensus_key_handle(validator)
+        .get(storage, epoch, pos_params)?;
+    let protocol_key = validator_protocol_key_handle(validator)
+        .get(storage, epoch, pos_params)?;
+    let eth_hot_key = validator_eth_hot_key_handle(validator)
+        .get(storage, epoch, pos_params)?;
+    let eth_cold_key = validator_eth_cold_key_handle(validator)
+        .get(storage, epoch, pos_params)?;
+    let commission_rate = validator_commission_rate_handle(validator)
+        .get(storage, epoch, pos_params)?;
+    let max_commission_rate_change =
+        read_validator_max_commission_rate_change(storage, validator)?;
+    let (
+        Some(consensus_key),
+        Some(protocol_key),
+        Some(eth_hot_key),
+        Some(eth_cold_key),
+        Some(commission_rate),
+        Some(max_commission_rate_change),
+    ) = (
+        consensus_key,
+        protocol_key,
+        eth_hot_key,
+        eth_cold_key,
+        commission_rate,
+        max_commission_rate_change,
+    )
+    else {
+        return Ok(Err(format!(
+            "Some keys or commission parameters of the validator {validator} \
+             are missing, so it was not exported"
+        )));
+    };
+    let metadata =
+        read_validator_metadata(storage, validator)?.unwrap_or_else(|| {
+            ValidatorMetaData {
+                email: String::new(),
+                description: None,
+                website: None,
+                discord_handle: None,
+                avatar: None,
+                name: None,
+            }
+        });
+    Ok(Ok(UnsignedValidatorAccountTx {
+        address: StringEncoded::new(address),
+        vp: VP_USER.to_string(),
+        commission_rate,
+        max_commission_rate_change,
+        net_address: PLACEHOLDER_NET_ADDRESS
+            .parse::<SocketAddr>()
+            .expect("The placeholder address must be valid"),
+        // The node key is not in the state, so the consensus key stands in
+        // for it until the validator replaces it
+        tendermint_node_key: StringEncoded::new(consensus_key.clone()),
+        consensus_key: StringEncoded::new(consensus_key),
+        protocol_key: StringEncoded::new(protocol_key),
+        eth_hot_key: StringEncoded::new(eth_hot_key),
+        eth_cold_key: StringEncoded::new(eth_cold_key),
+        metadata,
+    }))
+}
+
+/// Export the chain, PoS, governance, PGF and IBC parameters.
+fn export_parameters<S>(
+    storage: &S,
+    tokens: &BTreeMap<Address, (Alias, TokenConfig)>,
+    stewards: BTreeSet<Address>,
+    warnings: &mut Vec<String>,
+) -> eyre::Result<Parameters<Unvalidated>>
+where
+    S: StorageRead,
+{
+    let native_token = storage.get_native_token()?;
+    let token_alias = |token: &Address| {
+        tokens
+            .get(token)
+            .map(|(alias, _)| alias.clone())
+            .ok_or_else(|| {
+                eyre::eyre!("Token {token} was not found in the state")
+            })
+    };
+
+    let parameters::Parameters {
+        max_tx_bytes,
+        epoch_duration,
+        max_proposal_bytes,
+        max_block_gas,
+        vp_allowlist,
+        tx_allowlist,
+        implicit_vp_code_hash: _,
+        epochs_per_year,
+        masp_epoch_multiplier,
+        masp_fee_payment_gas_limit,
+        gas_scale,
+        minimum_gas_price,
+        is_native_token_transferable,
+        fee_market,
+    } = parameters::read(storage)?;
+    let mut min_gas_prices = BTreeMap::new();
+    for (token, amount) in minimum_gas_price {
+        let denom = tokens
+            .get(&token)
+            .map(|(_, config)| config.denom)
+            .unwrap_or_else(|| Denomination(0));
+        min_gas_prices.insert(
+            token_alias(&token)?,
+            DenominatedAmount::new(amount, denom),
+        );
+    }
+    let chain_params = ChainParams {
+        max_tx_bytes,
+        native_token: token_alias(&native_token)?,
+        is_native_token_transferable,
+        min_num_of_blocks: epoch_duration.min_num_of_blocks,
+        max_proposal_bytes,
+        vp_allowlist: Some(vp_allowlist),
+        tx_allowlist: Some(tx_allowlist),
+        implicit_vp: VP_IMPLICIT.to_string(),
+        epochs_per_year,
+        masp_epoch_multiplier,
+        max_block_gas,
+        masp_fee_payment_gas_limit,
+        gas_scale,
+        minimum_gas_price: min_gas_prices,
+        // NB: the base fees are not exported, so they start back from the
+        // minimum gas prices
+        fee_market,
+    };
+
+    let pos_params = read_pos_params::<S, governance::Store<S>>(storage)?.owned;
+    let pos_params = templates::PosParams {
+        max_validator_slots: pos_params.max_validator_slots,
+        pipeline_len: pos_params.pipeline_len,
+        unbonding_len: pos_params.unbonding_len,
+        tm_votes_per_token: pos_params.tm_votes_per_token,
+        block_proposer_reward: pos_params.block_proposer_reward,
+        block_vote_reward: pos_params.block_vote_reward,
+        max_inflation_rate: pos_params.max_inflation_rate,
+        target_staked_ratio: pos_params.target_staked_ratio,
+        duplicate_vote_min_slash_rate: pos_params.duplicate_vote_min_slash_rate,
+        light_client_attack_min_slash_rate: pos_params
+            .light_client_attack_min_slash_rate,
+        cubic_slashing_window_length: pos_params.cubic_slashing_window_length,
+        validator_stake_threshold: pos_params.validator_stake_threshold,
+        liveness_window_check: pos_params.liveness_window_check,
+        liveness_threshold: pos_params.liveness_threshold,
+        rewards_gain_p: pos_params.rewards_gain_p,
+        rewards_gain_d: pos_params.rewards_gain_d,
+    };
+
+    let gov_params = get_gov_parameters(storage)?;
+    let whole_fund = gov_params
+        .min_proposal_fund
+        .checked_div_u64(NATIVE_SCALE)
+        .unwrap_or_default();
+    let mut min_proposal_fund = u64::try_from(u128::try_from(whole_fund)?)?;
+    if Amount::native_whole(min_proposal_fund) != gov_params.min_proposal_fund {
+        min_proposal_fund = min_proposal_fund
+            .checked_add(1)
+            .ok_or_else(|| eyre::eyre!("Minimum proposal fund overflow"))?;
+        warnings.push(format!(
+            "The minimum proposal fund of {} was rounded up to {} whole tokens",
+            gov_params.min_proposal_fund.to_string_native(),
+            min_proposal_fund
+        ));
+    }
+    let gov_params = GovernanceParams {
+        min_proposal_fund,
+        max_proposal_code_size: gov_params.max_proposal_code_size,
+        min_proposal_voting_period: gov_params.min_proposal_voting_period,
+        max_proposal_period: gov_params.max_proposal_period,
+        max_proposal_content_size: gov_params.max_proposal_content_size,
+        min_proposal_grace_epochs: gov_params.min_proposal_grace_epochs,
+        max_proposal_latency: gov_params.max_proposal_latency,
+    };
+
+    let pgf_params = get_pgf_parameters(storage)?;
+    let pgf_params = PgfParams::new(
+        stewards,
+        pgf_params.pgf_inflation_rate,
+        pgf_params.stewards_inflation_rate,
+        pgf_params.maximum_number_of_stewards,
+    );
+
+    let ibc_params: IbcParameters =
+        storage.read(&ibc_params_key())?.unwrap_or_default();
+    let ibc_params = IbcParams {
+        default_mint_limit: ibc_params.default_rate_limits.mint_limit,
+        default_per_epoch_throughput_limit: ibc_params
+            .default_rate_limits
+            .throughput_per_epoch_limit,
+    };
+
+    Ok(Parameters {
+        parameters: chain_params,
+        pos_params,
+        gov_params,
+        pgf_params,
+        eth_bridge_params: None,
+        ibc_params,
+    })
+}
+
+fn checked_add(a: Amount, b: Amount) -> eyre::Result<Amount> {
+    a.checked_add(b)
+        .ok_or_else(|| eyre::eyre!("Token amount overflow"))
+}
+
+#[cfg(test)]
+mod tests {
+    use namada_sdk::address::testing::established_address_1;
+    use namada_sdk::governance::parameters::GovernanceParameters;
+    use namada_sdk::governance::pgf::parameters::PgfParameters;
+    use namada_sdk::ibc::storage::ibc_trace_key;
+    use namada_sdk::ibc::trace::calc_hash;
+    use namada_sdk::key::RefTo;
+    use namada_sdk::key::testing::{keypair_1, keypair_2};
+    use namada_sdk::proof_of_stake::OwnedPosParams;
+    use namada_sdk::proof_of_stake::storage::write_pos_params;
+    use namada_sdk::state::StorageWrite;
+    use namada_sdk::state::testing::TestState;
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::config::genesis::templates::load_and_validate;
+
+    /// Init the parameters and the native token of a test state
+    fn init_state(gov_params: GovernanceParameters) -> TestState {
+        let mut state = TestState::default();
+        parameters::init_test_storage(&mut state).unwrap();
+        gov_params.init_storage(&mut state).unwrap();
+        PgfParameters::default().init_storage(&mut state).unwrap();
+        write_pos_params(&mut state, &OwnedPosParams::default()).unwrap();
+
+        let native_token = state.in_mem().native_token.clone();
+        token::write_denom(
+            &mut state,
+            &native_token,
+            token::NATIVE_MAX_DECIMAL_PLACES.into(),
+        )
+        .unwrap();
+        state
+    }
+
+    /// Test that the exported templates of a state are valid and carry over
+    /// the balances to the new addresses.
+    #[test]
+    fn test_export_templates() {
+        let mut state = init_state(GovernanceParameters::default());
+        let native_token = state.in_mem().native_token.clone();
+
+        let implicit = Address::from(&keypair_1().ref_to());
+        let established = established_address_1();
+        account::init_account_storage(
+            &mut state,
+            &established,
+            &[keypair_2().ref_to()],
+            1,
+        )
+        .unwrap();
+        let implicit_amount = Amount::native_whole(100);
+        let established_amount = Amount::native_whole(200);
+        token::credit_tokens(
+            &mut state,
+            &native_token,
+            &implicit,
+            implicit_amount,
+        )
+        .unwrap();
+        token::credit_tokens(
+            &mut state,
+            &native_token,
+            &established,
+            established_amount,
+        )
+        .unwrap();
+
+        let exported = export_templates(&state).unwrap();
+        assert!(exported.warnings.is_empty(), "{:?}", exported.warnings);
+
+        let new_established = exported.addresses.get(&established).unwrap();
+        assert_ne!(*new_established, established);
+        let balances = exported
+            .templates
+            .balances
+            .token
+            .get(&Alias::from(NATIVE_TOKEN_ALIAS))
+            .unwrap();
+        assert_eq!(
+            balances.0.get(&implicit),
+            Some(&DenominatedAmount::native(implicit_amount))
+        );
+        assert_eq!(
+            balances.0.get(new_established),
+            Some(&DenominatedAmount::native(established_amount))
+        );
+
+        let templates_dir = tempdir().unwrap();
+        exported.write_toml_files(templates_dir.path()).unwrap();
+        assert!(
+            load_and_validate(templates_dir.path()).is_some(),
+            "Exported genesis templates must be valid"
+        );
+    }
+
+    /// Test that a fractional minimum proposal fund is rounded up with a
+    /// warning.
+    #[test]
+    fn test_export_fractional_proposal_fund() {
+        let min_proposal_fund = Amount::native_whole(500)
+            .checked_add(Amount::from(1_u64))
+            .unwrap();
+        let state = init_state(GovernanceParameters {
+            min_proposal_fund,
+            ..Default::default()
+        });
+
+        let exported = export_templates(&state).unwrap();
+        assert_eq!(exported.warnings.len(), 1, "{:?}", exported.warnings);
+        assert_eq!(
+            exported.templates.parameters.gov_params.min_proposal_fund,
+            501
+        );
+
+        let templates_dir = tempdir().unwrap();
+        exported.write_toml_files(templates_dir.path()).unwrap();
+        assert!(
+            load_and_validate(templates_dir.path()).is_some(),
+            "Exported genesis templates must be valid"
+        );
+    }
+
+    /// Test that the export fails if any account holds IBC tokens, as their
+    /// IBC traces cannot be preserved.
+    #[test]
+    fn test_export_ibc_tokens_fails() {
+        let mut state = init_state(GovernanceParameters::default());
+
+        let trace = "transfer/channel-0/uatom";
+        let ibc_token = ibc_token(trace);
+        let owner = Address::from(&keypair_1().ref_to());
+        token::write_denom(&mut state, &ibc_token, 0_u8.into()).unwrap();
+        token::credit_tokens(
+            &mut state,
+            &ibc_token,
+            &owner,
+            Amount::from(100_u64),
+        )
+        .unwrap();
+        state
+            .write(
+                &ibc_trace_key(owner.to_string(), calc_hash(trace)),
+                trace.to_string(),
+            )
+            .unwrap();
+
+        let err = export_templates(&state).unwrap_err().to_string();
+        assert!(err.contains(&format!("{ibc_token} ({trace})")), "{err}");
+    }
+}
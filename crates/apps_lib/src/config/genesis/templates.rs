@@ -13,7 +13,7 @@ use namada_sdk::dec::Dec;
 use namada_sdk::eth_bridge::storage::parameters::{
     Contracts, Erc20WhitelistEntry, MinimumConfirmations,
 };
-use namada_sdk::parameters::ProposalBytes;
+use namada_sdk::parameters::{FeeMarketParams, ProposalBytes};
 use namada_sdk::token::{
     Amount, DenominatedAmount, Denomination, NATIVE_MAX_DECIMAL_PLACES,
 };
@@ -294,6 +294,8 @@ pub struct ChainParams<T: TemplateValidation> {
     pub gas_scale: u64,
     /// Map of the cost per gas unit for every token allowed for fee payment
     pub minimum_gas_price: T::GasMinimums,
+    /// The dynamic base fee market, disabled if not set
+    pub fee_market: Option<FeeMarketParams>,
 }
 
 impl ChainParams<Unvalidated> {
@@ -316,6 +318,7 @@ impl ChainParams<Unvalidated> {
             masp_fee_payment_gas_limit,
             gas_scale,
             minimum_gas_price,
+            fee_market,
         } = self;
         let mut min_gas_prices = BTreeMap::default();
         for (token, amount) in minimum_gas_price.into_iter() {
@@ -361,6 +364,7 @@ impl ChainParams<Unvalidated> {
             masp_fee_payment_gas_limit,
             gas_scale,
             minimum_gas_price: min_gas_prices,
+            fee_market,
         })
     }
 }
@@ -475,6 +479,23 @@ pub struct PgfParams<T: TemplateValidation> {
     valid: PhantomData<T>,
 }
 
+impl PgfParams<Unvalidated> {
+    pub fn new(
+        stewards: BTreeSet<Address>,
+        pgf_inflation_rate: Dec,
+        stewards_inflation_rate: Dec,
+        maximum_number_of_stewards: u64,
+    ) -> Self {
+        Self {
+            stewards,
+            pgf_inflation_rate,
+            stewards_inflation_rate,
+            maximum_number_of_stewards,
+            valid: Default::default(),
+        }
+    }
+}
+
 #[derive(
     Clone,
     Debug,
@@ -883,6 +904,19 @@ pub fn validate_parameters(
             );
         }
     }
+    // check that the fractions of the fee market are in the unit interval
+    if let Some(fee_market) = &parameters.parameters.fee_market {
+        let unit_interval = Dec::zero()..=Dec::one();
+        if !unit_interval.contains(&fee_market.target_block_gas_fraction)
+            || !unit_interval.contains(&fee_market.max_base_fee_change)
+        {
+            eprintln!(
+                "The target block gas fraction and the max base fee change of \
+                 the fee market must be between 0 and 1"
+            );
+            is_valid = false;
+        }
+    }
     let Parameters {
         parameters,
         pos_params,
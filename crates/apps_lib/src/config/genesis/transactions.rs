@@ -78,6 +78,7 @@ fn get_tx_args(use_device: bool) -> TxArgs {
 
     TxArgs {
         dry_run: None,
+        gas_profile: false,
         dump_tx: None,
         output_folder: None,
         force: false,
@@ -7,7 +7,7 @@ pub mod utils;
 
 use std::fs::{File, create_dir_all};
 use std::io::Write;
-use std::num::NonZeroU64;
+use std::num::{NonZeroU64, NonZeroUsize};
 use std::path::{Path, PathBuf};
 
 use directories::ProjectDirs;
@@ -89,6 +89,19 @@ pub struct ActionAtHeight {
     pub action: Action,
 }
 
+/// The format of the state-sync snapshots taken by a node.
+#[derive(
+    Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq,
+)]
+pub enum SnapshotFormat {
+    /// A tarball of a raw RocksDB checkpoint.
+    #[default]
+    DbCheckpoint,
+    /// The sorted key-value pairs of the state, from which restoring nodes
+    /// rebuild and verify the merkle tree against the trusted app hash.
+    KeyValue,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Ledger {
     pub genesis_time: Rfc3339String,
@@ -128,6 +141,33 @@ pub struct Shell {
     pub blocks_between_snapshots: Option<NonZeroU64>,
     /// Number of snapshots to keep
     pub snapshots_to_keep: Option<NonZeroU64>,
+    /// The format of the snapshots to take. When not set, defaults to
+    /// [`SnapshotFormat::DbCheckpoint`].
+    pub snapshot_format: Option<SnapshotFormat>,
+    /// When set, only the events emitted in this many most recent blocks are
+    /// kept in the DB's event log. When not set, the events of all blocks are
+    /// kept.
+    pub event_log_retention_blocks: Option<NonZeroU64>,
+    /// When set, the data needed to roll back the state is kept for this many
+    /// most recent blocks, which is how far `ledger rollback --to-height` can
+    /// rewind. When not set, only the last block can be rolled back.
+    pub rollback_window_blocks: Option<NonZeroU64>,
+    /// When set, the inner transactions of the different wrapper transactions
+    /// of a block are executed optimistically in parallel and committed in
+    /// the block order, re-executing those that conflict with the preceding
+    /// ones. The results are the same as with sequential execution.
+    #[serde(default)]
+    pub parallel_inner_txs: bool,
+    /// When set, the storage changeset of every committed block is written
+    /// to a file in this directory. Use the [`Shell::changeset_export_dir()`]
+    /// method to read the value.
+    changeset_export_dir: Option<PathBuf>,
+    /// The number of threads used to pre-warm the wasm compilation caches
+    /// with the allowlisted wasm, when the node starts and when the
+    /// allowlists change. When not set, defaults to half of the available
+    /// parallelism.
+    #[serde(default)]
+    pub wasm_cache_pre_warm_threads: Option<NonZeroUsize>,
 }
 
 impl Ledger {
@@ -159,6 +199,12 @@ impl Ledger {
                 last_tendermint_mode: None,
                 blocks_between_snapshots: None,
                 snapshots_to_keep: None,
+                snapshot_format: None,
+                event_log_retention_blocks: None,
+                rollback_window_blocks: None,
+                parallel_inner_txs: false,
+                changeset_export_dir: None,
+                wasm_cache_pre_warm_threads: None,
             },
             cometbft: tendermint_config,
             ethereum_bridge: ethereum_bridge::ledger::Config::default(),
@@ -193,6 +239,24 @@ impl Shell {
             .join(chain_id.as_str())
             .join(&self.cometbft_dir)
     }
+
+    /// Get the directory path to the VP wasm compilation cache
+    pub fn vp_wasm_cache_dir(&self, chain_id: &ChainId) -> PathBuf {
+        self.base_dir.join(chain_id.as_str()).join("vp_wasm_cache")
+    }
+
+    /// Get the directory path to the tx wasm compilation cache
+    pub fn tx_wasm_cache_dir(&self, chain_id: &ChainId) -> PathBuf {
+        self.base_dir.join(chain_id.as_str()).join("tx_wasm_cache")
+    }
+
+    /// Get the directory path to export the block changesets to, if enabled.
+    /// A relative path is relative to the chain directory.
+    pub fn changeset_export_dir(&self, chain_id: &ChainId) -> Option<PathBuf> {
+        self.changeset_export_dir
+            .as_ref()
+            .map(|dir| self.base_dir.join(chain_id.as_str()).join(dir))
+    }
 }
 
 #[derive(Error, Debug)]
@@ -11,6 +11,7 @@ use namada_node::bench_utils::{
     BenchShell, TX_INIT_PROPOSAL_WASM, TX_REVEAL_PK_WASM, TX_TRANSFER_WASM,
     TX_UPDATE_ACCOUNT_WASM, VP_USER_WASM, WASM_DIR,
 };
+use namada_vm::host_env::HashFunction;
 use namada_vm::wasm::TxCache;
 
 // Benchmarks the validation of a single signature on a single `Section` of a
@@ -331,6 +332,48 @@ fn storage_write(c: &mut Criterion) {
     group.finish();
 }
 
+// Benchmarks the fixed cost of pushing a savepoint of the write log of a
+// transaction, i.e. with an empty write log
+fn write_log_push_savepoint(c: &mut Criterion) {
+    let bench_shell = BenchShell::default();
+    let mut shell = bench_shell.write();
+    shell.state.write_log_mut().drop_tx();
+
+    c.bench_function("write_log_push_savepoint", |b| {
+        b.iter(|| {
+            let write_log = shell.state.write_log_mut();
+            let gas = write_log.push_savepoint().unwrap();
+            write_log.release_savepoint().unwrap();
+            gas
+        })
+    });
+}
+
+// Benchmarks the hash functions exposed to the wasm environments. The empty
+// input gives the fixed cost of hashing while the throughput on the larger
+// inputs gives the cost per byte of each function
+fn host_hash(c: &mut Criterion) {
+    let mut group = c.benchmark_group("host_hash");
+
+    for hash_fn in [
+        HashFunction::Sha256,
+        HashFunction::Keccak256,
+        HashFunction::Blake2b,
+    ] {
+        for len in [0_u64, 1_000, 100_000, 1_000_000] {
+            group.throughput(criterion::Throughput::Bytes(len));
+            // Generate random bytes for the data to hash
+            let data: Vec<u8> = (0..len).map(|_| rand::random()).collect();
+
+            group.bench_function(format!("{hash_fn:?}, bytes: {len}"), |b| {
+                b.iter(|| hash_fn.hash(&data))
+            });
+        }
+    }
+
+    group.finish();
+}
+
 criterion_group!(
     host_env,
     tx_section_signature_validation,
@@ -340,5 +383,7 @@ criterion_group!(
     storage_read,
     write_log_write,
     storage_write,
+    write_log_push_savepoint,
+    host_hash,
 );
 criterion_main!(host_env);
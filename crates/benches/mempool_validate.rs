@@ -41,7 +41,7 @@ fn max_tx_sections_validation(c: &mut Criterion) {
         amount_per_gas_unit,
         token,
     };
-    let wrapper = WrapperTx { fee, pk, gas_limit };
+    let wrapper = WrapperTx::new(fee, pk, gas_limit);
     let tx_type = TxType::Wrapper(Box::new(wrapper));
 
     let batch = HashSet::new();
@@ -123,7 +123,7 @@ fn max_tx_sections_with_auth_validation(c: &mut Criterion) {
         amount_per_gas_unit,
         token,
     };
-    let wrapper = WrapperTx { fee, pk, gas_limit };
+    let wrapper = WrapperTx::new(fee, pk, gas_limit);
     let tx_type = TxType::Wrapper(Box::new(wrapper));
 
     let batch = HashSet::new();
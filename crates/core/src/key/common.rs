@@ -529,6 +529,36 @@ impl super::SigScheme for SigScheme {
     }
 }
 
+/// Verify a signature over the raw bytes of an arbitrary message, without
+/// hashing them first as [`SigScheme::verify_signature`] does. Ed25519
+/// signatures are checked as in RFC 8032 and secp256k1 signatures as ECDSA
+/// over the SHA-256 digest of the message.
+pub fn verify_raw_signature(
+    pk: &PublicKey,
+    msg: &[u8],
+    sig: &Signature,
+) -> Result<(), VerifySigError> {
+    match (pk, sig) {
+        (PublicKey::Ed25519(pk), Signature::Ed25519(sig)) => {
+            pk.0.verify(&sig.0, msg)
+                .map_err(|err| VerifySigError::SigVerifyError(err.to_string()))
+        }
+        (PublicKey::Secp256k1(pk), Signature::Secp256k1(sig)) => {
+            use k256::ecdsa::signature::Verifier;
+
+            k256::ecdsa::VerifyingKey::from(&pk.0)
+                .verify(msg, &sig.0)
+                .map_err(|err| {
+                    VerifySigError::SigVerifyError(format!(
+                        "Error verifying secp256k1 signature: {}",
+                        err
+                    ))
+                })
+        }
+        _ => Err(VerifySigError::MismatchedScheme),
+    }
+}
+
 /// Share behavior for both private and public keys. Useful to dispatch function
 /// calls when mocking signatures
 pub trait SigOrPubKey {
@@ -11,6 +11,7 @@ use namada_migrations::*;
 use serde::{Deserialize, Serialize};
 
 use super::address::Address;
+use super::dec::Dec;
 use super::hash::Hash;
 use super::time::DurationSecs;
 use super::token;
@@ -58,6 +59,74 @@ pub struct Parameters {
     pub minimum_gas_price: BTreeMap<Address, token::Amount>,
     /// Enable the native token transfer if it is true
     pub is_native_token_transferable: bool,
+    /// The dynamic base fee market. When `None`, the gas price is only bound
+    /// by the `minimum_gas_price`
+    pub fee_market: Option<FeeMarketParams>,
+}
+
+/// Parameters of the dynamic base fee market. At the end of every block, the
+/// base fee of each token allowed for fee payment is adjusted based on the gas
+/// used by the block with respect to a target: it rises when the block used
+/// more gas than the target and it falls when it used less, but never below
+/// the `minimum_gas_price` of the token.
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshDeserializer,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub struct FeeMarketParams {
+    /// The fraction of `max_block_gas` that blocks are targeted to use
+    pub target_block_gas_fraction: Dec,
+    /// The max change of the base fee from one block to the next, as a
+    /// fraction of the base fee
+    pub max_base_fee_change: Dec,
+    /// Where the base fee part of the fees goes
+    pub base_fee_destination: BaseFeeDestination,
+}
+
+/// The destination of the base fee part of the fees paid by a transaction. The
+/// rest of the fees, the tip, always goes to the block proposer.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshDeserializer,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum BaseFeeDestination {
+    /// Burn the base fee, reducing the total supply of the fee token
+    Burn,
+    /// Send the base fee to the PGF account
+    Pgf,
+}
+
+impl fmt::Display for BaseFeeDestination {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Burn => write!(f, "burn"),
+            Self::Pgf => write!(f, "pgf"),
+        }
+    }
 }
 
 /// Epoch duration. A new epoch begins as soon as both the `min_num_of_blocks`
@@ -101,6 +170,7 @@ impl Default for Parameters {
             gas_scale: 100_000_000,
             minimum_gas_price: Default::default(),
             is_native_token_transferable: true,
+            fee_market: None,
         }
     }
 }
@@ -93,6 +93,8 @@ pub enum DbColFam {
     ROLLBACK,
     /// Replay protection
     REPLAYPROT,
+    /// Events log
+    EVENTS,
 }
 
 /// Subspace column family name
@@ -107,6 +109,8 @@ pub const STATE_CF: &str = "state";
 pub const BLOCK_CF: &str = "block";
 /// Replay protection column family name
 pub const REPLAY_PROTECTION_CF: &str = "replay_protection";
+/// Events log column family name
+pub const EVENTS_CF: &str = "events";
 
 impl DbColFam {
     /// Get the name of the column family
@@ -118,11 +122,12 @@ impl DbColFam {
             DbColFam::DIFFS => DIFFS_CF,
             DbColFam::ROLLBACK => ROLLBACK_CF,
             DbColFam::REPLAYPROT => REPLAY_PROTECTION_CF,
+            DbColFam::EVENTS => EVENTS_CF,
         }
     }
 
     /// Return an array of all column families
-    pub fn all() -> [&'static str; 6] {
+    pub fn all() -> [&'static str; 7] {
         [
             SUBSPACE_CF,
             BLOCK_CF,
@@ -130,6 +135,7 @@ impl DbColFam {
             DIFFS_CF,
             ROLLBACK_CF,
             REPLAY_PROTECTION_CF,
+            EVENTS_CF,
         ]
     }
 }
@@ -145,6 +151,7 @@ impl FromStr for DbColFam {
             STATE_CF => Ok(Self::STATE),
             REPLAY_PROTECTION_CF => Ok(Self::REPLAYPROT),
             BLOCK_CF => Ok(Self::BLOCK),
+            EVENTS_CF => Ok(Self::EVENTS),
             _ => Err(Error::DbColFamily(s.to_string())),
         }
     }
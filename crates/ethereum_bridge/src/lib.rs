@@ -20,6 +20,8 @@
 pub mod event;
 pub mod oracle;
 pub mod protocol;
+#[cfg(feature = "migrations")]
+mod schema;
 pub mod storage;
 #[cfg(any(test, feature = "testing"))]
 pub mod test_utils;
@@ -0,0 +1,32 @@
+//! Storage schemas of the Ethereum bridge keys
+
+use namada_core::ethereum_events::EthAddress;
+use namada_core::ethereum_structs;
+use namada_core::token::Amount;
+use namada_migrations::storage_schema;
+
+use crate::storage::eth_bridge_queries::EthBridgeStatus;
+use crate::storage::parameters::{MinimumConfirmations, UpgradeableContract};
+
+// Ethereum bridge parameters
+storage_schema!(
+    ACTIVE_STATUS: "#Parameters/active_status" => EthBridgeStatus as debug
+);
+storage_schema!(
+    MIN_CONFIRMATIONS: "#Parameters/min_confirmations" => MinimumConfirmations
+);
+storage_schema!(NATIVE_ERC20: "#Parameters/native_erc20" => EthAddress);
+storage_schema!(
+    BRIDGE_CONTRACT: "#Parameters/bridge_contract_address"
+        => UpgradeableContract
+);
+storage_schema!(
+    ETH_START_HEIGHT: "#Parameters/eth_start_height"
+        => ethereum_structs::BlockHeight
+);
+
+// ERC20 whitelist
+storage_schema!(
+    WHITELISTED: "#EthBridge/whitelist/<asset>/whitelisted" => bool
+);
+storage_schema!(WHITELIST_CAP: "#EthBridge/whitelist/<asset>/cap" => Amount);
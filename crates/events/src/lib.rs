@@ -411,6 +411,17 @@ impl Event {
         DATA::raw_read_opt_from_event_attributes(&self.attributes)
     }
 
+    /// Get the raw string value of the attribute with the given key, if it
+    /// exists.
+    ///
+    /// Prefer [`Event::raw_read_attribute`] when the attribute is known at
+    /// compile time. This is meant for consumers matching arbitrary keys,
+    /// such as event queries.
+    #[inline]
+    pub fn raw_read_attribute_with_key(&self, key: &str) -> Option<&str> {
+        self.attributes.get(key).map(String::as_str)
+    }
+
     /// Get the value corresponding to a given attribute.
     #[inline]
     pub fn read_attribute<'value, DATA>(
@@ -16,6 +16,7 @@
 )]
 
 pub mod event;
+pub mod profile;
 pub mod storage;
 
 use std::fmt::Display;
@@ -30,6 +31,8 @@ use namada_migrations::*;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use self::profile::GasProfiler;
+
 /// Choose the gas mmeter used for WASM instructions
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum GasMeterKind {
@@ -129,6 +132,17 @@ const MASP_CONVERT_CHECK_GAS_RAW: u64 = 188_590;
 const MASP_OUTPUT_CHECK_GAS_RAW: u64 = 204_430;
 // The cost to run the final masp check in the bundle
 const MASP_FINAL_CHECK_GAS_RAW: u64 = 43;
+// The fixed cost of hashing arbitrary data, from the `host_hash` benchmarks on
+// empty data
+const HASH_FIXED_GAS_RAW: u64 = 1_500;
+// The cost of hashing arbitrary data with SHA-256, per byte
+const SHA256_GAS_PER_BYTE_RAW: u64 = 45;
+// The cost of hashing arbitrary data with Keccak-256, per byte
+const KECCAK256_GAS_PER_BYTE_RAW: u64 = 52;
+// The cost of hashing arbitrary data with BLAKE2b, per byte
+const BLAKE2B_GAS_PER_BYTE_RAW: u64 = 28;
+// The fixed cost of pushing a savepoint of the write log of a transaction
+const SAVEPOINT_FIXED_GAS_RAW: u64 = 2_000;
 // =============================================================================
 
 // A correction factor for non-WASM-opcodes costs. We can see that the
@@ -217,6 +231,20 @@ pub const MASP_OUTPUT_CHECK_GAS: u64 =
 /// The cost to run the final masp check in the bundle
 pub const MASP_FINAL_CHECK_GAS: u64 =
     MASP_FINAL_CHECK_GAS_RAW * GAS_COST_CORRECTION;
+/// The fixed cost of hashing arbitrary data
+pub const HASH_FIXED_GAS: u64 = HASH_FIXED_GAS_RAW * GAS_COST_CORRECTION;
+/// The cost of hashing arbitrary data with SHA-256, per byte
+pub const SHA256_GAS_PER_BYTE: u64 =
+    SHA256_GAS_PER_BYTE_RAW * GAS_COST_CORRECTION;
+/// The cost of hashing arbitrary data with Keccak-256, per byte
+pub const KECCAK256_GAS_PER_BYTE: u64 =
+    KECCAK256_GAS_PER_BYTE_RAW * GAS_COST_CORRECTION;
+/// The cost of hashing arbitrary data with BLAKE2b, per byte
+pub const BLAKE2B_GAS_PER_BYTE: u64 =
+    BLAKE2B_GAS_PER_BYTE_RAW * GAS_COST_CORRECTION;
+/// The fixed cost of pushing a savepoint of the write log of a transaction
+pub const SAVEPOINT_FIXED_GAS: u64 =
+    SAVEPOINT_FIXED_GAS_RAW * GAS_COST_CORRECTION;
 // =============================================================================
 
 /// Gas module result for functions that may fail
@@ -374,6 +402,11 @@ pub trait GasMetering {
     /// Get the protocol gas scale
     fn get_gas_scale(&self) -> u64;
 
+    /// Get the profiler of the consumed gas, if any
+    fn gas_profiler(&self) -> Option<&GasProfiler> {
+        None
+    }
+
     /// Get the amount of gas still available to the transaction
     fn get_available_gas(&self) -> Gas {
         self.get_gas_limit()
@@ -383,32 +416,38 @@ pub trait GasMetering {
 
     /// Add the compiling cost proportionate to the code length
     fn add_compiling_gas(&mut self, bytes_len: u64) -> Result<()> {
-        self.consume(
-            bytes_len
-                .checked_mul(COMPILE_GAS_PER_BYTE)
-                .ok_or(Error::GasOverflow)?
-                .into(),
-        )
+        let gas: Gas = bytes_len
+            .checked_mul(COMPILE_GAS_PER_BYTE)
+            .ok_or(Error::GasOverflow)?
+            .into();
+        if let Some(profiler) = self.gas_profiler() {
+            profiler.record_wasm_compilation(gas.clone());
+        }
+        self.consume(gas)
     }
 
     /// Add the gas for loading the wasm code from storage
     fn add_wasm_load_from_storage_gas(&mut self, bytes_len: u64) -> Result<()> {
-        self.consume(
-            bytes_len
-                .checked_mul(STORAGE_ACCESS_GAS_PER_BYTE)
-                .ok_or(Error::GasOverflow)?
-                .into(),
-        )
+        let gas: Gas = bytes_len
+            .checked_mul(STORAGE_ACCESS_GAS_PER_BYTE)
+            .ok_or(Error::GasOverflow)?
+            .into();
+        if let Some(profiler) = self.gas_profiler() {
+            profiler.record_wasm_loading(gas.clone());
+        }
+        self.consume(gas)
     }
 
     /// Add the gas for validating untrusted wasm code
     fn add_wasm_validation_gas(&mut self, bytes_len: u64) -> Result<()> {
-        self.consume(
-            bytes_len
-                .checked_mul(WASM_CODE_VALIDATION_GAS_PER_BYTE)
-                .ok_or(Error::GasOverflow)?
-                .into(),
-        )
+        let gas: Gas = bytes_len
+            .checked_mul(WASM_CODE_VALIDATION_GAS_PER_BYTE)
+            .ok_or(Error::GasOverflow)?
+            .into();
+        if let Some(profiler) = self.gas_profiler() {
+            profiler.record_wasm_validation(gas.clone());
+        }
+        self.consume(gas)
     }
 
     /// Check if the meter ran out of gas. Starts with the initial gas.
@@ -426,7 +465,7 @@ pub trait GasMetering {
 }
 
 /// Gas metering in a transaction
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct TxGasMeter {
     /// Track gas overflow
     gas_overflow: bool,
@@ -436,10 +475,12 @@ pub struct TxGasMeter {
     tx_gas_limit: Gas,
     /// Gas consumption of the tx
     transaction_gas: Gas,
+    /// The optional profiler of the consumed gas
+    profiler: Option<GasProfiler>,
 }
 
 /// Gas metering in a validity predicate
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct VpGasMeter {
     /// Track gas overflow
     gas_overflow: bool,
@@ -451,6 +492,8 @@ pub struct VpGasMeter {
     prev_meter_consumed_gas: Gas,
     /// The current gas usage in the VP
     current_gas: Gas,
+    /// The optional profiler of the consumed gas
+    profiler: Option<GasProfiler>,
 }
 
 impl GasMetering for TxGasMeter {
@@ -497,6 +540,10 @@ impl GasMetering for TxGasMeter {
     fn get_gas_scale(&self) -> u64 {
         self.gas_scale
     }
+
+    fn gas_profiler(&self) -> Option<&GasProfiler> {
+        self.profiler.as_ref()
+    }
 }
 
 impl TxGasMeter {
@@ -512,6 +559,7 @@ impl TxGasMeter {
             gas_scale: 0u64,
             tx_gas_limit: Gas::new(0u64),
             transaction_gas: Gas::new(0u64),
+            profiler: None,
         }
     }
 
@@ -523,27 +571,41 @@ impl TxGasMeter {
             gas_scale,
             tx_gas_limit: tx_gas_limit.into(),
             transaction_gas: Gas::default(),
+            profiler: None,
         }
     }
 
+    /// Attach a profiler to the meter, to collect a categorized profile of
+    /// the consumed gas. The profiler is shared with the VP gas meters
+    /// initialized from this meter.
+    pub fn with_gas_profiler(mut self, profiler: GasProfiler) -> Self {
+        self.profiler = Some(profiler);
+        self
+    }
+
     /// Add the gas required by a wrapper transaction which is comprised of:
     ///  - cost of validating the wrapper tx
     ///  - space that the transaction requires in the block
     ///  - cost of downloading (as part of the block) the transaction bytes over
     ///    the network
     pub fn add_wrapper_gas(&mut self, tx_bytes: &[u8]) -> Result<()> {
+        if let Some(profiler) = &self.profiler {
+            profiler.record_wrapper(WRAPPER_TX_VALIDATION_GAS.into());
+        }
         self.consume(WRAPPER_TX_VALIDATION_GAS.into())?;
 
         let bytes_len = tx_bytes.len() as u64;
-        self.consume(
-            bytes_len
-                .checked_mul(
-                    STORAGE_OCCUPATION_GAS_PER_BYTE
-                        + NETWORK_TRANSMISSION_GAS_PER_BYTE,
-                )
-                .ok_or(Error::GasOverflow)?
-                .into(),
-        )
+        let gas: Gas = bytes_len
+            .checked_mul(
+                STORAGE_OCCUPATION_GAS_PER_BYTE
+                    + NETWORK_TRANSMISSION_GAS_PER_BYTE,
+            )
+            .ok_or(Error::GasOverflow)?
+            .into();
+        if let Some(profiler) = &self.profiler {
+            profiler.record_wrapper(gas.clone());
+        }
+        self.consume(gas)
     }
 }
 
@@ -594,6 +656,10 @@ impl GasMetering for VpGasMeter {
     fn get_gas_scale(&self) -> u64 {
         self.gas_scale
     }
+
+    fn gas_profiler(&self) -> Option<&GasProfiler> {
+        self.profiler.as_ref()
+    }
 }
 
 impl VpGasMeter {
@@ -610,6 +676,7 @@ impl VpGasMeter {
             tx_gas_limit: Gas::new(0u64),
             prev_meter_consumed_gas: Gas::new(0u64),
             current_gas: Gas::new(0u64),
+            profiler: None,
         }
     }
 
@@ -626,6 +693,7 @@ impl VpGasMeter {
             tx_gas_limit: gas_meter.get_gas_limit(),
             prev_meter_consumed_gas: gas_meter.get_consumed_gas(),
             current_gas: Gas::default(),
+            profiler: gas_meter.gas_profiler().cloned(),
         }
     }
 
@@ -0,0 +1,236 @@
+//! Categorized profile of the gas consumed by a transaction.
+//!
+//! A profile is only collected on demand, e.g. when dry-running a transaction,
+//! by attaching a [`GasProfiler`] to the gas meters. The meters without a
+//! profiler don't pay for any of the bookkeeping.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+use namada_core::address::Address;
+use namada_core::borsh::{BorshDeserialize, BorshSerialize};
+use namada_core::hash::Hash;
+use serde::{Deserialize, Serialize};
+
+use crate::Gas;
+
+/// The gas consumed by a transaction, broken down by category. All the gas
+/// values are in sub units, see [`GasProfile::gas_scale`].
+///
+/// The categories overlap: the gas of the host functions is also part of the
+/// gas of the wasm code that called them, which in turn is part of the gas of
+/// the VP when the code is a VP.
+#[derive(
+    Debug,
+    Clone,
+    Default,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub struct GasProfile {
+    /// The protocol gas scale, to convert the sub units to whole gas units
+    pub gas_scale: u64,
+    /// The gas of the validation, the storage and the network transmission of
+    /// the wrapper tx
+    pub wrapper: Gas,
+    /// The gas of compiling wasm code
+    pub wasm_compilation: Gas,
+    /// The gas of loading wasm code from storage
+    pub wasm_loading: Gas,
+    /// The gas of validating untrusted wasm code
+    pub wasm_validation: Gas,
+    /// The gas of the host function calls, by host function name. The gas of
+    /// a host function that runs other code, like `namada_vp_eval`, includes
+    /// the gas of that code.
+    pub host_fns: BTreeMap<String, Gas>,
+    /// The gas of the VPs, by address of the account they belong to
+    pub vps: BTreeMap<Address, Gas>,
+    /// The gas of the tx and VP wasm executions, by code hash
+    pub wasm: BTreeMap<Hash, Gas>,
+    /// The number of bytes read from storage by the wasm
+    pub storage_bytes_read: u64,
+    /// The number of bytes written to storage by the wasm
+    pub storage_bytes_written: u64,
+}
+
+/// Add some gas to the gas of a category, saturating on overflow
+fn add_gas(category: &mut Gas, gas: Gas) {
+    *category = category.checked_add(gas).unwrap_or_else(|| u64::MAX.into());
+}
+
+/// A handle to a [`GasProfile`] shared by all the gas meters involved in the
+/// execution of a transaction, including the ones of the VPs running in
+/// parallel.
+#[derive(Debug, Clone, Default)]
+pub struct GasProfiler(Arc<Mutex<GasProfile>>);
+
+impl GasProfiler {
+    /// Create a new profiler with an empty profile
+    pub fn new(gas_scale: u64) -> Self {
+        Self(Arc::new(Mutex::new(GasProfile {
+            gas_scale,
+            ..Default::default()
+        })))
+    }
+
+    fn update(&self, f: impl FnOnce(&mut GasProfile)) {
+        let mut profile = self
+            .0
+            .lock()
+            .expect("The gas profile lock must not be poisoned");
+        f(&mut profile)
+    }
+
+    /// Get a copy of the profile collected thus far
+    pub fn snapshot(&self) -> GasProfile {
+        self.0
+            .lock()
+            .expect("The gas profile lock must not be poisoned")
+            .clone()
+    }
+
+    /// Replace the profile collected thus far, returning it
+    pub fn replace(&self, profile: GasProfile) -> GasProfile {
+        let mut current = self
+            .0
+            .lock()
+            .expect("The gas profile lock must not be poisoned");
+        std::mem::replace(&mut current, profile)
+    }
+
+    /// Record the gas of a wrapper tx
+    pub fn record_wrapper(&self, gas: Gas) {
+        self.update(|profile| add_gas(&mut profile.wrapper, gas))
+    }
+
+    /// Record the gas of compiling wasm code
+    pub fn record_wasm_compilation(&self, gas: Gas) {
+        self.update(|profile| add_gas(&mut profile.wasm_compilation, gas))
+    }
+
+    /// Record the gas of loading wasm code from storage
+    pub fn record_wasm_loading(&self, gas: Gas) {
+        self.update(|profile| add_gas(&mut profile.wasm_loading, gas))
+    }
+
+    /// Record the gas of validating untrusted wasm code
+    pub fn record_wasm_validation(&self, gas: Gas) {
+        self.update(|profile| add_gas(&mut profile.wasm_validation, gas))
+    }
+
+    /// Record the gas of a host function call
+    pub fn record_host_fn(&self, name: &str, gas: Gas) {
+        self.update(|profile| {
+            if let Some(host_fn_gas) = profile.host_fns.get_mut(name) {
+                add_gas(host_fn_gas, gas);
+            } else {
+                profile.host_fns.insert(name.to_string(), gas);
+            }
+        })
+    }
+
+    /// Record the gas of a VP
+    pub fn record_vp(&self, address: &Address, gas: Gas) {
+        self.update(|profile| {
+            add_gas(profile.vps.entry(address.clone()).or_default(), gas)
+        })
+    }
+
+    /// Record the gas of a wasm execution
+    pub fn record_wasm(&self, code_hash: Hash, gas: Gas) {
+        self.update(|profile| {
+            add_gas(profile.wasm.entry(code_hash).or_default(), gas)
+        })
+    }
+
+    /// Record the number of bytes read from storage
+    pub fn record_storage_read(&self, bytes_len: u64) {
+        self.update(|profile| {
+            profile.storage_bytes_read =
+                profile.storage_bytes_read.saturating_add(bytes_len)
+        })
+    }
+
+    /// Record the number of bytes written to storage
+    pub fn record_storage_write(&self, bytes_len: u64) {
+        self.update(|profile| {
+            profile.storage_bytes_written =
+                profile.storage_bytes_written.saturating_add(bytes_len)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use namada_core::address::testing::{established_address_1, nam};
+
+    use super::*;
+    use crate::{COMPILE_GAS_PER_BYTE, GasMetering, TxGasMeter, VpGasMeter};
+
+    #[test]
+    fn test_gas_profiler_records() {
+        let profiler = GasProfiler::new(10);
+        let shared = profiler.clone();
+        profiler.record_host_fn("namada_tx_read", 5.into());
+        shared.record_host_fn("namada_tx_read", 7.into());
+        shared.record_host_fn("namada_tx_write", 3.into());
+        profiler.record_vp(&nam(), 11.into());
+        shared.record_vp(&nam(), 1.into());
+        profiler.record_vp(&established_address_1(), 2.into());
+        profiler.record_wasm(Hash::zero(), 4.into());
+        profiler.record_wrapper(8.into());
+        profiler.record_storage_read(100);
+        shared.record_storage_write(u64::MAX);
+        shared.record_storage_write(1);
+
+        let profile = profiler.snapshot();
+        assert_eq!(profile.gas_scale, 10);
+        assert_eq!(profile.host_fns["namada_tx_read"], 12.into());
+        assert_eq!(profile.host_fns["namada_tx_write"], 3.into());
+        assert_eq!(profile.vps[&nam()], 12.into());
+        assert_eq!(profile.vps[&established_address_1()], 2.into());
+        assert_eq!(profile.wasm[&Hash::zero()], 4.into());
+        assert_eq!(profile.wrapper, 8.into());
+        assert_eq!(profile.storage_bytes_read, 100);
+        assert_eq!(profile.storage_bytes_written, u64::MAX);
+
+        // Replacing the profile is seen from all the handles
+        assert_eq!(shared.replace(GasProfile::default()), profile);
+        assert_eq!(profiler.snapshot(), GasProfile::default());
+    }
+
+    #[test]
+    fn test_gas_profile_saturates() {
+        let profiler = GasProfiler::new(1);
+        profiler.record_wasm_compilation(u64::MAX.into());
+        profiler.record_wasm_compilation(1.into());
+        assert_eq!(profiler.snapshot().wasm_compilation, u64::MAX.into());
+    }
+
+    #[test]
+    fn test_gas_meters_share_profiler() {
+        let profiler = GasProfiler::new(1);
+        let mut tx_gas_meter =
+            TxGasMeter::new(u64::MAX, 1).with_gas_profiler(profiler.clone());
+        tx_gas_meter.add_wrapper_gas(&[0; 10]).unwrap();
+        assert_eq!(
+            profiler.snapshot().wrapper,
+            tx_gas_meter.get_consumed_gas()
+        );
+
+        let mut vp_gas_meter = VpGasMeter::new_from_tx_meter(&tx_gas_meter);
+        vp_gas_meter.add_compiling_gas(2).unwrap();
+        assert_eq!(
+            profiler.snapshot().wasm_compilation,
+            COMPILE_GAS_PER_BYTE.checked_mul(2).unwrap().into()
+        );
+
+        // The meters without a profiler don't record anything
+        let mut tx_gas_meter = TxGasMeter::new(u64::MAX, 1);
+        tx_gas_meter.add_wrapper_gas(&[0; 10]).unwrap();
+        assert!(tx_gas_meter.gas_profiler().is_none());
+    }
+}
@@ -30,6 +30,8 @@ mod finalize_block;
 pub mod parameters;
 /// governance public good fundings
 pub mod pgf;
+#[cfg(feature = "migrations")]
+mod schema;
 /// governance storage
 pub mod storage;
 /// Governance utility functions/structs
@@ -0,0 +1,80 @@
+//! Storage schemas of the governance and PGF keys
+
+use std::collections::BTreeMap;
+
+use namada_core::address::Address;
+use namada_core::chain::Epoch;
+use namada_core::dec::Dec;
+use namada_core::token;
+use namada_migrations::storage_schema;
+
+use crate::pgf::storage::steward::StewardDetail;
+use crate::storage::proposal::{ProposalType, StoragePgfFunding};
+use crate::storage::vote::ProposalVote;
+use crate::utils::ProposalResult;
+
+// Governance parameters
+storage_schema!(MIN_PROPOSAL_FUND: "#Governance/min_fund" => token::Amount);
+storage_schema!(MAX_PROPOSAL_CODE_SIZE: "#Governance/max_code_size" => u64);
+storage_schema!(MIN_PROPOSAL_VOTING_PERIOD: "#Governance/min_period" => u64);
+storage_schema!(MAX_PROPOSAL_PERIOD: "#Governance/max_period" => u64);
+storage_schema!(MAX_PROPOSAL_CONTENT: "#Governance/max_content" => u64);
+storage_schema!(MAX_PROPOSAL_LATENCY: "#Governance/max_latency" => u64);
+storage_schema!(
+    MIN_PROPOSAL_GRACE_EPOCHS: "#Governance/min_grace_epochs" => u64
+);
+storage_schema!(PROPOSAL_COUNTER: "#Governance/counter" => u64);
+
+// Proposals
+storage_schema!(
+    PROPOSAL_CONTENT: "#Governance/proposal/<id>/content"
+        => BTreeMap<String, String>
+);
+storage_schema!(PROPOSAL_AUTHOR: "#Governance/proposal/<id>/author" => Address);
+storage_schema!(
+    PROPOSAL_TYPE: "#Governance/proposal/<id>/proposal_type" => ProposalType
+);
+storage_schema!(
+    PROPOSAL_START_EPOCH: "#Governance/proposal/<id>/start_epoch" => Epoch
+);
+storage_schema!(
+    PROPOSAL_END_EPOCH: "#Governance/proposal/<id>/end_epoch" => Epoch
+);
+storage_schema!(
+    PROPOSAL_ACTIVATION_EPOCH:
+        "#Governance/proposal/<id>/activation_epoch" => Epoch
+);
+storage_schema!(
+    PROPOSAL_FUNDS: "#Governance/proposal/<id>/funds" => token::Amount
+);
+storage_schema!(
+    PROPOSAL_CODE: "#Governance/proposal/<id>/proposal_code" => Vec<u8>
+);
+storage_schema!(
+    PROPOSAL_RESULT: "#Governance/proposal/<id>/result"
+        => ProposalResult as debug
+);
+storage_schema!(
+    PROPOSAL_VOTE: "#Governance/proposal/<id>/vote/<validator>/<voter>"
+        => ProposalVote
+);
+storage_schema!(
+    COMMITTING_PROPOSAL: "#Governance/proposal/committing_epoch/<epoch>/<id>"
+        => ()
+);
+storage_schema!(PENDING_PROPOSAL: "#Governance/pending/<id>" => ());
+
+// PGF
+storage_schema!(
+    PGF_STEWARD: "#PGF/stewards/data/<steward>" => StewardDetail
+);
+storage_schema!(
+    PGF_FUNDING: "#PGF/fundings/data/<id>" => StoragePgfFunding
+);
+storage_schema!(PGF_INFLATION_RATE: "#PGF/pgf_inflation_rate" => Dec);
+storage_schema!(
+    PGF_STEWARD_INFLATION_RATE: "#PGF/steward_inflation_rate" => Dec
+);
+storage_schema!(
+    PGF_MAXIMUM_NUMBER_OF_STEWARDS: "#PGF/maximum_number_of_stewards" => u64
+);
@@ -20,8 +20,8 @@ use namada_core::tendermint::Time as TmTime;
 use namada_core::token::Amount;
 use namada_events::EmitEvents;
 use namada_state::{
-    BlockHeader, BlockHeight, Epoch, Epochs, Key, Result, ResultExt, State,
-    StorageRead, StorageWrite, TxIndex,
+    BlockHeader, BlockHeight, Epoch, Epochs, Key, KeyRange, Result, ResultExt,
+    State, StorageRead, StorageWrite, TxIndex,
 };
 use namada_systems::{parameters, trans_token};
 
@@ -63,6 +63,14 @@ where
         self.state.iter_prefix(prefix)
     }
 
+    fn iter_prefix_range<'iter>(
+        &'iter self,
+        prefix: &Key,
+        range: &KeyRange,
+    ) -> Result<Self::PrefixIter<'iter>> {
+        self.state.iter_prefix_range(prefix, range)
+    }
+
     fn iter_next<'iter>(
         &'iter self,
         iter: &mut Self::PrefixIter<'iter>,
@@ -24,6 +24,8 @@ pub mod event;
 mod msg;
 mod nft;
 pub mod parameters;
+#[cfg(feature = "migrations")]
+mod schema;
 pub mod storage;
 pub mod trace;
 pub mod vp;
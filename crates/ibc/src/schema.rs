@@ -0,0 +1,18 @@
+//! Storage schemas of the Borsh encoded IBC keys. The IBC states defined by
+//! ICS are Protobuf encoded and are not covered here.
+
+use namada_core::token::Amount;
+use namada_migrations::storage_schema;
+
+use crate::parameters::IbcParameters;
+
+storage_schema!(PARAMS: "#IBC/params" => IbcParameters as debug);
+storage_schema!(CLIENT_COUNTER: "#IBC/clients/counter" => u64);
+storage_schema!(CONNECTION_COUNTER: "#IBC/connections/counter" => u64);
+storage_schema!(CHANNEL_COUNTER: "#IBC/channelEnds/counter" => u64);
+storage_schema!(TRACE: "#IBC/ibc_trace/<owner>/<token_hash>" => String);
+storage_schema!(MINT_LIMIT: "#IBC/mint_limit/<token>" => Amount);
+storage_schema!(MINT_AMOUNT: "#IBC/mint/<token>" => Amount);
+storage_schema!(THROUGHPUT_LIMIT: "#IBC/throughput_limit/<token>" => Amount);
+storage_schema!(DEPOSIT: "#IBC/deposit/<token>" => Amount);
+storage_schema!(WITHDRAW: "#IBC/withdraw/<token>" => Amount);
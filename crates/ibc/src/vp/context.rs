@@ -12,7 +12,9 @@ use namada_events::Event;
 use namada_gas::MEMORY_ACCESS_GAS_PER_BYTE;
 pub use namada_state::Result;
 use namada_state::write_log::StorageModification;
-use namada_state::{Error, PrefixIter, StateRead, StorageRead, StorageWrite};
+use namada_state::{
+    Error, KeyRange, PrefixIter, StateRead, StorageRead, StorageWrite,
+};
 use namada_systems::trans_token::{self as token, Amount};
 use namada_vp::VpEnv;
 use namada_vp::native_vp::{CtxPreStorageRead, VpEvaluator};
@@ -137,6 +139,16 @@ where
         self.ctx.iter_prefix(prefix)
     }
 
+    fn iter_prefix_range<'iter>(
+        &'iter self,
+        prefix: &Key,
+        range: &KeyRange,
+    ) -> Result<Self::PrefixIter<'iter>> {
+        // NOTE: Read only the previous state since the updated state isn't
+        // needed for the caller
+        self.ctx.iter_prefix_range(prefix, range)
+    }
+
     fn iter_next<'iter>(
         &'iter self,
         iter: &mut Self::PrefixIter<'iter>,
@@ -329,6 +341,14 @@ where
         self.ctx.iter_prefix(prefix)
     }
 
+    fn iter_prefix_range<'iter>(
+        &'iter self,
+        prefix: &Key,
+        range: &KeyRange,
+    ) -> Result<Self::PrefixIter<'iter>> {
+        self.ctx.iter_prefix_range(prefix, range)
+    }
+
     fn iter_next<'iter>(
         &'iter self,
         iter: &mut Self::PrefixIter<'iter>,
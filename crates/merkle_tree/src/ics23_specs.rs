@@ -44,6 +44,21 @@ pub fn ibc_leaf_spec<H: StorageHasher>() -> LeafOp {
     }
 }
 
+/// Get the leaf spec for the non-existence proofs of the account subtree.
+///
+/// The account subtree stores the key-value pairs after hashing, so the
+/// neighbours of an absent key can only be given with their hashed keys and
+/// values, which are thus not hashed again for the verification.
+pub fn hashed_leaf_spec<H: StorageHasher>() -> LeafOp {
+    LeafOp {
+        hash: H::hash_op().into(),
+        prehash_key: HashOp::NoHash.into(),
+        prehash_value: HashOp::NoHash.into(),
+        length: LengthOp::NoPrefix.into(),
+        prefix: H256::zero().as_slice().to_vec(),
+    }
+}
+
 /// Get the proof specs for ibc
 #[allow(dead_code)]
 pub fn ibc_proof_specs<H: StorageHasher>() -> Vec<ProofSpec> {
@@ -73,3 +88,18 @@ pub fn proof_specs<H: StorageHasher>() -> Vec<ProofSpec> {
     };
     vec![sub_tree_spec, base_tree_spec]
 }
+
+/// Get the proof specs for the non-existence proofs of the account subtree,
+/// which prove the absence of the hash of a key.
+pub fn hashed_key_proof_specs<H: StorageHasher>() -> Vec<ProofSpec> {
+    let spec = arse_merkle_tree::proof_ics23::get_spec(H::hash_op());
+    let sub_tree_spec = ProofSpec {
+        leaf_spec: Some(hashed_leaf_spec::<H>()),
+        ..spec.clone()
+    };
+    let base_tree_spec = ProofSpec {
+        leaf_spec: Some(base_leaf_spec::<H>()),
+        ..spec
+    };
+    vec![sub_tree_spec, base_tree_spec]
+}
@@ -138,6 +138,8 @@ pub enum Error {
         "The merklized data did not produce that same hash as the stored root."
     )]
     RootValidationError,
+    #[error("Invalid proof: {0}")]
+    InvalidProof(String),
 }
 
 impl From<MtError> for Error {
@@ -304,15 +306,14 @@ impl StoreType {
 
     /// Get an iterator for the provable subtrees
     pub fn iter_provable() -> std::slice::Iter<'static, Self> {
-        static SUB_TREE_TYPES: [StoreType; 2] =
-            [StoreType::Ibc, StoreType::BridgePool];
+        static SUB_TREE_TYPES: [StoreType; 3] =
+            [StoreType::Account, StoreType::Ibc, StoreType::BridgePool];
         SUB_TREE_TYPES.iter()
     }
 
     /// Get an iterator for the non-provable subtrees
     pub fn iter_non_provable() -> std::slice::Iter<'static, Self> {
-        static SUB_TREE_TYPES: [StoreType; 2] =
-            [StoreType::Account, StoreType::PoS];
+        static SUB_TREE_TYPES: [StoreType; 1] = [StoreType::PoS];
         SUB_TREE_TYPES.iter()
     }
 
@@ -692,6 +693,15 @@ impl<H: StorageHasher + Default> MerkleTree<H> {
         self.tree(store_type).root()
     }
 
+    /// Get the root of a sub-tree as committed in the base tree
+    pub fn committed_sub_root(
+        &self,
+        store_type: &StoreType,
+    ) -> Result<MerkleRoot> {
+        let key = H::hash(store_type.to_string());
+        Ok(self.base.get(&key.into())?.into())
+    }
+
     /// Get the stores of the base and sub trees
     pub fn stores(&self) -> MerkleTreeStoresWrite<'_> {
         MerkleTreeStoresWrite {
@@ -737,26 +747,46 @@ impl<H: StorageHasher + Default> MerkleTree<H> {
             .subtree_membership_proof(std::array::from_ref(&sub_key), values)
     }
 
-    /// Get the non-existence proof
+    /// Get the non-existence proof. The keys of the account and PoS subtrees
+    /// are hashed, so their proofs are of the absence of the hash of the key
+    /// and they are verified with [`ics23_specs::hashed_key_proof_specs`].
     pub fn get_non_existence_proof(&self, key: &Key) -> Result<Proof> {
         let (store_type, sub_key) = StoreType::sub_key(key)?;
-        if store_type != StoreType::Ibc {
-            return Err(Error::NonExistenceProof(store_type.to_string()));
-        }
-
-        let string_key =
-            StringKey::try_from_bytes(sub_key.to_string().as_bytes())?;
-        let mut nep = self.ibc.non_membership_proof(&string_key)?;
+        let (mut nep, leaf_spec) = match store_type {
+            StoreType::Ibc => {
+                let string_key =
+                    StringKey::try_from_bytes(sub_key.to_string().as_bytes())?;
+                (
+                    self.ibc.non_membership_proof(&string_key)?,
+                    ibc_leaf_spec::<H>(),
+                )
+            }
+            StoreType::Account | StoreType::PoS => {
+                let hashed_key: SmtHash = H::hash(sub_key.to_string()).into();
+                let tree = if store_type == StoreType::Account {
+                    &self.account
+                } else {
+                    &self.pos
+                };
+                (
+                    tree.non_membership_proof(&hashed_key)?,
+                    ics23_specs::hashed_leaf_spec::<H>(),
+                )
+            }
+            _ => {
+                return Err(Error::NonExistenceProof(store_type.to_string()));
+            }
+        };
         // Replace the values and the leaf op for the verification
         if let Some(nep) = &mut nep.proof {
             match nep {
                 Ics23Proof::Nonexist(ep) => {
                     let NonExistenceProof { left, right, .. } = ep;
                     if let Some(left) = left.as_mut() {
-                        left.leaf = Some(ibc_leaf_spec::<H>());
+                        left.leaf = Some(leaf_spec.clone());
                     }
                     if let Some(right) = right.as_mut() {
-                        right.leaf = Some(ibc_leaf_spec::<H>());
+                        right.leaf = Some(leaf_spec);
                     }
                 }
                 _ => unreachable!(),
@@ -1000,6 +1030,163 @@ impl From<Proof> for namada_core::tendermint::merkle::proof::ProofOps {
     }
 }
 
+impl TryFrom<&namada_core::tendermint::merkle::proof::ProofOps> for Proof {
+    type Error = Error;
+
+    fn try_from(
+        proof_ops: &namada_core::tendermint::merkle::proof::ProofOps,
+    ) -> Result<Self> {
+        use prost::Message;
+
+        let [sub_proof_op, base_proof_op] = &proof_ops.ops[..] else {
+            return Err(Error::InvalidProof(format!(
+                "Expected a sub proof and a base proof, got {} proof ops",
+                proof_ops.ops.len()
+            )));
+        };
+        if sub_proof_op.key != base_proof_op.key {
+            return Err(Error::InvalidProof(
+                "The sub proof and the base proof are for different keys"
+                    .to_string(),
+            ));
+        }
+        let key = std::str::from_utf8(&sub_proof_op.key)
+            .map_err(|err| Error::InvalidProof(err.to_string()))?;
+        let key = Key::parse(key)?;
+        let decode = |data: &[u8]| {
+            CommitmentProof::decode(data)
+                .map_err(|err| Error::InvalidProof(err.to_string()))
+        };
+
+        Ok(Self {
+            key,
+            sub_proof: decode(&sub_proof_op.data)?,
+            base_proof: decode(&base_proof_op.data)?,
+        })
+    }
+}
+
+impl Proof {
+    /// Check if this is a proof of the absence of the key, rather than of
+    /// its value.
+    pub fn is_non_existence(&self) -> bool {
+        matches!(self.sub_proof.proof, Some(Ics23Proof::Nonexist(_)))
+    }
+
+    /// Verify that the key of this proof has the given value in the tree
+    /// with the given root.
+    pub fn verify_existence<H: StorageHasher>(
+        &self,
+        value: &[u8],
+        root: &MerkleRoot,
+    ) -> Result<()> {
+        let (store_type, sub_key) = StoreType::sub_key(&self.key)?;
+        let specs = match store_type {
+            StoreType::Ibc => ics23_specs::ibc_proof_specs::<H>(),
+            StoreType::Account | StoreType::PoS | StoreType::NoDiff => {
+                ics23_specs::proof_specs::<H>()
+            }
+            _ => return Err(Error::StoreType(store_type.to_string())),
+        };
+        let sub_root = match &self.sub_proof.proof {
+            Some(Ics23Proof::Exist(ep)) => ics23::calculate_existence_root::<
+                ics23::HostFunctionsManager,
+            >(ep)
+            .map_err(|err| Error::InvalidProof(err.to_string()))?,
+            _ => {
+                return Err(Error::InvalidProof(
+                    "The sub proof is not an existence proof".to_string(),
+                ));
+            }
+        };
+        if !ics23::verify_membership::<ics23::HostFunctionsManager>(
+            &self.sub_proof,
+            &specs[0],
+            &sub_root,
+            sub_key.to_string().as_bytes(),
+            value,
+        ) {
+            return Err(Error::InvalidProof(format!(
+                "The sub proof doesn't prove the value of the key {}",
+                self.key
+            )));
+        }
+        self.verify_base::<H>(&store_type, &specs[1], sub_root, root)
+    }
+
+    /// Verify that the key of this proof doesn't exist in the tree with the
+    /// given root.
+    pub fn verify_non_existence<H: StorageHasher>(
+        &self,
+        root: &MerkleRoot,
+    ) -> Result<()> {
+        let (store_type, sub_key) = StoreType::sub_key(&self.key)?;
+        let (specs, sub_key) = match store_type {
+            StoreType::Ibc => (
+                ics23_specs::ibc_proof_specs::<H>(),
+                sub_key.to_string().into_bytes(),
+            ),
+            StoreType::Account | StoreType::PoS => (
+                ics23_specs::hashed_key_proof_specs::<H>(),
+                H::hash(sub_key.to_string()).as_slice().to_vec(),
+            ),
+            _ => return Err(Error::NonExistenceProof(store_type.to_string())),
+        };
+        let neighbour = match &self.sub_proof.proof {
+            Some(Ics23Proof::Nonexist(nep)) => {
+                nep.left.as_ref().or(nep.right.as_ref())
+            }
+            _ => None,
+        }
+        .ok_or_else(|| {
+            Error::InvalidProof(
+                "The sub proof is not a non-existence proof".to_string(),
+            )
+        })?;
+        let sub_root = ics23::calculate_existence_root::<
+            ics23::HostFunctionsManager,
+        >(neighbour)
+        .map_err(|err| Error::InvalidProof(err.to_string()))?;
+        if !ics23::verify_non_membership::<ics23::HostFunctionsManager>(
+            &self.sub_proof,
+            &specs[0],
+            &sub_root,
+            &sub_key,
+        ) {
+            return Err(Error::InvalidProof(format!(
+                "The sub proof doesn't prove the absence of the key {}",
+                self.key
+            )));
+        }
+        self.verify_base::<H>(&store_type, &specs[1], sub_root, root)
+    }
+
+    /// Verify that the sub root is the value of the store type in the base
+    /// tree with the given root
+    fn verify_base<H: StorageHasher>(
+        &self,
+        store_type: &StoreType,
+        spec: &ics23::ProofSpec,
+        sub_root: Vec<u8>,
+        root: &MerkleRoot,
+    ) -> Result<()> {
+        if ics23::verify_membership::<ics23::HostFunctionsManager>(
+            &self.base_proof,
+            spec,
+            &root.0.to_vec(),
+            store_type.to_string().as_bytes(),
+            &sub_root,
+        ) {
+            Ok(())
+        } else {
+            Err(Error::InvalidProof(format!(
+                "The base proof doesn't prove the root of the {store_type} \
+                 store"
+            )))
+        }
+    }
+}
+
 impl<H: StorageHasher + Default> SubTreeRead for &Smt<H> {
     fn root(&self) -> MerkleRoot {
         Smt::<H>::root(self).into()
@@ -1255,7 +1442,9 @@ mod test {
     use namada_core::hash::Sha256Hasher;
 
     use super::*;
-    use crate::ics23_specs::{ibc_proof_specs, proof_specs};
+    use crate::ics23_specs::{
+        hashed_key_proof_specs, ibc_proof_specs, proof_specs,
+    };
 
     #[test]
     fn test_crud_value() {
@@ -1604,4 +1793,155 @@ mod test {
             );
         assert!(basetree_verification_res);
     }
+
+    #[test]
+    fn test_account_non_existence_proof() {
+        let mut tree = MerkleTree::<Sha256Hasher>::default();
+
+        let account_non_key =
+            Key::parse("account/balance/test").expect("Test failed");
+        for i in 0..10_u8 {
+            let account_key = Key::parse(format!("account/balance/test{i}"))
+                .expect("Test failed");
+            tree.update(&account_key, [i; 8]).expect("Test failed");
+        }
+        let (store_type, sub_key) =
+            StoreType::sub_key(&account_non_key).expect("Test failed");
+        assert_eq!(store_type, StoreType::Account);
+
+        let nep = tree
+            .get_non_existence_proof(&account_non_key)
+            .expect("Test failed");
+        let nep_commitment_proof = nep.sub_proof;
+        let non_existence_proof =
+            match nep_commitment_proof.clone().proof.expect("Test failed") {
+                Ics23Proof::Nonexist(nep) => nep,
+                _ => unreachable!(),
+            };
+        let subtree_root = if let Some(left) = &non_existence_proof.left {
+            ics23::calculate_existence_root::<HostFunctionsManager>(left)
+                .unwrap()
+        } else if let Some(right) = &non_existence_proof.right {
+            ics23::calculate_existence_root::<HostFunctionsManager>(right)
+                .unwrap()
+        } else {
+            unreachable!()
+        };
+        assert_eq!(subtree_root, tree.sub_root(&StoreType::Account).0);
+        let specs = hashed_key_proof_specs::<Sha256Hasher>();
+
+        // The absence of the hash of the key is proven
+        let hashed_key = Sha256Hasher::hash(sub_key.to_string());
+        let nep_verification_res =
+            ics23::verify_non_membership::<HostFunctionsManager>(
+                &nep_commitment_proof,
+                &specs[0],
+                &subtree_root,
+                hashed_key.as_slice(),
+            );
+        assert!(nep_verification_res);
+        let basetree_ep_commitment_proof = nep.base_proof;
+        let basetree_ics23_ep =
+            match basetree_ep_commitment_proof.clone().proof.unwrap() {
+                Ics23Proof::Exist(ep) => ep,
+                _ => unreachable!(),
+            };
+        let basetree_root = ics23::calculate_existence_root::<
+            HostFunctionsManager,
+        >(&basetree_ics23_ep)
+        .unwrap();
+        assert_eq!(basetree_root, tree.root().0);
+        let basetree_verification_res =
+            ics23::verify_membership::<HostFunctionsManager>(
+                &basetree_ep_commitment_proof,
+                &specs[1],
+                &basetree_root,
+                store_type.to_string().as_bytes(),
+                &subtree_root,
+            );
+        assert!(basetree_verification_res);
+    }
+
+    #[test]
+    fn test_verify_proof_ops() {
+        use namada_core::tendermint::merkle::proof::ProofOps;
+
+        let mut tree = MerkleTree::<Sha256Hasher>::default();
+
+        let account_key = Key::parse("account/balance/test").unwrap();
+        let ibc_key: Key =
+            Key::from(Address::Internal(InternalAddress::Ibc).to_db_key())
+                .push(&"test".to_string())
+                .unwrap();
+        let pos_key: Key =
+            Key::from(Address::Internal(InternalAddress::PoS).to_db_key())
+                .push(&"test".to_string())
+                .unwrap();
+        for (key, i) in
+            [&account_key, &ibc_key, &pos_key].into_iter().zip(0_u8..)
+        {
+            tree.update(key, [i; 8]).unwrap();
+        }
+        let root = tree.root();
+        let wrong_root = MerkleRoot([1; 32]);
+
+        // Round-trip the proofs through the `ProofOps` sent by the queries
+        let to_proof = |proof: Proof| {
+            let proof_ops: ProofOps = proof.into();
+            Proof::try_from(&proof_ops).unwrap()
+        };
+
+        for (key, i) in
+            [&account_key, &ibc_key, &pos_key].into_iter().zip(0_u8..)
+        {
+            let value = [i; 8];
+            let proof = match tree
+                .get_sub_tree_existence_proof(
+                    std::array::from_ref(key),
+                    vec![&value],
+                )
+                .unwrap()
+            {
+                MembershipProof::ICS23(proof) => proof,
+                _ => panic!("Test failed"),
+            };
+            let proof = to_proof(tree.get_sub_tree_proof(key, proof).unwrap());
+            assert_eq!(&proof.key, key);
+            proof
+                .verify_existence::<Sha256Hasher>(&value, &root)
+                .unwrap();
+            assert_matches!(
+                proof.verify_existence::<Sha256Hasher>(&[9; 8], &root),
+                Err(Error::InvalidProof(_))
+            );
+            assert_matches!(
+                proof.verify_existence::<Sha256Hasher>(&value, &wrong_root),
+                Err(Error::InvalidProof(_))
+            );
+            assert_matches!(
+                proof.verify_non_existence::<Sha256Hasher>(&root),
+                Err(Error::InvalidProof(_))
+            );
+        }
+
+        for key in [&account_key, &ibc_key, &pos_key] {
+            let absent_key = key.push(&"absent".to_string()).unwrap();
+            let proof =
+                to_proof(tree.get_non_existence_proof(&absent_key).unwrap());
+            proof.verify_non_existence::<Sha256Hasher>(&root).unwrap();
+            assert_matches!(
+                proof.verify_non_existence::<Sha256Hasher>(&wrong_root),
+                Err(Error::InvalidProof(_))
+            );
+            // The proof can't be used for a present key
+            let proof = Proof {
+                key: key.clone(),
+                ..proof
+            };
+            assert_matches!(
+                proof.verify_non_existence::<Sha256Hasher>(&root),
+                Err(Error::InvalidProof(_))
+            );
+        }
+    }
 }
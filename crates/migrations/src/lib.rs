@@ -3,9 +3,12 @@ use std::collections::HashMap;
 use std::sync::{Mutex, OnceLock};
 
 use lazy_static::lazy_static;
+#[doc(hidden)]
+pub use linkme;
 pub use linkme::distributed_slice;
 
 pub mod foreign_types;
+pub mod schema;
 
 /// Byte deserialization predicate.
 ///
@@ -0,0 +1,244 @@
+//! Declarative storage schemas.
+//!
+//! Modules register a [`StorageSchema`] for each kind of key they write,
+//! mapping a key pattern to the type of the value stored under it, e.g.:
+//!
+//! ```ignore
+//! namada_migrations::storage_schema!(
+//!     BALANCE: "#Multitoken/balance/<token>/<owner>" => Amount
+//! );
+//! ```
+//!
+//! A pattern is a sequence of segments separated by `/`:
+//!
+//! - `<name>` matches any single key segment and binds it to `name`
+//! - `<name..>` may only be the last segment and matches all the remaining key
+//!   segments (at least one)
+//! - `#Name` matches an address segment of the internal address displayed as
+//!   `Name` (e.g. `#PoS`, `#Multitoken`)
+//! - any other segment must be equal to the raw key segment
+//!
+//! With this, any storage value can be decoded without prior knowledge of
+//! its type (see [`find_schema`]).
+
+use std::fmt::Debug;
+
+use borsh::BorshDeserialize;
+use linkme::distributed_slice;
+use serde::Serialize;
+
+/// Decode a Borsh encoded value into JSON. Returns `None` if the bytes don't
+/// decode as the type erased inside of the callback.
+pub type CbDecodeValue = fn(&[u8]) -> Option<serde_json::Value>;
+
+/// All the storage schemas registered with [`crate::storage_schema`].
+#[distributed_slice]
+pub static STORAGE_SCHEMAS: [StorageSchema];
+
+/// The schema of a storage key.
+#[derive(Debug)]
+pub struct StorageSchema {
+    /// The pattern of the keys described by this schema
+    pub pattern: &'static str,
+    /// The name of the type of the values stored under the matching keys
+    pub type_name: &'static str,
+    /// The decoder of the values stored under the matching keys
+    pub decode: CbDecodeValue,
+}
+
+/// A segment of a storage key to be matched against a schema's pattern.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeySegment {
+    /// The raw segment, as it is in the DB key
+    pub raw: String,
+    /// The name of the internal address in an address segment, if any
+    pub internal_address: Option<String>,
+}
+
+impl KeySegment {
+    fn matches_literal(&self, literal: &str) -> bool {
+        match literal.strip_prefix('#') {
+            Some(name) if self.internal_address.as_deref() == Some(name) => {
+                true
+            }
+            _ => self.raw == literal,
+        }
+    }
+}
+
+/// The segments of a key bound to the named segments of a schema's pattern.
+pub type Bindings = Vec<(&'static str, String)>;
+
+impl StorageSchema {
+    /// Match the segments of a key against the pattern of this schema. On
+    /// success, the key segments bound to the pattern's named segments are
+    /// returned.
+    pub fn matches(&self, segments: &[KeySegment]) -> Option<Bindings> {
+        let mut bindings = Bindings::new();
+        let mut pattern = self.pattern.split('/').peekable();
+        let mut segments = segments.iter();
+        while let Some(pat) = pattern.next() {
+            match pat.strip_prefix('<').and_then(|p| p.strip_suffix('>')) {
+                Some(name) => match name.strip_suffix("..") {
+                    Some(name) if pattern.peek().is_none() => {
+                        let rest: Vec<&str> =
+                            segments.by_ref().map(|s| s.raw.as_str()).collect();
+                        if rest.is_empty() {
+                            return None;
+                        }
+                        bindings.push((name, rest.join("/")));
+                    }
+                    _ => bindings.push((name, segments.next()?.raw.clone())),
+                },
+                None => {
+                    if !segments.next()?.matches_literal(pat) {
+                        return None;
+                    }
+                }
+            }
+        }
+        segments.next().is_none().then_some(bindings)
+    }
+
+    /// The number of literal segments in the pattern, used to pick the most
+    /// specific schema when several match the same key.
+    fn specificity(&self) -> usize {
+        self.pattern
+            .split('/')
+            .filter(|pat| !pat.starts_with('<'))
+            .count()
+    }
+}
+
+/// Find the schema of a key with the given segments, together with the
+/// key segments bound to the schema's pattern. When multiple schemas match,
+/// the one with the most literal segments is returned.
+pub fn find_schema(
+    segments: &[KeySegment],
+) -> Option<(&'static StorageSchema, Bindings)> {
+    let mut found: Option<(&'static StorageSchema, Bindings)> = None;
+    for schema in STORAGE_SCHEMAS {
+        if let Some(bindings) = schema.matches(segments) {
+            match &found {
+                Some((best, _))
+                    if best.specificity() >= schema.specificity() => {}
+                _ => found = Some((schema, bindings)),
+            }
+        }
+    }
+    found
+}
+
+/// Decode a value of a type that can be serialized into JSON.
+pub fn decode_json<T>(bytes: &[u8]) -> Option<serde_json::Value>
+where
+    T: BorshDeserialize + Serialize,
+{
+    let value = T::try_from_slice(bytes).ok()?;
+    serde_json::to_value(value).ok()
+}
+
+/// Decode a value of a type that cannot be serialized into JSON. The value
+/// is returned as a JSON string of its debug representation.
+pub fn decode_debug<T>(bytes: &[u8]) -> Option<serde_json::Value>
+where
+    T: BorshDeserialize + Debug,
+{
+    let value = T::try_from_slice(bytes).ok()?;
+    Some(serde_json::Value::String(format!("{:?}", value)))
+}
+
+/// Register a [`StorageSchema`]. The value type must implement
+/// `serde::Serialize`, unless the schema is declared `as debug`, in which
+/// case the value is decoded into its `Debug` representation.
+///
+/// ```ignore
+/// storage_schema!(BALANCE: "#Multitoken/balance/<token>/<owner>" => Amount);
+/// storage_schema!(PROPOSAL: "#Governance/proposal/<id>/content" =>
+///     BTreeMap<String, String> as debug);
+/// ```
+#[macro_export]
+macro_rules! storage_schema {
+    ($name:ident: $pattern:literal => $ty:ty) => {
+        $crate::storage_schema!(@register $name, $pattern, $ty, decode_json);
+    };
+    ($name:ident: $pattern:literal => $ty:ty as debug) => {
+        $crate::storage_schema!(@register $name, $pattern, $ty, decode_debug);
+    };
+    (@register $name:ident, $pattern:literal, $ty:ty, $decode:ident) => {
+        #[$crate::distributed_slice($crate::schema::STORAGE_SCHEMAS)]
+        #[linkme(crate = $crate::linkme)]
+        static $name: $crate::schema::StorageSchema =
+            $crate::schema::StorageSchema {
+                pattern: $pattern,
+                type_name: stringify!($ty),
+                decode: $crate::schema::$decode::<$ty>,
+            };
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segments(key: &str) -> Vec<KeySegment> {
+        key.split('/')
+            .map(|raw| KeySegment {
+                raw: raw.to_string(),
+                internal_address: (raw == "#tnam1multitoken")
+                    .then(|| "Multitoken".to_string()),
+            })
+            .collect()
+    }
+
+    fn schema(pattern: &'static str) -> StorageSchema {
+        StorageSchema {
+            pattern,
+            type_name: "u64",
+            decode: decode_json::<u64>,
+        }
+    }
+
+    #[test]
+    fn test_schema_pattern_matching() {
+        let balance = schema("#Multitoken/balance/<token>/<owner>");
+        assert_eq!(
+            balance.matches(&segments("#tnam1multitoken/balance/nam/alice")),
+            Some(vec![
+                ("token", "nam".to_string()),
+                ("owner", "alice".to_string())
+            ])
+        );
+        // The address segment may also be matched by its raw form
+        let raw = schema("#tnam1multitoken/balance/<token>/<owner>");
+        assert!(
+            raw.matches(&segments("#tnam1multitoken/balance/nam/alice"))
+                .is_some()
+        );
+        // Too short, too long and different keys don't match
+        for key in [
+            "#tnam1multitoken/balance/nam",
+            "#tnam1multitoken/balance/nam/alice/extra",
+            "#tnam1multitoken/minted/nam/alice",
+            "#tnam1other/balance/nam/alice",
+        ] {
+            assert!(balance.matches(&segments(key)).is_none(), "{key}");
+        }
+
+        let rest = schema("#Multitoken/<rest..>");
+        assert_eq!(
+            rest.matches(&segments("#tnam1multitoken/balance/nam")),
+            Some(vec![("rest", "balance/nam".to_string())])
+        );
+        assert!(rest.matches(&segments("#tnam1multitoken")).is_none());
+        assert!(rest.specificity() < balance.specificity());
+    }
+
+    #[test]
+    fn test_decoders() {
+        let bytes = borsh::to_vec(&42_u64).unwrap();
+        assert_eq!(decode_json::<u64>(&bytes), Some(serde_json::json!(42)));
+        assert_eq!(decode_debug::<u64>(&bytes), Some(serde_json::json!("42")));
+        assert_eq!(decode_json::<u64>(&bytes[..4]), None);
+    }
+}
@@ -87,8 +87,8 @@ use namada_sdk::masp::{
 use namada_sdk::queries::{
     EncodedResponseQuery, RPC, RequestCtx, RequestQuery, Router,
 };
-use namada_sdk::state::StorageRead;
 use namada_sdk::state::write_log::StorageModification;
+use namada_sdk::state::{HistoricState, StorageRead};
 use namada_sdk::storage::{Key, KeySeg, TxIndex};
 use namada_sdk::time::DateTimeUtc;
 use namada_sdk::token::storage_key::minted_balance_key;
@@ -132,7 +132,7 @@ use crate::config::global::GlobalConfig;
 use crate::shell::Shell;
 use crate::tendermint::abci::request::InitChain;
 use crate::tendermint_proto::google::protobuf::Timestamp;
-use crate::{config, dry_run_tx, tendermint_rpc};
+use crate::{config, dry_run_tx, is_dry_run_tx_path, tendermint_rpc};
 
 pub const WASM_DIR: &str = "../../wasm";
 
@@ -928,7 +928,7 @@ impl Client for BenchShell {
 
         let shell = self.read();
 
-        if request.path == RPC.shell().dry_run_tx_path() {
+        if is_dry_run_tx_path(&request.path) {
             dry_run_tx(
                 // This is safe because nothing else is using `self.state`
                 // concurrently and the `TempWlState` will be dropped right
@@ -940,7 +940,7 @@ impl Client for BenchShell {
             )
         } else {
             let ctx = RequestCtx {
-                state: &shell.state,
+                state: HistoricState::new(&shell.state),
                 event_log: shell.event_log(),
                 vp_wasm_cache: shell.vp_wasm_cache.read_only(),
                 tx_wasm_cache: shell.tx_wasm_cache.read_only(),
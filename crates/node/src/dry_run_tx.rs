@@ -3,13 +3,16 @@
 use std::cell::RefCell;
 
 use namada_sdk::borsh::BorshSerializeExt;
+use namada_sdk::gas::profile::GasProfiler;
 use namada_sdk::gas::{GasMetering, TxGasMeter};
 use namada_sdk::parameters;
-use namada_sdk::queries::{EncodedResponseQuery, RequestQuery};
+use namada_sdk::queries::{EncodedResponseQuery, RPC, RequestQuery};
 use namada_sdk::state::{
     DB, DBIter, Error, Result, ResultExt, StorageHasher, TxIndex,
 };
-use namada_sdk::tx::data::{DryRunResult, GasLimit, TxResult, TxType};
+use namada_sdk::tx::data::{
+    DryRunResult, GasLimit, ProfiledDryRunResult, TxResult, TxType,
+};
 use namada_sdk::tx::{self, Tx};
 use namada_vm::WasmCacheAccess;
 use namada_vm::wasm::{TxCache, VpCache};
@@ -17,7 +20,15 @@ use namada_vm::wasm::{TxCache, VpCache};
 use crate::protocol;
 use crate::protocol::ShellParams;
 
-/// Dry run a transaction
+/// Check if a query path is one of the dry run routes, which are handled by
+/// [`dry_run_tx`] instead of the RPC router
+pub fn is_dry_run_tx_path(path: &str) -> bool {
+    path == RPC.shell().dry_run_tx_path()
+        || path == RPC.shell().dry_run_tx_with_gas_profile_path()
+}
+
+/// Dry run a transaction. The breakdown of the consumed gas is only collected
+/// and returned on the gas profile route.
 pub fn dry_run_tx<D, H, CA>(
     mut state: namada_sdk::state::TempWlState<'static, D, H>,
     mut vp_wasm_cache: VpCache<CA>,
@@ -33,6 +44,20 @@ where
 
     let gas_scale = parameters::get_gas_scale(&state)?;
     let height = state.in_mem().get_block_height().0;
+    // On the gas profile route, collect the breakdown of the consumed gas,
+    // shared by all the gas meters
+    let gas_profiler = (request.path
+        == RPC.shell().dry_run_tx_with_gas_profile_path())
+    .then(|| GasProfiler::new(gas_scale));
+    let new_gas_meter = |gas_limit| {
+        let gas_meter = TxGasMeter::new(gas_limit, gas_scale);
+        match &gas_profiler {
+            Some(gas_profiler) => {
+                gas_meter.with_gas_profiler(gas_profiler.clone())
+            }
+            None => gas_meter,
+        }
+    };
 
     // Wrapper dry run to allow estimating the entire gas cost of a transaction
     let (wrapper_hash, tx_result, tx_gas_meter) = match tx.header().tx_type {
@@ -41,8 +66,7 @@ where
                 .gas_limit
                 .as_scaled_gas(gas_scale)
                 .into_storage_result()?;
-            let tx_gas_meter =
-                RefCell::new(TxGasMeter::new(gas_limit, gas_scale));
+            let tx_gas_meter = RefCell::new(new_gas_meter(gas_limit));
             let mut shell_params = ShellParams::new(
                 &tx_gas_meter,
                 &mut state,
@@ -91,7 +115,7 @@ where
             (
                 None,
                 TxResult::default(),
-                RefCell::new(TxGasMeter::new(gas_limit, gas_scale)),
+                RefCell::new(new_gas_meter(gas_limit)),
             )
         }
     };
@@ -119,9 +143,16 @@ where
             .get_consumed_gas()
             .get_whole_gas_units(gas_scale),
     );
+    let data = match gas_profiler {
+        Some(gas_profiler) => {
+            ProfiledDryRunResult(dry_run_result, gas_profiler.snapshot())
+                .serialize_to_vec()
+        }
+        None => dry_run_result.serialize_to_vec(),
+    };
 
     Ok(EncodedResponseQuery {
-        data: dry_run_result.serialize_to_vec(),
+        data,
         proof: None,
         info: Default::default(),
         height,
@@ -138,8 +169,8 @@ mod test {
     use namada_sdk::queries::{
         EncodedResponseQuery, RPC, RequestCtx, RequestQuery, Router,
     };
-    use namada_sdk::state::StorageWrite;
     use namada_sdk::state::testing::TestState;
+    use namada_sdk::state::{HistoricState, StorageWrite};
     use namada_sdk::storage::Key;
     use namada_sdk::tendermint_rpc::{Error as RpcError, Response};
     use namada_sdk::tx::data::TxType;
@@ -243,7 +274,7 @@ mod test {
             // TODO(namada#3240): this is a hack to propagate errors to the
             // caller, we should really permit error types other
             // than [`std::io::Error`]
-            if request.path == RPC.shell().dry_run_tx_path() {
+            if is_dry_run_tx_path(&request.path) {
                 dry_run_tx(
                     // This is safe because nothing else is using `self.state`
                     // concurrently and the `TempWlState` will be dropped right
@@ -257,7 +288,7 @@ mod test {
                 )
             } else {
                 let ctx = RequestCtx {
-                    state: self.state.read_only(),
+                    state: HistoricState::new(self.state.read_only()),
                     event_log: &self.event_log,
                     vp_wasm_cache: self.vp_wasm_cache.clone(),
                     tx_wasm_cache: self.tx_wasm_cache.clone(),
@@ -324,6 +355,28 @@ mod test {
                 .unwrap()
                 .is_accepted()
         );
+        // The gas of the tx code is profiled on request
+        let tx_bytes = outer_tx.to_bytes();
+        let result = RPC
+            .shell()
+            .dry_run_tx_with_gas_profile(&client, Some(tx_bytes), None, false)
+            .await
+            .unwrap();
+        let ProfiledDryRunResult(dry_run_result, gas_profile) = &result.data;
+        assert!(
+            dry_run_result
+                .0
+                .get_inner_tx_result(None, either::Right(cmt))
+                .unwrap()
+                .as_ref()
+                .unwrap()
+                .is_accepted()
+        );
+        assert_eq!(
+            gas_profile.gas_scale,
+            parameters::get_gas_scale(&client.state)?
+        );
+        assert!(gas_profile.wasm.contains_key(&tx_hash));
 
         // Request storage value for a balance key ...
         let token_addr = address::testing::established_address_1();
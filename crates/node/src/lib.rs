@@ -32,7 +32,7 @@ use std::path::PathBuf;
 
 use byte_unit::{Byte, UnitType};
 use data_encoding::HEXUPPER;
-pub use dry_run_tx::dry_run_tx;
+pub use dry_run_tx::{dry_run_tx, is_dry_run_tx_path};
 use futures::future::TryFutureExt;
 use namada_apps_lib::cli::args;
 use namada_apps_lib::config::utils::{
@@ -447,9 +447,51 @@ pub fn query_db(
     );
 }
 
-/// Roll Namada state back to the previous height
-pub fn rollback(config: config::Ledger) -> Result<(), shell::Error> {
-    shell::rollback(config)
+/// Decode the value of a key from Namada ledger node's DB with the storage
+/// schema registered for the key and print it as JSON
+#[cfg(feature = "migrations")]
+pub fn inspect_db(config: config::Ledger, key: &namada_sdk::storage::Key) {
+    use namada_apps_lib::storage::DBUpdateVisitor;
+
+    let chain_id = config.chain_id;
+    let db_path = config.shell.db_dir(&chain_id);
+
+    let db = storage::PersistentDB::open(db_path, None);
+    let db_visitor = storage::RocksDBUpdateVisitor::default();
+    let bytes = db_visitor
+        .read(&db, key, &DbColFam::SUBSPACE)
+        .unwrap_or_else(|| panic!("No value found under key <{}>", key));
+
+    let decoded = namada_sdk::migrations::decode_storage_value(key, &bytes)
+        .unwrap_or_else(|| {
+            panic!(
+                "No storage schema is registered for the key <{}>. Use \
+                 `query-db` with the type hash of the value instead.",
+                key
+            )
+        });
+    let mut json = decoded.to_json();
+    json["key"] = key.to_string().into();
+    json["bytes"] = HEXUPPER.encode(&bytes).into();
+    println!("{}", serde_json::to_string_pretty(&json).unwrap());
+}
+
+/// Roll Namada state back to the given height, or to the previous height if
+/// not specified
+pub fn rollback(
+    config: config::Ledger,
+    to_height: Option<BlockHeight>,
+) -> Result<(), shell::Error> {
+    match to_height {
+        Some(height) => shell::rollback_to_height(config, height),
+        None => shell::rollback(config),
+    }
+}
+
+/// Verify that the Merkle tree committed in Namada ledger node's DB matches
+/// the state in its subspace
+pub fn verify_db(config: config::Ledger) -> Result<(), shell::Error> {
+    shell::verify_db(config)
 }
 
 /// Runs and monitors a few concurrent tasks.
@@ -5,7 +5,8 @@ use std::fmt::{Debug, Display};
 
 use either::Either;
 use eyre::{WrapErr, eyre};
-use namada_sdk::address::{Address, InternalAddress};
+use namada_sdk::account::FeeAllowance;
+use namada_sdk::address::{self, Address, InternalAddress};
 use namada_sdk::booleans::BoolResultUnitExt;
 use namada_sdk::chain::BlockHeight;
 use namada_sdk::collections::HashSet;
@@ -16,7 +17,8 @@ use namada_sdk::events::extend::{
 };
 use namada_sdk::gas::{self, Gas, GasMetering, TxGasMeter, VpGasMeter};
 use namada_sdk::hash::Hash;
-use namada_sdk::parameters::get_gas_scale;
+use namada_sdk::parameters::{BaseFeeDestination, get_gas_scale};
+use namada_sdk::state::write_log::{BlockSnapshot, WriteLog};
 use namada_sdk::state::{
     DB, DBIter, State, StorageHasher, StorageRead, TxWrites, WlState,
 };
@@ -36,7 +38,7 @@ use namada_sdk::validation::{
     AirdropVp, EthBridgeNutVp, EthBridgePoolVp, EthBridgeVp, GovernanceVp,
     IbcVp, MaspVp, MultitokenVp, NativeVpCtx, ParametersVp, PgfVp, PosVp,
 };
-use namada_sdk::{governance, parameters, state, storage, token};
+use namada_sdk::{account, governance, parameters, state, storage, token};
 #[doc(inline)]
 pub use namada_vm::wasm::run::GasMeterKind;
 use namada_vm::wasm::{TxCache, VpCache};
@@ -474,6 +476,108 @@ where
     Ok(tx_result)
 }
 
+/// The outcome of a speculative execution of the inner txs of a batch, see
+/// [`speculate_inner_txs`]
+pub struct SpeculativeBatch {
+    /// The result of the execution. The results of the inner txs that were
+    /// applied together with the wrapper tx are only placeholders.
+    result: std::result::Result<TxResult<Error>, Box<DispatchError>>,
+    /// The gas meter after the execution
+    tx_gas_meter: TxGasMeter,
+    /// The speculative write log with the modifications and the reads of the
+    /// execution
+    pub write_log: WriteLog,
+}
+
+impl SpeculativeBatch {
+    /// Split the speculation into the result of the execution, as it would
+    /// have been returned by [`dispatch_tx`] given the result of the wrapper
+    /// tx, the gas meter and the speculative write log
+    pub fn into_parts(
+        self,
+        mut wrapper_tx_result: TxResult<Error>,
+    ) -> (
+        std::result::Result<TxResult<Error>, Box<DispatchError>>,
+        TxGasMeter,
+        WriteLog,
+    ) {
+        let mut merge = |mut tx_result: TxResult<Error>| {
+            for (inner_tx_hash, result) in std::mem::take(&mut *tx_result) {
+                if !wrapper_tx_result.contains_key(&inner_tx_hash) {
+                    wrapper_tx_result.insert(inner_tx_hash, result);
+                }
+            }
+            std::mem::take(&mut wrapper_tx_result)
+        };
+        let result = match self.result {
+            Ok(tx_result) => Ok(merge(tx_result)),
+            Err(mut err) => {
+                err.tx_result = err.tx_result.map(merge);
+                Err(err)
+            }
+        };
+        (result, self.tx_gas_meter, self.write_log)
+    }
+}
+
+/// Speculatively execute the inner txs of a batch, whose wrapper tx has already
+/// been applied, on top of a snapshot of the block's modifications. The state
+/// is only read and the modifications are kept in the returned speculative
+/// write log, so that the batches of a block can be executed in parallel.
+#[allow(clippy::too_many_arguments)]
+pub fn speculate_inner_txs<D, H, CA>(
+    tx: &Tx,
+    wrapper_hash: &Hash,
+    wrapper_tx_result: &TxResult<Error>,
+    tx_index: TxIndex,
+    height: BlockHeight,
+    tx_gas_meter: &TxGasMeter,
+    state: &WlState<D, H>,
+    snapshot: &BlockSnapshot,
+    mut vp_wasm_cache: VpCache<CA>,
+    mut tx_wasm_cache: TxCache<CA>,
+) -> SpeculativeBatch
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+    CA: 'static + WasmCacheAccess + Sync,
+{
+    // Only the hashes of the inner txs applied together with the wrapper tx
+    // are needed to skip them
+    let mut tx_result = TxResult::default();
+    for inner_tx_hash in wrapper_tx_result.keys() {
+        tx_result.insert(*inner_tx_hash, Ok(BatchedTxResult::default()));
+    }
+    // SAFETY: The `state` is only borrowed immutably, also by the other
+    // speculations running in parallel, until the speculative state is
+    // consumed at the end of this function. The speculative modifications are
+    // kept in its own write log and are only applied to the `state` after
+    // all the speculations are collected.
+    let mut speculative_state =
+        unsafe { state.with_static_speculative_write_log(snapshot) };
+    let tx_gas_meter = RefCell::new(tx_gas_meter.clone());
+
+    let result = dispatch_inner_txs(
+        tx,
+        Some(wrapper_hash),
+        tx_result,
+        tx_index,
+        height,
+        &tx_gas_meter,
+        &mut speculative_state,
+        &mut vp_wasm_cache,
+        &mut tx_wasm_cache,
+        GasMeterKind::MutGlobal,
+        false,
+    );
+
+    SpeculativeBatch {
+        result,
+        tx_gas_meter: tx_gas_meter.into_inner(),
+        write_log: speculative_state.into_write_log(),
+    }
+}
+
 /// Transaction result for masp transfer
 pub struct MaspTxResult {
     tx_result: BatchedTxResult,
@@ -604,12 +708,13 @@ where
                 shell_params.state,
             )
             .map_err(Error::Error)?;
+            let fee_payer = tx.fee_payer(wrapper);
 
             #[cfg(not(fuzzing))]
             let balance = token::read_balance(
                 shell_params.state,
                 &wrapper.fee.token,
-                &wrapper.fee_payer(),
+                &fee_payer,
             )
             .map_err(Error::Error)?;
 
@@ -618,18 +723,43 @@ where
             #[cfg(fuzzing)]
             let balance = Amount::max().checked_div_u64(2).unwrap();
 
+            let fee_allowance =
+                check_fee_allowance(shell_params.state, tx, wrapper, fees)?;
+
+            // Split the fees between the base fee, if the fee market is
+            // enabled, and the tip of the block proposer
+            let base_fee = base_fee_part(shell_params.state, wrapper, fees)?;
+            let tip = match base_fee {
+                Some((base_fee, _)) => checked!(fees - base_fee)
+                    .map_err(|err| Error::FeeError(err.to_string()))?,
+                None => fees,
+            };
+
             let (post_bal, valid_batched_tx_result) = if let Some(post_bal) =
                 balance.checked_sub(fees)
             {
-                fee_token_transfer(
+                fee_payment(
                     shell_params.state,
-                    &wrapper.fee.token,
-                    &wrapper.fee_payer(),
+                    wrapper,
+                    &fee_payer,
                     block_proposer,
-                    fees,
+                    tip,
+                    base_fee,
                 )?;
 
                 (post_bal, None)
+            } else if let Some(fee_granter) = tx.fee_granter() {
+                // This shouldn't happen as it should be prevented from
+                // process_proposal.
+                tracing::error!(
+                    "Transfer of tx fee cannot be applied to due to \
+                     insufficient funds of the fee granter. This shouldn't \
+                     happen."
+                );
+                return Err(Error::FeeError(format!(
+                    "Insufficient funds of the fee granter {fee_granter} for \
+                     fee payment"
+                )));
             } else {
                 // See if the first inner transaction of the batch pays the fees
                 // with a masp unshield
@@ -639,7 +769,7 @@ where
                         let balance = token::read_balance(
                             shell_params.state,
                             &wrapper.fee.token,
-                            &wrapper.fee_payer(),
+                            &fee_payer,
                         )
                         .expect("Could not read balance key from storage");
                         #[cfg(fuzzing)]
@@ -649,12 +779,13 @@ where
                             Some(post_bal) => {
                                 // This cannot fail given the checked_sub check
                                 // here above
-                                fee_token_transfer(
+                                fee_payment(
                                     shell_params.state,
-                                    &wrapper.fee.token,
-                                    &wrapper.fee_payer(),
+                                    wrapper,
+                                    &fee_payer,
                                     block_proposer,
-                                    fees,
+                                    tip,
+                                    base_fee,
                                 )?;
 
                                 post_bal
@@ -693,6 +824,15 @@ where
                 }
             };
 
+            if let Some(fee_allowance) = fee_allowance {
+                spend_fee_allowance(
+                    shell_params.state,
+                    tx,
+                    wrapper,
+                    fee_allowance,
+                )?;
+            }
+
             let target_post_balance = Some(
                 token::read_balance(
                     shell_params.state,
@@ -705,6 +845,8 @@ where
 
             const FEE_PAYMENT_DESCRIPTOR: std::borrow::Cow<'static, str> =
                 std::borrow::Cow::Borrowed("wrapper-fee-payment");
+            const BASE_FEE_PAYMENT_DESCRIPTOR: std::borrow::Cow<'static, str> =
+                std::borrow::Cow::Borrowed("wrapper-base-fee-payment");
             let current_block_height = shell_params
                 .state
                 .in_mem()
@@ -715,10 +857,10 @@ where
                     descriptor: FEE_PAYMENT_DESCRIPTOR,
                     level: EventLevel::Tx,
                     operation: TokenOperation::transfer(
-                        UserAccount::Internal(wrapper.fee_payer()),
+                        UserAccount::Internal(fee_payer.clone()),
                         UserAccount::Internal(block_proposer.clone()),
                         wrapper.fee.token.clone(),
-                        fees.into(),
+                        tip.into(),
                         post_bal.into(),
                         target_post_balance,
                     ),
@@ -727,6 +869,44 @@ where
                 .with(TxHashAttr(tx.header_hash())),
             );
 
+            if let Some((base_fee, destination)) = base_fee {
+                let operation = match destination {
+                    BaseFeeDestination::Burn => TokenOperation::Burn {
+                        target_account: UserAccount::Internal(
+                            fee_payer.clone(),
+                        ),
+                        token: wrapper.fee.token.clone(),
+                        amount: base_fee.into(),
+                        post_balance: post_bal.into(),
+                    },
+                    BaseFeeDestination::Pgf => TokenOperation::transfer(
+                        UserAccount::Internal(fee_payer.clone()),
+                        UserAccount::Internal(address::PGF),
+                        wrapper.fee.token.clone(),
+                        base_fee.into(),
+                        post_bal.into(),
+                        Some(
+                            token::read_balance(
+                                shell_params.state,
+                                &wrapper.fee.token,
+                                &address::PGF,
+                            )
+                            .map_err(Error::Error)?
+                            .into(),
+                        ),
+                    ),
+                };
+                shell_params.state.write_log_mut().emit_event(
+                    TokenEvent {
+                        descriptor: BASE_FEE_PAYMENT_DESCRIPTOR,
+                        level: EventLevel::Tx,
+                        operation,
+                    }
+                    .with(HeightAttr(current_block_height))
+                    .with(TxHashAttr(tx.header_hash())),
+                );
+            }
+
             Ok(valid_batched_tx_result)
         }
         Err(e) => {
@@ -961,6 +1141,175 @@ where
         })
 }
 
+// The base fee part of the fees of a wrapper tx, capped at the fees, and its
+// destination. Returns `None` if the fee market is disabled. Like the fees, the
+// base fee is charged on the gas limit of the wrapper, as the gas used by the
+// batch is only known after its execution and the unused gas is not refunded
+fn base_fee_part<S>(
+    state: &S,
+    wrapper: &WrapperTx,
+    fees: Amount,
+) -> Result<Option<(Amount, BaseFeeDestination)>>
+where
+    S: StorageRead,
+{
+    let Some(fee_market) =
+        parameters::read_fee_market_parameters(state).map_err(Error::Error)?
+    else {
+        return Ok(None);
+    };
+    let base_fee = parameters::read_base_fee(state, &wrapper.fee.token)
+        .map_err(Error::Error)?
+        .unwrap_or_default();
+    let base_fee = checked!(base_fee * u64::from(wrapper.gas_limit))
+        .map_err(|err| Error::FeeError(err.to_string()))?;
+
+    // The fee checks already ensure that the gas price covers the base fee
+    Ok(Some((
+        std::cmp::min(base_fee, fees),
+        fee_market.base_fee_destination,
+    )))
+}
+
+// Manage the token transfers for the fee payment: the base fee part, if any,
+// is burned or sent to PGF and the tip goes to the block proposer. If an error
+// is detected the write log is dropped to prevent committing an inconsistent
+// state. Propagates the result to the caller
+fn fee_payment<WLS>(
+    state: &mut WLS,
+    wrapper: &WrapperTx,
+    fee_payer: &Address,
+    block_proposer: &Address,
+    tip: Amount,
+    base_fee: Option<(Amount, BaseFeeDestination)>,
+) -> Result<()>
+where
+    WLS: State + StorageRead + TxWrites,
+{
+    let token = &wrapper.fee.token;
+    match base_fee {
+        Some((base_fee, BaseFeeDestination::Burn)) => token::burn_tokens(
+            &mut state.with_tx_writes(),
+            token,
+            fee_payer,
+            base_fee,
+        )
+        .map_err(|err| {
+            state.write_log_mut().drop_tx();
+
+            Error::Error(err)
+        })?,
+        Some((base_fee, BaseFeeDestination::Pgf)) => fee_token_transfer(
+            state,
+            token,
+            fee_payer,
+            &address::PGF,
+            base_fee,
+        )?,
+        None => {}
+    }
+    fee_token_transfer(state, token, fee_payer, block_proposer, tip)
+}
+
+/// Check that the fee allowance granted by the fee granter of the wrapper, if
+/// any, to the wrapper signer can pay the fees of the tx. Returns the allowance
+/// left once the fees are paid.
+fn check_fee_allowance<S>(
+    state: &S,
+    tx: &Tx,
+    wrapper: &WrapperTx,
+    fees: Amount,
+) -> Result<Option<FeeAllowance>>
+where
+    S: StorageRead,
+{
+    let Some(granter) = tx.fee_granter() else {
+        return Ok(None);
+    };
+    let grantee = wrapper.signer();
+    let allowance = account::fee_allowance(state, &granter, &grantee)
+        .map_err(Error::Error)?
+        .ok_or_else(|| {
+            Error::FeeError(format!(
+                "No fee allowance was granted by {granter} to {grantee}"
+            ))
+        })?;
+
+    if allowance.token != wrapper.fee.token {
+        return Err(Error::FeeError(format!(
+            "The fee allowance granted by {granter} to {grantee} only pays \
+             fees in {}",
+            allowance.token
+        )));
+    }
+    if let Some(expiration) = allowance.expiration {
+        let current_epoch = state.get_block_epoch().map_err(Error::Error)?;
+        if current_epoch >= expiration {
+            return Err(Error::FeeError(format!(
+                "The fee allowance granted by {granter} to {grantee} expired \
+                 at epoch {expiration}"
+            )));
+        }
+    }
+    if let Some(allowed_code_hashes) = &allowance.allowed_code_hashes {
+        for cmt in tx.commitments() {
+            let code_hash = tx
+                .get_section(cmt.code_sechash())
+                .and_then(|section| section.code_sec())
+                .map(|code| code.code.hash());
+            if !code_hash
+                .is_some_and(|hash| allowed_code_hashes.contains(&hash))
+            {
+                return Err(Error::FeeError(format!(
+                    "The fee allowance granted by {granter} to {grantee} \
+                     doesn't pay the fees of the tx code {}",
+                    cmt.code_sechash()
+                )));
+            }
+        }
+    }
+
+    let spend_limit =
+        allowance.spend_limit.checked_sub(fees).ok_or_else(|| {
+            Error::FeeError(format!(
+                "The fees {fees} exceed the fee allowance {} granted by \
+                 {granter} to {grantee}",
+                allowance.spend_limit
+            ))
+        })?;
+    Ok(Some(FeeAllowance {
+        spend_limit,
+        ..allowance
+    }))
+}
+
+// Write the fee allowance left after the fee payment. If an error is detected
+// the write log is dropped to prevent committing an inconsistent state
+fn spend_fee_allowance<WLS>(
+    state: &mut WLS,
+    tx: &Tx,
+    wrapper: &WrapperTx,
+    allowance: FeeAllowance,
+) -> Result<()>
+where
+    WLS: State + StorageRead + TxWrites,
+{
+    let Some(granter) = tx.fee_granter() else {
+        return Ok(());
+    };
+    account::grant_fee_allowance(
+        &mut state.with_tx_writes(),
+        &granter,
+        &wrapper.signer(),
+        allowance,
+    )
+    .map_err(|err| {
+        state.write_log_mut().drop_tx();
+
+        Error::Error(err)
+    })
+}
+
 /// Check if the fee payer has enough transparent balance to pay fees
 pub fn check_fees<S, D, H, CA>(
     shell_params: &mut ShellParams<'_, S, D, H, CA>,
@@ -988,15 +1337,28 @@ where
             )
             .map_err(Error::Error)?;
 
+            check_fee_allowance(shell_params.state, tx, wrapper, fees)?;
+
+            let fee_payer = tx.fee_payer(wrapper);
             let balance = token::read_balance(
                 shell_params.state,
                 &wrapper.fee.token,
-                &wrapper.fee_payer(),
+                &fee_payer,
             )
             .map_err(Error::Error)?;
 
             checked!(balance - fees).map_or_else(
                 |_| {
+                    // The fees granted by another account can't be paid via
+                    // the masp
+                    if let Some(fee_granter) = tx.fee_granter() {
+                        return Err(Error::FeeError(format!(
+                            "The fee granter {fee_granter} has insufficient \
+                             balance to pay fees. Balance: {balance} {}, \
+                             required {fees}",
+                            wrapper.fee.token
+                        )));
+                    }
                     // See if the first inner transaction of the batch pays
                     // the fees with a masp unshield
                     let valid_batched_tx_result = try_masp_fee_payment(
@@ -1008,7 +1370,7 @@ where
                     let balance = token::read_balance(
                         shell_params.state,
                         &wrapper.fee.token,
-                        &wrapper.fee_payer(),
+                        &fee_payer,
                     )
                     .map_err(Error::Error)?;
 
@@ -1193,18 +1555,27 @@ where
     H: 'static + StorageHasher + Sync,
     CA: 'static + WasmCacheAccess + Sync,
 {
-    wasm::run::tx(
-        state,
-        tx_gas_meter,
-        wrapper_hash,
-        tx_index,
-        batched_tx.tx,
-        batched_tx.cmt,
-        vp_wasm_cache,
-        tx_wasm_cache,
-        gas_meter_kind,
-        dry_run,
-    )
+    let code_hash = || {
+        batched_tx
+            .tx
+            .get_section(batched_tx.cmt.code_sechash())
+            .and_then(|section| section.code_sec())
+            .map(|code| code.code.hash())
+    };
+    profile_wasm_gas(tx_gas_meter, code_hash, || {
+        wasm::run::tx(
+            state,
+            tx_gas_meter,
+            wrapper_hash,
+            tx_index,
+            batched_tx.tx,
+            batched_tx.cmt,
+            vp_wasm_cache,
+            tx_wasm_cache,
+            gas_meter_kind,
+            dry_run,
+        )
+    })
     .map_err(|err| match err {
         wasm::run::Error::GasError(msg) => Error::GasError(msg),
         wasm::run::Error::MissingSection(msg) => Error::MissingSection(msg),
@@ -1212,6 +1583,36 @@ where
     })
 }
 
+/// Run some wasm, recording the gas that it consumes under its code hash when
+/// the gas meter has a profiler.
+fn profile_wasm_gas<M, T>(
+    gas_meter: &RefCell<M>,
+    code_hash: impl FnOnce() -> Option<Hash>,
+    run: impl FnOnce() -> T,
+) -> T
+where
+    M: GasMetering,
+{
+    let profiled =
+        gas_meter
+            .borrow()
+            .gas_profiler()
+            .cloned()
+            .and_then(|profiler| {
+                let gas_before = gas_meter.borrow().get_consumed_gas();
+                code_hash().map(|code_hash| (profiler, code_hash, gas_before))
+            });
+    let result = run();
+    if let Some((profiler, code_hash, gas_before)) = profiled {
+        let gas_after = gas_meter.borrow().get_consumed_gas();
+        profiler.record_wasm(
+            code_hash,
+            gas_after.checked_sub(gas_before).unwrap_or_default(),
+        );
+    }
+    result
+}
+
 /// Arguments to [`check_vps`].
 struct CheckVps<'a, S, CA>
 where
@@ -1306,18 +1707,24 @@ where
                             return Err(Error::MissingAddress(addr.clone()));
                         };
 
-                        wasm::run::vp(
-                            vp_code_hash,
-                            batched_tx,
-                            tx_index,
-                            addr,
-                            state,
+                        profile_wasm_gas(
                             &gas_meter,
-                            &keys_changed,
-                            &verifiers,
-                            vp_wasm_cache.clone(),
-                            gas_meter_kind,
-                            dry_run,
+                            || Some(vp_code_hash),
+                            || {
+                                wasm::run::vp(
+                                    vp_code_hash,
+                                    batched_tx,
+                                    tx_index,
+                                    addr,
+                                    state,
+                                    &gas_meter,
+                                    &keys_changed,
+                                    &verifiers,
+                                    vp_wasm_cache.clone(),
+                                    gas_meter_kind,
+                                    dry_run,
+                                )
+                            },
                         )
                         .map_err(|err| match err {
                             wasm::run::Error::GasError(msg) => {
@@ -1491,11 +1898,13 @@ where
                 // all the other errors we keep evaluating the vps. This
                 // allows to display a consistent VpsResult across all
                 // nodes and find any invalid signatures
-                vps_gas = vps_gas
-                    .checked_add(gas_meter.borrow().get_vp_consumed_gas())
-                    .ok_or(Error::GasError(
-                        gas::Error::GasOverflow.to_string(),
-                    ))?;
+                let vp_gas = gas_meter.borrow().get_vp_consumed_gas();
+                if let Some(profiler) = gas_meter.borrow().gas_profiler() {
+                    profiler.record_vp(addr, vp_gas.clone());
+                }
+                vps_gas = vps_gas.checked_add(vp_gas).ok_or(
+                    Error::GasError(gas::Error::GasOverflow.to_string()),
+                )?;
                 gas_meter
                     .borrow()
                     .check_limit(vps_gas.clone())
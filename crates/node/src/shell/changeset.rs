@@ -0,0 +1,328 @@
+//! Export of the storage changeset of the committed blocks, for consumers
+//! that need the exact state changes of every block, such as indexers.
+//!
+//! The changeset of each block is written to its own file
+//! `{height}.changeset` in the export directory. It contains the same data
+//! that gets stored in the diffs of the block, encoded as follows, where all
+//! the integers are big-endian:
+//!
+//! - the magic bytes [`MAGIC`]
+//! - the format version, a `u8`, currently [`FORMAT_VERSION`]
+//! - the block height, a `u64`
+//! - the number of changes, a `u32`
+//! - for every change, sorted by its key:
+//!   - the merkle tree store type of the key, a borsh encoded [`StoreType`]
+//!   - the key, a `u32` length-prefixed string
+//!   - the value before the block, a `u8` flag that is `1` when present,
+//!     followed by the `u32` length-prefixed bytes of the value
+//!   - the value after the block, encoded the same way. It's absent when the
+//!     key was deleted.
+
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+use namada_sdk::borsh::{BorshDeserialize, BorshSerializeExt};
+use namada_sdk::eth_bridge_pool::is_pending_transfer_key;
+use namada_sdk::state::{
+    BlockHeight, DB, DBIter, StorageHasher, StoreType, SubspaceChange,
+};
+use namada_sdk::storage::Key;
+
+use super::{Shell, is_key_diff_storable};
+
+/// The magic bytes at the start of a changeset file
+pub const MAGIC: &[u8; 8] = b"NAMCHSET";
+
+/// The current version of the changeset file format
+pub const FORMAT_VERSION: u8 = 1;
+
+/// The extension of the changeset files
+const FILE_EXTENSION: &str = "changeset";
+
+/// A change of a storage key in a block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    /// The merkle tree store of the key
+    pub store_type: StoreType,
+    /// The changed key
+    pub key: Key,
+    /// The value before the block, if any
+    pub old_value: Option<Vec<u8>>,
+    /// The value after the block, `None` if the key was deleted
+    pub new_value: Option<Vec<u8>>,
+}
+
+/// The storage changes of a block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockChangeset {
+    /// The height of the block
+    pub height: BlockHeight,
+    /// The changes, sorted by their keys
+    pub changes: Vec<Change>,
+}
+
+impl BlockChangeset {
+    /// Make the changeset of a block from its subspace changes
+    pub fn new(height: BlockHeight, changes: Vec<SubspaceChange>) -> Self {
+        let changes = changes
+            .into_iter()
+            .map(
+                |SubspaceChange {
+                     key,
+                     old_value,
+                     new_value,
+                 }| Change {
+                    store_type: store_type(&key),
+                    key,
+                    old_value,
+                    new_value,
+                },
+            )
+            .collect();
+        Self { height, changes }
+    }
+
+    /// The path of the file of the changeset of the block at the given
+    /// height in the export directory
+    pub fn file_path(dir: impl AsRef<Path>, height: BlockHeight) -> PathBuf {
+        dir.as_ref()
+            .join(height.0.to_string())
+            .with_extension(FILE_EXTENSION)
+    }
+
+    /// Write the changeset to its file in the export directory. The file is
+    /// moved in place once complete, so that it's never read partially.
+    pub fn write_to_dir(&self, dir: impl AsRef<Path>) -> io::Result<()> {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)?;
+        let path = Self::file_path(dir, self.height);
+        let tmp_path = path.with_extension(format!("{FILE_EXTENSION}.tmp"));
+        let mut writer = BufWriter::new(File::create(&tmp_path)?);
+        self.encode(&mut writer)?;
+        writer
+            .into_inner()
+            .map_err(|err| err.into_error())?
+            .sync_all()?;
+        std::fs::rename(tmp_path, path)
+    }
+
+    /// Read the changeset of the block at the given height from its file in
+    /// the export directory
+    pub fn read_from_dir(
+        dir: impl AsRef<Path>,
+        height: BlockHeight,
+    ) -> io::Result<Self> {
+        let file = File::open(Self::file_path(dir, height))?;
+        Self::decode(&mut BufReader::new(file))
+    }
+
+    /// Encode the changeset in the versioned format of the changeset files
+    pub fn encode(&self, writer: &mut impl Write) -> io::Result<()> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[FORMAT_VERSION])?;
+        writer.write_all(&self.height.0.to_be_bytes())?;
+        write_len(writer, self.changes.len())?;
+        for change in &self.changes {
+            writer.write_all(&change.store_type.serialize_to_vec())?;
+            write_bytes(writer, change.key.to_string().as_bytes())?;
+            write_opt_bytes(writer, change.old_value.as_deref())?;
+            write_opt_bytes(writer, change.new_value.as_deref())?;
+        }
+        writer.flush()
+    }
+
+    /// Decode a changeset encoded in the versioned format of the changeset
+    /// files
+    pub fn decode(reader: &mut impl Read) -> io::Result<Self> {
+        let mut magic = [0; MAGIC.len()];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid_data("Not a changeset file"));
+        }
+        let [version] = read_array::<1>(reader)?;
+        if version != FORMAT_VERSION {
+            return Err(invalid_data(format!(
+                "Unsupported changeset format version {version}"
+            )));
+        }
+        let height = BlockHeight(u64::from_be_bytes(read_array(reader)?));
+        let len = read_len(reader)?;
+        let mut changes = Vec::with_capacity(len.min(1024));
+        for _ in 0..len {
+            let store_type = StoreType::deserialize_reader(reader)?;
+            let key =
+                String::from_utf8(read_bytes(reader)?).map_err(invalid_data)?;
+            let key = Key::parse(key).map_err(invalid_data)?;
+            let old_value = read_opt_bytes(reader)?;
+            let new_value = read_opt_bytes(reader)?;
+            changes.push(Change {
+                store_type,
+                key,
+                old_value,
+                new_value,
+            });
+        }
+        Ok(Self { height, changes })
+    }
+}
+
+impl<D, H> Shell<D, H>
+where
+    D: DB + for<'iter> DBIter<'iter> + Sync + 'static,
+    H: StorageHasher + Sync + 'static,
+{
+    /// Export the changeset of the last committed block, if enabled. A
+    /// failure is only logged, as it must not halt the node.
+    pub(super) fn export_changeset(&self) {
+        let Some(dir) = self.changeset_export_dir.as_ref() else {
+            return;
+        };
+        let height = self.state.in_mem().get_last_block_height();
+        let result = self
+            .state
+            .db()
+            .read_subspace_changeset(height)
+            .map_err(io::Error::other)
+            .and_then(|changes| {
+                BlockChangeset::new(height, changes).write_to_dir(dir)
+            });
+        if let Err(err) = result {
+            tracing::error!(
+                "Failed to export the changeset of the block at height \
+                 {height}: {err}"
+            );
+        }
+    }
+}
+
+/// Get the merkle tree store of a key, the same way it's assigned when the
+/// key gets written to storage
+fn store_type(key: &Key) -> StoreType {
+    if is_pending_transfer_key(key) {
+        return StoreType::BridgePool;
+    }
+    if !is_key_diff_storable(key) {
+        return StoreType::NoDiff;
+    }
+    StoreType::sub_key(key)
+        .map(|(store_type, _)| store_type)
+        .unwrap_or(StoreType::Account)
+}
+
+fn invalid_data(
+    err: impl Into<Box<dyn std::error::Error + Send + Sync>>,
+) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
+
+fn write_len(writer: &mut impl Write, len: usize) -> io::Result<()> {
+    let len = u32::try_from(len).map_err(invalid_data)?;
+    writer.write_all(&len.to_be_bytes())
+}
+
+fn write_bytes(writer: &mut impl Write, bytes: &[u8]) -> io::Result<()> {
+    write_len(writer, bytes.len())?;
+    writer.write_all(bytes)
+}
+
+fn write_opt_bytes(
+    writer: &mut impl Write,
+    bytes: Option<&[u8]>,
+) -> io::Result<()> {
+    match bytes {
+        Some(bytes) => {
+            writer.write_all(&[1])?;
+            write_bytes(writer, bytes)
+        }
+        None => writer.write_all(&[0]),
+    }
+}
+
+fn read_array<const N: usize>(reader: &mut impl Read) -> io::Result<[u8; N]> {
+    let mut buf = [0; N];
+    reader.read_exact(&mut buf)?;
+    Ok(buf)
+}
+
+fn read_len(reader: &mut impl Read) -> io::Result<usize> {
+    let len = u32::from_be_bytes(read_array(reader)?);
+    usize::try_from(len).map_err(invalid_data)
+}
+
+fn read_bytes(reader: &mut impl Read) -> io::Result<Vec<u8>> {
+    let len = read_len(reader)?;
+    let mut bytes = Vec::new();
+    reader.take(len as u64).read_to_end(&mut bytes)?;
+    if bytes.len() != len {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(bytes)
+}
+
+fn read_opt_bytes(reader: &mut impl Read) -> io::Result<Option<Vec<u8>>> {
+    match read_array::<1>(reader)? {
+        [0] => Ok(None),
+        [1] => read_bytes(reader).map(Some),
+        [flag] => Err(invalid_data(format!("Invalid value flag {flag}"))),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use namada_sdk::storage::KeySeg;
+    use namada_sdk::{address, token};
+
+    use super::*;
+
+    #[test]
+    fn test_changeset_file_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let account_key = Key::parse("some/key").unwrap();
+        let pos_key = Key::from(address::POS.to_db_key())
+            .push(&"params".to_string())
+            .unwrap();
+        let masp_key = token::storage_key::masp_commitment_tree_key();
+        let changeset = BlockChangeset::new(
+            BlockHeight(7),
+            vec![
+                SubspaceChange {
+                    key: account_key,
+                    old_value: None,
+                    new_value: Some(vec![1, 2]),
+                },
+                SubspaceChange {
+                    key: pos_key,
+                    old_value: Some(vec![3]),
+                    new_value: None,
+                },
+                SubspaceChange {
+                    key: masp_key,
+                    old_value: Some(vec![]),
+                    new_value: Some(vec![4]),
+                },
+            ],
+        );
+        let store_types: Vec<_> = changeset
+            .changes
+            .iter()
+            .map(|change| change.store_type)
+            .collect();
+        assert_eq!(
+            store_types,
+            vec![StoreType::Account, StoreType::PoS, StoreType::NoDiff]
+        );
+
+        changeset.write_to_dir(dir.path()).unwrap();
+        let path = BlockChangeset::file_path(dir.path(), BlockHeight(7));
+        assert!(path.ends_with("7.changeset"));
+        let read =
+            BlockChangeset::read_from_dir(dir.path(), BlockHeight(7)).unwrap();
+        assert_eq!(read, changeset);
+
+        // A truncated file is rejected
+        let bytes = std::fs::read(&path).unwrap();
+        let truncated = &bytes[..bytes.len() - 1];
+        assert!(BlockChangeset::decode(&mut &truncated[..]).is_err());
+    }
+}
@@ -26,6 +26,7 @@ use namada_sdk::tx::new_tx_event;
 use namada_sdk::{ibc, proof_of_stake};
 use namada_vote_ext::ethereum_events::MultiSignedEthEvent;
 use namada_vote_ext::ethereum_tx_data_variants;
+use rayon::prelude::*;
 use tendermint::abci::types::Misbehavior;
 
 use super::*;
@@ -178,6 +179,18 @@ where
         tracing::info!("{}", stats);
         tracing::info!("{}", stats.format_tx_executed());
 
+        // Adjust the base fees of the fee market to the gas used by this block
+        let block_gas_used = Gas::from(
+            self.state
+                .in_mem()
+                .commit_only_data
+                .tx_gas
+                .values()
+                .fold(0_u64, |acc, gas| acc.saturating_add(*gas)),
+        )
+        .get_whole_gas_units(gas_scale);
+        parameters::update_base_fees(&mut self.state, block_gas_used.into())?;
+
         // Update the MASP commitment tree anchor if the tree was updated
         let tree_key = token::storage_key::masp_commitment_tree_key();
         if let Some(StorageModification::Write { value }) = self
@@ -208,6 +221,7 @@ where
         )?;
 
         self.event_log_mut().emit_many(response.events.clone());
+        self.block_events.clone_from(&response.events);
         tracing::debug!("End finalize_block {height} of epoch {current_epoch}");
 
         Ok(response)
@@ -830,39 +844,112 @@ where
             height,
         }: ExecutionArgs<'_>,
     ) {
-        for WrapperCache {
-            mut tx,
-            tx_index,
-            gas_meter: tx_gas_meter,
-            event: tx_event,
-            tx_result: wrapper_tx_result,
-        } in successful_wrappers
+        let batches: Vec<_> = successful_wrappers
+            .into_iter()
+            .map(|mut wrapper| {
+                let replay_protection_hashes = ReplayProtectionHashes {
+                    raw_header_hash: wrapper.tx.raw_header_hash(),
+                    header_hash: wrapper.tx.header_hash(),
+                };
+                // change tx type to raw for execution
+                wrapper.tx.update_header(TxType::Raw);
+                (wrapper, replay_protection_hashes)
+            })
+            .collect();
+
+        // When enabled, speculatively execute all the batches in parallel on
+        // top of a snapshot of the block write log, which at this point
+        // already contains the changes of all the wrapper txs. The
+        // speculations are then validated and committed in the original order
+        // of the block: a speculation is only adopted if all of its reads
+        // resolve to the same modifications as they would when executing
+        // sequentially, in which case it produces the exact same changes,
+        // results and gas. Otherwise the batch is re-executed sequentially.
+        let snapshot = (self.parallel_inner_txs && batches.len() > 1)
+            .then(|| self.state.write_log().block_snapshot());
+        let speculations: Vec<Option<protocol::SpeculativeBatch>> =
+            match &snapshot {
+                Some(snapshot) => {
+                    let state = self.state.read_only();
+                    let vp_wasm_cache = &self.vp_wasm_cache;
+                    let tx_wasm_cache = &self.tx_wasm_cache;
+                    batches
+                        .par_iter()
+                        .map(|(wrapper, replay_protection_hashes)| {
+                            Some(protocol::speculate_inner_txs(
+                                &wrapper.tx,
+                                &replay_protection_hashes.header_hash,
+                                &wrapper.tx_result,
+                                TxIndex::must_from_usize(wrapper.tx_index),
+                                height,
+                                &wrapper.gas_meter,
+                                state,
+                                snapshot,
+                                vp_wasm_cache.clone(),
+                                tx_wasm_cache.clone(),
+                            ))
+                        })
+                        .collect()
+                }
+                None => batches.iter().map(|_| None).collect(),
+            };
+
+        for (
+            (
+                WrapperCache {
+                    tx,
+                    tx_index,
+                    gas_meter: tx_gas_meter,
+                    event: tx_event,
+                    tx_result: wrapper_tx_result,
+                },
+                replay_protection_hashes,
+            ),
+            speculation,
+        ) in batches.into_iter().zip(speculations)
         {
-            let tx_hash = tx.header_hash();
+            let tx_hash = replay_protection_hashes.header_hash;
             let is_atomic_batch = tx.header.atomic;
             let commitments_len = tx.commitments().len() as u64;
-            let replay_protection_hashes = Some(ReplayProtectionHashes {
-                raw_header_hash: tx.raw_header_hash(),
-                header_hash: tx.header_hash(),
-            });
 
-            // change tx type to raw for execution
-            tx.update_header(TxType::Raw);
-            let tx_gas_meter = RefCell::new(tx_gas_meter);
-            let dispatch_result = protocol::dispatch_tx(
-                &tx,
-                DispatchArgs::Raw {
-                    wrapper_hash: Some(&tx_hash),
-                    tx_index: TxIndex::must_from_usize(tx_index),
-                    height,
-                    wrapper_tx_result: Some(wrapper_tx_result),
-                    vp_wasm_cache: &mut self.vp_wasm_cache,
-                    tx_wasm_cache: &mut self.tx_wasm_cache,
-                },
-                &tx_gas_meter,
-                &mut self.state,
-            );
-            let tx_gas_meter = tx_gas_meter.into_inner();
+            let speculation = speculation.filter(|speculation| {
+                let write_log = self.state.write_log();
+                // Leave the rejection of a replayed batch to the sequential
+                // execution
+                !write_log.has_replay_protection_entry(
+                    &replay_protection_hashes.raw_header_hash,
+                ) && snapshot
+                    .as_ref()
+                    .zip(speculation.write_log.read_set())
+                    .is_some_and(|(snapshot, read_set)| {
+                        write_log.is_speculation_valid(snapshot, &read_set)
+                    })
+            });
+            let (dispatch_result, tx_gas_meter) = match speculation {
+                Some(speculation) => {
+                    let (dispatch_result, tx_gas_meter, write_log) =
+                        speculation.into_parts(wrapper_tx_result);
+                    self.state.write_log_mut().adopt_speculative(write_log);
+                    (dispatch_result, tx_gas_meter)
+                }
+                None => {
+                    let tx_gas_meter = RefCell::new(tx_gas_meter);
+                    let dispatch_result = protocol::dispatch_tx(
+                        &tx,
+                        DispatchArgs::Raw {
+                            wrapper_hash: Some(&tx_hash),
+                            tx_index: TxIndex::must_from_usize(tx_index),
+                            height,
+                            wrapper_tx_result: Some(wrapper_tx_result),
+                            vp_wasm_cache: &mut self.vp_wasm_cache,
+                            tx_wasm_cache: &mut self.tx_wasm_cache,
+                        },
+                        &tx_gas_meter,
+                        &mut self.state,
+                    );
+                    (dispatch_result, tx_gas_meter.into_inner())
+                }
+            };
             let consumed_gas = tx_gas_meter.get_consumed_gas();
 
             // update the gas cost of the corresponding wrapper
@@ -876,7 +963,7 @@ where
                     tx: &tx,
                     commitments_len,
                     tx_index,
-                    replay_protection_hashes,
+                    replay_protection_hashes: Some(replay_protection_hashes),
                     tx_gas_meter,
                     height,
                 },
@@ -1295,7 +1382,9 @@ mod test_finalize_block {
     use namada_sdk::hash::Hash;
     use namada_sdk::keccak::KeccakHash;
     use namada_sdk::key::testing::common_sk_from_simple_seed;
-    use namada_sdk::parameters::EpochDuration;
+    use namada_sdk::parameters::{
+        BaseFeeDestination, EpochDuration, FeeMarketParams,
+    };
     use namada_sdk::proof_of_stake::storage::{
         enqueued_slashes_handle, get_num_consensus_validators,
         liveness_missed_votes_handle, liveness_sum_missed_votes_handle,
@@ -3780,7 +3869,7 @@ mod test_finalize_block {
         let signer_balance = namada_sdk::token::read_balance(
             &shell.state,
             &shell.state.in_mem().native_token,
-            &wrapper.header().wrapper().unwrap().fee_payer(),
+            &wrapper.fee_payer(&wrapper.header().wrapper().unwrap()),
         )
         .unwrap();
 
@@ -3815,7 +3904,7 @@ mod test_finalize_block {
         let new_signer_balance = namada_sdk::token::read_balance(
             &shell.state,
             &shell.state.in_mem().native_token,
-            &wrapper.header().wrapper().unwrap().fee_payer(),
+            &wrapper.fee_payer(&wrapper.header().wrapper().unwrap()),
         )
         .unwrap();
         assert_eq!(
@@ -3894,6 +3983,180 @@ mod test_finalize_block {
         }
     }
 
+    // Test that the fees of a wrapper with a fee granter are paid by the
+    // granter and deducted from the allowance of the signer
+    #[test]
+    fn test_fee_payment_with_fee_allowance() {
+        let (mut shell, _, _, _) = setup();
+        let native_token = shell.state.in_mem().native_token.clone();
+        let granter = Address::from(&albert_keypair().to_public());
+        let keypair = gen_keypair();
+        let grantee = Address::from(&keypair.to_public());
+        let spend_limit = Amount::native_whole(1_000);
+
+        namada_sdk::account::grant_fee_allowance(
+            &mut shell.state,
+            &granter,
+            &grantee,
+            namada_sdk::account::FeeAllowance {
+                token: native_token.clone(),
+                spend_limit,
+                expiration: None,
+                allowed_code_hashes: None,
+            },
+        )
+        .unwrap();
+
+        let mut wrapper =
+            Tx::from_type(TxType::Wrapper(Box::new(WrapperTx::new(
+                Fee {
+                    amount_per_gas_unit: DenominatedAmount::native(100.into()),
+                    token: native_token.clone(),
+                },
+                keypair.ref_to(),
+                WRAPPER_GAS_LIMIT.into(),
+            ))));
+        wrapper.header.chain_id = shell.chain_id.clone();
+        wrapper.add_fee_granter(granter.clone());
+        wrapper.set_code(Code::new(TestWasms::TxNoOp.read_bytes(), None));
+        wrapper.set_data(Data::new("transaction data".as_bytes().to_owned()));
+        wrapper.add_section(Section::Authorization(Authorization::new(
+            wrapper.sechashes(),
+            [(0, keypair.clone())].into_iter().collect(),
+            None,
+        )));
+
+        let fee_amount =
+            wrapper.header().wrapper().unwrap().get_tx_fee().unwrap();
+        let fee_amount = namada_sdk::token::denom_to_amount(
+            fee_amount,
+            &native_token,
+            &shell.state,
+        )
+        .unwrap();
+        let granter_balance =
+            read_balance(&shell.state, &native_token, &granter).unwrap();
+
+        let processed_tx = ProcessedTx {
+            tx: wrapper.to_bytes().into(),
+            result: TxResult {
+                code: ResultCode::Ok.into(),
+                info: "".into(),
+            },
+        };
+        let event = &shell
+            .finalize_block(FinalizeBlock {
+                txs: vec![processed_tx],
+                ..Default::default()
+            })
+            .expect("Test failed")[0];
+        assert_eq!(*event.kind(), APPLIED_TX);
+        let code = event.read_attribute::<CodeAttr>().expect("Test failed");
+        assert_eq!(code, ResultCode::Ok);
+
+        // Check that the granter paid the fees on behalf of the grantee
+        assert_eq!(
+            read_balance(&shell.state, &native_token, &granter).unwrap(),
+            granter_balance.checked_sub(fee_amount).unwrap()
+        );
+        assert!(
+            read_balance(&shell.state, &native_token, &grantee)
+                .unwrap()
+                .is_zero()
+        );
+
+        // Check that the fees have been deducted from the allowance
+        let allowance = namada_sdk::account::fee_allowance(
+            &shell.state,
+            &granter,
+            &grantee,
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(
+            allowance.spend_limit,
+            spend_limit.checked_sub(fee_amount).unwrap()
+        );
+    }
+
+    // Test that a wrapper with a fee granter is rejected if the fees exceed
+    // the allowance of the signer
+    #[test]
+    fn test_fee_payment_exceeding_fee_allowance() {
+        let (mut shell, _, _, _) = setup();
+        let native_token = shell.state.in_mem().native_token.clone();
+        let granter = Address::from(&albert_keypair().to_public());
+        let keypair = gen_keypair();
+        let grantee = Address::from(&keypair.to_public());
+        let spend_limit = Amount::from_u64(1);
+
+        namada_sdk::account::grant_fee_allowance(
+            &mut shell.state,
+            &granter,
+            &grantee,
+            namada_sdk::account::FeeAllowance {
+                token: native_token.clone(),
+                spend_limit,
+                expiration: None,
+                allowed_code_hashes: None,
+            },
+        )
+        .unwrap();
+        let granter_balance =
+            read_balance(&shell.state, &native_token, &granter).unwrap();
+
+        let mut wrapper =
+            Tx::from_type(TxType::Wrapper(Box::new(WrapperTx::new(
+                Fee {
+                    amount_per_gas_unit: DenominatedAmount::native(100.into()),
+                    token: native_token.clone(),
+                },
+                keypair.ref_to(),
+                WRAPPER_GAS_LIMIT.into(),
+            ))));
+        wrapper.header.chain_id = shell.chain_id.clone();
+        wrapper.add_fee_granter(granter.clone());
+        wrapper.set_code(Code::new(TestWasms::TxNoOp.read_bytes(), None));
+        wrapper.set_data(Data::new("transaction data".as_bytes().to_owned()));
+        wrapper.add_section(Section::Authorization(Authorization::new(
+            wrapper.sechashes(),
+            [(0, keypair.clone())].into_iter().collect(),
+            None,
+        )));
+
+        let processed_tx = ProcessedTx {
+            tx: wrapper.to_bytes().into(),
+            result: TxResult {
+                code: ResultCode::Ok.into(),
+                info: "".into(),
+            },
+        };
+        let event = &shell
+            .finalize_block(FinalizeBlock {
+                txs: vec![processed_tx],
+                ..Default::default()
+            })
+            .expect("Test failed")[0];
+        assert_eq!(*event.kind(), APPLIED_TX);
+        let code = event.read_attribute::<CodeAttr>().expect("Test failed");
+        assert_eq!(code, ResultCode::InvalidTx);
+
+        // Check that neither the balance of the granter nor the allowance
+        // have been touched
+        assert_eq!(
+            read_balance(&shell.state, &native_token, &granter).unwrap(),
+            granter_balance
+        );
+        let allowance = namada_sdk::account::fee_allowance(
+            &shell.state,
+            &granter,
+            &grantee,
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(allowance.spend_limit, spend_limit);
+    }
+
     // Test that paying fees with a whitelisted token which is not the native
     // one is accepted
     #[test]
@@ -4024,7 +4287,7 @@ mod test_finalize_block {
         let signer_balance = namada_sdk::token::read_balance(
             &shell.state,
             &shell.state.in_mem().native_token,
-            &wrapper.header().wrapper().unwrap().fee_payer(),
+            &wrapper.fee_payer(&wrapper.header().wrapper().unwrap()),
         )
         .unwrap();
 
@@ -4063,13 +4326,141 @@ mod test_finalize_block {
         let new_signer_balance = namada_sdk::token::read_balance(
             &shell.state,
             &shell.state.in_mem().native_token,
-            &wrapper.header().wrapper().unwrap().fee_payer(),
+            &wrapper.fee_payer(&wrapper.header().wrapper().unwrap()),
+        )
+        .unwrap();
+        assert_eq!(
+            new_signer_balance,
+            signer_balance.checked_sub(fee_amount).unwrap()
+        )
+    }
+
+    // Test that with the fee market enabled, the base fee part of the fees is
+    // burned and only the tip is credited to the block proposer
+    #[test]
+    fn test_fee_market_burns_base_fee() {
+        let (mut shell, _, _, _) = setup();
+        let native_token = shell.state.in_mem().native_token.clone();
+
+        // Enable the fee market with a base fee of 1 raw unit of the native
+        // token
+        shell
+            .state
+            .write(
+                &namada_sdk::parameters::storage::get_gas_cost_key(),
+                BTreeMap::from([(native_token.clone(), Amount::from(1))]),
+            )
+            .unwrap();
+        shell
+            .state
+            .write(
+                &namada_sdk::parameters::storage::get_fee_market_key(),
+                FeeMarketParams {
+                    target_block_gas_fraction: Dec::new(5, 1).unwrap(),
+                    max_base_fee_change: Dec::new(125, 3).unwrap(),
+                    base_fee_destination: BaseFeeDestination::Burn,
+                },
+            )
+            .unwrap();
+        shell.commit();
+
+        let validator = shell.mode.get_validator_address().unwrap().to_owned();
+        let pos_params = read_pos_params(&shell.state).unwrap();
+        let consensus_key =
+            proof_of_stake::storage::validator_consensus_key_handle(&validator)
+                .get(&shell.state, Epoch::default(), &pos_params)
+                .unwrap()
+                .unwrap();
+        let proposer_address = HEXUPPER
+            .decode(consensus_key.tm_raw_hash().as_bytes())
+            .unwrap();
+
+        let proposer_balance = namada_sdk::token::read_balance(
+            &shell.state,
+            &native_token,
+            &validator,
+        )
+        .unwrap();
+        let signer_balance = namada_sdk::token::read_balance(
+            &shell.state,
+            &native_token,
+            &Address::from(&albert_keypair().ref_to()),
+        )
+        .unwrap();
+
+        let gas_limit = 5_000_000_u64;
+        let mut wrapper =
+            Tx::from_type(TxType::Wrapper(Box::new(WrapperTx::new(
+                Fee {
+                    amount_per_gas_unit: DenominatedAmount::native(3.into()),
+                    token: native_token.clone(),
+                },
+                namada_apps_lib::wallet::defaults::albert_keypair().ref_to(),
+                gas_limit.into(),
+            ))));
+        wrapper.header.chain_id = shell.chain_id.clone();
+        wrapper
+            .add_code(TestWasms::TxNoOp.read_bytes(), None)
+            .add_data("Transaction data");
+        wrapper.sign_wrapper(albert_keypair());
+
+        let processed_tx = ProcessedTx {
+            tx: wrapper.to_bytes().into(),
+            result: TxResult {
+                code: ResultCode::Ok.into(),
+                info: "".into(),
+            },
+        };
+
+        let event = &shell
+            .finalize_block(FinalizeBlock {
+                txs: vec![processed_tx],
+                proposer_address,
+                ..Default::default()
+            })
+            .expect("Test failed")[0];
+        assert_eq!(*event.kind(), APPLIED_TX);
+        let code = event.read_attribute::<CodeAttr>().expect("Test failed");
+        assert_eq!(code, ResultCode::Ok);
+
+        // The signer pays the whole fees but the proposer only gets the tip,
+        // the base fee is burned
+        let fee_amount = Amount::from(gas_limit.checked_mul(3).unwrap());
+        let tip = Amount::from(gas_limit.checked_mul(2).unwrap());
+        let new_signer_balance = namada_sdk::token::read_balance(
+            &shell.state,
+            &native_token,
+            &Address::from(&albert_keypair().ref_to()),
         )
         .unwrap();
         assert_eq!(
             new_signer_balance,
             signer_balance.checked_sub(fee_amount).unwrap()
+        );
+        let new_proposer_balance = namada_sdk::token::read_balance(
+            &shell.state,
+            &native_token,
+            &validator,
         )
+        .unwrap();
+        assert_eq!(
+            new_proposer_balance,
+            proposer_balance.checked_add(tip).unwrap()
+        );
+
+        // The block used less gas than the target, but the base fee can't
+        // fall below the minimum gas price
+        assert_eq!(
+            namada_sdk::parameters::read_base_fee(&shell.state, &native_token)
+                .unwrap(),
+            Some(Amount::from(1))
+        );
+        assert!(
+            shell
+                .state
+                .has_key(&namada_sdk::parameters::storage::get_base_fee_key())
+                .unwrap()
+        );
     }
 
     #[test]
@@ -5808,6 +6199,79 @@ mod test_finalize_block {
         }
     }
 
+    // Test that the parallel execution of the batches of a block, whose inner
+    // txs conflict or fail, produces the same results, events, gas and app
+    // hash as their sequential execution
+    #[test]
+    fn test_parallel_inner_txs_match_sequential() {
+        let (mut sequential, _, _, _) = setup();
+        let (mut parallel, _, _, _) = setup();
+        sequential.parallel_inner_txs = false;
+        parallel.parallel_inner_txs = true;
+
+        // The batches write the same keys, which are also read by the inner
+        // txs of the later batches
+        let (_, no_op) = mk_wrapper_tx(&sequential, &albert_keypair());
+        let txs = vec![
+            mk_tx_batch(
+                &sequential,
+                &wallet::defaults::bertha_keypair(),
+                false,
+                false,
+                false,
+            )
+            .1,
+            no_op,
+            mk_tx_batch(&sequential, &albert_keypair(), false, true, false).1,
+            mk_tx_batch(
+                &sequential,
+                &wallet::defaults::christel_keypair(),
+                true,
+                false,
+                false,
+            )
+            .1,
+        ];
+        let request = FinalizeBlock {
+            header: BlockHeader {
+                hash: Hash([0; 32]),
+                time: DateTimeUtc::unix_epoch(),
+                next_validators_hash: Hash([0; 32]),
+            },
+            txs,
+            ..Default::default()
+        };
+
+        let run_block = |shell: &mut TestShell| {
+            let events =
+                shell.finalize_block(request.clone()).expect("Test failed");
+            let tx_gas = shell.state.in_mem().commit_only_data.tx_gas.clone();
+            shell.commit();
+            (events, tx_gas, shell.state.in_mem().merkle_root())
+        };
+        let (sequential_events, sequential_gas, sequential_root) =
+            run_block(&mut sequential);
+        let (parallel_events, parallel_gas, parallel_root) =
+            run_block(&mut parallel);
+
+        let codes = sequential_events
+            .iter()
+            .filter_map(|event| event.read_attribute::<CodeAttr>().ok())
+            .collect::<Vec<_>>();
+        assert_eq!(codes, vec![ResultCode::Ok; 4]);
+        assert_eq!(sequential_gas.len(), 4);
+        assert_eq!(parallel_events, sequential_events);
+        assert_eq!(parallel_gas, sequential_gas);
+        assert_eq!(parallel_root, sequential_root);
+        for key in ["random_key_1", "random_key_2", "random_key_3"] {
+            let key = key.parse().unwrap();
+            assert_eq!(
+                parallel.state.read::<String>(&key).unwrap(),
+                sequential.state.read::<String>(&key).unwrap(),
+            );
+        }
+    }
+
     // Test a failing atomic batch with two successful txs and a failing one.
     // Verify that also the changes applied by the valid txs are dropped and
     // that the last transaction is never executed (batch short-circuit)
@@ -6,6 +6,7 @@
 //! (unless we can simply overwrite them in the next block).
 //! More info in <https://github.com/anoma/namada/issues/362>.
 pub mod block_alloc;
+pub mod changeset;
 mod finalize_block;
 mod init_chain;
 pub use init_chain::InitChainValidation;
@@ -23,14 +24,16 @@ mod stats;
 #[allow(dead_code)]
 pub mod testing;
 mod vote_extensions;
+pub mod wasm_cache;
 
 use std::cell::RefCell;
 use std::collections::BTreeSet;
-use std::num::NonZeroU64;
+use std::num::{NonZeroU64, NonZeroUsize};
 use std::path::{Path, PathBuf};
 #[allow(unused_imports)]
 use std::rc::Rc;
 
+use data_encoding::HEXLOWER;
 use namada_apps_lib::wallet::{self, ValidatorData, ValidatorKeys};
 use namada_sdk::address::Address;
 use namada_sdk::borsh::{BorshDeserialize, BorshSerializeExt};
@@ -41,6 +44,7 @@ use namada_sdk::eth_bridge::protocol::validation::ethereum_events::validate_eth_
 use namada_sdk::eth_bridge::protocol::validation::validator_set_update::validate_valset_upd_vext;
 use namada_sdk::eth_bridge::{EthBridgeQueries, EthereumOracleConfig};
 use namada_sdk::ethereum_events::EthereumEvent;
+use namada_sdk::events::Event;
 use namada_sdk::events::log::EventLog;
 use namada_sdk::gas::{Gas, GasMetering, TxGasMeter};
 use namada_sdk::hash::Hash;
@@ -51,10 +55,11 @@ use namada_sdk::proof_of_stake::storage::read_pos_params;
 use namada_sdk::proof_of_stake::types::{
     ConsensusValidator, ValidatorSetUpdate,
 };
+use namada_sdk::state::event_store::EventRetention;
 use namada_sdk::state::tx_queue::ExpiredTx;
 use namada_sdk::state::{
-    DB, DBIter, EPOCH_SWITCH_BLOCKS_DELAY, FullAccessState, Sha256Hasher,
-    StorageHasher, StorageRead, TempWlState, WlState,
+    DB, DBIter, DbError, EPOCH_SWITCH_BLOCKS_DELAY, FullAccessState,
+    Sha256Hasher, StorageHasher, StorageRead, TempWlState, WlState,
 };
 use namada_sdk::storage::{Key, TxIndex};
 use namada_sdk::tendermint::AppHash;
@@ -129,6 +134,8 @@ pub enum Error {
     ReplayAttempt(String),
     #[error("Error with snapshots: {0}")]
     Snapshot(std::io::Error),
+    #[error("The DB state is corrupted: {0}")]
+    CorruptedDb(String),
     #[error(
         "Received a finalize request for a block that was rejected by process \
          proposal"
@@ -183,6 +190,89 @@ pub fn rollback(config: config::Ledger) -> ShellResult<()> {
         .map_err(|e| Error::Storage(namada_sdk::state::Error::new(e)))
 }
 
+pub fn rollback_to_height(
+    config: config::Ledger,
+    target: BlockHeight,
+) -> ShellResult<()> {
+    let db_path = config.shell.db_dir(&config.chain_id);
+    let mut db = storage::PersistentDB::open(db_path, None);
+
+    // Check that Namada state can be rolled back before touching Tendermint
+    let last_block = db
+        .check_rollback_to_height(target)
+        .map_err(|e| Error::Storage(namada_sdk::state::Error::new(e)))?;
+    if last_block.height == target {
+        tracing::info!(
+            "Namada height already matches the target height, no need to \
+             rollback."
+        );
+        return Ok(());
+    }
+
+    // Rollback Tendermint state, only if it's in sync with Namada state
+    tracing::info!("Rollback Tendermint state to height {target}");
+    tendermint_node::rollback_to_height(
+        config.cometbft_dir(),
+        last_block.height,
+        target,
+    )
+    .map_err(Error::Tendermint)?;
+
+    // Rollback Namada state
+    tracing::info!("Rollback Namada state to height {target}");
+    db.rollback_to_height(target)
+        .map_err(|e| Error::Storage(namada_sdk::state::Error::new(e)))
+}
+
+/// Rebuild the Merkle tree of the last committed block from the subspace of a
+/// read-only DB and check it against the committed roots
+pub fn verify_db(config: config::Ledger) -> ShellResult<()> {
+    let db_path = config.shell.db_dir(&config.chain_id);
+    let db = storage::PersistentDB::open_read_only(db_path, None);
+
+    tracing::info!("Rebuilding the Merkle tree from the DB subspace");
+    let verification = namada_sdk::state::verify::verify_merkle_tree::<
+        _,
+        Sha256Hasher,
+    >(&db, is_key_diff_storable)?;
+    tracing::info!(
+        "Verified {} subspace keys at height {}. Committed root (app hash): \
+         {}, rebuilt root: {}",
+        verification.num_keys,
+        verification.height,
+        verification.committed_root,
+        verification.rebuilt_root,
+    );
+
+    let Some(mismatch) = verification.mismatch else {
+        tracing::info!("The DB state is intact");
+        return Ok(());
+    };
+    let key_ranges = match &mismatch.key_ranges {
+        Some(ranges) if !ranges.is_empty() => ranges
+            .iter()
+            .map(|(first, last)| {
+                if first == last {
+                    format!("{first}")
+                } else {
+                    format!("{first}..={last}")
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(", "),
+        _ => "unknown".to_string(),
+    };
+    Err(Error::CorruptedDb(format!(
+        "The {} store at height {} doesn't match the subspace. Committed \
+         root: {}, rebuilt root: {}, mismatching keys: {}",
+        mismatch.store_type,
+        verification.height,
+        mismatch.committed_root,
+        mismatch.rebuilt_root,
+        key_ranges,
+    )))
+}
+
 #[derive(Debug)]
 #[allow(dead_code, clippy::large_enum_variant)]
 pub(super) enum ShellMode {
@@ -350,13 +440,39 @@ pub enum MempoolTxType {
     RecheckTransaction,
 }
 
+/// A snapshot being synced from peers. Its chunks can be received in any
+/// order and are persisted until the snapshot is restored, so that the sync
+/// can be resumed after a restart.
 #[derive(Debug)]
 pub struct SnapshotSync {
-    pub next_chunk: u64,
     pub height: BlockHeight,
+    /// The trusted app hash of the snapshot's height, as provided by CometBFT
+    pub app_hash: Vec<u8>,
+    /// The hashes of the chunks of the snapshot
     pub expected: Vec<Hash>,
-    pub strikes: u64,
-    pub snapshot: std::fs::File,
+    /// The path of the persisted chunks
+    pub path: storage::SnapshotSyncPath,
+    /// The chunks received and persisted so far, including those persisted
+    /// before a restart
+    pub received: BTreeSet<u64>,
+    /// The chunks delivered by CometBFT since the snapshot was offered
+    pub delivered: BTreeSet<u64>,
+    /// The next chunk to apply to the snapshot data, which takes the chunks
+    /// in order
+    pub next_chunk: u64,
+    /// The number of invalid copies received of each chunk
+    pub strikes: HashMap<u64, u64>,
+    pub snapshot: SnapshotSyncData,
+}
+
+/// The data of a snapshot being synced, depending on its format
+#[derive(Debug)]
+pub enum SnapshotSyncData {
+    /// The chunks of a DB checkpoint tarball, restored once all of them are
+    /// received
+    Tarball(std::fs::File),
+    /// A key-value snapshot, restored chunk by chunk
+    KeyValue(Box<storage::KvSnapshotRestore>),
 }
 
 #[derive(Debug)]
@@ -386,14 +502,29 @@ where
     storage_read_past_height_limit: Option<u64>,
     /// Log of events emitted by `FinalizeBlock` ABCI calls.
     event_log: EventLog,
+    /// Events emitted by the last `FinalizeBlock` call, to be persisted in the
+    /// DB's event log on `Commit`.
+    block_events: Vec<Event>,
+    /// Taken from config `event_log_retention_blocks`. Determines how many
+    /// blocks' events are kept in the DB's event log.
+    event_retention: EventRetention,
     /// A migration that can be scheduled at a given block height
     pub scheduled_migration: Option<ScheduledMigration>,
     /// When set, indicates after how many blocks a new snapshot
     /// will be taken (counting from the first block)
     pub blocks_between_snapshots: Option<NonZeroU64>,
+    /// Taken from config `parallel_inner_txs`. When set, the inner txs of
+    /// different wrappers are executed optimistically in parallel.
+    pub parallel_inner_txs: bool,
+    /// When set, the storage changeset of every committed block is exported
+    /// to this directory
+    pub changeset_export_dir: Option<PathBuf>,
     /// Data for a node downloading and apply snapshots as part of
     /// the fast sync protocol.
     pub syncing: Option<SnapshotSync>,
+    /// Taken from config `wasm_cache_pre_warm_threads`. The number of threads
+    /// used to pre-warm the wasm compilation caches.
+    pub wasm_cache_pre_warm_threads: NonZeroUsize,
 }
 
 /// Storage key filter to store the diffs into the storage. Return `false` for
@@ -433,9 +564,15 @@ impl EthereumOracleChannels {
 
 impl Shell<crate::storage::PersistentDB, Sha256Hasher> {
     /// Restore the database with data fetched from the State Sync protocol.
-    pub fn restore_database_from_state_sync(&mut self) {
-        let Some(syncing) = self.syncing.as_mut() else {
-            return;
+    /// Fails if the restored state doesn't match the snapshot's height and
+    /// trusted app hash. A key-value snapshot is checked before the database
+    /// is replaced, in which case it is left untouched, while a tarball can
+    /// only be checked once restored.
+    pub fn restore_database_from_state_sync(
+        &mut self,
+    ) -> std::result::Result<(), DbError> {
+        let Some(syncing) = self.syncing.take() else {
+            return Ok(());
         };
 
         let db_block_cache_size_bytes = {
@@ -464,13 +601,39 @@ impl Shell<crate::storage::PersistentDB, Sha256Hasher> {
             ),
         );
 
-        self.state
-            .db_mut()
-            .restore_from((&db_cache, &mut syncing.snapshot))
-            .expect("Failed to restore state from snapshot");
+        match syncing.snapshot {
+            SnapshotSyncData::Tarball(mut snapshot) => {
+                self.state.db_mut().restore_from((
+                    &db_cache,
+                    storage::DbSnapshotSource::Tarball(&mut snapshot),
+                ))
+            }
+            SnapshotSyncData::KeyValue(restore) => {
+                // check the rebuilt merkle root before touching the db
+                let restored_dir = restore.finalize()?;
+                self.state.db_mut().restore_from((
+                    &db_cache,
+                    storage::DbSnapshotSource::Restored(&restored_dir),
+                ))
+            }
+        }
+        .expect("Failed to restore state from snapshot");
 
         // rebuild the in-memory state
         self.state.load_last_state();
+
+        let height = self.state.in_mem().get_last_block_height();
+        let root = self.state.in_mem().merkle_root();
+        if height != syncing.height || root.0.as_slice() != syncing.app_hash {
+            return Err(DbError::DBError(format!(
+                "The restored state at height {height} with Merkle root {} \
+                 doesn't match the snapshot at height {} with app hash {}",
+                root,
+                syncing.height,
+                HEXLOWER.encode(&syncing.app_hash),
+            )));
+        }
+        Ok(())
     }
 }
 
@@ -494,6 +657,13 @@ where
     ) -> Self {
         let chain_id = config.chain_id;
         let db_path = config.shell.db_dir(&chain_id);
+        let changeset_export_dir = config.shell.changeset_export_dir(&chain_id);
+        let vp_wasm_cache_dir = config.shell.vp_wasm_cache_dir(&chain_id);
+        let tx_wasm_cache_dir = config.shell.tx_wasm_cache_dir(&chain_id);
+        let wasm_cache_pre_warm_threads = config
+            .shell
+            .wasm_cache_pre_warm_threads
+            .unwrap_or_else(wasm_cache::default_pre_warm_threads);
         let base_dir = config.shell.base_dir;
         let mode = config.shell.tendermint_mode;
         let storage_read_past_height_limit =
@@ -527,7 +697,7 @@ where
         };
 
         // load last state from storage
-        let state = FullAccessState::open(
+        let mut state = FullAccessState::open(
             db_path,
             db_cache,
             chain_id.clone(),
@@ -535,10 +705,9 @@ where
             config.shell.storage_read_past_height_limit,
             is_key_diff_storable,
         );
-        let vp_wasm_cache_dir =
-            base_dir.join(chain_id.as_str()).join("vp_wasm_cache");
-        let tx_wasm_cache_dir =
-            base_dir.join(chain_id.as_str()).join("tx_wasm_cache");
+        if let Some(num_blocks) = config.shell.rollback_window_blocks {
+            state.in_mem_mut().rollback_window_blocks = num_blocks;
+        }
         // load in keys and address from wallet if mode is set to `Validator`
         let mode = match mode {
             TendermintMode::Validator => {
@@ -680,11 +849,27 @@ where
             storage_read_past_height_limit,
             // TODO(namada#3237): config event log params
             event_log: EventLog::default(),
+            block_events: vec![],
+            event_retention: config
+                .shell
+                .event_log_retention_blocks
+                .map_or(EventRetention::Archive, |num_blocks| {
+                    EventRetention::Blocks(num_blocks.get())
+                }),
             scheduled_migration,
             blocks_between_snapshots: config.shell.blocks_between_snapshots,
+            parallel_inner_txs: config.shell.parallel_inner_txs,
+            changeset_export_dir,
             syncing: None,
+            wasm_cache_pre_warm_threads,
         };
         shell.update_eth_oracle(&Default::default());
+        // NB: before the chain is initialized, the wasm get pre-compiled in
+        // `init_chain`
+        #[cfg(not(any(test, fuzzing)))]
+        if shell.state.in_mem().last_block.is_some() {
+            shell.pre_warm_wasm_cache();
+        }
         shell
     }
 
@@ -818,8 +1003,11 @@ where
             _ => None,
         };
 
+        let wasm_allowlists_changed = self.wasm_allowlists_changed();
+
+        let block_events = std::mem::take(&mut self.block_events);
         self.state
-            .commit_block()
+            .commit_block_with_events(&block_events, self.event_retention)
             .expect("Encountered a storage error while committing a block");
 
         if let Some(migration) = migration {
@@ -829,6 +1017,11 @@ where
                 .expect("Must update merkle tree after migration");
         }
 
+        self.export_changeset();
+        if wasm_allowlists_changed {
+            self.pre_warm_wasm_cache();
+        }
+
         let merkle_root = self.state.in_mem().merkle_root();
 
         tracing::info!(
@@ -1511,8 +1704,8 @@ where
     CA: 'static + WasmCacheAccess + Sync,
 {
     let minimum_gas_price =
-        parameters::read_gas_cost(shell_params.state, &wrapper.fee.token)
-            .expect("Must be able to read gas cost parameter")
+        parameters::read_base_fee(shell_params.state, &wrapper.fee.token)
+            .expect("Must be able to read the base fee")
             .ok_or(Error::TxApply(protocol::Error::FeeError(format!(
                 "The provided {} token is not allowed for fee payment",
                 wrapper.fee.token
@@ -3073,16 +3266,28 @@ mod shell_tests {
         let new_root = shell.state.in_mem().merkle_root();
         assert_ne!(new_root, original_root);
 
-        shell.restore_database_from_state_sync();
+        shell
+            .restore_database_from_state_sync()
+            .expect("Test failed");
         assert_eq!(shell.state.in_mem().merkle_root(), new_root,);
         shell.syncing = Some(SnapshotSync {
-            next_chunk: 0,
             height: BlockHeight::first(),
             expected: vec![],
-            strikes: 0,
-            snapshot,
+            path: storage::SnapshotSyncPath(
+                config.shell.base_dir.clone(),
+                BlockHeight::first(),
+                Hash::zero(),
+            ),
+            app_hash: original_root.0.to_vec(),
+            received: Default::default(),
+            delivered: Default::default(),
+            next_chunk: 0,
+            strikes: HashMap::new(),
+            snapshot: SnapshotSyncData::Tarball(snapshot),
         });
-        shell.restore_database_from_state_sync();
+        shell
+            .restore_database_from_state_sync()
+            .expect("Test failed");
         assert_eq!(shell.state.in_mem().merkle_root(), original_root,);
     }
 
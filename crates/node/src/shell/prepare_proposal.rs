@@ -1,9 +1,13 @@
 //! Implementation of the [`RequestPrepareProposal`] ABCI++ method for the Shell
 
 use std::cell::RefCell;
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
 
 use namada_sdk::address::Address;
+use namada_sdk::collections::HashMap;
 use namada_sdk::gas::TxGasMeter;
+use namada_sdk::hash::Hash;
 use namada_sdk::key::tm_raw_hash_to_string;
 use namada_sdk::parameters::get_gas_scale;
 use namada_sdk::proof_of_stake::storage::find_validator_by_raw_hash;
@@ -126,30 +130,72 @@ where
         let mut vp_wasm_cache = self.vp_wasm_cache.clone();
         let mut tx_wasm_cache = self.tx_wasm_cache.clone();
 
-        let txs = txs
-            .iter()
+        let max_block_gas =
+            namada_sdk::parameters::get_max_block_gas(&self.state)
+                .expect("Must be able to read the max block gas");
+        let max_signer_gas = max_block_gas
+            .checked_div(MAX_SIGNER_BLOCK_GAS_DIVISOR)
+            .unwrap_or(max_block_gas);
+        let mut signers_gas: HashMap<Address, u64> = HashMap::new();
+
+        let txs = self
+            .order_wrappers_by_fee(txs, proposer_local_config)
+            .into_iter()
             .enumerate()
-            .filter_map(|(tx_index, tx_bytes)| {
-                let result = validate_wrapper_bytes(
-                    tx_bytes,
-                    &TxIndex::must_from_usize(tx_index),
-                    block_time,
-                    block_proposer,
-                    proposer_local_config,
-                    &mut temp_state,
-                    &mut vp_wasm_cache,
-                    &mut tx_wasm_cache
-                );
-                match result {
-                    Ok(gas) => {
-                        temp_state.write_log_mut().commit_batch_and_current_tx();
-                        Some((tx_bytes.to_owned(), gas))
-                    },
-                    Err(()) => {
-                        temp_state.write_log_mut().drop_batch();
-                        None
+            .filter_map(|(tx_index, alternatives)| {
+                // Fall back to the wrappers of the same inner txs paying a
+                // lower fee if the preferred one can't be included
+                alternatives.into_iter().find_map(|candidate| {
+                    let WrapperCandidate {
+                        tx_bytes,
+                        signer,
+                        gas_limit,
+                        ..
+                    } = candidate;
+                    let signer_gas = signers_gas
+                        .get(&signer)
+                        .copied()
+                        .unwrap_or_default();
+                    if signer_gas
+                        .checked_add(gas_limit)
+                        .is_none_or(|gas| gas > max_signer_gas)
+                    {
+                        tracing::debug!(
+                            ?tx_bytes,
+                            %signer,
+                            proposal_height =
+                                ?self.get_current_decision_height(),
+                            "Dropping wrapper tx of a signer that would \
+                             exceed its gas cap from the current proposal",
+                        );
+                        return None;
                     }
-                }
+                    let result = validate_wrapper_bytes(
+                        tx_bytes,
+                        &TxIndex::must_from_usize(tx_index),
+                        block_time,
+                        block_proposer,
+                        proposer_local_config,
+                        &mut temp_state,
+                        &mut vp_wasm_cache,
+                        &mut tx_wasm_cache,
+                    );
+                    match result {
+                        Ok(gas) => {
+                            temp_state
+                                .write_log_mut()
+                                .commit_batch_and_current_tx();
+                            let signer_gas =
+                                signers_gas.entry(signer).or_default();
+                            *signer_gas = signer_gas.saturating_add(gas);
+                            Some((tx_bytes.to_owned(), gas))
+                        }
+                        Err(()) => {
+                            temp_state.write_log_mut().drop_batch();
+                            None
+                        }
+                    }
+                })
             })
             .take_while(|(tx_bytes, tx_gas)| {
                 alloc.try_alloc(BlockResources::new(&tx_bytes[..], tx_gas.to_owned()))
@@ -188,6 +234,86 @@ where
         (txs, alloc)
     }
 
+    /// Order the wrapper txs retrieved from CometBFT's mempool by the fee
+    /// they pay per gas unit, in decreasing order. Wrappers paying the same
+    /// fee keep their mempool order, while the wrappers whose fees can't be
+    /// converted into the native token are ranked last.
+    ///
+    /// Wrappers of the same inner txs can't all be executed, so they are
+    /// grouped together and only one of them may be included. A later wrapper
+    /// of the same inner txs is only preferred over an earlier one if it pays
+    /// a higher fee, while the others are kept as fallbacks in case the
+    /// preferred one turns out to be invalid. Txs that aren't wrappers are
+    /// dropped.
+    fn order_wrappers_by_fee<'tx>(
+        &self,
+        txs: &'tx [TxBytes],
+        proposer_local_config: Option<&ValidatorLocalConfig>,
+    ) -> Vec<Vec<WrapperCandidate<'tx>>> {
+        // The minimum gas prices used to convert the fees into the native
+        // token
+        let gas_prices: BTreeMap<Address, Amount> = match proposer_local_config
+        {
+            Some(config) => config
+                .accepted_gas_tokens
+                .iter()
+                .map(|(token, price)| (token.clone(), *price))
+                .collect(),
+            None => namada_sdk::parameters::read_base_fees(&self.state)
+                .unwrap_or_default(),
+        };
+        let native_token = &self.state.in_mem().native_token;
+
+        let mut candidates: HashMap<Hash, Vec<WrapperCandidate<'tx>>> =
+            HashMap::new();
+        for (mempool_index, tx_bytes) in txs.iter().enumerate() {
+            let Ok(tx) = Tx::try_from_bytes(tx_bytes) else {
+                continue;
+            };
+            let Some(wrapper) = tx.header.wrapper() else {
+                continue;
+            };
+            let Ok(fee_per_gas) = namada_sdk::token::denom_to_amount(
+                wrapper.fee.amount_per_gas_unit,
+                &wrapper.fee.token,
+                &self.state,
+            ) else {
+                continue;
+            };
+            let candidate = WrapperCandidate {
+                tx_bytes,
+                signer: wrapper.signer(),
+                gas_limit: u64::from(wrapper.gas_limit),
+                fee_per_gas: native_fee_per_gas(
+                    fee_per_gas,
+                    &wrapper.fee.token,
+                    native_token,
+                    &gas_prices,
+                ),
+                mempool_index,
+            };
+            candidates
+                .entry(tx.raw_header_hash())
+                .or_default()
+                .push(candidate);
+        }
+
+        let priority = |candidate: &WrapperCandidate<'tx>| {
+            (Reverse(candidate.fee_per_gas), candidate.mempool_index)
+        };
+        let mut candidates: Vec<_> = candidates
+            .into_values()
+            .map(|mut alternatives| {
+                alternatives.sort_by_key(priority);
+                alternatives
+            })
+            .collect();
+        // The groups are non-empty and ordered by their preferred wrapper
+        candidates
+            .sort_by_key(|alternatives| alternatives.first().map(priority));
+        candidates
+    }
+
     /// Allocate an initial set of protocol txs and advance to the
     /// next allocation state.
     fn build_protocol_tx_with_normal_txs(
@@ -272,6 +398,48 @@ where
     }
 }
 
+/// The wrappers of a single signer are not included in a proposal if their
+/// gas limits exceed this fraction of the max block gas, such that a single
+/// account can't fill the block
+const MAX_SIGNER_BLOCK_GAS_DIVISOR: u64 = 4;
+
+/// A wrapper tx from the mempool, candidate for inclusion in a proposal
+struct WrapperCandidate<'tx> {
+    /// The bytes of the tx
+    tx_bytes: &'tx TxBytes,
+    /// The signer of the wrapper
+    signer: Address,
+    /// The gas limit of the wrapper
+    gas_limit: u64,
+    /// The fee paid per gas unit, converted into the native token, or
+    /// [`None`] if there's no known conversion rate
+    fee_per_gas: Option<Amount>,
+    /// The position of the tx in the mempool
+    mempool_index: usize,
+}
+
+/// Convert a fee paid per gas unit in the given token into the native token,
+/// using the ratio between the minimum gas prices of the native and the fee
+/// tokens as the conversion rate. Returns [`None`] if the rate is unknown.
+fn native_fee_per_gas(
+    fee_per_gas: Amount,
+    fee_token: &Address,
+    native_token: &Address,
+    gas_prices: &BTreeMap<Address, Amount>,
+) -> Option<Amount> {
+    if fee_token == native_token {
+        return Some(fee_per_gas);
+    }
+    gas_prices
+        .get(native_token)
+        .zip(gas_prices.get(fee_token))
+        .and_then(|(native_price, token_price)| {
+            fee_per_gas
+                .checked_mul(*native_price)?
+                .checked_div(*token_price)
+        })
+}
+
 // Validity checks on a wrapper tx
 #[allow(clippy::too_many_arguments)]
 fn validate_wrapper_bytes<D, H, CA>(
@@ -367,8 +535,8 @@ where
 {
     #[cfg(not(fuzzing))]
     let consensus_min_gas_price =
-        namada_sdk::parameters::read_gas_cost(temp_state, fee_token)
-            .expect("Must be able to read gas cost parameter")
+        namada_sdk::parameters::read_base_fee(temp_state, fee_token)
+            .expect("Must be able to read the base fee")
             .ok_or_else(|| {
                 Error::TxApply(protocol::Error::FeeError(format!(
                     "The provided {fee_token} token is not allowed for fee \
@@ -425,7 +593,7 @@ mod test_prepare_proposal {
     use namada_apps_lib::wallet;
     use namada_replay_protection as replay_protection;
     use namada_sdk::ethereum_events::EthereumEvent;
-    use namada_sdk::key::RefTo;
+    use namada_sdk::key::{RefTo, common};
     use namada_sdk::proof_of_stake::Epoch;
     use namada_sdk::proof_of_stake::storage::{
         consensus_validator_set_handle,
@@ -1510,4 +1678,259 @@ mod test_prepare_proposal {
 
         assert_eq!(computed_min_gas_price, consensus_min_gas_price);
     }
+
+    /// Build a signed wrapper of a tx with the given data
+    fn signed_wrapper(
+        shell: &TestShell,
+        keypair: common::SecretKey,
+        amount_per_gas_unit: u64,
+        gas_limit: u64,
+        data: &str,
+    ) -> Tx {
+        let mut wrapper =
+            Tx::from_type(TxType::Wrapper(Box::new(WrapperTx::new(
+                Fee {
+                    amount_per_gas_unit: DenominatedAmount::native(
+                        amount_per_gas_unit.into(),
+                    ),
+                    token: shell.state.in_mem().native_token.clone(),
+                },
+                keypair.ref_to(),
+                gas_limit.into(),
+            ))));
+        wrapper.header.chain_id = shell.chain_id.clone();
+        wrapper.set_code(Code::new("wasm_code".as_bytes().to_owned(), None));
+        wrapper.set_data(Data::new(data.as_bytes().to_owned()));
+        wrapper.sign_wrapper(keypair);
+        wrapper
+    }
+
+    /// Test that the wrapper txs are included in the block by decreasing fee
+    /// per gas unit, regardless of their mempool order
+    #[test]
+    fn test_fee_priority_ordering() {
+        let (shell, _recv, _, _) = test_utils::setup();
+
+        let low_fee = signed_wrapper(
+            &shell,
+            wallet::defaults::daewon_keypair(),
+            100,
+            GAS_LIMIT,
+            "low fee",
+        );
+        let high_fee = signed_wrapper(
+            &shell,
+            wallet::defaults::albert_keypair(),
+            200,
+            GAS_LIMIT,
+            "high fee",
+        );
+
+        let req = RequestPrepareProposal {
+            txs: vec![low_fee.to_bytes().into(), high_fee.to_bytes().into()],
+            ..Default::default()
+        };
+        let received_txs = shell.prepare_proposal(req).txs;
+        assert_eq!(
+            received_txs,
+            vec![
+                TxBytes::from(high_fee.to_bytes()),
+                low_fee.to_bytes().into()
+            ]
+        );
+    }
+
+    /// Test that a later wrapper of the same inner tx displaces the earlier
+    /// one only if it pays a higher fee
+    #[test]
+    fn test_fee_replacement() {
+        let (shell, _recv, _, _) = test_utils::setup();
+
+        let keypair = wallet::defaults::daewon_keypair();
+        let keypair_2 = wallet::defaults::albert_keypair();
+        let mut wrapper =
+            signed_wrapper(&shell, keypair, 100, GAS_LIMIT, "inner tx");
+        let replace_with_fee = |wrapper: &Tx, amount_per_gas_unit: u64| {
+            let mut new_wrapper = wrapper.clone();
+            new_wrapper.update_header(TxType::Wrapper(Box::new(
+                WrapperTx::new(
+                    Fee {
+                        amount_per_gas_unit: DenominatedAmount::native(
+                            amount_per_gas_unit.into(),
+                        ),
+                        token: shell.state.in_mem().native_token.clone(),
+                    },
+                    keypair_2.ref_to(),
+                    GAS_LIMIT.into(),
+                ),
+            )));
+            new_wrapper.sign_wrapper(keypair_2.clone());
+            new_wrapper
+        };
+
+        // A replacement paying a higher fee displaces the earlier wrapper
+        let new_wrapper = replace_with_fee(&wrapper, 200);
+        let req = RequestPrepareProposal {
+            txs: vec![wrapper.to_bytes().into(), new_wrapper.to_bytes().into()],
+            ..Default::default()
+        };
+        let received_txs = shell.prepare_proposal(req).txs;
+        assert_eq!(received_txs, vec![TxBytes::from(new_wrapper.to_bytes())]);
+
+        // A replacement paying a lower fee is dropped
+        wrapper = replace_with_fee(&wrapper, 300);
+        let new_wrapper = replace_with_fee(&wrapper, 200);
+        let req = RequestPrepareProposal {
+            txs: vec![wrapper.to_bytes().into(), new_wrapper.to_bytes().into()],
+            ..Default::default()
+        };
+        let received_txs = shell.prepare_proposal(req).txs;
+        assert_eq!(received_txs, vec![TxBytes::from(wrapper.to_bytes())]);
+    }
+
+    /// Test that a wrapper of the same inner tx paying a lower fee is included
+    /// in place of a replacement that turns out to be invalid
+    #[test]
+    fn test_fee_replacement_fallback() {
+        let (shell, _recv, _, _) = test_utils::setup();
+
+        let wrapper = signed_wrapper(
+            &shell,
+            wallet::defaults::daewon_keypair(),
+            100,
+            GAS_LIMIT,
+            "inner tx",
+        );
+        // The signer of the replacement can't pay for the fees
+        let keypair = gen_keypair();
+        let mut replacement = wrapper.clone();
+        replacement.update_header(TxType::Wrapper(Box::new(WrapperTx::new(
+            Fee {
+                amount_per_gas_unit: DenominatedAmount::native(200.into()),
+                token: shell.state.in_mem().native_token.clone(),
+            },
+            keypair.ref_to(),
+            GAS_LIMIT.into(),
+        ))));
+        replacement.sign_wrapper(keypair);
+
+        let req = RequestPrepareProposal {
+            txs: vec![wrapper.to_bytes().into(), replacement.to_bytes().into()],
+            ..Default::default()
+        };
+        let received_txs = shell.prepare_proposal(req).txs;
+        assert_eq!(received_txs, vec![TxBytes::from(wrapper.to_bytes())]);
+    }
+
+    /// Test that the wrapper txs of a signer that reached its share of the
+    /// block gas are not included in the block
+    #[test]
+    fn test_signer_gas_cap() {
+        let (shell, _recv, _, _) = test_utils::setup();
+
+        let max_signer_gas =
+            namada_sdk::parameters::get_max_block_gas(&shell.state)
+                .unwrap()
+                .checked_div(MAX_SIGNER_BLOCK_GAS_DIVISOR)
+                .unwrap();
+        let keypair = wallet::defaults::albert_keypair();
+        let mut txs: Vec<TxBytes> = (0..3)
+            .map(|i| {
+                signed_wrapper(
+                    &shell,
+                    keypair.clone(),
+                    200,
+                    max_signer_gas,
+                    &format!("capped tx {i}"),
+                )
+                .to_bytes()
+                .into()
+            })
+            .collect();
+        // A wrapper of another signer paying a lower fee is still included
+        let other_signer = signed_wrapper(
+            &shell,
+            wallet::defaults::bertha_keypair(),
+            100,
+            GAS_LIMIT,
+            "other signer",
+        );
+        txs.push(other_signer.to_bytes().into());
+
+        let req = RequestPrepareProposal {
+            txs: txs.clone(),
+            ..Default::default()
+        };
+        let received_txs = shell.prepare_proposal(req).txs;
+        assert_eq!(received_txs, vec![txs[0].clone(), txs[3].clone()]);
+
+        // A wrapper is not included if its gas limit would exceed the cap of
+        // its signer, even if the signer is still below the cap
+        let below_cap = signed_wrapper(
+            &shell,
+            keypair.clone(),
+            300,
+            GAS_LIMIT,
+            "below cap",
+        );
+        let exceeding_cap = signed_wrapper(
+            &shell,
+            keypair,
+            200,
+            max_signer_gas,
+            "exceeding cap",
+        );
+        let req = RequestPrepareProposal {
+            txs: vec![
+                below_cap.to_bytes().into(),
+                exceeding_cap.to_bytes().into(),
+            ],
+            ..Default::default()
+        };
+        let received_txs = shell.prepare_proposal(req).txs;
+        assert_eq!(received_txs, vec![TxBytes::from(below_cap.to_bytes())]);
+    }
+
+    /// Test the conversion of the fees into the native token
+    #[test]
+    fn test_native_fee_per_gas() {
+        let native_token = address::testing::nam();
+        let btc = address::testing::btc();
+        let gas_prices = BTreeMap::from([
+            (native_token.clone(), Amount::from(100)),
+            (btc.clone(), Amount::from(2)),
+            (address::testing::eth(), Amount::zero()),
+        ]);
+
+        assert_eq!(
+            native_fee_per_gas(
+                Amount::from(3),
+                &native_token,
+                &native_token,
+                &gas_prices
+            ),
+            Some(Amount::from(3))
+        );
+        assert_eq!(
+            native_fee_per_gas(
+                Amount::from(3),
+                &btc,
+                &native_token,
+                &gas_prices
+            ),
+            Some(Amount::from(150))
+        );
+        // The fees can't be converted if the rate is unknown
+        for token in [address::testing::eth(), address::testing::dot()] {
+            assert_eq!(
+                native_fee_per_gas(
+                    Amount::from(3),
+                    &token,
+                    &native_token,
+                    &gas_prices
+                ),
+                None
+            );
+        }
+    }
 }
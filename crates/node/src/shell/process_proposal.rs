@@ -577,8 +577,8 @@ where
     CA: 'static + WasmCacheAccess + Sync,
 {
     let minimum_gas_price =
-        parameters::read_gas_cost(shell_params.state, &wrapper.fee.token)
-            .expect("Must be able to read gas cost parameter")
+        parameters::read_base_fee(shell_params.state, &wrapper.fee.token)
+            .expect("Must be able to read the base fee")
             .ok_or(Error::TxApply(protocol::Error::FeeError(format!(
                 "The provided {} token is not allowed for fee payment",
                 wrapper.fee.token
@@ -601,6 +601,7 @@ mod test_process_proposal {
     use namada_apps_lib::wallet;
     use namada_replay_protection as replay_protection;
     use namada_sdk::address;
+    use namada_sdk::dec::Dec;
     use namada_sdk::eth_bridge::storage::eth_bridge_queries::{
         EthBridgeQueries, is_bridge_comptime_enabled,
     };
@@ -1707,6 +1708,71 @@ mod test_process_proposal {
         }
     }
 
+    // Check that, with the fee market enabled, a wrapper paying the minimum gas
+    // price but less than the base fee causes a block rejection
+    #[test]
+    fn test_fee_below_base_fee() {
+        let (mut shell, _recv, _, _) = test_utils::setup();
+        let native_token = shell.state.in_mem().native_token.clone();
+        let minimum_gas_price =
+            parameters::read_gas_cost(&shell.state, &native_token)
+                .unwrap()
+                .unwrap();
+        shell
+            .state
+            .write(
+                &parameters::storage::get_fee_market_key(),
+                parameters::FeeMarketParams {
+                    target_block_gas_fraction: Dec::new(5, 1).unwrap(),
+                    max_base_fee_change: Dec::new(125, 3).unwrap(),
+                    base_fee_destination: parameters::BaseFeeDestination::Pgf,
+                },
+            )
+            .unwrap();
+        shell
+            .state
+            .write(
+                &parameters::storage::get_base_fee_key(),
+                BTreeMap::from([(
+                    native_token.clone(),
+                    minimum_gas_price.checked_mul(2).unwrap(),
+                )]),
+            )
+            .unwrap();
+        shell.commit();
+
+        let mut wrapper =
+            Tx::from_type(TxType::Wrapper(Box::new(WrapperTx::new(
+                Fee {
+                    amount_per_gas_unit: DenominatedAmount::native(
+                        minimum_gas_price,
+                    ),
+                    token: native_token,
+                },
+                namada_apps_lib::wallet::defaults::albert_keypair().ref_to(),
+                GAS_LIMIT.into(),
+            ))));
+        wrapper.header.chain_id = shell.chain_id.clone();
+        wrapper.set_code(Code::new("wasm code".as_bytes().to_owned(), None));
+        wrapper.set_data(Data::new("transaction data".as_bytes().to_owned()));
+        wrapper
+            .sign_wrapper(namada_apps_lib::wallet::defaults::albert_keypair());
+
+        // Run validation
+        let request = ProcessProposal {
+            txs: vec![wrapper.to_bytes()],
+        };
+        match shell.process_proposal(request) {
+            Ok(_) => panic!("Test failed"),
+            Err(TestError::RejectProposal(response)) => {
+                assert_eq!(
+                    response[0].result.code,
+                    u32::from(ResultCode::FeeError)
+                );
+            }
+        }
+    }
+
     // Check that a wrapper transactions whose fees cannot be paid causes a
     // block rejection
     #[test]
@@ -1,9 +1,10 @@
 //! Shell methods for querying state
 
-use namada_sdk::queries::{RPC, RequestCtx, ResponseQuery};
+use namada_sdk::queries::{RequestCtx, ResponseQuery};
+use namada_sdk::state::HistoricState;
 
 use super::*;
-use crate::dry_run_tx;
+use crate::{dry_run_tx, is_dry_run_tx_path};
 
 impl<D, H> Shell<D, H>
 where
@@ -16,7 +17,7 @@ where
     /// INVARIANT: This method must be stateless.
     pub fn query(&self, query: request::Query) -> response::Query {
         // Invoke the root RPC handler - returns borsh-encoded data on success
-        let result = if query.path == RPC.shell().dry_run_tx_path() {
+        let result = if is_dry_run_tx_path(&query.path) {
             dry_run_tx(
                 // This is safe as neither the inner `db` nor `in_mem` are
                 // actually mutable, only the `write_log` which is owned by
@@ -30,7 +31,7 @@ where
             )
         } else {
             let ctx = RequestCtx {
-                state: self.state.read_only(),
+                state: HistoricState::new(self.state.read_only()),
                 event_log: self.event_log(),
                 vp_wasm_cache: self.vp_wasm_cache.read_only(),
                 tx_wasm_cache: self.tx_wasm_cache.read_only(),
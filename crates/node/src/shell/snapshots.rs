@@ -1,14 +1,18 @@
+use std::collections::BTreeSet;
 use std::io::Write;
 
 use namada_sdk::arith::checked;
 use namada_sdk::borsh::{BorshDeserialize, BorshSerializeExt};
+use namada_sdk::collections::HashMap;
 use namada_sdk::hash::{Hash, Sha256Hasher};
-use namada_sdk::state::{BlockHeight, StorageRead};
+use namada_sdk::state::{BlockHeight, DB, DbError, StorageRead};
 
-use super::SnapshotSync;
+use super::{SnapshotSync, SnapshotSyncData};
 use crate::shell::Shell;
 use crate::storage;
-use crate::storage::{DbSnapshot, DbSnapshotMeta};
+use crate::storage::{
+    DbSnapshot, DbSnapshotMeta, KvSnapshotRestore, SnapshotSyncPath,
+};
 use crate::tendermint::abci::types::Snapshot;
 use crate::tendermint::abci::{
     ApplySnapshotChunkResult, request as tm_request, response as tm_response,
@@ -42,10 +46,11 @@ impl Shell<storage::PersistentDB, Sha256Hasher> {
                         height,
                         chunk_hashes,
                         root_hash,
+                        format,
                     } = result?;
                     std::io::Result::Ok(Snapshot {
                         height: u32::try_from(height.0).unwrap().into(),
-                        format: DbSnapshot::FORMAT_MAGIC,
+                        format,
                         #[allow(clippy::cast_possible_truncation)]
                         chunks: chunk_hashes.len() as u32,
                         hash: root_hash.0.to_vec().into(),
@@ -102,7 +107,9 @@ impl Shell<storage::PersistentDB, Sha256Hasher> {
         &mut self,
         req: tm_request::OfferSnapshot,
     ) -> tm_response::OfferSnapshot {
-        if req.snapshot.format != DbSnapshot::FORMAT_MAGIC {
+        if req.snapshot.format != DbSnapshot::FORMAT_MAGIC
+            && req.snapshot.format != DbSnapshot::KV_FORMAT_MAGIC
+        {
             tracing::debug!(
                 format = req.snapshot.format,
                 "Received snapshot with an incompatible format"
@@ -115,21 +122,7 @@ impl Shell<storage::PersistentDB, Sha256Hasher> {
                 if self.state.get_block_height().unwrap_or_default().0
                     < u64::from(req.snapshot.height)
                 {
-                    let Ok(chunks) =
-                        Vec::<Hash>::try_from_slice(&req.snapshot.metadata)
-                    else {
-                        return tm_response::OfferSnapshot::Reject;
-                    };
-                    self.syncing = Some(SnapshotSync {
-                        next_chunk: 0,
-                        height: u64::from(req.snapshot.height).into(),
-                        expected: chunks,
-                        strikes: 0,
-                        snapshot: tempfile::tempfile()
-                            .expect("Failed to create snapshot temp file"),
-                    });
-                    tracing::info!("Accepting snapshot offer");
-                    tm_response::OfferSnapshot::Accept
+                    self.start_snapshot_sync(&req)
                 } else {
                     tracing::info!("Rejecting snapshot offer");
                     tm_response::OfferSnapshot::Reject
@@ -137,22 +130,7 @@ impl Shell<storage::PersistentDB, Sha256Hasher> {
             }
             Some(snapshot_sync) => {
                 if snapshot_sync.height.0 < u64::from(req.snapshot.height) {
-                    let Ok(chunks) =
-                        Vec::<Hash>::try_from_slice(&req.snapshot.metadata)
-                    else {
-                        tracing::info!("Rejecting snapshot offer");
-                        return tm_response::OfferSnapshot::Reject;
-                    };
-                    self.syncing = Some(SnapshotSync {
-                        next_chunk: 0,
-                        height: u64::from(req.snapshot.height).into(),
-                        expected: chunks,
-                        strikes: 0,
-                        snapshot: tempfile::tempfile()
-                            .expect("Failed to create snapshot temp file"),
-                    });
-                    tracing::info!("Accepting snapshot offer");
-                    tm_response::OfferSnapshot::Accept
+                    self.start_snapshot_sync(&req)
                 } else {
                     tracing::info!("Rejecting snapshot offer");
                     tm_response::OfferSnapshot::Reject
@@ -161,7 +139,118 @@ impl Shell<storage::PersistentDB, Sha256Hasher> {
         }
     }
 
-    /// Write a snapshot chunk to the database
+    /// Start syncing the offered snapshot, replacing any sync in progress.
+    /// The chunks of the snapshot persisted by a previous sync are reused.
+    fn start_snapshot_sync(
+        &mut self,
+        req: &tm_request::OfferSnapshot,
+    ) -> tm_response::OfferSnapshot {
+        let Ok(chunks) = Vec::<Hash>::try_from_slice(&req.snapshot.metadata)
+        else {
+            tracing::info!("Rejecting snapshot offer");
+            return tm_response::OfferSnapshot::Reject;
+        };
+        let Ok(snapshot_hash) = Hash::try_from(req.snapshot.hash.as_ref())
+        else {
+            tracing::info!("Rejecting snapshot offer");
+            return tm_response::OfferSnapshot::Reject;
+        };
+        // close the DB of a key-value snapshot being restored, if any, as
+        // its directory is reused
+        self.syncing = None;
+        let height = BlockHeight(u64::from(req.snapshot.height));
+        let path =
+            SnapshotSyncPath(self.base_dir.clone(), height, snapshot_hash);
+        // only the chunks of the last offered snapshot are kept
+        if let Err(err) = path
+            .remove_others()
+            .and_then(|()| std::fs::create_dir_all(path.base()))
+        {
+            tracing::error!(
+                error = %err,
+                "Failed to prepare the directory of the snapshot chunks"
+            );
+            return tm_response::OfferSnapshot::Reject;
+        }
+        let snapshot = if req.snapshot.format == DbSnapshot::KV_FORMAT_MAGIC {
+            let Some(db_path) = self.state.db().path() else {
+                tracing::info!("Rejecting snapshot offer");
+                return tm_response::OfferSnapshot::Reject;
+            };
+            // the restored merkle root is checked against the app hash
+            // that cometbft obtained from the light client
+            match KvSnapshotRestore::new(
+                db_path,
+                height,
+                req.app_hash.as_bytes().to_vec(),
+                super::is_key_diff_storable,
+            ) {
+                Ok(restore) => SnapshotSyncData::KeyValue(Box::new(restore)),
+                Err(err) => {
+                    tracing::error!(
+                        error = %err,
+                        "Failed to prepare the key-value snapshot restore"
+                    );
+                    return tm_response::OfferSnapshot::Reject;
+                }
+            }
+        } else {
+            SnapshotSyncData::Tarball(
+                tempfile::tempfile()
+                    .expect("Failed to create snapshot temp file"),
+            )
+        };
+        // resume from the valid chunks persisted by a previous sync
+        let received: BTreeSet<u64> = chunks
+            .iter()
+            .zip(0_u64..)
+            .filter_map(|(expected_hash, chunk)| {
+                let bytes = std::fs::read(path.chunk_with_id(chunk)).ok()?;
+                (Hash::sha256(bytes) == *expected_hash).then_some(chunk)
+            })
+            .collect();
+        if !received.is_empty() {
+            tracing::info!(
+                "Resuming the sync of the snapshot at height {height} with {} \
+                 out of {} chunks",
+                received.len(),
+                chunks.len(),
+            );
+        }
+        let mut snapshot_sync = SnapshotSync {
+            height,
+            app_hash: req.app_hash.as_bytes().to_vec(),
+            expected: chunks,
+            path,
+            received,
+            delivered: Default::default(),
+            next_chunk: 0,
+            strikes: HashMap::new(),
+            snapshot,
+        };
+        if let Err(err) = snapshot_sync.apply_received_chunks() {
+            tracing::error!(
+                error = %err,
+                "Failed to apply the persisted snapshot chunks"
+            );
+            remove_snapshot_chunks(&snapshot_sync.path);
+            return tm_response::OfferSnapshot::Reject;
+        }
+        self.syncing = Some(snapshot_sync);
+        tracing::info!("Accepting snapshot offer");
+        tm_response::OfferSnapshot::Accept
+    }
+
+    /// Stop syncing the current snapshot and remove its chunks
+    fn abort_snapshot_sync(&mut self) {
+        if let Some(snapshot_sync) = self.syncing.take() {
+            remove_snapshot_chunks(&snapshot_sync.path);
+        }
+    }
+
+    /// Persist a snapshot chunk and apply it to the snapshot data once all
+    /// the chunks preceding it were applied. Chunks can be received in any
+    /// order.
     pub fn apply_snapshot_chunk(
         &mut self,
         req: tm_request::ApplySnapshotChunk,
@@ -177,29 +266,15 @@ impl Shell<storage::PersistentDB, Sha256Hasher> {
             };
         };
 
-        // make sure we have been given the correct chunk
-        if u64::from(req.index) != snapshot_sync.next_chunk {
-            tracing::error!(
-                "Received wrong chunk, expected {}, got {}",
-                snapshot_sync.next_chunk,
-                req.index,
-            );
-            return tm_response::ApplySnapshotChunk {
-                result: ApplySnapshotChunkResult::Unknown,
-                refetch_chunks: vec![
-                    u32::try_from(snapshot_sync.next_chunk).unwrap(),
-                ],
-                reject_senders: vec![],
-            };
-        }
-
+        let index = u64::from(req.index);
         let Some(expected_hash) =
             snapshot_sync.expected.get(req.index as usize)
         else {
             tracing::error!(
-                "Received more chunks than expected; rejecting snapshot"
+                "Received a chunk with an index out of range; rejecting \
+                 snapshot"
             );
-            self.syncing = None;
+            self.abort_snapshot_sync();
             // if we get more chunks than expected, there is something wrong
             // with this snapshot and we should reject it.
             return tm_response::ApplySnapshotChunk {
@@ -209,57 +284,80 @@ impl Shell<storage::PersistentDB, Sha256Hasher> {
             };
         };
 
-        // check that the chunk matches the expected hash, otherwise
-        // re-fetch it in case it was corrupted. If the chunk fails
-        // to validate too many times, we reject the snapshot and sender.
-        let chunk_hash = Hash::sha256(&req.chunk);
-        if *expected_hash != chunk_hash {
-            tracing::error!(
-                "Hash of chunk did not match, expected {}, got {}",
-                expected_hash,
-                chunk_hash,
-            );
-            snapshot_sync.strikes =
-                checked!(snapshot_sync.strikes + 1).unwrap();
-            if snapshot_sync.strikes == MAX_SENDER_STRIKES {
-                snapshot_sync.strikes = 0;
-                self.syncing = None;
+        // a chunk persisted before is not written again
+        if !snapshot_sync.received.contains(&index) {
+            // check that the chunk matches the expected hash, otherwise
+            // re-fetch it from another sender in case it was corrupted. If
+            // the chunk fails to validate too many times, we reject the
+            // snapshot.
+            let chunk_hash = Hash::sha256(&req.chunk);
+            if *expected_hash != chunk_hash {
+                tracing::error!(
+                    "Hash of chunk {} did not match, expected {}, got {}",
+                    req.index,
+                    expected_hash,
+                    chunk_hash,
+                );
+                let strikes = snapshot_sync.strikes.entry(index).or_default();
+                *strikes = checked!(*strikes + 1).unwrap();
+                if *strikes == MAX_SENDER_STRIKES {
+                    self.abort_snapshot_sync();
+
+                    tracing::info!(
+                        "Max number of strikes reached on chunk, rejecting \
+                         snapshot"
+                    );
+                    return tm_response::ApplySnapshotChunk {
+                        result: ApplySnapshotChunkResult::RejectSnapshot,
+                        refetch_chunks: vec![],
+                        reject_senders: vec![req.sender],
+                    };
+                } else {
+                    return tm_response::ApplySnapshotChunk {
+                        result: ApplySnapshotChunkResult::Retry,
+                        refetch_chunks: vec![req.index],
+                        reject_senders: vec![req.sender],
+                    };
+                }
+            }
 
-                tracing::info!(
-                    "Max number of strikes reached on chunk, rejecting \
-                     snapshot"
+            // write snapshot chunk
+            snapshot_sync
+                .persist_chunk(index, &req.chunk)
+                .expect("Failed to save snapshot chunk");
+            if let Err(err) = snapshot_sync.apply_received_chunks() {
+                tracing::error!(
+                    error = %err,
+                    "Failed to apply snapshot chunk; rejecting snapshot"
                 );
+                self.abort_snapshot_sync();
                 return tm_response::ApplySnapshotChunk {
                     result: ApplySnapshotChunkResult::RejectSnapshot,
                     refetch_chunks: vec![],
-                    reject_senders: vec![req.sender],
+                    reject_senders: vec![],
                 };
-            } else {
+            }
+        }
+        snapshot_sync.delivered.insert(index);
+
+        // check if all chunks have been applied, and restore the
+        // database from the fetched snapshot
+        if snapshot_sync.is_complete(index) {
+            let path = snapshot_sync.path.clone();
+            let result = self.restore_database_from_state_sync();
+            remove_snapshot_chunks(&path);
+            if let Err(err) = result {
+                tracing::error!(
+                    error = %err,
+                    "Failed to restore the database from the snapshot; \
+                     rejecting snapshot"
+                );
                 return tm_response::ApplySnapshotChunk {
-                    result: ApplySnapshotChunkResult::Retry,
-                    refetch_chunks: vec![req.index],
+                    result: ApplySnapshotChunkResult::RejectSnapshot,
+                    refetch_chunks: vec![],
                     reject_senders: vec![],
                 };
             }
-        } else {
-            snapshot_sync.strikes = 0;
-        };
-
-        // write snapshot chunk
-        snapshot_sync
-            .snapshot
-            .write_all(&req.chunk)
-            .expect("Failed to save snapshot chunk");
-
-        // increment the chunk counter
-        snapshot_sync.next_chunk =
-            checked!(snapshot_sync.next_chunk + 1).unwrap();
-
-        // check if all chunks have been saved, and restore the
-        // database from the fetched tar archive
-        if snapshot_sync.next_chunk == snapshot_sync.expected.len() as u64 {
-            self.restore_database_from_state_sync();
-            self.syncing = None;
             tracing::info!("Snapshot completely applied");
         }
 
@@ -270,3 +368,56 @@ impl Shell<storage::PersistentDB, Sha256Hasher> {
         }
     }
 }
+
+impl SnapshotSync {
+    /// Persist a valid chunk of the snapshot.
+    fn persist_chunk(
+        &mut self,
+        index: u64,
+        chunk: &[u8],
+    ) -> std::io::Result<()> {
+        std::fs::write(self.path.chunk_with_id(index), chunk)?;
+        self.received.insert(index);
+        Ok(())
+    }
+
+    /// Apply the received chunks that follow the last applied chunk to the
+    /// snapshot data, in order.
+    fn apply_received_chunks(&mut self) -> Result<(), DbError> {
+        while self.received.contains(&self.next_chunk) {
+            let chunk = std::fs::read(self.path.chunk_with_id(self.next_chunk))
+                .map_err(|e| DbError::DBError(e.to_string()))?;
+            match &mut self.snapshot {
+                SnapshotSyncData::Tarball(snapshot) => snapshot
+                    .write_all(&chunk)
+                    .map_err(|e| DbError::DBError(e.to_string()))?,
+                SnapshotSyncData::KeyValue(restore) => {
+                    restore.apply_chunk(&chunk)?
+                }
+            }
+            self.next_chunk = checked!(self.next_chunk + 1)?;
+        }
+        Ok(())
+    }
+
+    /// Check if all the chunks were applied and CometBFT is done delivering
+    /// them. As CometBFT delivers the chunks in order, this is the case once
+    /// it delivers the last chunk, even if some earlier chunks were persisted
+    /// before a restart.
+    fn is_complete(&self, last_delivered: u64) -> bool {
+        let num_chunks = self.expected.len() as u64;
+        self.next_chunk == num_chunks
+            && (self.delivered.len() as u64 == num_chunks
+                || checked!(last_delivered + 1).ok() == Some(num_chunks))
+    }
+}
+
+/// Remove the persisted chunks of a snapshot
+fn remove_snapshot_chunks(path: &SnapshotSyncPath) {
+    if let Err(err) = path.remove() {
+        tracing::warn!(
+            error = %err,
+            "Failed to remove the chunks of the synced snapshot"
+        );
+    }
+}
@@ -31,7 +31,8 @@ use namada_sdk::queries::{
     EncodedResponseQuery, RPC, RequestCtx, RequestQuery, Router,
 };
 use namada_sdk::state::{
-    DB, EPOCH_SWITCH_BLOCKS_DELAY, LastBlock, Sha256Hasher, StorageRead,
+    DB, EPOCH_SWITCH_BLOCKS_DELAY, HistoricState, LastBlock, Sha256Hasher,
+    StorageRead,
 };
 use namada_sdk::tendermint::abci::response::Info;
 use namada_sdk::tendermint::abci::types::VoteInfo;
@@ -62,7 +63,9 @@ use crate::tendermint_proto::abci::{
 use crate::tendermint_rpc::SimpleRequest;
 use crate::tendermint_rpc::endpoint::block;
 use crate::tendermint_rpc::error::Error as RpcError;
-use crate::{dry_run_tx, storage, tendermint, tendermint_rpc};
+use crate::{
+    dry_run_tx, is_dry_run_tx_path, storage, tendermint, tendermint_rpc,
+};
 
 /// Mock Ethereum oracle used for testing purposes.
 struct MockEthOracle {
@@ -833,7 +836,7 @@ impl Client for MockNode {
             prove,
         };
         let borrowed = self.shell.lock().unwrap();
-        if request.path == RPC.shell().dry_run_tx_path() {
+        if is_dry_run_tx_path(&request.path) {
             dry_run_tx(
                 // This is safe because nothing else is using `self.state`
                 // concurrently and the `TempWlState` will be dropped right
@@ -847,7 +850,7 @@ impl Client for MockNode {
             )
         } else {
             let ctx = RequestCtx {
-                state: &borrowed.state,
+                state: HistoricState::new(&borrowed.state),
                 event_log: borrowed.event_log(),
                 vp_wasm_cache: borrowed.vp_wasm_cache.read_only(),
                 tx_wasm_cache: borrowed.tx_wasm_cache.read_only(),
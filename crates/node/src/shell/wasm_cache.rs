@@ -0,0 +1,240 @@
+//! Pre-warming of the wasm compilation caches with the allowlisted wasm.
+//!
+//! The caches compile the wasm lazily on their first use, which slows down the
+//! first blocks after a restart or after a change of the allowlists. To avoid
+//! it, the allowlisted wasm that are stored on chain get compiled in the
+//! background when the node starts and whenever the allowlists change.
+
+use std::collections::BTreeSet;
+use std::num::NonZeroUsize;
+
+use namada_sdk::gas::GasMeterKind;
+use namada_sdk::hash::Hash;
+use namada_sdk::parameters::storage::{
+    get_tx_allowlist_storage_key, get_vp_allowlist_storage_key,
+};
+use namada_sdk::state::{
+    self, DB, DBIter, StorageHasher, StorageRead, iter_prefix,
+};
+use namada_sdk::storage::{DbKeySeg, Key};
+
+use super::Shell;
+
+/// Get the default number of threads used to pre-warm the wasm caches, which
+/// is half of the available parallelism
+pub fn default_pre_warm_threads() -> NonZeroUsize {
+    std::thread::available_parallelism()
+        .ok()
+        .and_then(|threads| NonZeroUsize::new(threads.get() / 2))
+        .unwrap_or(NonZeroUsize::MIN)
+}
+
+/// The code hashes allowed by a wasm allowlist
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Allowed {
+    /// The allowlist is empty, so any code is allowed
+    Any,
+    /// Only the code of these hashes is allowed
+    Only(BTreeSet<Hash>),
+}
+
+impl Allowed {
+    /// Read the allowlist under the given key
+    fn read<S>(storage: &S, key: &Key) -> state::Result<Self>
+    where
+        S: StorageRead,
+    {
+        let allowlist: Vec<String> = storage.read(key)?.unwrap_or_default();
+        if allowlist.is_empty() {
+            return Ok(Self::Any);
+        }
+        allowlist
+            .iter()
+            .map(|hash| Hash::try_from(hash.as_str()))
+            .collect::<Result<_, _>>()
+            .map(Self::Only)
+            .map_err(state::Error::new)
+    }
+
+    /// Check if the code of the given hash is allowed
+    pub fn contains(&self, hash: &Hash) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Only(hashes) => hashes.contains(hash),
+        }
+    }
+}
+
+/// Read the tx and VP wasm allowlists, in this order
+pub fn read_allowlists<S>(storage: &S) -> state::Result<(Allowed, Allowed)>
+where
+    S: StorageRead,
+{
+    let tx = Allowed::read(storage, &get_tx_allowlist_storage_key())?;
+    let vp = Allowed::read(storage, &get_vp_allowlist_storage_key())?;
+    Ok((tx, vp))
+}
+
+/// Read the code of the tx and VP wasm that are stored on chain and allowed by
+/// the allowlists, in this order. When an allowlist is empty, all the stored
+/// wasm of its kind are returned.
+pub fn read_allowed_wasm<S>(
+    storage: &S,
+) -> state::Result<(Vec<Vec<u8>>, Vec<Vec<u8>>)>
+where
+    S: StorageRead,
+{
+    let (tx_allowed, vp_allowed) = read_allowlists(storage)?;
+
+    // The named wasm, stored at genesis, tell us the kind of their code
+    let mut named_txs = BTreeSet::new();
+    let mut named_vps = BTreeSet::new();
+    let mut hash_key_prefix = Key::wasm_hash("");
+    hash_key_prefix.segments.pop();
+    for entry in iter_prefix::<Hash>(storage, hash_key_prefix)? {
+        let (key, hash) = entry?;
+        match key.last() {
+            Some(DbKeySeg::StringSeg(name)) if name.starts_with("tx_") => {
+                named_txs.insert(hash);
+            }
+            Some(DbKeySeg::StringSeg(name)) if name.starts_with("vp_") => {
+                named_vps.insert(hash);
+            }
+            _ => {}
+        }
+    }
+
+    let read_codes = |allowed: Allowed, named: BTreeSet<Hash>| {
+        let hashes = match allowed {
+            Allowed::Any => named,
+            Allowed::Only(hashes) => hashes,
+        };
+        let mut codes = Vec::with_capacity(hashes.len());
+        for hash in hashes {
+            match storage.read::<Vec<u8>>(&Key::wasm_code(&hash))? {
+                Some(code) => codes.push(code),
+                // NB: the code of the allowlisted wasm that are not stored
+                // on chain is only known once a tx carries it
+                None => tracing::debug!(
+                    "The code of the allowlisted wasm {hash} isn't stored, \
+                     skipping its pre-warming."
+                ),
+            }
+        }
+        state::Result::Ok(codes)
+    };
+    Ok((
+        read_codes(tx_allowed, named_txs)?,
+        read_codes(vp_allowed, named_vps)?,
+    ))
+}
+
+impl<D, H> Shell<D, H>
+where
+    D: DB + for<'iter> DBIter<'iter> + Sync + 'static,
+    H: StorageHasher + Sync + 'static,
+{
+    /// Compile the allowlisted wasm in the background into the filesystem
+    /// caches, skipping those that are already compiled.
+    pub fn pre_warm_wasm_cache(&mut self) {
+        let (txs, vps) = match read_allowed_wasm(&self.state) {
+            Ok(codes) => codes,
+            Err(err) => {
+                tracing::error!(
+                    "Failed to read the allowlisted wasm to pre-warm the wasm \
+                     caches: {err}"
+                );
+                return;
+            }
+        };
+        tracing::info!(
+            "Pre-warming the wasm caches with {} tx and {} VP wasm.",
+            txs.len(),
+            vps.len()
+        );
+        self.tx_wasm_cache.pre_warm(
+            txs,
+            GasMeterKind::MutGlobal,
+            self.wasm_cache_pre_warm_threads,
+        );
+        self.vp_wasm_cache.pre_warm(
+            vps,
+            GasMeterKind::MutGlobal,
+            self.wasm_cache_pre_warm_threads,
+        );
+    }
+
+    /// Check if the wasm allowlists are changed by the block that is about to
+    /// be committed.
+    pub(super) fn wasm_allowlists_changed(&self) -> bool {
+        [
+            get_tx_allowlist_storage_key(),
+            get_vp_allowlist_storage_key(),
+        ]
+        .iter()
+        .any(|key| {
+            let (committed, _gas) = self
+                .state
+                .db_read(key)
+                .expect("Must be able to read the wasm allowlist");
+            let updated = self
+                .state
+                .read_bytes(key)
+                .expect("Must be able to read the wasm allowlist");
+            committed != updated
+        })
+    }
+}
+
+#[cfg(test)]
+mod test_wasm_cache {
+    use namada_sdk::state::StorageWrite;
+
+    use super::*;
+    use crate::shell::test_utils::TestShell;
+
+    /// Test that the allowed wasm are read from the named wasm when the
+    /// allowlists are empty, and from the allowlists otherwise.
+    #[test]
+    fn test_read_allowed_wasm() {
+        let (mut shell, _recv, _, _) = TestShell::new();
+        let tx_code = b"tx code".to_vec();
+        let vp_code = b"vp code".to_vec();
+        let other_code = b"other code".to_vec();
+        let not_stored_code = b"not stored code".to_vec();
+        for (name, code) in [
+            ("tx_test.wasm", &tx_code),
+            ("vp_test.wasm", &vp_code),
+            ("other.wasm", &other_code),
+        ] {
+            let hash = Hash::sha256(code);
+            shell.state.write(&Key::wasm_code(&hash), code).unwrap();
+            shell.state.write(&Key::wasm_hash(name), hash).unwrap();
+        }
+
+        let (txs, vps) = read_allowed_wasm(&shell.state).unwrap();
+        assert_eq!(txs, vec![tx_code.clone()]);
+        assert_eq!(vps, vec![vp_code.clone()]);
+
+        let allowlist = |codes: &[&Vec<u8>]| {
+            codes
+                .iter()
+                .map(|code| Hash::sha256(code).to_string().to_lowercase())
+                .collect::<Vec<_>>()
+        };
+        shell
+            .state
+            .write(
+                &get_tx_allowlist_storage_key(),
+                allowlist(&[&other_code, &not_stored_code]),
+            )
+            .unwrap();
+        assert!(shell.wasm_allowlists_changed());
+        let (txs, vps) = read_allowed_wasm(&shell.state).unwrap();
+        assert_eq!(txs, vec![other_code]);
+        assert_eq!(vps, vec![vp_code]);
+
+        shell.state.commit_block().expect("Test failed");
+        assert!(!shell.wasm_allowlists_changed());
+    }
+}
@@ -43,6 +43,7 @@ pub struct AbcippShim {
     )>,
     snapshot_task: Option<std::thread::JoinHandle<Result<(), DbError>>>,
     snapshots_to_keep: u64,
+    snapshot_format: config::SnapshotFormat,
     namada_version: String,
 }
 
@@ -69,6 +70,7 @@ impl AbcippShim {
         let action_at_height = config.shell.action_at_height.clone();
         let snapshots_to_keep =
             config.shell.snapshots_to_keep.map(|n| n.get()).unwrap_or(1);
+        let snapshot_format = config.shell.snapshot_format.unwrap_or_default();
         (
             Self {
                 service: Shell::new(
@@ -86,6 +88,7 @@ impl AbcippShim {
                 shell_recv,
                 snapshot_task: None,
                 snapshots_to_keep,
+                snapshot_format,
                 namada_version,
             },
             AbciService {
@@ -239,6 +242,7 @@ impl AbcippShim {
         let (snap_send, snap_recv) = tokio::sync::oneshot::channel();
 
         let snapshots_to_keep = self.snapshots_to_keep;
+        let snapshot_format = self.snapshot_format;
         let snapshot_task = std::thread::spawn(move || {
             let db = crate::storage::open(db_path, true, None)
                 .expect("Could not open DB");
@@ -247,9 +251,11 @@ impl AbcippShim {
             snap_send.send(()).unwrap();
             DbSnapshot::cleanup(height, &base_dir, snapshots_to_keep)
                 .map_err(|e| DbError::DBError(e.to_string()))?;
-            snapshot
-                .package()
-                .map_err(|e| DbError::DBError(e.to_string()))
+            match snapshot_format {
+                config::SnapshotFormat::DbCheckpoint => snapshot.package(),
+                config::SnapshotFormat::KeyValue => snapshot.package_kv(),
+            }
+            .map_err(|e| DbError::DBError(e.to_string()))
         });
 
         // it's important that the thread is
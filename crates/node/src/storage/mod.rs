@@ -12,7 +12,10 @@ use blake2b_rs::{Blake2b, Blake2bBuilder};
 use namada_sdk::state::{FullAccessState, StorageHasher};
 #[cfg(test)]
 pub use rocksdb::SnapshotPath;
-pub use rocksdb::{DbSnapshot, DbSnapshotMeta, RocksDBUpdateVisitor, open};
+pub use rocksdb::{
+    DbSnapshot, DbSnapshotMeta, DbSnapshotSource, KvSnapshotRestore,
+    RocksDBUpdateVisitor, SnapshotSyncPath, open,
+};
 
 #[derive(Default)]
 pub struct PersistentStorageHasher(Blake2bHasher);
@@ -69,7 +72,8 @@ mod tests {
     use namada_sdk::parameters::Parameters;
     use namada_sdk::state::merkle_tree::NO_DIFF_KEY_PREFIX;
     use namada_sdk::state::{
-        self, DB, StateRead, StorageRead, StorageWrite, StoreType,
+        self, DB, HistoricState, StateRead, StorageRead, StorageWrite,
+        StoreType,
     };
     use namada_sdk::storage::{Key, KeySeg};
     use namada_sdk::token::conversion::update_allowed_conversions;
@@ -573,6 +577,49 @@ mod tests {
             }
         }
 
+        // Check Account subtree, which is rebuilt from the last rebuilt one
+        // while the heights are increasing in the same epoch
+        let mut current_state = HashMap::new();
+        for i in 0..num_keys {
+            let key = make_key(i);
+            current_state.insert(key, true);
+        }
+        for (height, key, write_type) in blocks_write_type.clone() {
+            if is_ibc_key(&key) {
+                continue;
+            }
+            let tree =
+                state.get_merkle_tree(height, Some(StoreType::Account))?;
+            // Check if the rebuilt tree's root is the same as the saved one
+            assert_eq!(tree.root().0, roots.get(&height).unwrap().0);
+            match write_type {
+                0 => {
+                    // data was not updated
+                    if *current_state.get(&key).unwrap() {
+                        assert!(tree.has_key(&key)?);
+                    } else {
+                        assert!(!tree.has_key(&key)?);
+                    }
+                }
+                1 | 3 => {
+                    // data was deleted
+                    assert!(!tree.has_key(&key)?);
+                    current_state.insert(key, false);
+                }
+                _ => {
+                    // data was updated
+                    assert!(tree.has_key(&key)?);
+                    current_state.insert(key, true);
+                }
+            }
+        }
+        // The decreasing heights are rebuilt from the stored subtree
+        for (height, _, _) in blocks_write_type.clone().rev() {
+            let tree =
+                state.get_merkle_tree(height, Some(StoreType::Account))?;
+            assert_eq!(tree.root().0, roots.get(&height).unwrap().0);
+        }
+
         // Check NoDiff subtree
         let mut current_state = HashMap::new();
         for i in 0..num_keys {
@@ -750,6 +797,9 @@ mod tests {
         // ibc tree should be able to be restored
         let result = state.get_merkle_tree(6.into(), Some(StoreType::Ibc));
         assert!(result.is_ok(), "The ibc tree should be restored");
+        // the account tree is provable too
+        let result = state.get_merkle_tree(6.into(), Some(StoreType::Account));
+        assert!(result.is_ok(), "The account tree should be restored");
         // bridge pool tree should be pruned because of the nonce
         let result =
             state.get_merkle_tree(6.into(), Some(StoreType::BridgePool));
@@ -861,6 +911,70 @@ mod tests {
         itertools::assert_equal(iter, expected);
     }
 
+    /// Test the prefix iterator at past heights with RocksDB.
+    #[test]
+    fn test_persistent_storage_historic_prefix_iter() {
+        let db_path =
+            TempDir::new().expect("Unable to create a temporary DB directory");
+        let mut state = PersistentState::open(
+            db_path.path(),
+            None,
+            ChainId::default(),
+            address::testing::nam(),
+            None,
+            is_key_diff_storable,
+        );
+
+        let prefix = storage::Key::parse("prefix").unwrap();
+        let key = |i: u64| prefix.push(&i).unwrap();
+
+        // Height 1: write 1 and 2
+        state.in_mem_mut().begin_block(1.into()).unwrap();
+        state.write(&key(1), 1_u64).unwrap();
+        state.write(&key(2), 2_u64).unwrap();
+        state.commit_block().unwrap();
+
+        // Height 2: delete 1, update 2 and write 3
+        state.in_mem_mut().begin_block(2.into()).unwrap();
+        state.delete(&key(1)).unwrap();
+        state.write(&key(2), 20_u64).unwrap();
+        state.write(&key(3), 3_u64).unwrap();
+        state.commit_block().unwrap();
+
+        // Height 3: delete 3 and write 4
+        state.in_mem_mut().begin_block(3.into()).unwrap();
+        state.delete(&key(3)).unwrap();
+        state.write(&key(4), 4_u64).unwrap();
+        state.commit_block().unwrap();
+
+        let expected_at_height = [
+            (1_u64, vec![(1_u64, 1_u64), (2, 2)]),
+            (2, vec![(2, 20), (3, 3)]),
+            (3, vec![(2, 20), (4, 4)]),
+        ];
+        for (height, expected) in expected_at_height {
+            let historic =
+                HistoricState::new(&state).at_height(height.into()).unwrap();
+            let iter = state::iter_prefix(&historic, prefix.clone())
+                .unwrap()
+                .map(Result::unwrap);
+            let expected = expected.into_iter().map(|(i, val)| (key(i), val));
+            itertools::assert_equal(iter, expected);
+
+            // Single reads must agree with the iterator
+            for i in 1..=4 {
+                let iterated = state::iter_prefix::<u64>(&historic, key(i))
+                    .unwrap()
+                    .next()
+                    .map(|res| res.unwrap().1);
+                assert_eq!(historic.read::<u64>(&key(i)).unwrap(), iterated);
+            }
+        }
+
+        // A height that hasn't been committed yet cannot be read
+        assert!(HistoricState::new(&state).at_height(4.into()).is_err());
+    }
+
     fn test_key_1() -> Key {
         Key::parse("testing1").unwrap()
     }
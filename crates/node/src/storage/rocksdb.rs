@@ -26,8 +26,8 @@
 //!   - `{height}/new/{dyn}`: value set in block height `h`
 //!   - `{height}/old/{dyn}`: value from predecessor block height
 //! - `rollback`: diffs in account subspaces' key-vals for keys modified with
-//!   `persist_diff == false` which are only kept for 1 block to support
-//!   rollback
+//!   `persist_diff == false` which are only kept for the blocks of the rollback
+//!   window (1 block by default) to support rollback
 //!   - `{height}/new/{dyn}`: value set in block height `h`
 //!   - `{height}/old/{dyn}`: value from predecessor block height
 //! - `block`: block state
@@ -40,10 +40,21 @@
 //!     - `epoch`: block epoch
 //!     - `address_gen`: established address generator
 //!     - `header`: block's header
+//!   - `rollback`: data kept to roll back the state to one of the most recent
+//!     blocks
+//!     - `height/{h}`: for each block at height `h`:
+//!       - `{key}`: the value of the `state` key of the same name
+//!       - `replay_protection/{hash}`: a hash included in the block
+//!     - `epoch/{e}/conversion_state`: the conversion state of epoch `e`
 //! - `replay_protection`: hashes of processed tx for replay protection purposes
 //!     - `current/{hash}`: a hash included in the current block
 //!     - `{hash}`: a hash included in previous blocks
+//! - `events`: log of the events emitted by the ledger
+//!   - `block/{h}/{index}`: an event emitted at height `h`
+//!   - `attr/{digest}/{h}/{index}`: index of the events at height `h` by the
+//!     digest of one of their attributes
 
+use std::collections::{BTreeMap, btree_map};
 use std::fs::File;
 use std::io::{BufWriter, Read, Seek, Write};
 use std::mem::ManuallyDrop;
@@ -56,22 +67,25 @@ use itertools::Either;
 use namada_replay_protection as replay_protection;
 use namada_sdk::arith::checked;
 use namada_sdk::borsh::{BorshDeserialize, BorshSerialize, BorshSerializeExt};
-use namada_sdk::collections::HashSet;
+use namada_sdk::collections::{HashMap, HashSet};
 use namada_sdk::eth_bridge::storage::bridge_pool;
 use namada_sdk::eth_bridge::storage::proof::BridgePoolRootProof;
+use namada_sdk::eth_bridge_pool::is_pending_transfer_key;
 use namada_sdk::gas::Gas;
 use namada_sdk::hash::Hash;
 use namada_sdk::state::merkle_tree::{
-    tree_key_prefix_with_epoch, tree_key_prefix_with_height,
+    NO_DIFF_KEY_PREFIX, tree_key_prefix_with_epoch, tree_key_prefix_with_height,
 };
 use namada_sdk::state::{
     BlockStateRead, BlockStateWrite, DB, DBIter, DBWriteBatch,
-    DbError as Error, DbResult as Result, MerkleTreeStoresRead,
-    PatternIterator, PrefixIterator, StoreType,
+    DbError as Error, DbResult as Result, KeyRange, MerkleTree,
+    MerkleTreeStoresRead, MerkleTreeStoresWrite, PatternIterator,
+    PrefixIterator, Sha256Hasher, StoreType, SubspaceChange, event_store,
+    restore_merkle_tree,
 };
 use namada_sdk::storage::{
     BLOCK_CF, BlockHeader, BlockHeight, DBUpdateVisitor, DIFFS_CF, DbColFam,
-    Epoch, Key, KeySeg, REPLAY_PROTECTION_CF, ROLLBACK_CF, STATE_CF,
+    EVENTS_CF, Epoch, Key, KeySeg, REPLAY_PROTECTION_CF, ROLLBACK_CF, STATE_CF,
     SUBSPACE_CF,
 };
 use namada_sdk::{decode, encode, ethereum_events};
@@ -101,6 +115,7 @@ const ETHEREUM_HEIGHT_KEY: &str = "ethereum_height";
 const ETH_EVENTS_QUEUE_KEY: &str = "eth_events_queue";
 const RESULTS_KEY_PREFIX: &str = "results";
 const PRED_KEY_PREFIX: &str = "pred";
+const ROLLBACK_KEY_PREFIX: &str = "rollback";
 
 const MERKLE_TREE_ROOT_KEY_SEGMENT: &str = "root";
 const MERKLE_TREE_STORE_KEY_SEGMENT: &str = "store";
@@ -109,6 +124,20 @@ const BLOCK_TIME_KEY_SEGMENT: &str = "time";
 const EPOCH_KEY_SEGMENT: &str = "epoch";
 const PRED_EPOCHS_KEY_SEGMENT: &str = "pred_epochs";
 const ADDRESS_GEN_KEY_SEGMENT: &str = "address_gen";
+const HEIGHT_KEY_SEGMENT: &str = "height";
+const REPLAY_PROTECTION_KEY_SEGMENT: &str = "replay_protection";
+
+/// The state keys written with every block that are kept in the rollback
+/// journal, together with a flag indicating whether their predecessor value
+/// is also stored under [`PRED_KEY_PREFIX`]
+const ROLLBACK_JOURNAL_STATE_KEYS: [(&str, bool); 6] = [
+    (NEXT_EPOCH_MIN_START_HEIGHT_KEY, true),
+    (NEXT_EPOCH_MIN_START_TIME_KEY, true),
+    (UPDATE_EPOCH_BLOCKS_DELAY_KEY, true),
+    (COMMIT_ONLY_DATA_KEY, true),
+    (ETHEREUM_HEIGHT_KEY, false),
+    (ETH_EVENTS_QUEUE_KEY, false),
+];
 
 const OLD_DIFF_PREFIX: &str = "old";
 const NEW_DIFF_PREFIX: &str = "new";
@@ -230,6 +259,14 @@ pub fn open(
         REPLAY_PROTECTION_CF,
         replay_protection_cf_opts,
     ));
+
+    // for the events log (insert-intensive)
+    let mut events_cf_opts = Options::default();
+    events_cf_opts.set_compression_type(DBCompressionType::Zstd);
+    events_cf_opts.set_compression_options(0, 0, 0, 1024 * 1024);
+    events_cf_opts.set_compaction_style(DBCompactionStyle::Level);
+    events_cf_opts.set_block_based_table_factory(&table_opts);
+    cfs.push(ColumnFamilyDescriptor::new(EVENTS_CF, events_cf_opts));
     Ok(if read_only {
         RocksDB {
             inner: ManuallyDrop::new(
@@ -342,6 +379,45 @@ impl RocksDB {
         batch.0.put_cf(cf, key.as_ref(), value);
     }
 
+    /// Write the Merkle tree stores of a block in a batch. The stores that are
+    /// not stored with every block are only written on a full commit.
+    fn add_merkle_tree_stores_to_batch(
+        &self,
+        merkle_tree_stores: &MerkleTreeStoresWrite<'_>,
+        height: BlockHeight,
+        epoch: Epoch,
+        is_full_commit: bool,
+        batch: &mut RocksDBWriteBatch,
+    ) -> Result<()> {
+        let block_cf = self.get_column_family(BLOCK_CF)?;
+        for st in StoreType::iter() {
+            if st.is_stored_every_block() || is_full_commit {
+                let key_prefix = if st.is_stored_every_block() {
+                    tree_key_prefix_with_height(st, height)
+                } else {
+                    tree_key_prefix_with_epoch(st, epoch)
+                };
+                let root_key =
+                    format!("{key_prefix}/{MERKLE_TREE_ROOT_KEY_SEGMENT}");
+                self.add_value_to_batch(
+                    block_cf,
+                    root_key,
+                    merkle_tree_stores.root(st),
+                    batch,
+                );
+                let store_key =
+                    format!("{key_prefix}/{MERKLE_TREE_STORE_KEY_SEGMENT}");
+                self.add_value_bytes_to_batch(
+                    block_cf,
+                    store_key,
+                    merkle_tree_stores.store(st).encode(),
+                    batch,
+                );
+            }
+        }
+        Ok(())
+    }
+
     /// Persist the diff of an account subspace key-val under the height where
     /// it was changed in a batch write.
     fn batch_write_subspace_diff(
@@ -535,6 +611,136 @@ impl RocksDB {
         Ok(DbSnapshot(snapshot_path))
     }
 
+    /// Stream the state of the last committed block as key-value snapshot
+    /// items. The entries of the column families that are not committed to by
+    /// the Merkle tree come first, followed by the subspace entries of each
+    /// Merkle tree store, sorted by their keys, and by the heights of the
+    /// pending bridge pool transfers. Each item holds roughly at most
+    /// `max_item_size` bytes of keys and values.
+    fn kv_snapshot_items(
+        &self,
+        max_item_size: usize,
+        mut emit: impl FnMut(KvSnapshotItem) -> Result<()>,
+    ) -> Result<()> {
+        let last_block = self.read_last_block()?.ok_or(Error::DBError(
+            "Missing last block in storage".to_string(),
+        ))?;
+        let height = last_block.height;
+
+        // The state of the last block
+        let state_cf = self.get_column_family(STATE_CF)?;
+        let mut entries = vec![];
+        for result in self.inner.iterator_cf(state_cf, IteratorMode::Start) {
+            let (key, value) =
+                result.map_err(|e| Error::DBError(e.into_string()))?;
+            let key = String::from_utf8(key.to_vec())
+                .map_err(|e| Error::DBError(e.to_string()))?;
+            entries.push((key, value.to_vec()));
+        }
+        emit(KvSnapshotItem::Raw {
+            cf: STATE_CF.to_owned(),
+            entries,
+        })?;
+
+        // The last block. Its Merkle tree stores are rebuilt from the
+        // subspace by the restoring node.
+        let block_cf = self.get_column_family(BLOCK_CF)?;
+        let mut entries = vec![];
+        for key in kv_snapshot_block_keys(height) {
+            if let Some(value) = self.read_value_bytes(block_cf, &key)? {
+                entries.push((key, value));
+            }
+        }
+        emit(KvSnapshotItem::Raw {
+            cf: BLOCK_CF.to_owned(),
+            entries,
+        })?;
+
+        // The replay protection entries
+        let replay_protection_cf =
+            self.get_column_family(REPLAY_PROTECTION_CF)?;
+        let mut buffer = KvSnapshotBuffer::default();
+        for result in self
+            .inner
+            .iterator_cf(replay_protection_cf, IteratorMode::Start)
+        {
+            let (key, value) =
+                result.map_err(|e| Error::DBError(e.into_string()))?;
+            let key = String::from_utf8(key.to_vec())
+                .map_err(|e| Error::DBError(e.to_string()))?;
+            let key_len = key.len();
+            if let Some(entries) =
+                buffer.push(key, key_len, value.to_vec(), max_item_size)
+            {
+                emit(KvSnapshotItem::Raw {
+                    cf: REPLAY_PROTECTION_CF.to_owned(),
+                    entries,
+                })?;
+            }
+        }
+        let entries = buffer.take();
+        if !entries.is_empty() {
+            emit(KvSnapshotItem::Raw {
+                cf: REPLAY_PROTECTION_CF.to_owned(),
+                entries,
+            })?;
+        }
+
+        // The subspace, split by Merkle tree stores. The bridge pool store
+        // commits to the heights at which the pending transfers were added
+        // rather than to their values, so these are taken from the store.
+        let bridge_pool = restore_merkle_tree::<_, Sha256Hasher>(
+            self,
+            &last_block.pred_epochs,
+            height,
+            Some(StoreType::BridgePool),
+        )
+        .map_err(|e| Error::DBError(e.to_string()))?;
+        let mut bridge_pool_heights = vec![];
+        let mut buffers = HashMap::<StoreType, KvSnapshotBuffer<Key>>::new();
+        let subspace_cf = self.get_column_family(SUBSPACE_CF)?;
+        for (key, value, _gas) in iter_prefix(self, subspace_cf, None, None) {
+            let key_len = key.len();
+            let key = Key::parse(key).map_err(Error::KeyError)?;
+            let (store_type, _) = StoreType::sub_key(&key)?;
+            if is_pending_transfer_key(&key) {
+                let transfer_height = match bridge_pool.get(&key) {
+                    Ok(bytes) => decode(bytes)?,
+                    Err(_) => height,
+                };
+                bridge_pool_heights.push((key.clone(), transfer_height));
+            }
+            if let Some(entries) = buffers.entry(store_type).or_default().push(
+                key,
+                key_len,
+                value,
+                max_item_size,
+            ) {
+                emit(KvSnapshotItem::Subspace {
+                    store_type,
+                    entries,
+                })?;
+            }
+        }
+        for store_type in StoreType::iter_subtrees() {
+            let Some(buffer) = buffers.get_mut(store_type) else {
+                continue;
+            };
+            let entries = buffer.take();
+            if !entries.is_empty() {
+                emit(KvSnapshotItem::Subspace {
+                    store_type: *store_type,
+                    entries,
+                })?;
+            }
+        }
+        if !bridge_pool_heights.is_empty() {
+            emit(KvSnapshotItem::BridgePoolHeights(bridge_pool_heights))?;
+        }
+
+        Ok(())
+    }
+
     /// Rollback to previous block. Given the inner working of tendermint
     /// rollback and of the key structure of Namada, calling rollback more than
     /// once without restarting the chain results in a single rollback.
@@ -622,6 +828,14 @@ impl RocksDB {
             format!("{RESULTS_KEY_PREFIX}/{}", last_block.height),
         );
 
+        // Delete the events emitted in the last block
+        tracing::info!("Removing last block events");
+        event_store::delete_events_to_batch(
+            self,
+            &mut batch,
+            last_block.height,
+        )?;
+
         // Restore the state of replay protection to the last block
         let reprot_cf = self.get_column_family(REPLAY_PROTECTION_CF)?;
         tracing::info!("Restoring replay protection state");
@@ -731,8 +945,371 @@ impl RocksDB {
         self.exec_batch(batch)
     }
 
+    /// Check that the state can be rolled back to the given height, i.e. that
+    /// the data needed to restore it hasn't been pruned. Returns the last
+    /// committed block on success.
+    pub fn check_rollback_to_height(
+        &self,
+        target: BlockHeight,
+    ) -> Result<BlockStateRead> {
+        let last_block = self.read_last_block()?.ok_or(Error::DBError(
+            "Missing last block in storage".to_string(),
+        ))?;
+        let cannot_rollback = |reason: String| Error::CannotRollback {
+            height: target,
+            reason,
+        };
+
+        if target.0 == 0 {
+            return Err(cannot_rollback(
+                "the height must be at least 1".to_string(),
+            ));
+        }
+        if target > last_block.height {
+            return Err(cannot_rollback(format!(
+                "the last committed height is {}",
+                last_block.height
+            )));
+        }
+        if target == last_block.height {
+            return Ok(last_block);
+        }
+
+        let block_cf = self.get_column_family(BLOCK_CF)?;
+        let journal_key = format!(
+            "{}/{NEXT_EPOCH_MIN_START_HEIGHT_KEY}",
+            rollback_height_prefix(target)
+        );
+        if self.read_value_bytes(block_cf, journal_key)?.is_none() {
+            return Err(cannot_rollback(
+                "the data needed to restore its state has been pruned, the \
+                 `rollback_window_blocks` of the node's config limits how \
+                 many blocks can be rolled back"
+                    .to_string(),
+            ));
+        }
+
+        let target_epoch =
+            last_block.pred_epochs.get_epoch(target).ok_or_else(|| {
+                cannot_rollback(
+                    "the epoch of the height is unknown".to_string(),
+                )
+            })?;
+        for st in StoreType::iter() {
+            let key_prefix = if st.is_stored_every_block() {
+                tree_key_prefix_with_height(st, target)
+            } else {
+                tree_key_prefix_with_epoch(st, target_epoch)
+            };
+            let root_key =
+                format!("{key_prefix}/{MERKLE_TREE_ROOT_KEY_SEGMENT}");
+            if self.read_value_bytes(block_cf, root_key)?.is_none() {
+                return Err(cannot_rollback(format!(
+                    "the {st} Merkle tree store has been pruned"
+                )));
+            }
+        }
+
+        if target_epoch != last_block.epoch {
+            let conversion_state_key = format!(
+                "{}/{CONVERSION_STATE_KEY}",
+                rollback_epoch_prefix(target_epoch)
+            );
+            if self
+                .read_value_bytes(block_cf, conversion_state_key)?
+                .is_none()
+            {
+                return Err(cannot_rollback(format!(
+                    "the conversion state of epoch {target_epoch} has been \
+                     pruned"
+                )));
+            }
+        }
+
+        Ok(last_block)
+    }
+
+    /// Rollback to the given height, one block at a time, using the diffs and
+    /// the rollback journal. Unlike [`RocksDB::rollback`], this can rewind any
+    /// number of blocks within the node's rollback window. Nothing is written
+    /// if the state of the target height cannot be restored.
+    pub fn rollback_to_height(&mut self, target: BlockHeight) -> Result<()> {
+        let last_block = self.check_rollback_to_height(target)?;
+        tracing::info!(
+            "Namada last block height: {}, rolling back to height {}",
+            last_block.height,
+            target
+        );
+        if target == last_block.height {
+            tracing::info!(
+                "Namada height already matches the target height, no need to \
+                 rollback."
+            );
+            return Ok(());
+        }
+        let target_epoch = last_block
+            .pred_epochs
+            .get_epoch(target)
+            .expect("The epoch of the target height must be known");
+
+        let mut batch = RocksDB::batch();
+        let state_cf = self.get_column_family(STATE_CF)?;
+        let block_cf = self.get_column_family(BLOCK_CF)?;
+        let subspace_cf = self.get_column_family(SUBSPACE_CF)?;
+        let reprot_cf = self.get_column_family(REPLAY_PROTECTION_CF)?;
+
+        // Undo the blocks from the last one down to the target. Because the
+        // later writes to the same key in a batch take precedence, the
+        // subspace ends up with the values from the target height.
+        let mut height = last_block.height;
+        while height > target {
+            tracing::info!("Reverting block at height {height}");
+
+            for cf in [DIFFS_CF, ROLLBACK_CF] {
+                let cf = self.get_column_family(cf)?;
+                // Restore the old values and keep the keys that have one
+                let mut keys_with_old_value = HashSet::<String>::new();
+                for (key_str, val, _) in
+                    iter_diffs_prefix(self, cf, height, None, true)
+                {
+                    batch.0.put_cf(subspace_cf, &key_str, val);
+                    keys_with_old_value.insert(key_str);
+                }
+                // Delete the keys that were newly written in the block
+                for (key_str, _val, _) in
+                    iter_diffs_prefix(self, cf, height, None, false)
+                {
+                    if !keys_with_old_value.contains(&key_str) {
+                        batch.0.delete_cf(subspace_cf, key_str)
+                    }
+                }
+                // Delete the diffs of the block
+                let height_prefix = Key::from(height.to_db_key());
+                for (key_str, _val, _) in
+                    iter_prefix(self, cf, None, Some(&height_prefix))
+                {
+                    batch.0.delete_cf(cf, key_str)
+                }
+            }
+
+            // Delete the block's metadata, results and events
+            let prefix = height.raw();
+            for segment in [
+                BLOCK_HEADER_KEY_SEGMENT,
+                BLOCK_TIME_KEY_SEGMENT,
+                EPOCH_KEY_SEGMENT,
+                PRED_EPOCHS_KEY_SEGMENT,
+                ADDRESS_GEN_KEY_SEGMENT,
+            ] {
+                batch.0.delete_cf(block_cf, format!("{prefix}/{segment}"));
+            }
+            batch
+                .0
+                .delete_cf(block_cf, format!("{RESULTS_KEY_PREFIX}/{prefix}"));
+            for st in StoreType::iter().filter(|st| st.is_stored_every_block())
+            {
+                self.delete_merkle_tree_store(
+                    &mut batch,
+                    st,
+                    Either::Left(height),
+                )?;
+            }
+            event_store::delete_events_to_batch(self, &mut batch, height)?;
+
+            // Remove the tx hashes of the block from replay protection. The
+            // last block's hashes are still in the current bucket.
+            if height == last_block.height {
+                for (current_key, _, _) in self.iter_current_replay_protection()
+                {
+                    batch.0.delete_cf(reprot_cf, current_key);
+                }
+            } else {
+                for hash in self.iter_rollback_journal_hashes(height)? {
+                    batch.0.delete_cf(
+                        reprot_cf,
+                        replay_protection::key(&hash).to_string(),
+                    );
+                }
+            }
+
+            height = height
+                .prev_height()
+                .expect("The height must be above the target");
+        }
+
+        // Move the tx hashes of the target block back to the current bucket
+        tracing::info!("Restoring replay protection state");
+        for hash in self.iter_rollback_journal_hashes(target)? {
+            batch.0.delete_cf(
+                reprot_cf,
+                replay_protection::key(&hash).to_string(),
+            );
+            batch.0.put_cf(
+                reprot_cf,
+                replay_protection::current_key(&hash).to_string(),
+                vec![],
+            );
+        }
+
+        // Delete the data of the epochs after the target one
+        let mut epoch = last_block.epoch;
+        while epoch > target_epoch {
+            tracing::info!("Removing the Merkle tree stores of epoch {epoch}");
+            for st in StoreType::iter().filter(|st| !st.is_stored_every_block())
+            {
+                self.delete_merkle_tree_store(
+                    &mut batch,
+                    st,
+                    Either::Right(epoch),
+                )?;
+            }
+            epoch = epoch.prev().expect("The epoch must be above the target");
+        }
+
+        // Restore the non-height-prepended metadata storage keys and their
+        // predecessors from the journal
+        tracing::info!("Reverting non-height-prepended metadata keys");
+        batch.0.put_cf(state_cf, BLOCK_HEIGHT_KEY, encode(&target));
+        let journal_prefix = rollback_height_prefix(target);
+        let pred_journal_prefix =
+            target.prev_height().map(rollback_height_prefix);
+        for (state_key, has_pred) in ROLLBACK_JOURNAL_STATE_KEYS {
+            let journal_key = format!("{journal_prefix}/{state_key}");
+            let value = self
+                .read_value_bytes(block_cf, &journal_key)?
+                .ok_or(Error::UnknownKey { key: journal_key })?;
+            self.add_value_bytes_to_batch(
+                state_cf, state_key, value, &mut batch,
+            );
+            if has_pred {
+                let pred_value = match &pred_journal_prefix {
+                    Some(prefix) => self.read_value_bytes(
+                        block_cf,
+                        format!("{prefix}/{state_key}"),
+                    )?,
+                    None => None,
+                };
+                self.put_or_delete_pred_value(
+                    state_cf, state_key, pred_value, &mut batch,
+                );
+            }
+        }
+
+        // Revert the conversion state if the epoch had been changed
+        if target_epoch != last_block.epoch {
+            let conversion_state_key = format!(
+                "{}/{CONVERSION_STATE_KEY}",
+                rollback_epoch_prefix(target_epoch)
+            );
+            let value = self
+                .read_value_bytes(block_cf, &conversion_state_key)?
+                .ok_or(Error::UnknownKey {
+                    key: conversion_state_key,
+                })?;
+            self.add_value_bytes_to_batch(
+                state_cf,
+                CONVERSION_STATE_KEY,
+                value,
+                &mut batch,
+            );
+            let pred_value = match target_epoch.prev() {
+                Some(epoch) => self.read_value_bytes(
+                    block_cf,
+                    format!(
+                        "{}/{CONVERSION_STATE_KEY}",
+                        rollback_epoch_prefix(epoch)
+                    ),
+                )?,
+                None => None,
+            };
+            self.put_or_delete_pred_value(
+                state_cf,
+                CONVERSION_STATE_KEY,
+                pred_value,
+                &mut batch,
+            );
+        }
+
+        // Delete the journal of the reverted blocks and epochs
+        batch.0.delete_range_cf(
+            block_cf,
+            rollback_height_prefix(target.next_height()),
+            rollback_height_prefix(last_block.height.next_height()),
+        );
+        batch.0.delete_range_cf(
+            block_cf,
+            rollback_epoch_prefix(target_epoch.next()),
+            rollback_epoch_prefix(last_block.epoch.next()),
+        );
+
+        // Write the batch and persist changes to disk
+        tracing::info!("Flushing restored state to disk");
+        self.exec_batch(batch)
+    }
+
+    /// Read the tx hashes of the block at the given height from the rollback
+    /// journal
+    fn iter_rollback_journal_hashes(
+        &self,
+        height: BlockHeight,
+    ) -> Result<impl Iterator<Item = Hash> + '_> {
+        let block_cf = self.get_column_family(BLOCK_CF)?;
+        let prefix = Key::parse(format!(
+            "{}/{REPLAY_PROTECTION_KEY_SEGMENT}",
+            rollback_height_prefix(height)
+        ))
+        .map_err(Error::KeyError)?;
+        Ok(iter_prefix(self, block_cf, Some(&prefix), None).map(
+            |(hash_str, _, _)| {
+                Hash::from_str(&hash_str).expect("Failed hash conversion")
+            },
+        ))
+    }
+
+    /// Write the predecessor value of the given state key if any, otherwise
+    /// delete it
+    fn put_or_delete_pred_value(
+        &self,
+        cf: &ColumnFamily,
+        key: &str,
+        pred_value: Option<Vec<u8>>,
+        batch: &mut RocksDBWriteBatch,
+    ) {
+        let pred_key = format!("{PRED_KEY_PREFIX}/{key}");
+        match pred_value {
+            Some(value) => {
+                self.add_value_bytes_to_batch(cf, pred_key, value, batch)
+            }
+            None => batch.0.delete_cf(cf, pred_key),
+        }
+    }
+
+    /// Delete the Merkle tree store of the given type at the given height or
+    /// epoch
+    fn delete_merkle_tree_store(
+        &self,
+        batch: &mut RocksDBWriteBatch,
+        store_type: &StoreType,
+        target: Either<BlockHeight, Epoch>,
+    ) -> Result<()> {
+        let block_cf = self.get_column_family(BLOCK_CF)?;
+        let key_prefix = match target {
+            Either::Left(height) => {
+                tree_key_prefix_with_height(store_type, height)
+            }
+            Either::Right(epoch) => {
+                tree_key_prefix_with_epoch(store_type, epoch)
+            }
+        };
+        let root_key = format!("{key_prefix}/{MERKLE_TREE_ROOT_KEY_SEGMENT}");
+        batch.0.delete_cf(block_cf, root_key);
+        let store_key = format!("{key_prefix}/{MERKLE_TREE_STORE_KEY_SEGMENT}");
+        batch.0.delete_cf(block_cf, store_key);
+        Ok(())
+    }
+
     #[inline]
-    pub fn column_families(&self) -> [(&'static str, &ColumnFamily); 6] {
+    pub fn column_families(&self) -> [(&'static str, &ColumnFamily); 7] {
         DbColFam::all()
             .iter()
             .map(|cf| {
@@ -744,7 +1321,7 @@ impl RocksDB {
             })
             .collect::<Vec<_>>()
             .try_into()
-            .map_err(|_| "There should be exactly six column families")
+            .map_err(|_| "There should be exactly seven column families")
             .unwrap()
     }
 
@@ -833,6 +1410,14 @@ impl SnapshotPath {
         buf
     }
 
+    /// Return the path of the format of the snapshot associated with this
+    /// [`SnapshotPath`].
+    pub fn format(&self) -> PathBuf {
+        let mut buf = self.base();
+        buf.push("format");
+        buf
+    }
+
     /// Return the temporary rocksdb path associated with this [`SnapshotPath`].
     pub fn temp_rocksdb(&self) -> PathBuf {
         let mut buf = self.base();
@@ -859,11 +1444,67 @@ impl SnapshotPath {
     }
 }
 
+/// The path to the chunks of a snapshot being synced from peers, identified
+/// by its height and hash.
+#[derive(Clone, Debug)]
+pub struct SnapshotSyncPath(pub PathBuf, pub BlockHeight, pub Hash);
+
+impl SnapshotSyncPath {
+    /// Return the root path where the chunks of synced snapshots are stored.
+    pub fn sync_root_path(mut base_dir: PathBuf) -> PathBuf {
+        base_dir.push("state-sync");
+        base_dir
+    }
+
+    /// Remove all the chunks of the current snapshot.
+    pub fn remove(&self) -> std::io::Result<()> {
+        match std::fs::remove_dir_all(self.base()) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+
+    /// Remove the chunks of all the snapshots other than the current one.
+    pub fn remove_others(&self) -> std::io::Result<()> {
+        let root = Self::sync_root_path(self.0.clone());
+        if !root.exists() {
+            return Ok(());
+        }
+        let base = self.base();
+        for entry in std::fs::read_dir(root)? {
+            let path = entry?.path();
+            if path != base {
+                std::fs::remove_dir_all(path)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Return the base path associated with this [`SnapshotSyncPath`].
+    pub fn base(&self) -> PathBuf {
+        let mut buf = Self::sync_root_path(self.0.clone());
+        let height = self.1.0;
+        let hash = self.2;
+        buf.push(format!("block-{height:016}-{hash}"));
+        buf
+    }
+
+    /// Return the path of the chunk `chk` associated with this
+    /// [`SnapshotSyncPath`].
+    pub fn chunk_with_id(&self, chk: u64) -> PathBuf {
+        let mut buf = self.base();
+        buf.push(format!("chunk-{chk:032}"));
+        buf
+    }
+}
+
 /// Metadata pertaining to some database snapshot.
 #[derive(Debug)]
 pub struct DbSnapshotMeta {
     /// The height of the snapshot.
     pub height: BlockHeight,
+    /// The format of the snapshot.
+    pub format: u32,
     /// List of the hashes of all chunks.
     pub chunk_hashes: Vec<Hash>,
     /// Hash of all the chunk hashes, forming a shallow tree.
@@ -876,6 +1517,9 @@ pub struct DbSnapshot(pub SnapshotPath);
 impl DbSnapshot {
     /// The magic number referring to the format of the snapshot.
     pub const FORMAT_MAGIC: u32 = 0;
+    /// The magic number referring to the format of the key-value snapshots,
+    /// whose chunks are [`KvSnapshotItem`]s.
+    pub const KV_FORMAT_MAGIC: u32 = 1;
 
     /// Package and chunk the contents of the db snapshot.
     // NB: passing an owned `self` guarantees we don't attempt to call
@@ -887,6 +1531,33 @@ impl DbSnapshot {
         Ok(())
     }
 
+    /// Stream the contents of the db snapshot into key-value chunks.
+    // NB: passing an owned `self` guarantees we don't attempt to call
+    // this method again, which removes the temporary checkpoint dir
+    // created by rocksdb
+    pub fn package_kv(self) -> std::io::Result<()> {
+        let snapshot_temp_db_path = self.0.temp_rocksdb();
+        let mut chunk_hashes = vec![];
+        {
+            let db = open(&snapshot_temp_db_path, true, None)
+                .map_err(std::io::Error::other)?;
+            let mut chunk_id = 0;
+            db.kv_snapshot_items(MAX_STATE_SYNC_CHUNK_SIZE, |item| {
+                let chunk = item.serialize_to_vec();
+                std::fs::write(self.0.chunk_with_id(chunk_id), &chunk)
+                    .map_err(|e| Error::DBError(e.to_string()))?;
+                chunk_hashes.push(Hash::sha256(&chunk));
+                chunk_id = checked!(chunk_id + 1)?;
+                Ok(())
+            })
+            .map_err(std::io::Error::other)?;
+        }
+        self.write_chunk_hashes(chunk_hashes, Self::KV_FORMAT_MAGIC)?;
+
+        // remove aux checkpoint dir
+        std::fs::remove_dir_all(&snapshot_temp_db_path)
+    }
+
     pub fn unpack(
         archive_file: &mut std::fs::File,
         dest: impl AsRef<Path>,
@@ -966,15 +1637,25 @@ impl DbSnapshot {
             }
         }
 
+        std::fs::remove_file(tarball_path)?;
+        self.write_chunk_hashes(chunk_hashes, Self::FORMAT_MAGIC)
+    }
+
+    /// Write the hashes of the chunks of the snapshot and the root hash of
+    /// the snapshot, which commits to its format and its chunks.
+    fn write_chunk_hashes(
+        &self,
+        chunk_hashes: Vec<Hash>,
+        format: u32,
+    ) -> std::io::Result<()> {
         let chunk_hashes = chunk_hashes.serialize_to_vec();
         let hash_of_all_chunks = Hash::sha256(&chunk_hashes);
-        let snapshot_hash = Hash::sha256(
-            (Self::FORMAT_MAGIC, hash_of_all_chunks).serialize_to_vec(),
-        );
+        let snapshot_hash =
+            Hash::sha256((format, hash_of_all_chunks).serialize_to_vec());
 
-        std::fs::remove_file(tarball_path)?;
         std::fs::write(self.0.chunk_hashes(), chunk_hashes)?;
         std::fs::write(self.0.chunks_root_hash(), snapshot_hash)?;
+        std::fs::write(self.0.format(), format.serialize_to_vec())?;
 
         Ok(())
     }
@@ -1018,9 +1699,19 @@ impl DbSnapshot {
                 let root_hash = BorshDeserialize::try_from_slice(
                     &std::fs::read(snap.chunks_root_hash())?,
                 )?;
+                // Snapshots without a format file predate the key-value
+                // format
+                let format = match std::fs::read(snap.format()) {
+                    Ok(bytes) => BorshDeserialize::try_from_slice(&bytes)?,
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                        Self::FORMAT_MAGIC
+                    }
+                    Err(e) => return Err(e),
+                };
 
                 Ok(DbSnapshotMeta {
                     height,
+                    format,
                     chunk_hashes,
                     root_hash,
                 })
@@ -1073,10 +1764,300 @@ impl DbSnapshot {
     }
 }
 
+/// An item of a key-value snapshot (see [`DbSnapshot::KV_FORMAT_MAGIC`]).
+/// Every chunk of such snapshot is a single Borsh encoded item.
+#[derive(Debug, BorshSerialize, BorshDeserialize)]
+pub enum KvSnapshotItem {
+    /// Key-vals of a column family that are not committed to by the Merkle
+    /// tree, i.e. the state of the last block and the replay protection
+    /// entries
+    Raw {
+        cf: String,
+        entries: Vec<(String, Vec<u8>)>,
+    },
+    /// Subspace key-vals of a Merkle tree store, sorted by their keys
+    Subspace {
+        store_type: StoreType,
+        entries: Vec<(Key, Vec<u8>)>,
+    },
+    /// The heights at which the pending bridge pool transfers were added,
+    /// which the bridge pool store commits to in place of their values
+    BridgePoolHeights(Vec<(Key, BlockHeight)>),
+}
+
+/// The keys of the entries of the last block in the block column family that
+/// are part of a key-value snapshot
+fn kv_snapshot_block_keys(height: BlockHeight) -> Vec<String> {
+    let prefix = height.raw();
+    [
+        BLOCK_HEADER_KEY_SEGMENT,
+        BLOCK_TIME_KEY_SEGMENT,
+        EPOCH_KEY_SEGMENT,
+        PRED_EPOCHS_KEY_SEGMENT,
+        ADDRESS_GEN_KEY_SEGMENT,
+    ]
+    .into_iter()
+    .map(|segment| format!("{prefix}/{segment}"))
+    .chain([format!("{RESULTS_KEY_PREFIX}/{prefix}")])
+    .collect()
+}
+
+/// Entries of a key-value snapshot item being filled up
+struct KvSnapshotBuffer<K> {
+    entries: Vec<(K, Vec<u8>)>,
+    size: usize,
+}
+
+impl<K> Default for KvSnapshotBuffer<K> {
+    fn default() -> Self {
+        Self {
+            entries: vec![],
+            size: 0,
+        }
+    }
+}
+
+impl<K> KvSnapshotBuffer<K> {
+    /// Add an entry and return all the entries once their size reaches the
+    /// given maximum.
+    fn push(
+        &mut self,
+        key: K,
+        key_len: usize,
+        value: Vec<u8>,
+        max_size: usize,
+    ) -> Option<Vec<(K, Vec<u8>)>> {
+        self.size = self
+            .size
+            .saturating_add(key_len)
+            .saturating_add(value.len());
+        self.entries.push((key, value));
+        (self.size >= max_size).then(|| self.take())
+    }
+
+    /// Take all the entries.
+    fn take(&mut self) -> Vec<(K, Vec<u8>)> {
+        self.size = 0;
+        std::mem::take(&mut self.entries)
+    }
+}
+
+/// A key-value snapshot being restored chunk by chunk into a new DB, next to
+/// the DB of the node. The Merkle tree is rebuilt from the restored subspace
+/// as the chunks are applied and the restored DB is only accepted if its root
+/// matches the app hash of the snapshot's height, as provided by CometBFT.
+///
+/// The state of the last block and the replay protection entries are not
+/// committed to by the app hash. The last block's entries are only accepted
+/// for the snapshot's height, but the replay protection entries are trusted
+/// as they are.
+pub struct KvSnapshotRestore {
+    /// The DB being restored
+    db: RocksDB,
+    /// The height of the snapshot
+    height: BlockHeight,
+    /// The Merkle tree rebuilt from the restored subspace
+    tree: MerkleTree<Sha256Hasher>,
+    /// The last key restored into each Merkle tree store
+    last_keys: HashMap<StoreType, String>,
+    /// The trusted app hash of the snapshot's height
+    app_hash: Vec<u8>,
+    /// The filter of the keys whose diffs are persisted, the others being
+    /// committed to by the no-diff store
+    diff_key_filter: fn(&Key) -> bool,
+}
+
+impl std::fmt::Debug for KvSnapshotRestore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KvSnapshotRestore")
+            .field("db", &self.db)
+            .field("app_hash", &HEXLOWER.encode(&self.app_hash))
+            .finish_non_exhaustive()
+    }
+}
+
+impl KvSnapshotRestore {
+    /// Start restoring a key-value snapshot of the given height with its
+    /// trusted app hash next to the DB in `db_dir`. The `diff_key_filter` must
+    /// be the one of the chain's state.
+    pub fn new(
+        db_dir: &Path,
+        height: BlockHeight,
+        app_hash: Vec<u8>,
+        diff_key_filter: fn(&Key) -> bool,
+    ) -> Result<Self> {
+        let restore_dir = db_dir.with_extension("state-sync");
+        // Discard any previous incomplete restoration
+        if restore_dir.exists() {
+            std::fs::remove_dir_all(&restore_dir)
+                .map_err(|e| Error::DBError(e.to_string()))?;
+        }
+        Ok(Self {
+            db: open(restore_dir, false, None)?,
+            height,
+            tree: MerkleTree::default(),
+            last_keys: HashMap::new(),
+            app_hash,
+            diff_key_filter,
+        })
+    }
+
+    /// Apply a chunk of the snapshot to the restored DB and Merkle tree.
+    pub fn apply_chunk(&mut self, chunk: &[u8]) -> Result<()> {
+        let item = KvSnapshotItem::try_from_slice(chunk)
+            .map_err(Error::BorshCodingError)?;
+        let mut batch = RocksDB::batch();
+        match item {
+            KvSnapshotItem::Raw { cf, entries } => {
+                if ![STATE_CF, BLOCK_CF, REPLAY_PROTECTION_CF]
+                    .contains(&cf.as_str())
+                {
+                    return Err(Error::DBError(format!(
+                        "The column family {cf} cannot be restored from raw \
+                         entries"
+                    )));
+                }
+                let block_keys = (cf == BLOCK_CF)
+                    .then(|| kv_snapshot_block_keys(self.height));
+                let cf = self.db.get_column_family(&cf)?;
+                for (key, value) in entries {
+                    if block_keys
+                        .as_ref()
+                        .is_some_and(|block_keys| !block_keys.contains(&key))
+                    {
+                        return Err(Error::DBError(format!(
+                            "The block key {key} is not part of the last \
+                             block at height {}",
+                            self.height
+                        )));
+                    }
+                    self.db
+                        .add_value_bytes_to_batch(cf, key, value, &mut batch);
+                }
+            }
+            KvSnapshotItem::Subspace {
+                store_type,
+                entries,
+            } => {
+                let subspace_cf = self.db.get_column_family(SUBSPACE_CF)?;
+                for (key, value) in entries {
+                    if StoreType::sub_key(&key)?.0 != store_type {
+                        return Err(Error::DBError(format!(
+                            "The key {key} doesn't belong to the {store_type} \
+                             store"
+                        )));
+                    }
+                    let key_str = key.to_string();
+                    if self
+                        .last_keys
+                        .get(&store_type)
+                        .is_some_and(|last_key| *last_key >= key_str)
+                    {
+                        return Err(Error::DBError(format!(
+                            "The keys of the {store_type} store are not \
+                             sorted at key {key}"
+                        )));
+                    }
+                    // The pending transfers are committed with their heights
+                    // and the keys without diffs under the no-diff prefix
+                    if !is_pending_transfer_key(&key) {
+                        if (self.diff_key_filter)(&key) {
+                            self.tree.update(&key, &value)?;
+                        } else {
+                            let prefix = Key::from(
+                                NO_DIFF_KEY_PREFIX.to_string().to_db_key(),
+                            );
+                            self.tree.update(&prefix.join(&key), &value)?;
+                        }
+                    }
+                    self.db.add_value_bytes_to_batch(
+                        subspace_cf,
+                        &key_str,
+                        value,
+                        &mut batch,
+                    );
+                    self.last_keys.insert(store_type, key_str);
+                }
+            }
+            KvSnapshotItem::BridgePoolHeights(heights) => {
+                for (key, height) in heights {
+                    if !is_pending_transfer_key(&key) {
+                        return Err(Error::DBError(format!(
+                            "The key {key} is not a pending bridge pool \
+                             transfer"
+                        )));
+                    }
+                    self.tree.update(&key, height.serialize_to_vec())?;
+                }
+            }
+        }
+        self.db.exec_batch(batch)
+    }
+
+    /// Check the root of the rebuilt Merkle tree against the trusted app hash
+    /// and write the tree's stores for the restored block. Returns the path
+    /// of the restored DB, which is removed if the check fails.
+    pub fn finalize(mut self) -> Result<PathBuf> {
+        let restore_dir = self.db.inner.path().to_owned();
+        let result = self.commit_merkle_tree();
+        drop(self);
+        match result {
+            Ok(()) => Ok(restore_dir),
+            Err(err) => {
+                std::fs::remove_dir_all(&restore_dir)
+                    .map_err(|e| Error::DBError(e.to_string()))?;
+                Err(err)
+            }
+        }
+    }
+
+    fn commit_merkle_tree(&mut self) -> Result<()> {
+        let last_block = self.db.read_last_block()?.ok_or(Error::DBError(
+            "Missing last block in the snapshot".to_string(),
+        ))?;
+        if last_block.height != self.height {
+            return Err(Error::DBError(format!(
+                "The last block height {} of the snapshot doesn't match its \
+                 height {}",
+                last_block.height, self.height,
+            )));
+        }
+        self.tree
+            .update_commit_data(last_block.commit_only_data.serialize())?;
+        let root = self.tree.root();
+        if root.0.as_slice() != self.app_hash.as_slice() {
+            return Err(Error::DBError(format!(
+                "The Merkle root {} of the snapshot doesn't match the trusted \
+                 app hash {}",
+                HEXLOWER.encode(&root.0),
+                HEXLOWER.encode(&self.app_hash),
+            )));
+        }
+        let mut batch = RocksDB::batch();
+        self.db.add_merkle_tree_stores_to_batch(
+            &self.tree.stores(),
+            last_block.height,
+            last_block.epoch,
+            true,
+            &mut batch,
+        )?;
+        self.db.exec_batch(batch)?;
+        self.db.flush(true)
+    }
+}
+
+/// The source of a DB restored from a state sync snapshot
+pub enum DbSnapshotSource<'a> {
+    /// A tarball of a RocksDB checkpoint
+    Tarball(&'a mut std::fs::File),
+    /// The directory of a DB restored from a key-value snapshot
+    Restored(&'a Path),
+}
+
 impl DB for RocksDB {
     type Cache = rocksdb::Cache;
     type Migrator = RocksDBUpdateVisitor;
-    type RestoreSource<'a> = (&'a rocksdb::Cache, &'a mut std::fs::File);
+    type RestoreSource<'a> = (&'a rocksdb::Cache, DbSnapshotSource<'a>);
     type WriteBatch = RocksDBWriteBatch;
 
     fn open(
@@ -1095,11 +2076,13 @@ impl DB for RocksDB {
 
     fn restore_from(
         &mut self,
-        (cache, snapshot): Self::RestoreSource<'_>,
+        (cache, mut source): Self::RestoreSource<'_>,
     ) -> Result<()> {
-        snapshot.rewind().map_err(|e| {
-            Error::DBError(format!("Failed to rewind snapshot file: {e}",))
-        })?;
+        if let DbSnapshotSource::Tarball(snapshot) = &mut source {
+            snapshot.rewind().map_err(|e| {
+                Error::DBError(format!("Failed to rewind snapshot file: {e}",))
+            })?;
+        }
 
         let db_dir = self.inner.path().to_owned();
 
@@ -1120,8 +2103,16 @@ impl DB for RocksDB {
 
         std::fs::remove_dir_all(&db_dir)
             .expect("Failed to nuke database directory");
-        DbSnapshot::unpack(snapshot, unpack_dir)
-            .expect("Failed to unpack new db");
+        match source {
+            DbSnapshotSource::Tarball(snapshot) => {
+                DbSnapshot::unpack(snapshot, unpack_dir)
+                    .expect("Failed to unpack new db")
+            }
+            DbSnapshotSource::Restored(restored_dir) => {
+                std::fs::rename(restored_dir, &db_dir)
+                    .expect("Failed to move the restored db")
+            }
+        }
 
         *self = Self::open(db_dir, Some(cache));
 
@@ -1336,33 +2327,54 @@ impl DB for RocksDB {
         let block_cf = self.get_column_family(BLOCK_CF)?;
         let prefix = height.raw();
 
-        // Merkle tree
-        for st in StoreType::iter() {
-            if st.is_stored_every_block() || is_full_commit {
-                let key_prefix = if st.is_stored_every_block() {
-                    tree_key_prefix_with_height(st, height)
-                } else {
-                    tree_key_prefix_with_epoch(st, epoch)
-                };
-                let root_key =
-                    format!("{key_prefix}/{MERKLE_TREE_ROOT_KEY_SEGMENT}");
-                self.add_value_to_batch(
-                    block_cf,
-                    root_key,
-                    merkle_tree_stores.root(st),
-                    batch,
-                );
-                let store_key =
-                    format!("{key_prefix}/{MERKLE_TREE_STORE_KEY_SEGMENT}");
-                self.add_value_bytes_to_batch(
-                    block_cf,
-                    store_key,
-                    merkle_tree_stores.store(st).encode(),
-                    batch,
-                );
-            }
+        // Rollback journal of the state values
+        let journal_prefix = rollback_height_prefix(height);
+        for (key, value) in [
+            (
+                NEXT_EPOCH_MIN_START_HEIGHT_KEY,
+                encode(&next_epoch_min_start_height),
+            ),
+            (
+                NEXT_EPOCH_MIN_START_TIME_KEY,
+                encode(&next_epoch_min_start_time),
+            ),
+            (
+                UPDATE_EPOCH_BLOCKS_DELAY_KEY,
+                encode(&update_epoch_blocks_delay),
+            ),
+            (COMMIT_ONLY_DATA_KEY, encode(&commit_only_data)),
+            (ETHEREUM_HEIGHT_KEY, encode(&ethereum_height)),
+            (ETH_EVENTS_QUEUE_KEY, encode(&eth_events_queue)),
+        ] {
+            self.add_value_bytes_to_batch(
+                block_cf,
+                format!("{journal_prefix}/{key}"),
+                value,
+                batch,
+            );
+        }
+        if is_full_commit {
+            let conversion_state_key = format!(
+                "{}/{CONVERSION_STATE_KEY}",
+                rollback_epoch_prefix(epoch)
+            );
+            self.add_value_to_batch(
+                block_cf,
+                conversion_state_key,
+                &conversion_state,
+                batch,
+            );
         }
 
+        // Merkle tree
+        self.add_merkle_tree_stores_to_batch(
+            &merkle_tree_stores,
+            height,
+            epoch,
+            is_full_commit,
+            batch,
+        )?;
+
         // Block header
         if let Some(h) = header {
             let header_key = format!("{prefix}/{BLOCK_HEADER_KEY_SEGMENT}");
@@ -1477,6 +2489,40 @@ impl DB for RocksDB {
         self.read_value_bytes(diffs_cf, key)
     }
 
+    fn read_subspace_changeset(
+        &self,
+        height: BlockHeight,
+    ) -> Result<Vec<SubspaceChange>> {
+        let mut changes = BTreeMap::<String, SubspaceChange>::new();
+        for cf in [DIFFS_CF, ROLLBACK_CF] {
+            let cf = self.get_column_family(cf)?;
+            for is_old in [true, false] {
+                for (key_str, val, _) in
+                    iter_diffs_prefix(self, cf, height, None, is_old)
+                {
+                    let change = match changes.entry(key_str) {
+                        btree_map::Entry::Occupied(entry) => entry.into_mut(),
+                        btree_map::Entry::Vacant(entry) => {
+                            let key = Key::parse(entry.key())
+                                .map_err(Error::KeyError)?;
+                            entry.insert(SubspaceChange {
+                                key,
+                                old_value: None,
+                                new_value: None,
+                            })
+                        }
+                    };
+                    if is_old {
+                        change.old_value = Some(val);
+                    } else {
+                        change.new_value = Some(val);
+                    }
+                }
+            }
+        }
+        Ok(changes.into_values().collect())
+    }
+
     fn read_subspace_val(&self, key: &Key) -> Result<Option<Vec<u8>>> {
         let subspace_cf = self.get_column_family(SUBSPACE_CF)?;
         self.read_value_bytes(subspace_cf, key.to_string())
@@ -1675,20 +2721,7 @@ impl DB for RocksDB {
         store_type: &StoreType,
         pruned_target: Either<BlockHeight, Epoch>,
     ) -> Result<()> {
-        let block_cf = self.get_column_family(BLOCK_CF)?;
-        let key_prefix = match pruned_target {
-            Either::Left(height) => {
-                tree_key_prefix_with_height(store_type, height)
-            }
-            Either::Right(epoch) => {
-                tree_key_prefix_with_epoch(store_type, epoch)
-            }
-        };
-        let root_key = format!("{key_prefix}/{MERKLE_TREE_ROOT_KEY_SEGMENT}");
-        batch.0.delete_cf(block_cf, root_key);
-        let store_key = format!("{key_prefix}/{MERKLE_TREE_STORE_KEY_SEGMENT}");
-        batch.0.delete_cf(block_cf, store_key);
-        Ok(())
+        self.delete_merkle_tree_store(batch, store_type, pruned_target)
     }
 
     fn read_bridge_pool_signed_nonce(
@@ -1736,7 +2769,13 @@ impl DB for RocksDB {
     ) -> Result<()> {
         let replay_protection_cf =
             self.get_column_family(REPLAY_PROTECTION_CF)?;
+        let block_cf = self.get_column_family(BLOCK_CF)?;
+        let state_cf = self.get_column_family(STATE_CF)?;
         let stripped_prefix = Some(replay_protection::current_prefix());
+        // The current hashes belong to the last committed block
+        let journal_prefix = self
+            .read_value::<BlockHeight>(state_cf, BLOCK_HEIGHT_KEY)?
+            .map(rollback_height_prefix);
 
         for (hash_str, _, _) in iter_prefix(
             self,
@@ -1756,6 +2795,17 @@ impl DB for RocksDB {
             batch
                 .0
                 .put_cf(replay_protection_cf, key.to_string(), vec![]);
+            // Journal the hash to be able to restore the current bucket
+            if let Some(journal_prefix) = &journal_prefix {
+                batch.0.put_cf(
+                    block_cf,
+                    format!(
+                        "{journal_prefix}/{REPLAY_PROTECTION_KEY_SEGMENT}/\
+                         {hash_str}"
+                    ),
+                    vec![],
+                );
+            }
         }
 
         Ok(())
@@ -1767,30 +2817,35 @@ impl DB for RocksDB {
         height: BlockHeight,
     ) -> Result<()> {
         let rollback_cf = self.get_column_family(ROLLBACK_CF)?;
+        // The diffs are sorted by height. Pruning all the heights up to the
+        // given one also removes the diffs left behind when the rollback
+        // window of the node is lowered.
+        batch.0.delete_range_cf(
+            rollback_cf,
+            BlockHeight(0).raw(),
+            height.next_height().raw(),
+        );
+        Ok(())
+    }
 
-        let diff_old_key_prefix = Key {
-            segments: vec![
-                height.to_db_key(),
-                OLD_DIFF_PREFIX.to_string().to_db_key(),
-            ],
-        };
-        for (key_str, _val, _) in
-            iter_prefix(self, rollback_cf, None, Some(&diff_old_key_prefix))
-        {
-            batch.0.delete_cf(rollback_cf, key_str)
-        }
-
-        let diff_new_key_prefix = Key {
-            segments: vec![
-                height.to_db_key(),
-                NEW_DIFF_PREFIX.to_string().to_db_key(),
-            ],
-        };
-        for (key_str, _val, _) in
-            iter_prefix(self, rollback_cf, None, Some(&diff_new_key_prefix))
-        {
-            batch.0.delete_cf(rollback_cf, key_str)
-        }
+    fn prune_rollback_journal(
+        &mut self,
+        batch: &mut Self::WriteBatch,
+        oldest_height: BlockHeight,
+        oldest_epoch: Epoch,
+    ) -> Result<()> {
+        let block_cf = self.get_column_family(BLOCK_CF)?;
+        // The journal keys are sorted by the height and the epoch
+        batch.0.delete_range_cf(
+            block_cf,
+            rollback_height_prefix(BlockHeight(0)),
+            rollback_height_prefix(oldest_height),
+        );
+        batch.0.delete_range_cf(
+            block_cf,
+            rollback_epoch_prefix(Epoch(0)),
+            rollback_epoch_prefix(oldest_epoch),
+        );
         Ok(())
     }
 
@@ -1830,6 +2885,45 @@ impl DB for RocksDB {
         Ok(())
     }
 
+    fn read_event_entry(&self, key: &Key) -> Result<Option<Vec<u8>>> {
+        let events_cf = self.get_column_family(EVENTS_CF)?;
+        self.read_value_bytes(events_cf, key.to_string())
+    }
+
+    fn batch_write_event_entry(
+        &self,
+        batch: &mut Self::WriteBatch,
+        key: &Key,
+        value: impl AsRef<[u8]>,
+    ) -> Result<()> {
+        let events_cf = self.get_column_family(EVENTS_CF)?;
+        batch.0.put_cf(events_cf, key.to_string(), value.as_ref());
+        Ok(())
+    }
+
+    fn batch_delete_event_entry(
+        &self,
+        batch: &mut Self::WriteBatch,
+        key: &Key,
+    ) -> Result<()> {
+        let events_cf = self.get_column_family(EVENTS_CF)?;
+        batch.0.delete_cf(events_cf, key.to_string());
+        Ok(())
+    }
+
+    fn batch_delete_event_range(
+        &self,
+        batch: &mut Self::WriteBatch,
+        from: &Key,
+        to: &Key,
+    ) -> Result<()> {
+        let events_cf = self.get_column_family(EVENTS_CF)?;
+        batch
+            .0
+            .delete_range_cf(events_cf, from.to_string(), to.to_string());
+        Ok(())
+    }
+
     fn migrator() -> Self::Migrator {
         RocksDBUpdateVisitor::default()
     }
@@ -1975,6 +3069,17 @@ impl<'iter> DBIter<'iter> for RocksDB {
         iter_subspace_prefix(self, prefix)
     }
 
+    fn iter_prefix_range(
+        &'iter self,
+        prefix: Option<&Key>,
+        range: &KeyRange,
+    ) -> PersistentPrefixIterator<'iter> {
+        let subspace_cf = self
+            .get_column_family(SUBSPACE_CF)
+            .expect("{SUBSPACE_CF} column family should exist");
+        iter_prefix_range(self, subspace_cf, prefix, range)
+    }
+
     fn iter_pattern(
         &'iter self,
         prefix: Option<&Key>,
@@ -2029,6 +3134,13 @@ impl<'iter> DBIter<'iter> for RocksDB {
         let prefix = Some(replay_protection::current_prefix());
         iter_prefix(self, replay_protection_cf, None, prefix.as_ref())
     }
+
+    fn iter_event_entries(&'iter self, prefix: &Key) -> Self::PrefixIter {
+        let events_cf = self
+            .get_column_family(EVENTS_CF)
+            .expect("{EVENTS_CF} column family should exist");
+        iter_prefix(self, events_cf, None, Some(prefix))
+    }
 }
 
 fn iter_subspace_prefix<'iter>(
@@ -2104,6 +3216,47 @@ fn iter_prefix<'a>(
     PersistentPrefixIterator(PrefixIterator::new(iter, stripped_prefix))
 }
 
+/// Create an iterator over key-vals in the given CF matching the given prefix
+/// and within the given range, in the range's direction. The DB iterator is
+/// bounded by the range, so that the entries outside of it are not visited.
+fn iter_prefix_range<'a>(
+    db: &'a RocksDB,
+    cf: &'a ColumnFamily,
+    prefix: Option<&Key>,
+    range: &KeyRange,
+) -> PersistentPrefixIterator<'a> {
+    let prefix = match prefix {
+        Some(p) if !p.is_empty() => format!("{p}/"),
+        _ => "".to_owned(),
+    };
+    let mut read_opts = make_iter_read_opts(None);
+    // The lower bound is inclusive
+    let lower_bound = match &range.start {
+        Some(start) if start > &prefix => start.clone(),
+        _ => prefix.clone(),
+    };
+    read_opts.set_iterate_lower_bound(lower_bound.into_bytes());
+    // The upper bound is exclusive
+    let prefix_upper_bound = prefix_upper_bound(&prefix);
+    let upper_bound = match (&range.end, prefix_upper_bound) {
+        (Some(end), Some(prefix_end)) => {
+            Some(std::cmp::min(end.as_bytes().to_vec(), prefix_end))
+        }
+        (Some(end), None) => Some(end.as_bytes().to_vec()),
+        (None, prefix_end) => prefix_end,
+    };
+    if let Some(upper_bound) = upper_bound {
+        read_opts.set_iterate_upper_bound(upper_bound);
+    }
+    let mode = if range.rev {
+        IteratorMode::End
+    } else {
+        IteratorMode::Start
+    };
+    let iter = db.inner.iterator_cf_opt(cf, read_opts, mode);
+    PersistentPrefixIterator(PrefixIterator::new(iter, "".to_owned()))
+}
+
 /// Create an iterator over key-vals in the given CF matching the given
 /// pattern(s).
 fn iter_pattern<'a>(
@@ -2184,19 +3337,44 @@ fn make_iter_read_opts(prefix: Option<String>) -> ReadOptions {
     // don't use the prefix bloom filter
     read_opts.set_total_order_seek(true);
 
-    if let Some(prefix) = prefix {
-        let mut upper_prefix = prefix.into_bytes();
-        if let Some(last) = upper_prefix.last_mut() {
-            *last = last.checked_add(1).expect("cannot overflow");
-            read_opts.set_iterate_upper_bound(upper_prefix);
-        }
+    if let Some(upper_prefix) =
+        prefix.and_then(|prefix| prefix_upper_bound(&prefix))
+    {
+        read_opts.set_iterate_upper_bound(upper_prefix);
     }
 
     read_opts
 }
 
+/// The exclusive upper bound of the keys matching the given prefix, if the
+/// prefix is not empty
+fn prefix_upper_bound(prefix: &str) -> Option<Vec<u8>> {
+    let mut upper_prefix = prefix.as_bytes().to_vec();
+    let last = upper_prefix.last_mut()?;
+    *last = last.checked_add(1).expect("cannot overflow");
+    Some(upper_prefix)
+}
+
 impl DBWriteBatch for RocksDBWriteBatch {}
 
+/// The prefix of the rollback journal of the block at the given height
+fn rollback_height_prefix(height: BlockHeight) -> String {
+    format!(
+        "{ROLLBACK_KEY_PREFIX}/{HEIGHT_KEY_SEGMENT}/{}",
+        height.raw()
+    )
+}
+
+/// The prefix of the rollback journal of the given epoch
+fn rollback_epoch_prefix(epoch: Epoch) -> String {
+    // Use the fixed-width encoding of the epoch to keep the journal keys
+    // sorted by epoch
+    format!(
+        "{ROLLBACK_KEY_PREFIX}/{EPOCH_KEY_SEGMENT}/{}",
+        epoch.0.raw()
+    )
+}
+
 fn old_and_new_diff_key(
     key: &Key,
     height: BlockHeight,
@@ -2274,7 +3452,9 @@ mod imp {
 #[cfg(test)]
 mod test {
     use namada_apps_lib::collections::HashMap;
-    use namada_sdk::address::EstablishedAddressGen;
+    use namada_sdk::address::{
+        Address, EstablishedAddressGen, InternalAddress,
+    };
     use namada_sdk::state::{MerkleTree, Sha256Hasher};
     use namada_sdk::storage::conversion_state::ConversionState;
     use namada_sdk::storage::types::CommitOnlyData;
@@ -2635,6 +3815,248 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_rollback_to_height() {
+        let dir = tempdir().unwrap();
+        let mut db = RocksDB::open(dir.path(), None);
+
+        // A key with persisted diffs
+        let diffs_key = Key::parse("with_diffs").unwrap();
+        // A key without persisted diffs, that's deleted in the third block
+        let no_diffs_key = Key::parse("without_diffs").unwrap();
+        // A key that's added in the third block
+        let add_key = Key::parse("add").unwrap();
+
+        let conversion_state = ConversionState::default();
+        let mut pred_epochs = Epochs::default();
+        for height in 1..=4_u64 {
+            let mut batch = RocksDB::batch();
+            let height = BlockHeight(height);
+            let val = vec![u8::try_from(height.0).unwrap()];
+            // The second epoch starts at height 3
+            let epoch = if height.0 < 3 { Epoch(0) } else { Epoch(1) };
+            if height.0 == 3 {
+                pred_epochs.new_epoch(height);
+            }
+
+            db.batch_write_subspace_val(
+                &mut batch, height, &diffs_key, &val, true,
+            )
+            .unwrap();
+            if height.0 == 3 {
+                db.batch_delete_subspace_val(
+                    &mut batch,
+                    height,
+                    &no_diffs_key,
+                    false,
+                )
+                .unwrap();
+                db.batch_write_subspace_val(
+                    &mut batch, height, &add_key, &val, true,
+                )
+                .unwrap();
+            } else {
+                db.batch_write_subspace_val(
+                    &mut batch,
+                    height,
+                    &no_diffs_key,
+                    &val,
+                    false,
+                )
+                .unwrap();
+            }
+
+            db.move_current_replay_protection_entries(&mut batch)
+                .unwrap();
+            db.write_replay_protection_entry(
+                &mut batch,
+                &replay_protection::current_key(&Hash::sha256(val)),
+            )
+            .unwrap();
+
+            add_block_to_batch(
+                &db,
+                &mut batch,
+                height,
+                epoch,
+                pred_epochs.clone(),
+                &conversion_state,
+            )
+            .unwrap();
+            db.exec_batch(batch).unwrap();
+        }
+
+        // Cannot roll back to a height that doesn't exist
+        for height in [BlockHeight(0), BlockHeight(5)] {
+            let result = db.rollback_to_height(height);
+            assert!(matches!(result, Err(Error::CannotRollback { .. })));
+        }
+
+        // Prune the data needed to roll back to the first block
+        let mut batch = RocksDB::batch();
+        db.prune_rollback_journal(&mut batch, BlockHeight(2), Epoch(0))
+            .unwrap();
+        db.exec_batch(batch).unwrap();
+        let result = db.rollback_to_height(BlockHeight(1));
+        assert!(matches!(result, Err(Error::CannotRollback { .. })));
+        let last_block = db.read_last_block().unwrap().unwrap();
+        assert_eq!(last_block.height, BlockHeight(4));
+
+        // Rollback two blocks, across the epoch boundary
+        db.rollback_to_height(BlockHeight(2)).unwrap();
+
+        let last_block = db.read_last_block().unwrap().unwrap();
+        assert_eq!(last_block.height, BlockHeight(2));
+        assert_eq!(last_block.epoch, Epoch(0));
+        assert_eq!(db.read_subspace_val(&diffs_key).unwrap(), Some(vec![2]));
+        assert_eq!(db.read_subspace_val(&no_diffs_key).unwrap(), Some(vec![2]));
+        assert_eq!(db.read_subspace_val(&add_key).unwrap(), None);
+
+        // The tx hash of the first block is in the general bucket, the one of
+        // the second block back in the current bucket
+        let reprot_cf = db.get_column_family(REPLAY_PROTECTION_CF).unwrap();
+        let first_hash = Hash::sha256([1_u8]);
+        let second_hash = Hash::sha256([2_u8]);
+        assert!(
+            db.read_value_bytes(
+                reprot_cf,
+                replay_protection::key(&first_hash).to_string()
+            )
+            .unwrap()
+            .is_some()
+        );
+        assert!(
+            db.read_value_bytes(
+                reprot_cf,
+                replay_protection::current_key(&second_hash).to_string()
+            )
+            .unwrap()
+            .is_some()
+        );
+        for val in [2_u8, 3, 4] {
+            let hash = Hash::sha256([val]);
+            assert!(
+                db.read_value_bytes(
+                    reprot_cf,
+                    replay_protection::key(&hash).to_string()
+                )
+                .unwrap()
+                .is_none()
+            );
+        }
+        for val in [3_u8, 4] {
+            assert!(
+                !db.has_replay_protection_entry(&Hash::sha256([val]))
+                    .unwrap()
+            );
+        }
+
+        // The data of the reverted blocks and epoch is removed
+        let block_cf = db.get_column_family(BLOCK_CF).unwrap();
+        let diffs_cf = db.get_column_family(DIFFS_CF).unwrap();
+        for height in [BlockHeight(3), BlockHeight(4)] {
+            for key in [
+                format!("{}/{BLOCK_TIME_KEY_SEGMENT}", height.raw()),
+                format!(
+                    "{}/{NEXT_EPOCH_MIN_START_HEIGHT_KEY}",
+                    rollback_height_prefix(height)
+                ),
+            ] {
+                assert!(db.read_value_bytes(block_cf, key).unwrap().is_none());
+            }
+            assert!(
+                iter_diffs_prefix(&db, diffs_cf, height, None, false)
+                    .next()
+                    .is_none()
+            );
+        }
+        let account_root_key = format!(
+            "{}/{MERKLE_TREE_ROOT_KEY_SEGMENT}",
+            tree_key_prefix_with_epoch(&StoreType::Account, Epoch(1))
+        );
+        assert!(
+            db.read_value_bytes(block_cf, account_root_key)
+                .unwrap()
+                .is_none()
+        );
+
+        // The state can be rolled back again, here to the current height
+        db.rollback_to_height(BlockHeight(2)).unwrap();
+        // but not to the pruned heights
+        let result = db.rollback_to_height(BlockHeight(1));
+        assert!(matches!(result, Err(Error::CannotRollback { .. })));
+    }
+
+    #[test]
+    fn test_rollback_journal_across_epochs() {
+        let dir = tempdir().unwrap();
+        let mut db = RocksDB::open(dir.path(), None);
+
+        let key = Key::parse("with_diffs").unwrap();
+        let conversion_state = ConversionState::default();
+        let mut pred_epochs = Epochs::default();
+        // A new epoch at every block, up to the epoch 11
+        for height in 1..=12_u64 {
+            let mut batch = RocksDB::batch();
+            let height = BlockHeight(height);
+            let val = vec![u8::try_from(height.0).unwrap()];
+            let epoch = Epoch(checked!(height.0 - 1).unwrap());
+            if height.0 > 1 {
+                pred_epochs.new_epoch(height);
+            }
+
+            db.batch_write_subspace_val(&mut batch, height, &key, &val, true)
+                .unwrap();
+            db.move_current_replay_protection_entries(&mut batch)
+                .unwrap();
+            db.write_replay_protection_entry(
+                &mut batch,
+                &replay_protection::current_key(&Hash::sha256(val)),
+            )
+            .unwrap();
+            add_block_to_batch(
+                &db,
+                &mut batch,
+                height,
+                epoch,
+                pred_epochs.clone(),
+                &conversion_state,
+            )
+            .unwrap();
+            db.exec_batch(batch).unwrap();
+        }
+
+        let has_epoch_journal = |db: &RocksDB, epoch: Epoch| {
+            let block_cf = db.get_column_family(BLOCK_CF).unwrap();
+            let key = format!(
+                "{}/{CONVERSION_STATE_KEY}",
+                rollback_epoch_prefix(epoch)
+            );
+            db.read_value_bytes(block_cf, key).unwrap().is_some()
+        };
+
+        // Pruning below the epoch 9 keeps the journal of the later epochs
+        let mut batch = RocksDB::batch();
+        db.prune_rollback_journal(&mut batch, BlockHeight(10), Epoch(9))
+            .unwrap();
+        db.exec_batch(batch).unwrap();
+        for epoch in 0..=11_u64 {
+            assert_eq!(has_epoch_journal(&db, Epoch(epoch)), epoch >= 9);
+        }
+
+        // Rollback across the epochs 9, 10 and 11
+        db.rollback_to_height(BlockHeight(10)).unwrap();
+
+        let last_block = db.read_last_block().unwrap().unwrap();
+        assert_eq!(last_block.height, BlockHeight(10));
+        assert_eq!(last_block.epoch, Epoch(9));
+        assert_eq!(db.read_subspace_val(&key).unwrap(), Some(vec![10]));
+        // Only the journal of the reverted epochs is removed
+        for epoch in 0..=11_u64 {
+            assert_eq!(has_epoch_journal(&db, Epoch(epoch)), epoch == 9);
+        }
+    }
+
     #[test]
     fn test_diffs() {
         let dir = tempdir().unwrap();
@@ -2791,6 +4213,319 @@ mod test {
         }
     }
 
+    /// Test that pruning the non-persisted diffs also removes the ones of the
+    /// heights skipped when the rollback window is lowered.
+    #[test]
+    fn test_prune_non_persisted_diffs_below_height() {
+        let dir = tempdir().unwrap();
+        let mut db = RocksDB::open(dir.path(), None);
+        let key = Key::parse("without_diffs").unwrap();
+
+        let mut batch = RocksDB::batch();
+        for height in 1..=12_u64 {
+            db.batch_write_subspace_val(
+                &mut batch,
+                BlockHeight(height),
+                &key,
+                [u8::try_from(height).unwrap()],
+                false,
+            )
+            .unwrap();
+        }
+        db.exec_batch(batch).unwrap();
+
+        // Lowering the window jumps from pruning height 1 to height 10
+        let mut batch = RocksDB::batch();
+        db.prune_non_persisted_diffs(&mut batch, BlockHeight(10))
+            .unwrap();
+        db.exec_batch(batch).unwrap();
+
+        let rollback_cf = db.get_column_family(ROLLBACK_CF).unwrap();
+        for height in 1..=12_u64 {
+            let (_old, new) =
+                old_and_new_diff_key(&key, BlockHeight(height)).unwrap();
+            assert_eq!(
+                db.inner.get_cf(rollback_cf, new).unwrap().is_some(),
+                height > 10,
+                "height {height}"
+            );
+        }
+    }
+
+    /// Test that the changeset of a block includes the changes of the keys
+    /// both with and without persisted diffs.
+    #[test]
+    fn test_read_subspace_changeset() {
+        let dir = tempdir().unwrap();
+        let db = RocksDB::open(dir.path(), None);
+
+        let diffs_key = Key::parse("with_diffs").unwrap();
+        let no_diffs_key = Key::parse("without_diffs").unwrap();
+
+        let mut batch = RocksDB::batch();
+        let height = BlockHeight(1);
+        db.batch_write_subspace_val(
+            &mut batch,
+            height,
+            &diffs_key,
+            [1_u8],
+            true,
+        )
+        .unwrap();
+        db.batch_write_subspace_val(
+            &mut batch,
+            height,
+            &no_diffs_key,
+            [1_u8],
+            false,
+        )
+        .unwrap();
+        db.exec_batch(batch).unwrap();
+
+        let mut batch = RocksDB::batch();
+        let height = BlockHeight(2);
+        db.batch_write_subspace_val(
+            &mut batch,
+            height,
+            &diffs_key,
+            [2_u8],
+            true,
+        )
+        .unwrap();
+        db.batch_delete_subspace_val(&mut batch, height, &no_diffs_key, false)
+            .unwrap();
+        db.exec_batch(batch).unwrap();
+
+        assert_eq!(
+            db.read_subspace_changeset(BlockHeight(1)).unwrap(),
+            vec![
+                SubspaceChange {
+                    key: diffs_key.clone(),
+                    old_value: None,
+                    new_value: Some(vec![1]),
+                },
+                SubspaceChange {
+                    key: no_diffs_key.clone(),
+                    old_value: None,
+                    new_value: Some(vec![1]),
+                },
+            ]
+        );
+        assert_eq!(
+            db.read_subspace_changeset(BlockHeight(2)).unwrap(),
+            vec![
+                SubspaceChange {
+                    key: diffs_key,
+                    old_value: Some(vec![1]),
+                    new_value: Some(vec![2]),
+                },
+                SubspaceChange {
+                    key: no_diffs_key,
+                    old_value: Some(vec![1]),
+                    new_value: None,
+                },
+            ]
+        );
+    }
+
+    /// Test that a key-value snapshot restores the state with the same
+    /// Merkle root and that it's rejected on an app hash mismatch.
+    #[test]
+    fn test_kv_snapshot_restore() {
+        let dir = tempdir().unwrap();
+        let db = RocksDB::open(dir.path().join("db"), None);
+
+        let height = BlockHeight(100);
+        let epoch = Epoch(1);
+        let mut pred_epochs = Epochs::default();
+        pred_epochs.new_epoch(height);
+        let conversion_state = ConversionState::default();
+        let keys: Vec<Key> = (0..10_u8)
+            .map(|i| Key::parse(format!("account/{i}")).unwrap())
+            .chain((0..10_u8).map(|i| {
+                Key::from(Address::Internal(InternalAddress::PoS).to_db_key())
+                    .push(&format!("pos{i}"))
+                    .unwrap()
+            }))
+            .chain([Key::parse("nodiff").unwrap()])
+            .collect();
+        // The keys without diffs are committed to by the no-diff store
+        fn diff_key_filter(key: &Key) -> bool {
+            key.to_string() != "nodiff"
+        }
+
+        let mut merkle_tree = MerkleTree::<Sha256Hasher>::default();
+        let mut batch = RocksDB::batch();
+        for (i, key) in keys.iter().enumerate() {
+            let value = vec![u8::try_from(i).unwrap(); 8];
+            let persist_diffs = diff_key_filter(key);
+            db.batch_write_subspace_val(
+                &mut batch,
+                height,
+                key,
+                &value,
+                persist_diffs,
+            )
+            .unwrap();
+            if persist_diffs {
+                merkle_tree.update(key, &value).unwrap();
+            } else {
+                let prefix =
+                    Key::from(NO_DIFF_KEY_PREFIX.to_string().to_db_key());
+                merkle_tree.update(&prefix.join(key), &value).unwrap();
+            }
+        }
+        db.write_replay_protection_entry(
+            &mut batch,
+            &replay_protection::key(&Hash::sha256(b"tx")),
+        )
+        .unwrap();
+        let commit_only_data = CommitOnlyData::default();
+        merkle_tree
+            .update_commit_data(commit_only_data.serialize())
+            .unwrap();
+        #[allow(clippy::disallowed_methods)]
+        let time = DateTimeUtc::now();
+        let block = BlockStateWrite {
+            merkle_tree_stores: merkle_tree.stores(),
+            header: None,
+            height,
+            time,
+            epoch,
+            results: &BlockResults::default(),
+            conversion_state: &conversion_state,
+            pred_epochs: &pred_epochs,
+            next_epoch_min_start_height: BlockHeight::default(),
+            next_epoch_min_start_time: time,
+            update_epoch_blocks_delay: None,
+            address_gen: &EstablishedAddressGen::new("whatever"),
+            ethereum_height: None,
+            eth_events_queue: &EthEventsQueue::default(),
+            commit_only_data: &commit_only_data,
+        };
+        db.add_block_to_batch(block, &mut batch, true).unwrap();
+        db.exec_batch(batch).unwrap();
+        let app_hash = merkle_tree.root().0.to_vec();
+
+        // Use small items to split the stores across multiple chunks
+        let mut chunks = vec![];
+        db.kv_snapshot_items(32, |item| {
+            chunks.push(item.serialize_to_vec());
+            Ok(())
+        })
+        .unwrap();
+        assert!(chunks.len() > 4);
+
+        // A snapshot with a different app hash is rejected
+        let mut restore = KvSnapshotRestore::new(
+            &dir.path().join("db"),
+            height,
+            vec![0; 32],
+            diff_key_filter,
+        )
+        .unwrap();
+        for chunk in &chunks {
+            restore.apply_chunk(chunk).unwrap();
+        }
+        assert!(restore.finalize().is_err());
+        assert!(!dir.path().join("db.state-sync").exists());
+
+        // Chunks applied out of order are rejected
+        let mut restore = KvSnapshotRestore::new(
+            &dir.path().join("db"),
+            height,
+            app_hash.clone(),
+            diff_key_filter,
+        )
+        .unwrap();
+        let subspace_chunks: Vec<_> = chunks
+            .iter()
+            .filter(|chunk| {
+                matches!(
+                    KvSnapshotItem::try_from_slice(chunk).unwrap(),
+                    KvSnapshotItem::Subspace {
+                        store_type: StoreType::Account,
+                        ..
+                    }
+                )
+            })
+            .collect();
+        assert!(subspace_chunks.len() > 1);
+        restore.apply_chunk(subspace_chunks[1]).unwrap();
+        assert!(restore.apply_chunk(subspace_chunks[0]).is_err());
+        drop(restore);
+
+        // The entries of another block are rejected
+        let mut restore = KvSnapshotRestore::new(
+            &dir.path().join("db"),
+            height,
+            app_hash.clone(),
+            diff_key_filter,
+        )
+        .unwrap();
+        let item = KvSnapshotItem::Raw {
+            cf: BLOCK_CF.to_owned(),
+            entries: vec![(
+                format!(
+                    "{}/{BLOCK_TIME_KEY_SEGMENT}",
+                    height.next_height().raw()
+                ),
+                vec![],
+            )],
+        };
+        assert!(restore.apply_chunk(&item.serialize_to_vec()).is_err());
+        drop(restore);
+
+        // A snapshot of another height is rejected
+        let mut restore = KvSnapshotRestore::new(
+            &dir.path().join("db"),
+            height.next_height(),
+            app_hash.clone(),
+            diff_key_filter,
+        )
+        .unwrap();
+        let result = chunks
+            .iter()
+            .try_for_each(|chunk| restore.apply_chunk(chunk))
+            .and_then(|()| restore.finalize().map(|_| ()));
+        assert!(result.is_err());
+
+        // The restored state matches the original one
+        let mut restore = KvSnapshotRestore::new(
+            &dir.path().join("db"),
+            height,
+            app_hash.clone(),
+            diff_key_filter,
+        )
+        .unwrap();
+        for chunk in &chunks {
+            restore.apply_chunk(chunk).unwrap();
+        }
+        let restored_dir = restore.finalize().unwrap();
+        let restored = RocksDB::open(restored_dir, None);
+        let last_block = restored.read_last_block().unwrap().unwrap();
+        assert_eq!(last_block.height, height);
+        let restored_tree = restore_merkle_tree::<_, Sha256Hasher>(
+            &restored,
+            &last_block.pred_epochs,
+            height,
+            None,
+        )
+        .unwrap();
+        assert_eq!(restored_tree.root().0.to_vec(), app_hash);
+        for key in &keys {
+            assert_eq!(
+                restored.read_subspace_val(key).unwrap(),
+                db.read_subspace_val(key).unwrap()
+            );
+        }
+        assert!(
+            restored
+                .has_replay_protection_entry(&Hash::sha256(b"tx"))
+                .unwrap()
+        );
+    }
+
     /// A test helper to write a block
     fn add_block_to_batch(
         db: &RocksDB,
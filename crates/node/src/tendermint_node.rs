@@ -216,20 +216,167 @@ pub fn reset(tendermint_dir: impl AsRef<Path>) -> Result<()> {
 }
 
 pub fn rollback(tendermint_dir: impl AsRef<Path>) -> Result<BlockHeight> {
+    rollback_block(tendermint_dir, false)
+}
+
+/// Rollback the state one block at a time until the given height is reached.
+/// The rolled back blocks are removed from the block store, which allows the
+/// successive rollbacks to proceed. Nothing is rolled back unless the last
+/// block of CometBFT is at the expected height.
+pub fn rollback_to_height(
+    tendermint_dir: impl AsRef<Path>,
+    expected_height: BlockHeight,
+    target: BlockHeight,
+) -> Result<()> {
+    let tendermint_dir = tendermint_dir.as_ref();
+    let last_height = last_block_height(tendermint_dir)?;
+    if last_height != expected_height {
+        return Err(Error::RollBack(format!(
+            "The last CometBFT block height {last_height} doesn't match the \
+             expected height {expected_height}"
+        )));
+    }
+    if last_height == target {
+        return Ok(());
+    }
+    if last_height < target {
+        return Err(Error::RollBack(format!(
+            "The target height {target} is above the last CometBFT block \
+             height {last_height}"
+        )));
+    }
+    loop {
+        let height = rollback_block(tendermint_dir, true)?;
+        tracing::info!("Rolled back Tendermint state to height {height}");
+        match height.cmp(&target) {
+            std::cmp::Ordering::Greater => continue,
+            std::cmp::Ordering::Equal => return Ok(()),
+            std::cmp::Ordering::Less => {
+                return Err(Error::RollBack(format!(
+                    "Rolled back to height {height} below the target height \
+                     {target}"
+                )));
+            }
+        }
+    }
+}
+
+/// Number of attempts to connect to the RPC server of `cometbft inspect`
+const INSPECT_CONNECT_ATTEMPTS: u64 = 300;
+/// Delay between the attempts to connect to `cometbft inspect`
+const INSPECT_CONNECT_DELAY: std::time::Duration =
+    std::time::Duration::from_millis(100);
+
+/// Get the height of the last block in the CometBFT block store without
+/// modifying it. The node must not be running, the block store is served by
+/// `cometbft inspect` for the time of the query.
+pub fn last_block_height(
+    tendermint_dir: impl AsRef<Path>,
+) -> Result<BlockHeight> {
     let tendermint_path = from_env_or_default()?;
     let tendermint_dir = tendermint_dir.as_ref().to_string_lossy();
+    let inspect_error = |msg: String| {
+        Error::RollBack(format!("Failed to inspect the CometBFT state: {msg}"))
+    };
 
-    // Rollback tendermint state, see https://github.com/tendermint/tendermint/blob/main/cmd/tendermint/commands/rollback.go for details
-    // on how the tendermint rollback behaves
-    let output = std::process::Command::new(tendermint_path)
+    // Let the OS pick a free local port for the RPC server
+    let rpc_addr = std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .map_err(|e| inspect_error(e.to_string()))?;
+    let rpc_laddr = format!("tcp://{rpc_addr}");
+    let mut inspector = std::process::Command::new(tendermint_path)
         .args([
-            "rollback",
-            "unsafe-all",
-            // NOTE: log config: https://docs.tendermint.com/master/nodes/logging.html#configuring-log-levels
-            // "--log-level=\"*debug\"",
+            "inspect",
             "--home",
             &tendermint_dir,
+            "--rpc.laddr",
+            &rpc_laddr,
         ])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| inspect_error(e.to_string()))?;
+
+    let response = query_last_block(&mut inspector, rpc_addr);
+    // The inspector only reads the data, it is safe to kill it
+    let _ = inspector.kill();
+    let _ = inspector.wait();
+    let response = response.map_err(inspect_error)?;
+
+    let response: serde_json::Value = serde_json::from_str(&response)
+        .map_err(|e| inspect_error(e.to_string()))?;
+    let block = &response["result"]["block"];
+    if block.is_null() {
+        // The block store is empty
+        return Ok(BlockHeight(0));
+    }
+    block["header"]["height"]
+        .as_str()
+        .and_then(|height| height.parse::<u64>().ok())
+        .map(BlockHeight)
+        .ok_or_else(|| {
+            inspect_error(format!("Unexpected response to /block: {response}"))
+        })
+}
+
+/// Query the last block from the RPC server of `cometbft inspect`, retrying
+/// until the server is up. Returns the body of the response.
+fn query_last_block(
+    inspector: &mut std::process::Child,
+    rpc_addr: std::net::SocketAddr,
+) -> std::result::Result<String, String> {
+    use std::io::{Read, Write};
+
+    for _ in 0..INSPECT_CONNECT_ATTEMPTS {
+        if let Some(status) = inspector.try_wait().map_err(|e| e.to_string())? {
+            return Err(format!("cometbft inspect exited with {status}"));
+        }
+        let Ok(mut stream) = std::net::TcpStream::connect(rpc_addr) else {
+            std::thread::sleep(INSPECT_CONNECT_DELAY);
+            continue;
+        };
+        // HTTP/1.0 to have the connection closed after the response
+        stream
+            .write_all(b"GET /block HTTP/1.0\r\nHost: 127.0.0.1\r\n\r\n")
+            .map_err(|e| e.to_string())?;
+        let mut response = String::new();
+        stream
+            .read_to_string(&mut response)
+            .map_err(|e| e.to_string())?;
+        return response
+            .split_once("\r\n\r\n")
+            .map(|(_headers, body)| body.to_string())
+            .ok_or_else(|| format!("Malformed HTTP response: {response}"));
+    }
+    Err(format!(
+        "Couldn't connect to cometbft inspect at {rpc_addr}"
+    ))
+}
+
+fn rollback_block(
+    tendermint_dir: impl AsRef<Path>,
+    hard: bool,
+) -> Result<BlockHeight> {
+    let tendermint_path = from_env_or_default()?;
+    let tendermint_dir = tendermint_dir.as_ref().to_string_lossy();
+
+    let mut args = vec![
+        "rollback",
+        "unsafe-all",
+        // NOTE: log config: https://docs.tendermint.com/master/nodes/logging.html#configuring-log-levels
+        // "--log-level=\"*debug\"",
+        "--home",
+        &tendermint_dir,
+    ];
+    if hard {
+        // Also remove the last block from the block store
+        args.push("--hard");
+    }
+
+    // Rollback tendermint state, see https://github.com/tendermint/tendermint/blob/main/cmd/tendermint/commands/rollback.go for details
+    // on how the tendermint rollback behaves
+    let output = std::process::Command::new(tendermint_path)
+        .args(args)
         .output()
         .map_err(|e| Error::RollBack(e.to_string()))?;
 
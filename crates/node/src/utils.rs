@@ -4,24 +4,29 @@ use std::cell::RefCell;
 use std::str::FromStr;
 
 use namada_apps_lib::cli::api::CliIo;
-use namada_apps_lib::cli::args::{self, DryRunProposal, TestGenesis};
+use namada_apps_lib::cli::args::{
+    self, DryRunProposal, ExportGenesis, TestGenesis,
+};
 use namada_apps_lib::client::utils::PRE_GENESIS_DIR;
 use namada_apps_lib::config::genesis::{self, AddrOrPk};
 use namada_apps_lib::{cli, wallet};
-use namada_sdk::address::{Address, ImplicitAddress};
+use namada_sdk::address::{Address, ImplicitAddress, MASP};
 use namada_sdk::gas::TxGasMeter;
 use namada_sdk::key::common;
 use namada_sdk::state::{
-    FullAccessState, Sha256Hasher, StorageWrite, TxIndex, WlState,
+    FullAccessState, HistoricState, Sha256Hasher, StorageWrite, TxIndex,
+    WlState,
 };
 use namada_sdk::tx::data::TxType;
 use namada_sdk::tx::{self, Tx};
 use namada_sdk::wallet::FindKeyError;
 use namada_sdk::{encode, governance, parameters};
 use namada_vm::WasmCacheRwAccess;
+use namada_vm::wasm::compilation_cache::common as wasm_cache;
 use namada_vm::wasm::{TxCache, VpCache};
 use tracing::info;
 
+use crate::shell::wasm_cache::read_allowlists;
 use crate::tendermint::Timeout;
 use crate::{protocol, storage};
 
@@ -248,3 +253,156 @@ pub fn dry_run_proposal(
 
     Ok(())
 }
+
+pub fn export_genesis(
+    args: ExportGenesis,
+    global_args: args::Global,
+) -> eyre::Result<()> {
+    let ExportGenesis { height, path } = args;
+
+    let ctx = cli::Context::new::<CliIo>(global_args)?;
+    let chain_ctx = ctx.take_chain_or_exit();
+    let native_token = chain_ctx.native_token.clone();
+    let config = &chain_ctx.config.ledger;
+    let chain_id = &config.chain_id;
+    let db_path = config.shell.db_dir(chain_id);
+    let state: WlState<storage::PersistentDB, Sha256Hasher> =
+        FullAccessState::open_read_only(
+            db_path,
+            None,
+            chain_id.clone(),
+            native_token,
+            config.shell.storage_read_past_height_limit,
+            |_key| true,
+        );
+
+    let last_height = state.in_mem().get_last_block_height();
+    let historic_state = match height {
+        Some(height) => HistoricState::new(&state)
+            .at_height(height)
+            .map_err(|e| eyre::eyre!(e.to_string()))?,
+        None => HistoricState::new(&state),
+    };
+    let height = historic_state.height();
+    info!("Exporting the state at height {height}...");
+    let mut exported = genesis::export::export_templates(&historic_state)?;
+    if height != last_height
+        && exported
+            .templates
+            .balances
+            .token
+            .values()
+            .any(|balances| balances.0.contains_key(&MASP))
+    {
+        exported.warnings.push(format!(
+            "The balances of the MASP were exported at the last committed \
+             height {last_height}, their history is not kept"
+        ));
+    }
+    std::fs::create_dir_all(&path)?;
+    exported.write_toml_files(&path)?;
+
+    if !exported.addresses.is_empty() {
+        println!(
+            "The established accounts and tokens were exported with new \
+             addresses:"
+        );
+        for (old, new) in &exported.addresses {
+            println!("  {old} -> {new}");
+        }
+    }
+    for warning in &exported.warnings {
+        eprintln!("Warning: {warning}");
+    }
+
+    if genesis::templates::load_and_validate(&path).is_none() {
+        eprintln!("The exported genesis templates are invalid");
+        cli::safe_exit(1);
+    }
+    println!(
+        "Genesis templates of the state at height {height} written to {}. \
+         Sign the validator account and bond transactions in {} with `namada \
+         client utils sign-genesis-txs` and append them to {} before using \
+         the templates.",
+        path.to_string_lossy(),
+        genesis::export::UNSIGNED_TRANSACTIONS_FILE_NAME,
+        genesis::templates::TRANSACTIONS_FILE_NAME,
+    );
+
+    Ok(())
+}
+
+pub fn wasm_cache_verify(global_args: args::Global) -> eyre::Result<()> {
+    let ctx = cli::Context::new::<CliIo>(global_args)?;
+    let chain_ctx = ctx.take_chain_or_exit();
+    let config = &chain_ctx.config.ledger;
+    let chain_id = &config.chain_id;
+
+    let mut all_valid = true;
+    for (name, dir) in [
+        ("tx", config.shell.tx_wasm_cache_dir(chain_id)),
+        ("VP", config.shell.vp_wasm_cache_dir(chain_id)),
+    ] {
+        let report = wasm_cache::verify(&dir)?;
+        println!(
+            "The {name} wasm cache in {} has {} valid compiled wasm.",
+            dir.to_string_lossy(),
+            report.valid.len()
+        );
+        for path in &report.invalid {
+            println!("  Cannot be loaded: {}", path.to_string_lossy());
+        }
+        for path in &report.stale {
+            println!(
+                "  Compiled by another version: {}",
+                path.to_string_lossy()
+            );
+        }
+        all_valid &= report.invalid.is_empty();
+    }
+    if !all_valid {
+        eprintln!(
+            "Some compiled wasm cannot be loaded. Remove them with `namada \
+             node utils wasm-cache prune`."
+        );
+        cli::safe_exit(1);
+    }
+
+    Ok(())
+}
+
+pub fn wasm_cache_prune(global_args: args::Global) -> eyre::Result<()> {
+    let ctx = cli::Context::new::<CliIo>(global_args)?;
+    let chain_ctx = ctx.take_chain_or_exit();
+    let native_token = chain_ctx.native_token.clone();
+    let config = &chain_ctx.config.ledger;
+    let chain_id = &config.chain_id;
+    let db_path = config.shell.db_dir(chain_id);
+    let state: WlState<storage::PersistentDB, Sha256Hasher> =
+        FullAccessState::open_read_only(
+            db_path,
+            None,
+            chain_id.clone(),
+            native_token,
+            config.shell.storage_read_past_height_limit,
+            |_key| true,
+        );
+    let (tx_allowed, vp_allowed) = read_allowlists(&state)?;
+
+    for (name, dir, allowed) in [
+        ("tx", config.shell.tx_wasm_cache_dir(chain_id), tx_allowed),
+        ("VP", config.shell.vp_wasm_cache_dir(chain_id), vp_allowed),
+    ] {
+        let removed = wasm_cache::prune(&dir, |hash| allowed.contains(hash))?;
+        println!(
+            "Removed {} entries from the {name} wasm cache in {}.",
+            removed.len(),
+            dir.to_string_lossy(),
+        );
+        for path in removed {
+            info!("Removed {}", path.to_string_lossy());
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,340 @@
+//! Dynamic base fee market.
+//!
+//! When the fee market is enabled, the minimum price per unit of gas of a
+//! token is its base fee, which is adjusted at the end of every block based on
+//! the gas used by the block. The base fee of a token never falls below its
+//! `minimum_gas_price` parameter. A zero base fee still rises under load,
+//! starting from the smallest unit of the token.
+//!
+//! Like the fees, which are paid for the whole gas limit of a wrapper tx, the
+//! base fee part of the fees is charged on the gas limit rather than on the
+//! gas used, which is only known after the execution of the batch.
+
+use std::cmp;
+use std::collections::BTreeMap;
+
+use namada_core::address::Address;
+use namada_core::arith::checked;
+use namada_core::dec::Dec;
+use namada_core::parameters::FeeMarketParams;
+use namada_core::token;
+use namada_state::{Result, ResultExt, StorageRead, StorageWrite};
+
+use crate::{ReadError, storage};
+
+/// Read the parameters of the fee market. Returns `None` if the fee market is
+/// disabled.
+pub fn read_fee_market_parameters<S>(
+    storage: &S,
+) -> Result<Option<FeeMarketParams>>
+where
+    S: StorageRead,
+{
+    storage.read(&storage::get_fee_market_key())
+}
+
+/// Read the base fee of every token allowed for fee payment, i.e. the minimum
+/// amount of the token that a transaction must pay per unit of gas. These are
+/// the `minimum_gas_price` parameters if the fee market is disabled.
+pub fn read_base_fees<S>(
+    storage: &S,
+) -> Result<BTreeMap<Address, token::Amount>>
+where
+    S: StorageRead,
+{
+    let minimum_gas_price: BTreeMap<Address, token::Amount> = storage
+        .read(&storage::get_gas_cost_key())?
+        .ok_or(ReadError::ParametersMissing)
+        .into_storage_result()?;
+    if read_fee_market_parameters(storage)?.is_none() {
+        return Ok(minimum_gas_price);
+    }
+
+    // The base fees are only written at the end of a block, so a token
+    // allowed for fee payment in the meantime might not have one yet
+    let base_fees: BTreeMap<Address, token::Amount> = storage
+        .read(&storage::get_base_fee_key())?
+        .unwrap_or_default();
+    Ok(minimum_gas_price
+        .into_iter()
+        .map(|(token, minimum)| {
+            let base_fee = base_fees
+                .get(&token)
+                .map_or(minimum, |base_fee| cmp::max(*base_fee, minimum));
+            (token, base_fee)
+        })
+        .collect())
+}
+
+/// Read the base fee of the provided token. Returns `None` if the token is not
+/// allowed for fee payment.
+pub fn read_base_fee<S>(
+    storage: &S,
+    token: &Address,
+) -> Result<Option<token::Amount>>
+where
+    S: StorageRead,
+{
+    Ok(read_base_fees(storage)?.remove(token))
+}
+
+/// Adjust the base fees to the gas used by the last block, in whole gas units.
+/// Does nothing if the fee market is disabled.
+pub fn update_base_fees<S>(storage: &mut S, block_gas_used: u64) -> Result<()>
+where
+    S: StorageRead + StorageWrite,
+{
+    let Some(fee_market) = read_fee_market_parameters(storage)? else {
+        return Ok(());
+    };
+    let max_block_gas = storage::get_max_block_gas(storage)?;
+    let change = base_fee_change(&fee_market, max_block_gas, block_gas_used)?;
+    let minimum_gas_price: BTreeMap<Address, token::Amount> = storage
+        .read(&storage::get_gas_cost_key())?
+        .ok_or(ReadError::ParametersMissing)
+        .into_storage_result()?;
+
+    let mut base_fees = BTreeMap::new();
+    for (token, base_fee) in read_base_fees(storage)? {
+        let base_fee = next_base_fee(base_fee, change)?;
+        let minimum =
+            minimum_gas_price.get(&token).copied().unwrap_or_default();
+        base_fees.insert(token, cmp::max(base_fee, minimum));
+    }
+    storage.write(&storage::get_base_fee_key(), base_fees)
+}
+
+/// The relative change of the base fees after a block that used
+/// `block_gas_used` gas. The change is proportional to the deviation of the gas
+/// used from the target, relative to the target, and it's capped at the max
+/// change, which is reached by an empty block and by a block using at least
+/// twice the target.
+fn base_fee_change(
+    fee_market: &FeeMarketParams,
+    max_block_gas: u64,
+    block_gas_used: u64,
+) -> Result<Dec> {
+    let target = checked!(
+        Dec::from(max_block_gas) * fee_market.target_block_gas_fraction
+    )?;
+    let used = Dec::from(block_gas_used);
+    let deviation = if target.is_zero() {
+        if used.is_zero() {
+            Dec::zero()
+        } else {
+            Dec::one()
+        }
+    } else {
+        checked!((used - target) / target)?
+    };
+    let deviation =
+        deviation.clamp(checked!(Dec::zero() - Dec::one())?, Dec::one());
+    Ok(checked!(fee_market.max_base_fee_change * deviation)?)
+}
+
+/// Apply a relative change to a base fee. The rounding is away from the
+/// current base fee such that even a small change moves it. A rising base fee
+/// is at least the smallest unit of the token, otherwise a zero base fee could
+/// never rise.
+fn next_base_fee(
+    base_fee: token::Amount,
+    change: Dec,
+) -> Result<token::Amount> {
+    let factor = cmp::max(checked!(Dec::one() + change)?, Dec::zero());
+    let base_fee = if change.is_negative() {
+        base_fee.mul_floor(factor)?
+    } else if change.is_zero() {
+        base_fee
+    } else {
+        cmp::max(base_fee.mul_ceil(factor)?, token::Amount::from(1))
+    };
+    Ok(base_fee)
+}
+
+#[cfg(test)]
+mod tests {
+    use namada_core::address::testing::{btc, established_address_1, nam};
+    use namada_core::parameters::BaseFeeDestination;
+    use namada_state::testing::TestStorage;
+
+    use super::*;
+
+    fn init_fee_market(storage: &mut TestStorage) {
+        crate::init_test_storage(storage).unwrap();
+        storage
+            .write(
+                &storage::get_gas_cost_key(),
+                BTreeMap::from([
+                    (nam(), token::Amount::from(1_000)),
+                    (btc(), token::Amount::from(10)),
+                ]),
+            )
+            .unwrap();
+        storage
+            .write(
+                &storage::get_fee_market_key(),
+                FeeMarketParams {
+                    target_block_gas_fraction: Dec::new(5, 1).unwrap(),
+                    max_base_fee_change: Dec::new(125, 3).unwrap(),
+                    base_fee_destination: BaseFeeDestination::Burn,
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn test_base_fees_without_fee_market() {
+        let mut storage = TestStorage::default();
+        init_fee_market(&mut storage);
+        storage.delete(&storage::get_fee_market_key()).unwrap();
+
+        // A full block doesn't affect the base fees
+        update_base_fees(&mut storage, 100).unwrap();
+        assert_eq!(
+            read_base_fee(&storage, &nam()).unwrap(),
+            Some(token::Amount::from(1_000))
+        );
+        assert!(!storage.has_key(&storage::get_base_fee_key()).unwrap());
+        assert!(crate::read(&storage).unwrap().fee_market.is_none());
+    }
+
+    #[test]
+    fn test_update_base_fees() {
+        let mut storage = TestStorage::default();
+        init_fee_market(&mut storage);
+        assert!(crate::read(&storage).unwrap().fee_market.is_some());
+
+        // Before any block, the base fees are the minimum gas prices
+        assert_eq!(
+            read_base_fee(&storage, &nam()).unwrap(),
+            Some(token::Amount::from(1_000))
+        );
+        assert_eq!(
+            read_base_fee(&storage, &established_address_1()).unwrap(),
+            None
+        );
+
+        // A full block raises the base fees by the max change, rounding up
+        update_base_fees(&mut storage, 100).unwrap();
+        assert_eq!(
+            read_base_fees(&storage).unwrap(),
+            BTreeMap::from([
+                (nam(), token::Amount::from(1_125)),
+                (btc(), token::Amount::from(12)),
+            ])
+        );
+
+        // A block on target doesn't change the base fees
+        update_base_fees(&mut storage, 50).unwrap();
+        assert_eq!(
+            read_base_fee(&storage, &nam()).unwrap(),
+            Some(token::Amount::from(1_125))
+        );
+
+        // A block below target lowers the base fees proportionally
+        update_base_fees(&mut storage, 25).unwrap();
+        assert_eq!(
+            read_base_fees(&storage).unwrap(),
+            BTreeMap::from([
+                (nam(), token::Amount::from(1_054)),
+                (btc(), token::Amount::from(11)),
+            ])
+        );
+
+        // The base fees never fall below the minimum gas prices
+        for _ in 0..10 {
+            update_base_fees(&mut storage, 0).unwrap();
+        }
+        assert_eq!(
+            read_base_fees(&storage).unwrap(),
+            BTreeMap::from([
+                (nam(), token::Amount::from(1_000)),
+                (btc(), token::Amount::from(10)),
+            ])
+        );
+
+        // Raising a minimum gas price raises the base fee too
+        storage
+            .write(
+                &storage::get_gas_cost_key(),
+                BTreeMap::from([(nam(), token::Amount::from(2_000))]),
+            )
+            .unwrap();
+        assert_eq!(
+            read_base_fees(&storage).unwrap(),
+            BTreeMap::from([(nam(), token::Amount::from(2_000))])
+        );
+    }
+
+    #[test]
+    fn test_zero_base_fee_rises() {
+        let mut storage = TestStorage::default();
+        init_fee_market(&mut storage);
+        storage
+            .write(
+                &storage::get_gas_cost_key(),
+                BTreeMap::from([(nam(), token::Amount::zero())]),
+            )
+            .unwrap();
+
+        // A full block raises a zero base fee to the smallest unit, from
+        // which it keeps rising
+        update_base_fees(&mut storage, 100).unwrap();
+        assert_eq!(
+            read_base_fee(&storage, &nam()).unwrap(),
+            Some(token::Amount::from(1))
+        );
+        update_base_fees(&mut storage, 100).unwrap();
+        assert_eq!(
+            read_base_fee(&storage, &nam()).unwrap(),
+            Some(token::Amount::from(2))
+        );
+
+        // A block on target doesn't change it and empty blocks lower it back
+        // to the minimum gas price
+        update_base_fees(&mut storage, 50).unwrap();
+        assert_eq!(
+            read_base_fee(&storage, &nam()).unwrap(),
+            Some(token::Amount::from(2))
+        );
+        for _ in 0..10 {
+            update_base_fees(&mut storage, 0).unwrap();
+        }
+        assert_eq!(
+            read_base_fee(&storage, &nam()).unwrap(),
+            Some(token::Amount::zero())
+        );
+    }
+
+    #[test]
+    fn test_base_fee_change_is_capped() {
+        let fee_market = FeeMarketParams {
+            target_block_gas_fraction: Dec::new(25, 2).unwrap(),
+            max_base_fee_change: Dec::new(1, 1).unwrap(),
+            base_fee_destination: BaseFeeDestination::Pgf,
+        };
+        assert_eq!(
+            base_fee_change(&fee_market, 100, 100).unwrap(),
+            Dec::new(1, 1).unwrap()
+        );
+        assert_eq!(
+            base_fee_change(&fee_market, 100, 0).unwrap(),
+            Dec::new(-1, 1).unwrap()
+        );
+        assert_eq!(
+            base_fee_change(&fee_market, 100, 30).unwrap(),
+            Dec::new(2, 2).unwrap()
+        );
+
+        // A zero target raises the base fees on any gas used
+        let fee_market = FeeMarketParams {
+            target_block_gas_fraction: Dec::zero(),
+            ..fee_market
+        };
+        assert_eq!(base_fee_change(&fee_market, 100, 0).unwrap(), Dec::zero());
+        assert_eq!(
+            base_fee_change(&fee_market, 100, 1).unwrap(),
+            Dec::new(1, 1).unwrap()
+        );
+    }
+}
@@ -17,12 +17,18 @@
     clippy::print_stderr
 )]
 
+mod fee_market;
+#[cfg(feature = "migrations")]
+mod schema;
 pub mod storage;
 pub mod vp;
 mod wasm_allowlist;
 use std::collections::BTreeMap;
 use std::marker::PhantomData;
 
+pub use fee_market::{
+    read_base_fee, read_base_fees, read_fee_market_parameters, update_base_fees,
+};
 use namada_core::address::{Address, InternalAddress};
 use namada_core::arith::checked;
 use namada_core::chain::BlockHeight;
@@ -134,6 +140,7 @@ where
         masp_fee_payment_gas_limit,
         gas_scale,
         is_native_token_transferable,
+        fee_market,
     } = parameters;
 
     // write max tx bytes parameter
@@ -199,6 +206,14 @@ where
     storage
         .write(&native_token_transferable_key, is_native_token_transferable)?;
 
+    // write the fee market parameters, only present if the fee market is
+    // enabled
+    let fee_market_key = storage::get_fee_market_key();
+    match fee_market {
+        Some(fee_market) => storage.write(&fee_market_key, fee_market)?,
+        None => storage.delete(&fee_market_key)?,
+    }
+
     Ok(())
 }
 
@@ -447,6 +462,9 @@ where
         .ok_or(ReadError::ParametersMissing)
         .into_storage_result()?;
 
+    // read the fee market parameters
+    let fee_market = read_fee_market_parameters(storage)?;
+
     Ok(Parameters {
         max_tx_bytes,
         epoch_duration,
@@ -461,6 +479,7 @@ where
         masp_fee_payment_gas_limit,
         gas_scale,
         is_native_token_transferable,
+        fee_market,
     })
 }
 
@@ -503,6 +522,7 @@ where
         gas_scale: 10_000_000,
         minimum_gas_price: Default::default(),
         is_native_token_transferable: true,
+        fee_market: None,
     };
     init_storage(&params, storage)
 }
@@ -0,0 +1,43 @@
+//! Storage schemas of the protocol parameters keys
+
+use std::collections::BTreeMap;
+
+use namada_core::address::Address;
+use namada_core::hash::Hash;
+use namada_core::parameters::{EpochDuration, FeeMarketParams, ProposalBytes};
+use namada_core::token;
+use namada_migrations::storage_schema;
+
+storage_schema!(
+    EPOCH_DURATION: "#Parameters/epoch_duration" => EpochDuration as debug
+);
+storage_schema!(EPOCHS_PER_YEAR: "#Parameters/epochs_per_year" => u64);
+storage_schema!(
+    MASP_EPOCH_MULTIPLIER: "#Parameters/masp_epoch_multiplier" => u64
+);
+storage_schema!(IMPLICIT_VP: "#Parameters/implicit_vp" => Hash);
+storage_schema!(TX_ALLOWLIST: "#Parameters/tx_allowlist" => Vec<String>);
+storage_schema!(VP_ALLOWLIST: "#Parameters/vp_allowlist" => Vec<String>);
+storage_schema!(
+    MAX_PROPOSAL_BYTES: "#Parameters/max_proposal_bytes" => ProposalBytes
+);
+storage_schema!(MAX_TX_BYTES: "#Parameters/max_tx_bytes" => u32);
+storage_schema!(MAX_BLOCK_GAS: "#Parameters/max_block_gas" => u64);
+storage_schema!(
+    MINIMUM_GAS_PRICE: "#Parameters/minimum_gas_price"
+        => BTreeMap<Address, token::Amount>
+);
+storage_schema!(
+    MASP_FEE_PAYMENT_GAS_LIMIT: "#Parameters/masp_fee_payment_gas_limit"
+        => u64
+);
+storage_schema!(GAS_SCALE: "#Parameters/gas_scale" => u64);
+storage_schema!(
+    NATIVE_TOKEN_TRANSFERABLE: "#Parameters/native_token_transferable" => bool
+);
+storage_schema!(
+    FEE_MARKET: "#Parameters/fee_market" => FeeMarketParams as debug
+);
+storage_schema!(
+    BASE_FEE: "#Parameters/base_fee" => BTreeMap<Address, token::Amount>
+);
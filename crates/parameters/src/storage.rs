@@ -41,6 +41,8 @@ struct Keys {
     masp_fee_payment_gas_limit: &'static str,
     gas_scale: &'static str,
     native_token_transferable: &'static str,
+    fee_market: &'static str,
+    base_fee: &'static str,
 }
 
 /// Returns if the key is a parameter key.
@@ -156,6 +158,16 @@ pub fn get_gas_cost_key() -> Key {
     get_minimum_gas_price_key_at_addr(ADDRESS)
 }
 
+/// Storage key used for the fee market parameters
+pub fn get_fee_market_key() -> Key {
+    get_fee_market_key_at_addr(ADDRESS)
+}
+
+/// Storage key used for the base fees of the fee market
+pub fn get_base_fee_key() -> Key {
+    get_base_fee_key_at_addr(ADDRESS)
+}
+
 /// Helper function to retrieve the `max_block_gas` protocol parameter from
 /// storage
 pub fn get_max_block_gas(storage: &impl StorageRead) -> Result<u64> {
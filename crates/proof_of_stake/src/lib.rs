@@ -22,6 +22,8 @@ pub mod event;
 pub mod parameters;
 pub mod queries;
 pub mod rewards;
+#[cfg(feature = "migrations")]
+mod schema;
 pub mod slashing;
 pub mod storage;
 pub mod storage_key;
@@ -3331,6 +3333,7 @@ pub mod test_utils {
             gas_scale: 100_000_000,
             minimum_gas_price: BTreeMap::new(),
             is_native_token_transferable: true,
+            fee_market: None,
         };
         Params::write(storage, &chain_parameters).unwrap();
         init_genesis_helper::<S, Gov, Token>(
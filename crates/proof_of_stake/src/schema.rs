@@ -0,0 +1,90 @@
+//! Storage schemas of the PoS keys
+
+use namada_core::address::Address;
+use namada_core::chain::Epoch;
+use namada_core::dec::Dec;
+use namada_core::key::common;
+use namada_core::token;
+use namada_migrations::storage_schema;
+
+use crate::parameters::OwnedPosParams;
+use crate::types::ValidatorState;
+
+storage_schema!(PARAMS: "#PoS/params" => OwnedPosParams);
+storage_schema!(LAST_BLOCK_PROPOSER: "#PoS/last_block_proposer" => Address);
+storage_schema!(LAST_STAKED_RATIO: "#PoS/last_staked_ratio" => Dec);
+storage_schema!(
+    LAST_INFLATION_AMOUNT: "#PoS/last_inflation_amount" => token::Amount
+);
+
+// Epoched validator data
+storage_schema!(
+    VALIDATOR_STATE:
+        "#PoS/validator/<validator>/state/lazy_map/data/<epoch>"
+        => ValidatorState
+);
+storage_schema!(
+    VALIDATOR_DELTAS:
+        "#PoS/validator/<validator>/deltas/lazy_map/data/<epoch>"
+        => token::Change as debug
+);
+storage_schema!(
+    VALIDATOR_COMMISSION_RATE:
+        "#PoS/validator/<validator>/commission_rate/lazy_map/data/<epoch>"
+        => Dec
+);
+storage_schema!(
+    VALIDATOR_CONSENSUS_KEY:
+        "#PoS/validator/<validator>/consensus_key/lazy_map/data/<epoch>"
+        => common::PublicKey
+);
+storage_schema!(
+    VALIDATOR_EPOCHED_LAST_UPDATE:
+        "#PoS/validator/<validator>/<field>/last_update" => Epoch
+);
+storage_schema!(
+    VALIDATOR_EPOCHED_OLDEST_EPOCH:
+        "#PoS/validator/<validator>/<field>/oldest_epoch" => Epoch
+);
+
+// Validator metadata
+storage_schema!(
+    VALIDATOR_MAX_COMMISSION_RATE_CHANGE:
+        "#PoS/validator/<validator>/max_commission_rate_change" => Dec
+);
+storage_schema!(
+    VALIDATOR_LAST_SLASH_EPOCH:
+        "#PoS/validator/<validator>/last_slash_epoch" => Epoch
+);
+storage_schema!(VALIDATOR_EMAIL: "#PoS/validator/<validator>/email" => String);
+storage_schema!(
+    VALIDATOR_DESCRIPTION: "#PoS/validator/<validator>/description" => String
+);
+storage_schema!(
+    VALIDATOR_WEBSITE: "#PoS/validator/<validator>/website" => String
+);
+storage_schema!(
+    VALIDATOR_DISCORD: "#PoS/validator/<validator>/discord_handle" => String
+);
+storage_schema!(
+    VALIDATOR_AVATAR: "#PoS/validator/<validator>/avatar" => String
+);
+storage_schema!(VALIDATOR_NAME: "#PoS/validator/<validator>/name" => String);
+
+// Bonds and unbonds
+storage_schema!(
+    BOND: "#PoS/bond/<source>/<validator>/lazy_map/data/<start>"
+        => token::Amount
+);
+storage_schema!(
+    UNBOND: "#PoS/unbond/<source>/<validator>/data/<start>/data/<withdraw>"
+        => token::Amount
+);
+storage_schema!(
+    LAST_REWARD_CLAIM_EPOCH:
+        "#PoS/last_reward_claim_epoch/<source>/<validator>" => Epoch
+);
+storage_schema!(
+    TOTAL_DELTAS: "#PoS/total_deltas/lazy_map/data/<epoch>"
+        => token::Change as debug
+);
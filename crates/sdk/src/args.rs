@@ -1445,6 +1445,85 @@ impl TxUpdateAccount {
     }
 }
 
+/// Grant or revoke a fee allowance arguments
+#[derive(Clone, Debug)]
+pub struct TxUpdateFeeAllowance<C: NamadaTypes = SdkTypes> {
+    /// Common tx arguments
+    pub tx: Tx<C>,
+    /// Address of the account paying the fees
+    pub granter: C::Address,
+    /// Address of the account whose fees are paid
+    pub grantee: C::Address,
+    /// The token in which the fees can be paid
+    pub token: C::AddrOrNativeToken,
+    /// The maximum amount of fees that can be paid
+    pub spend_limit: InputAmount,
+    /// The epoch from which the allowance can no longer be used
+    pub expiration: Option<Epoch>,
+    /// Paths to the TX WASM codes that the allowance is restricted to
+    pub allowed_tx_code_paths: Vec<PathBuf>,
+    /// Revoke the allowance instead of granting it
+    pub revoke: bool,
+    /// Path to the TX WASM code file
+    pub tx_code_path: PathBuf,
+}
+
+impl<C: NamadaTypes> TxBuilder<C> for TxUpdateFeeAllowance<C> {
+    fn tx<F>(self, func: F) -> Self
+    where
+        F: FnOnce(Tx<C>) -> Tx<C>,
+    {
+        TxUpdateFeeAllowance {
+            tx: func(self.tx),
+            ..self
+        }
+    }
+}
+
+impl<C: NamadaTypes> TxUpdateFeeAllowance<C> {
+    /// The epoch from which the allowance can no longer be used
+    pub fn expiration(self, expiration: Epoch) -> Self {
+        Self {
+            expiration: Some(expiration),
+            ..self
+        }
+    }
+
+    /// Paths to the TX WASM codes that the allowance is restricted to
+    pub fn allowed_tx_code_paths(
+        self,
+        allowed_tx_code_paths: Vec<PathBuf>,
+    ) -> Self {
+        Self {
+            allowed_tx_code_paths,
+            ..self
+        }
+    }
+
+    /// Revoke the allowance instead of granting it
+    pub fn revoke(self, revoke: bool) -> Self {
+        Self { revoke, ..self }
+    }
+
+    /// Path to the TX WASM code file
+    pub fn tx_code_path(self, tx_code_path: PathBuf) -> Self {
+        Self {
+            tx_code_path,
+            ..self
+        }
+    }
+}
+
+impl TxUpdateFeeAllowance {
+    /// Build a transaction from this builder
+    pub async fn build(
+        &self,
+        context: &impl Namada,
+    ) -> crate::error::Result<(namada_tx::Tx, SigningData)> {
+        tx::build_update_fee_allowance(context, self).await
+    }
+}
+
 /// Bond arguments
 #[derive(Clone, Debug)]
 pub struct Bond<C: NamadaTypes = SdkTypes> {
@@ -2686,10 +2765,33 @@ pub struct Wrapper<C: NamadaTypes = SdkTypes> {
     pub fee_amount: Option<InputAmount>,
     /// The fee payer signing key
     pub wrapper_fee_payer: Option<C::PublicKey>,
+    /// The account that grants a fee allowance to the fee payer and pays the
+    /// fees in its place
+    pub fee_granter: Option<C::Address>,
     /// The token in which the fee is being paid
     pub fee_token: C::AddrOrNativeToken,
-    /// The max amount of gas used to process tx
+    /// The max amount of gas used to process tx. When the gas is estimated,
+    /// this is only the upper bound used for the estimation
     pub gas_limit: GasLimit,
+    /// Estimate the gas limit by dry-running the transaction
+    pub gas_estimation: Option<GasEstimation>,
+}
+
+/// Automatic estimation of the gas limit of a transaction
+#[derive(Clone, Copy, Debug)]
+pub struct GasEstimation {
+    /// The multiplier applied to the gas consumed by the dry-run, to account
+    /// for changes in the state before the transaction is applied
+    pub safety_multiplier: Dec,
+}
+
+impl Default for GasEstimation {
+    fn default() -> Self {
+        Self {
+            safety_multiplier: Dec::new(13, 1)
+                .expect("The default safety multiplier must be valid"),
+        }
+    }
 }
 
 /// Common transaction arguments
@@ -2697,6 +2799,9 @@ pub struct Wrapper<C: NamadaTypes = SdkTypes> {
 pub struct Tx<C: NamadaTypes = SdkTypes> {
     /// Simulate applying the transaction (possibly the wrapper too)
     pub dry_run: Option<DryRun>,
+    /// Display the breakdown of the gas consumed by the simulated
+    /// transaction
+    pub gas_profile: bool,
     /// Dump the transaction bytes to file, either the raw or the whole wrapper
     /// transaction
     pub dump_tx: Option<DumpTx>,
@@ -2768,6 +2873,10 @@ pub trait TxBuilder<C: NamadaTypes>: Sized {
     fn dry_run(self, dry_run: Option<DryRun>) -> Self {
         self.tx(|x| Tx { dry_run, ..x })
     }
+    /// Display the breakdown of the gas consumed by the simulated transaction
+    fn gas_profile(self, gas_profile: bool) -> Self {
+        self.tx(|x| Tx { gas_profile, ..x })
+    }
     /// Dump the transaction bytes to file
     fn dump_tx(self, dump_tx: Option<DumpTx>) -> Self {
         self.tx(|x| Tx { dump_tx, ..x })
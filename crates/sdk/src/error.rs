@@ -68,6 +68,9 @@ pub enum QueryError {
     /// synchronizing with the network.
     #[error("Node is still catching up with the network")]
     CatchingUp,
+    /// The query response could not be verified
+    #[error("Unable to verify the query response: {0}")]
+    Unverified(String),
 }
 
 /// Errors that deal with Decoding, Encoding, or Conversions
@@ -199,6 +202,12 @@ pub enum TxSubmitError {
          required for fees. Amount of the fees is {2} and the balance is {3}."
     )]
     BalanceTooLowForFees(Address, Address, String, String),
+    /// Fee allowance is missing or cannot cover the fees
+    #[error(
+        "The fee allowance granted by {0} to {1} cannot be used to pay the \
+         fees: {2}"
+    )]
+    InvalidFeeAllowance(Address, Address, String),
     /// Token Address does not exist on chain
     #[error("The token address {0} doesn't exist on chain.")]
     TokenDoesNotExist(Address),
@@ -25,7 +25,6 @@ use namada_io::{Client, Io, display, display_line, edisplay_line};
 use namada_token::Amount;
 use namada_token::storage_key::balance_key;
 use namada_tx::Tx;
-use namada_tx::data::Fee;
 use owo_colors::OwoColorize;
 use serde::Serialize;
 
@@ -43,7 +42,7 @@ use crate::queries::{
 };
 use crate::rpc::{query_storage_value, query_wasm_code_hash, validate_amount};
 use crate::signing::SigningData;
-use crate::tx::{ExtendedWrapperArgs, WrapArgs, derive_build_data};
+use crate::tx::{ExtendedWrapperArgs, add_wrapper, derive_build_data};
 use crate::{MaybeSync, Namada, args};
 
 /// Craft a transaction that adds a transfer to the Ethereum bridge pool.
@@ -113,22 +112,7 @@ pub async fn build_bridge_pool_tx(
     )
     .add_data(transfer);
 
-    if let Some(WrapArgs {
-        fee_amount,
-        fee_payer,
-        fee_token,
-        gas_limit,
-    }) = wrap_args
-    {
-        tx.add_wrapper(
-            Fee {
-                amount_per_gas_unit: fee_amount,
-                token: fee_token,
-            },
-            fee_payer,
-            gas_limit,
-        );
-    }
+    add_wrapper(context, &tx_args, &mut tx, wrap_args).await?;
 
     Ok((tx, signing_data))
 }
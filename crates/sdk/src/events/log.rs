@@ -1,14 +1,20 @@
 //! A log to store events emitted by `FinalizeBlock` calls in the ledger.
 //!
 //! The log will hold up to `N` events of a certain kind at a time, before
-//! resorting to pruning older events contained within.
+//! resorting to pruning older events contained within. Events of committed
+//! blocks are additionally persisted in the DB by the ledger (see
+//! [`namada_state::event_store`]), which can be queried along with the
+//! in-memory log via [`WithMatcher::collect_with_persisted`], or searched
+//! with a CometBFT [query](query::Query).
 
 use circular_queue::CircularQueue;
+use namada_state::{DB, DBIter, DbResult, event_store};
 use patricia_tree::map::StringPatriciaMap;
 
 use super::{EmitEvents, Event, EventType};
 
 pub mod dumb_queries;
+pub mod query;
 
 /// Parameters to configure the pruning of the event log.
 #[derive(Debug, Copy, Clone)]
@@ -139,6 +145,61 @@ impl<'log> WithMatcher<'log> {
                 queue.iter().filter(|&event| self.matcher.matches(event))
             })
     }
+
+    /// Iterates over the events matching the associated
+    /// [event matcher](dumb_queries::QueryMatcher) that were persisted
+    /// in the event store of `db`, in order of emission.
+    ///
+    /// Only the attributes index of the event store is looked up: a matcher
+    /// without attributes would have to scan every persisted event, so no
+    /// persisted events are returned for it.
+    pub fn iter_persisted<'db, D>(
+        &'db self,
+        db: &'db D,
+    ) -> impl Iterator<Item = DbResult<Event>> + 'db
+    where
+        D: DB + for<'iter> DBIter<'iter>,
+    {
+        let candidates = match self.matcher.first_attribute() {
+            Some((key, value)) => itertools::Either::Left(
+                event_store::iter_events_with_attribute(db, key, value),
+            ),
+            None => itertools::Either::Right(std::iter::empty()),
+        };
+        candidates.filter_map(|result| match result {
+            Ok((_height, event)) => {
+                self.matcher.matches(&event).then_some(Ok(event))
+            }
+            Err(err) => Some(Err(err)),
+        })
+    }
+
+    /// Collects the events matching the associated
+    /// [event matcher](dumb_queries::QueryMatcher) from the event store of
+    /// `db`. If no matching events were persisted, e.g. because the block
+    /// that emitted them has not been committed yet, these are collected
+    /// from the associated in-memory [`EventLog`] instead.
+    ///
+    /// The events of a block are persisted atomically, therefore the events
+    /// emitted by a single tx are never split across both sources.
+    pub fn collect_with_persisted<D>(&self, db: &D) -> DbResult<Vec<Event>>
+    where
+        D: DB + for<'iter> DBIter<'iter>,
+    {
+        let persisted =
+            self.iter_persisted(db).collect::<DbResult<Vec<_>>>()?;
+        if !persisted.is_empty() {
+            return Ok(persisted);
+        }
+        Ok(self
+            .log
+            .map
+            .iter_prefix(self.matcher.event_type())
+            .flat_map(|(_, queue)| queue.iter())
+            .filter(|&event| self.matcher.matches(event))
+            .cloned()
+            .collect())
+    }
 }
 
 #[cfg(test)]
@@ -278,4 +339,32 @@ mod event_log_tests {
             assert_eq!(mock_event(APPLIED_TX, format!("{i:064X}")), event);
         }
     }
+
+    /// Test that only matchers with attributes look up the persisted events.
+    #[test]
+    fn test_log_iter_persisted() {
+        use namada_core::chain::BlockHeight;
+        use namada_state::mockdb::MockDB;
+
+        let db = MockDB::default();
+        let mut batch = MockDB::batch();
+        event_store::add_events_to_batch(
+            &db,
+            &mut batch,
+            BlockHeight(1),
+            &mock_tx_events(HASH),
+        )
+        .unwrap();
+        db.exec_batch(batch).unwrap();
+
+        let log = EventLog::new(Params::default());
+        let with_attribute = log.with_matcher(applied!(HASH));
+        assert_eq!(with_attribute.iter_persisted(&db).count(), 1);
+
+        // A matcher without attributes would have to scan all the events
+        let without_attribute = log.with_matcher(
+            dumb_queries::QueryMatcher::with_event_type(APPLIED_TX),
+        );
+        assert_eq!(without_attribute.iter_persisted(&db).count(), 0);
+    }
 }
@@ -92,6 +92,15 @@ impl QueryMatcher {
         }
     }
 
+    /// Returns one of the attributes that this [`QueryMatcher`]
+    /// attempts to match, if any. Useful to look up candidate events
+    /// in an index of event attributes.
+    pub fn first_attribute(&self) -> Option<(&str, &str)> {
+        self.attributes
+            .first()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+
     /// Add a new attribute to the [`QueryMatcher`].
     #[inline]
     pub fn and_attribute<DATA>(mut self, data: DATA) -> Self
@@ -0,0 +1,760 @@
+//! Parser and evaluator of the CometBFT event query language.
+//!
+//! A query is a conjunction of conditions over the attributes of an event:
+//!
+//! ```text
+//! tm.event = 'tx/applied' AND tx/applied.height >= 100 AND code EXISTS
+//! ```
+//!
+//! Each condition is made of a tag, an operator and an operand (except for
+//! `EXISTS`, which takes no operand). The supported operators are `=`, `<`,
+//! `<=`, `>`, `>=`, `CONTAINS` and `EXISTS`. Operands are either strings
+//! delimited by single quotes, numbers, dates (`DATE 2024-01-31`) or
+//! timestamps (`TIME 2024-01-31T12:00:00Z`).
+//!
+//! Tags are resolved against an [`Event`] as follows:
+//! - `tm.event` refers to the type of the event.
+//! - `{event_type}.{key}` refers to the attribute `key` of the events of type
+//!   `event_type`, e.g. `tx/applied.hash`.
+//! - A tag without a dot refers to the attribute with that key, regardless of
+//!   the type of the event, e.g. `hash`.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use namada_core::chain::BlockHeight;
+use namada_state::{DB, DBIter, DbResult, event_store};
+use thiserror::Error;
+
+use crate::events::Event;
+
+/// The tag that refers to the type of an event.
+const EVENT_TYPE_TAG: &str = "tm.event";
+
+/// Errors encountered while parsing an event query.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum QueryError {
+    /// The query has no conditions.
+    #[error("The event query is empty")]
+    Empty,
+    /// A condition is missing its tag.
+    #[error("Expected a tag at position {0}")]
+    MissingTag(usize),
+    /// A condition has an unknown operator.
+    #[error("Expected an operator at position {0}")]
+    InvalidOperator(usize),
+    /// A condition is missing its operand.
+    #[error("Expected an operand at position {0}")]
+    MissingOperand(usize),
+    /// A string operand is missing its closing quote.
+    #[error("Unterminated string starting at position {0}")]
+    UnterminatedString(usize),
+    /// An operand could not be parsed.
+    #[error("Invalid operand {operand:?}: {reason}")]
+    InvalidOperand {
+        /// The operand that failed to parse.
+        operand: String,
+        /// Why the operand is invalid.
+        reason: &'static str,
+    },
+    /// An operator was given an operand it cannot be applied to.
+    #[error("Operator {operator} cannot be applied to {operand}")]
+    OperandMismatch {
+        /// The operator of the condition.
+        operator: Operator,
+        /// The operand of the condition.
+        operand: Operand,
+    },
+    /// Two conditions are not joined by `AND`.
+    #[error("Expected AND at position {0}")]
+    ExpectedAnd(usize),
+}
+
+/// A parsed CometBFT event query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Query {
+    conditions: Vec<Condition>,
+}
+
+/// A condition of a [`Query`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Condition {
+    tag: Tag,
+    operator: Operator,
+    operand: Option<Operand>,
+}
+
+/// What a condition of a [`Query`] refers to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Tag {
+    /// The type of the event.
+    EventType,
+    /// An attribute of the event, optionally restricted to events of the
+    /// given type.
+    Attribute {
+        event_type: Option<String>,
+        key: String,
+    },
+}
+
+/// Operator of a condition of a [`Query`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    /// `=`
+    Equal,
+    /// `<`
+    Less,
+    /// `<=`
+    LessOrEqual,
+    /// `>`
+    Greater,
+    /// `>=`
+    GreaterOrEqual,
+    /// `CONTAINS`
+    Contains,
+    /// `EXISTS`
+    Exists,
+}
+
+/// Operand of a condition of a [`Query`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Operand {
+    /// A string literal.
+    String(String),
+    /// A decimal number of arbitrary size and precision.
+    Number(Number),
+    /// A calendar date.
+    Date(NaiveDate),
+    /// A point in time.
+    Time(DateTime<Utc>),
+}
+
+/// Decimal number of arbitrary size and precision, such that the values of
+/// event attributes (e.g. token amounts) can be compared without loss.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Number {
+    negative: bool,
+    /// Integral digits, without leading zeros.
+    integral: String,
+    /// Fractional digits, without trailing zeros.
+    fractional: String,
+}
+
+impl FromStr for Number {
+    type Err = QueryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason| QueryError::InvalidOperand {
+            operand: s.to_owned(),
+            reason,
+        };
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, s),
+        };
+        let (integral, fractional) =
+            digits.split_once('.').unwrap_or((digits, ""));
+        if integral.is_empty() {
+            return Err(invalid("missing integral digits"));
+        }
+        if !integral
+            .chars()
+            .chain(fractional.chars())
+            .all(|c| c.is_ascii_digit())
+        {
+            return Err(invalid("not a decimal number"));
+        }
+        let integral = integral.trim_start_matches('0').to_owned();
+        let fractional = fractional.trim_end_matches('0').to_owned();
+        let is_zero = integral.is_empty() && fractional.is_empty();
+        Ok(Self {
+            negative: negative && !is_zero,
+            integral,
+            fractional,
+        })
+    }
+}
+
+impl Ord for Number {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let magnitude = || {
+            self.integral
+                .len()
+                .cmp(&other.integral.len())
+                .then_with(|| self.integral.cmp(&other.integral))
+                .then_with(|| self.fractional.cmp(&other.fractional))
+        };
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => magnitude(),
+            (true, true) => magnitude().reverse(),
+        }
+    }
+}
+
+impl PartialOrd for Number {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.negative {
+            write!(f, "-")?;
+        }
+        if self.integral.is_empty() {
+            write!(f, "0")?;
+        } else {
+            write!(f, "{}", self.integral)?;
+        }
+        if !self.fractional.is_empty() {
+            write!(f, ".{}", self.fractional)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let operator = match self {
+            Self::Equal => "=",
+            Self::Less => "<",
+            Self::LessOrEqual => "<=",
+            Self::Greater => ">",
+            Self::GreaterOrEqual => ">=",
+            Self::Contains => "CONTAINS",
+            Self::Exists => "EXISTS",
+        };
+        write!(f, "{operator}")
+    }
+}
+
+impl fmt::Display for Operand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::String(string) => write!(f, "'{string}'"),
+            Self::Number(number) => write!(f, "{number}"),
+            Self::Date(date) => write!(f, "DATE {}", date.format("%Y-%m-%d")),
+            Self::Time(time) => write!(f, "TIME {}", time.to_rfc3339()),
+        }
+    }
+}
+
+impl fmt::Display for Tag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EventType => write!(f, "{EVENT_TYPE_TAG}"),
+            Self::Attribute {
+                event_type: Some(event_type),
+                key,
+            } => write!(f, "{event_type}.{key}"),
+            Self::Attribute {
+                event_type: None,
+                key,
+            } => write!(f, "{key}"),
+        }
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.tag, self.operator)?;
+        if let Some(operand) = &self.operand {
+            write!(f, " {operand}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Query {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let conditions = itertools::join(&self.conditions, " AND ");
+        write!(f, "{conditions}")
+    }
+}
+
+impl FromStr for Query {
+    type Err = QueryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Parser { input: s, pos: 0 }.parse()
+    }
+}
+
+impl Tag {
+    fn parse(tag: &str) -> Self {
+        if tag == EVENT_TYPE_TAG {
+            return Self::EventType;
+        }
+        match tag.rsplit_once('.') {
+            Some((event_type, key)) => Self::Attribute {
+                event_type: Some(event_type.to_owned()),
+                key: key.to_owned(),
+            },
+            None => Self::Attribute {
+                event_type: None,
+                key: tag.to_owned(),
+            },
+        }
+    }
+
+    /// Resolve the value of this tag in the given event, if any.
+    fn resolve(&self, event: &Event) -> Option<String> {
+        match self {
+            Self::EventType => Some(event.kind().to_string()),
+            Self::Attribute { event_type, key } => {
+                if let Some(event_type) = event_type {
+                    if &**event.kind() != event_type {
+                        return None;
+                    }
+                }
+                event.raw_read_attribute_with_key(key).map(str::to_owned)
+            }
+        }
+    }
+}
+
+impl Operand {
+    /// Compare the value of an event attribute with this operand. Returns
+    /// `None` if the value cannot be interpreted as this kind of operand.
+    fn compare(&self, value: &str) -> Option<Ordering> {
+        match self {
+            Self::String(string) => Some(value.cmp(string)),
+            Self::Number(number) => {
+                Some(value.parse::<Number>().ok()?.cmp(number))
+            }
+            Self::Date(date) => Some(parse_date(value)?.cmp(date)),
+            Self::Time(time) => Some(parse_time(value)?.cmp(time)),
+        }
+    }
+}
+
+impl Condition {
+    /// Check if the given event satisfies this condition.
+    pub fn matches(&self, event: &Event) -> bool {
+        let Some(value) = self.tag.resolve(event) else {
+            return false;
+        };
+        let Some(operand) = &self.operand else {
+            // `EXISTS` is the only operator without an operand
+            return true;
+        };
+        if let (Operator::Contains, Operand::String(string)) =
+            (self.operator, operand)
+        {
+            return value.contains(string.as_str());
+        }
+        let Some(ordering) = operand.compare(&value) else {
+            return false;
+        };
+        match self.operator {
+            Operator::Equal => ordering.is_eq(),
+            Operator::Less => ordering.is_lt(),
+            Operator::LessOrEqual => ordering.is_le(),
+            Operator::Greater => ordering.is_gt(),
+            Operator::GreaterOrEqual => ordering.is_ge(),
+            Operator::Contains | Operator::Exists => false,
+        }
+    }
+}
+
+impl Query {
+    /// Return the conditions of this [`Query`].
+    pub fn conditions(&self) -> &[Condition] {
+        &self.conditions
+    }
+
+    /// Check if the given event satisfies all the conditions of this
+    /// [`Query`].
+    pub fn matches(&self, event: &Event) -> bool {
+        self.conditions
+            .iter()
+            .all(|condition| condition.matches(event))
+    }
+
+    /// Returns the key and value of an attribute that all the events
+    /// matching this [`Query`] must have, if any. Useful to look up
+    /// candidate events in an index of event attributes.
+    pub fn indexed_attribute(&self) -> Option<(&str, &str)> {
+        self.conditions.iter().find_map(|condition| {
+            match (&condition.tag, condition.operator, &condition.operand) {
+                (
+                    Tag::Attribute { key, .. },
+                    Operator::Equal,
+                    Some(Operand::String(value)),
+                ) => Some((key.as_str(), value.as_str())),
+                _ => None,
+            }
+        })
+    }
+
+    /// Iterates over the events matching this [`Query`] that were persisted
+    /// in the event store of `db`, along with the height they were emitted
+    /// at, in order of emission.
+    ///
+    /// Only the attributes index of the event store is looked up: a query
+    /// without an [indexed attribute](Self::indexed_attribute) would have to
+    /// scan every persisted event, so no persisted events are returned for it.
+    pub fn iter_persisted<'db, D>(
+        &'db self,
+        db: &'db D,
+    ) -> impl Iterator<Item = DbResult<(BlockHeight, Event)>> + 'db
+    where
+        D: DB + for<'iter> DBIter<'iter>,
+    {
+        let candidates = match self.indexed_attribute() {
+            Some((key, value)) => itertools::Either::Left(
+                event_store::iter_events_with_attribute(db, key, value),
+            ),
+            None => itertools::Either::Right(std::iter::empty()),
+        };
+        candidates.filter(move |result| match result {
+            Ok((_height, event)) => self.matches(event),
+            Err(_) => true,
+        })
+    }
+}
+
+/// Parse the value of an attribute as a date. Timestamps are truncated to
+/// their date.
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .or_else(|| parse_time(value).map(|time| time.date_naive()))
+}
+
+/// Parse the value of an attribute as a timestamp.
+fn parse_time(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|time| time.with_timezone(&Utc))
+}
+
+/// Recursive descent parser of event queries.
+struct Parser<'input> {
+    input: &'input str,
+    pos: usize,
+}
+
+impl<'input> Parser<'input> {
+    fn parse(mut self) -> Result<Query, QueryError> {
+        let mut conditions = vec![];
+        self.skip_whitespace();
+        if self.rest().is_empty() {
+            return Err(QueryError::Empty);
+        }
+        loop {
+            conditions.push(self.parse_condition()?);
+            self.skip_whitespace();
+            if self.rest().is_empty() {
+                return Ok(Query { conditions });
+            }
+            if !self.eat_keyword("AND") {
+                return Err(QueryError::ExpectedAnd(self.pos));
+            }
+        }
+    }
+
+    fn parse_condition(&mut self) -> Result<Condition, QueryError> {
+        self.skip_whitespace();
+        let tag = self.take_while(|c| {
+            !c.is_whitespace() && !matches!(c, '=' | '<' | '>' | '\'')
+        });
+        if tag.is_empty() {
+            return Err(QueryError::MissingTag(self.pos));
+        }
+        let tag = Tag::parse(tag);
+
+        self.skip_whitespace();
+        let operator = self.parse_operator()?;
+        if operator == Operator::Exists {
+            return Ok(Condition {
+                tag,
+                operator,
+                operand: None,
+            });
+        }
+
+        self.skip_whitespace();
+        let operand = self.parse_operand()?;
+        let is_valid = match (operator, &operand) {
+            (Operator::Equal, _) => true,
+            (Operator::Contains, operand) => {
+                matches!(operand, Operand::String(_))
+            }
+            (_, operand) => !matches!(operand, Operand::String(_)),
+        };
+        if !is_valid {
+            return Err(QueryError::OperandMismatch { operator, operand });
+        }
+
+        Ok(Condition {
+            tag,
+            operator,
+            operand: Some(operand),
+        })
+    }
+
+    fn parse_operator(&mut self) -> Result<Operator, QueryError> {
+        const OPERATORS: [(&str, Operator); 5] = [
+            ("<=", Operator::LessOrEqual),
+            (">=", Operator::GreaterOrEqual),
+            ("<", Operator::Less),
+            (">", Operator::Greater),
+            ("=", Operator::Equal),
+        ];
+        for (symbol, operator) in OPERATORS {
+            if self.rest().starts_with(symbol) {
+                self.pos += symbol.len();
+                return Ok(operator);
+            }
+        }
+        if self.eat_keyword("CONTAINS") {
+            return Ok(Operator::Contains);
+        }
+        if self.eat_keyword("EXISTS") {
+            return Ok(Operator::Exists);
+        }
+        Err(QueryError::InvalidOperator(self.pos))
+    }
+
+    fn parse_operand(&mut self) -> Result<Operand, QueryError> {
+        if let Some(rest) = self.rest().strip_prefix('\'') {
+            let start = self.pos;
+            let end = rest
+                .find('\'')
+                .ok_or(QueryError::UnterminatedString(start))?;
+            let string = rest[..end].to_owned();
+            // skip the quotes along with the string
+            self.pos += end + 2;
+            return Ok(Operand::String(string));
+        }
+        let invalid = |operand: &str, reason| QueryError::InvalidOperand {
+            operand: operand.to_owned(),
+            reason,
+        };
+        if self.eat_keyword("DATE") {
+            let date = self.take_operand()?;
+            return NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                .map(Operand::Date)
+                .map_err(|_| invalid(date, "expected a date as YYYY-MM-DD"));
+        }
+        if self.eat_keyword("TIME") {
+            let time = self.take_operand()?;
+            return parse_time(time).map(Operand::Time).ok_or_else(|| {
+                invalid(time, "expected an RFC 3339 timestamp")
+            });
+        }
+        self.take_operand()?.parse().map(Operand::Number)
+    }
+
+    /// Take the next whitespace delimited operand.
+    fn take_operand(&mut self) -> Result<&'input str, QueryError> {
+        self.skip_whitespace();
+        let operand = self.take_while(|c| !c.is_whitespace());
+        if operand.is_empty() {
+            return Err(QueryError::MissingOperand(self.pos));
+        }
+        Ok(operand)
+    }
+
+    /// Consume the given keyword, if it is next in the input and is followed
+    /// by whitespace or the end of the input.
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let Some(rest) = self.rest().strip_prefix(keyword) else {
+            return false;
+        };
+        if rest.is_empty() || rest.starts_with(char::is_whitespace) {
+            self.pos += keyword.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn take_while(&mut self, pred: impl Fn(char) -> bool) -> &'input str {
+        let rest = self.rest();
+        let len = rest.find(|c| !pred(c)).unwrap_or(rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+
+    fn skip_whitespace(&mut self) {
+        self.take_while(char::is_whitespace);
+    }
+
+    fn rest(&self) -> &'input str {
+        &self.input[self.pos..]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use namada_core::hash::Hash;
+
+    use super::*;
+    use crate::events::extend::{ComposeEvent, Height, TxHash};
+    use crate::events::{EventLevel, EventType};
+
+    const HASH: &str =
+        "DEADBEEFDEADBEEFDEADBEEFDEADBEEFDEADBEEFDEADBEEFDEADBEEFDEADBEEF";
+
+    fn mock_event(height: u64) -> Event {
+        let mut event: Event =
+            Event::new(EventType::new("tx/applied"), EventLevel::Tx)
+                .with(TxHash(Hash::try_from(HASH).unwrap()))
+                .with(Height(BlockHeight(height)))
+                .into();
+        #[allow(deprecated)]
+        event.attributes_mut().extend([
+            ("amount".to_owned(), "1000000000000000000000.5".to_owned()),
+            ("time".to_owned(), "2024-03-01T12:00:00Z".to_owned()),
+        ]);
+        event
+    }
+
+    fn matches(query: &str, event: &Event) -> bool {
+        query.parse::<Query>().unwrap().matches(event)
+    }
+
+    /// Test parsing queries and printing them back.
+    #[test]
+    fn test_query_parse_roundtrip() {
+        let query = "tm.event = 'tx/applied' AND tx/applied.height >= 10 AND \
+                     hash EXISTS AND memo CONTAINS 'foo bar' AND time < TIME \
+                     2024-01-01T00:00:00+00:00 AND day > DATE 2023-12-31";
+        let parsed: Query = query.parse().unwrap();
+        assert_eq!(parsed.conditions().len(), 6);
+        assert_eq!(parsed.to_string(), query);
+        assert_eq!(parsed.to_string().parse::<Query>().unwrap(), parsed);
+
+        // whitespace around operators is optional
+        assert_eq!(
+            "height>=10".parse::<Query>().unwrap(),
+            "height >= 10".parse::<Query>().unwrap(),
+        );
+    }
+
+    /// Test that malformed queries are rejected.
+    #[test]
+    fn test_query_parse_errors() {
+        assert_eq!("".parse::<Query>(), Err(QueryError::Empty));
+        assert_eq!("  ".parse::<Query>(), Err(QueryError::Empty));
+        assert_eq!("= 'foo'".parse::<Query>(), Err(QueryError::MissingTag(0)));
+        assert_eq!(
+            "hash ~ 'foo'".parse::<Query>(),
+            Err(QueryError::InvalidOperator(5))
+        );
+        assert_eq!(
+            "hash = 'foo".parse::<Query>(),
+            Err(QueryError::UnterminatedString(7))
+        );
+        assert_eq!(
+            "hash = 'foo' OR height = 1".parse::<Query>(),
+            Err(QueryError::ExpectedAnd(13))
+        );
+        assert!(matches!(
+            "height < 'foo'".parse::<Query>(),
+            Err(QueryError::OperandMismatch { .. })
+        ));
+        assert!(matches!(
+            "height CONTAINS 1".parse::<Query>(),
+            Err(QueryError::OperandMismatch { .. })
+        ));
+        assert!(matches!(
+            "height = 1.2.3".parse::<Query>(),
+            Err(QueryError::InvalidOperand { .. })
+        ));
+        assert!(matches!(
+            "day = DATE 2024-13-01".parse::<Query>(),
+            Err(QueryError::InvalidOperand { .. })
+        ));
+    }
+
+    /// Test comparing arbitrary precision numbers.
+    #[test]
+    fn test_number_ordering() {
+        let num = |s: &str| s.parse::<Number>().unwrap();
+        assert_eq!(num("0010.500"), num("10.5"));
+        assert_eq!(num("-0"), num("0.0"));
+        assert!(num("9") < num("10"));
+        assert!(num("0.5") < num("0.51"));
+        assert!(num("0.6") > num("0.51"));
+        assert!(num("-10") < num("-9"));
+        assert!(num("-0.1") < num("0"));
+        assert!(
+            num("1000000000000000000000000000000")
+                > num("999999999999999999999999999999.99")
+        );
+    }
+
+    /// Test evaluating queries over events.
+    #[test]
+    fn test_query_matches() {
+        let event = mock_event(42);
+
+        assert!(matches("tm.event = 'tx/applied'", &event));
+        assert!(!matches("tm.event = 'tx/other'", &event));
+        assert!(matches(&format!("hash = '{HASH}'"), &event));
+        assert!(matches(&format!("tx/applied.hash = '{HASH}'"), &event));
+        assert!(!matches(&format!("tx/other.hash = '{HASH}'"), &event));
+
+        assert!(matches("height = 42", &event));
+        assert!(matches("height = 42.0", &event));
+        assert!(matches("height > 41 AND height <= 42", &event));
+        assert!(!matches("height > 42", &event));
+        assert!(!matches("height < 100 AND height >= 43", &event));
+        assert!(matches("amount > 1000000000000000000000.4", &event));
+        assert!(!matches("amount >= 1000000000000000000001", &event));
+
+        assert!(matches("hash CONTAINS 'BEEF'", &event));
+        assert!(!matches("hash CONTAINS 'CAFE'", &event));
+        assert!(matches("hash EXISTS AND height EXISTS", &event));
+        assert!(!matches("code EXISTS", &event));
+        // non-numeric values never satisfy numeric conditions
+        assert!(!matches("hash > 0", &event));
+
+        assert!(matches("time > TIME 2024-03-01T11:59:59Z", &event));
+        assert!(matches("time = TIME 2024-03-01T13:00:00+01:00", &event));
+        assert!(matches("time = DATE 2024-03-01", &event));
+        assert!(matches("time < DATE 2024-03-02", &event));
+        assert!(!matches("time > DATE 2024-03-01", &event));
+    }
+
+    /// Test looking up persisted events with a query.
+    #[test]
+    fn test_query_persisted_events() {
+        use namada_state::mockdb::MockDB;
+
+        let db = MockDB::default();
+        let mut batch = MockDB::batch();
+        for height in 1..=5 {
+            let height = BlockHeight(height);
+            event_store::add_events_to_batch(
+                &db,
+                &mut batch,
+                height,
+                &[mock_event(height.0)],
+            )
+            .unwrap();
+        }
+        db.exec_batch(batch).unwrap();
+
+        let query: Query =
+            format!("hash = '{HASH}' AND height > 3").parse().unwrap();
+        assert_eq!(query.indexed_attribute(), Some(("hash", HASH)));
+        let heights: Vec<_> = query
+            .iter_persisted(&db)
+            .map(|result| result.unwrap().0)
+            .collect();
+        assert_eq!(heights, vec![BlockHeight(4), BlockHeight(5)]);
+
+        let query: Query = "height <= 2".parse().unwrap();
+        assert_eq!(query.indexed_attribute(), None);
+        assert_eq!(query.iter_persisted(&db).count(), 0);
+    }
+}
@@ -36,6 +36,7 @@ pub mod validation;
 
 pub mod error;
 pub mod events;
+pub mod light_client;
 pub(crate) mod internal_macros;
 
 #[cfg(feature = "migrations")]
@@ -71,8 +72,9 @@ use tx::{
     TX_IBC_WASM, TX_INIT_ACCOUNT_WASM, TX_INIT_PROPOSAL,
     TX_REACTIVATE_VALIDATOR_WASM, TX_REDELEGATE_WASM, TX_RESIGN_STEWARD,
     TX_REVEAL_PK, TX_TRANSFER_WASM, TX_UNBOND_WASM, TX_UNJAIL_VALIDATOR_WASM,
-    TX_UPDATE_ACCOUNT_WASM, TX_UPDATE_STEWARD_COMMISSION, TX_VOTE_PROPOSAL,
-    TX_WITHDRAW_WASM, VP_USER_WASM,
+    TX_UPDATE_ACCOUNT_WASM, TX_UPDATE_FEE_ALLOWANCE_WASM,
+    TX_UPDATE_STEWARD_COMMISSION, TX_VOTE_PROPOSAL, TX_WITHDRAW_WASM,
+    VP_USER_WASM,
 };
 use wallet::{Wallet, WalletIo, WalletStorage};
 pub use {namada_io as io, namada_wallet as wallet};
@@ -124,6 +126,7 @@ pub trait Namada: NamadaIo {
     fn tx_builder(&self) -> args::Tx {
         args::Tx {
             dry_run: None,
+            gas_profile: false,
             dump_tx: None,
             output_folder: None,
             force: false,
@@ -346,6 +349,28 @@ pub trait Namada: NamadaIo {
         }
     }
 
+    /// Make a TxUpdateFeeAllowance builder from the given minimum set of
+    /// arguments
+    fn new_update_fee_allowance(
+        &self,
+        granter: Address,
+        grantee: Address,
+        token: Address,
+        spend_limit: InputAmount,
+    ) -> args::TxUpdateFeeAllowance {
+        args::TxUpdateFeeAllowance {
+            granter,
+            grantee,
+            token,
+            spend_limit,
+            expiration: None,
+            allowed_tx_code_paths: vec![],
+            revoke: false,
+            tx_code_path: PathBuf::from(TX_UPDATE_FEE_ALLOWANCE_WASM),
+            tx: self.tx_builder(),
+        }
+    }
+
     /// Make a VoteProposal builder from the given minimum set of arguments
     fn new_proposal_vote(
         &self,
@@ -717,6 +742,7 @@ where
             native_token: native_token.clone(),
             prototype: args::Tx {
                 dry_run: None,
+                gas_profile: false,
                 dump_tx: None,
                 output_folder: None,
                 force: false,
@@ -0,0 +1,888 @@
+//! Storage queries with verified responses.
+//!
+//! The [`VerifiedClient`] wraps a [`Client`] to only return storage values
+//! whose proofs are verified against the app hash of a block header. The
+//! app hash of a header commits to the state after the previous block, so a
+//! value queried at a height `h` is verified with the header at `h + 1`.
+//!
+//! When the client is given a [`LightBlock`] that is trusted (e.g. one that
+//! was stored locally from a previous session), the headers are themselves
+//! verified with the CometBFT light client verification from the trusted
+//! block. Otherwise, the headers returned by the RPC node are trusted.
+
+use std::sync::Mutex;
+
+use borsh::BorshDeserialize;
+use namada_account::{Account, AccountPublicKeysMap};
+use namada_core::address::Address;
+use namada_core::chain::{BlockHeight, Epoch};
+use namada_core::hash::{Hash, Sha256Hasher};
+use namada_core::key::common;
+use namada_core::storage::Key;
+use namada_core::time::DateTimeUtc;
+use namada_core::token;
+use namada_governance::storage::keys as governance_keys;
+use namada_governance::storage::proposal::{ProposalType, StorageProposal};
+use namada_io::Client;
+use namada_state::merkle_tree::{MerkleRoot, Proof};
+use serde::{Deserialize, Serialize};
+use tendermint_light_client_verifier::options::Options;
+use tendermint_light_client_verifier::types::{
+    TrustedBlockState, UntrustedBlockState,
+};
+use tendermint_light_client_verifier::{ProdVerifier, Verdict, Verifier};
+
+use crate::error::{EncodingError, Error, QueryError};
+use crate::queries::RPC;
+use crate::rpc::convert_response;
+use crate::tendermint::block::Height;
+use crate::tendermint::block::signed_header::SignedHeader;
+use crate::tendermint::{AppHash, validator};
+use crate::tendermint_rpc::endpoint::validators;
+use crate::tendermint_rpc::{PageNumber, PerPage};
+
+/// The maximum number of validators that can be fetched per request
+const VALIDATORS_PER_PAGE: u8 = 100;
+
+/// A signed header with the validator sets needed to verify it and the
+/// headers following it
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LightBlock {
+    /// The signed header
+    pub signed_header: SignedHeader,
+    /// The validator set that signed the header
+    pub validators: validator::Set,
+    /// The validator set of the next block
+    pub next_validators: validator::Set,
+}
+
+impl LightBlock {
+    /// Fetch the light block at the given height. The validator sets are
+    /// checked against the hashes in the header.
+    pub async fn fetch<C: Client + Sync>(
+        client: &C,
+        height: Height,
+    ) -> Result<Self, Error> {
+        let signed_header = client
+            .commit(height)
+            .await
+            .map_err(|err| Error::from(QueryError::General(err.to_string())))?
+            .signed_header;
+        let validators = fetch_validators(client, height).await?;
+        let next_validators =
+            fetch_validators(client, height.increment()).await?;
+        let header = &signed_header.header;
+        if validators.hash() != header.validators_hash
+            || next_validators.hash() != header.next_validators_hash
+        {
+            return Err(Error::from(QueryError::Unverified(format!(
+                "The validator sets don't match the header at height {height}"
+            ))));
+        }
+        Ok(Self {
+            signed_header,
+            validators,
+            next_validators,
+        })
+    }
+
+    /// Get the height of the header
+    pub fn height(&self) -> Height {
+        self.signed_header.header.height
+    }
+
+    fn as_trusted_state(&self) -> TrustedBlockState<'_> {
+        let header = &self.signed_header.header;
+        TrustedBlockState {
+            chain_id: &header.chain_id,
+            header_time: header.time,
+            height: header.height,
+            next_validators: &self.next_validators,
+            next_validators_hash: header.next_validators_hash,
+        }
+    }
+
+    fn as_untrusted_state(&self) -> UntrustedBlockState<'_> {
+        UntrustedBlockState {
+            signed_header: &self.signed_header,
+            validators: &self.validators,
+            next_validators: Some(&self.next_validators),
+        }
+    }
+}
+
+/// Fetch all the validators at the given height
+async fn fetch_validators<C: Client + Sync>(
+    client: &C,
+    height: Height,
+) -> Result<validator::Set, Error> {
+    let mut validators = vec![];
+    for page in 1_usize.. {
+        let response = client
+            .perform(validators::Request::new(
+                Some(height),
+                Some(PageNumber::from(page)),
+                Some(PerPage::from(VALIDATORS_PER_PAGE)),
+            ))
+            .await
+            .map_err(|err| Error::from(QueryError::General(err.to_string())))?;
+        let is_empty = response.validators.is_empty();
+        validators.extend(response.validators);
+        let total = usize::try_from(response.total).map_err(|err| {
+            Error::from(EncodingError::Conversion(err.to_string()))
+        })?;
+        if is_empty || validators.len() >= total {
+            break;
+        }
+    }
+    Ok(validator::Set::without_proposer(validators))
+}
+
+/// A wrapper of a [`Client`] that only returns storage values that are
+/// verified against the app hash of a block header
+pub struct VerifiedClient<'a, C> {
+    client: &'a C,
+    /// The last trusted block and the light client verification options,
+    /// if the headers are to be verified
+    trusted: Option<(Mutex<LightBlock>, Options)>,
+}
+
+impl<'a, C: Client + Sync> VerifiedClient<'a, C> {
+    /// Wrap the client. The block headers returned by the client are trusted.
+    pub fn new(client: &'a C) -> Self {
+        Self {
+            client,
+            trusted: None,
+        }
+    }
+
+    /// Wrap the client and verify the block headers from the given trusted
+    /// block with the given options.
+    pub fn with_trusted_block(
+        client: &'a C,
+        trusted_block: LightBlock,
+        options: Options,
+    ) -> Self {
+        Self {
+            client,
+            trusted: Some((Mutex::new(trusted_block), options)),
+        }
+    }
+
+    /// Get the latest trusted block, which should be stored to verify the
+    /// headers of the later sessions from it
+    pub fn trusted_block(&self) -> Option<LightBlock> {
+        self.trusted
+            .as_ref()
+            .map(|(trusted, _)| trusted.lock().unwrap().clone())
+    }
+
+    /// Get the last block height whose state can be verified, i.e. the height
+    /// before the last committed block
+    pub async fn last_verifiable_height(&self) -> Result<BlockHeight, Error> {
+        let last_block = self
+            .client
+            .latest_commit()
+            .await
+            .map_err(|err| Error::from(QueryError::General(err.to_string())))?
+            .signed_header
+            .header
+            .height
+            .value();
+        last_block.checked_sub(1).map(BlockHeight).ok_or_else(|| {
+            Error::from(QueryError::NoResponse(
+                "No block has been committed yet".to_string(),
+            ))
+        })
+    }
+
+    /// Get the app hash which commits to the state at the given height,
+    /// from the header of the following block
+    pub async fn app_hash(
+        &self,
+        height: BlockHeight,
+    ) -> Result<AppHash, Error> {
+        let header_height = height
+            .checked_add(1_u64)
+            .and_then(|height| Height::try_from(height.0).ok())
+            .ok_or_else(|| {
+                Error::from(QueryError::General(format!(
+                    "Invalid block height {height}"
+                )))
+            })?;
+        let signed_header = match &self.trusted {
+            Some((trusted, options)) => {
+                self.verify_header(trusted, options, header_height).await?
+            }
+            None => {
+                self.client
+                    .commit(header_height)
+                    .await
+                    .map_err(|err| {
+                        Error::from(QueryError::General(err.to_string()))
+                    })?
+                    .signed_header
+            }
+        };
+        Ok(signed_header.header.app_hash)
+    }
+
+    /// Verify the header at the given height from the trusted block, by
+    /// bisection if the validator set changed too much to skip to it.
+    async fn verify_header(
+        &self,
+        trusted: &Mutex<LightBlock>,
+        options: &Options,
+        height: Height,
+    ) -> Result<SignedHeader, Error> {
+        let mut trusted_block = trusted.lock().unwrap().clone();
+        if height == trusted_block.height() {
+            return Ok(trusted_block.signed_header);
+        }
+        if height < trusted_block.height() {
+            return Err(Error::from(QueryError::Unverified(format!(
+                "The header at height {height} is before the trusted height {}",
+                trusted_block.height()
+            ))));
+        }
+        let now = {
+            #[allow(clippy::disallowed_methods)]
+            DateTimeUtc::now()
+        }
+        .try_into()
+        .map_err(|err: crate::tendermint::Error| {
+            Error::from(EncodingError::Conversion(err.to_string()))
+        })?;
+
+        let verifier = ProdVerifier::default();
+        let mut pending = vec![LightBlock::fetch(self.client, height).await?];
+        while let Some(untrusted_block) = pending.last() {
+            match verifier.verify_update_header(
+                untrusted_block.as_untrusted_state(),
+                trusted_block.as_trusted_state(),
+                options,
+                now,
+            ) {
+                Verdict::Success => {
+                    trusted_block =
+                        pending.pop().expect("The block should exist");
+                }
+                Verdict::NotEnoughTrust(tally) => {
+                    let trusted_height = trusted_block.height().value();
+                    let untrusted_height = untrusted_block.height().value();
+                    let pivot = trusted_height.checked_add(
+                        untrusted_height.saturating_sub(trusted_height) / 2,
+                    );
+                    let pivot = pivot
+                        .filter(|pivot| *pivot > trusted_height)
+                        .and_then(|pivot| Height::try_from(pivot).ok())
+                        .ok_or_else(|| {
+                            Error::from(QueryError::Unverified(format!(
+                                "Not enough trust in the header at height \
+                                 {untrusted_height}: {tally}"
+                            )))
+                        })?;
+                    pending.push(LightBlock::fetch(self.client, pivot).await?);
+                }
+                Verdict::Invalid(detail) => {
+                    return Err(Error::from(QueryError::Unverified(format!(
+                        "Invalid header at height {}: {detail}",
+                        untrusted_block.height()
+                    ))));
+                }
+            }
+        }
+        let signed_header = trusted_block.signed_header.clone();
+        *trusted.lock().unwrap() = trusted_block;
+        Ok(signed_header)
+    }
+
+    /// Query a storage value at the given height and verify it against the
+    /// given app hash. Returns `None` if the key is proven to not exist.
+    pub async fn storage_value_bytes(
+        &self,
+        key: &Key,
+        height: BlockHeight,
+        app_hash: &AppHash,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        let response = convert_response::<C, _>(
+            RPC.shell()
+                .storage_value(self.client, None, Some(height), true, key)
+                .await,
+        )?;
+        let unverified = |msg: String| Error::from(QueryError::Unverified(msg));
+        if response.height != height {
+            return Err(unverified(format!(
+                "Queried the height {height}, but got the value at height {}",
+                response.height
+            )));
+        }
+        let proof_ops = response.proof.ok_or_else(|| {
+            unverified(format!("No proof was given for the key {key}"))
+        })?;
+        let proof = Proof::try_from(&proof_ops)
+            .map_err(|err| unverified(err.to_string()))?;
+        if proof.key != *key {
+            return Err(unverified(format!(
+                "Queried the key {key}, but got a proof for the key {}",
+                proof.key
+            )));
+        }
+        let root = <[u8; 32]>::try_from(app_hash.as_bytes())
+            .map(MerkleRoot)
+            .map_err(|_| unverified(format!("Invalid app hash {app_hash}")))?;
+        // The kind of the proof, rather than the empty data, tells if the key
+        // is absent, as an existing key may have an empty value
+        if proof.is_non_existence() {
+            if !response.data.is_empty() {
+                return Err(unverified(format!(
+                    "Got a value with a non-existence proof for the key {key}"
+                )));
+            }
+            proof
+                .verify_non_existence::<Sha256Hasher>(&root)
+                .map_err(|err| unverified(err.to_string()))?;
+            Ok(None)
+        } else {
+            proof
+                .verify_existence::<Sha256Hasher>(&response.data, &root)
+                .map_err(|err| unverified(err.to_string()))?;
+            Ok(Some(response.data))
+        }
+    }
+
+    /// Query a storage value at the given height, verify it against the given
+    /// app hash and decode it with [`BorshDeserialize`].
+    pub async fn storage_value<T: BorshDeserialize>(
+        &self,
+        key: &Key,
+        height: BlockHeight,
+        app_hash: &AppHash,
+    ) -> Result<Option<T>, Error> {
+        self.storage_value_bytes(key, height, app_hash)
+            .await?
+            .map(|bytes| {
+                T::try_from_slice(&bytes).map_err(|err| {
+                    Error::from(EncodingError::Decoding(err.to_string()))
+                })
+            })
+            .transpose()
+    }
+
+    /// Get the given height or the last verifiable height, and the app hash
+    /// which commits to the state at that height
+    async fn height_and_app_hash(
+        &self,
+        height: Option<BlockHeight>,
+    ) -> Result<(BlockHeight, AppHash), Error> {
+        let height = match height {
+            Some(height) => height,
+            None => self.last_verifiable_height().await?,
+        };
+        let app_hash = self.app_hash(height).await?;
+        Ok((height, app_hash))
+    }
+
+    /// Query the verified token balance of the owner. The last verifiable
+    /// height is queried if no height is given.
+    pub async fn balance(
+        &self,
+        token: &Address,
+        owner: &Address,
+        height: Option<BlockHeight>,
+    ) -> Result<token::Amount, Error> {
+        let (height, app_hash) = self.height_and_app_hash(height).await?;
+        let key = crate::token::storage_key::balance_key(token, owner);
+        Ok(self
+            .storage_value(&key, height, &app_hash)
+            .await?
+            .unwrap_or_default())
+    }
+
+    /// Query the verified account of the owner. The last verifiable height is
+    /// queried if no height is given.
+    pub async fn account(
+        &self,
+        owner: &Address,
+        height: Option<BlockHeight>,
+    ) -> Result<Option<Account>, Error> {
+        let (height, app_hash) = self.height_and_app_hash(height).await?;
+        let exists = match owner {
+            Address::Established(_) => {
+                let vp_key = Key::validity_predicate(owner);
+                self.storage_value_bytes(&vp_key, height, &app_hash)
+                    .await?
+                    .is_some()
+            }
+            Address::Implicit(_) => true,
+            Address::Internal(_) => false,
+        };
+        if !exists {
+            return Ok(None);
+        }
+
+        let threshold: Option<u8> = self
+            .storage_value(
+                &namada_account::threshold_key(owner),
+                height,
+                &app_hash,
+            )
+            .await?;
+        // The public keys are stored with consecutive indices from 0, so the
+        // first absent index ends them
+        let pks_handle = namada_account::pks_handle(owner);
+        let mut public_keys: Vec<common::PublicKey> = vec![];
+        for index in 0..=u8::MAX {
+            let key = pks_handle.get_data_key(&index);
+            match self.storage_value(&key, height, &app_hash).await? {
+                Some(public_key) => public_keys.push(public_key),
+                None => break,
+            }
+        }
+
+        Ok(Some(Account {
+            public_keys_map: AccountPublicKeysMap::from_iter(public_keys),
+            address: owner.clone(),
+            threshold: threshold.unwrap_or(1),
+        }))
+    }
+
+    /// Query the verified amount of the bond of the source to the validator
+    /// that started contributing to the validator's stake at the given epoch.
+    /// The last verifiable height is queried if no height is given.
+    pub async fn bond(
+        &self,
+        source: &Address,
+        validator: &Address,
+        start_epoch: Epoch,
+        height: Option<BlockHeight>,
+    ) -> Result<token::Amount, Error> {
+        let (height, app_hash) = self.height_and_app_hash(height).await?;
+        let key =
+            namada_proof_of_stake::storage::bond_handle(source, validator)
+                .get_data_handler()
+                .get_data_key(&start_epoch);
+        Ok(self
+            .storage_value(&key, height, &app_hash)
+            .await?
+            .unwrap_or_default())
+    }
+
+    /// Query the verified proposal with the given ID. The last verifiable
+    /// height is queried if no height is given.
+    pub async fn proposal(
+        &self,
+        id: u64,
+        height: Option<BlockHeight>,
+    ) -> Result<Option<StorageProposal>, Error> {
+        let (height, app_hash) = self.height_and_app_hash(height).await?;
+        let Some(proposal_type) = self
+            .storage_value::<ProposalType>(
+                &governance_keys::get_proposal_type_key(id),
+                height,
+                &app_hash,
+            )
+            .await?
+        else {
+            return Ok(None);
+        };
+        let proposal_type =
+            if let ProposalType::DefaultWithWasm(_) = proposal_type {
+                let proposal_code: Option<Vec<u8>> = self
+                    .storage_value(
+                        &governance_keys::get_proposal_code_key(id),
+                        height,
+                        &app_hash,
+                    )
+                    .await?;
+                ProposalType::DefaultWithWasm(Hash::sha256(
+                    proposal_code.unwrap_or_default(),
+                ))
+            } else {
+                proposal_type
+            };
+
+        let missing = |field: &str| {
+            Error::from(QueryError::NoSuchKey(format!(
+                "The {field} of the proposal {id}"
+            )))
+        };
+        let author = self
+            .storage_value(
+                &governance_keys::get_author_key(id),
+                height,
+                &app_hash,
+            )
+            .await?
+            .ok_or_else(|| missing("author"))?;
+        let content = self
+            .storage_value(
+                &governance_keys::get_content_key(id),
+                height,
+                &app_hash,
+            )
+            .await?
+            .ok_or_else(|| missing("content"))?;
+        let voting_start_epoch = self
+            .storage_value(
+                &governance_keys::get_voting_start_epoch_key(id),
+                height,
+                &app_hash,
+            )
+            .await?
+            .ok_or_else(|| missing("voting start epoch"))?;
+        let voting_end_epoch = self
+            .storage_value(
+                &governance_keys::get_voting_end_epoch_key(id),
+                height,
+                &app_hash,
+            )
+            .await?
+            .ok_or_else(|| missing("voting end epoch"))?;
+        let activation_epoch = self
+            .storage_value(
+                &governance_keys::get_activation_epoch_key(id),
+                height,
+                &app_hash,
+            )
+            .await?
+            .ok_or_else(|| missing("activation epoch"))?;
+
+        Ok(Some(StorageProposal {
+            id,
+            content,
+            author,
+            r#type: proposal_type,
+            voting_start_epoch,
+            voting_end_epoch,
+            activation_epoch,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::time::Duration;
+
+    use assert_matches::assert_matches;
+    use namada_core::address::testing::{
+        established_address_1, established_address_2, nam,
+    };
+    use namada_storage::StorageWrite;
+    use tendermint_light_client_verifier::types::TrustThreshold;
+    use tendermint_testgen::{Generator, Header, Validator};
+
+    use super::*;
+    use crate::queries::EncodedResponseQuery;
+    use crate::queries::testing::TestClient;
+    use crate::tendermint::Time;
+    use crate::tendermint_rpc::endpoint::commit;
+    use crate::tendermint_rpc::error::Error as RpcError;
+    use crate::tendermint_rpc::request::RequestMessage;
+    use crate::tendermint_rpc::{Response, SimpleRequest};
+    use crate::token::storage_key::balance_key;
+
+    /// A mocked RPC node, which answers the storage queries from a test state
+    /// and the CometBFT queries from generated light blocks
+    struct MockClient {
+        client: TestClient<RPC>,
+        blocks: BTreeMap<u64, LightBlock>,
+        /// If set, the storage queries are answered for this key instead
+        proof_key: Option<Key>,
+        /// If set, the storage responses are labelled with this height
+        response_height: Option<BlockHeight>,
+        /// Drop the values from the storage responses
+        drop_values: bool,
+    }
+
+    impl MockClient {
+        /// Commit a balance of the first owner at height 1 and generate a
+        /// light block at each height from 1 signed by the given validator
+        /// sets. The header at height 2 commits to the state at height 1.
+        fn new(validator_sets: &[Vec<Validator>]) -> Self {
+            let mut client = TestClient::new(RPC);
+            client.state.in_mem_mut().block.height = 1.into();
+            client
+                .state
+                .write(
+                    &balance_key(&nam(), &established_address_1()),
+                    token::Amount::from(100),
+                )
+                .expect("Test failed");
+            client.state.commit_block().expect("Test failed");
+            let app_hash = AppHash::try_from(
+                client.state.in_mem().merkle_root().0.to_vec(),
+            )
+            .expect("Test failed");
+
+            let mut blocks = BTreeMap::new();
+            for (index, validators) in validator_sets.iter().enumerate() {
+                let height = u64::try_from(index)
+                    .ok()
+                    .and_then(|index| index.checked_add(1))
+                    .expect("Test failed");
+                let next_validators = validator_sets
+                    .get(index.checked_add(1).expect("Test failed"))
+                    .unwrap_or(validators);
+                let app_hash = (height == 2).then(|| app_hash.clone());
+                blocks.insert(
+                    height,
+                    light_block(height, validators, next_validators, app_hash),
+                );
+            }
+            Self {
+                client,
+                blocks,
+                proof_key: None,
+                response_height: None,
+                drop_values: false,
+            }
+        }
+    }
+
+    #[cfg_attr(feature = "async-send", async_trait::async_trait)]
+    #[cfg_attr(not(feature = "async-send"), async_trait::async_trait(?Send))]
+    impl Client for MockClient {
+        type Error = std::io::Error;
+
+        async fn request(
+            &self,
+            path: String,
+            data: Option<Vec<u8>>,
+            height: Option<BlockHeight>,
+            prove: bool,
+        ) -> Result<EncodedResponseQuery, Self::Error> {
+            let path = match &self.proof_key {
+                Some(key) => RPC.shell().storage_value_path(key),
+                None => path,
+            };
+            let mut response =
+                self.client.request(path, data, height, prove).await?;
+            if let Some(height) = self.response_height {
+                response.height = height;
+            }
+            if self.drop_values {
+                response.data.clear();
+            }
+            Ok(response)
+        }
+
+        async fn perform<R>(&self, request: R) -> Result<R::Output, RpcError>
+        where
+            R: SimpleRequest,
+        {
+            let request: serde_json::Value =
+                serde_json::from_str(&request.into_json())
+                    .map_err(|err| RpcError::parse(err.to_string()))?;
+            let height = match request["params"]["height"].as_str() {
+                Some(height) => height
+                    .parse()
+                    .map_err(|_| RpcError::parse(height.to_string()))?,
+                None => *self.blocks.keys().next_back().expect("Test failed"),
+            };
+            let block = self.blocks.get(&height).ok_or_else(|| {
+                RpcError::server(format!("No block at height {height}"))
+            })?;
+            let result = match request["method"].as_str() {
+                Some("commit") => serde_json::to_value(commit::Response {
+                    signed_header: block.signed_header.clone(),
+                    canonical: true,
+                }),
+                Some("validators") => {
+                    let validators = block.validators.validators().clone();
+                    let total =
+                        i32::try_from(validators.len()).expect("Test failed");
+                    serde_json::to_value(validators::Response::new(
+                        block.height(),
+                        validators,
+                        total,
+                    ))
+                }
+                method => {
+                    return Err(RpcError::method_not_found(format!(
+                        "{method:?}"
+                    )));
+                }
+            }
+            .map_err(|err| RpcError::parse(err.to_string()))?;
+            let response = serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": request["id"],
+                "result": result,
+            });
+            R::Response::from_string(response.to_string()).map(Into::into)
+        }
+    }
+
+    /// Generate a light block at the given height signed by the given
+    /// validators
+    fn light_block(
+        height: u64,
+        validators: &[Validator],
+        next_validators: &[Validator],
+        app_hash: Option<AppHash>,
+    ) -> LightBlock {
+        let time = Time::unix_epoch()
+            .checked_add(Duration::from_secs(height))
+            .expect("Test failed");
+        let mut header = Header::new(validators)
+            .height(height)
+            .chain_id("test-chain")
+            .time(time)
+            .next_validators(next_validators);
+        if let Some(app_hash) = app_hash {
+            header = header.app_hash(app_hash);
+        }
+        let block =
+            tendermint_testgen::LightBlock::new_default_with_header(header)
+                .next_validators(next_validators)
+                .generate()
+                .expect("Test failed");
+        LightBlock {
+            signed_header: block.signed_header,
+            validators: block.validators,
+            next_validators: block.next_validators,
+        }
+    }
+
+    /// The light client options to verify the generated headers, which are
+    /// timed from the Unix epoch
+    fn options() -> Options {
+        Options {
+            trust_threshold: TrustThreshold::ONE_THIRD,
+            trusting_period: Duration::from_secs(u32::MAX.into()),
+            clock_drift: Duration::from_secs(5),
+        }
+    }
+
+    fn validator_set(ids: &[&str]) -> Vec<Validator> {
+        ids.iter()
+            .map(|id| Validator::new(id).voting_power(50))
+            .collect()
+    }
+
+    /// Test that the values and their absence are verified against the app
+    /// hash of the header that follows the queried height
+    #[tokio::test]
+    async fn test_verified_values() {
+        let vals = validator_set(&["1", "2"]);
+        let client = MockClient::new(&[vals.clone(), vals.clone(), vals]);
+        let verified = VerifiedClient::new(&client);
+        let height = BlockHeight(1);
+
+        let app_hash = verified.app_hash(height).await.unwrap();
+        assert_eq!(app_hash, client.blocks[&2].signed_header.header.app_hash);
+        assert_eq!(
+            app_hash.as_bytes(),
+            client.client.state.in_mem().merkle_root().0
+        );
+
+        let balance = verified
+            .balance(&nam(), &established_address_1(), Some(height))
+            .await
+            .unwrap();
+        assert_eq!(balance, token::Amount::from(100));
+
+        // The absent balance is proven to not exist
+        let key = balance_key(&nam(), &established_address_2());
+        let value = verified
+            .storage_value_bytes(&key, height, &app_hash)
+            .await
+            .unwrap();
+        assert_eq!(value, None);
+
+        // The header at the queried height doesn't commit to its state
+        let key = balance_key(&nam(), &established_address_1());
+        let app_hash = &client.blocks[&1].signed_header.header.app_hash;
+        let result = verified.storage_value_bytes(&key, height, app_hash).await;
+        assert_matches!(result, Err(Error::Query(QueryError::Unverified(_))));
+    }
+
+    /// Test that the responses for another key or height, or without the
+    /// proven value, are rejected
+    #[tokio::test]
+    async fn test_unverified_responses() {
+        let vals = validator_set(&["1", "2"]);
+        let mut client = MockClient::new(&[vals.clone(), vals.clone(), vals]);
+        let height = BlockHeight(1);
+        let app_hash = client.blocks[&2].signed_header.header.app_hash.clone();
+        let key = balance_key(&nam(), &established_address_2());
+
+        // A proof of the existing value of another key
+        client.proof_key = Some(balance_key(&nam(), &established_address_1()));
+        let result = VerifiedClient::new(&client)
+            .storage_value_bytes(&key, height, &app_hash)
+            .await;
+        assert_matches!(result, Err(Error::Query(QueryError::Unverified(_))));
+
+        // The existence proof without its value can't pass for an absent key
+        client.proof_key = None;
+        client.drop_values = true;
+        let key = balance_key(&nam(), &established_address_1());
+        let result = VerifiedClient::new(&client)
+            .storage_value_bytes(&key, height, &app_hash)
+            .await;
+        assert_matches!(result, Err(Error::Query(QueryError::Unverified(_))));
+
+        // A response for another height
+        client.drop_values = false;
+        client.response_height = Some(BlockHeight(2));
+        let result = VerifiedClient::new(&client)
+            .storage_value_bytes(&key, height, &app_hash)
+            .await;
+        assert_matches!(result, Err(Error::Query(QueryError::Unverified(_))));
+    }
+
+    /// Test that the headers are verified from the trusted block, by
+    /// bisection when the validator set changes
+    #[tokio::test]
+    async fn test_verified_headers() {
+        let old_vals = validator_set(&["1", "2"]);
+        let new_vals = validator_set(&["3", "4"]);
+        let client = MockClient::new(&[
+            old_vals.clone(),
+            old_vals,
+            new_vals.clone(),
+            new_vals.clone(),
+            new_vals,
+        ]);
+
+        // The adjacent header is verified to query a value
+        let verified = VerifiedClient::with_trusted_block(
+            &client,
+            client.blocks[&1].clone(),
+            options(),
+        );
+        let balance = verified
+            .balance(&nam(), &established_address_1(), Some(BlockHeight(1)))
+            .await
+            .unwrap();
+        assert_eq!(balance, token::Amount::from(100));
+        assert_eq!(verified.trusted_block().unwrap().height().value(), 2);
+
+        // The validators of the trusted block didn't sign the header at
+        // height 4, so it is verified with the header at height 2
+        let verified = VerifiedClient::with_trusted_block(
+            &client,
+            client.blocks[&1].clone(),
+            options(),
+        );
+        let app_hash = verified.app_hash(BlockHeight(3)).await.unwrap();
+        assert_eq!(app_hash, client.blocks[&4].signed_header.header.app_hash);
+        assert_eq!(verified.trusted_block().unwrap().height().value(), 4);
+
+        // A header before the trusted one can't be verified
+        let result = verified.app_hash(BlockHeight(1)).await;
+        assert_matches!(result, Err(Error::Query(QueryError::Unverified(_))));
+
+        // The bisection fails from a trusted block of another chain
+        let fork_vals = validator_set(&["5", "6"]);
+        let fork = light_block(1, &fork_vals, &fork_vals, None);
+        let verified =
+            VerifiedClient::with_trusted_block(&client, fork, options());
+        let result = verified.app_hash(BlockHeight(3)).await;
+        assert_matches!(result, Err(Error::Query(QueryError::Unverified(_))));
+        assert_eq!(verified.trusted_block().unwrap().height().value(), 1);
+    }
+}
@@ -7,9 +7,10 @@ use std::path::{Path, PathBuf};
 use borsh::{BorshDeserialize, BorshSerialize};
 use data_encoding::HEXUPPER;
 use eyre::eyre;
+use namada_core::address::Address;
 use namada_core::chain::BlockHeight;
 use namada_core::hash::Hash;
-use namada_core::storage;
+use namada_core::storage::{self, DbKeySeg};
 use namada_macros::{derive_borshdeserializer, typehash};
 use namada_migrations::{TypeHash, *};
 use namada_state::merkle_tree::NO_DIFF_KEY_PREFIX;
@@ -20,6 +21,7 @@ use serde::de::{Error, Visitor};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use crate::borsh::BorshSerializeExt;
+use crate::queries::DecodedValue;
 
 /// The maximum number of character printed per value.
 const PRINTLN_CUTOFF: usize = 300;
@@ -691,6 +693,37 @@ static BTREEMAP_STRING_ADDRESS: fn() = || {
     );
 };
 
+/// Decode a storage value with the storage schema registered for its key by
+/// the protocol's modules. Returns `None` if no schema matches the key.
+pub fn decode_storage_value(
+    key: &storage::Key,
+    bytes: &[u8],
+) -> Option<DecodedValue> {
+    let segments: Vec<schema::KeySegment> = key
+        .segments
+        .iter()
+        .map(|segment| schema::KeySegment {
+            raw: segment.raw(),
+            internal_address: match segment {
+                DbKeySeg::AddressSeg(Address::Internal(addr)) => {
+                    Some(addr.to_string())
+                }
+                _ => None,
+            },
+        })
+        .collect();
+    let (schema, bindings) = schema::find_schema(&segments)?;
+    Some(DecodedValue {
+        pattern: schema.pattern.to_string(),
+        type_name: schema.type_name.to_string(),
+        bindings: bindings
+            .into_iter()
+            .map(|(name, segment)| (name.to_string(), segment))
+            .collect(),
+        json: (schema.decode)(bytes).map(|value| value.to_string()),
+    })
+}
+
 #[cfg(test)]
 mod test_migrations {
     use namada_core::token::Amount;
@@ -721,4 +754,45 @@ mod test_migrations {
         );
         assert!(migration.is_err());
     }
+
+    /// Check that storage values are decoded with the schemas registered by
+    /// the protocol's modules.
+    #[test]
+    fn test_decode_storage_value() {
+        let token = namada_core::address::testing::nam();
+        let owner = namada_core::address::testing::established_address_1();
+        let key = namada_token::storage_key::balance_key(&token, &owner);
+        let amount = Amount::native_whole(1337);
+
+        let decoded = decode_storage_value(&key, &amount.serialize_to_vec())
+            .expect("Test failed");
+        assert_eq!(decoded.pattern, "#Multitoken/<token>/balance/<owner>");
+        assert_eq!(decoded.type_name, "Amount");
+        assert_eq!(
+            decoded.bindings,
+            vec![
+                ("token".to_string(), token.to_db_key().raw()),
+                ("owner".to_string(), owner.to_db_key().raw()),
+            ]
+        );
+        assert_eq!(
+            decoded.to_json()["value"],
+            serde_json::to_value(amount).expect("Test failed")
+        );
+
+        // The minted balance has a more specific schema
+        let key = namada_token::storage_key::minted_balance_key(&token);
+        let decoded = decode_storage_value(&key, &amount.serialize_to_vec())
+            .expect("Test failed");
+        assert_eq!(decoded.pattern, "#Multitoken/<token>/balance/minted");
+
+        // Bytes that don't decode as the schema's type
+        let decoded =
+            decode_storage_value(&key, &[1, 2, 3]).expect("Test failed");
+        assert!(decoded.json.is_none());
+
+        // A key without a schema
+        let key = storage::Key::parse("bing/bong").expect("Test failed");
+        assert!(decode_storage_value(&key, &[]).is_none());
+    }
 }
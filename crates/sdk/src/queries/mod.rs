@@ -2,9 +2,10 @@
 //! defined via `router!` macro.
 
 // Re-export to show in rustdoc!
+use namada_core::arith::checked;
 use namada_state::{DB, DBIter, StorageHasher};
 use shell::SHELL;
-pub use shell::Shell;
+pub use shell::{DecodedValue, MAX_EVENTS_PER_PAGE, MAX_EVENTS_SCANNED, Shell};
 pub use types::{
     EncodedResponseQuery, Error, RequestCtx, RequestQuery, ResponseQuery,
     Router,
@@ -46,6 +47,12 @@ where
 
 // Handler helpers:
 
+/// The error of the queries that only support the latest height
+const LATEST_HEIGHT_ONLY: &str = "This query doesn't support arbitrary block \
+                                  heights, only the latest committed block \
+                                  height ('0' can be used as a special value \
+                                  that means the latest block height)";
+
 /// For queries that only support latest height, check that the given height is
 /// not different from latest height, otherwise return an error.
 pub fn require_latest_height<D, H, V, T>(
@@ -60,15 +67,59 @@ where
         && request.height.value()
             != ctx.state.in_mem().get_last_block_height().0
     {
-        return Err(namada_storage::Error::new_const(
-            "This query doesn't support arbitrary block heights, only the \
-             latest committed block height ('0' can be used as a special \
-             value that means the latest block height)",
-        ));
+        return Err(namada_storage::Error::new_const(LATEST_HEIGHT_ONLY));
     }
     Ok(())
 }
 
+/// For queries that read the in-memory state of the last committed block, or
+/// otherwise don't read the state at the height that the router's handler has
+/// pinned, check that the state is at the latest height, otherwise return an
+/// error.
+pub fn require_latest_state<D, H, V, T>(
+    ctx: &RequestCtx<'_, D, H, V, T>,
+) -> namada_storage::Result<()>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    if ctx.state.height() != ctx.state.in_mem().get_last_block_height() {
+        return Err(namada_storage::Error::new_const(LATEST_HEIGHT_ONLY));
+    }
+    Ok(())
+}
+
+/// For queries that support arbitrary block heights, pin the context's state
+/// to the requested height. Returns an error if the block at the requested
+/// height hasn't been committed yet or if it's older than the configured
+/// `storage_read_past_height_limit`.
+pub fn with_request_height<'shell, D, H, V, T>(
+    ctx: RequestCtx<'shell, D, H, V, T>,
+    request: &RequestQuery,
+) -> namada_storage::Result<RequestCtx<'shell, D, H, V, T>>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    let state = ctx.state.at_height(request.height.into())?;
+    let queried_height = state.height();
+    let last_committed_height = ctx.state.in_mem().get_last_block_height();
+    if let Some(past_height_limit) = ctx.storage_read_past_height_limit {
+        if checked!(queried_height + past_height_limit)? < last_committed_height
+        {
+            return Err(namada_storage::Error::new(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "Cannot query more than {past_height_limit} blocks in the \
+                     past (configured via \
+                     `shell.storage_read_past_height_limit`)."
+                ),
+            )));
+        }
+    }
+    Ok(RequestCtx { state, ..ctx })
+}
+
 /// For queries that do not support proofs, check that proof is not requested,
 /// otherwise return an error.
 pub fn require_no_proof(request: &RequestQuery) -> namada_storage::Result<()> {
@@ -96,6 +147,7 @@ pub fn require_no_data(request: &RequestQuery) -> namada_storage::Result<()> {
 pub(crate) mod testing {
     use namada_core::chain::BlockHeight;
     use namada_io::client::Client;
+    use namada_state::HistoricState;
     use namada_state::testing::TestState;
     use tendermint_rpc::Response;
 
@@ -174,7 +226,7 @@ pub(crate) mod testing {
                 prove,
             };
             let ctx = RequestCtx {
-                state: self.state.read_only(),
+                state: HistoricState::new(self.state.read_only()),
                 event_log: &self.event_log,
                 vp_wasm_cache: (),
                 tx_wasm_cache: (),
@@ -82,11 +82,12 @@ macro_rules! handle_match {
                 break
         }
         // Check that the request is not sent with unsupported non-default
-        $crate::queries::require_latest_height(&$ctx, $request)?;
         $crate::queries::require_no_proof($request)?;
         $crate::queries::require_no_data($request)?;
 
-        let last_committed_height = $ctx.state.in_mem().get_last_block_height();
+        // Read the state at the requested height
+        let $ctx = $crate::queries::with_request_height($ctx, $request)?;
+        let queried_height = $ctx.state.height();
         // If you get a compile error from here with `expected function, found
         // queries::Storage`, you're probably missing the marker `(sub _)`
         let data = $handle($ctx, $( $matched_args ),* )?;
@@ -96,7 +97,7 @@ macro_rules! handle_match {
             data,
             info: Default::default(),
             proof: None,
-            height: last_committed_height,
+            height: queried_height,
         });
     };
 }
@@ -401,8 +402,8 @@ macro_rules! pattern_and_handler_to_method {
             #[allow(dead_code)]
             #[allow(clippy::too_many_arguments)]
             #[doc = "Request value with optional data (used for e.g. \
-                `dry_run_tx`), optionally specified height (supported for \
-                `storage_value`) and optional proof (supported for \
+                `dry_run_tx`), optionally specified height (defaults to \
+                the router's height) and optional proof (supported for \
                 `storage_value` and `storage_prefix`) from `storage_value`."]
             pub async fn storage_value<CLIENT>(&self, client: &CLIENT,
                 data: Option<Vec<u8>>,
@@ -419,7 +420,9 @@ macro_rules! pattern_and_handler_to_method {
 
                     let $crate::queries::ResponseQuery {
                         data, info, proof, height,
-                    } = client.request(path, data, height, prove).await?;
+                    } = client
+                        .request(path, data, height.or(self.height), prove)
+                        .await?;
 
                     Ok($crate::queries::ResponseQuery {
                         data,
@@ -453,8 +456,8 @@ macro_rules! pattern_and_handler_to_method {
             #[allow(dead_code)]
             #[allow(clippy::too_many_arguments)]
             #[doc = "Request value with optional data (used for e.g. \
-                `dry_run_tx`), optionally specified height (supported for \
-                `storage_value`) and optional proof (supported for \
+                `dry_run_tx`), optionally specified height (defaults to \
+                the router's height) and optional proof (supported for \
                 `storage_value` and `storage_prefix`) from `" $handle "`."]
             pub async fn $handle<CLIENT>(&self, client: &CLIENT,
                 data: Option<Vec<u8>>,
@@ -471,7 +474,9 @@ macro_rules! pattern_and_handler_to_method {
 
                     let $crate::queries::ResponseQuery {
                         data, info, proof, height
-                    } = client.request(path, data, height, prove).await?;
+                    } = client
+                        .request(path, data, height.or(self.height), prove)
+                        .await?;
 
                     let decoded: $return_type =
                         borsh::BorshDeserialize::try_from_slice(&data[..])?;
@@ -508,8 +513,8 @@ macro_rules! pattern_and_handler_to_method {
             #[allow(dead_code)]
             #[allow(clippy::too_many_arguments)]
             #[doc = "Request a simple borsh-encoded value from `" $handle "`, \
-                without any additional request data, specified block height or \
-                proof."]
+                without any additional request data or proof, at the router's \
+                block height, if any."]
             pub async fn $handle<CLIENT>(&self, client: &CLIENT,
                 $( $param: &$param_ty ),*
             )
@@ -520,7 +525,15 @@ macro_rules! pattern_and_handler_to_method {
                 where CLIENT: namada_io::Client + std::marker::Sync {
                     let path = self.[<$handle _path>]( $( $param ),* );
 
-                    let data = client.simple_request(path).await?;
+                    let data = match self.height {
+                        Some(height) => {
+                            client
+                                .request(path, None, Some(height), false)
+                                .await?
+                                .data
+                        }
+                        None => client.simple_request(path).await?,
+                    };
 
                     let decoded: $return_type =
                         borsh::BorshDeserialize::try_from_slice(&data[..])?;
@@ -633,6 +646,7 @@ macro_rules! router_type {
             #[doc = "`" $name "`path router type"]
             pub struct $name {
                 prefix: String,
+                height: Option<namada_core::chain::BlockHeight>,
             }
 
             impl $name {
@@ -640,6 +654,7 @@ macro_rules! router_type {
                 pub const fn new() -> Self {
                     Self {
                         prefix: String::new(),
+                        height: None,
                     }
                 }
 
@@ -648,18 +663,29 @@ macro_rules! router_type {
                 pub const fn sub(prefix: String) -> Self {
                     Self {
                         prefix,
+                        height: None,
                     }
                 }
 
+                #[allow(dead_code)]
+                #[doc = "Query the state at the given block height, or at the \
+                    last committed block height if `None`. The height is \
+                    inherited by sub-routers and it's used by requests that \
+                    don't specify a height explicitly."]
+                pub fn at_height(
+                    self,
+                    height: Option<namada_core::chain::BlockHeight>,
+                ) -> Self {
+                    Self { height, ..self }
+                }
+
                 // paste the generated methods
                 $( $methods )*
             }
 
             impl Default for $name {
                 fn default() -> Self {
-                    Self {
-                        prefix: String::new(),
-                    }
+                    Self::new()
                 }
             }
         }
@@ -679,7 +705,7 @@ macro_rules! router_type {
                         // prefix for a sub can only contain literals
                         let current_prefix: &[&'static str] = pattern_to_prefix!($pattern);
                         let path = [&[self.prefix.as_str()][..], current_prefix].concat().join("/");
-                        [<$router:camel>]::sub(path)
+                        [<$router:camel>]::sub(path).at_height(self.height)
                     }
                     $( $methods )*
                 },
@@ -1026,6 +1052,7 @@ mod test {
     use namada_core::tendermint::block;
     use namada_core::token;
     use namada_core::token::NATIVE_MAX_DECIMAL_PLACES;
+    use namada_state::HistoricState;
 
     use super::test_rpc::TEST_RPC;
     use crate::queries::testing::TestClient;
@@ -1045,7 +1072,7 @@ mod test {
         };
         let ctx = RequestCtx {
             event_log: &client.event_log,
-            state: &client.state,
+            state: HistoricState::new(&client.state),
             vp_wasm_cache: (),
             tx_wasm_cache: (),
             storage_read_past_height_limit: None,
@@ -1064,7 +1091,7 @@ mod test {
         };
         let ctx = RequestCtx {
             event_log: &client.event_log,
-            state: &client.state,
+            state: HistoricState::new(&client.state),
             vp_wasm_cache: (),
             tx_wasm_cache: (),
             storage_read_past_height_limit: None,
@@ -1081,7 +1108,7 @@ mod test {
         };
         let ctx = RequestCtx {
             event_log: &client.event_log,
-            state: &client.state,
+            state: HistoricState::new(&client.state),
             vp_wasm_cache: (),
             tx_wasm_cache: (),
             storage_read_past_height_limit: None,
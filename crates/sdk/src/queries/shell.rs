@@ -2,7 +2,7 @@ use std::collections::BTreeMap;
 
 pub(super) mod eth_bridge;
 
-use borsh::BorshDeserialize;
+use borsh::{BorshDeserialize, BorshSerialize};
 use masp_primitives::asset_type::AssetType;
 use masp_primitives::merkle_tree::MerklePath;
 use masp_primitives::sapling::Node;
@@ -12,30 +12,35 @@ use namada_core::arith::checked;
 use namada_core::chain::{BlockHeader, BlockHeight, Epoch};
 use namada_core::dec::Dec;
 use namada_core::hash::Hash;
-use namada_core::hints;
 use namada_core::masp::{MaspEpoch, TokenMap};
 use namada_core::storage::{self, BlockResults, KeySeg, PrefixValue};
 use namada_core::time::DurationSecs;
-use namada_core::token::{Denomination, MaspDigitPos};
+use namada_core::token::{self, Denomination, MaspDigitPos};
 use namada_core::uint::Uint;
 use namada_ibc::event::IbcEventType;
-use namada_state::{DB, DBIter, LastBlock, StateRead, StorageHasher};
+use namada_state::{
+    DB, DBIter, LastBlock, StateRead, StorageHasher, event_store,
+};
 use namada_storage::{ResultExt, StorageRead};
 use namada_token::masp::MaspTokenRewardData;
 use namada_token::storage_key::masp_token_map_key;
-use namada_tx::data::DryRunResult;
+use namada_tx::data::{DryRunResult, ProfiledDryRunResult};
 use namada_tx::event::types::APPLIED;
 
 use self::eth_bridge::{ETH_BRIDGE, EthBridge};
 use crate::borsh::BorshSerializeExt;
 use crate::events::Event;
 use crate::events::log::dumb_queries;
+use crate::events::log::query::Query;
 use crate::ibc::core::host::types::identifiers::{
     ChannelId, ClientId, PortId, Sequence,
 };
 use crate::queries::types::{RequestCtx, RequestQuery};
-use crate::queries::{EncodedResponseQuery, require_latest_height};
-use crate::rpc::TxAppliedEvents;
+use crate::queries::{
+    EncodedResponseQuery, require_latest_height, require_latest_state,
+    with_request_height,
+};
+use crate::rpc::{EventsPage, TxAppliedEvents};
 use crate::tendermint::merkle::proof::ProofOps;
 
 type ConversionWithoutPath = (
@@ -55,6 +60,51 @@ type Conversion = (
     MerklePath<Node>,
 );
 
+/// The maximum number of events returned in a page of the `events` query.
+pub const MAX_EVENTS_PER_PAGE: u64 = 100;
+
+/// The maximum number of persisted events with the indexed attribute of a
+/// query that are looked up by the `events` query.
+pub const MAX_EVENTS_SCANNED: u64 = 10_000;
+
+/// A storage value decoded with the storage schema registered for its key.
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize)]
+pub struct DecodedValue {
+    /// The pattern of the schema that matched the key
+    pub pattern: String,
+    /// The name of the value's type
+    pub type_name: String,
+    /// The key segments bound to the named segments of the pattern
+    pub bindings: Vec<(String, String)>,
+    /// The value encoded as JSON, or `None` if the stored bytes don't decode
+    /// as the schema's type
+    pub json: Option<String>,
+}
+
+impl DecodedValue {
+    /// Render the decoded value together with its schema as JSON.
+    pub fn to_json(&self) -> serde_json::Value {
+        let bindings: serde_json::Map<String, serde_json::Value> = self
+            .bindings
+            .iter()
+            .map(|(name, segment)| {
+                (name.clone(), serde_json::Value::String(segment.clone()))
+            })
+            .collect();
+        let value = self
+            .json
+            .as_ref()
+            .and_then(|json| serde_json::from_str(json).ok())
+            .unwrap_or(serde_json::Value::Null);
+        serde_json::json!({
+            "pattern": self.pattern,
+            "type": self.type_name,
+            "bindings": bindings,
+            "value": value,
+        })
+    }
+}
+
 router! {SHELL,
     // Shell provides storage read access, block metadata and can dry-run a tx
 
@@ -86,6 +136,10 @@ router! {SHELL,
     // Dry run a transaction
     ( "dry_run_tx" ) -> DryRunResult = (with_options dry_run_tx),
 
+    // Dry run a transaction and profile the gas it consumes
+    ( "dry_run_tx_with_gas_profile" )
+        -> ProfiledDryRunResult = (with_options dry_run_tx_with_gas_profile),
+
     // Raw storage access - prefix iterator
     ( "prefix" / [storage_key: storage::Key] )
         -> Vec<PrefixValue> = (with_options storage_prefix),
@@ -94,6 +148,11 @@ router! {SHELL,
     ( "has_key" / [storage_key: storage::Key] )
         -> bool = storage_has_key,
 
+    // Read a storage value and decode it with the storage schema registered
+    // for its key
+    ( "decoded_value" / [storage_key: storage::Key] )
+        -> Option<DecodedValue> = decoded_value,
+
     // Conversion state access - read conversion
     ( "conv" / [asset_type: AssetType] ) -> Option<Conversion> = read_conversion,
 
@@ -109,6 +168,11 @@ router! {SHELL,
     // was the transaction applied?
     ( "applied" / [tx_hash: Hash] ) -> Option<TxAppliedEvents> = applied,
 
+    // Search the persisted events with a CometBFT query, passed as the
+    // request data
+    ( "events" / [page: u64] / [per_page: u64] )
+        -> EventsPage = (with_options search_events),
+
     // Query account subspace
     ( "account" / [owner: Address] ) -> Option<Account> = account,
 
@@ -126,6 +190,9 @@ router! {SHELL,
 
     // Return an estimate of the maximum time taken to decide a block
     ( "max_block_time" ) -> DurationSecs = max_block_time,
+
+    // Get the current base fee of a token allowed for fee payment
+    ( "base_fee" / [token: Address] ) -> Option<token::Amount> = base_fee,
 }
 
 // Handlers:
@@ -141,6 +208,17 @@ where
     unimplemented!("Dry running tx requires \"wasm-runtime\" feature.")
 }
 
+fn dry_run_tx_with_gas_profile<D, H, V, T>(
+    _ctx: RequestCtx<'_, D, H, V, T>,
+    _request: &RequestQuery,
+) -> namada_storage::Result<EncodedResponseQuery>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    unimplemented!("Dry running tx requires \"wasm-runtime\" feature.")
+}
+
 /// Return an estimate of the maximum time taken to decide a block
 fn max_block_time<D, H, V, T>(
     ctx: RequestCtx<'_, D, H, V, T>,
@@ -153,12 +231,26 @@ where
     const NUM_BLOCKS_TO_READ: u64 = 5;
 
     namada_parameters::estimate_max_block_time_from_blocks_and_params(
-        ctx.state,
-        ctx.state.in_mem().get_last_block_height(),
+        &ctx.state,
+        ctx.state.height(),
         NUM_BLOCKS_TO_READ,
     )
 }
 
+/// Get the current base fee of a token, i.e. the minimum price per unit of gas
+/// that a transaction must pay in that token. Returns `None` if the token is
+/// not allowed for fee payment.
+fn base_fee<D, H, V, T>(
+    ctx: RequestCtx<'_, D, H, V, T>,
+    token: Address,
+) -> namada_storage::Result<Option<token::Amount>>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    namada_parameters::read_base_fee(&ctx.state, &token)
+}
+
 /// Get the block header associated with the requested height
 fn block_header<D, H, V, T>(
     ctx: RequestCtx<'_, D, H, V, T>,
@@ -168,7 +260,7 @@ where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
 {
-    StorageRead::get_block_header(ctx.state, height)
+    StorageRead::get_block_header(&ctx.state, height)
 }
 
 /// Query to read block results from storage
@@ -180,10 +272,9 @@ where
     H: 'static + StorageHasher + Sync,
 {
     let (iter, _gas) = ctx.state.db_iter_results();
-    let mut results = vec![
-        BlockResults::default();
-        ctx.state.in_mem().block.height.0 as usize + 1
-    ];
+    let queried_height = ctx.state.height();
+    let mut results =
+        vec![BlockResults::default(); queried_height.0 as usize + 1];
     for (key, value, _gas) in iter {
         let key = u64::parse(key.clone()).map_err(|_| {
             namada_storage::Error::new(std::io::Error::new(
@@ -191,6 +282,10 @@ where
                 format!("expected integer for block height {}", key),
             ))
         })?;
+        // Skip the results of the blocks after the queried height
+        if key > queried_height.0 {
+            continue;
+        }
         let value = BlockResults::try_from_slice(&value).map_err(|_| {
             namada_storage::Error::new(std::io::Error::new(
                 std::io::ErrorKind::InvalidData,
@@ -217,6 +312,7 @@ where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
 {
+    require_latest_state(&ctx)?;
     Ok(ctx
         .state
         .in_mem()
@@ -248,6 +344,7 @@ where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
 {
+    require_latest_state(&ctx)?;
     // Conversion values are constructed on request
     if let Some(asset) =
         ctx.state.in_mem().conversion_state.assets.get(&asset_type)
@@ -359,7 +456,7 @@ where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
 {
-    let data = ctx.state.in_mem().last_epoch;
+    let data = ctx.state.last_epoch();
     Ok(data)
 }
 
@@ -370,9 +467,9 @@ where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
 {
-    let epoch = ctx.state.in_mem().last_epoch;
+    let epoch = ctx.state.last_epoch();
     let masp_epoch_multiplier =
-        namada_parameters::read_masp_epoch_multiplier_parameter(ctx.state)?;
+        namada_parameters::read_masp_epoch_multiplier_parameter(&ctx.state)?;
     MaspEpoch::try_from_epoch(epoch, masp_epoch_multiplier)
         .map_err(namada_storage::Error::new_const)
 }
@@ -384,6 +481,7 @@ where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
 {
+    require_latest_state(&ctx)?;
     let data = ctx.state.in_mem().native_token.clone();
     Ok(data)
 }
@@ -406,6 +504,7 @@ where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
 {
+    require_latest_state(&ctx)?;
     Ok(ctx.state.in_mem().last_block.clone())
 }
 
@@ -416,6 +515,7 @@ where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
 {
+    require_latest_state(&ctx)?;
     ctx.state
         .in_mem()
         .block
@@ -442,31 +542,8 @@ where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
 {
-    let last_committed_height = ctx.state.in_mem().get_last_block_height();
-    let queried_height = {
-        let height: BlockHeight = request.height.into();
-        let is_last_height_query = height.0 == 0;
-
-        if hints::likely(is_last_height_query) {
-            last_committed_height
-        } else {
-            height
-        }
-    };
-
-    if let Some(past_height_limit) = ctx.storage_read_past_height_limit {
-        if checked!(queried_height + past_height_limit)? < last_committed_height
-        {
-            return Err(namada_storage::Error::new(std::io::Error::new(
-                std::io::ErrorKind::InvalidInput,
-                format!(
-                    "Cannot query more than {past_height_limit} blocks in the \
-                     past (configured via \
-                     `shell.storage_read_past_height_limit`)."
-                ),
-            )));
-        }
-    }
+    let ctx = with_request_height(ctx, request)?;
+    let queried_height = ctx.state.height();
 
     match ctx
         .state
@@ -519,9 +596,10 @@ where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
 {
-    require_latest_height(&ctx, request)?;
+    let ctx = with_request_height(ctx, request)?;
+    let queried_height = ctx.state.height();
 
-    let iter = namada_storage::iter_prefix_bytes(ctx.state, storage_key)?;
+    let iter = namada_storage::iter_prefix_bytes(&ctx.state, storage_key)?;
     let data: namada_storage::Result<Vec<PrefixValue>> = iter
         .map(|iter_result| {
             let (key, value) = iter_result?;
@@ -529,16 +607,6 @@ where
         })
         .collect();
     let data = data?;
-    let queried_height = {
-        let height: BlockHeight = request.height.into();
-        let is_last_height_query = height.0 == 0;
-
-        if hints::likely(is_last_height_query) {
-            ctx.state.in_mem().get_last_block_height()
-        } else {
-            height
-        }
-    };
     let proof = if request.prove {
         let mut ops = vec![];
         for PrefixValue { key, value } in &data {
@@ -571,10 +639,41 @@ where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
 {
-    let data = StorageRead::has_key(ctx.state, &storage_key)?;
+    let data = StorageRead::has_key(&ctx.state, &storage_key)?;
     Ok(data)
 }
 
+fn decoded_value<D, H, V, T>(
+    ctx: RequestCtx<'_, D, H, V, T>,
+    storage_key: storage::Key,
+) -> namada_storage::Result<Option<DecodedValue>>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    let Some(bytes) = StorageRead::read_bytes(&ctx.state, &storage_key)? else {
+        return Ok(None);
+    };
+    #[cfg(feature = "migrations")]
+    {
+        crate::migrations::decode_storage_value(&storage_key, &bytes)
+            .map(Some)
+            .ok_or_else(|| {
+                namada_storage::Error::new_alloc(format!(
+                    "No storage schema is registered for the key {storage_key}"
+                ))
+            })
+    }
+    #[cfg(not(feature = "migrations"))]
+    {
+        let _ = bytes;
+        Err(namada_storage::Error::new_const(
+            "The node was built without the \"migrations\" feature that \
+             provides the storage schemas",
+        ))
+    }
+}
+
 fn applied<D, H, V, T>(
     ctx: RequestCtx<'_, D, H, V, T>,
     tx_hash: Hash,
@@ -583,14 +682,14 @@ where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
 {
+    require_latest_state(&ctx)?;
     // Match all the events carrying the provided tx hash
     let matcher_tx_events = dumb_queries::QueryMatcher::tx_events(tx_hash);
     let mut tx_events: Vec<Event> = ctx
         .event_log
         .with_matcher(matcher_tx_events)
-        .iter()
-        .cloned()
-        .collect();
+        .collect_with_persisted(ctx.state.db())
+        .into_storage_result()?;
 
     Ok(tx_events
         .iter()
@@ -606,6 +705,75 @@ where
         }))
 }
 
+fn search_events<D, H, V, T>(
+    ctx: RequestCtx<'_, D, H, V, T>,
+    request: &RequestQuery,
+    page: u64,
+    per_page: u64,
+) -> namada_storage::Result<EncodedResponseQuery>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    require_latest_height(&ctx, request)?;
+
+    if page == 0 {
+        return Err(namada_storage::Error::new_const(
+            "Event query pages are numbered from 1",
+        ));
+    }
+    let per_page = per_page.clamp(1, MAX_EVENTS_PER_PAGE);
+    let query: Query = std::str::from_utf8(&request.data)
+        .into_storage_result()?
+        .parse()
+        .into_storage_result()?;
+    // Only the attributes index is looked up, scanning all the persisted
+    // events is not allowed
+    let Some((key, value)) = query.indexed_attribute() else {
+        return Err(namada_storage::Error::new_const(
+            "Event queries must have at least one condition of the form \
+             `attribute = 'value'`",
+        ));
+    };
+
+    let skip = usize::try_from(checked!((page - 1) * per_page)?)
+        .into_storage_result()?;
+    let take = usize::try_from(per_page).into_storage_result()?;
+    let max_scanned =
+        usize::try_from(MAX_EVENTS_SCANNED).into_storage_result()?;
+    let mut events = Vec::with_capacity(take);
+    let mut num_matching = 0_usize;
+    let mut candidates =
+        event_store::iter_events_with_attribute(ctx.state.db(), key, value);
+    for result in candidates.by_ref().take(max_scanned) {
+        let (height, event) = result.into_storage_result()?;
+        if !query.matches(&event) {
+            continue;
+        }
+        if num_matching >= skip && events.len() < take {
+            events.push((height, event));
+        }
+        num_matching = checked!(num_matching + 1)?;
+    }
+    // The total count is only known if all the candidates were scanned
+    let total_count = if candidates.next().is_none() {
+        Some(u64::try_from(num_matching).into_storage_result()?)
+    } else {
+        None
+    };
+
+    let data = EventsPage {
+        events,
+        total_count,
+    }
+    .serialize_to_vec();
+    Ok(EncodedResponseQuery {
+        data,
+        height: ctx.state.in_mem().get_last_block_height(),
+        ..Default::default()
+    })
+}
+
 fn ibc_client_update<D, H, V, T>(
     ctx: RequestCtx<'_, D, H, V, T>,
     client_id: ClientId,
@@ -615,11 +783,18 @@ where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
 {
+    require_latest_state(&ctx)?;
     let matcher = dumb_queries::QueryMatcher::ibc_update_client(
         client_id,
         consensus_height,
     );
-    Ok(ctx.event_log.with_matcher(matcher).iter().next().cloned())
+    Ok(ctx
+        .event_log
+        .with_matcher(matcher)
+        .collect_with_persisted(ctx.state.db())
+        .into_storage_result()?
+        .into_iter()
+        .next())
 }
 
 fn ibc_packet<D, H, V, T>(
@@ -635,6 +810,7 @@ where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
 {
+    require_latest_state(&ctx)?;
     let matcher = dumb_queries::QueryMatcher::ibc_packet(
         event_type,
         source_port,
@@ -643,7 +819,13 @@ where
         destination_channel,
         sequence,
     );
-    Ok(ctx.event_log.with_matcher(matcher).iter().next().cloned())
+    Ok(ctx
+        .event_log
+        .with_matcher(matcher)
+        .collect_with_persisted(ctx.state.db())
+        .into_storage_result()?
+        .into_iter()
+        .next())
 }
 
 fn account<D, H, V, T>(
@@ -654,11 +836,11 @@ where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
 {
-    let account_exists = namada_account::exists(ctx.state, &owner)?;
+    let account_exists = namada_account::exists(&ctx.state, &owner)?;
 
     if account_exists {
-        let public_keys = namada_account::public_keys(ctx.state, &owner)?;
-        let threshold = namada_account::threshold(ctx.state, &owner)?;
+        let public_keys = namada_account::public_keys(&ctx.state, &owner)?;
+        let threshold = namada_account::threshold(&ctx.state, &owner)?;
 
         Ok(Some(Account {
             public_keys_map: AccountPublicKeysMap::from_iter(public_keys),
@@ -678,7 +860,7 @@ where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
 {
-    let public_keys = namada_account::public_keys(ctx.state, &owner)?;
+    let public_keys = namada_account::public_keys(&ctx.state, &owner)?;
 
     Ok(!public_keys.is_empty())
 }
@@ -686,9 +868,20 @@ where
 #[cfg(test)]
 mod test {
     use namada_core::address;
+    use namada_core::chain::BlockHeight;
+    use namada_core::hash::Hash;
+    use namada_core::storage::BlockResults;
+    use namada_state::mockdb::MockDB;
+    use namada_state::{DB, StateRead, event_store};
     use namada_token::storage_key::balance_key;
+    use namada_tx::event::types::APPLIED;
 
-    use crate::queries::RPC;
+    use crate::events::extend::{ComposeEvent, Height, TxHash};
+    use crate::events::log::query::Query;
+    use crate::events::{Event, EventLevel};
+    use crate::queries::testing::TestClient;
+    use crate::queries::{MAX_EVENTS_SCANNED, RPC};
+    use crate::rpc;
 
     #[test]
     fn test_shell_queries_router_paths() {
@@ -704,10 +897,109 @@ mod test {
         let path = RPC.shell().dry_run_tx_path();
         assert_eq!("/shell/dry_run_tx", path);
 
+        let path = RPC.shell().dry_run_tx_with_gas_profile_path();
+        assert_eq!("/shell/dry_run_tx_with_gas_profile", path);
+
         let path = RPC.shell().storage_prefix_path(&key);
         assert_eq!(format!("/shell/prefix/{}", key), path);
 
         let path = RPC.shell().storage_has_key_path(&key);
         assert_eq!(format!("/shell/has_key/{}", key), path);
+
+        let path = RPC.shell().decoded_value_path(&key);
+        assert_eq!(format!("/shell/decoded_value/{}", key), path);
+
+        let path = RPC.shell().base_fee_path(&token_addr);
+        assert_eq!(format!("/shell/base_fee/{}", token_addr), path);
+    }
+
+    /// Test that the queries of the in-memory state of the last committed
+    /// block don't accept a past height, while the block results are read
+    /// up to the requested height
+    #[tokio::test]
+    async fn test_queries_heights() {
+        let mut client = TestClient::new(RPC);
+        for height in 1..=2 {
+            client.state.in_mem_mut().block.height = BlockHeight(height);
+            let mut results = BlockResults::default();
+            results.reject(height.try_into().unwrap());
+            client.state.in_mem_mut().block.results = results;
+            client.state.commit_block().unwrap();
+        }
+        let past = RPC.shell().at_height(Some(BlockHeight(1)));
+        let latest = RPC.shell().at_height(Some(BlockHeight(2)));
+
+        assert!(past.last_block(&client).await.is_err());
+        assert!(past.native_token(&client).await.is_err());
+        let last_block = latest.last_block(&client).await.unwrap().unwrap();
+        assert_eq!(last_block.height, BlockHeight(2));
+        assert!(RPC.shell().native_token(&client).await.is_ok());
+
+        let mut results = BlockResults::default();
+        results.reject(1);
+        assert_eq!(
+            past.read_results(&client).await.unwrap(),
+            vec![BlockResults::default(), results.clone()]
+        );
+        let mut last_results = BlockResults::default();
+        last_results.reject(2);
+        assert_eq!(
+            RPC.shell().read_results(&client).await.unwrap(),
+            vec![BlockResults::default(), results, last_results]
+        );
+    }
+
+    /// Persist an applied event of the tx with the given hash at each of the
+    /// given heights.
+    fn persist_applied_events(
+        client: &TestClient<RPC>,
+        hash: &Hash,
+        heights: impl IntoIterator<Item = u64>,
+    ) {
+        let db = client.state.db();
+        let mut batch = MockDB::batch();
+        for height in heights {
+            let height = BlockHeight(height);
+            let event: Event = Event::new(APPLIED, EventLevel::Tx)
+                .with(TxHash(*hash))
+                .with(Height(height))
+                .into();
+            event_store::add_events_to_batch(db, &mut batch, height, &[event])
+                .unwrap();
+        }
+        db.exec_batch(batch).unwrap();
+    }
+
+    /// Test that the events query only looks up the attributes index and
+    /// that its scan is bounded.
+    #[tokio::test]
+    async fn test_search_events() {
+        let client = TestClient::new(RPC);
+        let hash = Hash::sha256(b"tx");
+        persist_applied_events(&client, &hash, 1..=5);
+
+        let query: Query =
+            format!("hash = '{hash}' AND height > 2").parse().unwrap();
+        let page = rpc::query_events(&client, &query, 1, 2).await.unwrap();
+        let heights: Vec<_> =
+            page.events.iter().map(|(height, _)| height.0).collect();
+        assert_eq!(heights, vec![3, 4]);
+        assert_eq!(page.total_count, Some(3));
+
+        // A query without an indexed attribute is rejected
+        let query: Query = "height > 2".parse().unwrap();
+        assert!(rpc::query_events(&client, &query, 1, 2).await.is_err());
+
+        // The total count is unknown when the scan is capped
+        let other_hash = Hash::sha256(b"other tx");
+        persist_applied_events(
+            &client,
+            &other_hash,
+            1..=MAX_EVENTS_SCANNED.checked_add(1).unwrap(),
+        );
+        let query: Query = format!("hash = '{other_hash}'").parse().unwrap();
+        let page = rpc::query_events(&client, &query, 1, 2).await.unwrap();
+        assert_eq!(page.events.len(), 2);
+        assert_eq!(page.total_count, None);
     }
 }
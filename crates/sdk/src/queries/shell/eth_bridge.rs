@@ -44,7 +44,9 @@ use serde::{Deserialize, Serialize};
 use crate::borsh::BorshSerializeExt;
 use crate::eth_bridge::ethers::abi::AbiDecode;
 use crate::governance;
-use crate::queries::{EncodedResponseQuery, RequestCtx, RequestQuery};
+use crate::queries::{
+    EncodedResponseQuery, RequestCtx, RequestQuery, require_latest_state,
+};
 
 /// Container for the status of queried transfers to Ethereum.
 #[derive(
@@ -350,6 +352,7 @@ where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
 {
+    require_latest_state(&ctx)?;
     let ethbridge_queries = ctx.state.ethbridge_queries();
 
     let whitelisted = ethbridge_queries.is_token_whitelisted(&asset);
@@ -375,7 +378,7 @@ where
     H: 'static + StorageHasher + Sync,
     T: BorshDeserialize,
 {
-    let Some(contract) = StorageRead::read(ctx.state, key)? else {
+    let Some(contract) = StorageRead::read(&ctx.state, key)? else {
         return Err(namada_storage::Error::SimpleMessage(
             "Failed to read contract: The Ethereum bridge storage is not \
              initialized",
@@ -419,6 +422,7 @@ where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
 {
+    require_latest_state(&ctx)?;
     Ok(read_ethereum_bridge_pool_at_height(
         ctx.state.in_mem().get_last_block_height(),
         ctx,
@@ -434,6 +438,7 @@ where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
 {
+    require_latest_state(&ctx)?;
     // get the latest signed merkle root of the Ethereum bridge pool
     let (_, height) = ctx
         .state
@@ -624,6 +629,7 @@ where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
 {
+    require_latest_state(&ctx)?;
     let mut pending_events = HashMap::new();
     for (mut key, value) in ctx
         .state
@@ -669,7 +675,7 @@ where
                     "Iterating over storage should not yield keys without \
                      values.",
                 )
-                .fractional_stake::<_, _, governance::Store<_>>(ctx.state);
+                .fractional_stake::<_, _, governance::Store<_>>(&ctx.state);
             for transfer in transfers {
                 let key = get_key_from_hash(&transfer.keccak256());
                 let transfer = ctx
@@ -696,6 +702,7 @@ where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
 {
+    require_latest_state(&ctx)?;
     if epoch.0 == 0 {
         return Err(namada_storage::Error::Custom(CustomError(
             "Validator set update proofs should only be requested from epoch \
@@ -726,7 +733,7 @@ where
 
     let valset_upd_keys = vote_tallies::Keys::from(&epoch);
     let proof: EthereumProof<VotingPowersMap> =
-        StorageRead::read(ctx.state, &valset_upd_keys.body())?.expect(
+        StorageRead::read(&ctx.state, &valset_upd_keys.body())?.expect(
             "EthereumProof is seen in storage, therefore it must exist",
         );
 
@@ -746,6 +753,7 @@ where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
 {
+    require_latest_state(&ctx)?;
     let current_epoch = ctx.state.in_mem().last_epoch;
     if epoch > current_epoch.next() {
         Err(namada_storage::Error::Custom(CustomError(
@@ -776,6 +784,7 @@ where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
 {
+    require_latest_state(&ctx)?;
     let current_epoch = ctx.state.in_mem().last_epoch;
     if epoch > current_epoch.next() {
         Err(namada_storage::Error::Custom(CustomError(
@@ -823,6 +832,7 @@ where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
 {
+    require_latest_state(&ctx)?;
     let current_epoch = ctx.state.in_mem().get_current_epoch().0;
     if epoch > checked!(current_epoch + 1u64)? {
         return Err(namada_storage::Error::SimpleMessage(
@@ -1092,6 +1102,39 @@ mod test_ethbridge_router {
         assert_eq!(pool, Vec::from([transfer]));
     }
 
+    /// Test that the queries of the Ethereum bridge state of the last
+    /// committed block don't accept a past height, unlike the queries of the
+    /// stored contracts
+    #[tokio::test]
+    async fn test_eth_bridge_queries_latest_height() {
+        const ERC20_TOKEN: EthAddress = EthAddress([0; 20]);
+
+        let mut client = TestClient::new(RPC);
+        test_utils::init_default_storage(&mut client.state);
+        for height in 1..=2 {
+            client.state.in_mem_mut().block.height = height.into();
+            client.state.commit_block().expect("Test failed");
+        }
+        let latest = RPC.shell().eth_bridge();
+        let past = RPC.shell().eth_bridge().at_height(Some(1.into()));
+
+        assert!(
+            latest
+                .get_erc20_flow_control(&client, &ERC20_TOKEN)
+                .await
+                .is_ok()
+        );
+        assert!(
+            past.get_erc20_flow_control(&client, &ERC20_TOKEN)
+                .await
+                .is_err()
+        );
+        assert!(past.read_ethereum_bridge_pool(&client).await.is_err());
+        assert!(past.read_bridge_valset(&client, &0.into()).await.is_err());
+
+        assert!(past.read_native_erc20_contract(&client).await.is_ok());
+    }
+
     /// Test that reading the bridge pool always gets
     /// the latest pool
     #[tokio::test]
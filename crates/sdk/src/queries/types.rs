@@ -1,7 +1,7 @@
 use std::fmt::Debug;
 
 pub use namada_io::client::{EncodedResponseQuery, Error, ResponseQuery};
-use namada_state::{DB, DBIter, StorageHasher, WlState};
+use namada_state::{DB, DBIter, HistoricState, StorageHasher};
 
 use crate::events::log::EventLog;
 pub use crate::tendermint::abci::request::Query as RequestQuery;
@@ -13,8 +13,8 @@ where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
 {
-    /// Reference to the ledger's [`WlState`].
-    pub state: &'shell WlState<D, H>,
+    /// Read-only view of the ledger's state, pinned to the queried height.
+    pub state: HistoricState<'shell, D, H>,
     /// Log of events emitted by `FinalizeBlock` ABCI calls.
     pub event_log: &'shell EventLog,
     /// Cache of VP wasm compiled artifacts.
@@ -194,7 +194,7 @@ where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
 {
-    read_pos_params::<_, governance::Store<_>>(ctx.state)
+    read_pos_params::<_, governance::Store<_>>(&ctx.state)
 }
 
 /// Find if the given address belongs to a validator account.
@@ -206,7 +206,7 @@ where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
 {
-    namada_proof_of_stake::is_validator(ctx.state, &addr)
+    namada_proof_of_stake::is_validator(&ctx.state, &addr)
 }
 
 /// Find a consensus key of a validator account.
@@ -218,9 +218,9 @@ where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
 {
-    let current_epoch = ctx.state.in_mem().last_epoch;
+    let current_epoch = ctx.state.last_epoch();
     namada_proof_of_stake::storage::get_consensus_key::<_, governance::Store<_>>(
-        ctx.state,
+        &ctx.state,
         &addr,
         current_epoch,
     )
@@ -236,7 +236,7 @@ where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
 {
-    namada_proof_of_stake::is_delegator(ctx.state, &addr, epoch)
+    namada_proof_of_stake::is_delegator(&ctx.state, &addr, epoch)
 }
 
 /// Get all the validator known addresses. These validators may be in any state,
@@ -249,8 +249,8 @@ where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
 {
-    let epoch = epoch.unwrap_or(ctx.state.in_mem().last_epoch);
-    read_all_validator_addresses(ctx.state, epoch)
+    let epoch = epoch.unwrap_or(ctx.state.last_epoch());
+    read_all_validator_addresses(&ctx.state, epoch)
 }
 
 /// Get liveness information for all consensus validators in the current epoch.
@@ -261,20 +261,20 @@ where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
 {
-    let epoch = ctx.state.in_mem().last_epoch;
+    let epoch = ctx.state.last_epoch();
     let consensus_validators =
-        read_consensus_validator_set_addresses(ctx.state, epoch)?;
-    let params = read_pos_params::<_, governance::Store<_>>(ctx.state)?;
+        read_consensus_validator_set_addresses(&ctx.state, epoch)?;
+    let params = read_pos_params::<_, governance::Store<_>>(&ctx.state)?;
 
     let mut result = Vec::with_capacity(consensus_validators.len());
     for validator in consensus_validators {
         if let Some(pubkey) = get_consensus_key::<_, governance::Store<_>>(
-            ctx.state, &validator, epoch,
+            &ctx.state, &validator, epoch,
         )? {
             let comet_address = tm_consensus_key_raw_hash(&pubkey);
             let sum_liveness_handle = liveness_sum_missed_votes_handle();
             let missed_votes = sum_liveness_handle
-                .get(ctx.state, &validator)?
+                .get(&ctx.state, &validator)?
                 .unwrap_or_default();
             result.push(ValidatorLiveness {
                 native_address: validator,
@@ -301,12 +301,12 @@ where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
 {
-    let epoch = epoch.unwrap_or(ctx.state.in_mem().last_epoch);
-    let params = read_pos_params::<_, governance::Store<_>>(ctx.state)?;
+    let epoch = epoch.unwrap_or(ctx.state.last_epoch());
+    let params = read_pos_params::<_, governance::Store<_>>(&ctx.state)?;
     let commission_rate = validator_commission_rate_handle(&validator)
-        .get(ctx.state, epoch, &params)?;
+        .get(&ctx.state, epoch, &params)?;
     let max_commission_change_per_epoch =
-        read_validator_max_commission_rate_change(ctx.state, &validator)?;
+        read_validator_max_commission_rate_change(&ctx.state, &validator)?;
 
     Ok(CommissionPair {
         commission_rate,
@@ -324,7 +324,7 @@ where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
 {
-    read_validator_metadata(ctx.state, &validator)
+    read_validator_metadata(&ctx.state, &validator)
 }
 
 /// Get the validator state
@@ -337,11 +337,11 @@ where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
 {
-    let epoch = epoch.unwrap_or(ctx.state.in_mem().last_epoch);
+    let epoch = epoch.unwrap_or(ctx.state.last_epoch());
     let state = namada_proof_of_stake::storage::read_validator_state::<
         _,
         governance::Store<_>,
-    >(ctx.state, &validator, epoch)?;
+    >(&ctx.state, &validator, epoch)?;
     Ok((state, epoch))
 }
 
@@ -354,7 +354,7 @@ where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
 {
-    read_validator_last_slash_epoch(ctx.state, &validator)
+    read_validator_last_slash_epoch(&ctx.state, &validator)
 }
 
 /// Get the total stake of a validator at the given epoch or current when
@@ -371,11 +371,11 @@ where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
 {
-    let epoch = epoch.unwrap_or(ctx.state.in_mem().last_epoch);
-    let params = read_pos_params::<_, governance::Store<_>>(ctx.state)?;
-    if namada_proof_of_stake::is_validator(ctx.state, &validator)? {
+    let epoch = epoch.unwrap_or(ctx.state.last_epoch());
+    let params = read_pos_params::<_, governance::Store<_>>(&ctx.state)?;
+    if namada_proof_of_stake::is_validator(&ctx.state, &validator)? {
         let stake =
-            read_validator_stake(ctx.state, &params, &validator, epoch)?;
+            read_validator_stake(&ctx.state, &params, &validator, epoch)?;
         Ok(Some(stake))
     } else {
         Ok(None)
@@ -394,7 +394,7 @@ where
     H: 'static + StorageHasher + Sync,
 {
     let handle = validator_incoming_redelegations_handle(&src_validator);
-    handle.get(ctx.state, &delegator)
+    handle.get(&ctx.state, &delegator)
 }
 
 /// Get all the validator in the consensus set with their bonded stake.
@@ -406,8 +406,8 @@ where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
 {
-    let epoch = epoch.unwrap_or(ctx.state.in_mem().last_epoch);
-    read_consensus_validator_set_addresses_with_stake(ctx.state, epoch)
+    let epoch = epoch.unwrap_or(ctx.state.last_epoch());
+    read_consensus_validator_set_addresses_with_stake(&ctx.state, epoch)
 }
 
 /// Get all the validator in the below-capacity set with their bonded stake.
@@ -419,8 +419,8 @@ where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
 {
-    let epoch = epoch.unwrap_or(ctx.state.in_mem().last_epoch);
-    read_below_capacity_validator_set_addresses_with_stake(ctx.state, epoch)
+    let epoch = epoch.unwrap_or(ctx.state.last_epoch());
+    read_below_capacity_validator_set_addresses_with_stake(&ctx.state, epoch)
 }
 
 /// Get the total stake in PoS system at the given epoch or current when `None`.
@@ -432,9 +432,9 @@ where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
 {
-    let epoch = epoch.unwrap_or(ctx.state.in_mem().last_epoch);
-    let params = read_pos_params::<_, governance::Store<_>>(ctx.state)?;
-    read_total_stake(ctx.state, &params, epoch)
+    let epoch = epoch.unwrap_or(ctx.state.last_epoch());
+    let params = read_pos_params::<_, governance::Store<_>>(&ctx.state)?;
+    read_total_stake(&ctx.state, &params, epoch)
 }
 
 /// Get the total active voting power in PoS system at the given epoch or
@@ -447,9 +447,9 @@ where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
 {
-    let epoch = epoch.unwrap_or(ctx.state.in_mem().last_epoch);
-    let params = read_pos_params::<_, governance::Store<_>>(ctx.state)?;
-    read_total_active_stake(ctx.state, &params, epoch)
+    let epoch = epoch.unwrap_or(ctx.state.last_epoch());
+    let params = read_pos_params::<_, governance::Store<_>>(&ctx.state)?;
+    read_total_active_stake(&ctx.state, &params, epoch)
 }
 
 fn bond_deltas<D, H, V, T>(
@@ -461,7 +461,7 @@ where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
 {
-    bond_handle(&source, &validator).to_hashmap(ctx.state)
+    bond_handle(&source, &validator).to_hashmap(&ctx.state)
 }
 
 /// Find the sum of bond amount up the given epoch when `Some`, or up to the
@@ -476,17 +476,13 @@ where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
 {
-    let params = read_pos_params::<_, governance::Store<_>>(ctx.state)?;
-    let epoch = epoch.unwrap_or(
-        ctx.state
-            .in_mem()
-            .last_epoch
-            .unchecked_add(params.pipeline_len),
-    );
+    let params = read_pos_params::<_, governance::Store<_>>(&ctx.state)?;
+    let epoch = epoch
+        .unwrap_or(ctx.state.last_epoch().unchecked_add(params.pipeline_len));
 
     let handle = bond_handle(&source, &validator);
     handle
-        .get_sum(ctx.state, epoch, &params)?
+        .get_sum(&ctx.state, epoch, &params)?
         .ok_or_err_msg("Cannot find bond")
 }
 
@@ -500,10 +496,10 @@ where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
 {
-    let epoch = epoch.unwrap_or(ctx.state.in_mem().last_epoch);
+    let epoch = epoch.unwrap_or(ctx.state.last_epoch());
     let bond_id = BondId { source, validator };
 
-    bond_amount::<_, governance::Store<_>>(ctx.state, &bond_id, epoch)
+    bond_amount::<_, governance::Store<_>>(&ctx.state, &bond_id, epoch)
 }
 
 fn unbond<D, H, V, T>(
@@ -516,7 +512,7 @@ where
     H: 'static + StorageHasher + Sync,
 {
     let handle = unbond_handle(&source, &validator);
-    let iter = handle.iter(ctx.state)?;
+    let iter = handle.iter(&ctx.state)?;
     iter.map(|next_result| {
         next_result.map(
             |(
@@ -542,7 +538,7 @@ where
 {
     // TODO slashes
     let handle = unbond_handle(&source, &validator);
-    let iter = handle.iter(ctx.state)?;
+    let iter = handle.iter(&ctx.state)?;
     iter.map(|next_result| {
         next_result.map(
             |(
@@ -567,11 +563,11 @@ where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
 {
-    let epoch = epoch.unwrap_or(ctx.state.in_mem().last_epoch);
+    let epoch = epoch.unwrap_or(ctx.state.last_epoch());
 
     let handle = unbond_handle(&source, &validator);
     let mut total = token::Amount::zero();
-    for result in handle.iter(ctx.state)? {
+    for result in handle.iter(&ctx.state)? {
         let (
             lazy_map::NestedSubKey::Data {
                 key: _start,
@@ -597,10 +593,10 @@ where
     H: 'static + StorageHasher + Sync,
 {
     let reward_tokens = query_reward_tokens::<_, governance::Store<_>>(
-        ctx.state,
+        &ctx.state,
         source.as_ref(),
         &validator,
-        epoch.unwrap_or(ctx.state.in_mem().last_epoch),
+        epoch.unwrap_or(ctx.state.last_epoch()),
     )?;
 
     match epoch {
@@ -612,7 +608,7 @@ where
             // the height of the epoch we are querying.
             let source = source.unwrap_or_else(|| validator.clone());
             let rewards_counter_last_epoch =
-                read_rewards_counter(ctx.state, &source, &validator)?;
+                read_rewards_counter(&ctx.state, &source, &validator)?;
 
             let rewards_counter_at_epoch =
                 get_rewards_counter_at_epoch(ctx, &source, &validator, epoch)?;
@@ -651,7 +647,7 @@ where
     // by first checking to see if it currently exists in memory or has ever
     // been claimed before querying by height.
     if !ctx.state.has_key(&storage_key)?
-        && get_last_reward_claim_epoch(ctx.state, source, validator)?.is_none()
+        && get_last_reward_claim_epoch(&ctx.state, source, validator)?.is_none()
     {
         return Ok(token::Amount::zero());
     }
@@ -681,7 +677,7 @@ where
     H: 'static + StorageHasher + Sync,
 {
     namada_proof_of_stake::queries::bonds_and_unbonds::<_, governance::Store<_>>(
-        ctx.state, source, validator,
+        &ctx.state, source, validator,
     )
 }
 
@@ -696,8 +692,8 @@ where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
 {
-    let epoch = epoch.unwrap_or(ctx.state.in_mem().last_epoch);
-    find_delegation_validators(ctx.state, &owner, &epoch)
+    let epoch = epoch.unwrap_or(ctx.state.last_epoch());
+    find_delegation_validators(&ctx.state, &owner, &epoch)
 }
 
 /// Find all the validator addresses to whom the given `owner` address has
@@ -711,8 +707,8 @@ where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
 {
-    let epoch: Epoch = epoch.unwrap_or(ctx.state.in_mem().last_epoch);
-    find_delegations::<_, governance::Store<_>>(ctx.state, &owner, &epoch)
+    let epoch: Epoch = epoch.unwrap_or(ctx.state.last_epoch());
+    find_delegations::<_, governance::Store<_>>(&ctx.state, &owner, &epoch)
 }
 
 /// Validator slashes
@@ -725,7 +721,7 @@ where
     H: 'static + StorageHasher + Sync,
 {
     let slash_handle = validator_slashes_handle(&validator);
-    slash_handle.iter(ctx.state)?.collect()
+    slash_handle.iter(&ctx.state)?.collect()
 }
 
 /// All slashes
@@ -736,7 +732,7 @@ where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
 {
-    find_all_slashes(ctx.state)
+    find_all_slashes(&ctx.state)
 }
 
 /// Enqueued slashes
@@ -747,8 +743,8 @@ where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
 {
-    let current_epoch = ctx.state.in_mem().last_epoch;
-    find_all_enqueued_slashes(ctx.state, current_epoch)
+    let current_epoch = ctx.state.last_epoch();
+    find_all_enqueued_slashes(&ctx.state, current_epoch)
 }
 
 /// Native validator address by looking up the Tendermint address
@@ -768,7 +764,7 @@ where
         ));
     }
     namada_proof_of_stake::storage::find_validator_by_raw_hash(
-        ctx.state, tm_addr,
+        &ctx.state, tm_addr,
     )
 }
 
@@ -780,7 +776,7 @@ where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
 {
-    namada_proof_of_stake::storage::get_consensus_key_set(ctx.state)
+    namada_proof_of_stake::storage::get_consensus_key_set(&ctx.state)
 }
 
 /// Find if the given source address has any bonds.
@@ -793,7 +789,7 @@ where
     H: 'static + StorageHasher + Sync,
 {
     namada_proof_of_stake::queries::has_bonds::<_, governance::Store<_>>(
-        ctx.state, &source,
+        &ctx.state, &source,
     )
 }
 
@@ -902,7 +898,7 @@ fn enrich_bonds_and_unbonds(
 mod test {
     use namada_core::chain::Epoch;
     use namada_core::{address, token};
-    use namada_state::StorageWrite;
+    use namada_state::{HistoricState, StorageWrite};
 
     use super::*;
     use crate::queries::testing::TestClient;
@@ -926,7 +922,7 @@ mod test {
         };
         let ctx = RequestCtx {
             event_log: &client.event_log,
-            state: &client.state,
+            state: HistoricState::new(&client.state),
             vp_wasm_cache: (),
             tx_wasm_cache: (),
             storage_read_past_height_limit: None,
@@ -29,7 +29,7 @@ where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
 {
-    read_denom(ctx.state, &token)
+    read_denom(&ctx.state, &token)
 }
 
 /// Get the total supply for a token address
@@ -41,7 +41,7 @@ where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
 {
-    read_total_supply(ctx.state, &token)
+    read_total_supply(&ctx.state, &token)
 }
 
 /// Get the effective total supply of the native token
@@ -52,7 +52,7 @@ where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
 {
-    get_effective_total_native_supply(ctx.state)
+    get_effective_total_native_supply(&ctx.state)
 }
 
 /// Get the effective total supply of the native token
@@ -67,7 +67,7 @@ where
         _,
         crate::token::Store<_>,
         crate::parameters::Store<_>,
-    >(ctx.state)
+    >(&ctx.state)
 }
 
 pub mod client_only_methods {
@@ -11,7 +11,7 @@ use borsh::{BorshDeserialize, BorshSerialize};
 use masp_primitives::asset_type::AssetType;
 use masp_primitives::merkle_tree::MerklePath;
 use masp_primitives::sapling::Node;
-use namada_account::Account;
+use namada_account::{Account, FeeAllowance};
 use namada_core::address::{Address, InternalAddress};
 use namada_core::arith::checked;
 use namada_core::chain::{BlockHeight, Epoch};
@@ -31,8 +31,9 @@ use namada_core::token::{
 use namada_core::uint::Uint;
 use namada_core::{storage, token};
 use namada_events::extend::InnerTxHash;
-use namada_gas::WholeGas;
 use namada_gas::event::GasUsed as GasUsedAttr;
+use namada_gas::profile::GasProfile;
+use namada_gas::{Gas, WholeGas};
 use namada_governance::parameters::GovernanceParameters;
 use namada_governance::pgf::parameters::PgfParameters;
 use namada_governance::pgf::storage::steward::StewardDetail;
@@ -49,7 +50,9 @@ use namada_ibc::storage::{
 };
 use namada_ibc::trace::calc_ibc_denom;
 use namada_io::{Client, Io, display_line, edisplay_line};
-use namada_parameters::{EpochDuration, storage as params_storage};
+use namada_parameters::{
+    EpochDuration, FeeMarketParams, storage as params_storage,
+};
 use namada_proof_of_stake::parameters::PosParams;
 use namada_proof_of_stake::rewards::PosRewardsRates;
 use namada_proof_of_stake::types::{
@@ -58,19 +61,22 @@ use namada_proof_of_stake::types::{
 };
 use namada_state::{BlockHeader, LastBlock};
 use namada_token::masp::MaspTokenRewardData;
-use namada_tx::data::{BatchedTxResult, DryRunResult, ResultCode, TxResult};
+use namada_tx::data::{
+    BatchedTxResult, DryRunResult, ProfiledDryRunResult, ResultCode, TxResult,
+};
 use namada_tx::event::{Batch as BatchAttr, Code as CodeAttr};
 use serde::{Deserialize, Serialize};
 
 use crate::args::{InputAmount, OsmosisPoolHop, Slippage};
 use crate::control_flow::time;
 use crate::error::{EncodingError, Error, QueryError, TxSubmitError};
+use crate::events::log::query::Query;
 use crate::events::{Event, extend};
 use crate::internal_macros::echo_error;
-use crate::queries::RPC;
 use crate::queries::vp::pos::{
     EnrichedBondsAndUnbondsDetails, ValidatorStateInfo,
 };
+use crate::queries::{DecodedValue, RPC};
 use crate::tendermint::block::Height;
 use crate::tendermint::merkle::proof::ProofOps;
 use crate::tendermint_rpc::query::Query;
@@ -86,6 +92,33 @@ pub async fn query_max_block_time_estimate<C: Client + Sync>(
         .map_err(|err| Error::from(QueryError::NoResponse(err.to_string())))
 }
 
+/// Query the current base fee of a token, i.e. the minimum price per unit of
+/// gas that a transaction must pay in that token. Returns `None` if the token
+/// is not allowed for fee payment.
+pub async fn query_base_fee<C: namada_io::Client + Sync>(
+    client: &C,
+    token: &Address,
+) -> Result<Option<token::Amount>, Error> {
+    convert_response::<C, _>(RPC.shell().base_fee(client, token).await)
+}
+
+/// Query the parameters of the fee market. Returns `None` if the fee market is
+/// disabled.
+pub async fn query_fee_market_parameters<C: namada_io::Client + Sync>(
+    client: &C,
+) -> Result<Option<FeeMarketParams>, Error> {
+    let key = params_storage::get_fee_market_key();
+    let (value, _proof) =
+        query_storage_value_bytes(client, &key, None, false).await?;
+    value
+        .map(|bytes| {
+            FeeMarketParams::try_from_slice(&bytes).map_err(|err| {
+                Error::from(EncodingError::Decoding(err.to_string()))
+            })
+        })
+        .transpose()
+}
+
 /// Identical to [`query_tx_status`], but does not need a [`Namada`]
 /// context.
 pub async fn query_tx_status2<C, IO>(
@@ -215,7 +248,7 @@ fn unwrap_client_response<C: namada_io::Client, T>(
 /// A helper to turn client's response into an error type that can be used with
 /// ? The exact error type is a `QueryError::NoResponse`, and thus should be
 /// seen as getting no response back from a query.
-fn convert_response<C: namada_io::Client, T>(
+pub(crate) fn convert_response<C: namada_io::Client, T>(
     response: Result<T, C::Error>,
 ) -> Result<T, Error> {
     response.map_err(|err| Error::from(QueryError::NoResponse(err.to_string())))
@@ -483,6 +516,16 @@ where
         .map_err(|err| Error::from(EncodingError::Decoding(err.to_string())))
 }
 
+/// Query a storage value decoded with the storage schema registered for its
+/// key, without prior knowledge of its type. Returns `None` if there is no
+/// value under the key.
+pub async fn query_decoded_storage_value<C: namada_io::Client + Sync>(
+    client: &C,
+    key: &storage::Key,
+) -> Result<Option<DecodedValue>, error::Error> {
+    convert_response::<C, _>(RPC.shell().decoded_value(client, key).await)
+}
+
 /// Query a storage value and the proof without decoding.
 pub async fn query_storage_value_bytes<C: namada_io::Client + Sync>(
     client: &C,
@@ -595,19 +638,69 @@ pub async fn query_tx_events<C: namada_io::Client + Sync>(
     }
 }
 
+/// Search the events persisted by the ledger with a CometBFT event query.
+/// The query must have at least one `attribute = 'value'` condition, which is
+/// looked up in the index of the event attributes. Pages are numbered from 1,
+/// and hold up to `per_page` events (the node caps it at
+/// [`MAX_EVENTS_PER_PAGE`](crate::queries::MAX_EVENTS_PER_PAGE)).
+pub async fn query_events<C: namada_io::Client + Sync>(
+    client: &C,
+    query: &Query,
+    page: u64,
+    per_page: u64,
+) -> Result<EventsPage, Error> {
+    let data = Some(query.to_string().into_bytes());
+    convert_response::<C, _>(
+        RPC.shell()
+            .search_events(client, data, None, false, &page, &per_page)
+            .await,
+    )
+    .map(|response| response.data)
+}
+
+/// Dry run a transaction without displaying its result
+pub async fn query_dry_run_tx<C: namada_io::Client + Sync>(
+    client: &C,
+    tx_bytes: Vec<u8>,
+) -> Result<DryRunResult, Error> {
+    let (data, height, prove) = (Some(tx_bytes), None, false);
+    convert_response::<C, _>(
+        RPC.shell().dry_run_tx(client, data, height, prove).await,
+    )
+    .map(|response| response.data)
+}
+
 /// Dry run a transaction
 pub async fn dry_run_tx<N: Namada>(
     context: &N,
     tx_bytes: Vec<u8>,
 ) -> Result<DryRunResult, Error> {
+    let result = query_dry_run_tx(context.client(), tx_bytes).await?;
+    display_dry_run_result(context, &result);
+    Ok(result)
+}
+
+/// Dry run a transaction and display the breakdown of the gas it consumed
+pub async fn dry_run_tx_with_gas_profile<N: Namada>(
+    context: &N,
+    tx_bytes: Vec<u8>,
+) -> Result<ProfiledDryRunResult, Error> {
     let (data, height, prove) = (Some(tx_bytes), None, false);
     let result = convert_response::<N::Client, _>(
         RPC.shell()
-            .dry_run_tx(context.client(), data, height, prove)
+            .dry_run_tx_with_gas_profile(context.client(), data, height, prove)
             .await,
     )?
     .data;
-    let DryRunResult(tx_result, gas_cost) = &result;
+    let ProfiledDryRunResult(dry_run_result, gas_profile) = &result;
+    display_dry_run_result(context, dry_run_result);
+    display_gas_profile(context, gas_profile);
+    Ok(result)
+}
+
+/// Display the result of a dry run
+fn display_dry_run_result<N: Namada>(context: &N, result: &DryRunResult) {
+    let DryRunResult(tx_result, gas_cost) = result;
 
     display_line!(context.io(), "Dry-run result:");
     let mut all_inners_successful = true;
@@ -659,8 +752,51 @@ pub async fn dry_run_tx<N: Namada>(
              this value to set gas limit."
         }
     );
+}
 
-    Ok(result)
+/// Display the breakdown of the gas consumed by a dry run. The gas of each
+/// category is sorted in decreasing order.
+pub fn display_gas_profile<N: Namada>(context: &N, profile: &GasProfile) {
+    fn sorted<K: std::fmt::Display>(
+        gas: &BTreeMap<K, Gas>,
+    ) -> Vec<(String, u64)> {
+        let mut gas: Vec<_> = gas
+            .iter()
+            .map(|(key, gas)| (key.to_string(), u64::from(gas.clone())))
+            .collect();
+        gas.sort_by(|(_, a), (_, b)| b.cmp(a));
+        gas
+    }
+
+    display_line!(
+        context.io(),
+        "Gas profile (in gas sub-units, {} sub-units per gas unit):",
+        profile.gas_scale
+    );
+    for (category, gas) in [
+        ("Wrapper tx", &profile.wrapper),
+        ("Wasm compilation", &profile.wasm_compilation),
+        ("Wasm loading from storage", &profile.wasm_loading),
+        ("Wasm validation", &profile.wasm_validation),
+    ] {
+        display_line!(context.io(), "  {category}: {}", u64::from(gas.clone()));
+    }
+    for (category, gas) in [
+        ("Host functions", sorted(&profile.host_fns)),
+        ("VPs", sorted(&profile.vps)),
+        ("Wasm code", sorted(&profile.wasm)),
+    ] {
+        display_line!(context.io(), "  {category}:");
+        for (key, gas) in gas {
+            display_line!(context.io(), "    {key}: {gas}");
+        }
+    }
+    display_line!(
+        context.io(),
+        "  Storage bytes read: {}\n  Storage bytes written: {}",
+        profile.storage_bytes_read,
+        profile.storage_bytes_written
+    );
 }
 
 /// Data needed for broadcasting a tx and monitoring its progress on chain.
@@ -689,6 +825,20 @@ pub struct TxAppliedEvents {
     pub other: Vec<Event>,
 }
 
+/// A page of the events matching an event query
+#[derive(Debug, Clone, Default, BorshDeserialize, BorshSerialize)]
+pub struct EventsPage {
+    /// The matching events of the requested page, along with the height
+    /// they were emitted at, in order of emission
+    pub events: Vec<(BlockHeight, Event)>,
+    /// The total number of events matching the query, across all pages.
+    /// This is `None` if there are more than
+    /// [`MAX_EVENTS_SCANNED`](crate::queries::MAX_EVENTS_SCANNED) persisted
+    /// events with the indexed attribute of the query, in which case only
+    /// the matching events among the first of those can be paged through.
+    pub total_count: Option<u64>,
+}
+
 /// A parsed event from tendermint relating to a transaction
 #[derive(Debug, Serialize)]
 pub struct TxResponse {
@@ -1043,6 +1193,25 @@ pub async fn get_account_info<C: namada_io::Client + Sync>(
     )
 }
 
+/// Query the fee allowance granted by `granter` to `grantee`, if any
+pub async fn query_fee_allowance<C: namada_io::Client + Sync>(
+    client: &C,
+    granter: &Address,
+    grantee: &Address,
+) -> Result<Option<FeeAllowance>, error::Error> {
+    let key =
+        namada_account::fee_allowances_handle(granter).get_data_key(grantee);
+    let (value, _proof) =
+        query_storage_value_bytes(client, &key, None, false).await?;
+    value
+        .map(|bytes| {
+            FeeAllowance::try_from_slice(&bytes).map_err(|err| {
+                Error::from(EncodingError::Decoding(err.to_string()))
+            })
+        })
+        .transpose()
+}
+
 /// Query if the public_key is revealed
 pub async fn is_public_key_revealed<C: namada_io::Client + Sync>(
     client: &C,
@@ -2,7 +2,6 @@
 
 #![allow(clippy::result_large_err)]
 
-use std::collections::BTreeMap;
 use std::fmt::Display;
 
 use borsh::BorshDeserialize;
@@ -31,7 +30,6 @@ use namada_ibc::core::channel::types::timeout::{
 };
 use namada_ibc::{MsgNftTransfer, MsgTransfer};
 use namada_io::*;
-use namada_parameters::storage as parameter_storage;
 use namada_token as token;
 use namada_token::storage_key::balance_key;
 use namada_tx::data::pgf::UpdateStewardCommission;
@@ -526,8 +524,9 @@ pub async fn aux_signing_data(
                     disposable_fee_payer: false,
                 }
                 // NOTE: the disposable gas_payer is always overridden by
-                // signing inputs
-            } else if disposable_gas_payer {
+                // signing inputs. A freshly generated key cannot have been
+                // granted a fee allowance so it is not used with a granter.
+            } else if disposable_gas_payer && wrap_tx.fee_granter.is_none() {
                 FeeAuthorization::Signer {
                     pubkey: gen_disposable_signing_key(context).await,
                     disposable_fee_payer: true,
@@ -538,6 +537,23 @@ pub async fn aux_signing_data(
         }
     };
 
+    // The fee payer must have been granted an allowance by the fee granter
+    if let (Some(fee_granter), FeeAuthorization::Signer { pubkey, .. }) =
+        (&wrap_tx.fee_granter, &fee_auth)
+    {
+        let grantee = Address::from(pubkey);
+        if rpc::query_fee_allowance(context.client(), fee_granter, &grantee)
+            .await?
+            .is_none()
+        {
+            return Err(Error::from(TxSubmitError::InvalidFeeAllowance(
+                fee_granter.clone(),
+                grantee,
+                "no allowance has been granted".to_string(),
+            )));
+        }
+    }
+
     let signing_inner_data = SigningTxData {
         owner,
         public_keys,
@@ -696,20 +712,18 @@ pub async fn validate_fee<N: Namada>(
     }: &args::Wrapper<SdkTypes>,
     force: bool,
 ) -> Result<DenominatedAmount, Error> {
-    let gas_cost_key = parameter_storage::get_gas_cost_key();
-    let minimum_fee = match rpc::query_storage_value::<
-        _,
-        BTreeMap<Address, Amount>,
-    >(context.client(), &gas_cost_key)
-    .await
-    .and_then(|map| {
-        map.get(fee_token).map(ToOwned::to_owned).ok_or_else(|| {
-            Error::Other(format!(
-                "Could not retrieve from storage the gas cost for token {}",
-                fee_token
-            ))
-        })
-    }) {
+    // The minimum fee is the current base fee of the token, which accounts
+    // for the fee market if enabled
+    let minimum_fee = match rpc::query_base_fee(context.client(), fee_token)
+        .await
+        .and_then(|base_fee| {
+            base_fee.ok_or_else(|| {
+                Error::Other(format!(
+                    "Could not retrieve from storage the gas cost for token {}",
+                    fee_token
+                ))
+            })
+        }) {
         Ok(amount) => amount,
         Err(e) => {
             if !force {
@@ -763,9 +777,29 @@ pub async fn validate_transparent_fee<N: Namada>(
     let args::Wrapper {
         fee_token,
         gas_limit,
+        fee_granter,
         ..
     } = args;
-    let fee_payer_address = Address::from(fee_payer);
+    let total_fee =
+        checked!(fee_amount.amount() * u64::from(gas_limit.to_owned()))?;
+
+    // With a fee granter, the fees are paid from the balance of the granter
+    // and must be covered by the allowance granted to the fee payer
+    let fee_payer_address = match fee_granter {
+        Some(fee_granter) => {
+            validate_fee_allowance(
+                context,
+                fee_granter,
+                &Address::from(fee_payer),
+                fee_token,
+                total_fee,
+                force,
+            )
+            .await?;
+            fee_granter.clone()
+        }
+        None => Address::from(fee_payer),
+    };
 
     let balance_key = balance_key(fee_token, &fee_payer_address);
     #[allow(clippy::disallowed_methods)]
@@ -776,8 +810,6 @@ pub async fn validate_transparent_fee<N: Namada>(
     .await
     .unwrap_or_default();
 
-    let total_fee =
-        checked!(fee_amount.amount() * u64::from(gas_limit.to_owned()))?;
     let mut updated_balance = TxSourcePostBalance {
         post_balance: balance,
         source: fee_payer_address.clone(),
@@ -811,6 +843,63 @@ pub async fn validate_transparent_fee<N: Namada>(
     Ok((fee_amount, updated_balance))
 }
 
+/// Validate that the fee allowance granted by `fee_granter` to `grantee` can
+/// be used to pay the given fees
+async fn validate_fee_allowance<N: Namada>(
+    context: &N,
+    fee_granter: &Address,
+    grantee: &Address,
+    fee_token: &Address,
+    total_fee: Amount,
+    force: bool,
+) -> Result<(), Error> {
+    let invalid_allowance = |reason: String| {
+        Error::from(TxSubmitError::InvalidFeeAllowance(
+            fee_granter.clone(),
+            grantee.clone(),
+            reason,
+        ))
+    };
+    let Some(allowance) =
+        rpc::query_fee_allowance(context.client(), fee_granter, grantee)
+            .await?
+    else {
+        return Err(invalid_allowance(
+            "no allowance has been granted".to_string(),
+        ));
+    };
+
+    let reason = if &allowance.token != fee_token {
+        Some(format!(
+            "the allowance can only be used with token {}",
+            allowance.token
+        ))
+    } else if allowance.spend_limit < total_fee {
+        let spend_limit = context
+            .format_amount(fee_token, allowance.spend_limit)
+            .await;
+        let total_fee = context.format_amount(fee_token, total_fee).await;
+        Some(format!(
+            "the remaining spend limit {spend_limit} is lower than the fees \
+             {total_fee}"
+        ))
+    } else {
+        match allowance.expiration {
+            Some(expiration)
+                if rpc::query_epoch(context.client()).await? >= expiration =>
+            {
+                Some(format!("the allowance expired at epoch {expiration}"))
+            }
+            _ => None,
+        }
+    };
+
+    match reason {
+        Some(reason) if !force => Err(invalid_allowance(reason)),
+        _ => Ok(()),
+    }
+}
+
 #[allow(clippy::result_large_err)]
 fn other_err<T>(string: String) -> Result<T, Error> {
     Err(Error::Other(string))
@@ -2344,6 +2433,12 @@ pub async fn to_ledger_vector(
                     format!("Fees/gas unit : {}", fee_amount_per_gas_unit),
                 ]);
             }
+            // The fees are paid by another account with a fee allowance
+            if let Some(fee_granter) = tx.fee_granter() {
+                tv.output.push(format!("Fee granter : {}", fee_granter));
+                tv.output_expert
+                    .push(format!("Fee granter : {}", fee_granter));
+            }
         }
     }
 
@@ -2354,9 +2449,9 @@ pub async fn to_ledger_vector(
 }
 
 #[cfg(test)]
-mod test_signing {
+pub(crate) mod test_signing {
     use core::str::FromStr;
-    use std::collections::BTreeSet;
+    use std::collections::{BTreeMap, BTreeSet};
     use std::sync::Arc;
 
     use assert_matches::assert_matches;
@@ -2381,11 +2476,13 @@ mod test_signing {
     use crate::args::InputAmount;
     use crate::masp::fs::FsShieldedUtils;
     use crate::masp::{ShieldedContext, WalletMap};
+    use crate::queries::RPC;
     use crate::token::Account;
 
-    fn arbitrary_args() -> args::Tx {
+    pub(crate) fn arbitrary_args() -> args::Tx {
         args::Tx {
             dry_run: None,
+            gas_profile: false,
             dump_tx: None,
             output_folder: None,
             force: false,
@@ -2399,8 +2496,10 @@ mod test_signing {
                 broadcast_only: false,
                 fee_amount: None,
                 wrapper_fee_payer: None,
+                fee_granter: None,
                 fee_token: Address::Internal(InternalAddress::Governance),
                 gas_limit: namada_tx::data::GasLimit::from(2),
+                gas_estimation: None,
             }),
             expiration: Default::default(),
             chain_id: None,
@@ -2420,7 +2519,7 @@ mod test_signing {
     }
 
     impl TestNamadaImpl {
-        fn new(
+        pub(crate) fn new(
             paths: Option<HashSet<String>>,
         ) -> (Self, UnboundedSender<Option<EncodedResponseQuery>>) {
             let (send, recv) = tokio::sync::mpsc::unbounded_channel();
@@ -2588,12 +2687,11 @@ mod test_signing {
     /// the force argument set.
     #[tokio::test]
     async fn test_validate_fee() {
-        let (context, client_handle) =
-            TestNamadaImpl::new(Some(HashSet::from([format!(
-                "/shell/value/{}",
-                parameter_storage::get_gas_cost_key()
-            )])));
         let mut args = arbitrary_args();
+        let (context, client_handle) =
+            TestNamadaImpl::new(Some(HashSet::from([RPC
+                .shell()
+                .base_fee_path(&args.wrap_tx.as_ref().unwrap().fee_token)])));
 
         // we should fail to validate the fee due to an unresponsive client
         client_handle.send(None).expect("Test failed");
@@ -2618,8 +2716,10 @@ mod test_signing {
         let args::Wrapper {
             broadcast_only,
             wrapper_fee_payer,
+            fee_granter,
             fee_token,
             gas_limit,
+            gas_estimation,
             ..
         } = args.wrap_tx.as_ref().unwrap().to_owned();
 
@@ -2628,8 +2728,7 @@ mod test_signing {
         args.force = false;
         client_handle
             .send(Some(EncodedResponseQuery {
-                data: BTreeMap::from([(fee_token.clone(), Amount::from(100))])
-                    .serialize_to_vec(),
+                data: Some(Amount::from(100)).serialize_to_vec(),
                 info: "".to_string(),
                 proof: None,
                 height: Default::default(),
@@ -2638,8 +2737,10 @@ mod test_signing {
         args.wrap_tx = Some(args::Wrapper {
             broadcast_only,
             wrapper_fee_payer,
+            fee_granter,
             fee_token,
             gas_limit,
+            gas_estimation,
             fee_amount: Some(InputAmount::Validated(DenominatedAmount::new(
                 Amount::from_u64(1),
                 0.into(),
@@ -2657,14 +2758,15 @@ mod test_signing {
         let args::Wrapper {
             broadcast_only,
             wrapper_fee_payer,
+            fee_granter,
             fee_token,
             gas_limit,
+            gas_estimation,
             ..
         } = args.wrap_tx.as_ref().unwrap().to_owned();
         client_handle
             .send(Some(EncodedResponseQuery {
-                data: BTreeMap::from([(fee_token.clone(), Amount::from(100))])
-                    .serialize_to_vec(),
+                data: Some(Amount::from(100)).serialize_to_vec(),
                 info: "".to_string(),
                 proof: None,
                 height: Default::default(),
@@ -2673,8 +2775,10 @@ mod test_signing {
         args.wrap_tx = Some(args::Wrapper {
             broadcast_only,
             wrapper_fee_payer,
+            fee_granter,
             fee_token,
             gas_limit,
+            gas_estimation,
             fee_amount: Some(InputAmount::Validated(DenominatedAmount::new(
                 Amount::from_u64(1),
                 0.into(),
@@ -2693,17 +2797,12 @@ mod test_signing {
         let args = arbitrary_args();
         // the minimum fee is set above the fee in the args.
         let (context, client_handle) =
-            TestNamadaImpl::new(Some(HashSet::from([format!(
-                "/shell/value/{}",
-                parameter_storage::get_gas_cost_key()
-            )])));
+            TestNamadaImpl::new(Some(HashSet::from([RPC
+                .shell()
+                .base_fee_path(&args.wrap_tx.as_ref().unwrap().fee_token)])));
         client_handle
             .send(Some(EncodedResponseQuery {
-                data: BTreeMap::from([(
-                    args.wrap_tx.as_ref().unwrap().fee_token.clone(),
-                    Amount::from(100),
-                )])
-                .serialize_to_vec(),
+                data: Some(Amount::from(100)).serialize_to_vec(),
                 info: "".to_string(),
                 proof: None,
                 height: Default::default(),
@@ -2895,6 +2994,60 @@ mod test_signing {
         assert_eq!(output, expected);
     }
 
+    /// Test that the `to_ledger_vector` function displays the fee granter
+    /// of a wrapper tx
+    #[tokio::test]
+    async fn test_to_ledger_vector_fee_granter() {
+        let wallet = HashMap::new();
+        let secret_key = common::SecretKey::Ed25519(testing::gen_keypair::<
+            ed25519::SigScheme,
+        >());
+        let fee_granter =
+            namada_core::address::testing::established_address_1();
+        let wrapper = |fee_granter: Option<Address>| {
+            let wrapper = namada_tx::data::WrapperTx::new(
+                namada_tx::data::Fee {
+                    amount_per_gas_unit: DenominatedAmount::native(1.into()),
+                    token: namada_core::address::testing::nam(),
+                },
+                secret_key.to_public(),
+                100.into(),
+            );
+            let mut tx = Tx::new(ChainId::default(), None);
+            tx.update_header(namada_tx::data::TxType::Wrapper(Box::new(
+                wrapper,
+            )));
+            if let Some(fee_granter) = fee_granter {
+                tx.add_fee_granter(fee_granter);
+            }
+            tx
+        };
+        // Join the parts of the lines of the fee granter
+        let fee_granter_lines = |output: &[String]| {
+            output
+                .iter()
+                .filter(|line| line.contains("| Fee granter"))
+                .filter_map(|line| line.split_once(" : "))
+                .map(|(_, value)| value)
+                .collect::<String>()
+        };
+
+        let tv = to_ledger_vector(&wallet, &wrapper(Some(fee_granter.clone())))
+            .await
+            .expect("Test failed");
+        assert_eq!(fee_granter_lines(&tv.output), fee_granter.to_string());
+        assert_eq!(
+            fee_granter_lines(&tv.output_expert),
+            fee_granter.to_string()
+        );
+
+        let tv = to_ledger_vector(&wallet, &wrapper(None))
+            .await
+            .expect("Test failed");
+        assert!(fee_granter_lines(&tv.output).is_empty());
+        assert!(fee_granter_lines(&tv.output_expert).is_empty());
+    }
+
     /// Test the `to_ledger_vector` function correctly
     /// extracts and validates the presence of a code section
     #[tokio::test]
@@ -1,13 +1,13 @@
 //! SDK functions to construct different types of transactions
 
 use std::borrow::Cow;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs::{self, File};
 use std::path::{Path, PathBuf};
 use std::time::Duration;
 
 use borsh::BorshSerialize;
-use data::{Fee, GasLimit, airdrop};
+use data::{Fee, GasLimit, TxType, airdrop};
 use masp_primitives::asset_type::AssetType;
 use masp_primitives::transaction::Transaction as MaspTransaction;
 use masp_primitives::transaction::builder::Builder;
@@ -22,7 +22,9 @@ use masp_primitives::transaction::components::transparent::fees::{
     InputView as TransparentInputView, OutputView as TransparentOutputView,
 };
 use masp_primitives::zip32::PseudoExtendedKey;
-use namada_account::{InitAccount, UpdateAccount};
+use namada_account::{
+    FeeAllowance, InitAccount, UpdateAccount, UpdateFeeAllowance,
+};
 use namada_core::address::{Address, IBC, MASP};
 use namada_core::arith::checked;
 use namada_core::chain::Epoch;
@@ -112,6 +114,8 @@ pub const TX_VOTE_PROPOSAL: &str = "tx_vote_proposal.wasm";
 pub const TX_REVEAL_PK: &str = "tx_reveal_pk.wasm";
 /// Update validity predicate WASM path
 pub const TX_UPDATE_ACCOUNT_WASM: &str = "tx_update_account.wasm";
+/// Update fee allowance transaction WASM path
+pub const TX_UPDATE_FEE_ALLOWANCE_WASM: &str = "tx_update_fee_allowance.wasm";
 /// Transparent transfer transaction WASM path
 pub const TX_TRANSFER_WASM: &str = "tx_transfer.wasm";
 /// IBC transaction WASM path
@@ -275,7 +279,12 @@ pub async fn process_tx(
                     .to_string(),
             ));
         }
-        expect_dry_broadcast(TxBroadcastData::DryRun(tx), context).await
+        expect_dry_broadcast(
+            TxBroadcastData::DryRun(tx),
+            args.gas_profile,
+            context,
+        )
+        .await
     } else {
         // We use this to determine when the wrapper tx makes it on-chain
         let tx_hash = tx.header_hash().to_string();
@@ -2146,7 +2155,9 @@ pub(crate) async fn derive_build_data<'args>(
             )
             .await?;
             let fee_payer = signing_data.fee_payer_or_err()?.to_owned();
-            let (fee_amount, updated_balance) = if disposable_gas_payer {
+            let (fee_amount, updated_balance) = if disposable_gas_payer
+                && wrap_args.fee_granter.is_none()
+            {
                 // MASP fee payment
                 (validate_fee(context, wrap_args, force).await?, None)
             } else {
@@ -2158,13 +2169,25 @@ pub(crate) async fn derive_build_data<'args>(
                     })?
             };
 
+            let gas_estimation =
+                wrap_args
+                    .gas_estimation
+                    .map(|estimation| GasEstimationData {
+                        estimation,
+                        signing_data: SigningData::Wrapper(
+                            signing_data.clone(),
+                        ),
+                    });
+
             Ok((
                 SigningData::Wrapper(signing_data),
                 Some(WrapArgs {
                     fee_amount,
                     fee_payer,
+                    fee_granter: wrap_args.fee_granter.to_owned(),
                     fee_token: wrap_args.fee_token.to_owned(),
                     gas_limit: wrap_args.gas_limit,
+                    gas_estimation,
                 }),
                 updated_balance,
             ))
@@ -2730,13 +2753,21 @@ pub async fn build_become_validator(
         )
         .await?;
 
+        let gas_estimation =
+            wrap_tx.gas_estimation.map(|estimation| GasEstimationData {
+                estimation,
+                signing_data: SigningData::Wrapper(signing_data.clone()),
+            });
+
         (
             SigningData::Wrapper(signing_data),
             Some(WrapArgs {
                 fee_amount,
                 fee_payer,
+                fee_granter: wrap_tx.fee_granter.to_owned(),
                 fee_token: wrap_tx.fee_token.to_owned(),
                 gas_limit: wrap_tx.gas_limit,
+                gas_estimation,
             }),
         )
     } else {
@@ -3024,6 +3055,7 @@ pub async fn build_ibc_transfer(
     }
 
     // For transfer from a spending key
+    let masp_fee_payment = masp_fee_data.is_some();
     let shielded_parts = construct_shielded_parts(
         context,
         masp_transfer_data,
@@ -3189,21 +3221,16 @@ pub async fn build_ibc_transfer(
         Some(args.tx_code_path.to_string_lossy().into_owned()),
     )
     .add_serialized_data(data);
-    if let Some(WrapArgs {
-        fee_amount,
-        fee_payer,
-        fee_token,
-        gas_limit,
-    }) = wrap_args
-    {
-        tx.add_wrapper(
-            Fee {
-                amount_per_gas_unit: fee_amount,
-                token: fee_token,
-            },
-            fee_payer,
-            gas_limit,
-        );
+    add_wrapper(context, &args.tx, &mut tx, wrap_args).await?;
+
+    if masp_fee_payment {
+        if let Some(tx_args) = masp_fee_rebuild_args(&args.tx, &tx) {
+            let args = args::TxIbcTransfer {
+                tx: tx_args,
+                ..args.clone()
+            };
+            return Box::pin(build_ibc_transfer(context, &args, bparams)).await;
+        }
     }
 
     Ok((tx, signing_data, shielded_tx_epoch))
@@ -3212,8 +3239,219 @@ pub async fn build_ibc_transfer(
 pub(crate) struct WrapArgs {
     pub(crate) fee_amount: DenominatedAmount,
     pub(crate) fee_payer: common::PublicKey,
+    pub(crate) fee_granter: Option<Address>,
     pub(crate) fee_token: Address,
     pub(crate) gas_limit: GasLimit,
+    pub(crate) gas_estimation: Option<GasEstimationData>,
+}
+
+/// The data required to estimate the gas limit of a wrapper transaction
+pub(crate) struct GasEstimationData {
+    pub(crate) estimation: args::GasEstimation,
+    /// The signing data used to produce the mock signatures of the dry-run
+    pub(crate) signing_data: SigningData,
+}
+
+/// Wrap the transaction if requested. If gas estimation was requested, the
+/// gas limit of the wrapper is replaced with the estimated one.
+pub(crate) async fn add_wrapper(
+    context: &impl Namada,
+    tx_args: &args::Tx,
+    tx: &mut Tx,
+    wrap_args: Option<WrapArgs>,
+) -> Result<()> {
+    let Some(WrapArgs {
+        fee_amount,
+        fee_payer,
+        fee_granter,
+        fee_token,
+        gas_limit,
+        gas_estimation,
+    }) = wrap_args
+    else {
+        return Ok(());
+    };
+
+    tx.add_wrapper(
+        Fee {
+            amount_per_gas_unit: fee_amount,
+            token: fee_token,
+        },
+        fee_payer,
+        gas_limit,
+    );
+    if let Some(fee_granter) = fee_granter {
+        tx.add_fee_granter(fee_granter);
+    }
+
+    if let Some(gas_estimation) = gas_estimation {
+        apply_gas_estimation(context, tx_args, tx, gas_estimation).await?;
+    }
+
+    Ok(())
+}
+
+/// Re-estimate the gas limit of a batch produced by [`build_batch`], if gas
+/// estimation was requested. The batch keeps the wrapper of its first
+/// transaction, whose gas limit was only estimated for that transaction, so
+/// the estimation is repeated on the whole batch starting again from the gas
+/// limit of the arguments as the upper bound. This must be called before
+/// signing the batch.
+pub async fn estimate_batch_gas_limit(
+    context: &impl Namada,
+    tx_args: &args::Tx,
+    batched_tx: &mut Tx,
+    signing_data: &SigningWrapperData,
+) -> Result<()> {
+    let Some(Wrapper {
+        gas_limit,
+        gas_estimation: Some(estimation),
+        ..
+    }) = &tx_args.wrap_tx
+    else {
+        return Ok(());
+    };
+    // A batch of a single transaction keeps its own estimation
+    if batched_tx.header.batch.len() < 2 {
+        return Ok(());
+    }
+
+    if let TxType::Wrapper(wrapper) = &mut batched_tx.header.tx_type {
+        wrapper.gas_limit = *gas_limit;
+    }
+    apply_gas_estimation(
+        context,
+        tx_args,
+        batched_tx,
+        GasEstimationData {
+            estimation: *estimation,
+            signing_data: SigningData::Wrapper(signing_data.clone()),
+        },
+    )
+    .await
+}
+
+/// Estimate the gas limit of a wrapper transaction and replace the one in its
+/// header with the estimation.
+async fn apply_gas_estimation(
+    context: &impl Namada,
+    tx_args: &args::Tx,
+    tx: &mut Tx,
+    GasEstimationData {
+        estimation,
+        signing_data,
+    }: GasEstimationData,
+) -> Result<()> {
+    let gas_limit =
+        estimate_gas_limit(context, tx_args, tx, signing_data, estimation)
+            .await?;
+    let TxType::Wrapper(wrapper) = &mut tx.header.tx_type else {
+        return Err(Error::Other(
+            "Gas estimation requires a wrapper transaction".to_string(),
+        ));
+    };
+    wrapper.gas_limit = gas_limit;
+
+    let fee_token = wrapper.fee.token.clone();
+    let fee = checked!(
+        wrapper.fee.amount_per_gas_unit.amount() * u64::from(gas_limit)
+    )?;
+    display_line!(
+        context.io(),
+        "Estimated gas limit: {}. Estimated fee: {} {}",
+        u64::from(gas_limit),
+        context.format_amount(&fee_token, fee).await,
+        fee_token
+    );
+
+    Ok(())
+}
+
+/// Estimate the gas limit of a wrapper transaction by dry-running it with mock
+/// signatures. The gas limit currently set in the wrapper is used as the upper
+/// bound of the estimation. The gas used by the dry-run is scaled by the
+/// safety multiplier and rounded up to a multiple of
+/// [`GAS_LIMIT_RESOLUTION`](data::GAS_LIMIT_RESOLUTION).
+pub async fn estimate_gas_limit(
+    context: &impl Namada,
+    tx_args: &args::Tx,
+    tx: &Tx,
+    signing_data: SigningData,
+    estimation: args::GasEstimation,
+) -> Result<GasLimit> {
+    let upper_bound = tx
+        .header()
+        .wrapper()
+        .ok_or_else(|| {
+            Error::Other(
+                "Gas estimation requires a wrapper transaction".to_string(),
+            )
+        })?
+        .gas_limit;
+    if estimation.safety_multiplier < Dec::one() {
+        return Err(Error::Other(format!(
+            "The gas safety multiplier must be at least 1, got {}",
+            estimation.safety_multiplier
+        )));
+    }
+
+    let mut tx = tx.clone();
+    let dry_run_args = args::Tx {
+        dry_run: Some(args::DryRun::Wrapper),
+        ..tx_args.clone()
+    };
+    context
+        .sign(
+            &mut tx,
+            &dry_run_args,
+            signing_data,
+            signing::default_sign,
+            (),
+        )
+        .await?;
+    let DryRunResult(tx_result, gas_used) =
+        rpc::query_dry_run_tx(context.client(), tx.to_bytes()).await?;
+    let gas_used = u64::from(gas_used);
+
+    if gas_used >= u64::from(upper_bound) {
+        return Err(Error::Other(format!(
+            "The transaction consumed all of the {} gas units available to \
+             the estimation. Please provide a higher --gas-limit.",
+            u64::from(upper_bound)
+        )));
+    }
+    for (inner_hash, cmt_result) in tx_result.iter() {
+        let failure = match cmt_result {
+            Ok(result) if result.is_accepted() => continue,
+            Ok(result) => format!(
+                "rejected by VPs: {}",
+                serde_json::to_string(&result.vps_result.rejected_vps).unwrap()
+            ),
+            Err(msg) => format!("failed: {msg}"),
+        };
+        if tx_args.force {
+            edisplay_line!(
+                context.io(),
+                "Transaction {inner_hash} {failure}. The estimated gas limit \
+                 may be inaccurate."
+            );
+        } else {
+            return Err(Error::Other(format!(
+                "Could not estimate the gas limit, transaction {inner_hash} \
+                 {failure}"
+            )));
+        }
+    }
+
+    let scaled_gas = token::Amount::from_u64(gas_used)
+        .mul_ceil(estimation.safety_multiplier)?;
+    let scaled_gas = u64::try_from(scaled_gas.raw_amount())
+        .map_err(|e| Error::Other(e.to_string()))?;
+    let gas_limit = GasLimit::from(scaled_gas).round_up().ok_or_else(|| {
+        Error::Other("Overflow in the estimated gas limit".to_string())
+    })?;
+
+    Ok(std::cmp::min(u64::from(gas_limit), u64::from(upper_bound)).into())
 }
 
 /// Abstraction for helping build transactions. This function will build either
@@ -3252,22 +3490,7 @@ where
     .add_data(data);
 
     // Wrap the transaction if requested
-    if let Some(WrapArgs {
-        fee_amount,
-        fee_payer,
-        fee_token,
-        gas_limit,
-    }) = wrap_args
-    {
-        tx.add_wrapper(
-            Fee {
-                amount_per_gas_unit: fee_amount,
-                token: fee_token,
-            },
-            fee_payer,
-            gas_limit,
-        );
-    }
+    add_wrapper(context, tx_args, &mut tx, wrap_args).await?;
 
     Ok(tx)
 }
@@ -3583,6 +3806,7 @@ pub async fn build_shielded_transfer<N: Namada>(
         None
     };
 
+    let masp_fee_payment = masp_fee_data.is_some();
     let shielded_parts = construct_shielded_parts(
         context,
         transfer_data,
@@ -3643,9 +3867,42 @@ pub async fn build_shielded_transfer<N: Namada>(
         wrap_args,
     )
     .await?;
+
+    if masp_fee_payment {
+        if let Some(tx_args) = masp_fee_rebuild_args(&args.tx, &tx) {
+            let mut args = args::TxShieldedTransfer {
+                tx: tx_args,
+                ..args.clone()
+            };
+            return Box::pin(build_shielded_transfer(
+                context, &mut args, bparams,
+            ))
+            .await;
+        }
+    }
+
     Ok((tx, signing_data))
 }
 
+// If the gas limit of a transaction paying fees via the masp was estimated,
+// produce the arguments to rebuild it with the estimated gas limit. The fee
+// unshielded from the masp was computed on the upper bound of the gas limit,
+// rebuilding the transaction avoids unshielding more than necessary.
+fn masp_fee_rebuild_args(tx_args: &args::Tx, tx: &Tx) -> Option<args::Tx> {
+    let wrap_tx = tx_args.wrap_tx.as_ref()?;
+    wrap_tx.gas_estimation?;
+    let gas_limit = tx.header().wrapper()?.gas_limit;
+
+    Some(args::Tx {
+        wrap_tx: Some(Wrapper {
+            gas_limit,
+            gas_estimation: None,
+            ..wrap_tx.clone()
+        }),
+        ..tx_args.clone()
+    })
+}
+
 // Check if the transaction will need to pay fees via the masp and extract the
 // right masp data
 async fn get_masp_fee_payment_amount<N: Namada>(
@@ -3653,11 +3910,17 @@ async fn get_masp_fee_payment_amount<N: Namada>(
     WrapArgs {
         fee_amount,
         fee_payer,
+        fee_granter,
         fee_token,
         gas_limit,
+        gas_estimation: _,
     }: &WrapArgs,
     gas_spending_key: Option<PseudoExtendedKey>,
 ) -> Result<Option<MaspFeeData>> {
+    // Fees paid by a granter cannot be unshielded from the MASP
+    if fee_granter.is_some() {
+        return Ok(None);
+    }
     let fee_payer_address = Address::from(fee_payer);
     let balance_key = balance_key(fee_token, &fee_payer_address);
     #[allow(clippy::disallowed_methods)]
@@ -4074,6 +4337,7 @@ pub async fn build_unshielding_transfer<N: Namada>(
         None
     };
 
+    let masp_fee_payment = masp_fee_data.is_some();
     let shielded_parts = construct_shielded_parts(
         context,
         transfer_data,
@@ -4134,6 +4398,20 @@ pub async fn build_unshielding_transfer<N: Namada>(
         wrap_args,
     )
     .await?;
+
+    if masp_fee_payment {
+        if let Some(tx_args) = masp_fee_rebuild_args(&args.tx, &tx) {
+            let mut args = args::TxUnshieldingTransfer {
+                tx: tx_args,
+                ..args.clone()
+            };
+            return Box::pin(build_unshielding_transfer(
+                context, &mut args, bparams,
+            ))
+            .await;
+        }
+    }
+
     Ok((tx, signing_data))
 }
 
@@ -4398,6 +4676,86 @@ pub async fn build_update_account(
     .map(|tx| (tx, signing_data))
 }
 
+/// Submit a transaction to grant or revoke a fee allowance
+pub async fn build_update_fee_allowance(
+    context: &impl Namada,
+    args::TxUpdateFeeAllowance {
+        tx: tx_args,
+        granter,
+        grantee,
+        token,
+        spend_limit,
+        expiration,
+        allowed_tx_code_paths,
+        revoke,
+        tx_code_path,
+    }: &args::TxUpdateFeeAllowance,
+) -> Result<(Tx, SigningData)> {
+    let default_signer = Some(granter.clone());
+    let (signing_data, wrap_args, _) = derive_build_data(
+        context,
+        tx_args
+            .wrap_tx
+            .as_ref()
+            .map(|wrap_args| ExtendedWrapperArgs {
+                wrap_args,
+                disposable_gas_payer: false,
+            }),
+        tx_args.force,
+        default_signer,
+        tx_args.signing_keys.to_owned(),
+        vec![],
+    )
+    .await?;
+
+    // Check that the granter address exists on chain
+    let granter =
+        source_exists_or_err(granter.clone(), tx_args.force, context).await?;
+
+    let allowance = if *revoke {
+        None
+    } else {
+        let spend_limit =
+            validate_amount(context, *spend_limit, token, tx_args.force)
+                .await?
+                .amount();
+        let allowed_code_hashes = if allowed_tx_code_paths.is_empty() {
+            None
+        } else {
+            let mut code_hashes = BTreeSet::new();
+            for code_path in allowed_tx_code_paths {
+                code_hashes.insert(
+                    query_wasm_code_hash_buf(context, code_path).await?,
+                );
+            }
+            Some(code_hashes)
+        };
+        Some(FeeAllowance {
+            token: token.clone(),
+            spend_limit,
+            expiration: *expiration,
+            allowed_code_hashes,
+        })
+    };
+
+    let data = UpdateFeeAllowance {
+        granter,
+        grantee: grantee.clone(),
+        allowance,
+    };
+
+    build(
+        context,
+        tx_args,
+        tx_code_path.clone(),
+        data,
+        do_nothing,
+        wrap_args,
+    )
+    .await
+    .map(|tx| (tx, signing_data))
+}
+
 /// Submit a custom transaction
 pub async fn build_custom(
     context: &impl Namada,
@@ -4528,22 +4886,7 @@ pub async fn build_custom(
         (signing_data, wrap_tx)
     };
 
-    if let Some(WrapArgs {
-        fee_amount,
-        fee_payer,
-        fee_token,
-        gas_limit,
-    }) = wrap_tx
-    {
-        tx.add_wrapper(
-            Fee {
-                amount_per_gas_unit: fee_amount,
-                token: fee_token,
-            },
-            fee_payer,
-            gas_limit,
-        );
-    }
+    add_wrapper(context, tx_args, &mut tx, wrap_tx).await?;
 
     Ok((tx, signing_data))
 }
@@ -4697,11 +5040,18 @@ pub(crate) async fn get_ibc_src_port_channel(
 
 async fn expect_dry_broadcast(
     to_broadcast: TxBroadcastData,
+    gas_profile: bool,
     context: &impl Namada,
 ) -> Result<ProcessTxResponse> {
     match to_broadcast {
         TxBroadcastData::DryRun(tx) => {
-            let result = rpc::dry_run_tx(context, tx.to_bytes()).await?;
+            let result = if gas_profile {
+                rpc::dry_run_tx_with_gas_profile(context, tx.to_bytes())
+                    .await?
+                    .0
+            } else {
+                rpc::dry_run_tx(context, tx.to_bytes()).await?
+            };
             Ok(ProcessTxResponse::DryRun(result))
         }
         TxBroadcastData::Live { tx, tx_hash: _ } => {
@@ -4956,3 +5306,406 @@ fn proposal_to_vec(proposal: OnChainProposal) -> Result<Vec<u8>> {
     borsh::to_vec(&proposal.content)
         .map_err(|e| Error::from(EncodingError::Conversion(e.to_string())))
 }
+
+#[cfg(test)]
+mod test_gas_estimation {
+    use namada_core::chain::ChainId;
+    use namada_io::client::EncodedResponseQuery;
+    use namada_tx::data::{TxResult, VpsResult};
+    use tokio::sync::mpsc::UnboundedSender;
+
+    use super::*;
+    use crate::queries::RPC;
+    use crate::signing::test_signing::{TestNamadaImpl, arbitrary_args};
+
+    /// The upper bound of the estimations
+    const GAS_LIMIT: u64 = 100_000;
+
+    fn test_context() -> (
+        TestNamadaImpl,
+        UnboundedSender<Option<EncodedResponseQuery>>,
+    ) {
+        TestNamadaImpl::new(Some(HashSet::from([RPC
+            .shell()
+            .dry_run_tx_path()])))
+    }
+
+    fn estimation_args(safety_multiplier: Dec) -> args::Tx {
+        let mut args = arbitrary_args();
+        let wrap_tx = args.wrap_tx.as_mut().unwrap();
+        wrap_tx.gas_limit = GasLimit::from(GAS_LIMIT);
+        wrap_tx.gas_estimation =
+            Some(args::GasEstimation { safety_multiplier });
+        args
+    }
+
+    fn wrapper_signing_data() -> SigningWrapperData {
+        SigningWrapperData {
+            signing_data: vec![],
+            fee_auth: FeeAuthorization::Signer {
+                pubkey: key::testing::keypair_1().to_public(),
+                disposable_fee_payer: false,
+            },
+        }
+    }
+
+    fn wrap_args(
+        args: &args::Tx,
+        gas_estimation: Option<args::GasEstimation>,
+    ) -> WrapArgs {
+        let wrap_tx = args.wrap_tx.as_ref().unwrap();
+        WrapArgs {
+            fee_amount: DenominatedAmount::native(token::Amount::from_u64(1)),
+            fee_payer: key::testing::keypair_1().to_public(),
+            fee_granter: None,
+            fee_token: wrap_tx.fee_token.clone(),
+            gas_limit: wrap_tx.gas_limit,
+            gas_estimation: gas_estimation.map(|estimation| {
+                GasEstimationData {
+                    estimation,
+                    signing_data: SigningData::Wrapper(wrapper_signing_data()),
+                }
+            }),
+        }
+    }
+
+    /// A wrapper transaction with a gas limit equal to the upper bound of the
+    /// estimations
+    fn wrapper_tx(code: &[u8]) -> Tx {
+        let mut tx = Tx::new(ChainId::default(), None);
+        tx.add_code(code.to_vec(), None).add_data(code.to_vec());
+        tx.add_wrapper(
+            Fee {
+                amount_per_gas_unit: DenominatedAmount::native(
+                    token::Amount::from_u64(1),
+                ),
+                token: arbitrary_args().wrap_tx.unwrap().fee_token,
+            },
+            key::testing::keypair_1().to_public(),
+            GAS_LIMIT.into(),
+        );
+        tx
+    }
+
+    fn dry_run_response(
+        tx_result: TxResult<String>,
+        gas_used: u64,
+    ) -> Option<EncodedResponseQuery> {
+        Some(EncodedResponseQuery {
+            data: DryRunResult(tx_result, gas_used.into()).serialize_to_vec(),
+            info: "".to_string(),
+            proof: None,
+            height: Default::default(),
+        })
+    }
+
+    fn accepted(tx: &Tx) -> TxResult<String> {
+        let mut tx_result = TxResult::new();
+        for cmt in tx.commitments() {
+            tx_result.insert_inner_tx_result(
+                tx.wrapper_hash().as_ref(),
+                either::Right(cmt),
+                Ok(BatchedTxResult::default()),
+            );
+        }
+        tx_result
+    }
+
+    async fn estimate(
+        context: &TestNamadaImpl,
+        args: &args::Tx,
+        tx: &Tx,
+    ) -> Result<u64> {
+        let estimation = args.wrap_tx.as_ref().unwrap().gas_estimation.unwrap();
+        estimate_gas_limit(
+            context,
+            args,
+            tx,
+            SigningData::Wrapper(wrapper_signing_data()),
+            estimation,
+        )
+        .await
+        .map(u64::from)
+    }
+
+    /// Test that the gas used by the dry-run is scaled by the safety
+    /// multiplier and rounded up to the gas limit resolution
+    #[tokio::test]
+    async fn test_estimation_multiplier_and_rounding() {
+        let (context, client_handle) = test_context();
+        let tx = wrapper_tx(b"tx");
+
+        for (multiplier, gas_used, expected) in [
+            // An exact multiple of the resolution is left untouched
+            (Dec::one(), 10_000, 10_000),
+            // Any remainder is rounded up
+            (Dec::one(), 10_001, 11_000),
+            (Dec::one(), 1, 1_000),
+            // 7_000 * 1.3 = 9_100
+            (Dec::new(13, 1).unwrap(), 7_000, 10_000),
+            // 7_001 * 1.5 = 10_501.5, the multiplication also rounds up
+            (Dec::new(15, 1).unwrap(), 7_001, 11_000),
+            // The scaled gas is capped by the upper bound
+            (Dec::two(), 60_000, GAS_LIMIT),
+        ] {
+            client_handle
+                .send(dry_run_response(accepted(&tx), gas_used))
+                .expect("Test failed");
+            let gas_limit =
+                estimate(&context, &estimation_args(multiplier), &tx)
+                    .await
+                    .expect("Test failed");
+            assert_eq!(gas_limit, expected, "gas used: {gas_used}");
+        }
+
+        // A multiplier lower than one is rejected before the dry-run
+        let Err(Error::Other(msg)) =
+            estimate(&context, &estimation_args(Dec::new(9, 1).unwrap()), &tx)
+                .await
+        else {
+            panic!("Test failed")
+        };
+        assert!(msg.contains("must be at least 1"), "{msg}");
+    }
+
+    /// Test that the estimation fails if the dry-run consumed all of the gas
+    /// of the upper bound
+    #[tokio::test]
+    async fn test_estimation_upper_bound_exhausted() {
+        let (context, client_handle) = test_context();
+        let tx = wrapper_tx(b"tx");
+        let args = estimation_args(Dec::one());
+
+        client_handle
+            .send(dry_run_response(accepted(&tx), GAS_LIMIT))
+            .expect("Test failed");
+        let Err(Error::Other(msg)) = estimate(&context, &args, &tx).await
+        else {
+            panic!("Test failed")
+        };
+        assert!(msg.contains("consumed all"), "{msg}");
+
+        // Just below the upper bound the estimation is capped
+        client_handle
+            .send(dry_run_response(
+                accepted(&tx),
+                GAS_LIMIT.checked_sub(1).unwrap(),
+            ))
+            .expect("Test failed");
+        assert_eq!(
+            estimate(&context, &args, &tx).await.expect("Test failed"),
+            GAS_LIMIT
+        );
+    }
+
+    /// Test that the estimation fails if an inner tx is rejected or failed,
+    /// unless forced
+    #[tokio::test]
+    async fn test_estimation_rejected_inner_txs() {
+        let (context, client_handle) = test_context();
+        let tx = wrapper_tx(b"tx");
+        let cmt = tx.commitments().iter().next().unwrap().to_owned();
+
+        let mut rejected = TxResult::new();
+        rejected.insert_inner_tx_result(
+            tx.wrapper_hash().as_ref(),
+            either::Right(&cmt),
+            Ok(BatchedTxResult {
+                vps_result: VpsResult {
+                    rejected_vps: [Address::Internal(
+                        namada_core::address::InternalAddress::Governance,
+                    )]
+                    .into(),
+                    ..Default::default()
+                },
+                ..Default::default()
+            }),
+        );
+        let mut failed = TxResult::new();
+        failed.insert_inner_tx_result(
+            tx.wrapper_hash().as_ref(),
+            either::Right(&cmt),
+            Err("Out of gas".to_string()),
+        );
+
+        let mut args = estimation_args(Dec::one());
+        for (tx_result, expected) in [
+            (rejected.clone(), "rejected by VPs"),
+            (failed.clone(), "failed: Out of gas"),
+        ] {
+            client_handle
+                .send(dry_run_response(tx_result, 5_000))
+                .expect("Test failed");
+            let Err(Error::Other(msg)) = estimate(&context, &args, &tx).await
+            else {
+                panic!("Test failed")
+            };
+            assert!(msg.contains(expected), "{msg}");
+        }
+
+        // With force the estimation is still produced
+        args.force = true;
+        for tx_result in [rejected, failed] {
+            client_handle
+                .send(dry_run_response(tx_result, 5_000))
+                .expect("Test failed");
+            assert_eq!(
+                estimate(&context, &args, &tx).await.expect("Test failed"),
+                5_000
+            );
+        }
+    }
+
+    /// Test that the gas limit of a batch is estimated again on the whole
+    /// batch, starting from the upper bound of the arguments instead of the gas
+    /// limit estimated for the first tx
+    #[tokio::test]
+    async fn test_estimation_after_batching() {
+        let (context, client_handle) = test_context();
+        let args = estimation_args(Dec::one());
+
+        let mut first = wrapper_tx(b"first");
+        client_handle
+            .send(dry_run_response(accepted(&first), 2_000))
+            .expect("Test failed");
+        apply_gas_estimation(
+            &context,
+            &args,
+            &mut first,
+            GasEstimationData {
+                estimation: args
+                    .wrap_tx
+                    .as_ref()
+                    .unwrap()
+                    .gas_estimation
+                    .unwrap(),
+                signing_data: SigningData::Wrapper(wrapper_signing_data()),
+            },
+        )
+        .await
+        .expect("Test failed");
+        assert_eq!(
+            u64::from(first.header().wrapper().unwrap().gas_limit),
+            2_000
+        );
+
+        let second = wrapper_tx(b"second");
+        let (mut batch, signing_data) = build_batch(vec![
+            (first.clone(), SigningData::Wrapper(wrapper_signing_data())),
+            (second, SigningData::Wrapper(wrapper_signing_data())),
+        ])
+        .expect("Test failed");
+        let signing_data = signing_data.unwrap_left();
+        // The batch kept the gas limit of the first tx
+        assert_eq!(
+            u64::from(batch.header().wrapper().unwrap().gas_limit),
+            2_000
+        );
+
+        // The batch consumes more than the gas limit of the first tx
+        client_handle
+            .send(dry_run_response(accepted(&batch), 5_001))
+            .expect("Test failed");
+        estimate_batch_gas_limit(&context, &args, &mut batch, &signing_data)
+            .await
+            .expect("Test failed");
+        assert_eq!(
+            u64::from(batch.header().wrapper().unwrap().gas_limit),
+            6_000
+        );
+
+        // From here on no dry-run is expected: an unexpected query would fail
+        // on this response
+        client_handle.send(None).expect("Test failed");
+
+        // Without gas estimation the gas limit of the batch is left untouched
+        let mut no_estimation_args = args.clone();
+        no_estimation_args.wrap_tx.as_mut().unwrap().gas_estimation = None;
+        estimate_batch_gas_limit(
+            &context,
+            &no_estimation_args,
+            &mut batch,
+            &signing_data,
+        )
+        .await
+        .expect("Test failed");
+        assert_eq!(
+            u64::from(batch.header().wrapper().unwrap().gas_limit),
+            6_000
+        );
+
+        // A batch of a single tx keeps its own estimation
+        let (mut batch, signing_data) = build_batch(vec![(
+            first,
+            SigningData::Wrapper(wrapper_signing_data()),
+        )])
+        .expect("Test failed");
+        estimate_batch_gas_limit(
+            &context,
+            &args,
+            &mut batch,
+            &signing_data.unwrap_left(),
+        )
+        .await
+        .expect("Test failed");
+        assert_eq!(
+            u64::from(batch.header().wrapper().unwrap().gas_limit),
+            2_000
+        );
+    }
+
+    /// Test that a tx paying fees via the masp is rebuilt once with the
+    /// estimated gas limit and that the rebuild doesn't estimate again, which
+    /// stops the recursion
+    #[tokio::test]
+    async fn test_masp_fee_rebuild_recursion() {
+        let (context, client_handle) = test_context();
+        let args = estimation_args(Dec::new(13, 1).unwrap());
+
+        // The first build estimates the gas limit
+        let mut tx = wrapper_tx(b"tx");
+        client_handle
+            .send(dry_run_response(accepted(&tx), 7_000))
+            .expect("Test failed");
+        add_wrapper(
+            &context,
+            &args,
+            &mut tx,
+            Some(wrap_args(
+                &args,
+                args.wrap_tx.as_ref().unwrap().gas_estimation,
+            )),
+        )
+        .await
+        .expect("Test failed");
+        assert_eq!(u64::from(tx.header().wrapper().unwrap().gas_limit), 10_000);
+
+        // The rebuild uses the estimation as the gas limit and disables the
+        // estimation
+        let rebuild_args =
+            masp_fee_rebuild_args(&args, &tx).expect("Test failed");
+        let rebuild_wrap_tx = rebuild_args.wrap_tx.as_ref().unwrap();
+        assert_eq!(u64::from(rebuild_wrap_tx.gas_limit), 10_000);
+        assert!(rebuild_wrap_tx.gas_estimation.is_none());
+
+        // The rebuild doesn't query the dry-run again: an unexpected query
+        // would fail on this response
+        client_handle.send(None).expect("Test failed");
+        let mut rebuilt_tx = wrapper_tx(b"tx");
+        add_wrapper(
+            &context,
+            &rebuild_args,
+            &mut rebuilt_tx,
+            Some(wrap_args(&rebuild_args, None)),
+        )
+        .await
+        .expect("Test failed");
+        assert_eq!(
+            u64::from(rebuilt_tx.header().wrapper().unwrap().gas_limit),
+            10_000
+        );
+
+        // And it is not rebuilt a second time
+        assert!(masp_fee_rebuild_args(&rebuild_args, &rebuilt_tx).is_none());
+    }
+}
@@ -21,6 +21,8 @@ pub mod conversion;
 
 #[cfg(feature = "masp")]
 pub mod masp;
+#[cfg(feature = "migrations")]
+mod schema;
 mod storage;
 pub mod storage_key;
 pub mod utils;
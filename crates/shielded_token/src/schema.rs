@@ -0,0 +1,56 @@
+//! Storage schemas of the MASP keys
+
+use masp_primitives::merkle_tree::CommitmentTree;
+use masp_primitives::sapling::Node;
+use namada_core::dec::Dec;
+use namada_core::hash::Hash;
+use namada_core::masp::{Precision, TokenMap};
+use namada_core::token::Amount;
+use namada_migrations::storage_schema;
+
+storage_schema!(TOKEN_MAP: "#MASP/tokens" => TokenMap);
+storage_schema!(NULLIFIER: "#MASP/nullifiers/<nullifier>" => ());
+storage_schema!(UNDATED_BALANCE: "#MASP/undated_balance/<token>" => Amount);
+storage_schema!(
+    COMMITMENT_TREE: "#MASP/commitment_tree" => CommitmentTree<Node> as debug
+);
+storage_schema!(
+    COMMITMENT_ANCHOR: "#MASP/note_commitment_anchor/<anchor>" => ()
+);
+storage_schema!(CONVERT_ANCHOR: "#MASP/convert_anchor" => Hash);
+storage_schema!(ASSETS_HASH: "#MASP/assets_hash" => Hash);
+storage_schema!(
+    BASE_NATIVE_PRECISION: "#MASP/base_native_precision" => Precision
+);
+storage_schema!(
+    SCHEDULED_BASE_NATIVE_PRECISION:
+        "#MASP/scheduled_base_native_precision/<epoch>" => Precision
+);
+storage_schema!(
+    SCHEDULED_REWARD_PRECISION:
+        "#MASP/scheduled_reward_precision/<epoch>/<token>" => Precision
+);
+storage_schema!(
+    LAST_INFLATION: "#Multitoken/<token>/parameters/last_inflation" => Amount
+);
+storage_schema!(
+    LAST_LOCKED_AMOUNT:
+        "#Multitoken/<token>/parameters/last_locked_amount" => Amount
+);
+storage_schema!(
+    MAX_REWARD_RATE: "#Multitoken/<token>/parameters/max_reward_rate" => Dec
+);
+storage_schema!(
+    KP_GAIN: "#Multitoken/<token>/parameters/proportional_gain" => Dec
+);
+storage_schema!(
+    KD_GAIN: "#Multitoken/<token>/parameters/derivative_gain" => Dec
+);
+storage_schema!(
+    LOCKED_AMOUNT_TARGET:
+        "#Multitoken/<token>/parameters/locked_amount_target" => Amount
+);
+storage_schema!(
+    REWARD_PRECISION:
+        "#Multitoken/<token>/parameters/reward_precision" => Precision
+);
@@ -7,7 +7,9 @@ use namada_core::uint::Uint;
 use namada_systems::trans_token;
 
 use crate::storage_key::*;
-use crate::{Result, ResultExt, ShieldedParams, StorageRead, StorageWrite};
+use crate::{
+    Error, Result, ResultExt, ShieldedParams, StorageRead, StorageWrite,
+};
 
 /// Initialize parameters for the token in storage during the genesis block.
 pub fn write_params<S, TransToken>(
@@ -50,6 +52,48 @@ where
     Ok(())
 }
 
+/// Read the shielded parameters of the token from storage, if any.
+pub fn read_params<S, TransToken>(
+    storage: &S,
+    token: &Address,
+    denom: &token::Denomination,
+) -> Result<Option<ShieldedParams>>
+where
+    S: StorageRead,
+    TransToken: trans_token::Keys,
+{
+    let Some(max_reward_rate) =
+        storage.read(&masp_max_reward_rate_key::<TransToken>(token))?
+    else {
+        return Ok(None);
+    };
+    let kp_gain_nom = storage
+        .read(&masp_kp_gain_key::<TransToken>(token))?
+        .unwrap_or_default();
+    let kd_gain_nom = storage
+        .read(&masp_kd_gain_key::<TransToken>(token))?
+        .unwrap_or_default();
+
+    let raw_target: Amount = storage
+        .read(&masp_locked_amount_target_key::<TransToken>(token))?
+        .unwrap_or_default();
+    let locked_amount_target = checked!(
+        raw_target.raw_amount() / (Uint::from(10) ^ Uint::from(denom.0))
+    )?;
+    let locked_amount_target =
+        u64::try_from(locked_amount_target).map_err(|_| {
+            Error::new_alloc(format!(
+                "The locked amount target of token {token} doesn't fit in u64"
+            ))
+        })?;
+    Ok(Some(ShieldedParams {
+        max_reward_rate,
+        kd_gain_nom,
+        kp_gain_nom,
+        locked_amount_target,
+    }))
+}
+
 /// Mint MASP rewards tokens and increment the stored total rewards.
 pub fn mint_rewards<S, TransToken>(
     storage: &mut S,
@@ -0,0 +1,400 @@
+//! Persistent storage of the events emitted by the ledger.
+//!
+//! The events of each committed block are written to the events column family
+//! of the DB, next to the block they were emitted in:
+//! - `block/{height}/{index}`: the event at position `index` of the events
+//!   emitted at `height`
+//! - `attr/{digest}/{height}/{index}`: an empty entry indexing the event at
+//!   `block/{height}/{index}` by the digest of one of its attributes' key and
+//!   value
+//!
+//! Since tx hashes, inner tx hashes and the heights of the events are
+//! themselves event attributes, the attributes index covers lookups of the
+//! events emitted by a given (inner) tx.
+
+use namada_core::chain::BlockHeight;
+use namada_core::hash::Hash;
+use namada_core::storage::{Key, KeySeg};
+use namada_core::{decode, encode};
+use namada_events::Event;
+
+use crate::{DB, DBIter, DbError as Error, DbResult as Result};
+
+const BLOCK_KEY_SEGMENT: &str = "block";
+const ATTRIBUTE_KEY_SEGMENT: &str = "attr";
+
+/// Configures how long the events are kept in the event store.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EventRetention {
+    /// Keep the events of every block.
+    #[default]
+    Archive,
+    /// Keep only the events of the given number of most recent blocks.
+    Blocks(u64),
+}
+
+impl EventRetention {
+    /// Return the height below which the events have to be pruned after a
+    /// commit of the block at `committed_height`, if any.
+    pub fn pruning_cutoff(
+        &self,
+        committed_height: BlockHeight,
+    ) -> Option<BlockHeight> {
+        match self {
+            Self::Archive => None,
+            Self::Blocks(num_blocks) => committed_height
+                .0
+                .checked_sub(*num_blocks)
+                .filter(|height| *height > 0)
+                .and_then(|height| height.checked_add(1))
+                .map(BlockHeight),
+        }
+    }
+}
+
+/// Get the prefix of the events emitted at the given height.
+pub fn block_events_prefix(height: BlockHeight) -> Key {
+    Key::from(BLOCK_KEY_SEGMENT.to_owned().to_db_key())
+        .push(&height)
+        .expect("Cannot obtain a storage key")
+}
+
+/// Get the key of the event at the given position of a block's events.
+pub fn event_key(height: BlockHeight, index: u64) -> Key {
+    block_events_prefix(height)
+        .push(&index)
+        .expect("Cannot obtain a storage key")
+}
+
+/// Get the prefix of the index entries of the events with the given
+/// attribute.
+pub fn attribute_index_prefix(key: &str, value: &str) -> Key {
+    Key::from(ATTRIBUTE_KEY_SEGMENT.to_owned().to_db_key())
+        .push(&attribute_digest(key, value))
+        .expect("Cannot obtain a storage key")
+}
+
+/// Get the key of the index entry of an event with the given attribute.
+fn attribute_index_key(
+    key: &str,
+    value: &str,
+    height: BlockHeight,
+    index: u64,
+) -> Key {
+    attribute_index_prefix(key, value)
+        .push(&height)
+        .and_then(|k| k.push(&index))
+        .expect("Cannot obtain a storage key")
+}
+
+/// Digest of an attribute's key and value. Attribute values are arbitrary
+/// strings, so they can't be used as storage key segments directly.
+fn attribute_digest(key: &str, value: &str) -> Hash {
+    let mut bytes = Vec::with_capacity(
+        key.len().saturating_add(value.len()).saturating_add(1),
+    );
+    bytes.extend_from_slice(key.as_bytes());
+    bytes.push(0);
+    bytes.extend_from_slice(value.as_bytes());
+    Hash::sha256(bytes)
+}
+
+/// Write the given events emitted at `height` to the batch, indexed by each
+/// of their attributes.
+pub fn add_events_to_batch<D>(
+    db: &D,
+    batch: &mut D::WriteBatch,
+    height: BlockHeight,
+    events: &[Event],
+) -> Result<()>
+where
+    D: DB,
+{
+    for (index, event) in events.iter().enumerate() {
+        let index = u64::try_from(index)?;
+        db.batch_write_event_entry(
+            batch,
+            &event_key(height, index),
+            encode(event),
+        )?;
+        for (key, value) in event.clone().into_attributes() {
+            db.batch_write_event_entry(
+                batch,
+                &attribute_index_key(&key, &value, height, index),
+                [],
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Delete the events emitted at `height` and their index entries in the
+/// batch.
+pub fn delete_events_to_batch<D>(
+    db: &D,
+    batch: &mut D::WriteBatch,
+    height: BlockHeight,
+) -> Result<()>
+where
+    D: DB + for<'iter> DBIter<'iter>,
+{
+    for event in iter_events_at_height(db, height) {
+        let (index, event) = event?;
+        for (key, value) in event.into_attributes() {
+            db.batch_delete_event_entry(
+                batch,
+                &attribute_index_key(&key, &value, height, index),
+            )?;
+        }
+        db.batch_delete_event_entry(batch, &event_key(height, index))?;
+    }
+    Ok(())
+}
+
+/// Delete the events emitted below the `cutoff` height and their index
+/// entries in the batch. This also prunes the events left over from a
+/// previous, longer retention or from blocks committed before a restart.
+pub fn delete_events_below_to_batch<D>(
+    db: &D,
+    batch: &mut D::WriteBatch,
+    cutoff: BlockHeight,
+) -> Result<()>
+where
+    D: DB + for<'iter> DBIter<'iter>,
+{
+    // The index entries are keyed by the attributes' digests, so they have to
+    // be looked up from the pruned events
+    for event in iter_events_with_position(db) {
+        let (height, index, event) = event?;
+        if height >= cutoff {
+            break;
+        }
+        for (key, value) in event.into_attributes() {
+            db.batch_delete_event_entry(
+                batch,
+                &attribute_index_key(&key, &value, height, index),
+            )?;
+        }
+    }
+    // The heights are encoded in an order-preserving fixed-width format, so
+    // the events below the cutoff are in a single range of keys
+    db.batch_delete_event_range(
+        batch,
+        &Key::from(BLOCK_KEY_SEGMENT.to_owned().to_db_key()),
+        &block_events_prefix(cutoff),
+    )
+}
+
+/// Iterate over the events emitted at `height` along with their position in
+/// the block, in order of emission.
+pub fn iter_events_at_height<D>(
+    db: &D,
+    height: BlockHeight,
+) -> impl Iterator<Item = Result<(u64, Event)>> + '_
+where
+    D: DB + for<'iter> DBIter<'iter>,
+{
+    let prefix = block_events_prefix(height);
+    db.iter_event_entries(&prefix).map(|(key, bytes, _gas)| {
+        let (_height, index) = parse_event_position(&key)?;
+        let event = decode(bytes).map_err(Error::CodingError)?;
+        Ok((index, event))
+    })
+}
+
+/// Iterate over all the stored events, in order of emission.
+pub fn iter_events<D>(
+    db: &D,
+) -> impl Iterator<Item = Result<(BlockHeight, Event)>> + '_
+where
+    D: DB + for<'iter> DBIter<'iter>,
+{
+    iter_events_with_position(db)
+        .map(|event| event.map(|(height, _index, event)| (height, event)))
+}
+
+/// Iterate over all the stored events along with their height and position
+/// in the block, in order of emission.
+fn iter_events_with_position<D>(
+    db: &D,
+) -> impl Iterator<Item = Result<(BlockHeight, u64, Event)>> + '_
+where
+    D: DB + for<'iter> DBIter<'iter>,
+{
+    let prefix = Key::from(BLOCK_KEY_SEGMENT.to_owned().to_db_key());
+    db.iter_event_entries(&prefix).map(|(key, bytes, _gas)| {
+        let (height, index) = parse_event_position(&key)?;
+        let event = decode(bytes).map_err(Error::CodingError)?;
+        Ok((height, index, event))
+    })
+}
+
+/// Iterate over the stored events that have an attribute with the given key
+/// and value, in order of emission.
+pub fn iter_events_with_attribute<'db, D>(
+    db: &'db D,
+    key: &str,
+    value: &str,
+) -> impl Iterator<Item = Result<(BlockHeight, Event)>> + 'db
+where
+    D: DB + for<'iter> DBIter<'iter>,
+{
+    let prefix = attribute_index_prefix(key, value);
+    db.iter_event_entries(&prefix)
+        .map(move |(index_key, _, _gas)| {
+            let (height, index) = parse_event_position(&index_key)?;
+            let bytes = db.read_event_entry(&event_key(height, index))?.ok_or(
+                Error::UnknownKey {
+                    key: event_key(height, index).to_string(),
+                },
+            )?;
+            let event = decode(bytes).map_err(Error::CodingError)?;
+            Ok((height, event))
+        })
+}
+
+/// Parse the height and index of an event from the last two segments of
+/// either an event key or an index entry key.
+fn parse_event_position(key: &str) -> Result<(BlockHeight, u64)> {
+    let key = Key::parse(key).map_err(Error::KeyError)?;
+    match &key.segments[..] {
+        [.., height, index] => {
+            let height = u64::parse(height.raw()).map_err(Error::KeyError)?;
+            let index = u64::parse(index.raw()).map_err(Error::KeyError)?;
+            Ok((BlockHeight(height), index))
+        }
+        _ => Err(Error::UnknownKey {
+            key: key.to_string(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use namada_core::hash::Hash;
+    use namada_events::extend::{ComposeEvent, Height, TxHash};
+    use namada_events::{EventLevel, EventType};
+
+    use super::*;
+    use crate::mockdb::MockDB;
+
+    fn mock_event(hash: &Hash, height: BlockHeight) -> Event {
+        Event::new(EventType::new("test/applied"), EventLevel::Tx)
+            .with(TxHash(*hash))
+            .with(Height(height))
+            .into()
+    }
+
+    /// Test that the events are written, looked up by their attributes and
+    /// pruned.
+    #[test]
+    fn test_event_store_roundtrip() {
+        let db = MockDB::default();
+        let hash_1 = Hash::sha256(b"tx 1");
+        let hash_2 = Hash::sha256(b"tx 2");
+
+        let mut batch = MockDB::batch();
+        let height_1 = BlockHeight(1);
+        let events_1 = vec![mock_event(&hash_1, height_1)];
+        add_events_to_batch(&db, &mut batch, height_1, &events_1).unwrap();
+        let height_2 = BlockHeight(2);
+        let events_2 =
+            vec![mock_event(&hash_2, height_2), mock_event(&hash_1, height_2)];
+        add_events_to_batch(&db, &mut batch, height_2, &events_2).unwrap();
+        db.exec_batch(batch).unwrap();
+
+        assert_eq!(iter_events(&db).count(), 3);
+        let at_height_2: Vec<_> = iter_events_at_height(&db, height_2)
+            .map(|event| event.unwrap().1)
+            .collect();
+        assert_eq!(at_height_2, events_2);
+
+        let tx_1_events: Vec<_> =
+            iter_events_with_attribute(&db, "hash", &hash_1.to_string())
+                .map(Result::unwrap)
+                .collect();
+        assert_eq!(
+            tx_1_events,
+            vec![
+                (height_1, events_1[0].clone()),
+                (height_2, events_2[1].clone())
+            ]
+        );
+
+        let mut batch = MockDB::batch();
+        delete_events_to_batch(&db, &mut batch, height_1).unwrap();
+        db.exec_batch(batch).unwrap();
+
+        assert_eq!(iter_events(&db).count(), 2);
+        let tx_1_events: Vec<_> =
+            iter_events_with_attribute(&db, "hash", &hash_1.to_string())
+                .map(Result::unwrap)
+                .collect();
+        assert_eq!(tx_1_events, vec![(height_2, events_2[1].clone())]);
+    }
+
+    #[test]
+    fn test_event_retention() {
+        assert_eq!(
+            EventRetention::Archive.pruning_cutoff(BlockHeight(100)),
+            None
+        );
+        assert_eq!(
+            EventRetention::Blocks(10).pruning_cutoff(BlockHeight(5)),
+            None
+        );
+        assert_eq!(
+            EventRetention::Blocks(10).pruning_cutoff(BlockHeight(10)),
+            None
+        );
+        assert_eq!(
+            EventRetention::Blocks(10).pruning_cutoff(BlockHeight(11)),
+            Some(BlockHeight(2))
+        );
+    }
+
+    /// Test that all the events below the cutoff are pruned at once, e.g.
+    /// after the retention was shrunk.
+    #[test]
+    fn test_prune_events_below_cutoff() {
+        let db = MockDB::default();
+        let hash = Hash::sha256(b"tx");
+
+        let mut batch = MockDB::batch();
+        for height in 1..=20 {
+            let height = BlockHeight(height);
+            add_events_to_batch(
+                &db,
+                &mut batch,
+                height,
+                &[mock_event(&hash, height)],
+            )
+            .unwrap();
+        }
+        db.exec_batch(batch).unwrap();
+
+        let cutoff = EventRetention::Blocks(5)
+            .pruning_cutoff(BlockHeight(20))
+            .unwrap();
+        let mut batch = MockDB::batch();
+        delete_events_below_to_batch(&db, &mut batch, cutoff).unwrap();
+        db.exec_batch(batch).unwrap();
+
+        let heights: Vec<_> =
+            iter_events(&db).map(|event| event.unwrap().0.0).collect();
+        assert_eq!(heights, (16..=20).collect::<Vec<_>>());
+        let indexed: Vec<_> =
+            iter_events_with_attribute(&db, "hash", &hash.to_string())
+                .map(|event| event.unwrap().0.0)
+                .collect();
+        assert_eq!(indexed, (16..=20).collect::<Vec<_>>());
+        // No dangling index entries are left
+        let index_entries = db
+            .iter_event_entries(&attribute_index_prefix(
+                "hash",
+                &hash.to_string(),
+            ))
+            .count();
+        assert_eq!(index_entries, 5);
+    }
+}
@@ -0,0 +1,225 @@
+//! Read-only access to the committed state at a past block height.
+
+use itertools::Either;
+use namada_core::address::Address;
+use namada_core::chain::{BlockHeader, BlockHeight, ChainId, Epoch, Epochs};
+use namada_core::storage::{Key, TxIndex};
+use namada_storage::{
+    DB, DBIter, HistoricPrefixIter, KeyRange, ResultExt, StorageHasher,
+    StorageRead, iter_prefix_with_height,
+};
+
+use crate::{PrefixIter, WlState};
+
+/// Read-only access to the committed state at a given block height. Without
+/// a height, the latest state is read. The in-memory state is not historic,
+/// it's available via the dereferenced [`WlState`].
+#[derive(Debug)]
+pub struct HistoricState<'a, D, H>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter>,
+    H: 'static + StorageHasher,
+{
+    state: &'a WlState<D, H>,
+    /// A height before the last committed block, if any
+    height: Option<BlockHeight>,
+}
+
+impl<D, H> Clone for HistoricState<'_, D, H>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter>,
+    H: 'static + StorageHasher,
+{
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<D, H> Copy for HistoricState<'_, D, H>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter>,
+    H: 'static + StorageHasher,
+{
+}
+
+impl<'a, D, H> HistoricState<'a, D, H>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter>,
+    H: 'static + StorageHasher,
+{
+    /// Read the latest state
+    pub fn new(state: &'a WlState<D, H>) -> Self {
+        Self {
+            state,
+            height: None,
+        }
+    }
+
+    /// Read the state at the given height. The height `0` is the last
+    /// committed height. Returns an error if the block at the given height
+    /// hasn't been committed yet.
+    pub fn at_height(
+        self,
+        height: BlockHeight,
+    ) -> namada_storage::Result<Self> {
+        let last_height = self.state.in_mem().get_last_block_height();
+        if height > last_height {
+            return Err(namada_storage::Error::new_alloc(format!(
+                "The block at the height {height} hasn't been committed yet, \
+                 the last committed height is {last_height}",
+            )));
+        }
+        let height = (height != BlockHeight(0) && height != last_height)
+            .then_some(height);
+        Ok(Self { height, ..self })
+    }
+
+    /// Get the height of the state that is being read
+    pub fn height(&self) -> BlockHeight {
+        self.height
+            .unwrap_or_else(|| self.state.in_mem().get_last_block_height())
+    }
+
+    /// Get the epoch of the state that is being read
+    pub fn last_epoch(&self) -> Epoch {
+        self.height
+            .and_then(|height| {
+                self.state.in_mem().block.pred_epochs.get_epoch(height)
+            })
+            .unwrap_or(self.state.in_mem().last_epoch)
+    }
+}
+
+impl<D, H> std::ops::Deref for HistoricState<'_, D, H>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter>,
+    H: 'static + StorageHasher,
+{
+    type Target = WlState<D, H>;
+
+    fn deref(&self) -> &Self::Target {
+        self.state
+    }
+}
+
+impl<D, H> StorageRead for HistoricState<'_, D, H>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter>,
+    H: 'static + StorageHasher,
+{
+    type PrefixIter<'iter>
+        = Either<PrefixIter<'iter, D>, HistoricPrefixIter>
+    where
+        Self: 'iter;
+
+    fn read_bytes(&self, key: &Key) -> namada_storage::Result<Option<Vec<u8>>> {
+        match self.height {
+            Some(height) => self
+                .state
+                .db()
+                .read_subspace_val_with_height(
+                    key,
+                    height,
+                    self.state.in_mem().get_last_block_height(),
+                )
+                .into_storage_result(),
+            None => self.state.read_bytes(key),
+        }
+    }
+
+    fn has_key(&self, key: &Key) -> namada_storage::Result<bool> {
+        match self.height {
+            Some(_) => Ok(self.read_bytes(key)?.is_some()),
+            None => self.state.has_key(key),
+        }
+    }
+
+    fn iter_prefix<'iter>(
+        &'iter self,
+        prefix: &Key,
+    ) -> namada_storage::Result<Self::PrefixIter<'iter>> {
+        match self.height {
+            Some(height) => iter_prefix_with_height(
+                self.state.db(),
+                Some(prefix),
+                &KeyRange::default(),
+                height,
+                self.state.in_mem().get_last_block_height(),
+            )
+            .map(Either::Right)
+            .into_storage_result(),
+            None => self.state.iter_prefix(prefix).map(Either::Left),
+        }
+    }
+
+    fn iter_prefix_range<'iter>(
+        &'iter self,
+        prefix: &Key,
+        range: &KeyRange,
+    ) -> namada_storage::Result<Self::PrefixIter<'iter>> {
+        match self.height {
+            Some(height) => iter_prefix_with_height(
+                self.state.db(),
+                Some(prefix),
+                range,
+                height,
+                self.state.in_mem().get_last_block_height(),
+            )
+            .map(Either::Right)
+            .into_storage_result(),
+            None => self
+                .state
+                .iter_prefix_range(prefix, range)
+                .map(Either::Left),
+        }
+    }
+
+    fn iter_next<'iter>(
+        &'iter self,
+        iter: &mut Self::PrefixIter<'iter>,
+    ) -> namada_storage::Result<Option<(String, Vec<u8>)>> {
+        match iter {
+            Either::Left(iter) => self.state.iter_next(iter),
+            Either::Right(iter) => {
+                Ok(iter.next().map(|(key, val, _gas)| (key, val)))
+            }
+        }
+    }
+
+    fn get_chain_id(&self) -> namada_storage::Result<ChainId> {
+        self.state.get_chain_id()
+    }
+
+    fn get_block_height(&self) -> namada_storage::Result<BlockHeight> {
+        match self.height {
+            Some(height) => Ok(height.next_height()),
+            None => self.state.get_block_height(),
+        }
+    }
+
+    fn get_block_header(
+        &self,
+        height: BlockHeight,
+    ) -> namada_storage::Result<Option<BlockHeader>> {
+        StorageRead::get_block_header(self.state, height)
+    }
+
+    fn get_block_epoch(&self) -> namada_storage::Result<Epoch> {
+        match self.height {
+            Some(_) => Ok(self.last_epoch()),
+            None => self.state.get_block_epoch(),
+        }
+    }
+
+    fn get_pred_epochs(&self) -> namada_storage::Result<Epochs> {
+        self.state.get_pred_epochs()
+    }
+
+    fn get_tx_index(&self) -> namada_storage::Result<TxIndex> {
+        self.state.get_tx_index()
+    }
+
+    fn get_native_token(&self) -> namada_storage::Result<Address> {
+        self.state.get_native_token()
+    }
+}
@@ -1,4 +1,5 @@
-use std::num::NonZeroUsize;
+use std::num::{NonZeroU64, NonZeroUsize};
+use std::sync::Mutex;
 
 use clru::CLruCache;
 use namada_core::address::{Address, EstablishedAddressGen, InternalAddress};
@@ -10,7 +11,7 @@ use namada_core::time::DateTimeUtc;
 use namada_core::{encode, ethereum_structs};
 use namada_gas::{Gas, MEMORY_ACCESS_GAS_PER_BYTE};
 use namada_macros::BorshDeserializer;
-use namada_merkle_tree::{MerkleRoot, MerkleTree};
+use namada_merkle_tree::{MerkleRoot, MerkleTree, Store};
 #[cfg(feature = "migrations")]
 use namada_migrations::*;
 use namada_storage::conversion_state::ConversionState;
@@ -74,6 +75,9 @@ where
     pub eth_events_queue: EthEventsQueue,
     /// How many block heights in the past can the storage be queried
     pub storage_read_past_height_limit: Option<u64>,
+    /// How many of the most recent blocks can be rolled back. The data needed
+    /// to restore the state of older blocks is pruned on commit.
+    pub rollback_window_blocks: NonZeroU64,
     /// Data that needs to be committed to the merkle tree
     pub commit_only_data: CommitOnlyData,
     /// Cache of the results of process proposal for the next height to decide.
@@ -84,6 +88,30 @@ where
     /// the shim or the recheck option (comet only calls it at most once
     /// for a given height/round)
     pub block_proposals_cache: CLruCache<Hash, ProcessProposalCachedResult>,
+    /// The account subtree that was last rebuilt from the diffs for a proof.
+    /// The account subtree is only stored at the first height of each epoch,
+    /// so the next proof at the same or a later height of the epoch only
+    /// applies the diffs since this one.
+    pub account_tree_cache: Mutex<Option<RestoredSubTree>>,
+}
+
+/// A subtree rebuilt at a height
+pub struct RestoredSubTree {
+    /// The height at which the subtree was rebuilt
+    pub height: BlockHeight,
+    /// The root of the subtree
+    pub root: Hash,
+    /// The backing store of the subtree
+    pub store: Store,
+}
+
+impl std::fmt::Debug for RestoredSubTree {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RestoredSubTree")
+            .field("height", &self.height)
+            .field("root", &self.root)
+            .finish()
+    }
 }
 
 /// Last committed block
@@ -164,10 +192,12 @@ where
             ethereum_height: None,
             eth_events_queue: EthEventsQueue::default(),
             storage_read_past_height_limit,
+            rollback_window_blocks: NonZeroU64::MIN,
             commit_only_data: CommitOnlyData::default(),
             block_proposals_cache: CLruCache::new(
                 NonZeroUsize::new(10).unwrap(),
             ),
+            account_tree_cache: Mutex::new(None),
         }
     }
 
@@ -333,4 +363,13 @@ where
             .get_epoch(oldest_height)
             .unwrap_or_default()
     }
+
+    /// Get the oldest height to which the state can be rolled back, if any
+    pub fn get_oldest_rollback_height(&self) -> Option<BlockHeight> {
+        self.get_last_block_height()
+            .0
+            .checked_sub(self.rollback_window_blocks.get())
+            .filter(|height| *height > 0)
+            .map(BlockHeight)
+    }
 }
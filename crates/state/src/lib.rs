@@ -17,14 +17,18 @@
     clippy::print_stderr
 )]
 
+pub mod event_store;
+mod historic;
 mod in_memory;
 pub mod prefix_iter;
+pub mod verify;
 mod wl_state;
 pub mod write_log;
 
 use std::fmt::Debug;
 use std::iter::Peekable;
 
+pub use historic::HistoricState;
 pub use in_memory::{
     BlockStorage, InMemory, LastBlock, ProcessProposalCachedResult,
 };
@@ -54,17 +58,23 @@ pub use namada_storage as storage;
 pub use namada_storage::conversion_state::{
     ConversionLeaf, ConversionState, ReadConversionState, WithConversionState,
 };
-pub use namada_storage::types::{KVBytes, PatternIterator, PrefixIterator};
+pub use namada_storage::types::{
+    KVBytes, KeyRange, PatternIterator, PrefixIterator,
+};
 pub use namada_storage::{
     BlockStateRead, BlockStateWrite, DB, DBIter, DBWriteBatch, DbError,
     DbResult, Error, OptionExt, Result, ResultExt, StorageHasher, StorageRead,
-    StorageWrite, collections, iter_prefix, iter_prefix_bytes,
-    iter_prefix_with_filter, iter_prefix_with_filter_map, mockdb, tx_queue,
+    StorageWrite, SubspaceChange, collections, iter_prefix, iter_prefix_bytes,
+    iter_prefix_range, iter_prefix_range_bytes,
+    iter_prefix_range_with_filter_map, iter_prefix_with_filter,
+    iter_prefix_with_filter_map, mockdb, tx_queue,
 };
 use namada_systems::parameters;
 use thiserror::Error;
 use wl_state::TxWlState;
-pub use wl_state::{FullAccessState, TempWlState, WlState};
+pub use wl_state::{
+    FullAccessState, TempWlState, WlState, restore_merkle_tree,
+};
 use write_log::WriteLog;
 
 /// We delay epoch change 2 blocks to keep it in sync with Tendermint, because
@@ -206,6 +216,28 @@ pub trait State: StateRead + StorageWrite {
     fn write_tx_hash(&mut self, hash: Hash) -> write_log::Result<()> {
         self.write_log_mut().write_tx_hash(hash)
     }
+
+    /// Attempt an operation within a savepoint of the current transaction's
+    /// write log. The changes made by the operation are kept if it succeeds
+    /// and undone if it fails, in which case its error is returned and the
+    /// transaction can carry on.
+    fn with_savepoint<T, F>(&mut self, f: F) -> Result<T>
+    where
+        F: FnOnce(&mut Self) -> Result<T>,
+    {
+        let gas = self.write_log_mut().push_savepoint()?;
+        self.charge_gas(gas)?;
+        match f(self) {
+            Ok(res) => {
+                self.write_log_mut().release_savepoint()?;
+                Ok(res)
+            }
+            Err(err) => {
+                self.write_log_mut().rollback_to_savepoint()?;
+                Err(err)
+            }
+        }
+    }
 }
 
 /// Perform storage writes and deletions to write-log at tx level.
@@ -280,6 +312,21 @@ macro_rules! impl_storage_read {
                 Ok(iter)
             }
 
+            fn iter_prefix_range<'iter>(
+                &'iter self,
+                prefix: &storage::Key,
+                range: &$crate::KeyRange,
+            ) -> namada_storage::Result<Self::PrefixIter<'iter>> {
+                let (iter, gas) = $crate::iter_prefix_range_post(
+                    self.write_log(),
+                    self.db(),
+                    prefix,
+                    range,
+                )?;
+                self.charge_gas(gas).into_storage_result()?;
+                Ok(iter)
+            }
+
             fn iter_next<'iter>(
                 &'iter self,
                 iter: &mut Self::PrefixIter<'iter>,
@@ -451,6 +498,8 @@ where
     pub storage_iter: Peekable<<D as DBIter<'iter>>::PrefixIter>,
     /// Peekable write log iterator
     pub write_log_iter: Peekable<write_log::PrefixIter>,
+    /// Iterate in descending order of the keys
+    pub rev: bool,
 }
 
 /// Iterate write-log storage items prior to a tx execution, matching the
@@ -473,6 +522,7 @@ where
         PrefixIter::<D> {
             storage_iter,
             write_log_iter,
+            rev: false,
         },
         checked!(len * STORAGE_ACCESS_GAS_PER_BYTE)?.into(),
     ))
@@ -498,6 +548,59 @@ where
         PrefixIter::<D> {
             storage_iter,
             write_log_iter,
+            rev: false,
+        },
+        checked!(len * STORAGE_ACCESS_GAS_PER_BYTE)?.into(),
+    ))
+}
+
+/// Iterate write-log storage items prior to a tx execution, matching the
+/// given prefix and within the given range, in the range's direction. Returns
+/// the iterator and gas cost.
+pub fn iter_prefix_range_pre<'a, D>(
+    write_log: &'a WriteLog,
+    db: &'a D,
+    prefix: &storage::Key,
+    range: &KeyRange,
+) -> namada_storage::Result<(PrefixIter<'a, D>, Gas)>
+where
+    D: DB + for<'iter> DBIter<'iter>,
+{
+    let storage_iter = db.iter_prefix_range(Some(prefix), range).peekable();
+    let write_log_iter =
+        write_log.iter_prefix_range_pre(prefix, range).peekable();
+    let len = prefix.len() as u64;
+    Ok((
+        PrefixIter::<D> {
+            storage_iter,
+            write_log_iter,
+            rev: range.rev,
+        },
+        checked!(len * STORAGE_ACCESS_GAS_PER_BYTE)?.into(),
+    ))
+}
+
+/// Iterate write-log storage items posterior to a tx execution, matching the
+/// given prefix and within the given range, in the range's direction. Returns
+/// the iterator and gas cost.
+pub fn iter_prefix_range_post<'a, D>(
+    write_log: &'a WriteLog,
+    db: &'a D,
+    prefix: &storage::Key,
+    range: &KeyRange,
+) -> namada_storage::Result<(PrefixIter<'a, D>, Gas)>
+where
+    D: DB + for<'iter> DBIter<'iter>,
+{
+    let storage_iter = db.iter_prefix_range(Some(prefix), range).peekable();
+    let write_log_iter =
+        write_log.iter_prefix_range_post(prefix, range).peekable();
+    let len = prefix.len() as u64;
+    Ok((
+        PrefixIter::<D> {
+            storage_iter,
+            write_log_iter,
+            rev: range.rev,
         },
         checked!(len * STORAGE_ACCESS_GAS_PER_BYTE)?.into(),
     ))
@@ -530,7 +633,14 @@ where
                         what = Next::ReturnStorage;
                     }
                     (Some((storage_key, _, _)), Some((wl_key, _))) => {
-                        if wl_key <= storage_key {
+                        // The next key is the lower one, or the higher one
+                        // when iterating in descending order
+                        let wl_is_next = if self.rev {
+                            wl_key >= storage_key
+                        } else {
+                            wl_key <= storage_key
+                        };
+                        if wl_is_next {
                             what = Next::ReturnWl {
                                 advance_storage: wl_key == storage_key,
                             };
@@ -584,7 +694,7 @@ where
 #[cfg(any(test, feature = "testing"))]
 pub mod testing {
 
-    use std::num::NonZeroUsize;
+    use std::num::{NonZeroU64, NonZeroUsize};
 
     use clru::CLruCache;
     use namada_core::address;
@@ -649,6 +759,7 @@ pub mod testing {
                 ethereum_height: None,
                 eth_events_queue: EthEventsQueue::default(),
                 storage_read_past_height_limit: Some(1000),
+                rollback_window_blocks: NonZeroU64::MIN,
                 commit_only_data: CommitOnlyData::default(),
                 block_proposals_cache: CLruCache::new(
                     NonZeroUsize::new(10).unwrap(),
@@ -756,6 +867,7 @@ mod tests {
                 gas_scale: 10_000_000,
                 minimum_gas_price: BTreeMap::default(),
                 is_native_token_transferable: true,
+                fee_market: None,
             };
             // Initialize pred_epochs to the current height
             let height = state.in_mem().block.height;
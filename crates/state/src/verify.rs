@@ -0,0 +1,320 @@
+//! Offline verification of the integrity of the state persisted in a DB.
+//!
+//! The Merkle tree of the last committed block is rebuilt from scratch from
+//! the subspace key-vals and its sub-roots and base root are compared with the
+//! ones committed in the DB. The base root committed for the last block is the
+//! app hash that was returned to CometBFT. The keys whose diffs are not
+//! persisted are stored in the tree under the [`NO_DIFF_KEY_PREFIX`], so the
+//! verification needs the same diff key filter as the one used by the node.
+
+use namada_core::arith::checked;
+use namada_core::borsh::BorshSerializeExt;
+use namada_core::chain::{BlockHeight, Epochs};
+use namada_core::storage::{Key, KeySeg};
+use namada_merkle_tree::{MerkleRoot, NO_DIFF_KEY_PREFIX};
+
+use crate::wl_state::restore_merkle_tree;
+use crate::{
+    DB, DBIter, Error, MerkleTree, Result, ResultExt, StateError,
+    StorageHasher, StoreType, is_pending_transfer_key,
+};
+
+/// The result of the verification of a DB's Merkle tree against its subspace
+#[derive(Debug)]
+pub struct MerkleTreeVerification {
+    /// The last committed height
+    pub height: BlockHeight,
+    /// The number of key-vals found in the subspace
+    pub num_keys: u64,
+    /// The root committed for the last block, i.e. its app hash
+    pub committed_root: MerkleRoot,
+    /// The root of the tree rebuilt from the subspace
+    pub rebuilt_root: MerkleRoot,
+    /// The first store whose rebuilt root doesn't match the committed one, if
+    /// any
+    pub mismatch: Option<StoreMismatch>,
+}
+
+impl MerkleTreeVerification {
+    /// Check if the committed tree matches the subspace
+    pub fn is_valid(&self) -> bool {
+        self.mismatch.is_none()
+    }
+}
+
+/// A Merkle tree store whose rebuilt root doesn't match the committed one
+#[derive(Debug)]
+pub struct StoreMismatch {
+    /// The mismatching store
+    pub store_type: StoreType,
+    /// The committed root of the store
+    pub committed_root: MerkleRoot,
+    /// The root of the store rebuilt from the subspace
+    pub rebuilt_root: MerkleRoot,
+    /// The inclusive ranges of consecutive subspace keys whose committed
+    /// values differ from the subspace. This is `None` when the committed
+    /// store can't be restored key by key.
+    pub key_ranges: Option<Vec<(Key, Key)>>,
+}
+
+/// Rebuild the Merkle tree of the last committed block from the subspace and
+/// compare it with the committed one. The `diff_key_filter` must be the one
+/// the node's state was built with.
+pub fn verify_merkle_tree<D, H>(
+    db: &D,
+    diff_key_filter: fn(&Key) -> bool,
+) -> Result<MerkleTreeVerification>
+where
+    D: DB + for<'iter> DBIter<'iter>,
+    H: StorageHasher,
+{
+    let last_block = db.read_last_block()?.ok_or_else(|| {
+        Error::new_const("No committed block found in the DB")
+    })?;
+    let height = last_block.height;
+
+    // The base tree committed for the last block
+    let stores = db
+        .read_merkle_tree_stores(
+            last_block.epoch,
+            height,
+            Some(StoreType::Base),
+        )?
+        .ok_or(StateError::NoMerkleTree { height })?;
+    let committed_root = MerkleRoot::from(stores.get_root(StoreType::Base));
+    let committed = MerkleTree::<H>::new_partial(stores);
+
+    // The heights at which the pending bridge pool transfers were added are
+    // not in the subspace, so they're taken from the committed tree
+    let bridge_pool = restore_merkle_tree::<D, H>(
+        db,
+        &last_block.pred_epochs,
+        height,
+        Some(StoreType::BridgePool),
+    )?;
+
+    let mut rebuilt = MerkleTree::<H>::default();
+    let mut num_keys = 0_u64;
+    for (key, value, _gas) in db.iter_prefix(None) {
+        let key = Key::parse(key).into_storage_result()?;
+        let value = if is_pending_transfer_key(&key) {
+            bridge_pool
+                .get(&key)
+                .unwrap_or_else(|_| height.serialize_to_vec())
+        } else {
+            value
+        };
+        rebuilt.update(&tree_key(&key, diff_key_filter), value)?;
+        num_keys = checked!(num_keys + 1)?;
+    }
+    rebuilt.update_commit_data(last_block.commit_only_data.serialize())?;
+
+    let mut mismatch = None;
+    for st in StoreType::iter_subtrees() {
+        let committed_sub_root = committed.committed_sub_root(st)?;
+        let rebuilt_sub_root = rebuilt.sub_root(st);
+        if committed_sub_root != rebuilt_sub_root {
+            let key_ranges = mismatching_key_ranges::<D, H>(
+                db,
+                &last_block.pred_epochs,
+                height,
+                st,
+                &rebuilt,
+                diff_key_filter,
+            );
+            mismatch = Some(StoreMismatch {
+                store_type: *st,
+                committed_root: committed_sub_root,
+                rebuilt_root: rebuilt_sub_root,
+                key_ranges,
+            });
+            break;
+        }
+    }
+    let rebuilt_root = rebuilt.root();
+    if mismatch.is_none() && committed_root != rebuilt_root {
+        // The sub-roots match, but the base tree itself is corrupted
+        mismatch = Some(StoreMismatch {
+            store_type: StoreType::Base,
+            committed_root: MerkleRoot(committed_root.0),
+            rebuilt_root: MerkleRoot(rebuilt_root.0),
+            key_ranges: None,
+        });
+    }
+
+    Ok(MerkleTreeVerification {
+        height,
+        num_keys,
+        committed_root,
+        rebuilt_root,
+        mismatch,
+    })
+}
+
+/// The key of the given subspace key in the Merkle tree
+fn tree_key(key: &Key, diff_key_filter: fn(&Key) -> bool) -> Key {
+    if diff_key_filter(key) {
+        key.clone()
+    } else {
+        Key::from(NO_DIFF_KEY_PREFIX.to_string().to_db_key()).join(key)
+    }
+}
+
+/// Find the ranges of consecutive subspace keys of the given store whose
+/// values in the committed tree differ from the rebuilt tree. Returns `None`
+/// if the committed store can't be restored.
+fn mismatching_key_ranges<D, H>(
+    db: &D,
+    pred_epochs: &Epochs,
+    height: BlockHeight,
+    store_type: &StoreType,
+    rebuilt: &MerkleTree<H>,
+    diff_key_filter: fn(&Key) -> bool,
+) -> Option<Vec<(Key, Key)>>
+where
+    D: DB + for<'iter> DBIter<'iter>,
+    H: StorageHasher,
+{
+    if *store_type == StoreType::CommitData {
+        return None;
+    }
+    let committed =
+        restore_merkle_tree::<D, H>(db, pred_epochs, height, Some(*store_type))
+            .ok()?;
+
+    let mut ranges = vec![];
+    let mut current_range: Option<(Key, Key)> = None;
+    for (key, _value, _gas) in db.iter_prefix(None) {
+        let key = Key::parse(key).ok()?;
+        let tree_key = tree_key(&key, diff_key_filter);
+        match StoreType::sub_key(&tree_key) {
+            Ok((st, _)) if st == *store_type => {}
+            _ => continue,
+        }
+        if committed.get(&tree_key).ok() != rebuilt.get(&tree_key).ok() {
+            match current_range.as_mut() {
+                Some((_first, last)) => *last = key,
+                None => current_range = Some((key.clone(), key)),
+            }
+        } else if let Some(range) = current_range.take() {
+            ranges.push(range);
+        }
+    }
+    ranges.extend(current_range);
+    Some(ranges)
+}
+
+#[cfg(test)]
+mod tests {
+    use namada_storage::StorageWrite;
+
+    use super::*;
+    use crate::Sha256Hasher;
+    use crate::mockdb::MockDB;
+    use crate::testing::TestState;
+
+    fn diff_all_keys(_key: &Key) -> bool {
+        true
+    }
+
+    #[test]
+    fn test_verify_merkle_tree() {
+        let mut state = TestState::default();
+        state
+            .in_mem_mut()
+            .begin_block(BlockHeight::first())
+            .unwrap();
+        let keys = ["a", "b", "c"].map(|key| Key::parse(key).unwrap());
+        for (key, value) in keys.iter().zip(1_u64..) {
+            state.write(key, value).unwrap();
+        }
+        state.commit_block().unwrap();
+
+        let verification =
+            verify_merkle_tree::<_, Sha256Hasher>(state.db(), diff_all_keys)
+                .unwrap();
+        assert!(verification.is_valid());
+        assert_eq!(verification.height, BlockHeight::first());
+        assert_eq!(verification.committed_root, verification.rebuilt_root);
+        assert_eq!(verification.committed_root, state.in_mem().merkle_root());
+
+        // Corrupt the value of a key directly in the DB
+        let db = state.db_mut();
+        let mut batch = MockDB::batch();
+        db.batch_write_subspace_val(
+            &mut batch,
+            BlockHeight::first(),
+            &keys[1],
+            99_u64.serialize_to_vec(),
+            false,
+        )
+        .unwrap();
+        db.exec_batch(batch).unwrap();
+
+        let verification =
+            verify_merkle_tree::<_, Sha256Hasher>(state.db(), diff_all_keys)
+                .unwrap();
+        assert!(!verification.is_valid());
+        assert_ne!(verification.committed_root, verification.rebuilt_root);
+        let mismatch = verification.mismatch.unwrap();
+        assert_eq!(mismatch.store_type, StoreType::Account);
+        assert_eq!(
+            mismatch.key_ranges,
+            Some(vec![(keys[1].clone(), keys[1].clone())])
+        );
+    }
+
+    #[test]
+    fn test_verify_merkle_tree_no_diff_keys() {
+        fn diff_key_filter(key: &Key) -> bool {
+            key.segments[0].raw() != "no_diff_key"
+        }
+
+        let mut state = TestState::default();
+        state.0.diff_key_filter = diff_key_filter;
+        state
+            .in_mem_mut()
+            .begin_block(BlockHeight::first())
+            .unwrap();
+        let diff_key = Key::parse("diff_key").unwrap();
+        let no_diff_key = Key::parse("no_diff_key").unwrap();
+        state.write(&diff_key, 1_u64).unwrap();
+        state.write(&no_diff_key, 2_u64).unwrap();
+        state.commit_block().unwrap();
+
+        let verification =
+            verify_merkle_tree::<_, Sha256Hasher>(state.db(), diff_key_filter)
+                .unwrap();
+        assert!(verification.is_valid());
+        assert_eq!(verification.committed_root, state.in_mem().merkle_root());
+
+        // Without the filter, the no-diff key is misplaced in the tree
+        let verification =
+            verify_merkle_tree::<_, Sha256Hasher>(state.db(), diff_all_keys)
+                .unwrap();
+        assert!(!verification.is_valid());
+
+        // Corrupt the value of the no-diff key directly in the DB
+        let db = state.db_mut();
+        let mut batch = MockDB::batch();
+        db.batch_write_subspace_val(
+            &mut batch,
+            BlockHeight::first(),
+            &no_diff_key,
+            99_u64.serialize_to_vec(),
+            false,
+        )
+        .unwrap();
+        db.exec_batch(batch).unwrap();
+
+        let verification =
+            verify_merkle_tree::<_, Sha256Hasher>(state.db(), diff_key_filter)
+                .unwrap();
+        let mismatch = verification.mismatch.unwrap();
+        assert_eq!(mismatch.store_type, StoreType::NoDiff);
+        assert_eq!(
+            mismatch.key_ranges,
+            Some(vec![(no_diff_key.clone(), no_diff_key)])
+        );
+    }
+}
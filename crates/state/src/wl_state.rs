@@ -5,12 +5,12 @@ use itertools::Either;
 use namada_core::address::Address;
 use namada_core::arith::checked;
 use namada_core::borsh::BorshSerializeExt;
-use namada_core::chain::ChainId;
+use namada_core::chain::{ChainId, Epochs};
 use namada_core::masp::MaspEpoch;
 use namada_core::parameters::{EpochDuration, Parameters};
 use namada_core::time::DateTimeUtc;
 use namada_core::{decode, storage};
-use namada_events::{EmitEvents, EventToEmit};
+use namada_events::{EmitEvents, Event, EventToEmit};
 use namada_gas::Gas;
 use namada_merkle_tree::NO_DIFF_KEY_PREFIX;
 use namada_replay_protection as replay_protection;
@@ -21,13 +21,15 @@ use namada_storage::{
     BlockHeight, BlockStateRead, BlockStateWrite, ResultExt, StorageRead,
 };
 
-use crate::in_memory::InMemory;
+use crate::event_store::{self, EventRetention};
+use crate::in_memory::{InMemory, RestoredSubTree};
 use crate::write_log::{StorageModification, WriteLog};
 use crate::{
     DB, DBIter, EPOCH_SWITCH_BLOCKS_DELAY, Epoch, Error, Hash, Key, KeySeg,
-    LastBlock, MembershipProof, MerkleTree, MerkleTreeError, ProofOps, Result,
-    STORAGE_ACCESS_GAS_PER_BYTE, State, StateError, StateRead, StorageHasher,
-    StoreType, TxWrites, is_pending_transfer_key,
+    LastBlock, MembershipProof, MerkleTree, MerkleTreeError,
+    MerkleTreeStoresRead, ProofOps, Result, STORAGE_ACCESS_GAS_PER_BYTE, State,
+    StateError, StateRead, StorageHasher, StoreType, TxWrites,
+    is_pending_transfer_key,
 };
 
 /// Owned state with full R/W access.
@@ -282,6 +284,18 @@ where
     /// Commit the current block's write log to the storage and commit the block
     /// to DB. Starts a new block write log.
     pub fn commit_block(&mut self) -> Result<()> {
+        self.commit_block_with_events(&[], EventRetention::Archive)
+    }
+
+    /// Commit the current block's write log to the storage and commit the block
+    /// to DB together with the events emitted in it. The events of the block
+    /// falling out of the given `retention` are pruned. Starts a new block
+    /// write log.
+    pub fn commit_block_with_events(
+        &mut self,
+        events: &[Event],
+        retention: EventRetention,
+    ) -> Result<()> {
         if self.in_mem.last_epoch != self.in_mem.block.epoch {
             self.in_mem_mut()
                 .update_epoch_in_merkle_tree()
@@ -291,6 +305,16 @@ where
         let mut batch = D::batch();
         self.commit_write_log_block(&mut batch)
             .into_storage_result()?;
+
+        let height = self.in_mem.block.height;
+        event_store::add_events_to_batch(&self.db, &mut batch, height, events)
+            .into_storage_result()?;
+        if let Some(cutoff) = retention.pruning_cutoff(height) {
+            event_store::delete_events_below_to_batch(
+                &self.db, &mut batch, cutoff,
+            )
+            .into_storage_result()?;
+        }
         self.commit_block_from_batch(batch).into_storage_result()?;
 
         // Clear the tx gas map
@@ -429,12 +453,12 @@ where
         is_full_commit: bool,
         batch: &mut D::WriteBatch,
     ) -> Result<()> {
-        if let Some(prev_height) = self
-            .in_mem
-            .block
-            .height
-            .prev_height()
-            .and_then(|h| h.prev_height())
+        // The oldest height to which the state can be rolled back
+        let oldest_rollback_height = self.in_mem.get_oldest_rollback_height();
+        // Keep the stores stored every block from the predecessor of the
+        // oldest rollback height
+        if let Some(prev_height) =
+            oldest_rollback_height.and_then(|h| h.prev_height())
         {
             for st in StoreType::iter().filter(|st| st.is_stored_every_block())
             {
@@ -449,12 +473,12 @@ where
             }
         }
 
-        if !is_full_commit {
-            return Ok(());
-        }
-
-        // Prune non-provable stores at the previous epoch
-        if let Some(prev_epoch) = self.in_mem.block.epoch.prev() {
+        // Prune non-provable stores at the epoch preceding the one of the
+        // oldest rollback height
+        if let Some(prev_epoch) = oldest_rollback_height
+            .and_then(|h| self.in_mem.block.pred_epochs.get_epoch(h))
+            .and_then(|epoch| epoch.prev())
+        {
             for st in StoreType::iter_non_provable() {
                 self.0.db.prune_merkle_tree_store(
                     batch,
@@ -463,6 +487,11 @@ where
                 )?;
             }
         }
+
+        if !is_full_commit {
+            return Ok(());
+        }
+
         // Prune provable stores
         let oldest_epoch = self.in_mem.get_oldest_epoch();
         if oldest_epoch.0 > 0 {
@@ -704,9 +733,17 @@ where
         self.in_mem.last_epoch = self.in_mem.block.epoch;
         // prune old merkle tree stores
         self.prune_merkle_tree_stores(is_full_commit, &mut batch)?;
-        // If there's a previous block, prune non-persisted diffs from it
-        if let Some(height) = self.in_mem.block.height.prev_height() {
+        // Prune the non-persisted diffs and the rollback journal that are no
+        // longer needed to roll back to the oldest rollback height
+        if let Some(height) = self.in_mem.get_oldest_rollback_height() {
             self.db.prune_non_persisted_diffs(&mut batch, height)?;
+            let epoch = self
+                .in_mem
+                .block
+                .pred_epochs
+                .get_epoch(height)
+                .unwrap_or_default();
+            self.db.prune_rollback_journal(&mut batch, height, epoch)?;
         }
         self.db.exec_batch(batch)?;
         Ok(())
@@ -793,6 +830,27 @@ where
         }
     }
 
+    /// Borrow in-memory state and DB handle with a speculative write-log on
+    /// top of the given block snapshot, see [`WriteLog::new_speculative`].
+    ///
+    /// The lifetime of borrows is unsafely extended to `'static` for the same
+    /// reason as in [`WlState::with_static_temp_write_log`].
+    ///
+    /// # Safety
+    ///
+    /// The caller must guarantee that the source `WlState` is not being
+    /// accessed mutably before `TempWlState` gets dropped.
+    pub unsafe fn with_static_speculative_write_log(
+        &self,
+        snapshot: &crate::write_log::BlockSnapshot,
+    ) -> TempWlState<'static, D, H> {
+        TempWlState {
+            write_log: WriteLog::new_speculative(snapshot),
+            db: unsafe { &*(&self.db as *const _) },
+            in_mem: unsafe { &*(&self.in_mem as *const _) },
+        }
+    }
+
     /// Commit the current transaction's write log and the entire batch to the
     /// block. Starts a new transaction and batch write log.
     pub fn commit_tx_batch(&mut self) {
@@ -1054,152 +1112,62 @@ where
             height
         };
 
-        let epoch = self
-            .in_mem
-            .block
-            .pred_epochs
-            .get_epoch(height)
-            .unwrap_or_default();
-        let start_height = match store_type {
-            // subtree is stored every height
-            Some(st) if st.is_stored_every_block() => height,
-            // others are stored at the first height of each epoch
-            _ => match self
-                .in_mem
-                .block
-                .pred_epochs
-                .get_start_height_of_epoch(epoch)
-            {
-                Some(BlockHeight(0)) => BlockHeight(1),
-                Some(height) => height,
-                None => BlockHeight(1),
-            },
-        };
-        // Try to read all subtrees, but some subtrees would be stale or empty.
-        // They will be rebuild later. That's why the tree isn't validated here.
-        let stores = self
-            .db
-            .read_merkle_tree_stores(epoch, start_height, store_type)?
-            .ok_or(StateError::NoMerkleTree { height })?;
-        let mut tree = MerkleTree::<H>::new_partial(stores);
-        let prefix = store_type.and_then(|st| st.provable_prefix());
-        // Restore the tree state with diffs
-        let mut target_height = start_height;
-        while target_height < height {
-            target_height = target_height.next_height();
-            let mut old_diff_iter =
-                self.db.iter_old_diffs(target_height, prefix.as_ref());
-            let mut new_diff_iter =
-                self.db.iter_new_diffs(target_height, prefix.as_ref());
-
-            let mut old_diff = old_diff_iter.next();
-            let mut new_diff = new_diff_iter.next();
-            loop {
-                match (&old_diff, &new_diff) {
-                    (Some(old), Some(new)) => {
-                        let old_key = Key::parse(old.0.clone())
-                            .expect("the key should be parsable");
-                        let new_key = Key::parse(new.0.clone())
-                            .expect("the key should be parsable");
-
-                        // compare keys as String
-                        match old.0.cmp(&new.0) {
-                            Ordering::Equal => {
-                                // the value was updated
-                                tree.update(
-                                    &new_key,
-                                    if is_pending_transfer_key(&new_key) {
-                                        target_height.serialize_to_vec()
-                                    } else {
-                                        new.1.clone()
-                                    },
-                                )?;
-                                old_diff = old_diff_iter.next();
-                                new_diff = new_diff_iter.next();
-                            }
-                            Ordering::Less => {
-                                // the value was deleted
-                                tree.delete(&old_key)?;
-                                old_diff = old_diff_iter.next();
-                            }
-                            Ordering::Greater => {
-                                // the value was inserted
-                                tree.update(
-                                    &new_key,
-                                    if is_pending_transfer_key(&new_key) {
-                                        target_height.serialize_to_vec()
-                                    } else {
-                                        new.1.clone()
-                                    },
-                                )?;
-                                new_diff = new_diff_iter.next();
-                            }
-                        }
-                    }
-                    (Some(old), None) => {
-                        // the value was deleted
-                        let key = Key::parse(old.0.clone())
-                            .expect("the key should be parsable");
-                        tree.delete(&key)?;
-
-                        old_diff = old_diff_iter.next();
-                    }
-                    (None, Some(new)) => {
-                        // the value was inserted
-                        let key = Key::parse(new.0.clone())
-                            .expect("the key should be parsable");
-
-                        tree.update(
-                            &key,
-                            if is_pending_transfer_key(&key) {
-                                target_height.serialize_to_vec()
-                            } else {
-                                new.1.clone()
-                            },
-                        )?;
-
-                        new_diff = new_diff_iter.next();
-                    }
-                    (None, None) => break,
-                }
-            }
+        if store_type == Some(StoreType::Account) {
+            return self.get_account_merkle_tree(height);
         }
 
-        // Restore the base tree and subtrees
-        match store_type {
-            Some(st) => {
-                // It is enough to get the base tree
-                let mut stores = self
-                    .db
-                    .read_merkle_tree_stores(
-                        epoch,
-                        height,
-                        Some(StoreType::Base),
-                    )?
-                    .ok_or(StateError::NoMerkleTree { height })?;
-                let restored_stores = tree.stores();
-                stores.set_root(&st, *restored_stores.root(&st));
-                stores.set_store(restored_stores.store(&st).to_owned());
-                tree = MerkleTree::<H>::new_partial(stores);
-            }
-            None => {
-                // Get the base and subtrees stored in every block
-                let mut stores = self
-                    .db
-                    .read_merkle_tree_stores(epoch, height, None)?
-                    .ok_or(StateError::NoMerkleTree { height })?;
-                let restored_stores = tree.stores();
-                // Set all rebuilt subtrees except for the subtrees stored in
-                // every block
-                for st in StoreType::iter_subtrees() {
-                    if !st.is_stored_every_block() {
-                        stores.set_root(st, *restored_stores.root(st));
-                        stores.set_store(restored_stores.store(st).to_owned());
-                    }
-                }
-                tree = MerkleTree::<H>::new(stores)?;
+        restore_merkle_tree(
+            &self.db,
+            &self.in_mem.block.pred_epochs,
+            height,
+            store_type,
+        )
+    }
+
+    /// Rebuild Merkle tree with the account subtree at the given height. The
+    /// rebuilt account subtree is cached, so that the next one at the same or
+    /// a later height of the same epoch is rebuilt from it instead of from
+    /// the store at the first height of the epoch.
+    fn get_account_merkle_tree(
+        &self,
+        height: BlockHeight,
+    ) -> Result<MerkleTree<H>> {
+        let pred_epochs = &self.in_mem.block.pred_epochs;
+        let epoch = pred_epochs.get_epoch(height).unwrap_or_default();
+        let mut cache = self.in_mem.account_tree_cache.lock().unwrap();
+        let tree = match cache.take() {
+            Some(cached)
+                if cached.height <= height
+                    && pred_epochs
+                        .get_epoch(cached.height)
+                        .unwrap_or_default()
+                        == epoch =>
+            {
+                let mut stores = MerkleTreeStoresRead::default();
+                stores.set_root(&StoreType::Account, cached.root);
+                stores.set_store(cached.store);
+                rebuild_merkle_tree(
+                    &self.db,
+                    epoch,
+                    stores,
+                    cached.height,
+                    height,
+                    Some(StoreType::Account),
+                )?
             }
-        }
+            _ => restore_merkle_tree(
+                &self.db,
+                pred_epochs,
+                height,
+                Some(StoreType::Account),
+            )?,
+        };
+        let stores = tree.stores();
+        *cache = Some(RestoredSubTree {
+            height,
+            root: *stores.root(&StoreType::Account),
+            store: stores.store(&StoreType::Account).to_owned(),
+        });
         Ok(tree)
     }
 
@@ -1241,6 +1209,11 @@ where
         &mut self.write_log
     }
 
+    /// Drop the borrows and take the write-log
+    pub fn into_write_log(self) -> WriteLog {
+        self.write_log
+    }
+
     /// Check if the given tx hash has already been processed
     pub fn has_replay_protection_entry(&self, hash: &Hash) -> Result<bool> {
         if self.write_log.has_replay_protection_entry(hash) {
@@ -1629,3 +1602,167 @@ where
         }
     }
 }
+
+/// Rebuild Merkle tree at the given height with the stores and the diffs in
+/// the DB. Base tree and the specified `store_type` subtree is rebuilt. If
+/// `store_type` isn't given, full Merkle tree is restored.
+pub fn restore_merkle_tree<D, H>(
+    db: &D,
+    pred_epochs: &Epochs,
+    height: BlockHeight,
+    store_type: Option<StoreType>,
+) -> Result<MerkleTree<H>>
+where
+    D: DB + for<'iter> DBIter<'iter>,
+    H: StorageHasher,
+{
+    let epoch = pred_epochs.get_epoch(height).unwrap_or_default();
+    let start_height = match store_type {
+        // subtree is stored every height
+        Some(st) if st.is_stored_every_block() => height,
+        // others are stored at the first height of each epoch
+        _ => match pred_epochs.get_start_height_of_epoch(epoch) {
+            Some(BlockHeight(0)) => BlockHeight(1),
+            Some(height) => height,
+            None => BlockHeight(1),
+        },
+    };
+    // Try to read all subtrees, but some subtrees would be stale or empty.
+    // They will be rebuild later. That's why the tree isn't validated here.
+    let stores = db
+        .read_merkle_tree_stores(epoch, start_height, store_type)?
+        .ok_or(StateError::NoMerkleTree { height })?;
+    rebuild_merkle_tree(db, epoch, stores, start_height, height, store_type)
+}
+
+/// Rebuild Merkle tree at the given height from the stores at the start height
+/// of the same epoch by applying the diffs of the heights in between. Base
+/// tree and the specified `store_type` subtree is rebuilt. If `store_type`
+/// isn't given, full Merkle tree is restored.
+fn rebuild_merkle_tree<D, H>(
+    db: &D,
+    epoch: Epoch,
+    stores: MerkleTreeStoresRead,
+    start_height: BlockHeight,
+    height: BlockHeight,
+    store_type: Option<StoreType>,
+) -> Result<MerkleTree<H>>
+where
+    D: DB + for<'iter> DBIter<'iter>,
+    H: StorageHasher,
+{
+    let mut tree = MerkleTree::<H>::new_partial(stores);
+    let prefix = store_type.and_then(|st| st.provable_prefix());
+    // Restore the tree state with diffs
+    let mut target_height = start_height;
+    while target_height < height {
+        target_height = target_height.next_height();
+        let mut old_diff_iter =
+            db.iter_old_diffs(target_height, prefix.as_ref());
+        let mut new_diff_iter =
+            db.iter_new_diffs(target_height, prefix.as_ref());
+
+        let mut old_diff = old_diff_iter.next();
+        let mut new_diff = new_diff_iter.next();
+        loop {
+            match (&old_diff, &new_diff) {
+                (Some(old), Some(new)) => {
+                    let old_key = Key::parse(old.0.clone())
+                        .expect("the key should be parsable");
+                    let new_key = Key::parse(new.0.clone())
+                        .expect("the key should be parsable");
+
+                    // compare keys as String
+                    match old.0.cmp(&new.0) {
+                        Ordering::Equal => {
+                            // the value was updated
+                            tree.update(
+                                &new_key,
+                                if is_pending_transfer_key(&new_key) {
+                                    target_height.serialize_to_vec()
+                                } else {
+                                    new.1.clone()
+                                },
+                            )?;
+                            old_diff = old_diff_iter.next();
+                            new_diff = new_diff_iter.next();
+                        }
+                        Ordering::Less => {
+                            // the value was deleted
+                            tree.delete(&old_key)?;
+                            old_diff = old_diff_iter.next();
+                        }
+                        Ordering::Greater => {
+                            // the value was inserted
+                            tree.update(
+                                &new_key,
+                                if is_pending_transfer_key(&new_key) {
+                                    target_height.serialize_to_vec()
+                                } else {
+                                    new.1.clone()
+                                },
+                            )?;
+                            new_diff = new_diff_iter.next();
+                        }
+                    }
+                }
+                (Some(old), None) => {
+                    // the value was deleted
+                    let key = Key::parse(old.0.clone())
+                        .expect("the key should be parsable");
+                    tree.delete(&key)?;
+
+                    old_diff = old_diff_iter.next();
+                }
+                (None, Some(new)) => {
+                    // the value was inserted
+                    let key = Key::parse(new.0.clone())
+                        .expect("the key should be parsable");
+
+                    tree.update(
+                        &key,
+                        if is_pending_transfer_key(&key) {
+                            target_height.serialize_to_vec()
+                        } else {
+                            new.1.clone()
+                        },
+                    )?;
+
+                    new_diff = new_diff_iter.next();
+                }
+                (None, None) => break,
+            }
+        }
+    }
+
+    // Restore the base tree and subtrees
+    match store_type {
+        Some(st) => {
+            // It is enough to get the base tree
+            let mut stores = db
+                .read_merkle_tree_stores(epoch, height, Some(StoreType::Base))?
+                .ok_or(StateError::NoMerkleTree { height })?;
+            let restored_stores = tree.stores();
+            stores.set_root(&st, *restored_stores.root(&st));
+            stores.set_store(restored_stores.store(&st).to_owned());
+            tree = MerkleTree::<H>::new_partial(stores);
+        }
+        None => {
+            // Get the base and subtrees stored in every block
+            let mut stores = db
+                .read_merkle_tree_stores(epoch, height, None)?
+                .ok_or(StateError::NoMerkleTree { height })?;
+            let restored_stores = tree.stores();
+            // Set all rebuilt subtrees except for the subtrees stored in
+            // every block
+            for st in StoreType::iter_subtrees() {
+                if !st.is_stored_every_block() {
+                    stores.set_root(st, *restored_stores.root(st));
+                    stores.set_store(restored_stores.store(st).to_owned());
+                }
+            }
+            tree = MerkleTree::<H>::new(stores)?;
+        }
+    }
+    Ok(tree)
+}
@@ -2,6 +2,7 @@
 //! before they are committed to the ledger's storage.
 
 use std::collections::{BTreeMap, BTreeSet};
+use std::sync::{Arc, Mutex};
 
 use itertools::Itertools;
 use namada_core::address::{Address, EstablishedAddressGen};
@@ -12,9 +13,10 @@ use namada_core::{arith, storage};
 use namada_events::extend::{InnerTxHash, TxHash};
 use namada_events::{Event, EventToEmit, EventType};
 use namada_gas::{
-    Gas, MEMORY_ACCESS_GAS_PER_BYTE, STORAGE_DELETE_GAS_PER_BYTE,
-    STORAGE_WRITE_GAS_PER_BYTE,
+    Gas, MEMORY_ACCESS_GAS_PER_BYTE, SAVEPOINT_FIXED_GAS,
+    STORAGE_DELETE_GAS_PER_BYTE, STORAGE_WRITE_GAS_PER_BYTE,
 };
+use namada_storage::KeyRange;
 use namada_tx::data::InnerTxId;
 use patricia_tree::map::StringPatriciaMap;
 use thiserror::Error;
@@ -43,6 +45,8 @@ pub enum Error {
     SizeDiffOverflow,
     #[error("Value length overflowed")]
     ValueLenOverflow,
+    #[error("There is no savepoint in the current transaction")]
+    NoSavepoint,
 }
 
 impl From<Error> for crate::Error {
@@ -135,6 +139,60 @@ impl std::cmp::PartialEq for WriteLogEvents {
 
 impl std::cmp::Eq for WriteLogEvents {}
 
+/// A snapshot of the storage modifications of a block, on top of which its
+/// transactions can be executed speculatively in parallel. Cloning the snapshot
+/// is cheap, as the modifications are shared.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockSnapshot(Arc<HashMap<storage::Key, StorageModification>>);
+
+/// The storage reads of a speculatively executed transaction that weren't
+/// resolved by its own modifications, and so depend on the state of the block
+/// that it was executed against.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReadSet {
+    /// The keys read
+    pub keys: BTreeSet<storage::Key>,
+    /// The prefixes and ranges of keys iterated
+    pub prefixes: Vec<(storage::Key, KeyRange)>,
+}
+
+/// The speculative layer of a write log, see [`WriteLog::new_speculative`]
+#[derive(Debug, Clone)]
+pub(crate) struct Speculative {
+    /// The block's modifications that precede the write log's own
+    snapshot: BlockSnapshot,
+    /// The reads resolved against the snapshot or the DB. The write log may
+    /// be read concurrently by validity predicates, hence the mutex.
+    read_set: Arc<Mutex<ReadSet>>,
+}
+
+impl std::cmp::PartialEq for Speculative {
+    fn eq(&self, other: &Speculative) -> bool {
+        self.snapshot == other.snapshot
+            && Arc::ptr_eq(&self.read_set, &other.read_set)
+    }
+}
+
+impl std::cmp::Eq for Speculative {}
+
+impl Speculative {
+    fn record_read(&self, key: &storage::Key) {
+        let mut read_set = self.read_set.lock().unwrap();
+        if !read_set.keys.contains(key) {
+            read_set.keys.insert(key.clone());
+        }
+    }
+
+    fn record_iter(&self, prefix: &storage::Key, range: &KeyRange) {
+        let mut read_set = self.read_set.lock().unwrap();
+        if !read_set.prefixes.iter().any(|(read_prefix, read_range)| {
+            read_prefix == prefix && read_range == range
+        }) {
+            read_set.prefixes.push((prefix.clone(), range.clone()));
+        }
+    }
+}
+
 /// The write log storage
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct WriteLog {
@@ -149,10 +207,17 @@ pub struct WriteLog {
     pub(crate) batch_write_log: Vec<BatchedTxWriteLog>,
     // The write log of the current active transaction
     pub(crate) tx_write_log: TxWriteLog,
+    /// The stack of savepoints of the current transaction, each holding the
+    /// state of its write log at the time the savepoint was pushed. The last
+    /// savepoint is the innermost one.
+    pub(crate) tx_savepoints: Vec<TxWriteLog>,
     /// Storage modifications for the replay protection storage, cannot be
     /// managed in the normal write log because we need to commit them
     /// sometimes even on batch failure
     pub(crate) replay_protection: HashSet<Hash>,
+    /// The block snapshot underneath the block write log and the recorded
+    /// reads, when executing a transaction speculatively
+    pub(crate) speculative: Option<Speculative>,
 }
 
 /// Write log prefix iterator
@@ -161,13 +226,19 @@ pub struct PrefixIter {
     /// The concrete iterator for modifications sorted by storage keys
     pub iter:
         std::collections::btree_map::IntoIter<String, StorageModification>,
+    /// Iterate in descending order of the keys
+    pub rev: bool,
 }
 
 impl Iterator for PrefixIter {
     type Item = (String, StorageModification);
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.iter.next()
+        if self.rev {
+            self.iter.next_back()
+        } else {
+            self.iter.next()
+        }
     }
 }
 
@@ -178,7 +249,9 @@ impl Default for WriteLog {
             block_write_log: HashMap::with_capacity(100_000),
             batch_write_log: Vec::with_capacity(5),
             tx_write_log: Default::default(),
+            tx_savepoints: Vec::new(),
             replay_protection: HashSet::with_capacity(1_000),
+            speculative: None,
         }
     }
 }
@@ -206,7 +279,7 @@ impl WriteLog {
             })
             .or_else(|| {
                 // if not found, then try to read from block write log
-                self.block_write_log.get(key)
+                self.read_block(key)
             }) {
             Some(v) => {
                 let gas = match &v {
@@ -238,33 +311,45 @@ impl WriteLog {
         key: &storage::Key,
     ) -> std::result::Result<(Option<&StorageModification>, Gas), arith::Error>
     {
-        for bucket in self
+        let modification = self
             .batch_write_log
             .iter()
             .rev()
-            .map(|batch_log| &batch_log.write_log)
-            .chain([&self.block_write_log])
-        {
-            if let Some(v) = bucket.get(key) {
-                let gas = match &v {
-                    StorageModification::Write { value } => {
-                        checked!(key.len() + value.len())?
-                    }
-                    StorageModification::Delete => key.len(),
-                    StorageModification::InitAccount { vp_code_hash } => {
-                        checked!(key.len() + vp_code_hash.len())?
-                    }
-                } as u64;
-                return Ok((
-                    Some(v),
-                    checked!(gas * MEMORY_ACCESS_GAS_PER_BYTE)?.into(),
-                ));
-            }
+            .find_map(|batch_log| batch_log.write_log.get(key))
+            .or_else(|| self.read_block(key));
+        if let Some(v) = modification {
+            let gas = match &v {
+                StorageModification::Write { value } => {
+                    checked!(key.len() + value.len())?
+                }
+                StorageModification::Delete => key.len(),
+                StorageModification::InitAccount { vp_code_hash } => {
+                    checked!(key.len() + vp_code_hash.len())?
+                }
+            } as u64;
+            return Ok((
+                Some(v),
+                checked!(gas * MEMORY_ACCESS_GAS_PER_BYTE)?.into(),
+            ));
         }
         let gas = key.len() as u64;
         Ok((None, checked!(gas * MEMORY_ACCESS_GAS_PER_BYTE)?.into()))
     }
 
+    /// Read a modification of the block, which includes the block snapshot of
+    /// a speculative write log. The read is recorded in the read set of a
+    /// speculative write log.
+    fn read_block(&self, key: &storage::Key) -> Option<&StorageModification> {
+        let modification = self.block_write_log.get(key);
+        match &self.speculative {
+            Some(speculative) => {
+                speculative.record_read(key);
+                modification.or_else(|| speculative.snapshot.0.get(key))
+            }
+            None => modification,
+        }
+    }
+
     /// Read a temp value at the given key and return the value and the gas
     /// cost, returns [`None`] if the key is not present in the temp write
     /// log
@@ -634,10 +719,70 @@ impl WriteLog {
         self.tx_write_log.events.tree.values().flatten()
     }
 
+    /// Push a new savepoint of the current transaction's write log and return
+    /// the gas cost. The storage modifications, temporary writes and events of
+    /// the transaction made after this call can then be undone with
+    /// [`WriteLog::rollback_to_savepoint`] or kept with
+    /// [`WriteLog::release_savepoint`]. Savepoints can be nested.
+    pub fn push_savepoint(&mut self) -> Result<Gas> {
+        let tx_write_log = &self.tx_write_log;
+        let mut len: usize = 0;
+        for (key, modification) in &tx_write_log.write_log {
+            let value_len = match modification {
+                StorageModification::Write { value } => value.len(),
+                StorageModification::Delete => 0,
+                StorageModification::InitAccount { vp_code_hash } => {
+                    vp_code_hash.len()
+                }
+            };
+            len = checked!(len + key.len() + value_len)?;
+        }
+        for (key, value) in &tx_write_log.tx_temp_log {
+            len = checked!(len + key.len() + value.len())?;
+        }
+        for event in tx_write_log.events.tree.values().flatten() {
+            for (key, value) in event.attributes() {
+                len = checked!(len + key.len() + value.len())?;
+            }
+        }
+        self.tx_savepoints.push(tx_write_log.clone());
+
+        // Charge for copying the transaction's write log, including its
+        // events, on top of the fixed cost of a savepoint
+        let gas = len as u64;
+        let gas = checked!(gas * MEMORY_ACCESS_GAS_PER_BYTE)?;
+        Ok(checked!(gas + SAVEPOINT_FIXED_GAS)?.into())
+    }
+
+    /// Undo all the changes of the current transaction made since the
+    /// innermost savepoint and remove it. Gas consumed by the undone changes
+    /// is not refunded.
+    /// Fails with [`Error::NoSavepoint`] if there's no savepoint.
+    pub fn rollback_to_savepoint(&mut self) -> Result<()> {
+        self.tx_write_log =
+            self.tx_savepoints.pop().ok_or(Error::NoSavepoint)?;
+        Ok(())
+    }
+
+    /// Remove the innermost savepoint of the current transaction, keeping all
+    /// the changes made since it was pushed. These changes can still be undone
+    /// by rolling back to an outer savepoint, if any.
+    /// Fails with [`Error::NoSavepoint`] if there's no savepoint.
+    pub fn release_savepoint(&mut self) -> Result<()> {
+        self.tx_savepoints.pop().ok_or(Error::NoSavepoint)?;
+        Ok(())
+    }
+
+    /// Get the number of nested savepoints of the current transaction
+    pub fn savepoints_depth(&self) -> usize {
+        self.tx_savepoints.len()
+    }
+
     /// Commit the current transaction's write log to the batch when it's
     /// accepted by all the triggered validity predicates. Starts a new
-    /// transaction write log.
+    /// transaction write log and drops its savepoints.
     pub fn commit_tx_to_batch(&mut self) {
+        self.tx_savepoints.clear();
         let tx_write_log = std::mem::take(&mut self.tx_write_log);
         let batched_log = BatchedTxWriteLog {
             address_gen: tx_write_log.address_gen,
@@ -649,9 +794,11 @@ impl WriteLog {
 
     /// Drop the current transaction's write log and IBC events when it's
     /// declined by any of the triggered validity predicates. Starts a new
-    /// transaction write log and clears the temp write log.
+    /// transaction write log, clears the temp write log and drops the
+    /// savepoints.
     pub fn drop_tx(&mut self) {
         self.tx_write_log = Default::default();
+        self.tx_savepoints.clear();
     }
 
     /// Commit the current tx and the entire batch to the block log.
@@ -712,40 +859,171 @@ impl WriteLog {
     /// Iterate modifications prior to the current transaction, whose storage
     /// key matches the given prefix, sorted by their storage key.
     pub fn iter_prefix_pre(&self, prefix: &storage::Key) -> PrefixIter {
-        let mut matches = BTreeMap::new();
+        self.iter_prefix_range_pre(prefix, &KeyRange::default())
+    }
+
+    /// Iterate modifications posterior of the current tx, whose storage key
+    /// matches the given prefix, sorted by their storage key.
+    pub fn iter_prefix_post(&self, prefix: &storage::Key) -> PrefixIter {
+        self.iter_prefix_range_post(prefix, &KeyRange::default())
+    }
 
-        for (key, modification) in self.block_write_log.iter().chain(
+    /// Iterate modifications prior to the current transaction, whose storage
+    /// key matches the given prefix and is within the given range, sorted by
+    /// their storage key in the range's direction.
+    pub fn iter_prefix_range_pre(
+        &self,
+        prefix: &storage::Key,
+        range: &KeyRange,
+    ) -> PrefixIter {
+        let modifications = self.iter_block(prefix, range).chain(
             self.batch_write_log
                 .iter()
                 .flat_map(|batch_log| batch_log.write_log.iter()),
-        ) {
-            if key.split_prefix(prefix).is_some() {
-                matches.insert(key.to_string(), modification.clone());
-            }
-        }
-
-        let iter = matches.into_iter();
-        PrefixIter { iter }
+        );
+        Self::prefix_range_matches(modifications, prefix, range)
     }
 
     /// Iterate modifications posterior of the current tx, whose storage key
-    /// matches the given prefix, sorted by their storage key.
-    pub fn iter_prefix_post(&self, prefix: &storage::Key) -> PrefixIter {
-        let mut matches = BTreeMap::new();
-
-        for (key, modification) in self.block_write_log.iter().chain(
+    /// matches the given prefix and is within the given range, sorted by
+    /// their storage key in the range's direction.
+    pub fn iter_prefix_range_post(
+        &self,
+        prefix: &storage::Key,
+        range: &KeyRange,
+    ) -> PrefixIter {
+        let modifications = self.iter_block(prefix, range).chain(
             self.batch_write_log
                 .iter()
                 .flat_map(|batch_log| batch_log.write_log.iter())
                 .chain(self.tx_write_log.write_log.iter()),
-        ) {
+        );
+        Self::prefix_range_matches(modifications, prefix, range)
+    }
+
+    /// Iterate all the modifications of the block, starting with the block
+    /// snapshot of a speculative write log. The iterated prefix and range are
+    /// recorded in the read set of a speculative write log.
+    fn iter_block(
+        &self,
+        prefix: &storage::Key,
+        range: &KeyRange,
+    ) -> impl Iterator<Item = (&storage::Key, &StorageModification)> {
+        let snapshot = self.speculative.as_ref().map(|speculative| {
+            speculative.record_iter(prefix, range);
+            speculative.snapshot.0.iter()
+        });
+        snapshot
+            .into_iter()
+            .flatten()
+            .chain(self.block_write_log.iter())
+    }
+
+    /// Collect the modifications whose storage key matches the given prefix
+    /// and is within the given range. The later modifications of a key
+    /// override the earlier ones.
+    fn prefix_range_matches<'a>(
+        modifications: impl Iterator<
+            Item = (&'a storage::Key, &'a StorageModification),
+        >,
+        prefix: &storage::Key,
+        range: &KeyRange,
+    ) -> PrefixIter {
+        let mut matches = BTreeMap::new();
+
+        for (key, modification) in modifications {
             if key.split_prefix(prefix).is_some() {
-                matches.insert(key.to_string(), modification.clone());
+                let key = key.to_string();
+                if range.contains(&key) {
+                    matches.insert(key, modification.clone());
+                }
             }
         }
 
         let iter = matches.into_iter();
-        PrefixIter { iter }
+        PrefixIter {
+            iter,
+            rev: range.rev,
+        }
+    }
+
+    /// Take a snapshot of the modifications of the block, to execute its
+    /// transactions speculatively with [`WriteLog::new_speculative`]. The
+    /// modifications of the current batch and transaction are not included.
+    pub fn block_snapshot(&self) -> BlockSnapshot {
+        BlockSnapshot(Arc::new(self.block_write_log.clone()))
+    }
+
+    /// Create a write log to execute a transaction speculatively on top of
+    /// the given block snapshot. Its reads that aren't resolved by its own
+    /// modifications are recorded, so that it can be checked with
+    /// [`WriteLog::is_speculation_valid`] whether they are still the same
+    /// against the actual state of the block.
+    pub fn new_speculative(snapshot: &BlockSnapshot) -> Self {
+        Self {
+            block_address_gen: None,
+            block_write_log: HashMap::new(),
+            batch_write_log: Vec::with_capacity(1),
+            tx_write_log: Default::default(),
+            tx_savepoints: Vec::new(),
+            replay_protection: HashSet::new(),
+            speculative: Some(Speculative {
+                snapshot: snapshot.clone(),
+                read_set: Default::default(),
+            }),
+        }
+    }
+
+    /// Get the reads of a speculative write log that depend on its block
+    /// snapshot. Returns `None` if the write log is not speculative.
+    pub fn read_set(&self) -> Option<ReadSet> {
+        self.speculative
+            .as_ref()
+            .map(|speculative| speculative.read_set.lock().unwrap().clone())
+    }
+
+    /// Check that the given reads of a speculative write log on top of the
+    /// given snapshot resolve to the same modifications against this write
+    /// log, prior to its current batch. If so, the speculative execution would
+    /// have read the same values and consumed the same gas if it was executed
+    /// against this write log.
+    pub fn is_speculation_valid(
+        &self,
+        snapshot: &BlockSnapshot,
+        read_set: &ReadSet,
+    ) -> bool {
+        let prior = |key: &storage::Key| {
+            self.batch_write_log
+                .iter()
+                .rev()
+                .find_map(|batch_log| batch_log.write_log.get(key))
+                .or_else(|| self.block_write_log.get(key))
+        };
+        let keys_valid = read_set
+            .keys
+            .iter()
+            .all(|key| prior(key) == snapshot.0.get(key));
+        keys_valid
+            && read_set.prefixes.iter().all(|(prefix, range)| {
+                let snapshot_matches = Self::prefix_range_matches(
+                    snapshot.0.iter(),
+                    prefix,
+                    range,
+                );
+                self.iter_prefix_range_pre(prefix, range)
+                    .eq(snapshot_matches)
+            })
+    }
+
+    /// Adopt the batch and transaction write logs of a validated speculative
+    /// write log in place of the current ones, as if its transactions were
+    /// executed against this write log. Its replay protection entries are
+    /// merged into the ones of this write log.
+    pub fn adopt_speculative(&mut self, speculative: WriteLog) {
+        self.batch_write_log = speculative.batch_write_log;
+        self.tx_write_log = speculative.tx_write_log;
+        self.tx_savepoints = speculative.tx_savepoints;
+        self.replay_protection.extend(speculative.replay_protection);
     }
 
     /// Check if the given tx hash has already been processed
@@ -1158,6 +1436,186 @@ mod tests {
         ));
     }
 
+    // Test that nested savepoints undo or keep the changes made since they
+    // were pushed
+    #[test]
+    fn test_nested_savepoints() {
+        use namada_events::{EventLevel, EventTypeBuilder};
+
+        let mut write_log = WriteLog::default();
+        let key1 = storage::Key::parse("key1").unwrap();
+        let key2 = storage::Key::parse("key2").unwrap();
+        let key3 = storage::Key::parse("key3").unwrap();
+        let temp_key = storage::Key::parse("temp").unwrap();
+        let event = Event::new(
+            EventTypeBuilder::new_with_type("test").build(),
+            EventLevel::Tx,
+        );
+
+        // There's no savepoint to roll back to or release yet
+        assert_matches!(
+            write_log.rollback_to_savepoint(),
+            Err(Error::NoSavepoint)
+        );
+        assert_matches!(write_log.release_savepoint(), Err(Error::NoSavepoint));
+
+        let _ = write_log.write(&key1, vec![1]).unwrap();
+        let gas = write_log.push_savepoint().unwrap();
+        assert!(gas > Gas::from(0));
+
+        // Changes in the outer savepoint
+        let _ = write_log.write(&key1, vec![2]).unwrap();
+        let _ = write_log.write_temp(&temp_key, vec![1]).unwrap();
+        let _ = write_log.emit_event(event).unwrap();
+
+        // Changes in the inner savepoint are kept on release
+        let _ = write_log.push_savepoint().unwrap();
+        let _ = write_log.write(&key2, vec![1]).unwrap();
+        assert_eq!(write_log.savepoints_depth(), 2);
+        write_log.release_savepoint().unwrap();
+        assert!(write_log.read(&key2).unwrap().0.is_some());
+
+        // Changes in the inner savepoint are undone on rollback
+        let _ = write_log.push_savepoint().unwrap();
+        let _ = write_log.delete(&key1).unwrap();
+        let _ = write_log.write(&key3, vec![1]).unwrap();
+        write_log.rollback_to_savepoint().unwrap();
+        assert_eq!(
+            write_log.read(&key1).unwrap().0,
+            Some(&StorageModification::Write { value: vec![2] })
+        );
+        assert!(write_log.read(&key3).unwrap().0.is_none());
+        assert_eq!(write_log.savepoints_depth(), 1);
+
+        // Rolling back the outer savepoint undoes all the changes since it,
+        // including the temp writes and events
+        write_log.rollback_to_savepoint().unwrap();
+        assert_eq!(
+            write_log.read(&key1).unwrap().0,
+            Some(&StorageModification::Write { value: vec![1] })
+        );
+        assert!(write_log.read(&key2).unwrap().0.is_none());
+        assert!(write_log.read_temp(&temp_key).unwrap().0.is_none());
+        assert_eq!(write_log.get_events().count(), 0);
+        assert_eq!(write_log.savepoints_depth(), 0);
+
+        // The savepoints are dropped with the tx
+        let _ = write_log.push_savepoint().unwrap();
+        write_log.commit_tx_to_batch();
+        assert_eq!(write_log.savepoints_depth(), 0);
+        let _ = write_log.push_savepoint().unwrap();
+        write_log.drop_tx();
+        assert_eq!(write_log.savepoints_depth(), 0);
+        assert_eq!(
+            write_log.read(&key1).unwrap().0,
+            Some(&StorageModification::Write { value: vec![1] })
+        );
+    }
+
+    // Test that pushing a savepoint charges a fixed cost plus the copied
+    // bytes of the write log, including the events
+    #[test]
+    fn test_savepoint_gas() {
+        use namada_events::{EventLevel, EventTypeBuilder};
+
+        let mut write_log = WriteLog::default();
+        let key = storage::Key::parse("key").unwrap();
+        let mut event = Event::new(
+            EventTypeBuilder::new_with_type("test").build(),
+            EventLevel::Tx,
+        );
+        event
+            .attributes_mut()
+            .insert("attr".to_string(), "value".to_string());
+
+        // An empty write log only costs the fixed cost
+        assert_eq!(
+            write_log.push_savepoint().unwrap(),
+            Gas::from(SAVEPOINT_FIXED_GAS)
+        );
+
+        let _ = write_log.write(&key, vec![1, 2]).unwrap();
+        let write_len = key.len().checked_add(2).unwrap() as u64;
+        let gas = write_len.checked_mul(MEMORY_ACCESS_GAS_PER_BYTE).unwrap();
+        assert_eq!(
+            write_log.push_savepoint().unwrap(),
+            Gas::from(gas.checked_add(SAVEPOINT_FIXED_GAS).unwrap())
+        );
+
+        let _ = write_log.emit_event(event).unwrap();
+        let event_len = "attrvalue".len() as u64;
+        let gas = write_len
+            .checked_add(event_len)
+            .unwrap()
+            .checked_mul(MEMORY_ACCESS_GAS_PER_BYTE)
+            .unwrap();
+        assert_eq!(
+            write_log.push_savepoint().unwrap(),
+            Gas::from(gas.checked_add(SAVEPOINT_FIXED_GAS).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_speculative_write_log() {
+        let mut write_log = WriteLog::default();
+        let key1 = storage::Key::parse("key1").unwrap();
+        let key2 = storage::Key::parse("key2").unwrap();
+        let prefix = storage::Key::parse("prefix").unwrap();
+        let prefixed = prefix.push(&"sub".to_string()).unwrap();
+
+        let _ = write_log.write(&key1, vec![1]).unwrap();
+        write_log.commit_batch_and_current_tx();
+        let snapshot = write_log.block_snapshot();
+
+        // A speculative write log reads the block snapshot and records the
+        // reads that aren't resolved by its own modifications
+        let mut speculative = WriteLog::new_speculative(&snapshot);
+        assert_eq!(
+            speculative.read(&key1).unwrap().0,
+            Some(&StorageModification::Write { value: vec![1] })
+        );
+        let _ = speculative.write(&key2, vec![2]).unwrap();
+        assert!(speculative.read(&key2).unwrap().0.is_some());
+        assert_eq!(
+            speculative
+                .iter_prefix_range_pre(&prefix, &KeyRange::default())
+                .count(),
+            0
+        );
+        speculative.commit_tx_to_batch();
+        let read_set = speculative.read_set().unwrap();
+        assert_eq!(read_set.keys, BTreeSet::from([key1.clone()]));
+        assert_eq!(read_set.prefixes.len(), 1);
+        assert!(write_log.read_set().is_none());
+
+        // The speculation is valid as long as the reads are unchanged
+        let _ = write_log.write(&key2, vec![3]).unwrap();
+        write_log.commit_batch_and_current_tx();
+        assert!(write_log.is_speculation_valid(&snapshot, &read_set));
+
+        let mut conflicting = write_log.clone();
+        let _ = conflicting.write(&key1, vec![4]).unwrap();
+        conflicting.commit_batch_and_current_tx();
+        assert!(!conflicting.is_speculation_valid(&snapshot, &read_set));
+
+        let mut conflicting = write_log.clone();
+        let _ = conflicting.write(&prefixed, vec![4]).unwrap();
+        conflicting.commit_batch_and_current_tx();
+        assert!(!conflicting.is_speculation_valid(&snapshot, &read_set));
+
+        // Adopting the speculation applies its modifications and replay
+        // protection entries as if they were executed against the write log
+        let hash = Hash::sha256("tx".as_bytes());
+        speculative.write_tx_hash(hash).unwrap();
+        write_log.adopt_speculative(speculative);
+        write_log.commit_batch_only();
+        assert_eq!(
+            write_log.read(&key2).unwrap().0,
+            Some(&StorageModification::Write { value: vec![2] })
+        );
+        assert!(write_log.has_replay_protection_entry(&hash));
+    }
+
     prop_compose! {
         fn arb_verifiers_changed_key_tx_all_key()
             (verifiers_from_tx in testing::arb_verifiers_from_tx())
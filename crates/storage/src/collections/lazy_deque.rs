@@ -0,0 +1,620 @@
+//! Lazy double-ended queue.
+
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+use namada_core::arith::checked;
+use namada_core::borsh::{BorshDeserialize, BorshSerialize};
+use namada_core::storage::{self, DbKeySeg, KeySeg};
+use thiserror::Error;
+
+use super::super::Result;
+use super::lazy_map::Collectable;
+use super::{EagerCollection, LazyCollection};
+use crate::{KeyRange, ResultExt, StorageRead, StorageWrite};
+
+/// Subkey pointing to the bounds of the LazyDeque
+pub const BOUNDS_SUBKEY: &str = "bounds";
+/// Subkey corresponding to the data elements of the LazyDeque
+pub const DATA_SUBKEY: &str = "data";
+
+/// Using `u64` for deque's storage indices
+pub type Index = u64;
+
+/// The storage index of the first element pushed into an empty deque. It's in
+/// the middle of the index space to leave room for pushing at both ends.
+pub const INITIAL_INDEX: Index = 1 << 63;
+
+/// Lazy double-ended queue.
+///
+/// This can be used as an alternative to `std::collections::VecDeque`. In the
+/// lazy deque, the elements do not reside in memory but are instead read and
+/// written to storage sub-keys of the storage `key` used to construct the
+/// deque.
+///
+/// The elements are stored at consecutive storage indices within the deque's
+/// [`Bounds`], which makes it possible to push and pop elements at both ends
+/// without moving any other elements.
+#[derive(Clone, Debug)]
+pub struct LazyDeque<T> {
+    key: storage::Key,
+    phantom: PhantomData<T>,
+}
+
+/// The range of storage indices `[front, end)` occupied by the elements of a
+/// [`LazyDeque`]
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize,
+)]
+pub struct Bounds {
+    /// The storage index of the first element
+    pub front: Index,
+    /// The storage index one past the last element
+    pub end: Index,
+}
+
+impl Bounds {
+    /// The bounds of an empty deque
+    pub const EMPTY: Self = Self {
+        front: INITIAL_INDEX,
+        end: INITIAL_INDEX,
+    };
+
+    /// The number of elements within the bounds
+    pub fn len(&self) -> u64 {
+        self.end.saturating_sub(self.front)
+    }
+
+    /// Returns `true` if there are no elements within the bounds
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns `true` if the given storage index is within the bounds
+    pub fn contains(&self, index: Index) -> bool {
+        self.front <= index && index < self.end
+    }
+
+    /// The number of storage indices within both of the bounds
+    pub fn overlap(&self, other: &Self) -> u64 {
+        let front = self.front.max(other.front);
+        let end = self.end.min(other.end);
+        end.saturating_sub(front)
+    }
+}
+
+/// Possible sub-keys of a [`LazyDeque`]
+#[derive(Debug, PartialEq)]
+pub enum SubKey {
+    /// Bounds sub-key
+    Bounds,
+    /// Data sub-key, further sub-keyed by its storage index
+    Data(Index),
+}
+
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum ValidationError {
+    #[error("An empty LazyDeque must be deleted from storage")]
+    EmptyDequeShouldBeDeleted,
+    #[error("Invalid LazyDeque bounds: front {front} is after end {end}")]
+    InvalidBounds { front: Index, end: Index },
+    #[error(
+        "The changes of LazyDeque's elements don't match the change of its \
+         bounds"
+    )]
+    InvalidBoundsDiff,
+    #[error("Push at a wrong index {0}")]
+    UnexpectedPushIndex(Index),
+    #[error("Pop at a wrong index {0}")]
+    UnexpectedPopIndex(Index),
+    #[error("Update at a wrong index {0}")]
+    UnexpectedUpdateIndex(Index),
+    #[error("Invalid storage key {0}")]
+    InvalidSubKey(storage::Key),
+}
+
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum UpdateError {
+    #[error(
+        "Invalid position in a LazyDeque. Got {position}, but the length is \
+         {len}"
+    )]
+    InvalidPosition { position: u64, len: u64 },
+}
+
+impl<T> LazyCollection for LazyDeque<T>
+where
+    T: BorshSerialize + BorshDeserialize + 'static + Debug,
+{
+    type SubKey = SubKey;
+    type Value = T;
+
+    /// Create or use an existing deque with the given storage `key`.
+    fn open(key: storage::Key) -> Self {
+        Self {
+            key,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Check if the given storage key is a valid LazyDeque sub-key and if so
+    /// return which one
+    fn is_valid_sub_key(
+        &self,
+        key: &storage::Key,
+    ) -> crate::Result<Option<SubKey>> {
+        let suffix = match key.split_prefix(&self.key) {
+            None => {
+                // not matching prefix, irrelevant
+                return Ok(None);
+            }
+            Some(None) => {
+                // no suffix, invalid
+                return Err(ValidationError::InvalidSubKey(key.clone()))
+                    .into_storage_result();
+            }
+            Some(Some(suffix)) => suffix,
+        };
+
+        // Match the suffix against expected sub-keys
+        match &suffix.segments[..] {
+            [DbKeySeg::StringSeg(sub)] if sub == BOUNDS_SUBKEY => {
+                Ok(Some(SubKey::Bounds))
+            }
+            [DbKeySeg::StringSeg(sub_a), DbKeySeg::StringSeg(sub_b)]
+                if sub_a == DATA_SUBKEY =>
+            {
+                if let Ok(index) = storage::KeySeg::parse(sub_b.clone()) {
+                    Ok(Some(SubKey::Data(index)))
+                } else {
+                    Err(ValidationError::InvalidSubKey(key.clone()))
+                        .into_storage_result()
+                }
+            }
+            _ => Err(ValidationError::InvalidSubKey(key.clone()))
+                .into_storage_result(),
+        }
+    }
+
+    fn is_data_sub_key(&self, key: &storage::Key) -> bool {
+        let sub_key = self.is_valid_sub_key(key);
+        // The `SubKey::Bounds` is not data sub-key
+        matches!(sub_key, Ok(Some(SubKey::Data(_))))
+    }
+}
+
+impl<T, M> EagerCollection<LazyDeque<T>> for M
+where
+    T: BorshSerialize + BorshDeserialize + 'static + Debug,
+    M: FromIterator<T> + IntoIterator<Item = T>,
+{
+    fn collect_from<S>(lazy: &LazyDeque<T>, storage: &S) -> crate::Result<Self>
+    where
+        S: StorageRead,
+    {
+        lazy.iter(storage)?.collect()
+    }
+
+    fn extend_into<S>(
+        self,
+        lazy: &LazyDeque<T>,
+        storage: &mut S,
+    ) -> crate::Result<()>
+    where
+        S: StorageRead + StorageWrite,
+    {
+        for val in self {
+            lazy.push_back(storage, val)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T> Collectable for LazyDeque<T>
+where
+    T: BorshSerialize + BorshDeserialize + 'static + Debug,
+{
+    type Collected = VecDeque<T>;
+
+    fn collect_map<S>(&self, storage: &S) -> crate::Result<Self::Collected>
+    where
+        S: StorageRead,
+    {
+        self.collect(storage)
+    }
+}
+
+// Generic `LazyDeque` methods that require no bounds on values `T`
+impl<T> LazyDeque<T> {
+    /// Reads the storage indices occupied by the elements of the deque.
+    pub fn bounds<S>(&self, storage: &S) -> Result<Bounds>
+    where
+        S: StorageRead,
+    {
+        let bounds = storage.read(&self.get_bounds_key())?;
+        Ok(bounds.unwrap_or(Bounds::EMPTY))
+    }
+
+    /// Reads the number of elements in the deque.
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len<S>(&self, storage: &S) -> Result<u64>
+    where
+        S: StorageRead,
+    {
+        Ok(self.bounds(storage)?.len())
+    }
+
+    /// Returns `true` if the deque contains no elements.
+    pub fn is_empty<S>(&self, storage: &S) -> Result<bool>
+    where
+        S: StorageRead,
+    {
+        Ok(self.bounds(storage)?.is_empty())
+    }
+
+    /// Get the prefix of deque's elements storage
+    fn get_data_prefix(&self) -> storage::Key {
+        self.key.push(&DATA_SUBKEY.to_owned()).unwrap()
+    }
+
+    /// Get the sub-key of deque's elements storage
+    fn get_data_key(&self, index: Index) -> storage::Key {
+        self.get_data_prefix().push(&index).unwrap()
+    }
+
+    /// Get the sub-key of deque's bounds storage
+    fn get_bounds_key(&self) -> storage::Key {
+        self.key.push(&BOUNDS_SUBKEY.to_owned()).unwrap()
+    }
+
+    /// Write the deque's bounds or delete them if the deque is empty
+    fn write_bounds<S>(&self, storage: &mut S, bounds: Bounds) -> Result<()>
+    where
+        S: StorageWrite,
+    {
+        if bounds.is_empty() {
+            storage.delete(&self.get_bounds_key())
+        } else {
+            storage.write(&self.get_bounds_key(), bounds)
+        }
+    }
+}
+
+// `LazyDeque` methods with borsh encoded values `T`
+impl<T> LazyDeque<T>
+where
+    T: BorshSerialize + BorshDeserialize + 'static + Debug,
+{
+    /// Prepends an element to the front of the deque.
+    pub fn push_front<S>(&self, storage: &mut S, val: T) -> Result<()>
+    where
+        S: StorageWrite + StorageRead,
+    {
+        let bounds = self.bounds(storage)?;
+        let bounds = if bounds.is_empty() {
+            Bounds {
+                front: INITIAL_INDEX,
+                end: checked!(INITIAL_INDEX + 1)?,
+            }
+        } else {
+            Bounds {
+                front: checked!(bounds.front - 1)?,
+                end: bounds.end,
+            }
+        };
+        storage.write(&self.get_data_key(bounds.front), val)?;
+        self.write_bounds(storage, bounds)
+    }
+
+    /// Appends an element to the back of the deque.
+    pub fn push_back<S>(&self, storage: &mut S, val: T) -> Result<()>
+    where
+        S: StorageWrite + StorageRead,
+    {
+        let bounds = self.bounds(storage)?;
+        storage.write(&self.get_data_key(bounds.end), val)?;
+        self.write_bounds(
+            storage,
+            Bounds {
+                front: bounds.front,
+                end: checked!(bounds.end + 1)?,
+            },
+        )
+    }
+
+    /// Removes the first element from the deque and returns it, or `Ok(None)`
+    /// if it is empty.
+    ///
+    /// Note that an empty deque is completely removed from storage.
+    pub fn pop_front<S>(&self, storage: &mut S) -> Result<Option<T>>
+    where
+        S: StorageWrite + StorageRead,
+    {
+        let bounds = self.bounds(storage)?;
+        if bounds.is_empty() {
+            return Ok(None);
+        }
+        let data_key = self.get_data_key(bounds.front);
+        let popped_val = storage.read(&data_key)?;
+        storage.delete(&data_key)?;
+        self.write_bounds(
+            storage,
+            Bounds {
+                front: checked!(bounds.front + 1)?,
+                end: bounds.end,
+            },
+        )?;
+        Ok(popped_val)
+    }
+
+    /// Removes the last element from the deque and returns it, or `Ok(None)`
+    /// if it is empty.
+    ///
+    /// Note that an empty deque is completely removed from storage.
+    pub fn pop_back<S>(&self, storage: &mut S) -> Result<Option<T>>
+    where
+        S: StorageWrite + StorageRead,
+    {
+        let bounds = self.bounds(storage)?;
+        if bounds.is_empty() {
+            return Ok(None);
+        }
+        let index = checked!(bounds.end - 1)?;
+        let data_key = self.get_data_key(index);
+        let popped_val = storage.read(&data_key)?;
+        storage.delete(&data_key)?;
+        self.write_bounds(
+            storage,
+            Bounds {
+                front: bounds.front,
+                end: index,
+            },
+        )?;
+        Ok(popped_val)
+    }
+
+    /// Update an element at the given position from the front of the deque.
+    ///
+    /// The position must be smaller than the length of the deque, otherwise
+    /// this will fail with `UpdateError::InvalidPosition`.
+    pub fn update<S>(
+        &self,
+        storage: &mut S,
+        position: u64,
+        val: T,
+    ) -> Result<()>
+    where
+        S: StorageWrite + StorageRead,
+    {
+        let bounds = self.bounds(storage)?;
+        let len = bounds.len();
+        if position >= len {
+            return Err(UpdateError::InvalidPosition { position, len })
+                .into_storage_result();
+        }
+        let data_key = self.get_data_key(checked!(bounds.front + position)?);
+        storage.write(&data_key, val)
+    }
+
+    /// Read an element at the given position from the front of the deque or
+    /// `Ok(None)` if out of bounds.
+    pub fn get<S>(&self, storage: &S, position: u64) -> Result<Option<T>>
+    where
+        S: StorageRead,
+    {
+        let bounds = self.bounds(storage)?;
+        if position >= bounds.len() {
+            return Ok(None);
+        }
+        storage.read(&self.get_data_key(checked!(bounds.front + position)?))
+    }
+
+    /// Read the first element
+    pub fn front<S>(&self, storage: &S) -> Result<Option<T>>
+    where
+        S: StorageRead,
+    {
+        let bounds = self.bounds(storage)?;
+        if bounds.is_empty() {
+            return Ok(None);
+        }
+        storage.read(&self.get_data_key(bounds.front))
+    }
+
+    /// Read the last element
+    pub fn back<S>(&self, storage: &S) -> Result<Option<T>>
+    where
+        S: StorageRead,
+    {
+        let bounds = self.bounds(storage)?;
+        if bounds.is_empty() {
+            return Ok(None);
+        }
+        storage.read(&self.get_data_key(checked!(bounds.end - 1)?))
+    }
+
+    /// An iterator visiting all elements from the front to the back. The
+    /// iterator element type is `Result<T>`, because iterator's call to `next`
+    /// may fail with e.g. out of gas or data decoding error.
+    ///
+    /// Note that this function shouldn't be used in transactions and VPs code
+    /// on unbounded deques to avoid gas usage increasing with the length of
+    /// the deque.
+    pub fn iter<'iter>(
+        &self,
+        storage: &'iter impl StorageRead,
+    ) -> Result<impl Iterator<Item = Result<T>> + 'iter> {
+        let iter = crate::iter_prefix(storage, self.get_data_prefix())?;
+        Ok(iter.map(|key_val_res| {
+            let (_key, val) = key_val_res?;
+            Ok(val)
+        }))
+    }
+
+    /// Like [`LazyDeque::iter`], but visits the elements from the back to the
+    /// front.
+    pub fn iter_rev<'iter>(
+        &self,
+        storage: &'iter impl StorageRead,
+    ) -> Result<impl Iterator<Item = Result<T>> + 'iter> {
+        let iter = crate::iter_prefix_range(
+            storage,
+            self.get_data_prefix(),
+            KeyRange::default().rev(),
+        )?;
+        Ok(iter.map(|key_val_res| {
+            let (_key, val) = key_val_res?;
+            Ok(val)
+        }))
+    }
+
+    /// Collect all the elements of the deque into an eager in-memory
+    /// collection, e.g. a `VecDeque<T>`.
+    ///
+    /// Note that this function shouldn't be used in transactions and VPs code
+    /// on unbounded deques to avoid gas usage increasing with the length of
+    /// the deque.
+    pub fn collect<M>(&self, storage: &impl StorageRead) -> Result<M>
+    where
+        M: EagerCollection<Self>,
+    {
+        M::collect_from(self, storage)
+    }
+
+    /// Write all the elements of an eager in-memory collection into the
+    /// deque. The elements
+    /// are pushed to the back of the deque.
+    pub fn extend_from<M>(
+        &self,
+        storage: &mut (impl StorageRead + StorageWrite),
+        eager: M,
+    ) -> Result<()>
+    where
+        M: EagerCollection<Self>,
+    {
+        eager.extend_into(self, storage)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::arithmetic_side_effects)]
+mod test {
+    use super::*;
+    use crate::collections::lazy_map::{self, NestedMap};
+    use crate::testing::TestStorage;
+
+    #[test]
+    fn test_lazy_deque_basics() -> crate::Result<()> {
+        let mut storage = TestStorage::default();
+
+        let key = storage::Key::parse("test").unwrap();
+        let lazy_deque = LazyDeque::<u32>::open(key);
+
+        // The deque should be empty at first
+        assert!(lazy_deque.is_empty(&storage)?);
+        assert_eq!(lazy_deque.len(&storage)?, 0);
+        assert!(lazy_deque.iter(&storage)?.next().is_none());
+        assert!(lazy_deque.pop_front(&mut storage)?.is_none());
+        assert!(lazy_deque.pop_back(&mut storage)?.is_none());
+        assert!(lazy_deque.front(&storage)?.is_none());
+        assert!(lazy_deque.back(&storage)?.is_none());
+        assert!(lazy_deque.get(&storage, 0)?.is_none());
+
+        // Push at both ends
+        lazy_deque.push_back(&mut storage, 2)?;
+        lazy_deque.push_front(&mut storage, 1)?;
+        lazy_deque.push_back(&mut storage, 3)?;
+        lazy_deque.push_front(&mut storage, 0)?;
+        assert_eq!(lazy_deque.len(&storage)?, 4);
+        assert_eq!(
+            lazy_deque.bounds(&storage)?,
+            Bounds {
+                front: INITIAL_INDEX - 2,
+                end: INITIAL_INDEX + 2,
+            }
+        );
+        assert_eq!(
+            lazy_deque.iter(&storage)?.collect::<Result<Vec<_>>>()?,
+            vec![0, 1, 2, 3]
+        );
+        assert_eq!(
+            lazy_deque.iter_rev(&storage)?.collect::<Result<Vec<_>>>()?,
+            vec![3, 2, 1, 0]
+        );
+        assert_eq!(lazy_deque.front(&storage)?, Some(0));
+        assert_eq!(lazy_deque.back(&storage)?, Some(3));
+        assert_eq!(lazy_deque.get(&storage, 2)?, Some(2));
+        assert!(lazy_deque.get(&storage, 4)?.is_none());
+
+        // Update an element
+        lazy_deque.update(&mut storage, 1, 10)?;
+        assert_eq!(lazy_deque.get(&storage, 1)?, Some(10));
+        assert!(lazy_deque.update(&mut storage, 4, 10).is_err());
+
+        // Pop at both ends
+        assert_eq!(lazy_deque.pop_front(&mut storage)?, Some(0));
+        assert_eq!(lazy_deque.pop_back(&mut storage)?, Some(3));
+        assert_eq!(lazy_deque.pop_back(&mut storage)?, Some(2));
+        assert_eq!(lazy_deque.len(&storage)?, 1);
+        assert_eq!(lazy_deque.pop_front(&mut storage)?, Some(10));
+
+        // The empty deque should be deleted from storage
+        assert!(lazy_deque.is_empty(&storage)?);
+        assert!(!storage.has_key(&lazy_deque.get_bounds_key())?);
+        assert!(lazy_deque.iter(&storage)?.next().is_none());
+
+        let storage_key = lazy_deque.get_data_key(INITIAL_INDEX);
+        assert_eq!(
+            lazy_deque.is_valid_sub_key(&storage_key).unwrap(),
+            Some(SubKey::Data(INITIAL_INDEX))
+        );
+        assert_eq!(
+            lazy_deque
+                .is_valid_sub_key(&lazy_deque.get_bounds_key())
+                .unwrap(),
+            Some(SubKey::Bounds)
+        );
+
+        Ok(())
+    }
+
+    /// Test iterator on a `LazyDeque` nested inside a `LazyMap`
+    #[test]
+    fn test_nested_lazy_deque_iter() -> crate::Result<()> {
+        let mut storage = TestStorage::default();
+
+        let prefix = storage::Key::parse("test").unwrap();
+        let handle = NestedMap::<u64, LazyDeque<u32>>::open(prefix);
+
+        handle.at(&1).push_back(&mut storage, 2)?;
+        handle.at(&1).push_front(&mut storage, 1)?;
+
+        let mut iter = handle.iter(&storage)?;
+        assert_eq!(
+            iter.next().unwrap()?,
+            (
+                lazy_map::NestedSubKey::Data {
+                    key: 1,
+                    nested_sub_key: SubKey::Data(INITIAL_INDEX - 1),
+                },
+                1,
+            )
+        );
+        assert_eq!(
+            iter.next().unwrap()?,
+            (
+                lazy_map::NestedSubKey::Data {
+                    key: 1,
+                    nested_sub_key: SubKey::Data(INITIAL_INDEX),
+                },
+                2,
+            )
+        );
+        assert!(iter.next().is_none());
+
+        Ok(())
+    }
+}
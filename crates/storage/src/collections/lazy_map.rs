@@ -4,14 +4,15 @@ use std::collections::BTreeMap;
 use std::fmt::Debug;
 use std::hash::Hash;
 use std::marker::PhantomData;
+use std::ops::{Bound, RangeBounds};
 
 use namada_core::borsh::{BorshDeserialize, BorshSerialize};
 use namada_core::storage::{self, DbKeySeg, KeySeg};
 use thiserror::Error;
 
 use super::super::Result;
-use super::{LazyCollection, ReadError};
-use crate::{ResultExt, StorageRead, StorageWrite};
+use super::{EagerCollection, EagerMap, LazyCollection, ReadError};
+use crate::{KeyRange, ResultExt, StorageRead, StorageWrite};
 
 /// Subkey corresponding to the data elements of the LazyMap
 pub const DATA_SUBKEY: &str = "data";
@@ -91,14 +92,8 @@ where
         S: StorageRead,
     {
         let mut map = BTreeMap::<K, V::Collected>::new();
-        for res in self.iter(storage)? {
-            let (
-                NestedSubKey::Data {
-                    key,
-                    nested_sub_key: _,
-                },
-                _,
-            ) = res?;
+        for key in self.keys(storage)? {
+            let key = key?;
             let next_layer = self.at(&key).collect_map(storage)?;
             map.insert(key, next_layer);
         }
@@ -126,6 +121,77 @@ where
     }
 }
 
+impl<K, V, M> EagerCollection<LazyMap<K, V, super::Nested>> for M
+where
+    K: storage::KeySeg + Clone + Hash + Eq + Debug,
+    V: LazyCollection + Debug,
+    M: EagerMap<Key = K>
+        + FromIterator<(K, M::Value)>
+        + IntoIterator<Item = (K, M::Value)>,
+    M::Value: EagerCollection<V>,
+{
+    fn collect_from<S>(
+        lazy: &LazyMap<K, V, super::Nested>,
+        storage: &S,
+    ) -> crate::Result<Self>
+    where
+        S: StorageRead,
+    {
+        lazy.keys(storage)?
+            .map(|key| {
+                let key = key?;
+                let nested = M::Value::collect_from(&lazy.at(&key), storage)?;
+                Ok((key, nested))
+            })
+            .collect()
+    }
+
+    fn extend_into<S>(
+        self,
+        lazy: &LazyMap<K, V, super::Nested>,
+        storage: &mut S,
+    ) -> crate::Result<()>
+    where
+        S: StorageRead + StorageWrite,
+    {
+        for (key, nested) in self {
+            nested.extend_into(&lazy.at(&key), storage)?;
+        }
+        Ok(())
+    }
+}
+
+impl<K, V, M> EagerCollection<LazyMap<K, V, super::Simple>> for M
+where
+    K: storage::KeySeg,
+    V: BorshDeserialize + BorshSerialize + 'static,
+    M: FromIterator<(K, V)> + IntoIterator<Item = (K, V)>,
+{
+    fn collect_from<S>(
+        lazy: &LazyMap<K, V, super::Simple>,
+        storage: &S,
+    ) -> crate::Result<Self>
+    where
+        S: StorageRead,
+    {
+        lazy.iter(storage)?.collect()
+    }
+
+    fn extend_into<S>(
+        self,
+        lazy: &LazyMap<K, V, super::Simple>,
+        storage: &mut S,
+    ) -> crate::Result<()>
+    where
+        S: StorageRead + StorageWrite,
+    {
+        for (key, val) in self {
+            lazy.insert(storage, key, val)?;
+        }
+        Ok(())
+    }
+}
+
 impl<K, V> LazyCollection for LazyMap<K, V, super::Nested>
 where
     K: storage::KeySeg + Clone + Hash + Eq + Debug,
@@ -295,6 +361,45 @@ where
         let key_str = key.to_db_key();
         self.get_data_prefix().push(&key_str).unwrap()
     }
+
+    /// Collect all the elements of the map into an eager in-memory collection,
+    /// e.g. a `BTreeMap<K, V>`, or a `BTreeMap<K, BTreeMap<K2, V>>` for a
+    /// nested map.
+    ///
+    /// Note that this function shouldn't be used in transactions and VPs code
+    /// on unbounded maps to avoid gas usage increasing with the length of the
+    /// map.
+    pub fn collect<M>(&self, storage: &impl StorageRead) -> Result<M>
+    where
+        M: EagerCollection<Self>,
+    {
+        M::collect_from(self, storage)
+    }
+
+    /// Write all the elements of an eager in-memory collection into the map.
+    /// Existing elements with the same keys are overwritten.
+    pub fn extend_from<M>(
+        &self,
+        storage: &mut (impl StorageRead + StorageWrite),
+        eager: M,
+    ) -> Result<()>
+    where
+        M: EagerCollection<Self>,
+    {
+        eager.extend_into(self, storage)
+    }
+
+    /// Get the bounds of the storage key segments of the keys within the
+    /// given range
+    fn get_key_seg_bounds(
+        range: &impl RangeBounds<K>,
+    ) -> (Bound<String>, Bound<String>) {
+        let key_seg = |key: &K| key.to_db_key().raw();
+        (
+            range.start_bound().map(key_seg),
+            range.end_bound().map(key_seg),
+        )
+    }
 }
 
 // `LazyMap` methods with nested `LazyCollection`s `V`
@@ -310,6 +415,13 @@ where
         V::open(self.get_data_key(key))
     }
 
+    /// Get the range of the storage sub-keys of the nested collections whose
+    /// keys are within the given bounds
+    fn get_data_range(&self, range: impl RangeBounds<K>) -> KeyRange {
+        let (start, end) = Self::get_key_seg_bounds(&range);
+        KeyRange::nested_sub_keys(&self.get_data_prefix(), start, end)
+    }
+
     /// Returns whether the nested map contains a certain key with data inside.
     pub fn contains<S>(&self, storage: &S, key: &K) -> Result<bool>
     where
@@ -320,6 +432,40 @@ where
         Ok(iter.next().is_some())
     }
 
+    /// An iterator visiting the keys of the nested collections. Unlike
+    /// [`LazyMap::iter`], only the first element of each nested collection is
+    /// read from storage.
+    pub fn keys<'iter>(
+        &'iter self,
+        storage: &'iter impl StorageRead,
+    ) -> Result<impl Iterator<Item = Result<K>> + 'iter> {
+        let mut next_start = Bound::Unbounded;
+        let mut done = false;
+        Ok(std::iter::from_fn(move || {
+            if done {
+                return None;
+            }
+            // Skip to the first element after the nested collection at the
+            // previous key
+            let range = (next_start.clone(), Bound::Unbounded);
+            let next = match self.range(storage, range) {
+                Ok(mut iter) => iter.next(),
+                Err(err) => Some(Err(err)),
+            };
+            match next? {
+                Ok((NestedSubKey::Data { key, .. }, _)) => {
+                    next_start = Bound::Excluded(key.clone());
+                    Some(Ok(key))
+                }
+                Err(err) => {
+                    // Stop after propagating the error
+                    done = true;
+                    Some(Err(err))
+                }
+            }
+        }))
+    }
+
     /// Remove all map entries at a given key prefix
     pub fn remove_all<S>(&self, storage: &mut S, key: &K) -> Result<bool>
     where
@@ -366,6 +512,123 @@ where
         }))
     }
 
+    /// An iterator visiting the key-value elements of the nested collections
+    /// at the keys within the given range, ordered by the storage keys. The
+    /// values are from the inner-most collection. Only the elements within
+    /// the range are read from storage.
+    ///
+    /// The keys are compared by their storage key segments (see
+    /// [`KeySeg::to_db_key`]), which preserve the natural order of unsigned
+    /// integers, block heights and epochs, but not of e.g. addresses or
+    /// strings. A key whose segment is a prefix of another key's segment
+    /// may be ordered after it (e.g. "a" after "a-x"), because the storage
+    /// keys of its nested elements continue with a separator.
+    pub fn range<'iter>(
+        &'iter self,
+        storage: &'iter impl StorageRead,
+        range: impl RangeBounds<K>,
+    ) -> Result<
+        impl Iterator<
+            Item = Result<(
+                <Self as LazyCollection>::SubKey,
+                <Self as LazyCollection>::Value,
+            )>,
+        > + 'iter,
+    > {
+        self.iter_data_range(storage, self.get_data_range(range))
+    }
+
+    /// Like [`LazyMap::range`], but visits the elements in descending order.
+    pub fn range_rev<'iter>(
+        &'iter self,
+        storage: &'iter impl StorageRead,
+        range: impl RangeBounds<K>,
+    ) -> Result<
+        impl Iterator<
+            Item = Result<(
+                <Self as LazyCollection>::SubKey,
+                <Self as LazyCollection>::Value,
+            )>,
+        > + 'iter,
+    > {
+        self.iter_data_range(storage, self.get_data_range(range).rev())
+    }
+
+    /// Like [`LazyMap::iter`], but visits the elements in descending order.
+    pub fn iter_rev<'iter>(
+        &'iter self,
+        storage: &'iter impl StorageRead,
+    ) -> Result<
+        impl Iterator<
+            Item = Result<(
+                <Self as LazyCollection>::SubKey,
+                <Self as LazyCollection>::Value,
+            )>,
+        > + 'iter,
+    > {
+        self.range_rev(storage, ..)
+    }
+
+    /// Returns the inner-most element with the lowest sub-key, if any.
+    pub fn first<S>(
+        &self,
+        storage: &S,
+    ) -> Result<
+        Option<(
+            <Self as LazyCollection>::SubKey,
+            <Self as LazyCollection>::Value,
+        )>,
+    >
+    where
+        S: StorageRead,
+    {
+        self.range(storage, ..)?.next().transpose()
+    }
+
+    /// Returns the inner-most element with the highest sub-key, if any.
+    pub fn last<S>(
+        &self,
+        storage: &S,
+    ) -> Result<
+        Option<(
+            <Self as LazyCollection>::SubKey,
+            <Self as LazyCollection>::Value,
+        )>,
+    >
+    where
+        S: StorageRead,
+    {
+        self.range_rev(storage, ..)?.next().transpose()
+    }
+
+    /// Iterate the elements within the given range of the data sub-keys
+    fn iter_data_range<'iter>(
+        &'iter self,
+        storage: &'iter impl StorageRead,
+        range: KeyRange,
+    ) -> Result<
+        impl Iterator<
+            Item = Result<(
+                <Self as LazyCollection>::SubKey,
+                <Self as LazyCollection>::Value,
+            )>,
+        > + 'iter,
+    > {
+        let iter = crate::iter_prefix_range_with_filter_map(
+            storage,
+            self.get_data_prefix(),
+            range,
+            |key| self.is_data_sub_key(key).then(|| key.clone()),
+        )?;
+        Ok(iter.map(|key_val_res| {
+            let (key, val) = key_val_res?;
+            let sub_key = LazyCollection::is_valid_sub_key(self, &key)?
+                .ok_or(ReadError::UnexpectedlyEmptyStorageKey)
+                .into_storage_result()?;
+            Ok((sub_key, val))
+        }))
+    }
+
     /// Returns whether the map contains no elements.
     pub fn is_empty<S>(&self, storage: &S) -> Result<bool>
     where
@@ -435,6 +698,13 @@ where
         Self::read_key_val(storage, &data_key)
     }
 
+    /// Get the range of the elements' storage keys whose keys are within the
+    /// given bounds
+    fn get_data_range(&self, range: impl RangeBounds<K>) -> KeyRange {
+        let (start, end) = Self::get_key_seg_bounds(&range);
+        KeyRange::sub_keys(&self.get_data_prefix(), start, end)
+    }
+
     /// Update a value at the given key with the given function. If no existing
     /// value exists, the closure's argument will be `None`.
     pub fn update<S, F>(&self, storage: &mut S, key: K, f: F) -> Result<()>
@@ -519,18 +789,80 @@ where
         }))
     }
 
-    // /// Collect the lazy map into an eager map
-    // pub fn collect<M, S>(&self, storage: &S) -> Result<M>
-    // where
-    //     S: StorageRead,
-    //     M: EagerMapFromIter<K, V>,
-    //     K: Eq + Hash + Ord,
-    // {
-    //     let it = self
-    //         .iter(storage)?
-    //         .map(|res| res.expect("Failed to unwrap a lazy map element"));
-    //     Ok(M::from_iter(it))
-    // }
+    /// An iterator visiting the key-value elements with keys within the given
+    /// range, ordered by the storage keys. Only the elements within the range
+    /// are read from storage.
+    ///
+    /// The keys are compared by their storage key segments (see
+    /// [`KeySeg::to_db_key`]), which preserve the natural order of unsigned
+    /// integers, block heights and epochs, but not of e.g. addresses or
+    /// strings.
+    pub fn range<'iter>(
+        &self,
+        storage: &'iter impl StorageRead,
+        range: impl RangeBounds<K>,
+    ) -> Result<impl Iterator<Item = Result<(K, V)>> + 'iter> {
+        Self::iter_data_range(
+            storage,
+            self.get_data_prefix(),
+            self.get_data_range(range),
+        )
+    }
+
+    /// Like [`LazyMap::range`], but visits the elements in descending order.
+    pub fn range_rev<'iter>(
+        &self,
+        storage: &'iter impl StorageRead,
+        range: impl RangeBounds<K>,
+    ) -> Result<impl Iterator<Item = Result<(K, V)>> + 'iter> {
+        Self::iter_data_range(
+            storage,
+            self.get_data_prefix(),
+            self.get_data_range(range).rev(),
+        )
+    }
+
+    /// Like [`LazyMap::iter`], but visits the elements in descending order.
+    pub fn iter_rev<'iter>(
+        &self,
+        storage: &'iter impl StorageRead,
+    ) -> Result<impl Iterator<Item = Result<(K, V)>> + 'iter> {
+        self.range_rev(storage, ..)
+    }
+
+    /// Returns the element with the lowest key, if any.
+    pub fn first<S>(&self, storage: &S) -> Result<Option<(K, V)>>
+    where
+        S: StorageRead,
+    {
+        self.range(storage, ..)?.next().transpose()
+    }
+
+    /// Returns the element with the highest key, if any.
+    pub fn last<S>(&self, storage: &S) -> Result<Option<(K, V)>>
+    where
+        S: StorageRead,
+    {
+        self.range_rev(storage, ..)?.next().transpose()
+    }
+
+    /// Iterate the elements within the given range of the data sub-keys
+    fn iter_data_range(
+        storage: &impl StorageRead,
+        prefix: storage::Key,
+        range: KeyRange,
+    ) -> Result<impl Iterator<Item = Result<(K, V)>> + '_> {
+        let iter = crate::iter_prefix_range(storage, prefix, range)?;
+        Ok(iter.map(|key_val_res| {
+            let (key, val) = key_val_res?;
+            let last_key_seg = key
+                .last()
+                .ok_or(ReadError::UnexpectedlyEmptyStorageKey)
+                .into_storage_result()?;
+            let key = K::parse(last_key_seg.raw()).into_storage_result()?;
+            Ok((key, val))
+        }))
+    }
 
     /// Reads a value from storage
     fn read_key_val<S>(
@@ -556,9 +888,14 @@ where
 
 #[cfg(test)]
 mod test {
+    use std::collections::BTreeSet;
+
     use namada_core::address::{self, Address};
+    use namada_core::chain::Epoch;
+    use namada_core::collections::HashMap;
 
     use super::*;
+    use crate::collections::{LazySet, LazyVec};
     use crate::testing::TestStorage;
 
     #[test]
@@ -959,4 +1296,333 @@ mod test {
         assert_eq!(exp_simple, simple_eager);
         assert_eq!(exp_nested, nested_eager);
     }
+
+    #[test]
+    fn test_lazy_map_range() -> crate::Result<()> {
+        let mut storage = TestStorage::default();
+
+        let key = storage::Key::parse("test").unwrap();
+        let lazy_map = LazyMap::<u64, String>::open(key);
+
+        assert!(lazy_map.first(&storage)?.is_none());
+        assert!(lazy_map.last(&storage)?.is_none());
+        assert!(lazy_map.iter_rev(&storage)?.next().is_none());
+
+        // Insert keys whose ordering differs in decimal and storage encoding
+        let keys = [1_u64, 2, 9, 10, 11, 100, 1000];
+        for key in keys {
+            lazy_map.insert(&mut storage, key, key.to_string())?;
+        }
+
+        fn collect_keys(
+            iter: impl Iterator<Item = crate::Result<(u64, String)>>,
+        ) -> Vec<u64> {
+            iter.map(|res| res.unwrap().0).collect()
+        }
+        assert_eq!(
+            collect_keys(lazy_map.range(&storage, 2..=11)?),
+            vec![2, 9, 10, 11]
+        );
+        assert_eq!(collect_keys(lazy_map.range(&storage, 3..11)?), vec![9, 10]);
+        assert_eq!(
+            collect_keys(lazy_map.range(&storage, 100..)?),
+            vec![100, 1000]
+        );
+        assert_eq!(
+            collect_keys(lazy_map.range(&storage, ..=9)?),
+            vec![1, 2, 9]
+        );
+        assert_eq!(
+            collect_keys(lazy_map.range(&storage, 12..100)?),
+            Vec::<u64>::new()
+        );
+        assert_eq!(
+            collect_keys(lazy_map.range_rev(&storage, 2..=11)?),
+            vec![11, 10, 9, 2]
+        );
+        assert_eq!(
+            collect_keys(lazy_map.range_rev(&storage, ..100)?),
+            vec![11, 10, 9, 2, 1]
+        );
+        assert_eq!(
+            collect_keys(lazy_map.iter_rev(&storage)?),
+            vec![1000, 100, 11, 10, 9, 2, 1]
+        );
+
+        assert_eq!(lazy_map.first(&storage)?, Some((1, "1".to_string())));
+        assert_eq!(lazy_map.last(&storage)?, Some((1000, "1000".to_string())));
+
+        // The latest entry before a given key
+        let latest = lazy_map.range_rev(&storage, ..10)?.next().transpose()?;
+        assert_eq!(latest, Some((9, "9".to_string())));
+
+        Ok(())
+    }
+
+    #[test]
+    #[allow(clippy::arithmetic_side_effects)]
+    fn test_nested_map_range() -> crate::Result<()> {
+        let mut storage = TestStorage::default();
+        let key = storage::Key::parse("testing").unwrap();
+
+        // A nested map from u64 -> u64 -> u64
+        let nested_map = NestedMap::<u64, LazyMap<u64, u64>>::open(key);
+
+        assert!(nested_map.first(&storage)?.is_none());
+        assert!(nested_map.last(&storage)?.is_none());
+
+        for outer in [1_u64, 5, 10] {
+            for inner in [1_u64, 2] {
+                nested_map.at(&outer).insert(
+                    &mut storage,
+                    inner,
+                    outer * 10 + inner,
+                )?;
+            }
+        }
+
+        let values = nested_map
+            .range(&storage, 5..=10)?
+            .map(|res| res.unwrap().1)
+            .collect::<Vec<_>>();
+        assert_eq!(values, vec![51, 52, 101, 102]);
+
+        let values = nested_map
+            .range(&storage, 2..10)?
+            .map(|res| res.unwrap().1)
+            .collect::<Vec<_>>();
+        assert_eq!(values, vec![51, 52]);
+
+        let values = nested_map
+            .range_rev(&storage, ..10)?
+            .map(|res| res.unwrap().1)
+            .collect::<Vec<_>>();
+        assert_eq!(values, vec![52, 51, 12, 11]);
+
+        let values = nested_map
+            .iter_rev(&storage)?
+            .map(|res| res.unwrap().1)
+            .collect::<Vec<_>>();
+        assert_eq!(values, vec![102, 101, 52, 51, 12, 11]);
+
+        let (
+            NestedSubKey::Data {
+                key,
+                nested_sub_key: SubKey::Data(inner_key),
+            },
+            val,
+        ) = nested_map.first(&storage)?.unwrap();
+        assert_eq!((key, inner_key, val), (1, 1, 11));
+
+        let (
+            NestedSubKey::Data {
+                key,
+                nested_sub_key: SubKey::Data(inner_key),
+            },
+            val,
+        ) = nested_map.last(&storage)?.unwrap();
+        assert_eq!((key, inner_key, val), (10, 2, 102));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_lazy_map_range_storage_keys() -> crate::Result<()> {
+        let mut storage = TestStorage::default();
+
+        // Epochs are compared by their storage key segments, which differ
+        // from their decimal representation
+        let key = storage::Key::parse("epochs").unwrap();
+        let epoch_map = LazyMap::<Epoch, u64>::open(key);
+        for epoch in [1_u64, 2, 9, 10, 100] {
+            epoch_map.insert(&mut storage, Epoch(epoch), epoch)?;
+        }
+        let values = epoch_map
+            .range(&storage, Epoch(2)..=Epoch(10))?
+            .map(|res| res.unwrap().1)
+            .collect::<Vec<_>>();
+        assert_eq!(values, vec![2, 9, 10]);
+        let values = epoch_map
+            .range_rev(&storage, ..Epoch(100))?
+            .map(|res| res.unwrap().1)
+            .collect::<Vec<_>>();
+        assert_eq!(values, vec![10, 9, 2, 1]);
+
+        // Addresses are ordered by their storage key segments
+        let key = storage::Key::parse("addresses").unwrap();
+        let addr_map = LazyMap::<Address, u64>::open(key);
+        let mut addrs = vec![
+            address::testing::established_address_1(),
+            address::testing::established_address_2(),
+            address::testing::established_address_3(),
+            address::testing::established_address_4(),
+            address::testing::established_address_5(),
+        ];
+        for (addr, val) in addrs.iter().zip(0_u64..) {
+            addr_map.insert(&mut storage, addr.clone(), val)?;
+        }
+        addrs.sort_by_key(|addr| addr.to_db_key().raw());
+        let keys = addr_map
+            .range(&storage, &addrs[1]..=&addrs[3])?
+            .map(|res| res.unwrap().0)
+            .collect::<Vec<_>>();
+        assert_eq!(keys, addrs[1..=3].to_vec());
+        let keys = addr_map
+            .range_rev(
+                &storage,
+                (Bound::Excluded(&addrs[0]), Bound::Unbounded),
+            )?
+            .map(|res| res.unwrap().0)
+            .collect::<Vec<_>>();
+        assert_eq!(keys, addrs[1..].iter().rev().cloned().collect::<Vec<_>>());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_lazy_map_range_sibling_keys() -> crate::Result<()> {
+        let mut storage = TestStorage::default();
+
+        // The keys extending another key's segment are outside of its bounds
+        let key = storage::Key::parse("test").unwrap();
+        let lazy_map = LazyMap::<String, u64>::open(key);
+        let keys = ["a", "a-x", "a.x", "a0", "b"];
+        for (key, val) in keys.into_iter().zip(0_u64..) {
+            lazy_map.insert(&mut storage, key.to_string(), val)?;
+        }
+
+        fn collect_keys(
+            iter: impl Iterator<Item = crate::Result<(String, u64)>>,
+        ) -> Vec<String> {
+            iter.map(|res| res.unwrap().0).collect()
+        }
+        let a = "a".to_string();
+        assert_eq!(collect_keys(lazy_map.range(&storage, ..=&a)?), vec!["a"]);
+        assert_eq!(collect_keys(lazy_map.range(&storage, &a..=&a)?), vec!["a"]);
+        assert_eq!(
+            collect_keys(
+                lazy_map
+                    .range(&storage, (Bound::Excluded(&a), Bound::Unbounded))?
+            ),
+            vec!["a-x", "a.x", "a0", "b"]
+        );
+
+        // The nested elements of a key are within its bounds, without the
+        // elements of its siblings
+        let key = storage::Key::parse("nested").unwrap();
+        let nested_map = NestedMap::<String, LazyMap<u64, u64>>::open(key);
+        for (key, val) in keys.into_iter().zip(0_u64..) {
+            nested_map
+                .at(&key.to_string())
+                .insert(&mut storage, 1, val)?;
+        }
+        let values = nested_map
+            .range(&storage, &a..=&a)?
+            .map(|res| res.unwrap().1)
+            .collect::<Vec<_>>();
+        assert_eq!(values, vec![0]);
+        let values = nested_map
+            .range(&storage, (Bound::Excluded(&a), Bound::Unbounded))?
+            .map(|res| res.unwrap().1)
+            .collect::<Vec<_>>();
+        assert_eq!(values, vec![3, 4]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_lazy_map_collect_and_extend() -> crate::Result<()> {
+        let mut storage = TestStorage::default();
+
+        let key = storage::Key::parse("test").unwrap();
+        let lazy_map = LazyMap::<u64, String>::open(key);
+
+        let eager = BTreeMap::from([
+            (1, "one".to_string()),
+            (20, "twenty".to_string()),
+            (300, "three hundred".to_string()),
+        ]);
+        lazy_map.extend_from(&mut storage, eager.clone())?;
+
+        let collected: BTreeMap<u64, String> = lazy_map.collect(&storage)?;
+        assert_eq!(collected, eager);
+        let collected = lazy_map.collect::<HashMap<u64, String>>(&storage)?;
+        assert_eq!(collected.len(), 3);
+        assert_eq!(collected.get(&20).unwrap(), "twenty");
+        let collected = lazy_map.collect::<Vec<(u64, String)>>(&storage)?;
+        assert_eq!(collected, eager.into_iter().collect::<Vec<_>>());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_nested_map_collect_and_extend() -> crate::Result<()> {
+        let mut storage = TestStorage::default();
+
+        // A doubly nested map
+        let key = storage::Key::parse("maps").unwrap();
+        let nested_map =
+            NestedMap::<u64, NestedMap<String, LazyMap<u64, u32>>>::open(key);
+        let eager = BTreeMap::from([
+            (
+                1,
+                BTreeMap::from([
+                    ("a".to_string(), BTreeMap::from([(1, 11), (2, 12)])),
+                    ("b".to_string(), BTreeMap::from([(3, 13)])),
+                ]),
+            ),
+            (
+                2,
+                BTreeMap::from([("c".to_string(), BTreeMap::from([(4, 24)]))]),
+            ),
+        ]);
+        assert!(
+            nested_map
+                .collect::<BTreeMap<u64, BTreeMap<String, BTreeMap<u64, u32>>>>(
+                    &storage
+                )?
+                .is_empty()
+        );
+        nested_map.extend_from(&mut storage, eager.clone())?;
+        assert_eq!(
+            nested_map.at(&1).at(&"a".to_string()).get(&storage, &2)?,
+            Some(12)
+        );
+        assert_eq!(
+            nested_map
+                .keys(&storage)?
+                .collect::<crate::Result<Vec<_>>>()?,
+            vec![1, 2]
+        );
+        let collected: BTreeMap<u64, BTreeMap<String, BTreeMap<u64, u32>>> =
+            nested_map.collect(&storage)?;
+        assert_eq!(collected, eager);
+        assert_eq!(nested_map.collect_map(&storage)?, eager);
+
+        // A map of sets
+        let key = storage::Key::parse("sets").unwrap();
+        let nested_set = NestedMap::<u64, LazySet<u64>>::open(key);
+        let eager = BTreeMap::from([
+            (5, BTreeSet::from([1, 2, 3])),
+            (6, BTreeSet::from([4])),
+        ]);
+        nested_set.extend_from(&mut storage, eager.clone())?;
+        let collected: BTreeMap<u64, BTreeSet<u64>> =
+            nested_set.collect(&storage)?;
+        assert_eq!(collected, eager);
+        assert_eq!(nested_set.collect_map(&storage)?, eager);
+
+        // A map of vectors
+        let key = storage::Key::parse("vecs").unwrap();
+        let nested_vec = NestedMap::<u64, LazyVec<u32>>::open(key);
+        let eager = HashMap::<u64, Vec<u32>>::from_iter([
+            (7, vec![3, 2, 1]),
+            (8, vec![0]),
+        ]);
+        nested_vec.extend_from(&mut storage, eager.clone())?;
+        let collected: HashMap<u64, Vec<u32>> = nested_vec.collect(&storage)?;
+        assert_eq!(collected, eager);
+
+        Ok(())
+    }
 }
@@ -0,0 +1,384 @@
+//! Lazy priority queue.
+
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+use namada_core::arith::checked;
+use namada_core::borsh::{BorshDeserialize, BorshSerialize};
+use namada_core::storage::{self, DbKeySeg, KeySeg};
+use thiserror::Error;
+
+use super::super::Result;
+use super::{LazyCollection, ReadError};
+use crate::{KeyRange, ResultExt, StorageRead, StorageWrite};
+
+/// Subkey corresponding to the data elements of the LazyPriorityQueue
+pub const DATA_SUBKEY: &str = "data";
+
+/// Using `u64` for the sequence numbers of elements with the same priority
+pub type Seq = u64;
+
+/// Lazy priority queue.
+///
+/// This can be used as an alternative to `std::collections::BinaryHeap`, except
+/// that the elements are popped in the ascending order of their priorities. In
+/// the lazy priority queue, the elements do not reside in memory but are
+/// instead read and written to storage sub-keys of the storage `key` used to
+/// construct the queue.
+///
+/// In the [`LazyPriorityQueue`], the type of priority `K` can be anything that
+/// implements [`storage::KeySeg`] and its ordering is the ordering of its
+/// storage key segments, which is the same as the natural order for unsigned
+/// integers and epochs. Elements with the same priority are popped in the order
+/// in which they were pushed.
+#[derive(Debug)]
+pub struct LazyPriorityQueue<K, T> {
+    key: storage::Key,
+    phantom_k: PhantomData<K>,
+    phantom_t: PhantomData<T>,
+}
+
+/// Possible sub-keys of a [`LazyPriorityQueue`]
+#[derive(Clone, Debug, PartialEq)]
+pub enum SubKey<K> {
+    /// Data sub-key, further sub-keyed by its priority and sequence number
+    Data {
+        /// The priority of the element
+        priority: K,
+        /// The sequence number of the element within its priority
+        seq: Seq,
+    },
+}
+
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum ValidationError {
+    #[error("Invalid storage key {0}")]
+    InvalidSubKey(storage::Key),
+}
+
+impl<K, T> LazyCollection for LazyPriorityQueue<K, T>
+where
+    K: storage::KeySeg + Debug,
+    T: BorshSerialize + BorshDeserialize + 'static + Debug,
+{
+    type SubKey = SubKey<K>;
+    type Value = T;
+
+    /// Create or use an existing priority queue with the given storage `key`.
+    fn open(key: storage::Key) -> Self {
+        Self {
+            key,
+            phantom_k: PhantomData,
+            phantom_t: PhantomData,
+        }
+    }
+
+    fn is_valid_sub_key(
+        &self,
+        key: &storage::Key,
+    ) -> crate::Result<Option<Self::SubKey>> {
+        let suffix = match key.split_prefix(&self.key) {
+            None => {
+                // not matching prefix, irrelevant
+                return Ok(None);
+            }
+            Some(None) => {
+                // no suffix, invalid
+                return Err(ValidationError::InvalidSubKey(key.clone()))
+                    .into_storage_result();
+            }
+            Some(Some(suffix)) => suffix,
+        };
+
+        // A helper to validate the priority and sequence number key segments
+        let validate_sub_key = |raw_priority, raw_seq| match (
+            storage::KeySeg::parse(raw_priority),
+            storage::KeySeg::parse(raw_seq),
+        ) {
+            (Ok(priority), Ok(seq)) => Ok(Some(SubKey::Data { priority, seq })),
+            _ => Err(ValidationError::InvalidSubKey(key.clone()))
+                .into_storage_result(),
+        };
+
+        // Match the suffix against expected sub-keys
+        match &suffix.segments[..] {
+            [
+                DbKeySeg::StringSeg(sub_a),
+                DbKeySeg::StringSeg(sub_b),
+                DbKeySeg::StringSeg(sub_c),
+            ] if sub_a == DATA_SUBKEY => {
+                validate_sub_key(sub_b.clone(), sub_c.clone())
+            }
+            [
+                DbKeySeg::StringSeg(sub_a),
+                DbKeySeg::AddressSeg(sub_b),
+                DbKeySeg::StringSeg(sub_c),
+            ] if sub_a == DATA_SUBKEY => {
+                validate_sub_key(sub_b.raw(), sub_c.clone())
+            }
+            _ => Err(ValidationError::InvalidSubKey(key.clone()))
+                .into_storage_result(),
+        }
+    }
+
+    fn is_data_sub_key(&self, key: &storage::Key) -> bool {
+        matches!(self.is_valid_sub_key(key), Ok(Some(_)))
+    }
+}
+
+// Generic `LazyPriorityQueue` methods that require no bounds on values `T`
+impl<K, T> LazyPriorityQueue<K, T>
+where
+    K: storage::KeySeg,
+{
+    /// Get the prefix of queue's elements storage
+    fn get_data_prefix(&self) -> storage::Key {
+        self.key.push(&DATA_SUBKEY.to_owned()).unwrap()
+    }
+
+    /// Get the prefix of queue's elements storage with the given priority
+    fn get_priority_prefix(&self, priority: &K) -> storage::Key {
+        self.get_data_prefix().push(&priority.to_db_key()).unwrap()
+    }
+
+    /// Get the sub-key of an element with the given priority and sequence
+    /// number
+    pub fn get_data_key(&self, priority: &K, seq: Seq) -> storage::Key {
+        self.get_priority_prefix(priority).push(&seq).unwrap()
+    }
+
+    /// Returns `true` if the queue contains no elements.
+    pub fn is_empty<S>(&self, storage: &S) -> Result<bool>
+    where
+        S: StorageRead,
+    {
+        let mut iter =
+            crate::iter_prefix_bytes(storage, self.get_data_prefix())?;
+        Ok(iter.next().is_none())
+    }
+
+    /// Reads the number of elements in the queue.
+    ///
+    /// Note that this function shouldn't be used in transactions and VPs code
+    /// on unbounded queues to avoid gas usage increasing with the length of
+    /// the queue.
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len<S>(&self, storage: &S) -> Result<u64>
+    where
+        S: StorageRead,
+    {
+        let iter = crate::iter_prefix_bytes(storage, self.get_data_prefix())?;
+        iter.count().try_into().into_storage_result()
+    }
+
+    /// Parse the priority from an element's storage key
+    fn parse_priority(key: &storage::Key) -> Result<K> {
+        let raw_priority = key
+            .segments
+            .iter()
+            .rev()
+            .nth(1)
+            .ok_or(ReadError::UnexpectedlyEmptyStorageKey)
+            .into_storage_result()?;
+        K::parse(raw_priority.raw()).into_storage_result()
+    }
+}
+
+// `LazyPriorityQueue` methods with borsh encoded values `T`
+impl<K, T> LazyPriorityQueue<K, T>
+where
+    K: storage::KeySeg,
+    T: BorshSerialize + BorshDeserialize + 'static,
+{
+    /// Pushes an element with the given priority into the queue. The element
+    /// is placed after any elements that already have the same priority.
+    pub fn push<S>(&self, storage: &mut S, priority: K, val: T) -> Result<()>
+    where
+        S: StorageWrite + StorageRead,
+    {
+        let prefix = self.get_priority_prefix(&priority);
+        // Find the sequence number of the last element with the same priority
+        let last = crate::iter_prefix_range_bytes(
+            storage,
+            prefix.clone(),
+            KeyRange::default().rev(),
+        )?
+        .next()
+        .transpose()?;
+        let seq = match last {
+            Some((key, _)) => {
+                let last_seq: Seq = key
+                    .last()
+                    .ok_or(ReadError::UnexpectedlyEmptyStorageKey)
+                    .into_storage_result()
+                    .and_then(|seg| {
+                        Seq::parse(seg.raw()).into_storage_result()
+                    })?;
+                checked!(last_seq + 1)?
+            }
+            None => 0,
+        };
+        storage.write(&prefix.push(&seq).unwrap(), val)
+    }
+
+    /// Read the element with the lowest priority, without removing it from
+    /// the queue.
+    pub fn peek<S>(&self, storage: &S) -> Result<Option<(K, T)>>
+    where
+        S: StorageRead,
+    {
+        Ok(self
+            .peek_entry(storage)?
+            .map(|(_key, priority, val)| (priority, val)))
+    }
+
+    /// Removes the element with the lowest priority from the queue and returns
+    /// it together with its priority, or `Ok(None)` if the queue is empty.
+    pub fn pop<S>(&self, storage: &mut S) -> Result<Option<(K, T)>>
+    where
+        S: StorageWrite + StorageRead,
+    {
+        match self.peek_entry(storage)? {
+            Some((key, priority, val)) => {
+                storage.delete(&key)?;
+                Ok(Some((priority, val)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// An iterator visiting all elements in the order in which they would be
+    /// popped. The iterator element type is `Result<(K, T)>`, because
+    /// iterator's call to `next` may fail with e.g. out of gas or data
+    /// decoding error.
+    ///
+    /// Note that this function shouldn't be used in transactions and VPs code
+    /// on unbounded queues to avoid gas usage increasing with the length of
+    /// the queue.
+    pub fn iter<'iter>(
+        &self,
+        storage: &'iter impl StorageRead,
+    ) -> Result<impl Iterator<Item = Result<(K, T)>> + 'iter> {
+        let iter = crate::iter_prefix(storage, self.get_data_prefix())?;
+        Ok(iter.map(|key_val_res| {
+            let (key, val) = key_val_res?;
+            let priority = Self::parse_priority(&key)?;
+            Ok((priority, val))
+        }))
+    }
+
+    /// Read the first element in the queue together with its storage key
+    fn peek_entry<S>(&self, storage: &S) -> Result<Option<(storage::Key, K, T)>>
+    where
+        S: StorageRead,
+    {
+        let first = crate::iter_prefix(storage, self.get_data_prefix())?
+            .next()
+            .transpose()?;
+        match first {
+            Some((key, val)) => {
+                let priority = Self::parse_priority(&key)?;
+                Ok(Some((key, priority, val)))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use namada_core::address::{self, Address};
+
+    use super::*;
+    use crate::testing::TestStorage;
+
+    #[test]
+    fn test_lazy_priority_queue_basics() -> crate::Result<()> {
+        let mut storage = TestStorage::default();
+
+        let key = storage::Key::parse("test").unwrap();
+        let queue = LazyPriorityQueue::<u64, String>::open(key);
+
+        // The queue should be empty at first
+        assert!(queue.is_empty(&storage)?);
+        assert_eq!(queue.len(&storage)?, 0);
+        assert!(queue.peek(&storage)?.is_none());
+        assert!(queue.pop(&mut storage)?.is_none());
+
+        // Push elements out of order, with some sharing a priority
+        queue.push(&mut storage, 10, "a".to_string())?;
+        queue.push(&mut storage, 2, "b".to_string())?;
+        queue.push(&mut storage, 10, "c".to_string())?;
+        queue.push(&mut storage, 9, "d".to_string())?;
+        queue.push(&mut storage, 2, "e".to_string())?;
+        assert_eq!(queue.len(&storage)?, 5);
+
+        assert_eq!(
+            queue.iter(&storage)?.collect::<Result<Vec<_>>>()?,
+            vec![
+                (2, "b".to_string()),
+                (2, "e".to_string()),
+                (9, "d".to_string()),
+                (10, "a".to_string()),
+                (10, "c".to_string()),
+            ]
+        );
+        assert_eq!(queue.peek(&storage)?, Some((2, "b".to_string())));
+
+        // Pop elements in the order of priorities
+        assert_eq!(queue.pop(&mut storage)?, Some((2, "b".to_string())));
+        assert_eq!(queue.pop(&mut storage)?, Some((2, "e".to_string())));
+        assert_eq!(queue.pop(&mut storage)?, Some((9, "d".to_string())));
+
+        // A new element with the same priority goes after existing ones
+        queue.push(&mut storage, 10, "f".to_string())?;
+        assert_eq!(queue.pop(&mut storage)?, Some((10, "a".to_string())));
+        assert_eq!(queue.pop(&mut storage)?, Some((10, "c".to_string())));
+        assert_eq!(queue.pop(&mut storage)?, Some((10, "f".to_string())));
+
+        assert!(queue.is_empty(&storage)?);
+        assert!(queue.pop(&mut storage)?.is_none());
+
+        let storage_key = queue.get_data_key(&10, 1);
+        assert_eq!(
+            queue.is_valid_sub_key(&storage_key).unwrap(),
+            Some(SubKey::Data {
+                priority: 10,
+                seq: 1
+            })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_lazy_priority_queue_with_addr_priority() -> crate::Result<()> {
+        let mut storage = TestStorage::default();
+
+        let key = storage::Key::parse("test").unwrap();
+        let queue = LazyPriorityQueue::<Address, u64>::open(key);
+
+        let addr = address::testing::established_address_1();
+        let addr2 = address::testing::established_address_2();
+        assert!(addr < addr2, "sanity check - this influences the pop order");
+
+        queue.push(&mut storage, addr2.clone(), 1)?;
+        queue.push(&mut storage, addr.clone(), 2)?;
+
+        let storage_key = queue.get_data_key(&addr, 0);
+        assert_eq!(
+            queue.is_valid_sub_key(&storage_key).unwrap(),
+            Some(SubKey::Data {
+                priority: addr.clone(),
+                seq: 0
+            })
+        );
+
+        assert_eq!(queue.pop(&mut storage)?, Some((addr, 2)));
+        assert_eq!(queue.pop(&mut storage)?, Some((addr2, 1)));
+        assert!(queue.is_empty(&storage)?);
+
+        Ok(())
+    }
+}
@@ -1,14 +1,17 @@
 //! Lazy set.
 
+use std::collections::BTreeSet;
 use std::fmt::Debug;
 use std::marker::PhantomData;
+use std::ops::RangeBounds;
 
 use namada_core::storage::{self, DbKeySeg, KeySeg};
 use thiserror::Error;
 
 use super::super::Result;
-use super::{LazyCollection, ReadError};
-use crate::{ResultExt, StorageRead, StorageWrite};
+use super::lazy_map::Collectable;
+use super::{EagerCollection, LazyCollection, ReadError};
+use crate::{KeyRange, ResultExt, StorageRead, StorageWrite};
 
 /// A lazy set.
 ///
@@ -99,6 +102,47 @@ where
     }
 }
 
+impl<K, M> EagerCollection<LazySet<K>> for M
+where
+    K: storage::KeySeg,
+    M: FromIterator<K> + IntoIterator<Item = K>,
+{
+    fn collect_from<S>(lazy: &LazySet<K>, storage: &S) -> crate::Result<Self>
+    where
+        S: StorageRead,
+    {
+        lazy.iter(storage)?.collect()
+    }
+
+    fn extend_into<S>(
+        self,
+        lazy: &LazySet<K>,
+        storage: &mut S,
+    ) -> crate::Result<()>
+    where
+        S: StorageRead + StorageWrite,
+    {
+        for key in self {
+            lazy.insert(storage, key)?;
+        }
+        Ok(())
+    }
+}
+
+impl<K> Collectable for LazySet<K>
+where
+    K: storage::KeySeg + Ord,
+{
+    type Collected = BTreeSet<K>;
+
+    fn collect_map<S>(&self, storage: &S) -> crate::Result<Self::Collected>
+    where
+        S: StorageRead,
+    {
+        self.collect(storage)
+    }
+}
+
 // `LazySet` methods
 impl<K> LazySet<K>
 where
@@ -212,11 +256,124 @@ where
             Ok(key)
         }))
     }
+
+    /// An iterator visiting the keys within the given range, ordered by the
+    /// storage keys. Only the keys within the range are read from storage.
+    ///
+    /// The keys are compared by their storage key segments (see
+    /// [`KeySeg::to_db_key`]), which preserve the natural order of unsigned
+    /// integers, block heights and epochs, but not of e.g. addresses or
+    /// strings.
+    pub fn range<'iter>(
+        &self,
+        storage: &'iter impl StorageRead,
+        range: impl RangeBounds<K>,
+    ) -> Result<impl Iterator<Item = Result<K>> + 'iter> {
+        Self::iter_key_range(
+            storage,
+            self.key.clone(),
+            self.get_key_range(range),
+        )
+    }
+
+    /// Like [`LazySet::range`], but visits the keys in descending order.
+    pub fn range_rev<'iter>(
+        &self,
+        storage: &'iter impl StorageRead,
+        range: impl RangeBounds<K>,
+    ) -> Result<impl Iterator<Item = Result<K>> + 'iter> {
+        Self::iter_key_range(
+            storage,
+            self.key.clone(),
+            self.get_key_range(range).rev(),
+        )
+    }
+
+    /// Like [`LazySet::iter`], but visits the keys in descending order.
+    pub fn iter_rev<'iter>(
+        &self,
+        storage: &'iter impl StorageRead,
+    ) -> Result<impl Iterator<Item = Result<K>> + 'iter> {
+        self.range_rev(storage, ..)
+    }
+
+    /// Returns the lowest key in the set, if any.
+    pub fn first<S>(&self, storage: &S) -> Result<Option<K>>
+    where
+        S: StorageRead,
+    {
+        self.range(storage, ..)?.next().transpose()
+    }
+
+    /// Returns the highest key in the set, if any.
+    pub fn last<S>(&self, storage: &S) -> Result<Option<K>>
+    where
+        S: StorageRead,
+    {
+        self.range_rev(storage, ..)?.next().transpose()
+    }
+
+    /// Get the range of the storage sub-keys whose keys are within the given
+    /// bounds
+    fn get_key_range(&self, range: impl RangeBounds<K>) -> KeyRange {
+        let key_seg = |key: &K| key.to_db_key().raw();
+        KeyRange::sub_keys(
+            &self.key,
+            range.start_bound().map(key_seg),
+            range.end_bound().map(key_seg),
+        )
+    }
+
+    /// Iterate the keys within the given range of the storage sub-keys
+    fn iter_key_range(
+        storage: &impl StorageRead,
+        prefix: storage::Key,
+        range: KeyRange,
+    ) -> Result<impl Iterator<Item = Result<K>> + '_> {
+        let iter = crate::iter_prefix_range(storage, prefix, range)?;
+        Ok(iter.map(|key_val_res| {
+            let (key, ()) = key_val_res?;
+            let last_key_seg = key
+                .last()
+                .ok_or(ReadError::UnexpectedlyEmptyStorageKey)
+                .into_storage_result()?;
+            let key = K::parse(last_key_seg.raw()).into_storage_result()?;
+            Ok(key)
+        }))
+    }
+
+    /// Collect all the elements of the set into an eager in-memory
+    /// collection, e.g. a `BTreeSet<K>`.
+    ///
+    /// Note that this function shouldn't be used in transactions and VPs code
+    /// on unbounded sets to avoid gas usage increasing with the length of
+    /// the set.
+    pub fn collect<M>(&self, storage: &impl StorageRead) -> Result<M>
+    where
+        M: EagerCollection<Self>,
+    {
+        M::collect_from(self, storage)
+    }
+
+    /// Write all the elements of an eager in-memory collection into the
+    /// set. Existing
+    /// keys are kept.
+    pub fn extend_from<M>(
+        &self,
+        storage: &mut (impl StorageRead + StorageWrite),
+        eager: M,
+    ) -> Result<()>
+    where
+        M: EagerCollection<Self>,
+    {
+        eager.extend_into(self, storage)
+    }
 }
 
 #[cfg(test)]
 mod test {
     use namada_core::address::{self, Address};
+    use namada_core::chain::Epoch;
 
     use super::*;
     use crate::testing::TestStorage;
@@ -342,4 +499,89 @@ mod test {
 
         Ok(())
     }
+
+    #[test]
+    fn test_lazy_set_range() -> crate::Result<()> {
+        let mut storage = TestStorage::default();
+
+        let key = storage::Key::parse("test").unwrap();
+        let lazy_set = LazySet::<u64>::open(key);
+
+        assert!(lazy_set.first(&storage)?.is_none());
+        assert!(lazy_set.last(&storage)?.is_none());
+
+        for key in [3_u64, 7, 20, 300] {
+            lazy_set.insert(&mut storage, key)?;
+        }
+
+        let keys = lazy_set
+            .range(&storage, 7..300)?
+            .collect::<crate::Result<Vec<_>>>()?;
+        assert_eq!(keys, vec![7, 20]);
+
+        let keys = lazy_set
+            .range(&storage, 4..=300)?
+            .collect::<crate::Result<Vec<_>>>()?;
+        assert_eq!(keys, vec![7, 20, 300]);
+
+        let keys = lazy_set
+            .range_rev(&storage, ..20)?
+            .collect::<crate::Result<Vec<_>>>()?;
+        assert_eq!(keys, vec![7, 3]);
+
+        let keys = lazy_set
+            .iter_rev(&storage)?
+            .collect::<crate::Result<Vec<_>>>()?;
+        assert_eq!(keys, vec![300, 20, 7, 3]);
+
+        assert_eq!(lazy_set.first(&storage)?, Some(3));
+        assert_eq!(lazy_set.last(&storage)?, Some(300));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_lazy_set_range_storage_keys() -> crate::Result<()> {
+        let mut storage = TestStorage::default();
+
+        let key = storage::Key::parse("epochs").unwrap();
+        let epoch_set = LazySet::<Epoch>::open(key);
+        for epoch in [1_u64, 2, 9, 10, 100] {
+            epoch_set.insert(&mut storage, Epoch(epoch))?;
+        }
+        let keys = epoch_set
+            .range(&storage, Epoch(2)..=Epoch(10))?
+            .collect::<crate::Result<Vec<_>>>()?;
+        assert_eq!(keys, vec![Epoch(2), Epoch(9), Epoch(10)]);
+
+        let key = storage::Key::parse("addresses").unwrap();
+        let addr_set = LazySet::<Address>::open(key);
+        let mut addrs = vec![
+            address::testing::established_address_1(),
+            address::testing::established_address_2(),
+            address::testing::established_address_3(),
+        ];
+        for addr in &addrs {
+            addr_set.insert(&mut storage, addr.clone())?;
+        }
+        addrs.sort_by_key(|addr| addr.to_db_key().raw());
+        let keys = addr_set
+            .range(&storage, &addrs[1]..)?
+            .collect::<crate::Result<Vec<_>>>()?;
+        assert_eq!(keys, addrs[1..].to_vec());
+
+        // The keys extending another key's segment are outside of its bounds
+        let key = storage::Key::parse("strings").unwrap();
+        let string_set = LazySet::<String>::open(key);
+        for key in ["a", "a-x", "a.x", "a0", "b"] {
+            string_set.insert(&mut storage, key.to_string())?;
+        }
+        let a = "a".to_string();
+        let keys = string_set
+            .range(&storage, ..=&a)?
+            .collect::<crate::Result<Vec<_>>>()?;
+        assert_eq!(keys, vec!["a"]);
+
+        Ok(())
+    }
 }
@@ -9,7 +9,8 @@ use namada_core::storage::{self, DbKeySeg, KeySeg};
 use thiserror::Error;
 
 use super::super::Result;
-use super::LazyCollection;
+use super::lazy_map::Collectable;
+use super::{EagerCollection, LazyCollection};
 use crate::{ResultExt, StorageRead, StorageWrite};
 
 /// Subkey pointing to the length of the LazyVec
@@ -145,6 +146,47 @@ where
     }
 }
 
+impl<T, M> EagerCollection<LazyVec<T>> for M
+where
+    T: BorshSerialize + BorshDeserialize + 'static + Debug,
+    M: FromIterator<T> + IntoIterator<Item = T>,
+{
+    fn collect_from<S>(lazy: &LazyVec<T>, storage: &S) -> crate::Result<Self>
+    where
+        S: StorageRead,
+    {
+        lazy.iter(storage)?.collect()
+    }
+
+    fn extend_into<S>(
+        self,
+        lazy: &LazyVec<T>,
+        storage: &mut S,
+    ) -> crate::Result<()>
+    where
+        S: StorageRead + StorageWrite,
+    {
+        for val in self {
+            lazy.push(storage, val)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T> Collectable for LazyVec<T>
+where
+    T: BorshSerialize + BorshDeserialize + 'static + Debug,
+{
+    type Collected = Vec<T>;
+
+    fn collect_map<S>(&self, storage: &S) -> crate::Result<Self::Collected>
+    where
+        S: StorageRead,
+    {
+        self.collect(storage)
+    }
+}
+
 // Generic `LazyVec` methods that require no bounds on values `T`
 impl<T> LazyVec<T> {
     /// Reads the number of elements in the vector.
@@ -281,6 +323,33 @@ where
             Ok(val)
         }))
     }
+
+    /// Collect all the elements of the vector into an eager in-memory
+    /// collection, e.g. a `Vec<T>`.
+    ///
+    /// Note that this function shouldn't be used in transactions and VPs code
+    /// on unbounded vectors to avoid gas usage increasing with the length of
+    /// the vector.
+    pub fn collect<M>(&self, storage: &impl StorageRead) -> Result<M>
+    where
+        M: EagerCollection<Self>,
+    {
+        M::collect_from(self, storage)
+    }
+
+    /// Write all the elements of an eager in-memory collection into the
+    /// vector. The elements
+    /// are pushed to the back of the vector.
+    pub fn extend_from<M>(
+        &self,
+        storage: &mut (impl StorageRead + StorageWrite),
+        eager: M,
+    ) -> Result<()>
+    where
+        M: EagerCollection<Self>,
+    {
+        eager.extend_into(self, storage)
+    }
 }
 
 #[cfg(test)]
@@ -9,20 +9,29 @@
 //! just receive the storage sub-keys that have experienced changes without
 //! having to check any of the unchanged elements.
 
+use std::collections::BTreeMap;
 use std::fmt::Debug;
+use std::hash::{BuildHasher, Hash};
 
 use namada_core::borsh::BorshDeserialize;
+use namada_core::collections::HashMap;
 use thiserror::Error;
 
+pub mod lazy_deque;
 pub mod lazy_map;
+pub mod lazy_priority_queue;
 pub mod lazy_set;
 pub mod lazy_vec;
 
+pub use lazy_deque::LazyDeque;
 pub use lazy_map::LazyMap;
+pub use lazy_priority_queue::LazyPriorityQueue;
 pub use lazy_set::LazySet;
 pub use lazy_vec::LazyVec;
 use namada_core::storage;
 
+use crate::{StorageRead, StorageWrite};
+
 #[allow(missing_docs)]
 #[derive(Error, Debug)]
 pub enum ReadError {
@@ -74,3 +83,50 @@ pub trait LazyCollection {
     /// vec, only the element data sub-keys would return `true`.
     fn is_data_sub_key(&self, key: &storage::Key) -> bool;
 }
+
+/// An eager in-memory collection that a lazy collection `L` can be collected
+/// into and that can be written back into `L`.
+///
+/// This is implemented for the standard collections that match the lazy
+/// collection's elements, e.g. `BTreeMap<K, V>` or `HashMap<K, V>` for a
+/// `LazyMap<K, V>`, and recursively for nested lazy maps, e.g.
+/// `BTreeMap<K, BTreeSet<V>>` for a `NestedMap<K, LazySet<V>>`.
+pub trait EagerCollection<L>: Sized {
+    /// Read all the elements of the lazy collection into memory.
+    ///
+    /// Note that this shouldn't be used in transactions and VPs code on
+    /// unbounded collections to avoid gas usage increasing with the length of
+    /// the collection.
+    fn collect_from<S>(lazy: &L, storage: &S) -> crate::Result<Self>
+    where
+        S: StorageRead;
+
+    /// Write all the elements into the lazy collection. Existing elements with
+    /// the same keys are overwritten, while the other elements are kept.
+    fn extend_into<S>(self, lazy: &L, storage: &mut S) -> crate::Result<()>
+    where
+        S: StorageRead + StorageWrite;
+}
+
+/// An eager in-memory map. Used to determine the type of the values that the
+/// nested lazy collections of a nested `LazyMap` are collected into.
+pub trait EagerMap {
+    /// The type of the keys
+    type Key;
+    /// The type of the values
+    type Value;
+}
+
+impl<K, V> EagerMap for BTreeMap<K, V> {
+    type Key = K;
+    type Value = V;
+}
+
+impl<K, V, S> EagerMap for HashMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher + Default,
+{
+    type Key = K;
+    type Value = V;
+}
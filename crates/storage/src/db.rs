@@ -1,8 +1,10 @@
+use std::collections::BTreeMap;
 use std::fmt::Debug;
 use std::num::TryFromIntError;
 
 use itertools::Either;
 use namada_core::address::EstablishedAddressGen;
+use namada_core::arith::checked;
 use namada_core::chain::{BlockHeader, BlockHeight, Epoch, Epochs};
 use namada_core::hash::{Error as HashError, Hash};
 use namada_core::storage::{BlockResults, DbColFam, EthEventsQueue, Key};
@@ -17,7 +19,7 @@ use regex::Regex;
 use thiserror::Error;
 
 use crate::conversion_state::ConversionState;
-use crate::types::CommitOnlyData;
+use crate::types::{CommitOnlyData, KeyRange};
 
 #[allow(missing_docs)]
 #[derive(Error, Debug)]
@@ -44,6 +46,8 @@ pub enum Error {
     NumConversionError(#[from] TryFromIntError),
     #[error("Arithmetic {0}")]
     Arith(#[from] arith::Error),
+    #[error("Cannot roll back to height {height}: {reason}")]
+    CannotRollback { height: BlockHeight, reason: String },
 }
 
 /// A result of a function that may fail
@@ -115,6 +119,18 @@ pub struct BlockStateWrite<'a> {
     pub commit_only_data: &'a CommitOnlyData,
 }
 
+/// A change of an account subspace key-val in a block, as recorded in its
+/// diffs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubspaceChange {
+    /// The changed key
+    pub key: Key,
+    /// The value before the block, if any
+    pub old_value: Option<Vec<u8>>,
+    /// The value after the block, `None` if the key was deleted
+    pub new_value: Option<Vec<u8>>,
+}
+
 /// A database backend.
 pub trait DB: Debug {
     /// A DB's cache
@@ -207,6 +223,14 @@ pub trait DB: Debug {
         is_old: bool,
     ) -> Result<Option<Vec<u8>>>;
 
+    /// Read the changes of the account subspace in the block at the given
+    /// height, sorted by their keys. These include the changes of the keys
+    /// whose diffs are not persisted, as long as they are still kept.
+    fn read_subspace_changeset(
+        &self,
+        height: BlockHeight,
+    ) -> Result<Vec<SubspaceChange>>;
+
     /// Write the value with the given height and account subspace key to the
     /// DB. Returns the size difference from previous value, if any, or the
     /// size of the value otherwise.
@@ -285,13 +309,23 @@ pub trait DB: Debug {
         batch: &mut Self::WriteBatch,
     ) -> Result<()>;
 
-    /// Prune non-persisted diffs that are only kept for one block for rollback
+    /// Prune non-persisted diffs that are only kept for the blocks of the
+    /// rollback window, at and below the given height
     fn prune_non_persisted_diffs(
         &mut self,
         batch: &mut Self::WriteBatch,
         height: BlockHeight,
     ) -> Result<()>;
 
+    /// Prune the data kept to roll back the state of the blocks below the
+    /// given height and of the epochs below the given epoch
+    fn prune_rollback_journal(
+        &mut self,
+        batch: &mut Self::WriteBatch,
+        oldest_height: BlockHeight,
+        oldest_epoch: Epoch,
+    ) -> Result<()>;
+
     /// Overwrite a new value in storage, taking into
     /// account values stored at a previous height
     fn overwrite_entry(
@@ -303,6 +337,33 @@ pub trait DB: Debug {
         persist_diffs: bool,
     ) -> Result<()>;
 
+    /// Read an entry of the events log from the DB
+    fn read_event_entry(&self, key: &Key) -> Result<Option<Vec<u8>>>;
+
+    /// Batch write an entry of the events log to the DB
+    fn batch_write_event_entry(
+        &self,
+        batch: &mut Self::WriteBatch,
+        key: &Key,
+        value: impl AsRef<[u8]>,
+    ) -> Result<()>;
+
+    /// Batch delete an entry of the events log from the DB
+    fn batch_delete_event_entry(
+        &self,
+        batch: &mut Self::WriteBatch,
+        key: &Key,
+    ) -> Result<()>;
+
+    /// Batch delete the entries of the events log from the DB whose keys are
+    /// in the range `[from, to)`
+    fn batch_delete_event_range(
+        &self,
+        batch: &mut Self::WriteBatch,
+        from: &Key,
+        to: &Key,
+    ) -> Result<()>;
+
     /// Get an instance of DB migrator
     fn migrator() -> Self::Migrator;
 
@@ -367,6 +428,19 @@ pub trait DBIter<'iter> {
     /// ordered by the storage keys.
     fn iter_prefix(&'iter self, prefix: Option<&Key>) -> Self::PrefixIter;
 
+    /// WARNING: This only works for values that have been committed to DB.
+    /// To be able to see values written or deleted, but not yet committed,
+    /// use the `StorageWithWriteLog`.
+    ///
+    /// Read account subspace key value pairs with the given prefix and within
+    /// the given range from the DB, ordered by the storage keys in the
+    /// range's direction.
+    fn iter_prefix_range(
+        &'iter self,
+        prefix: Option<&Key>,
+        range: &KeyRange,
+    ) -> Self::PrefixIter;
+
     /// WARNING: This only works for values that have been committed to DB.
     /// To be able to see values written or deleted, but not yet committed,
     /// use the `StorageWithWriteLog`.
@@ -398,6 +472,80 @@ pub trait DBIter<'iter> {
 
     /// Read replay protection storage from the current bucket
     fn iter_current_replay_protection(&'iter self) -> Self::PrefixIter;
+
+    /// Read events log key value pairs with the given prefix from the DB,
+    /// ordered by the keys.
+    fn iter_event_entries(&'iter self, prefix: &Key) -> Self::PrefixIter;
+}
+
+/// Read account subspace key value pairs with the given prefix and within the
+/// given range from the DB at the given height, ordered by the storage keys in
+/// the range's direction. The values are rewound from the `last_height` with
+/// the diffs of the blocks after the given height, so the keys whose diffs are
+/// not persisted are read with their latest values.
+pub fn iter_prefix_with_height<D>(
+    db: &D,
+    prefix: Option<&Key>,
+    range: &KeyRange,
+    height: BlockHeight,
+    last_height: BlockHeight,
+) -> Result<HistoricPrefixIter>
+where
+    D: for<'iter> DBIter<'iter>,
+{
+    let mut values: BTreeMap<String, Vec<u8>> = db
+        .iter_prefix_range(prefix, range)
+        .map(|(key, val, _gas)| (key, val))
+        .collect();
+    // Rewind the values with the diffs of the blocks after the given height,
+    // starting from the last block
+    let mut raw_height = last_height.0;
+    while raw_height > height.0 {
+        let diffs_height = BlockHeight(raw_height);
+        let old_vals: BTreeMap<String, Vec<u8>> = db
+            .iter_old_diffs(diffs_height, prefix)
+            .filter(|(key, _val, _gas)| range.contains(key))
+            .map(|(key, val, _gas)| (key, val))
+            .collect();
+        for (key, _val, _gas) in db.iter_new_diffs(diffs_height, prefix) {
+            // A key that has a new value without an old value was created at
+            // this height
+            if !old_vals.contains_key(&key) {
+                values.remove(&key);
+            }
+        }
+        values.extend(old_vals);
+        raw_height = checked!(raw_height - 1)?;
+    }
+    Ok(HistoricPrefixIter {
+        iter: values.into_iter(),
+        rev: range.rev,
+    })
+}
+
+/// A prefix iterator over the subspace values at a past height, from
+/// [`iter_prefix_with_height`].
+#[derive(Debug)]
+pub struct HistoricPrefixIter {
+    iter: std::collections::btree_map::IntoIter<String, Vec<u8>>,
+    rev: bool,
+}
+
+impl Iterator for HistoricPrefixIter {
+    type Item = (String, Vec<u8>, Gas);
+
+    /// Returns the next pair and the gas cost
+    fn next(&mut self) -> Option<(String, Vec<u8>, Gas)> {
+        let next = if self.rev {
+            self.iter.next_back()
+        } else {
+            self.iter.next()
+        };
+        next.map(|(key, val)| {
+            let gas = key.len().saturating_add(val.len()) as u64;
+            (key, val, gas.into())
+        })
+    }
 }
 
 /// Atomic batch write.
@@ -42,6 +42,7 @@ pub use namada_core::chain::{
 };
 pub use namada_core::hash::{Hash, StorageHasher};
 pub use namada_core::storage::*;
+pub use types::KeyRange;
 
 /// Common storage read interface
 pub trait StorageRead {
@@ -79,6 +80,18 @@ pub trait StorageRead {
         prefix: &Key,
     ) -> Result<Self::PrefixIter<'iter>>;
 
+    /// Storage prefix iterator over the keys within the given range, ordered
+    /// by the storage keys in the range's direction. Only the entries within
+    /// the range are visited.
+    ///
+    /// For a more user-friendly iterator API, use [`fn@iter_prefix_range`] or
+    /// [`fn@iter_prefix_range_bytes`] instead.
+    fn iter_prefix_range<'iter>(
+        &'iter self,
+        prefix: &Key,
+        range: &KeyRange,
+    ) -> Result<Self::PrefixIter<'iter>>;
+
     /// Storage prefix iterator. It will try to read from the storage.
     fn iter_next<'iter>(
         &'iter self,
@@ -364,6 +377,108 @@ where
     Ok(iter)
 }
 
+/// Iterate items matching the given prefix within the given range of keys,
+/// ordered by the storage keys in the range's direction.
+pub fn iter_prefix_range_bytes<'a>(
+    storage: &'a impl StorageRead,
+    prefix: Key,
+    range: KeyRange,
+) -> Result<impl Iterator<Item = Result<(Key, Vec<u8>)>> + 'a> {
+    let mut iter = storage.iter_prefix_range(&prefix, &range)?;
+    let iter = std::iter::from_fn(move || {
+        match storage.iter_next(&mut iter) {
+            Ok(Some((key, val))) => {
+                let key = match Key::parse(key).into_storage_result() {
+                    Ok(key) => key,
+                    Err(err) => {
+                        // Propagate key encoding errors into Iterator's Item
+                        return Some(Err(err));
+                    }
+                };
+                Some(Ok((key, val)))
+            }
+            Ok(None) => None,
+            Err(err) => {
+                // Propagate `iter_next` errors into Iterator's Item
+                Some(Err(err))
+            }
+        }
+    });
+    Ok(iter)
+}
+
+/// Iterate Borsh encoded items matching the given prefix within the given
+/// range of keys, ordered by the storage keys in the range's direction.
+pub fn iter_prefix_range<'a, T>(
+    storage: &'a impl StorageRead,
+    prefix: Key,
+    range: KeyRange,
+) -> Result<impl Iterator<Item = Result<(Key, T)>> + 'a>
+where
+    T: BorshDeserialize,
+{
+    iter_prefix_range_with_filter_map(storage, prefix, range, |key| {
+        Some(key.clone())
+    })
+}
+
+/// Iterate Borsh encoded items matching the given prefix within the given
+/// range of keys and passing the given `filter_map` function, ordered by the
+/// storage keys in the range's direction.
+///
+/// Like with [`fn@iter_prefix_with_filter_map`], the values of the items that
+/// don't pass the filter are not decoded.
+pub fn iter_prefix_range_with_filter_map<'a, K, T, F>(
+    storage: &'a impl StorageRead,
+    prefix: Key,
+    range: KeyRange,
+    filter_map: F,
+) -> Result<impl Iterator<Item = Result<(K, T)>> + 'a>
+where
+    T: BorshDeserialize,
+    F: Fn(&Key) -> Option<K> + 'a,
+{
+    let mut iter = storage.iter_prefix_range(&prefix, &range)?;
+    let iter = std::iter::from_fn(move || {
+        // The loop is for applying filter - we `continue` when the current key
+        // doesn't pass the predicate.
+        loop {
+            match storage.iter_next(&mut iter) {
+                Ok(Some((key, val))) => {
+                    let key = match Key::parse(key).into_storage_result() {
+                        Ok(key) => key,
+                        Err(err) => {
+                            // Propagate key encoding errors into Iterator's
+                            // Item
+                            return Some(Err(err));
+                        }
+                    };
+                    // Check the predicate
+                    let Some(mapped_key) = filter_map(&key) else {
+                        continue;
+                    };
+                    let val =
+                        match T::try_from_slice(&val).into_storage_result() {
+                            Ok(val) => val,
+                            Err(err) => {
+                                // Propagate val encoding errors into Iterator's
+                                // Item
+                                return Some(Err(err));
+                            }
+                        };
+                    return Some(Ok((mapped_key, val)));
+                }
+                Ok(None) => return None,
+                Err(err) => {
+                    // Propagate `iter_next` errors into Iterator's Item
+                    return Some(Err(err));
+                }
+            }
+        }
+    });
+    Ok(iter)
+}
+
 /// Helpers for testing components that depend on storage
 #[cfg(any(test, feature = "testing"))]
 pub mod testing {
@@ -446,6 +561,17 @@ pub mod testing {
             })
         }
 
+        fn iter_prefix_range<'iter>(
+            &'iter self,
+            prefix: &Key,
+            range: &KeyRange,
+        ) -> Result<Self::PrefixIter<'iter>> {
+            let storage_iter = self.db.iter_prefix_range(Some(prefix), range);
+            Ok(PrefixIter {
+                db_iter: storage_iter,
+            })
+        }
+
         fn iter_next<'iter>(
             &'iter self,
             iter: &mut Self::PrefixIter<'iter>,
@@ -23,10 +23,12 @@ use regex::Regex;
 use crate::DBUpdateVisitor;
 use crate::db::{
     BlockStateRead, BlockStateWrite, DB, DBIter, DBWriteBatch, Error, Result,
+    SubspaceChange,
 };
-use crate::types::{KVBytes, PatternIterator, PrefixIterator};
+use crate::types::{KVBytes, KeyRange, PatternIterator, PrefixIterator};
 
 const SUBSPACE_CF: &str = "subspace";
+const EVENTS_CF: &str = "events";
 
 const BLOCK_HEIGHT_KEY: &str = "height";
 const NEXT_EPOCH_MIN_START_HEIGHT_KEY: &str = "next_epoch_min_start_height";
@@ -380,6 +382,45 @@ impl DB for MockDB {
         Ok(self.0.borrow().get(&prefix.to_string()).cloned())
     }
 
+    fn read_subspace_changeset(
+        &self,
+        height: BlockHeight,
+    ) -> Result<Vec<SubspaceChange>> {
+        let mut changes = BTreeMap::<String, SubspaceChange>::new();
+        for (is_old, old_new_seg) in
+            [(true, OLD_DIFF_PREFIX), (false, NEW_DIFF_PREFIX)]
+        {
+            let prefix = Key::from(height.to_db_key())
+                .push(&old_new_seg.to_string().to_db_key())
+                .map_err(Error::KeyError)?
+                .to_string();
+            let prefix = format!("{prefix}{KEY_SEGMENT_SEPARATOR}");
+            for (diff_key, value) in self.0.borrow().iter() {
+                let Some(key_str) = diff_key.strip_prefix(&prefix) else {
+                    continue;
+                };
+                let change = match changes.entry(key_str.to_owned()) {
+                    btree_map::Entry::Occupied(entry) => entry.into_mut(),
+                    btree_map::Entry::Vacant(entry) => {
+                        let key =
+                            Key::parse(key_str).map_err(Error::KeyError)?;
+                        entry.insert(SubspaceChange {
+                            key,
+                            old_value: None,
+                            new_value: None,
+                        })
+                    }
+                };
+                if is_old {
+                    change.old_value = Some(value.clone());
+                } else {
+                    change.new_value = Some(value.clone());
+                }
+            }
+        }
+        Ok(changes.into_values().collect())
+    }
+
     fn read_subspace_val(&self, key: &Key) -> Result<Option<Vec<u8>>> {
         let key = Key::parse(SUBSPACE_CF).map_err(Error::KeyError)?.join(key);
         Ok(self.0.borrow().get(&key.to_string()).cloned())
@@ -671,6 +712,16 @@ impl DB for MockDB {
         Ok(())
     }
 
+    fn prune_rollback_journal(
+        &mut self,
+        _batch: &mut Self::WriteBatch,
+        _oldest_height: BlockHeight,
+        _oldest_epoch: Epoch,
+    ) -> Result<()> {
+        // No-op - the mock DB doesn't support rolling back
+        Ok(())
+    }
+
     fn overwrite_entry(
         &self,
         _batch: &mut Self::WriteBatch,
@@ -682,6 +733,49 @@ impl DB for MockDB {
         unimplemented!()
     }
 
+    fn read_event_entry(&self, key: &Key) -> Result<Option<Vec<u8>>> {
+        let key = Key::parse(EVENTS_CF).map_err(Error::KeyError)?.join(key);
+        Ok(self.0.borrow().get(&key.to_string()).cloned())
+    }
+
+    fn batch_write_event_entry(
+        &self,
+        _batch: &mut Self::WriteBatch,
+        key: &Key,
+        value: impl AsRef<[u8]>,
+    ) -> Result<()> {
+        let key = Key::parse(EVENTS_CF).map_err(Error::KeyError)?.join(key);
+        self.0
+            .borrow_mut()
+            .insert(key.to_string(), value.as_ref().to_owned());
+        Ok(())
+    }
+
+    fn batch_delete_event_entry(
+        &self,
+        _batch: &mut Self::WriteBatch,
+        key: &Key,
+    ) -> Result<()> {
+        let key = Key::parse(EVENTS_CF).map_err(Error::KeyError)?.join(key);
+        self.0.borrow_mut().remove(&key.to_string());
+        Ok(())
+    }
+
+    fn batch_delete_event_range(
+        &self,
+        _batch: &mut Self::WriteBatch,
+        from: &Key,
+        to: &Key,
+    ) -> Result<()> {
+        let events_cf = Key::parse(EVENTS_CF).map_err(Error::KeyError)?;
+        let from = events_cf.join(from).to_string();
+        let to = events_cf.join(to).to_string();
+        self.0
+            .borrow_mut()
+            .retain(|key, _| *key < from || *key >= to);
+        Ok(())
+    }
+
     fn migrator() -> Self::Migrator {
         unimplemented!("Migration isn't implemented in MockDB")
     }
@@ -725,23 +819,46 @@ impl<'iter> DBIter<'iter> for MockDB {
     type PrefixIter = MockPrefixIterator;
 
     fn iter_prefix(&'iter self, prefix: Option<&Key>) -> MockPrefixIterator {
-        let stripped_prefix = "subspace/".to_owned();
-        let prefix = format!(
-            "{}{}",
-            stripped_prefix,
-            match prefix {
-                Some(prefix) => {
-                    if prefix == &Key::default() {
-                        prefix.to_string()
-                    } else {
-                        format!("{prefix}/")
-                    }
-                }
-                None => "".to_string(),
-            }
-        );
+        let stripped_prefix = format!("{SUBSPACE_CF}/");
+        let prefix = subspace_prefix(&stripped_prefix, prefix);
         let iter = self.0.borrow().clone().into_iter();
-        MockPrefixIterator::new(MockIterator { prefix, iter }, stripped_prefix)
+        MockPrefixIterator::new(
+            MockIterator {
+                prefix,
+                iter,
+                rev: false,
+            },
+            stripped_prefix,
+        )
+    }
+
+    fn iter_prefix_range(
+        &'iter self,
+        prefix: Option<&Key>,
+        range: &KeyRange,
+    ) -> MockPrefixIterator {
+        let stripped_prefix = format!("{SUBSPACE_CF}/");
+        let prefix = subspace_prefix(&stripped_prefix, prefix);
+        // Only the entries within the range are visited
+        let iter = self
+            .0
+            .borrow()
+            .iter()
+            .filter(|(key, _val)| {
+                key.strip_prefix(&stripped_prefix)
+                    .is_some_and(|key| range.contains(key))
+            })
+            .map(|(key, val)| (key.clone(), val.clone()))
+            .collect::<BTreeMap<_, _>>()
+            .into_iter();
+        MockPrefixIterator::new(
+            MockIterator {
+                prefix,
+                iter,
+                rev: range.rev,
+            },
+            stripped_prefix,
+        )
     }
 
     fn iter_pattern(
@@ -762,7 +879,14 @@ impl<'iter> DBIter<'iter> for MockDB {
         let stripped_prefix = "results/".to_owned();
         let prefix = "results".to_owned();
         let iter = self.0.borrow().clone().into_iter();
-        MockPrefixIterator::new(MockIterator { prefix, iter }, stripped_prefix)
+        MockPrefixIterator::new(
+            MockIterator {
+                prefix,
+                iter,
+                rev: false,
+            },
+            stripped_prefix,
+        )
     }
 
     fn iter_old_diffs(
@@ -783,7 +907,14 @@ impl<'iter> DBIter<'iter> for MockDB {
             })
             .unwrap_or("".to_string());
         let iter = self.0.borrow().clone().into_iter();
-        MockPrefixIterator::new(MockIterator { prefix, iter }, stripped_prefix)
+        MockPrefixIterator::new(
+            MockIterator {
+                prefix,
+                iter,
+                rev: false,
+            },
+            stripped_prefix,
+        )
     }
 
     fn iter_new_diffs(
@@ -804,7 +935,14 @@ impl<'iter> DBIter<'iter> for MockDB {
             })
             .unwrap_or("".to_string());
         let iter = self.0.borrow().clone().into_iter();
-        MockPrefixIterator::new(MockIterator { prefix, iter }, stripped_prefix)
+        MockPrefixIterator::new(
+            MockIterator {
+                prefix,
+                iter,
+                rev: false,
+            },
+            stripped_prefix,
+        )
     }
 
     fn iter_current_replay_protection(&'iter self) -> Self::PrefixIter {
@@ -814,7 +952,46 @@ impl<'iter> DBIter<'iter> for MockDB {
         );
         let prefix = stripped_prefix.clone();
         let iter = self.0.borrow().clone().into_iter();
-        MockPrefixIterator::new(MockIterator { prefix, iter }, stripped_prefix)
+        MockPrefixIterator::new(
+            MockIterator {
+                prefix,
+                iter,
+                rev: false,
+            },
+            stripped_prefix,
+        )
+    }
+
+    fn iter_event_entries(&'iter self, prefix: &Key) -> Self::PrefixIter {
+        let stripped_prefix = format!("{EVENTS_CF}/");
+        let prefix = if prefix.is_empty() {
+            stripped_prefix.clone()
+        } else {
+            format!("{stripped_prefix}{prefix}/")
+        };
+        let iter = self.0.borrow().clone().into_iter();
+        MockPrefixIterator::new(
+            MockIterator {
+                prefix,
+                iter,
+                rev: false,
+            },
+            stripped_prefix,
+        )
+    }
+}
+
+/// Get the prefix of the subspace keys matching the given `prefix`.
+fn subspace_prefix(stripped_prefix: &str, prefix: Option<&Key>) -> String {
+    match prefix {
+        Some(prefix) => {
+            if prefix == &Key::default() {
+                format!("{stripped_prefix}{prefix}")
+            } else {
+                format!("{stripped_prefix}{prefix}/")
+            }
+        }
+        None => stripped_prefix.to_owned(),
     }
 }
 
@@ -824,6 +1001,8 @@ pub struct MockIterator {
     prefix: String,
     /// The concrete iterator
     pub iter: btree_map::IntoIter<String, Vec<u8>>,
+    /// Iterate in descending order
+    rev: bool,
 }
 
 /// A prefix iterator for the [`MockDB`].
@@ -833,7 +1012,12 @@ impl Iterator for MockIterator {
     type Item = Result<KVBytes>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        for (key, val) in &mut self.iter {
+        loop {
+            let (key, val) = if self.rev {
+                self.iter.next_back()?
+            } else {
+                self.iter.next()?
+            };
             if key.starts_with(&self.prefix) {
                 return Some(Ok((
                     Box::from(key.as_bytes()),
@@ -841,7 +1025,6 @@ impl Iterator for MockIterator {
                 )));
             }
         }
-        None
     }
 }
 
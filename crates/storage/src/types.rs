@@ -1,10 +1,12 @@
 //! The key and values that may be persisted in a DB.
 
 use std::collections::BTreeMap;
+use std::ops::Bound;
 
 use borsh::{BorshDeserialize, BorshSerialize};
 use namada_core::borsh::BorshSerializeExt;
 use namada_core::hash::Hash;
+use namada_core::storage::Key;
 pub use regex::Regex;
 
 /// A key-value pair as raw bytes
@@ -64,6 +66,95 @@ impl<I> std::fmt::Debug for PatternIterator<I> {
     }
 }
 
+/// A range of storage keys for bounded prefix iteration. The bounds are
+/// compared with the string representation of the storage keys, which is the
+/// order in which the keys are iterated.
+#[derive(
+    Clone, Debug, Default, PartialEq, Eq, BorshSerialize, BorshDeserialize,
+)]
+pub struct KeyRange {
+    /// The inclusive lower bound of the keys, if any
+    pub start: Option<String>,
+    /// The exclusive upper bound of the keys, if any
+    pub end: Option<String>,
+    /// Iterate the keys in descending order
+    pub rev: bool,
+}
+
+impl KeyRange {
+    /// A range of the keys `{prefix}/{seg}` whose segment after the prefix is
+    /// within the given bounds of the storage key segments.
+    pub fn sub_keys(
+        prefix: &Key,
+        start: Bound<String>,
+        end: Bound<String>,
+    ) -> Self {
+        // The key followed by the lowest char is the key's direct successor,
+        // so the keys of the siblings that extend the segment (e.g.
+        // `{seg}-x`) are outside of the bounds
+        Self::from_bounds(
+            start,
+            end,
+            |seg| format!("{prefix}/{seg}"),
+            |seg| format!("{prefix}/{seg}\0"),
+        )
+    }
+
+    /// A range of the keys nested under `{prefix}/{seg}/` whose segment after
+    /// the prefix is within the given bounds of the storage key segments.
+    pub fn nested_sub_keys(
+        prefix: &Key,
+        start: Bound<String>,
+        end: Bound<String>,
+    ) -> Self {
+        // The keys nested under `{prefix}/{seg}/` are ordered before
+        // `{prefix}/{seg}0`, because `'0'` directly follows the separator
+        Self::from_bounds(
+            start,
+            end,
+            |seg| format!("{prefix}/{seg}/"),
+            |seg| format!("{prefix}/{seg}0"),
+        )
+    }
+
+    /// A range from the bounds of the key segments, where `at` gives the
+    /// lowest key of a segment and `after` gives the lowest key after all the
+    /// keys of a segment.
+    fn from_bounds(
+        start: Bound<String>,
+        end: Bound<String>,
+        at: impl Fn(String) -> String,
+        after: impl Fn(String) -> String,
+    ) -> Self {
+        let start = match start {
+            Bound::Included(seg) => Some(at(seg)),
+            Bound::Excluded(seg) => Some(after(seg)),
+            Bound::Unbounded => None,
+        };
+        let end = match end {
+            Bound::Included(seg) => Some(after(seg)),
+            Bound::Excluded(seg) => Some(at(seg)),
+            Bound::Unbounded => None,
+        };
+        Self {
+            start,
+            end,
+            rev: false,
+        }
+    }
+
+    /// Iterate the range in descending order
+    pub fn rev(self) -> Self {
+        Self { rev: true, ..self }
+    }
+
+    /// Check if the given key is within the bounds of the range
+    pub fn contains(&self, key: &str) -> bool {
+        self.start.as_deref().is_none_or(|start| key >= start)
+            && self.end.as_deref().is_none_or(|end| key < end)
+    }
+}
+
 /// Structure holding data that will be committed to the merkle tree
 #[derive(Debug, BorshSerialize, BorshDeserialize, Default)]
 pub struct CommitOnlyData {
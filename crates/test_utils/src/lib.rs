@@ -43,6 +43,25 @@ pub enum TestWasms {
     VpVerifySignature,
 }
 
+/// Get the path to the root of the repo, or panic if not able to.
+pub fn repo_root() -> PathBuf {
+    let cwd =
+        env::current_dir().expect("Couldn't get current working directory");
+    // crudely find the root of the repo, we can't rely on the `.git`
+    // directory being present, so look instead for the presence of a
+    // CHANGELOG.md file
+    cwd.ancestors()
+        .find(|path| path.join("CHANGELOG.md").exists())
+        .unwrap_or_else(|| {
+            panic!(
+                "Couldn't find the root of the repository for the current \
+                 working directory {}",
+                cwd.to_string_lossy()
+            )
+        })
+        .to_path_buf()
+}
+
 impl TestWasms {
     /// Get the path to where this test wasm is expected to be, or panic if not
     /// able to.
@@ -76,22 +95,7 @@ impl TestWasms {
             TestWasms::VpReadStorageKey => "vp_read_storage_key.wasm",
             TestWasms::VpVerifySignature => "vp_verify_signature.wasm",
         };
-        let cwd =
-            env::current_dir().expect("Couldn't get current working directory");
-        // crudely find the root of the repo, we can't rely on the `.git`
-        // directory being present, so look instead for the presence of a
-        // CHANGELOG.md file
-        let repo_root = cwd
-            .ancestors()
-            .find(|path| path.join("CHANGELOG.md").exists())
-            .unwrap_or_else(|| {
-                panic!(
-                    "Couldn't find the root of the repository for the current \
-                     working directory {}",
-                    cwd.to_string_lossy()
-                )
-            });
-        repo_root.join(WASM_FOR_TESTS_DIR).join(filename)
+        repo_root().join(WASM_FOR_TESTS_DIR).join(filename)
     }
 
     /// Attempts to read the contents of this test wasm. Panics if it is not
@@ -14,11 +14,11 @@ use namada_core::dec::Dec;
 use namada_core::hash::Hash;
 use namada_core::storage::{DbColFam, Key};
 use namada_core::token::NATIVE_MAX_DECIMAL_PLACES;
-use namada_node::shell::SnapshotSync;
 use namada_node::shell::testing::client::run;
 use namada_node::shell::testing::node::NodeResults;
 use namada_node::shell::testing::utils::{Bin, CapturedOutput};
-use namada_node::storage::DbSnapshot;
+use namada_node::shell::{SnapshotSync, SnapshotSyncData};
+use namada_node::storage::{DbSnapshot, SnapshotSyncPath};
 use namada_sdk::account::AccountPublicKeysMap;
 use namada_sdk::borsh::BorshSerializeExt;
 use namada_sdk::collections::HashMap;
@@ -2265,6 +2265,33 @@ fn apply_snapshot() -> Result<()> {
     {
         let shell = node.shell.lock().unwrap();
         let mut shell2 = node2.shell.lock().unwrap();
+        // apply some chunks out of order before a simulated restart
+        for c in (1..offer.chunks).rev().step_by(2) {
+            let chunk =
+                shell.load_snapshot_chunk(tm_request::LoadSnapshotChunk {
+                    height: (last_height.0 as u32).into(),
+                    format: 0,
+                    chunk: c,
+                });
+            let resp =
+                shell2.apply_snapshot_chunk(tm_request::ApplySnapshotChunk {
+                    index: c,
+                    chunk: chunk.chunk,
+                    sender: "".to_string(),
+                });
+            assert_eq!(resp.result, ApplySnapshotChunkResult::Accept);
+        }
+        shell2.syncing = None;
+        // the sync resumes from the persisted chunks
+        let resp = shell2.offer_snapshot(tm_request::OfferSnapshot {
+            snapshot: offer.clone(),
+            app_hash: Default::default(),
+        });
+        assert_eq!(tm_response::OfferSnapshot::Accept, resp);
+        assert_eq!(
+            shell2.syncing.as_ref().unwrap().received.len() as u32,
+            offer.chunks / 2
+        );
         for c in 0..offer.chunks {
             let chunk =
                 shell.load_snapshot_chunk(tm_request::LoadSnapshotChunk {
@@ -2336,18 +2363,29 @@ fn snapshot_unhappy_flows() -> Result<()> {
         }
     );
 
-    {
+    let start_syncing = || {
         let mut locked = node.shell.lock().unwrap();
+        let path = SnapshotSyncPath(
+            node.test_dir.path().to_path_buf(),
+            Default::default(),
+            Default::default(),
+        );
+        std::fs::create_dir_all(path.base()).unwrap();
         locked.syncing = Some(SnapshotSync {
-            next_chunk: 0,
             height: Default::default(),
+            app_hash: vec![],
             expected: vec![Default::default()],
-            strikes: 0,
-            snapshot: tempfile::tempfile().unwrap(),
+            path,
+            received: Default::default(),
+            delivered: Default::default(),
+            next_chunk: 0,
+            strikes: Default::default(),
+            snapshot: SnapshotSyncData::Tarball(tempfile::tempfile().unwrap()),
         });
-    }
+    };
 
-    // test we reject and re-fetch if the wrong chunk is given
+    // test we reject the snapshot if a chunk out of range is given
+    start_syncing();
     let resp = {
         let mut shell = node.shell.lock().unwrap();
         shell.apply_snapshot_chunk(tm_request::ApplySnapshotChunk {
@@ -2359,12 +2397,16 @@ fn snapshot_unhappy_flows() -> Result<()> {
     assert_eq!(
         resp,
         tm_response::ApplySnapshotChunk {
-            result: ApplySnapshotChunkResult::Unknown,
-            refetch_chunks: vec![0],
+            result: ApplySnapshotChunkResult::RejectSnapshot,
+            refetch_chunks: vec![],
             reject_senders: vec![],
         }
     );
-    // test we refetch a chunk if the hash is wrong up to five times.
+    assert!(node.shell.lock().unwrap().syncing.is_none());
+
+    // test we refetch a chunk and reject its sender if the hash is wrong up
+    // to five times.
+    start_syncing();
     for _ in 0..4 {
         let resp = {
             let mut shell = node.shell.lock().unwrap();
@@ -2379,7 +2421,7 @@ fn snapshot_unhappy_flows() -> Result<()> {
             tm_response::ApplySnapshotChunk {
                 result: ApplySnapshotChunkResult::Retry,
                 refetch_chunks: vec![0],
-                reject_senders: vec![],
+                reject_senders: vec!["".to_string()],
             }
         );
     }
@@ -0,0 +1,149 @@
+#[cfg(test)]
+mod tests {
+    use namada_sdk::address::{self, Address};
+    use namada_sdk::storage;
+    use namada_tx_prelude::collections::{
+        LazyCollection, LazyDeque, lazy_deque,
+    };
+    use namada_tx_prelude::storage::KeySeg;
+    use namada_tx_prelude::{Result, StorageWrite};
+    use namada_vp_prelude::collection_validation::{self, LazyCollectionExt};
+    use test_log::test;
+
+    use crate::tx::tx_host_env;
+    use crate::vp::vp_host_env;
+
+    /// Init the tx env with an account whose storage is used for the tested
+    /// collection, so that the collection's changes trigger its VP
+    fn init_collection_key() -> (Address, storage::Key) {
+        tx_host_env::init();
+        let address = address::testing::established_address_1();
+        tx_host_env::with(|env| env.spawn_accounts([&address]));
+        let prefix: storage::Key = address.to_db_key().into();
+        (address, prefix.push(&"arbitrary".to_string()).unwrap())
+    }
+
+    /// Run the collection's validation on the changes of the current tx and
+    /// return the validation result
+    fn validate<C>(address: &Address, collection: &C) -> Result<Vec<C::Action>>
+    where
+        C: LazyCollectionExt,
+    {
+        let tx_env = tx_host_env::take();
+        vp_host_env::init_from_tx(address.clone(), tx_env, |_| {});
+        let changed_keys =
+            vp_host_env::with(|env| env.all_touched_storage_keys());
+
+        let mut validation_builder = None;
+        for key in &changed_keys {
+            let is_sub_key = collection
+                .accumulate(vp_host_env::ctx(), &mut validation_builder, key)
+                .unwrap();
+            assert!(is_sub_key, "Unexpected changed key {key}");
+        }
+        let result = match validation_builder {
+            Some(builder) => C::validate(builder),
+            None => Ok(vec![]),
+        };
+
+        // Put the tx_env back for the following transitions
+        tx_host_env::set_from_vp_env(vp_host_env::take());
+        result
+    }
+
+    #[test]
+    fn lazy_deque_validation() {
+        use collection_validation::lazy_deque::Action;
+
+        let (address, key) = init_collection_key();
+        let lazy_deque = LazyDeque::<u64>::open(key);
+        let ctx = tx_host_env::ctx();
+
+        // Push at both ends of an empty deque
+        lazy_deque.push_back(ctx, 2).unwrap();
+        lazy_deque.push_front(ctx, 1).unwrap();
+        lazy_deque.push_back(ctx, 3).unwrap();
+        let actions = validate(&address, &lazy_deque).unwrap();
+        assert!(
+            matches!(
+                actions.as_slice(),
+                [
+                    Action::PushBack(1),
+                    Action::PushBack(2),
+                    Action::PushBack(3)
+                ]
+            ),
+            "{actions:#?}"
+        );
+        tx_host_env::commit_tx_and_block();
+
+        // Push and pop at both ends of a non-empty deque
+        let ctx = tx_host_env::ctx();
+        lazy_deque.push_front(ctx, 0).unwrap();
+        assert_eq!(lazy_deque.pop_back(ctx).unwrap(), Some(3));
+        let actions = validate(&address, &lazy_deque).unwrap();
+        assert!(
+            matches!(
+                actions.as_slice(),
+                [Action::PushFront(0), Action::PopBack(3)]
+            ),
+            "{actions:#?}"
+        );
+        tx_host_env::commit_tx_and_block();
+
+        // Update an element
+        let ctx = tx_host_env::ctx();
+        lazy_deque.update(ctx, 1, 10).unwrap();
+        let actions = validate(&address, &lazy_deque).unwrap();
+        assert!(
+            matches!(
+                actions.as_slice(),
+                [Action::Update {
+                    pre: 1,
+                    post: 10,
+                    ..
+                }]
+            ),
+            "{actions:#?}"
+        );
+        tx_host_env::commit_tx_and_block();
+
+        // Pop all the elements
+        let ctx = tx_host_env::ctx();
+        assert_eq!(lazy_deque.pop_front(ctx).unwrap(), Some(0));
+        assert_eq!(lazy_deque.pop_back(ctx).unwrap(), Some(2));
+        assert_eq!(lazy_deque.pop_front(ctx).unwrap(), Some(10));
+        assert!(lazy_deque.is_empty(ctx).unwrap());
+        let actions = validate(&address, &lazy_deque).unwrap();
+        assert!(
+            matches!(
+                actions.as_slice(),
+                [
+                    Action::PopFront(0),
+                    Action::PopFront(10),
+                    Action::PopFront(2)
+                ]
+            ),
+            "{actions:#?}"
+        );
+    }
+
+    #[test]
+    fn lazy_deque_invalid_changes() {
+        let (address, key) = init_collection_key();
+        let deque = LazyDeque::<u64>::open(key.clone());
+        let ctx = tx_host_env::ctx();
+        deque.push_back(ctx, 1).unwrap();
+        deque.push_back(ctx, 2).unwrap();
+        tx_host_env::commit_tx_and_block();
+
+        // Deleting an element without changing the bounds is invalid
+        let data_key = key
+            .push(&"data".to_string())
+            .unwrap()
+            .push(&lazy_deque::INITIAL_INDEX)
+            .unwrap();
+        tx_host_env::with(|env| env.state.delete(&data_key).unwrap());
+        assert!(validate(&address, &deque).is_err());
+    }
+}
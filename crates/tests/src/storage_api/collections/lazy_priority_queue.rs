@@ -0,0 +1,90 @@
+#[cfg(test)]
+mod tests {
+    use namada_sdk::address::{self, Address};
+    use namada_sdk::storage;
+    use namada_tx_prelude::Result;
+    use namada_tx_prelude::collections::{LazyCollection, LazyPriorityQueue};
+    use namada_tx_prelude::storage::KeySeg;
+    use namada_vp_prelude::collection_validation::{self, LazyCollectionExt};
+    use test_log::test;
+
+    use crate::tx::tx_host_env;
+    use crate::vp::vp_host_env;
+
+    /// Init the tx env with an account whose storage is used for the tested
+    /// collection, so that the collection's changes trigger its VP
+    fn init_collection_key() -> (Address, storage::Key) {
+        tx_host_env::init();
+        let address = address::testing::established_address_1();
+        tx_host_env::with(|env| env.spawn_accounts([&address]));
+        let prefix: storage::Key = address.to_db_key().into();
+        (address, prefix.push(&"arbitrary".to_string()).unwrap())
+    }
+
+    /// Run the collection's validation on the changes of the current tx and
+    /// return the validation result
+    fn validate<C>(address: &Address, collection: &C) -> Result<Vec<C::Action>>
+    where
+        C: LazyCollectionExt,
+    {
+        let tx_env = tx_host_env::take();
+        vp_host_env::init_from_tx(address.clone(), tx_env, |_| {});
+        let changed_keys =
+            vp_host_env::with(|env| env.all_touched_storage_keys());
+
+        let mut validation_builder = None;
+        for key in &changed_keys {
+            let is_sub_key = collection
+                .accumulate(vp_host_env::ctx(), &mut validation_builder, key)
+                .unwrap();
+            assert!(is_sub_key, "Unexpected changed key {key}");
+        }
+        let result = match validation_builder {
+            Some(builder) => C::validate(builder),
+            None => Ok(vec![]),
+        };
+
+        // Put the tx_env back for the following transitions
+        tx_host_env::set_from_vp_env(vp_host_env::take());
+        result
+    }
+
+    #[test]
+    fn lazy_priority_queue_validation() {
+        use collection_validation::lazy_priority_queue::Action;
+
+        let (address, key) = init_collection_key();
+        let queue = LazyPriorityQueue::<u64, u64>::open(key);
+        let ctx = tx_host_env::ctx();
+
+        queue.push(ctx, 5, 50).unwrap();
+        queue.push(ctx, 1, 10).unwrap();
+        queue.push(ctx, 5, 51).unwrap();
+        let actions = validate(&address, &queue).unwrap();
+        assert!(
+            matches!(
+                actions.as_slice(),
+                [
+                    Action::Push(1, 10),
+                    Action::Push(5, 50),
+                    Action::Push(5, 51)
+                ]
+            ),
+            "{actions:#?}"
+        );
+        tx_host_env::commit_tx_and_block();
+
+        let ctx = tx_host_env::ctx();
+        assert_eq!(queue.pop(ctx).unwrap(), Some((1, 10)));
+        assert_eq!(queue.pop(ctx).unwrap(), Some((5, 50)));
+        queue.push(ctx, 3, 30).unwrap();
+        let actions = validate(&address, &queue).unwrap();
+        assert!(
+            matches!(
+                actions.as_slice(),
+                [Action::Pop(1, 10), Action::Push(3, 30), Action::Pop(5, 50)]
+            ),
+            "{actions:#?}"
+        );
+    }
+}
@@ -1,4 +1,6 @@
+mod lazy_deque;
 mod lazy_map;
+mod lazy_priority_queue;
 mod lazy_set;
 mod lazy_vec;
 mod nested_lazy_map;
@@ -44,7 +44,9 @@ mod tests {
     use namada_tx_env::TxEnv;
     use namada_tx_prelude::address::InternalAddress;
     use namada_tx_prelude::chain::ChainId;
-    use namada_tx_prelude::{Address, BatchedTx, StorageRead, StorageWrite};
+    use namada_tx_prelude::{
+        self as tx_prelude, Address, BatchedTx, StorageRead, StorageWrite,
+    };
     use namada_vp_prelude::account::AccountPublicKeysMap;
     use namada_vp_prelude::{VpEnv, sha256};
     use prost::Message;
@@ -147,6 +149,45 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_tx_savepoints() {
+        // The environment must be initialized first
+        tx_host_env::init();
+
+        let key = storage::Key::parse("key").unwrap();
+        let optional_key = storage::Key::parse("optional").unwrap();
+        tx::ctx().write(&key, 1_u64).unwrap();
+
+        // A failed optional action is undone without failing the tx
+        let res = tx::ctx().try_with_savepoint(|ctx| {
+            ctx.write(&key, 2_u64)?;
+            ctx.write(&optional_key, 1_u64)?;
+            Err::<(), _>(namada_tx_prelude::Error::new_const("failed"))
+        });
+        assert!(res.is_err());
+        assert_eq!(tx::ctx().read::<u64>(&key).unwrap(), Some(1));
+        assert!(!tx::ctx().has_key(&optional_key).unwrap());
+
+        // A successful optional action is kept, unless an outer savepoint is
+        // rolled back
+        tx::ctx().push_savepoint().unwrap();
+        tx::ctx()
+            .try_with_savepoint(|ctx| ctx.write(&optional_key, 2_u64))
+            .unwrap();
+        assert_eq!(tx::ctx().read::<u64>(&optional_key).unwrap(), Some(2));
+        tx::ctx().rollback_to_savepoint().unwrap();
+        assert!(!tx::ctx().has_key(&optional_key).unwrap());
+
+        // Removing a savepoint that doesn't exist should fail
+        assert!(
+            panic::catch_unwind(|| tx::ctx().release_savepoint().unwrap())
+                .err()
+                .map(|a| a.downcast_ref::<String>().cloned().unwrap())
+                .unwrap()
+                .contains("NoSavepoint")
+        );
+    }
+
     #[test]
     fn test_tx_iter_prefix() {
         // The environment must be initialized first
@@ -364,6 +405,75 @@ mod tests {
         assert_eq!(expected, pred_epochs);
     }
 
+    #[test]
+    fn test_tx_hash_and_verify_signature() {
+        use data_encoding::HEXLOWER;
+        use namada_sdk::borsh::BorshDeserialize;
+
+        // The environment must be initialized first
+        tx_host_env::init();
+
+        // Known answers for the message "abc"
+        let data = b"abc";
+        assert_eq!(
+            HEXLOWER.encode(&tx_prelude::hash_sha256(data).0),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            HEXLOWER.encode(&tx_prelude::hash_keccak256(data).0),
+            "4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45"
+        );
+        assert_eq!(
+            HEXLOWER.encode(&tx_prelude::hash_blake2b(data)),
+            "bddd813c634239723171ef3fee98579b94964e3bb1cb3e427262c8c068d52319"
+        );
+
+        // The Ed25519 key of the test 1 of RFC 8032
+        let pk = common::PublicKey::Ed25519(
+            ed25519::PublicKey::try_from_slice(
+                &HEXLOWER
+                    .decode(
+                        b"d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+                    )
+                    .unwrap(),
+            )
+            .unwrap(),
+        );
+        let decode_sig = |sig: &[u8]| {
+            common::Signature::Ed25519(
+                ed25519::Signature::try_from_slice(
+                    &HEXLOWER.decode(sig).unwrap(),
+                )
+                .unwrap(),
+            )
+        };
+        // The signature of the empty message from the RFC
+        let empty_sig = decode_sig(
+            b"e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e065224901555fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b",
+        );
+        assert!(tx_prelude::verify_signature(&pk, &empty_sig, &[]));
+        // A signature of the raw bytes of "abc" with the same key
+        let sig = decode_sig(
+            b"80d724b01e7ca260f4cc7f8de7c95f73cfac615bab1f762b6435b6ec26c8cf6d2c758dae2f87399a8eeda1cbcd2835ac5ba66d6ecaa3aba5e567a751053dc207",
+        );
+        assert!(tx_prelude::verify_signature(&pk, &sig, data));
+        // A signature over a different message is rejected
+        assert!(!tx_prelude::verify_signature(&pk, &sig, &[]));
+        assert!(!tx_prelude::verify_signature(&pk, &empty_sig, data));
+        // A signature checked with another key is rejected
+        let other_pk = key::testing::keypair_2().ref_to();
+        assert!(!tx_prelude::verify_signature(&other_pk, &sig, data));
+        // A signature over the SHA-256 digest of the message, as made by
+        // `SigScheme::sign`, is not a signature of the raw message
+        let keypair = key::testing::keypair_1();
+        let hashed_sig = common::SigScheme::sign(&keypair, data);
+        assert!(!tx_prelude::verify_signature(
+            &keypair.ref_to(),
+            &hashed_sig,
+            data
+        ));
+    }
+
     /// An example how to write a VP host environment integration test
     #[test]
     fn test_vp_host_env() {
@@ -512,6 +512,12 @@ mod native_tx_host_env {
     ));
     native_host_fn!(tx_delete(key_ptr: u64, key_len: u64));
     native_host_fn!(tx_iter_prefix(prefix_ptr: u64, prefix_len: u64) -> u64);
+    native_host_fn!(tx_iter_prefix_range(
+        prefix_ptr: u64,
+        prefix_len: u64,
+        range_ptr: u64,
+        range_len: u64,
+    ) -> u64);
     native_host_fn!(tx_iter_next(iter_id: u64) -> i64);
     native_host_fn!(tx_insert_verifier(addr_ptr: u64, addr_len: u64));
     native_host_fn!(tx_update_validity_predicate(
@@ -543,6 +549,9 @@ mod native_tx_host_env {
     native_host_fn!(tx_log_string(str_ptr: u64, str_len: u64));
     native_host_fn!(tx_charge_gas(used_gas: u64));
     native_host_fn!("non-result", tx_set_commitment_sentinel());
+    native_host_fn!(tx_push_savepoint());
+    native_host_fn!(tx_rollback_to_savepoint());
+    native_host_fn!(tx_release_savepoint());
     native_host_fn!(tx_verify_tx_section_signature(
         hash_list_ptr: u64,
         hash_list_len: u64,
@@ -550,6 +559,17 @@ mod native_tx_host_env {
         public_keys_map_len: u64,
         threshold: u8,
     ) -> i64);
+    native_host_fn!(tx_hash_sha256(data_ptr: u64, data_len: u64, result_ptr: u64));
+    native_host_fn!(tx_hash_keccak256(data_ptr: u64, data_len: u64, result_ptr: u64));
+    native_host_fn!(tx_hash_blake2b(data_ptr: u64, data_len: u64, result_ptr: u64));
+    native_host_fn!(tx_verify_signature(
+        pk_ptr: u64,
+        pk_len: u64,
+        sig_ptr: u64,
+        sig_len: u64,
+        msg_ptr: u64,
+        msg_len: u64,
+    ) -> i64);
     native_host_fn!(tx_update_masp_note_commitment_tree(
         transaction_ptr: u64,
         transaction_len: u64,
@@ -327,6 +327,18 @@ mod native_vp_host_env {
     native_host_fn!(vp_has_key_post(key_ptr: u64, key_len: u64) -> i64);
     native_host_fn!(vp_iter_prefix_pre(prefix_ptr: u64, prefix_len: u64) -> u64);
     native_host_fn!(vp_iter_prefix_post(prefix_ptr: u64, prefix_len: u64) -> u64);
+    native_host_fn!(vp_iter_prefix_range_pre(
+        prefix_ptr: u64,
+        prefix_len: u64,
+        range_ptr: u64,
+        range_len: u64,
+    ) -> u64);
+    native_host_fn!(vp_iter_prefix_range_post(
+        prefix_ptr: u64,
+        prefix_len: u64,
+        range_ptr: u64,
+        range_len: u64,
+    ) -> u64);
     native_host_fn!(vp_iter_next(iter_id: u64) -> i64);
     native_host_fn!(vp_get_chain_id(result_ptr: u64));
     native_host_fn!(vp_get_block_height() -> u64);
@@ -352,6 +364,17 @@ mod native_vp_host_env {
         signer_len: u64,
         threshold: u8,
     ));
+    native_host_fn!(vp_hash_sha256(data_ptr: u64, data_len: u64, result_ptr: u64));
+    native_host_fn!(vp_hash_keccak256(data_ptr: u64, data_len: u64, result_ptr: u64));
+    native_host_fn!(vp_hash_blake2b(data_ptr: u64, data_len: u64, result_ptr: u64));
+    native_host_fn!(vp_verify_signature(
+        pk_ptr: u64,
+        pk_len: u64,
+        sig_ptr: u64,
+        sig_len: u64,
+        msg_ptr: u64,
+        msg_len: u64,
+    ) -> i64);
     native_host_fn!(vp_charge_gas(used_gas: u64));
     native_host_fn!(vp_yield_value(buf_ptr: u64, buf_len: u64));
 }
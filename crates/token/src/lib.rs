@@ -31,6 +31,8 @@ pub use namada_shielded_token::*;
 use namada_systems::parameters;
 pub use namada_trans_token::*;
 
+#[cfg(feature = "migrations")]
+mod schema;
 pub mod tx;
 
 /// Validity predicates
@@ -121,6 +123,20 @@ where
     Ok(())
 }
 
+/// Read the shielded parameters of the token, if any.
+pub fn read_params<S>(
+    storage: &S,
+    address: &Address,
+    denom: &Denomination,
+) -> Result<Option<ShieldedParams>>
+where
+    S: StorageRead,
+{
+    namada_shielded_token::read_params::<S, namada_trans_token::Store<()>>(
+        storage, address, denom,
+    )
+}
+
 /// Apply token logic for finalizing block (i.e. shielded token rewards)
 pub fn finalize_block<S, Params>(
     storage: &mut S,
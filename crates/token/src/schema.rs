@@ -0,0 +1,10 @@
+//! Storage schemas of the multitoken keys
+
+use namada_core::address::Address;
+use namada_core::token::{Amount, Denomination};
+use namada_migrations::storage_schema;
+
+storage_schema!(BALANCE: "#Multitoken/<token>/balance/<owner>" => Amount);
+storage_schema!(MINTED: "#Multitoken/<token>/balance/minted" => Amount);
+storage_schema!(MINTER: "#Multitoken/<token>/minter" => Address);
+storage_schema!(DENOMINATION: "<token>/denomination" => Denomination);
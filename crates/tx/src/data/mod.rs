@@ -31,6 +31,7 @@ use namada_core::hash::Hash;
 use namada_core::storage;
 use namada_events::Event;
 use namada_gas::WholeGas;
+use namada_gas::profile::GasProfile;
 use namada_macros::BorshDeserializer;
 #[cfg(feature = "migrations")]
 use namada_migrations::*;
@@ -225,6 +226,11 @@ impl InnerTxId<'_> {
 /// used
 pub struct DryRunResult(pub TxResult<String>, pub WholeGas);
 
+#[derive(Debug, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+/// The result of a dry run together with the breakdown of the gas used by
+/// category
+pub struct ProfiledDryRunResult(pub DryRunResult, pub GasProfile);
+
 /// Transaction application result. More specifically the set of inner tx
 /// results indexed by the inner tx hash
 // The generic is only used to return typed errors in protocol for error
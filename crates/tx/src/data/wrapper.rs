@@ -60,6 +60,10 @@ pub struct Fee {
     pub token: Address,
 }
 
+/// The estimated gas limits of transactions are rounded up to a multiple of
+/// this resolution
+pub const GAS_LIMIT_RESOLUTION: u64 = 1_000;
+
 /// Gas limit of a transaction
 #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[derive(
@@ -77,8 +81,7 @@ pub struct Fee {
 )]
 pub struct GasLimit(u64);
 
-/// Round the input number up to the next highest multiple
-/// of GAS_LIMIT_RESOLUTION
+/// Build a gas limit from a raw number
 impl From<u64> for GasLimit {
     fn from(amount: u64) -> GasLimit {
         Self(amount)
@@ -135,6 +138,15 @@ impl GasLimit {
             )
         })
     }
+
+    /// Round the gas limit up to the next highest multiple of
+    /// [`GAS_LIMIT_RESOLUTION`]. Returns `None` on overflow.
+    pub fn round_up(self) -> Option<Self> {
+        self.0
+            .div_ceil(GAS_LIMIT_RESOLUTION)
+            .checked_mul(GAS_LIMIT_RESOLUTION)
+            .map(Self)
+    }
 }
 
 /// A wrapper transaction with some metadata for inclusion and / or verification
@@ -174,12 +186,9 @@ impl WrapperTx {
         Self { fee, pk, gas_limit }
     }
 
-    /// Get the address of the implicit account associated
-    /// with the public key
-    /// NOTE: this is safe in case someone tried to use the masp address to
-    /// pay fees. All of the masp funds are kept in the established address,
-    /// while the implicit one has no funds leading to a tx failure
-    pub fn fee_payer(&self) -> Address {
+    /// Get the address of the implicit account associated with the public
+    /// key that signed the wrapper
+    pub fn signer(&self) -> Address {
         Address::from(&self.pk)
     }
 
@@ -221,4 +230,23 @@ mod test_gas_limits {
                 .expect("Test failed")
         );
     }
+
+    /// Test that gas limits are rounded up to the resolution
+    #[test]
+    fn test_gas_limit_round_up() {
+        assert_eq!(GasLimit(0).round_up(), Some(GasLimit(0)));
+        assert_eq!(
+            GasLimit(1).round_up(),
+            Some(GasLimit(GAS_LIMIT_RESOLUTION))
+        );
+        assert_eq!(
+            GasLimit(GAS_LIMIT_RESOLUTION).round_up(),
+            Some(GasLimit(GAS_LIMIT_RESOLUTION))
+        );
+        assert_eq!(
+            GasLimit(GAS_LIMIT_RESOLUTION.checked_add(1).unwrap()).round_up(),
+            Some(GasLimit(GAS_LIMIT_RESOLUTION.checked_mul(2).unwrap()))
+        );
+        assert_eq!(GasLimit(u64::MAX).round_up(), None);
+    }
 }
@@ -62,6 +62,11 @@ pub enum Section {
     MaspBuilder(MaspBuilder),
     /// Wrap a header with a section for the purposes of computing hashes
     Header(Header),
+    /// The account paying the fees of a wrapper tx out of a fee allowance
+    /// granted to the wrapper signer. It is kept out of the wrapper header so
+    /// that the encoding and the hash of the header of the wrapper txs without
+    /// a granter are unchanged.
+    FeeGranter(Address),
 }
 
 /// A Namada transaction header indicating where transaction subcomponents can
@@ -151,6 +156,10 @@ impl Section {
                 hasher
             }
             Self::Header(header) => header.hash(hasher),
+            Self::FeeGranter(granter) => {
+                hasher.update(granter.serialize_to_vec());
+                hasher
+            }
         }
     }
 
@@ -232,6 +241,15 @@ impl Section {
             None
         }
     }
+
+    /// Extract the fee granter from this section if possible
+    pub fn fee_granter(&self) -> Option<Address> {
+        if let Self::FeeGranter(granter) = self {
+            Some(granter.clone())
+        } else {
+            None
+        }
+    }
 }
 
 /// A section representing transaction data
@@ -159,6 +159,11 @@ impl Tx {
             }
 
             for section in rhs.sections.iter() {
+                // The fees of the batch are paid as specified by the wrapper
+                // of the left transaction
+                if let Section::FeeGranter(_) = section {
+                    continue;
+                }
                 // PartialEq implementation of Section relies on an
                 // implementation on the inner types that
                 // doesn't account for the possible salt
@@ -319,6 +324,22 @@ impl Tx {
         None
     }
 
+    /// Get the account paying the fees of the wrapper out of a fee allowance
+    /// granted to the wrapper signer, if any
+    pub fn fee_granter(&self) -> Option<Address> {
+        self.sections.iter().find_map(Section::fee_granter)
+    }
+
+    /// Get the address of the account paying the fees of the given wrapper of
+    /// this tx: the fee granter, if any, or else the implicit account
+    /// associated with the public key of the wrapper
+    /// NOTE: this is safe in case someone tried to use the masp address to
+    /// pay fees. All of the masp funds are kept in the established address,
+    /// while the implicit one has no funds leading to a tx failure
+    pub fn fee_payer(&self, wrapper: &WrapperTx) -> Address {
+        self.fee_granter().unwrap_or_else(|| wrapper.signer())
+    }
+
     /// Set the last transaction memo hash stored in the header
     pub fn set_memo_sechash(&mut self, hash: namada_core::hash::Hash) {
         let item = match self.header.batch.pop() {
@@ -694,6 +715,16 @@ impl Tx {
                 if hashes.len() != self.sections.len().saturating_add(1) {
                     return Err(TxError::RepeatedSections);
                 }
+                // A wrapper can only have one fee granter
+                if self
+                    .sections
+                    .iter()
+                    .filter(|section| matches!(section, Section::FeeGranter(_)))
+                    .count()
+                    > 1
+                {
+                    return Err(TxError::RepeatedSections);
+                }
                 self.verify_signature(&wrapper.pk, &hashes)
                     .map(Option::Some)
                     .map_err(|err| {
@@ -829,6 +860,18 @@ impl Tx {
         self
     }
 
+    /// Set the account paying the fees of the wrapper tx out of a fee
+    /// allowance, replacing the previous one if any. This must be done before
+    /// signing the wrapper. Has no effect if the tx is not a wrapper.
+    pub fn add_fee_granter(&mut self, fee_granter: Address) -> &mut Self {
+        if let TxType::Wrapper(_) = &self.header.tx_type {
+            self.sections
+                .retain(|section| !matches!(section, Section::FeeGranter(_)));
+            self.add_section(Section::FeeGranter(fee_granter));
+        }
+        self
+    }
+
     /// Add fee payer keypair to the tx builder
     pub fn sign_wrapper(&mut self, keypair: common::SecretKey) -> &mut Self {
         self.create_wrapper_sig(keypair)
@@ -1236,6 +1279,65 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_wrapper_tx_fee_granter() {
+        let sk1 = key::testing::keypair_1();
+        let pk1 = sk1.to_public();
+        let granter = key::testing::keypair_2().to_public();
+        let granter = Address::from(&granter);
+
+        let mut tx = Tx::default();
+        tx.add_wrapper(
+            data::wrapper::Fee {
+                amount_per_gas_unit: DenominatedAmount::native(1.into()),
+                token: nam(),
+            },
+            pk1.clone(),
+            1.into(),
+        );
+        let header_hash = tx.header_hash();
+        let wrapper = tx.header().wrapper().unwrap();
+        assert_eq!(tx.fee_granter(), None);
+        assert_eq!(tx.fee_payer(&wrapper), Address::from(&pk1));
+
+        // The granter is not part of the header
+        tx.add_fee_granter(granter.clone());
+        assert_eq!(tx.header_hash(), header_hash);
+        assert_eq!(tx.fee_granter(), Some(granter.clone()));
+        assert_eq!(tx.fee_payer(&wrapper), granter);
+
+        {
+            let mut tx = tx.clone();
+            tx.sign_wrapper(sk1.clone());
+
+            // The granter is signed by the wrapper signer
+            tx.validate_tx()
+                .expect("valid tx")
+                .expect("with authorization");
+        }
+
+        {
+            let mut tx = tx.clone();
+            tx.sign_wrapper(sk1.clone());
+            // Replace the granter after signing the tx
+            tx.add_fee_granter(nam());
+
+            // Should be rejected
+            tx.validate_tx()
+                .expect_err("invalid signature - wrong granter");
+        }
+
+        {
+            let mut tx = tx.clone();
+            // Add a second granter
+            tx.add_section(Section::FeeGranter(nam()));
+            tx.sign_wrapper(sk1);
+
+            // Should be rejected
+            assert_matches!(tx.validate_tx(), Err(TxError::RepeatedSections));
+        }
+    }
+
     #[test]
     fn test_protocol_tx_signing() {
         let sk1 = key::testing::keypair_1();
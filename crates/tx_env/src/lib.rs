@@ -105,6 +105,38 @@ pub trait TxEnv: StorageRead + StorageWrite {
     /// Set the sentinel for an invalid section commitment
     fn set_commitment_sentinel(&mut self);
 
+    /// Push a savepoint of the storage modifications, temporary writes and
+    /// events of the current transaction. Savepoints can be nested.
+    fn push_savepoint(&mut self) -> Result<()>;
+
+    /// Undo the changes of the current transaction made since its innermost
+    /// savepoint and remove the savepoint.
+    fn rollback_to_savepoint(&mut self) -> Result<()>;
+
+    /// Remove the innermost savepoint of the current transaction, keeping the
+    /// changes made since it was pushed.
+    fn release_savepoint(&mut self) -> Result<()>;
+
+    /// Attempt an optional action within a savepoint. Its changes are kept if
+    /// it succeeds and undone if it fails, in which case its error is returned
+    /// without failing the whole transaction.
+    fn try_with_savepoint<T, F>(&mut self, f: F) -> Result<T>
+    where
+        F: FnOnce(&mut Self) -> Result<T>,
+    {
+        self.push_savepoint()?;
+        match f(self) {
+            Ok(res) => {
+                self.release_savepoint()?;
+                Ok(res)
+            }
+            Err(err) => {
+                self.rollback_to_savepoint()?;
+                Err(err)
+            }
+        }
+    }
+
     /// Update the masp note commitment tree in storage with the new notes
     fn update_masp_note_commitment_tree(
         transaction: &MaspTransaction,
@@ -43,22 +43,22 @@ pub use namada_events::extend::Log;
 pub use namada_events::{
     EmitEvents, Event, EventLevel, EventToEmit, EventType,
 };
+pub use namada_gas as gas;
+pub use namada_governance as governance;
 pub use namada_governance::storage as gov_storage;
 pub use namada_macros::transaction;
+pub use namada_parameters as parameters;
 pub use namada_parameters::storage as parameters_storage;
 pub use namada_state::{
-    Error, OptionExt, Result, ResultExt, StorageRead, StorageWrite,
-    collections, iter_prefix, iter_prefix_bytes,
+    Error, KeyRange, OptionExt, Result, ResultExt, StorageRead, StorageWrite,
+    collections, iter_prefix, iter_prefix_bytes, iter_prefix_range,
+    iter_prefix_range_bytes,
 };
 use namada_token::MaspTransaction;
 pub use namada_tx::{BatchedTx, Section, Tx, action, data as transaction};
 pub use namada_tx_env::TxEnv;
 use namada_vm_env::tx::*;
 use namada_vm_env::{read_from_buffer, read_key_val_bytes_from_buffer};
-pub use {
-    namada_gas as gas, namada_governance as governance,
-    namada_parameters as parameters,
-};
 
 /// Log a string. The message will be printed at the `tracing::Level::Info`.
 pub fn log_string<T: AsRef<str>>(msg: T) {
@@ -230,6 +230,24 @@ impl StorageRead for Ctx {
         Ok(KeyValIterator(iter_id, PhantomData))
     }
 
+    fn iter_prefix_range<'iter>(
+        &'iter self,
+        prefix: &storage::Key,
+        range: &KeyRange,
+    ) -> Result<Self::PrefixIter<'iter>> {
+        let prefix = prefix.to_string();
+        let range = range.serialize_to_vec();
+        let iter_id = unsafe {
+            namada_tx_iter_prefix_range(
+                prefix.as_ptr() as _,
+                prefix.len() as _,
+                range.as_ptr() as _,
+                range.len() as _,
+            )
+        };
+        Ok(KeyValIterator(iter_id, PhantomData))
+    }
+
     fn iter_next<'iter>(
         &'iter self,
         iter: &mut Self::PrefixIter<'iter>,
@@ -408,6 +426,21 @@ impl TxEnv for Ctx {
         unsafe { namada_tx_set_commitment_sentinel() }
     }
 
+    fn push_savepoint(&mut self) -> Result<()> {
+        unsafe { namada_tx_push_savepoint() };
+        Ok(())
+    }
+
+    fn rollback_to_savepoint(&mut self) -> Result<()> {
+        unsafe { namada_tx_rollback_to_savepoint() };
+        Ok(())
+    }
+
+    fn release_savepoint(&mut self) -> Result<()> {
+        unsafe { namada_tx_release_savepoint() };
+        Ok(())
+    }
+
     fn update_masp_note_commitment_tree(
         transaction: &MaspTransaction,
     ) -> Result<bool> {
@@ -462,6 +495,68 @@ pub fn verify_signatures_of_pks(
     Ok(HostEnvResult::is_success(valid))
 }
 
+/// SHA-256 hash of the given bytes, computed and gas metered by the host
+pub fn hash_sha256(bytes: &[u8]) -> hash::Hash {
+    let mut result = [0; 32];
+    unsafe {
+        namada_tx_hash_sha256(
+            bytes.as_ptr() as _,
+            bytes.len() as _,
+            result.as_mut_ptr() as _,
+        );
+    }
+    hash::Hash(result)
+}
+
+/// Keccak-256 hash of the given bytes, computed and gas metered by the host
+pub fn hash_keccak256(bytes: &[u8]) -> keccak::KeccakHash {
+    let mut result = [0; 32];
+    unsafe {
+        namada_tx_hash_keccak256(
+            bytes.as_ptr() as _,
+            bytes.len() as _,
+            result.as_mut_ptr() as _,
+        );
+    }
+    keccak::KeccakHash(result)
+}
+
+/// 256-bit Blake2b hash of the given bytes, computed and gas metered by the
+/// host
+pub fn hash_blake2b(bytes: &[u8]) -> [u8; 32] {
+    let mut result = [0; 32];
+    unsafe {
+        namada_tx_hash_blake2b(
+            bytes.as_ptr() as _,
+            bytes.len() as _,
+            result.as_mut_ptr() as _,
+        );
+    }
+    result
+}
+
+/// Verify a signature over the raw bytes of an arbitrary message with the
+/// given public key
+pub fn verify_signature(
+    pk: &common::PublicKey,
+    sig: &common::Signature,
+    msg: &[u8],
+) -> bool {
+    let pk = pk.serialize_to_vec();
+    let sig = sig.serialize_to_vec();
+    let valid = unsafe {
+        namada_tx_verify_signature(
+            pk.as_ptr() as _,
+            pk.len() as _,
+            sig.as_ptr() as _,
+            sig.len() as _,
+            msg.as_ptr() as _,
+            msg.len() as _,
+        )
+    };
+    HostEnvResult::is_success(valid)
+}
+
 /// Update the masp note commitment tree in storage with the new notes
 pub fn update_masp_note_commitment_tree(
     transaction: &MaspTransaction,
@@ -10,6 +10,7 @@ use std::collections::BTreeSet;
 use std::fmt::Debug;
 use std::num::TryFromIntError;
 
+use blake2b_rs::Blake2bBuilder;
 use namada_account::AccountPublicKeysMap;
 use namada_core::address::{self, Address, ESTABLISHED_ADDRESS_BYTES_LEN};
 use namada_core::arith::checked;
@@ -19,8 +20,11 @@ use namada_core::collections::HashSet;
 use namada_core::decode;
 use namada_core::hash::Hash;
 use namada_core::internal::{HostEnvResult, KeyVal};
+use namada_core::keccak::keccak_hash;
+use namada_core::key::common;
 use namada_core::storage::{Key, TX_INDEX_LENGTH, TxIndex};
 use namada_events::{Event, EventTypeBuilder};
+use namada_gas::profile::GasProfiler;
 use namada_gas::{
     self as gas, Gas, GasMetering, MEMORY_ACCESS_GAS_PER_BYTE, TxGasMeter,
     VpGasMeter,
@@ -28,7 +32,7 @@ use namada_gas::{
 use namada_state::prefix_iter::{PrefixIteratorId, PrefixIterators};
 use namada_state::write_log::{self, WriteLog};
 use namada_state::{
-    DB, DBIter, InMemory, OptionExt, ResultExt, State, StateRead,
+    DB, DBIter, InMemory, KeyRange, OptionExt, ResultExt, State, StateRead,
     StorageHasher, StorageRead, StorageWrite,
 };
 pub use namada_state::{Error, Result};
@@ -235,6 +239,17 @@ where
     pub fn state(&self) -> TxHostEnvState<'_, D, H> {
         self.ctx.state()
     }
+
+    /// Call a host function, recording the gas that it consumes under the
+    /// given name when the gas meter has a profiler
+    pub fn profile_host_fn<RET>(
+        &mut self,
+        name: &str,
+        host_fn: impl FnOnce(&mut Self) -> RET,
+    ) -> RET {
+        let gas_meter = unsafe { self.ctx.gas_meter.get() };
+        profile_host_fn_gas(gas_meter, name, || host_fn(self))
+    }
 }
 
 impl<MEM, D, H, CA> Clone for TxVmEnv<MEM, D, H, CA>
@@ -466,6 +481,17 @@ where
     pub fn state(&self) -> VpHostEnvState<'_, D, H> {
         self.ctx.state()
     }
+
+    /// Call a host function, recording the gas that it consumes under the
+    /// given name when the gas meter has a profiler
+    pub fn profile_host_fn<RET>(
+        &mut self,
+        name: &str,
+        host_fn: impl FnOnce(&mut Self) -> RET,
+    ) -> RET {
+        let gas_meter = unsafe { self.ctx.gas_meter.get() };
+        profile_host_fn_gas(gas_meter, name, || host_fn(self))
+    }
 }
 
 impl<MEM, D, H, EVAL, CA> Clone for VpVmEnv<MEM, D, H, EVAL, CA>
@@ -695,6 +721,7 @@ where
     let value = state.read_bytes(&key)?;
     match value {
         Some(value) => {
+            profile_storage_read(env.ctx.gas_meter_and_sentinel().0, &value);
             let len: i64 = value
                 .len()
                 .try_into()
@@ -739,6 +766,7 @@ where
     consume_tx_gas::<MEM, D, H, CA>(env, gas)?;
     match log_val {
         Some(value) => {
+            profile_storage_read(env.ctx.gas_meter_and_sentinel().0, value);
             let len: i64 = value
                 .len()
                 .try_into()
@@ -816,6 +844,52 @@ where
         .id())
 }
 
+/// Storage prefix range iterator function exposed to the wasm VM Tx
+/// environment. The range is a borsh encoded [`KeyRange`] restricting the
+/// keys under the prefix and their iteration order.
+pub fn tx_iter_prefix_range<MEM, D, H, CA>(
+    env: &mut TxVmEnv<MEM, D, H, CA>,
+    prefix_ptr: u64,
+    prefix_len: u64,
+    range_ptr: u64,
+    range_len: u64,
+) -> TxResult<u64>
+where
+    MEM: VmMemory,
+    D: 'static + DB + for<'iter> DBIter<'iter>,
+    H: 'static + StorageHasher,
+    CA: WasmCacheAccess,
+{
+    let (prefix, gas) = env
+        .memory
+        .read_string(prefix_ptr, prefix_len.try_into()?)
+        .map_err(|e| TxRuntimeError::MemoryError(Box::new(e)))?;
+    consume_tx_gas::<MEM, D, H, CA>(env, gas)?;
+    let (range, gas) = env
+        .memory
+        .read_bytes(range_ptr, range_len.try_into()?)
+        .map_err(|e| TxRuntimeError::MemoryError(Box::new(e)))?;
+    consume_tx_gas::<MEM, D, H, CA>(env, gas)?;
+
+    tracing::debug!("tx_iter_prefix_range {}", prefix);
+
+    let prefix = Key::parse(prefix)?;
+    let range = KeyRange::try_from_slice(&range)
+        .map_err(TxRuntimeError::EncodingError)?;
+
+    let write_log = unsafe { env.ctx.write_log.get() };
+    let db = unsafe { env.ctx.db.get() };
+    let (iter, gas) =
+        namada_state::iter_prefix_range_post(write_log, db, &prefix, &range)?;
+    consume_tx_gas::<MEM, D, H, CA>(env, gas)?;
+
+    let iterators = unsafe { env.ctx.iterators.get_mut() };
+    Ok(iterators
+        .insert(iter)
+        .ok_or_err_msg("Iterator ID overflow")?
+        .id())
+}
+
 /// Storage prefix iterator next function exposed to the wasm VM Tx environment.
 /// It will try to read from the write log first and if no entry found then from
 /// the storage.
@@ -848,6 +922,10 @@ where
         consume_tx_gas::<MEM, D, H, CA>(env, checked!(iter_gas + log_gas)?)?;
         match log_val {
             Some(write_log::StorageModification::Write { value }) => {
+                profile_storage_read(
+                    env.ctx.gas_meter_and_sentinel().0,
+                    &value,
+                );
                 let key_val = borsh::to_vec(&KeyVal { key, val: value })
                     .map_err(TxRuntimeError::EncodingError)?;
                 let len: i64 = key_val
@@ -867,6 +945,7 @@ where
                 continue;
             }
             None => {
+                profile_storage_read(env.ctx.gas_meter_and_sentinel().0, &val);
                 let key_val = borsh::to_vec(&KeyVal { key, val })
                     .map_err(TxRuntimeError::EncodingError)?;
                 let len: i64 = key_val
@@ -917,6 +996,7 @@ where
 
     check_address_existence::<MEM, D, H, CA>(env, &key)?;
 
+    profile_storage_write(env.ctx.gas_meter_and_sentinel().0, &value);
     let mut state = env.state();
     state.write_bytes(&key, value)
 }
@@ -954,6 +1034,7 @@ where
 
     check_address_existence::<MEM, D, H, CA>(env, &key)?;
 
+    profile_storage_write(env.ctx.gas_meter_and_sentinel().0, &value);
     let mut state = env.state();
     let (gas, _size_diff) = state.write_log_mut().write_temp(&key, value)?;
     consume_tx_gas::<MEM, D, H, CA>(env, gas)
@@ -1136,6 +1217,9 @@ where
     let key = Key::parse(key)?;
     let state = env.state();
     let value = vp_host_fns::read_pre(gas_meter, &state, &key)?;
+    if let Some(value) = &value {
+        profile_storage_read(gas_meter, value);
+    }
     tracing::debug!(
         "vp_read_pre addr {}, key {}",
         unsafe { env.ctx.address.get() },
@@ -1183,6 +1267,9 @@ where
     let key = Key::parse(key)?;
     let state = env.state();
     let value = vp_host_fns::read_post(gas_meter, &state, &key)?;
+    if let Some(value) = &value {
+        profile_storage_read(gas_meter, value);
+    }
     Ok(match value {
         Some(value) => {
             let len: i64 = value.len().try_into()?;
@@ -1224,6 +1311,9 @@ where
     let key = Key::parse(key)?;
     let state = env.state();
     let value = vp_host_fns::read_temp(gas_meter, &state, &key)?;
+    if let Some(value) = &value {
+        profile_storage_read(gas_meter, value);
+    }
     Ok(match value {
         Some(value) => {
             let len: i64 = value.len().try_into()?;
@@ -1404,6 +1494,100 @@ where
         .id())
 }
 
+/// Storage prefix range iterator function for prior state (before tx execution)
+/// exposed to the wasm VM VP environment. The range is a borsh encoded
+/// [`KeyRange`].
+pub fn vp_iter_prefix_range_pre<MEM, D, H, EVAL, CA>(
+    env: &mut VpVmEnv<MEM, D, H, EVAL, CA>,
+    prefix_ptr: u64,
+    prefix_len: u64,
+    range_ptr: u64,
+    range_len: u64,
+) -> Result<u64>
+where
+    MEM: VmMemory,
+    D: 'static + DB + for<'iter> DBIter<'iter>,
+    H: 'static + StorageHasher,
+    EVAL: VpEvaluator,
+    CA: WasmCacheAccess,
+{
+    let (prefix, gas) = env
+        .memory
+        .read_string(prefix_ptr, prefix_len.try_into()?)
+        .map_err(Into::into)?;
+    let gas_meter = env.ctx.gas_meter();
+    vp_host_fns::add_gas(gas_meter, gas)?;
+    let (range, gas) = env
+        .memory
+        .read_bytes(range_ptr, range_len.try_into()?)
+        .map_err(Into::into)?;
+    vp_host_fns::add_gas(gas_meter, gas)?;
+
+    tracing::debug!("vp_iter_prefix_range_pre {}", prefix);
+
+    let prefix = Key::parse(prefix)?;
+    let range = KeyRange::try_from_slice(&range).into_storage_result()?;
+
+    let write_log = unsafe { env.ctx.write_log.get() };
+    let db = unsafe { env.ctx.db.get() };
+    let iter = vp_host_fns::iter_prefix_range_pre(
+        gas_meter, write_log, db, &prefix, &range,
+    )?;
+
+    let iterators = unsafe { env.ctx.iterators.get_mut() };
+    Ok(iterators
+        .insert(iter)
+        .ok_or_err_msg("Iterator ID overflow")?
+        .id())
+}
+
+/// Storage prefix range iterator function for posterior state (after tx
+/// execution) exposed to the wasm VM VP environment. The range is a borsh
+/// encoded [`KeyRange`].
+pub fn vp_iter_prefix_range_post<MEM, D, H, EVAL, CA>(
+    env: &mut VpVmEnv<MEM, D, H, EVAL, CA>,
+    prefix_ptr: u64,
+    prefix_len: u64,
+    range_ptr: u64,
+    range_len: u64,
+) -> Result<u64>
+where
+    MEM: VmMemory,
+    D: 'static + DB + for<'iter> DBIter<'iter>,
+    H: 'static + StorageHasher,
+    EVAL: VpEvaluator,
+    CA: WasmCacheAccess,
+{
+    let (prefix, gas) = env
+        .memory
+        .read_string(prefix_ptr, prefix_len.try_into()?)
+        .map_err(Into::into)?;
+    let gas_meter = env.ctx.gas_meter();
+    vp_host_fns::add_gas(gas_meter, gas)?;
+    let (range, gas) = env
+        .memory
+        .read_bytes(range_ptr, range_len.try_into()?)
+        .map_err(Into::into)?;
+    vp_host_fns::add_gas(gas_meter, gas)?;
+
+    tracing::debug!("vp_iter_prefix_range_post {}", prefix);
+
+    let prefix = Key::parse(prefix)?;
+    let range = KeyRange::try_from_slice(&range).into_storage_result()?;
+
+    let write_log = unsafe { env.ctx.write_log.get() };
+    let db = unsafe { env.ctx.db.get() };
+    let iter = vp_host_fns::iter_prefix_range_post(
+        gas_meter, write_log, db, &prefix, &range,
+    )?;
+
+    let iterators = unsafe { env.ctx.iterators.get_mut() };
+    Ok(iterators
+        .insert(iter)
+        .ok_or_err_msg("Iterator ID overflow")?
+        .id())
+}
+
 /// Storage prefix iterator for prior or posterior state function
 /// exposed to the wasm VM VP environment.
 ///
@@ -1427,6 +1611,7 @@ where
     if let Some(iter) = iterators.get_mut(iter_id) {
         let gas_meter = env.ctx.gas_meter();
         if let Some((key, val)) = vp_host_fns::iter_next(gas_meter, iter)? {
+            profile_storage_read(gas_meter, &val);
             let key_val = KeyVal { key, val }.serialize_to_vec();
             let len: i64 = key_val.len().try_into()?;
             let result_buffer = unsafe { env.ctx.result_buffer.get_mut() };
@@ -2022,6 +2207,129 @@ where
     }
 }
 
+/// Hash the given data with SHA-256, exposed to the wasm VM VP environment.
+/// The 32 bytes of the digest are written to the result pointer.
+pub fn vp_hash_sha256<MEM, D, H, EVAL, CA>(
+    env: &mut VpVmEnv<MEM, D, H, EVAL, CA>,
+    data_ptr: u64,
+    data_len: u64,
+    result_ptr: u64,
+) -> Result<()>
+where
+    MEM: VmMemory,
+    D: 'static + DB + for<'iter> DBIter<'iter>,
+    H: 'static + StorageHasher,
+    EVAL: VpEvaluator,
+    CA: WasmCacheAccess,
+{
+    vp_hash(env, HashFunction::Sha256, data_ptr, data_len, result_ptr)
+}
+
+/// Hash the given data with Keccak-256, exposed to the wasm VM VP
+/// environment. The 32 bytes of the digest are written to the result pointer.
+pub fn vp_hash_keccak256<MEM, D, H, EVAL, CA>(
+    env: &mut VpVmEnv<MEM, D, H, EVAL, CA>,
+    data_ptr: u64,
+    data_len: u64,
+    result_ptr: u64,
+) -> Result<()>
+where
+    MEM: VmMemory,
+    D: 'static + DB + for<'iter> DBIter<'iter>,
+    H: 'static + StorageHasher,
+    EVAL: VpEvaluator,
+    CA: WasmCacheAccess,
+{
+    vp_hash(env, HashFunction::Keccak256, data_ptr, data_len, result_ptr)
+}
+
+/// Hash the given data with BLAKE2b-256, exposed to the wasm VM VP
+/// environment. The 32 bytes of the digest are written to the result pointer.
+pub fn vp_hash_blake2b<MEM, D, H, EVAL, CA>(
+    env: &mut VpVmEnv<MEM, D, H, EVAL, CA>,
+    data_ptr: u64,
+    data_len: u64,
+    result_ptr: u64,
+) -> Result<()>
+where
+    MEM: VmMemory,
+    D: 'static + DB + for<'iter> DBIter<'iter>,
+    H: 'static + StorageHasher,
+    EVAL: VpEvaluator,
+    CA: WasmCacheAccess,
+{
+    vp_hash(env, HashFunction::Blake2b, data_ptr, data_len, result_ptr)
+}
+
+fn vp_hash<MEM, D, H, EVAL, CA>(
+    env: &mut VpVmEnv<MEM, D, H, EVAL, CA>,
+    hash_fn: HashFunction,
+    data_ptr: u64,
+    data_len: u64,
+    result_ptr: u64,
+) -> Result<()>
+where
+    MEM: VmMemory,
+    D: 'static + DB + for<'iter> DBIter<'iter>,
+    H: 'static + StorageHasher,
+    EVAL: VpEvaluator,
+    CA: WasmCacheAccess,
+{
+    let (data, gas) = env
+        .memory
+        .read_bytes(data_ptr, data_len.try_into()?)
+        .map_err(Into::into)?;
+    let gas_meter = env.ctx.gas_meter();
+    vp_host_fns::add_gas(gas_meter, gas)?;
+
+    vp_host_fns::add_gas(gas_meter, hash_fn.gas(data.len())?)?;
+    let digest = hash_fn.hash(&data);
+    let gas = env
+        .memory
+        .write_bytes(result_ptr, digest)
+        .map_err(Into::into)?;
+    vp_host_fns::add_gas(gas_meter, gas)
+}
+
+/// Verify a signature over an arbitrary message, exposed to the wasm VM VP
+/// environment. The public key and the signature are borsh encoded
+/// [`common::PublicKey`] and [`common::Signature`] and the signature is over
+/// the raw message bytes (see [`common::verify_raw_signature`]).
+#[allow(clippy::too_many_arguments)]
+pub fn vp_verify_signature<MEM, D, H, EVAL, CA>(
+    env: &mut VpVmEnv<MEM, D, H, EVAL, CA>,
+    pk_ptr: u64,
+    pk_len: u64,
+    sig_ptr: u64,
+    sig_len: u64,
+    msg_ptr: u64,
+    msg_len: u64,
+) -> Result<i64>
+where
+    MEM: VmMemory,
+    D: 'static + DB + for<'iter> DBIter<'iter>,
+    H: 'static + StorageHasher,
+    EVAL: VpEvaluator,
+    CA: WasmCacheAccess,
+{
+    let gas_meter = env.ctx.gas_meter();
+    let mut read = |ptr: u64, len: u64| -> Result<Vec<u8>> {
+        let (bytes, gas) = env
+            .memory
+            .read_bytes(ptr, len.try_into()?)
+            .map_err(Into::into)?;
+        vp_host_fns::add_gas(gas_meter, gas)?;
+        Ok(bytes)
+    };
+    let pk = read(pk_ptr, pk_len)?;
+    let sig = read(sig_ptr, sig_len)?;
+    let msg = read(msg_ptr, msg_len)?;
+
+    vp_host_fns::add_gas(gas_meter, verify_signature_gas(msg.len())?)?;
+    let valid = verify_signature(&pk, &sig, &msg);
+    Ok(HostEnvResult::from(valid).to_i64())
+}
+
 /// Log a string from exposed to the wasm VM Tx environment. The message will be
 /// printed at the [`tracing::Level::INFO`]. This function is for development
 /// only.
@@ -2102,6 +2410,61 @@ where
     Ok(())
 }
 
+/// Push a savepoint of the current tx's write log, exposed to the wasm VM Tx
+/// environment. The changes made after it can be undone with
+/// [`tx_rollback_to_savepoint`] or kept with [`tx_release_savepoint`].
+pub fn tx_push_savepoint<MEM, D, H, CA>(
+    env: &mut TxVmEnv<MEM, D, H, CA>,
+) -> TxResult<()>
+where
+    MEM: VmMemory,
+    D: 'static + DB + for<'iter> DBIter<'iter>,
+    H: 'static + StorageHasher,
+    CA: WasmCacheAccess,
+{
+    tracing::debug!("tx_push_savepoint");
+
+    let mut state = env.state();
+    let gas = state.write_log_mut().push_savepoint()?;
+    consume_tx_gas::<MEM, D, H, CA>(env, gas)
+}
+
+/// Undo the changes of the current tx made since its innermost savepoint and
+/// remove the savepoint, exposed to the wasm VM Tx environment.
+pub fn tx_rollback_to_savepoint<MEM, D, H, CA>(
+    env: &mut TxVmEnv<MEM, D, H, CA>,
+) -> TxResult<()>
+where
+    MEM: VmMemory,
+    D: 'static + DB + for<'iter> DBIter<'iter>,
+    H: 'static + StorageHasher,
+    CA: WasmCacheAccess,
+{
+    tracing::debug!("tx_rollback_to_savepoint");
+
+    let mut state = env.state();
+    state.write_log_mut().rollback_to_savepoint()?;
+    Ok(())
+}
+
+/// Remove the innermost savepoint of the current tx keeping its changes,
+/// exposed to the wasm VM Tx environment.
+pub fn tx_release_savepoint<MEM, D, H, CA>(
+    env: &mut TxVmEnv<MEM, D, H, CA>,
+) -> TxResult<()>
+where
+    MEM: VmMemory,
+    D: 'static + DB + for<'iter> DBIter<'iter>,
+    H: 'static + StorageHasher,
+    CA: WasmCacheAccess,
+{
+    tracing::debug!("tx_release_savepoint");
+
+    let mut state = env.state();
+    state.write_log_mut().release_savepoint()?;
+    Ok(())
+}
+
 /// Set the sentinel for an invalid tx section commitment
 pub fn tx_set_commitment_sentinel<MEM, D, H, CA>(
     env: &mut TxVmEnv<MEM, D, H, CA>,
@@ -2192,6 +2555,122 @@ where
     .into_storage_result()
 }
 
+/// Hash the given data with SHA-256, exposed to the wasm VM Tx environment.
+/// The 32 bytes of the digest are written to the result pointer.
+pub fn tx_hash_sha256<MEM, D, H, CA>(
+    env: &mut TxVmEnv<MEM, D, H, CA>,
+    data_ptr: u64,
+    data_len: u64,
+    result_ptr: u64,
+) -> TxResult<()>
+where
+    MEM: VmMemory,
+    D: 'static + DB + for<'iter> DBIter<'iter>,
+    H: 'static + StorageHasher,
+    CA: WasmCacheAccess,
+{
+    tx_hash(env, HashFunction::Sha256, data_ptr, data_len, result_ptr)
+}
+
+/// Hash the given data with Keccak-256, exposed to the wasm VM Tx
+/// environment. The 32 bytes of the digest are written to the result pointer.
+pub fn tx_hash_keccak256<MEM, D, H, CA>(
+    env: &mut TxVmEnv<MEM, D, H, CA>,
+    data_ptr: u64,
+    data_len: u64,
+    result_ptr: u64,
+) -> TxResult<()>
+where
+    MEM: VmMemory,
+    D: 'static + DB + for<'iter> DBIter<'iter>,
+    H: 'static + StorageHasher,
+    CA: WasmCacheAccess,
+{
+    tx_hash(env, HashFunction::Keccak256, data_ptr, data_len, result_ptr)
+}
+
+/// Hash the given data with BLAKE2b-256, exposed to the wasm VM Tx
+/// environment. The 32 bytes of the digest are written to the result pointer.
+pub fn tx_hash_blake2b<MEM, D, H, CA>(
+    env: &mut TxVmEnv<MEM, D, H, CA>,
+    data_ptr: u64,
+    data_len: u64,
+    result_ptr: u64,
+) -> TxResult<()>
+where
+    MEM: VmMemory,
+    D: 'static + DB + for<'iter> DBIter<'iter>,
+    H: 'static + StorageHasher,
+    CA: WasmCacheAccess,
+{
+    tx_hash(env, HashFunction::Blake2b, data_ptr, data_len, result_ptr)
+}
+
+fn tx_hash<MEM, D, H, CA>(
+    env: &mut TxVmEnv<MEM, D, H, CA>,
+    hash_fn: HashFunction,
+    data_ptr: u64,
+    data_len: u64,
+    result_ptr: u64,
+) -> TxResult<()>
+where
+    MEM: VmMemory,
+    D: 'static + DB + for<'iter> DBIter<'iter>,
+    H: 'static + StorageHasher,
+    CA: WasmCacheAccess,
+{
+    let (data, gas) = env
+        .memory
+        .read_bytes(data_ptr, data_len.try_into()?)
+        .map_err(|e| TxRuntimeError::MemoryError(Box::new(e)))?;
+    consume_tx_gas::<MEM, D, H, CA>(env, gas)?;
+
+    consume_tx_gas::<MEM, D, H, CA>(env, hash_fn.gas(data.len())?)?;
+    let digest = hash_fn.hash(&data);
+    let gas = env
+        .memory
+        .write_bytes(result_ptr, digest)
+        .map_err(|e| TxRuntimeError::MemoryError(Box::new(e)))?;
+    consume_tx_gas::<MEM, D, H, CA>(env, gas)
+}
+
+/// Verify a signature over an arbitrary message, exposed to the wasm VM Tx
+/// environment. The public key and the signature are borsh encoded
+/// [`common::PublicKey`] and [`common::Signature`] and the signature is over
+/// the raw message bytes (see [`common::verify_raw_signature`]).
+#[allow(clippy::too_many_arguments)]
+pub fn tx_verify_signature<MEM, D, H, CA>(
+    env: &mut TxVmEnv<MEM, D, H, CA>,
+    pk_ptr: u64,
+    pk_len: u64,
+    sig_ptr: u64,
+    sig_len: u64,
+    msg_ptr: u64,
+    msg_len: u64,
+) -> TxResult<i64>
+where
+    MEM: VmMemory,
+    D: 'static + DB + for<'iter> DBIter<'iter>,
+    H: 'static + StorageHasher,
+    CA: WasmCacheAccess,
+{
+    let mut read = |ptr: u64, len: u64| -> TxResult<Vec<u8>> {
+        let (bytes, gas) = env
+            .memory
+            .read_bytes(ptr, len.try_into()?)
+            .map_err(|e| TxRuntimeError::MemoryError(Box::new(e)))?;
+        consume_tx_gas::<MEM, D, H, CA>(env, gas)?;
+        Ok(bytes)
+    };
+    let pk = read(pk_ptr, pk_len)?;
+    let sig = read(sig_ptr, sig_len)?;
+    let msg = read(msg_ptr, msg_len)?;
+
+    consume_tx_gas::<MEM, D, H, CA>(env, verify_signature_gas(msg.len())?)?;
+    let valid = verify_signature(&pk, &sig, &msg);
+    Ok(HostEnvResult::from(valid).to_i64())
+}
+
 /// Appends the new note commitments to the tree in storage
 pub fn tx_update_masp_note_commitment_tree<MEM, D, H, CA>(
     env: &mut TxVmEnv<MEM, D, H, CA>,
@@ -2369,6 +2848,69 @@ where
     Ok(())
 }
 
+/// The hash functions exposed to the wasm VM environments
+#[derive(Debug, Clone, Copy)]
+pub enum HashFunction {
+    /// SHA-256
+    Sha256,
+    /// Keccak-256
+    Keccak256,
+    /// BLAKE2b with a 256-bit digest
+    Blake2b,
+}
+
+impl HashFunction {
+    /// The gas cost of hashing data of the given length
+    fn gas(self, len: usize) -> Result<Gas> {
+        let gas_per_byte = match self {
+            Self::Sha256 => gas::SHA256_GAS_PER_BYTE,
+            Self::Keccak256 => gas::KECCAK256_GAS_PER_BYTE,
+            Self::Blake2b => gas::BLAKE2B_GAS_PER_BYTE,
+        };
+        let len = u64::try_from(len)?;
+        Ok(checked!(gas::HASH_FIXED_GAS + len * gas_per_byte)?.into())
+    }
+
+    /// Hash the data into a 32 bytes digest
+    pub fn hash(self, data: &[u8]) -> [u8; 32] {
+        match self {
+            Self::Sha256 => Hash::sha256(data).0,
+            Self::Keccak256 => keccak_hash(data).0,
+            Self::Blake2b => {
+                let mut digest = [0; 32];
+                let mut hasher = Blake2bBuilder::new(digest.len()).build();
+                hasher.update(data);
+                hasher.finalize(&mut digest);
+                digest
+            }
+        }
+    }
+}
+
+/// The gas cost of verifying a signature over a message of the given length,
+/// which gets hashed by the signature scheme
+fn verify_signature_gas(msg_len: usize) -> Result<Gas> {
+    let len = u64::try_from(msg_len)?;
+    Ok(checked!(
+        gas::VERIFY_TX_SIG_GAS
+            + gas::HASH_FIXED_GAS
+            + len * gas::SHA256_GAS_PER_BYTE
+    )?
+    .into())
+}
+
+/// Verify a signature over an arbitrary message. Any public key or signature
+/// that cannot be decoded makes the verification fail.
+fn verify_signature(pk: &[u8], sig: &[u8], msg: &[u8]) -> bool {
+    let (Ok(pk), Ok(sig)) = (
+        common::PublicKey::try_from_slice(pk),
+        common::Signature::try_from_slice(sig),
+    ) else {
+        return false;
+    };
+    common::verify_raw_signature(&pk, msg, &sig).is_ok()
+}
+
 // Internal funtion to charge gas for txs. Called by the other functions in this
 // file while the public version is left to be used directly from wasm and as a
 // hook for gas instrumentation
@@ -2400,6 +2942,65 @@ where
         .into_storage_result()
 }
 
+/// Call a host function, recording the gas that it consumes under the given
+/// name when the gas meter has a profiler
+fn profile_host_fn_gas<N, RET>(
+    gas_meter: &RefCell<gas_meter::GasMeter<N>>,
+    name: &str,
+    host_fn: impl FnOnce() -> RET,
+) -> RET
+where
+    N: GasMetering,
+{
+    let Some(profiler) = gas_meter.borrow().gas_profiler().cloned() else {
+        return host_fn();
+    };
+    let gas_before = gas_meter.borrow().get_consumed_gas();
+    let ret = host_fn();
+    let gas_after = gas_meter.borrow().get_consumed_gas();
+    profiler.record_host_fn(
+        name,
+        gas_after.checked_sub(gas_before).unwrap_or_default(),
+    );
+    ret
+}
+
+/// Apply the given recording to the gas profiler of the meter, if any
+fn with_gas_profiler<N>(
+    gas_meter: &RefCell<gas_meter::GasMeter<N>>,
+    record: impl FnOnce(&GasProfiler),
+) where
+    N: GasMetering,
+{
+    if let Some(profiler) = gas_meter.borrow().gas_profiler() {
+        record(profiler)
+    }
+}
+
+/// Record a value read from storage in the gas profile, if any
+fn profile_storage_read<N>(
+    gas_meter: &RefCell<gas_meter::GasMeter<N>>,
+    value: &[u8],
+) where
+    N: GasMetering,
+{
+    with_gas_profiler(gas_meter, |profiler| {
+        profiler.record_storage_read(value.len() as u64)
+    })
+}
+
+/// Record a value written to storage in the gas profile, if any
+fn profile_storage_write<N>(
+    gas_meter: &RefCell<gas_meter::GasMeter<N>>,
+    value: &[u8],
+) where
+    N: GasMetering,
+{
+    with_gas_profiler(gas_meter, |profiler| {
+        profiler.record_storage_write(value.len() as u64)
+    })
+}
+
 /// A helper module for testing
 #[cfg(feature = "testing")]
 pub mod testing {
@@ -1,5 +1,6 @@
 //! Gas meter used in the vm.
 
+use namada_gas::profile::GasProfiler;
 use namada_gas::{Gas, GasMeterKind, GasMetering, TxGasMeter, VpGasMeter};
 
 #[cfg(feature = "wasm-runtime")]
@@ -175,4 +176,12 @@ impl<N: GasMetering> GasMetering for GasMeter<N> {
             Self::Wasm(meter) => meter.get_gas_scale(),
         }
     }
+
+    fn gas_profiler(&self) -> Option<&GasProfiler> {
+        match self {
+            Self::Native(meter) => meter.gas_profiler(),
+            #[cfg(feature = "wasm-runtime")]
+            Self::Wasm(meter) => meter.gas_profiler(),
+        }
+    }
 }
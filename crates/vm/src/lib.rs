@@ -27,6 +27,8 @@ pub mod memory;
 pub mod types;
 #[cfg(feature = "wasm-runtime")]
 pub mod wasm;
+#[cfg(feature = "wasmtime-runtime")]
+pub mod wasmtime_runtime;
 use thiserror::Error;
 
 const UNTRUSTED_WASM_FEATURES: WasmFeatures = WasmFeatures {
@@ -4,10 +4,13 @@
 //! limit and a file system cache of serialized modules.
 
 use std::collections::hash_map::RandomState;
+use std::ffi::OsStr;
 use std::fs;
 use std::marker::PhantomData;
 use std::num::NonZeroUsize;
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, RwLock};
 use std::thread::sleep;
 use std::time::Duration;
@@ -17,6 +20,7 @@ use namada_core::collections::HashMap;
 use namada_core::control_flow::time::{ExponentialBackoff, SleepStrategy};
 use namada_core::hash::Hash;
 use namada_gas::GasMeterKind;
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
 use wasmer::{Module, Store};
 use wasmer_cache::{FileSystemCache, Hash as CacheHash};
 
@@ -71,6 +75,53 @@ enum Compilation {
     Done,
 }
 
+/// The progress of pre-warming a cache, see [`Cache::pre_warm`]
+#[derive(Debug)]
+pub struct PreWarmProgress {
+    /// The number of WASM to compile
+    total: usize,
+    /// The number of WASM compiled so far
+    compiled: AtomicUsize,
+    /// The number of WASM that failed to compile so far
+    failed: AtomicUsize,
+}
+
+impl PreWarmProgress {
+    /// Get the number of WASM to compile
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    /// Get the number of WASM compiled so far
+    pub fn compiled(&self) -> usize {
+        self.compiled.load(Ordering::Acquire)
+    }
+
+    /// Get the number of WASM that failed to compile so far
+    pub fn failed(&self) -> usize {
+        self.failed.load(Ordering::Acquire)
+    }
+
+    /// Get the number of WASM processed so far
+    pub fn processed(&self) -> usize {
+        self.compiled().saturating_add(self.failed())
+    }
+
+    /// Check if all the WASM have been processed
+    pub fn is_finished(&self) -> bool {
+        self.processed() >= self.total
+    }
+
+    fn record(&self, compiled: bool) {
+        let counter = if compiled {
+            &self.compiled
+        } else {
+            &self.failed
+        };
+        counter.fetch_add(1, Ordering::AcqRel);
+    }
+}
+
 /// Configures the cache scale of modules that limits the maximum capacity
 /// of the cache (CLruCache::len + CLruCache::weight <= CLruCache::capacity).
 #[derive(Debug)]
@@ -95,20 +146,7 @@ impl<N: CacheName, A: WasmCacheAccess> Cache<N, A> {
         );
         let in_memory = Arc::new(RwLock::new(cache));
 
-        let target_hash = {
-            use std::hash::{Hash, Hasher};
-            let mut hasher = std::hash::DefaultHasher::new();
-            wasmer::Target::default().hash(&mut hasher);
-            hasher.finish()
-        };
-        let version_prefix =
-            option_env!("GIT_DESCRIBED").unwrap_or(env!("CARGO_PKG_VERSION"));
-        let version = format!(
-            "{version_prefix}{}{:x}",
-            concat!("_", env!("RUSTUP_TOOLCHAIN"), "_"),
-            target_hash,
-        );
-        let dir = dir.into().join(version);
+        let dir = dir.into().join(cache_version());
 
         fs::create_dir_all(&dir)
             .expect("Couldn't create the wasm cache directory");
@@ -497,78 +535,92 @@ impl<N: CacheName, A: WasmCacheAccess> Cache<N, A> {
                     let dir = self.dir.clone();
                     let store = self.store.clone();
                     std::thread::spawn(move || {
-                        tracing::info!("Compiling WASM {}.", hash.to_string());
-
-                        let _module = match wasm::run::prepare_wasm_code(
-                            code,
-                            gas_meter_kind,
-                        ) {
-                            Ok(code) => {
-                                match compile(code, &store) {
-                                    Ok(module) => {
-                                        // Write the file
-                                        file_write_module(
-                                            &dir,
-                                            &module,
-                                            &hash,
-                                            gas_meter_kind,
-                                        );
-
-                                        // Update progress
-                                        let mut progress =
-                                            progress.write().unwrap();
-                                        progress.insert(key, Compilation::Done);
-                                        tracing::info!(
-                                            "Finished compiling WASM {hash}."
-                                        );
-                                        if progress.values().all(
-                                            |compilation| {
-                                                matches!(
-                                                    compilation,
-                                                    Compilation::Done
-                                                )
-                                            },
-                                        ) {
-                                            tracing::info!(
-                                                "Finished compiling all {}.",
-                                                N::name()
-                                            )
-                                        }
-                                        module
-                                    }
-                                    Err(err) => {
-                                        let mut progress =
-                                            progress.write().unwrap();
-                                        tracing::info!(
-                                            "Failed to compile WASM {} with {}",
-                                            hash.to_string(),
-                                            err
-                                        );
-                                        progress.swap_remove(&key);
-                                        return Err(err);
-                                    }
-                                }
-                            }
-                            Err(err) => {
-                                let mut progress = progress.write().unwrap();
-                                tracing::info!(
-                                    "Failed to prepare WASM {} with {}",
-                                    hash.to_string(),
-                                    err
-                                );
-                                progress.swap_remove(&key);
-                                return Err(err);
-                            }
-                        };
-
-                        let res: Result<(), wasm::run::Error> = Ok(());
-                        res
+                        compile_to_file::<N>(&dir, &store, &progress, key, code)
                     });
                 }
             }
         }
     }
 
+    /// Pre-warm the cache by compiling the given WASM codes to files, skipping
+    /// those that are already compiled. The compilation runs in the background
+    /// on a pool of `num_threads` threads and the function returns immediately
+    /// with a handle to its progress, which also gets logged.
+    pub fn pre_warm(
+        &mut self,
+        codes: Vec<Vec<u8>>,
+        gas_meter_kind: GasMeterKind,
+        num_threads: NonZeroUsize,
+    ) -> Arc<PreWarmProgress> {
+        let mut to_compile = vec![];
+        if A::is_read_write() {
+            let mut progress = self.progress.write().unwrap();
+            for code in codes {
+                let hash = hash_of_code(&code);
+                let key = CacheKey {
+                    code_hash: hash,
+                    gas_meter_kind,
+                };
+                if progress.contains_key(&key) {
+                    // Already known, do nothing
+                    continue;
+                }
+                if module_file_exists(&self.dir, &hash, gas_meter_kind) {
+                    progress.insert(key, Compilation::Done);
+                    continue;
+                }
+                progress.insert(key, Compilation::Compiling);
+                to_compile.push((key, code));
+            }
+        }
+
+        let pre_warm = Arc::new(PreWarmProgress {
+            total: to_compile.len(),
+            compiled: AtomicUsize::new(0),
+            failed: AtomicUsize::new(0),
+        });
+        if to_compile.is_empty() {
+            return pre_warm;
+        }
+        tracing::info!(
+            "Pre-warming the {} cache with {} WASM on {num_threads} threads.",
+            N::name(),
+            to_compile.len(),
+        );
+
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(num_threads.get())
+            .thread_name(|ix| format!("{}-wasm-pre-warm-{ix}", N::name()))
+            .build()
+            .expect("Couldn't build the wasm pre-warm thread pool");
+        let progress = self.progress.clone();
+        let dir = self.dir.clone();
+        let store = self.store.clone();
+        let handle = pre_warm.clone();
+        // NB: the pool's threads terminate once the spawned work is done
+        pool.spawn(move || {
+            to_compile.into_par_iter().for_each(|(key, code)| {
+                let result =
+                    compile_to_file::<N>(&dir, &store, &progress, key, code);
+                handle.record(result.is_ok());
+                tracing::info!(
+                    "Pre-warmed {}/{} {} WASM.",
+                    handle.processed(),
+                    handle.total,
+                    N::name()
+                );
+            });
+            tracing::info!(
+                "Finished pre-warming the {} cache: {} compiled, {} failed.",
+                N::name(),
+                handle.compiled(),
+                handle.failed(),
+            );
+        });
+
+        pre_warm
+    }
+
     /// Get a read-only cache handle.
     pub fn read_only(&self) -> Cache<N, WasmCacheRoAccess> {
         Cache {
@@ -582,6 +634,173 @@ impl<N: CacheName, A: WasmCacheAccess> Cache<N, A> {
     }
 }
 
+/// The modules found in a cache directory, see [`verify`]
+#[derive(Debug, Default)]
+pub struct CacheReport {
+    /// The modules compiled by this version that can be loaded
+    pub valid: Vec<(GasMeterKind, Hash, PathBuf)>,
+    /// The modules compiled by this version that can't be loaded
+    pub invalid: Vec<PathBuf>,
+    /// The directories of the modules compiled by other versions
+    pub stale: Vec<PathBuf>,
+}
+
+/// Verify the modules persisted in a cache directory, by loading all the
+/// modules compiled by this version. The directory is the one that the
+/// [`Cache`] was created with.
+pub fn verify(dir: impl AsRef<Path>) -> std::io::Result<CacheReport> {
+    let mut report = CacheReport::default();
+    let dir = dir.as_ref();
+    if !dir.is_dir() {
+        return Ok(report);
+    }
+    let version = cache_version();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.file_name() != Some(OsStr::new(&version)) {
+            report.stale.push(path);
+        }
+    }
+
+    let dir = dir.join(version);
+    let store = store();
+    for gas_meter_kind in [GasMeterKind::HostFn, GasMeterKind::MutGlobal] {
+        let kind_dir = dir.join(gas_meter_kind_dir(gas_meter_kind));
+        if !kind_dir.is_dir() {
+            continue;
+        }
+        for entry in fs::read_dir(kind_dir)? {
+            let path = entry?.path();
+            let hash = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| Hash::from_str(name).ok());
+            match hash {
+                Some(hash)
+                    if module_file_exists(&dir, &hash, gas_meter_kind)
+                        && file_load_module(
+                            &dir,
+                            &hash,
+                            gas_meter_kind,
+                            &store,
+                        )
+                        .is_ok() =>
+                {
+                    report.valid.push((gas_meter_kind, hash, path))
+                }
+                _ => report.invalid.push(path),
+            }
+        }
+    }
+    Ok(report)
+}
+
+/// Prune a cache directory, removing the modules compiled by other versions,
+/// the modules that can't be loaded and the modules of the code hashes that
+/// are not retained. Returns the removed paths.
+pub fn prune(
+    dir: impl AsRef<Path>,
+    retain: impl Fn(&Hash) -> bool,
+) -> std::io::Result<Vec<PathBuf>> {
+    let CacheReport {
+        valid,
+        invalid,
+        stale,
+    } = verify(dir)?;
+    let removed: Vec<PathBuf> = stale
+        .into_iter()
+        .chain(invalid)
+        .chain(
+            valid
+                .into_iter()
+                .filter_map(|(_, hash, path)| (!retain(&hash)).then_some(path)),
+        )
+        .collect();
+    for path in &removed {
+        if path.is_dir() {
+            fs::remove_dir_all(path)?;
+        } else {
+            fs::remove_file(path)?;
+        }
+    }
+    Ok(removed)
+}
+
+/// The version of the compiled modules, which depends on the version of this
+/// crate, the toolchain and the compilation target
+fn cache_version() -> String {
+    let target_hash = {
+        use std::hash::{Hash, Hasher};
+        let mut hasher = std::hash::DefaultHasher::new();
+        wasmer::Target::default().hash(&mut hasher);
+        hasher.finish()
+    };
+    let version_prefix =
+        option_env!("GIT_DESCRIBED").unwrap_or(env!("CARGO_PKG_VERSION"));
+    format!(
+        "{version_prefix}{}{:x}",
+        concat!("_", env!("RUSTUP_TOOLCHAIN"), "_"),
+        target_hash,
+    )
+}
+
+/// Compile a WASM module to a file and update the compilation progress.
+fn compile_to_file<N: CacheName>(
+    dir: &Path,
+    store: &Store,
+    progress: &RwLock<HashMap<CacheKey, Compilation>>,
+    key: CacheKey,
+    code: Vec<u8>,
+) -> Result<(), wasm::run::Error> {
+    let CacheKey {
+        code_hash: hash,
+        gas_meter_kind,
+    } = key;
+    tracing::info!("Compiling WASM {}.", hash.to_string());
+
+    let code = match wasm::run::prepare_wasm_code(code, gas_meter_kind) {
+        Ok(code) => code,
+        Err(err) => {
+            let mut progress = progress.write().unwrap();
+            tracing::info!(
+                "Failed to prepare WASM {} with {}",
+                hash.to_string(),
+                err
+            );
+            progress.swap_remove(&key);
+            return Err(err);
+        }
+    };
+    match compile(code, store) {
+        Ok(module) => {
+            // Write the file
+            file_write_module(dir, &module, &hash, gas_meter_kind);
+
+            // Update progress
+            let mut progress = progress.write().unwrap();
+            progress.insert(key, Compilation::Done);
+            tracing::info!("Finished compiling WASM {hash}.");
+            if progress
+                .values()
+                .all(|compilation| matches!(compilation, Compilation::Done))
+            {
+                tracing::info!("Finished compiling all {}.", N::name())
+            }
+            Ok(())
+        }
+        Err(err) => {
+            let mut progress = progress.write().unwrap();
+            tracing::info!(
+                "Failed to compile WASM {} with {}",
+                hash.to_string(),
+                err
+            );
+            progress.swap_remove(&key);
+            Err(err)
+        }
+    }
+}
+
 fn hash_of_code(code: impl AsRef<[u8]>) -> Hash {
     Hash::sha256(code.as_ref())
 }
@@ -1303,6 +1522,109 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_pre_warm() {
+        let vp_always_true = load_wasm(TestWasms::VpAlwaysTrue.path());
+        let vp_eval = load_wasm(TestWasms::VpEval.path());
+        // Some random bytes
+        let invalid_wasm = vec![1_u8];
+        let invalid_hash = hash_of_code(&invalid_wasm);
+        let (mut cache, _tmp_dir) = testing::cache::<TestCache>();
+        let gas_meter_kind = GasMeterKind::MutGlobal;
+
+        // `vp_always_true` is already compiled, so it's skipped
+        cache.pre_compile(&vp_always_true.code, gas_meter_kind);
+        cache.fetch(&vp_always_true.hash, gas_meter_kind).unwrap();
+
+        let progress = cache.pre_warm(
+            vec![
+                vp_always_true.code.clone(),
+                vp_eval.code.clone(),
+                invalid_wasm,
+            ],
+            gas_meter_kind,
+            NonZeroUsize::new(2).unwrap(),
+        );
+        assert_eq!(progress.total(), 2);
+
+        // Fetch `vp_eval` to wait for it finish compilation
+        let fetched = cache.fetch(&vp_eval.hash, gas_meter_kind).unwrap();
+        assert_matches!(fetched, Some(_), "The module must be in cache");
+        assert!(
+            module_file_exists(&cache.dir, &vp_eval.hash, gas_meter_kind),
+            "The file must be written"
+        );
+        // Wait for the pre-warming to finish, for at most 10 seconds
+        let finished = (0..1_000).any(|_| {
+            if progress.is_finished() {
+                return true;
+            }
+            sleep(Duration::from_millis(10));
+            false
+        });
+        assert!(finished, "The pre-warming must finish");
+        assert_eq!(progress.compiled(), 1);
+        assert_eq!(progress.failed(), 1);
+        assert!(
+            !module_file_exists(&cache.dir, &invalid_hash, gas_meter_kind),
+            "The file must not be written"
+        );
+        let cache_progress = cache.progress.read().unwrap();
+        assert_matches!(
+            cache_progress.get(&CacheKey {
+                code_hash: invalid_hash,
+                gas_meter_kind
+            }),
+            None,
+            "Any progress is removed"
+        );
+    }
+
+    #[test]
+    fn test_verify_and_prune() {
+        let vp_always_true = load_wasm(TestWasms::VpAlwaysTrue.path());
+        let vp_eval = load_wasm(TestWasms::VpEval.path());
+        let (mut cache, tmp_dir) = testing::cache::<TestCache>();
+        let gas_meter_kind = GasMeterKind::MutGlobal;
+        for wasm in [&vp_always_true, &vp_eval] {
+            cache.compile_or_fetch(&wasm.code, gas_meter_kind).unwrap();
+        }
+
+        // Add a module from another version and corrupt `vp_eval`'s module
+        let stale_dir = tmp_dir.path().join("v0.0.0");
+        fs::create_dir_all(&stale_dir).unwrap();
+        let vp_eval_dir = cache
+            .dir
+            .join(gas_meter_kind_dir(gas_meter_kind))
+            .join(vp_eval.hash.to_string().to_lowercase());
+        for entry in fs::read_dir(&vp_eval_dir).unwrap() {
+            fs::write(entry.unwrap().path(), [1_u8]).unwrap();
+        }
+
+        let report = verify(tmp_dir.path()).unwrap();
+        assert_eq!(report.stale, vec![stale_dir.clone()]);
+        assert_eq!(report.invalid, vec![vp_eval_dir.clone()]);
+        assert_matches!(
+            &report.valid[..],
+            [(GasMeterKind::MutGlobal, hash, _)] if *hash == vp_always_true.hash
+        );
+
+        // Prune with no code retained
+        let removed = prune(tmp_dir.path(), |_| false).unwrap();
+        assert_eq!(removed.len(), 3);
+        assert!(!stale_dir.exists());
+        assert!(!vp_eval_dir.exists());
+        assert!(!module_file_exists(
+            &cache.dir,
+            &vp_always_true.hash,
+            gas_meter_kind
+        ));
+        let report = verify(tmp_dir.path()).unwrap();
+        assert!(report.valid.is_empty());
+        assert!(report.invalid.is_empty());
+        assert!(report.stale.is_empty());
+    }
+
     /// Get the WASM code bytes, its hash and find the compiled module's size
     fn load_wasm(file: impl AsRef<Path>) -> WasmWithMeta {
         let file = file.as_ref();
@@ -7,6 +7,7 @@ use std::cell::RefCell;
 use std::rc;
 
 use namada_core::hints;
+use namada_gas::profile::GasProfiler;
 use namada_gas::{Gas, GasMetering};
 use namada_state::{DB, DBIter, StorageHasher};
 use wasmer::{Function, FunctionEnv, Imports};
@@ -23,6 +24,7 @@ pub struct WasmGasMeter {
     tx_gas_limit: Gas,
     wasm_transaction_gas_global: Option<wasmer::Global>,
     store: Option<rc::Weak<RefCell<wasmer::Store>>>,
+    profiler: Option<GasProfiler>,
 }
 
 impl WasmGasMeter {
@@ -38,6 +40,7 @@ impl WasmGasMeter {
             tx_gas_limit: Gas::new(0u64),
             wasm_transaction_gas_global: None,
             store: None,
+            profiler: None,
         }
     }
 
@@ -53,6 +56,7 @@ impl WasmGasMeter {
         self.initial_gas = meter.get_available_gas();
         self.wasm_transaction_gas_global = Some(gas_global);
         self.store = Some(store);
+        self.profiler = meter.gas_profiler().cloned();
 
         self.write_wasm_gas(self.initial_gas.clone(), None);
     }
@@ -203,6 +207,10 @@ impl GasMetering for WasmGasMeter {
     fn get_gas_scale(&self) -> u64 {
         self.gas_scale
     }
+
+    fn gas_profiler(&self) -> Option<&GasProfiler> {
+        self.profiler.as_ref()
+    }
 }
 
 /// Prepare imports (memory and host functions) exposed to the vm guest running
@@ -223,34 +231,42 @@ where
         // Default namespace
         "env" => {
             // Gas injection hook
-            "gas" => Function::new_typed_with_env(wasm_store, &env, wrap_tx::_1(host_env::tx_charge_gas)),
+            "gas" => Function::new_typed_with_env(wasm_store, &env, wrap_tx::_1("gas", host_env::tx_charge_gas)),
             // Tx Host functions
-            "namada_tx_delete" => Function::new_typed_with_env(wasm_store, &env, wrap_tx::_2(host_env::tx_delete)),
-            "namada_tx_emit_event" => Function::new_typed_with_env(wasm_store, &env, wrap_tx::_2(host_env::tx_emit_event)),
-            "namada_tx_get_block_epoch" => Function::new_typed_with_env(wasm_store, &env, wrap_tx::_0(host_env::tx_get_block_epoch)),
-            "namada_tx_get_block_header" => Function::new_typed_with_env(wasm_store, &env, wrap_tx::_1(host_env::tx_get_block_header)),
-            "namada_tx_get_block_height" => Function::new_typed_with_env(wasm_store, &env, wrap_tx::_0(host_env::tx_get_block_height)),
-            "namada_tx_get_chain_id" => Function::new_typed_with_env(wasm_store, &env, wrap_tx::_1(host_env::tx_get_chain_id)),
-            "namada_tx_get_events" => Function::new_typed_with_env(wasm_store, &env, wrap_tx::_2(host_env::tx_get_events)),
-            "namada_tx_get_native_token" => Function::new_typed_with_env(wasm_store, &env, wrap_tx::_1(host_env::tx_get_native_token)),
-            "namada_tx_get_pred_epochs" => Function::new_typed_with_env(wasm_store, &env, wrap_tx::_0(host_env::tx_get_pred_epochs)),
-            "namada_tx_get_tx_index" => Function::new_typed_with_env(wasm_store, &env, wrap_tx::_0(host_env::tx_get_tx_index)),
-            "namada_tx_has_key" => Function::new_typed_with_env(wasm_store, &env, wrap_tx::_2(host_env::tx_has_key)),
-            "namada_tx_init_account" => Function::new_typed_with_env(wasm_store, &env, wrap_tx::_7(host_env::tx_init_account)),
-            "namada_tx_insert_verifier" => Function::new_typed_with_env(wasm_store, &env, wrap_tx::_2(host_env::tx_insert_verifier)),
-            "namada_tx_iter_next" => Function::new_typed_with_env(wasm_store, &env, wrap_tx::_1(host_env::tx_iter_next)),
-            "namada_tx_iter_prefix" => Function::new_typed_with_env(wasm_store, &env, wrap_tx::_2(host_env::tx_iter_prefix)),
-            "namada_tx_log_string" => Function::new_typed_with_env(wasm_store, &env, wrap_tx::_2(host_env::tx_log_string)),
-            "namada_tx_read" => Function::new_typed_with_env(wasm_store, &env, wrap_tx::_2(host_env::tx_read)),
-            "namada_tx_read_temp" => Function::new_typed_with_env(wasm_store, &env, wrap_tx::_2(host_env::tx_read_temp)),
-            "namada_tx_result_buffer" => Function::new_typed_with_env(wasm_store, &env, wrap_tx::_1(host_env::tx_result_buffer)),
-            "namada_tx_set_commitment_sentinel" => Function::new_typed_with_env(wasm_store, &env, wrap_tx::_0(host_env::tx_set_commitment_sentinel)),
-            "namada_tx_update_masp_note_commitment_tree" => Function::new_typed_with_env(wasm_store, &env, wrap_tx::_2(host_env::tx_update_masp_note_commitment_tree)),
-            "namada_tx_update_validity_predicate" => Function::new_typed_with_env(wasm_store, &env, wrap_tx::_6(host_env::tx_update_validity_predicate)),
-            "namada_tx_verify_tx_section_signature" => Function::new_typed_with_env(wasm_store, &env, wrap_tx::_5(host_env::tx_verify_tx_section_signature)),
-            "namada_tx_write" => Function::new_typed_with_env(wasm_store, &env, wrap_tx::_4(host_env::tx_write)),
-            "namada_tx_write_temp" => Function::new_typed_with_env(wasm_store, &env, wrap_tx::_4(host_env::tx_write_temp)),
-            "namada_tx_yield_value" => Function::new_typed_with_env(wasm_store, &env, wrap_tx::_2(host_env::tx_yield_value)),
+            "namada_tx_delete" => Function::new_typed_with_env(wasm_store, &env, wrap_tx::_2("namada_tx_delete", host_env::tx_delete)),
+            "namada_tx_emit_event" => Function::new_typed_with_env(wasm_store, &env, wrap_tx::_2("namada_tx_emit_event", host_env::tx_emit_event)),
+            "namada_tx_get_block_epoch" => Function::new_typed_with_env(wasm_store, &env, wrap_tx::_0("namada_tx_get_block_epoch", host_env::tx_get_block_epoch)),
+            "namada_tx_get_block_header" => Function::new_typed_with_env(wasm_store, &env, wrap_tx::_1("namada_tx_get_block_header", host_env::tx_get_block_header)),
+            "namada_tx_get_block_height" => Function::new_typed_with_env(wasm_store, &env, wrap_tx::_0("namada_tx_get_block_height", host_env::tx_get_block_height)),
+            "namada_tx_get_chain_id" => Function::new_typed_with_env(wasm_store, &env, wrap_tx::_1("namada_tx_get_chain_id", host_env::tx_get_chain_id)),
+            "namada_tx_get_events" => Function::new_typed_with_env(wasm_store, &env, wrap_tx::_2("namada_tx_get_events", host_env::tx_get_events)),
+            "namada_tx_get_native_token" => Function::new_typed_with_env(wasm_store, &env, wrap_tx::_1("namada_tx_get_native_token", host_env::tx_get_native_token)),
+            "namada_tx_get_pred_epochs" => Function::new_typed_with_env(wasm_store, &env, wrap_tx::_0("namada_tx_get_pred_epochs", host_env::tx_get_pred_epochs)),
+            "namada_tx_get_tx_index" => Function::new_typed_with_env(wasm_store, &env, wrap_tx::_0("namada_tx_get_tx_index", host_env::tx_get_tx_index)),
+            "namada_tx_hash_blake2b" => Function::new_typed_with_env(wasm_store, &env, wrap_tx::_3("namada_tx_hash_blake2b", host_env::tx_hash_blake2b)),
+            "namada_tx_hash_keccak256" => Function::new_typed_with_env(wasm_store, &env, wrap_tx::_3("namada_tx_hash_keccak256", host_env::tx_hash_keccak256)),
+            "namada_tx_hash_sha256" => Function::new_typed_with_env(wasm_store, &env, wrap_tx::_3("namada_tx_hash_sha256", host_env::tx_hash_sha256)),
+            "namada_tx_has_key" => Function::new_typed_with_env(wasm_store, &env, wrap_tx::_2("namada_tx_has_key", host_env::tx_has_key)),
+            "namada_tx_init_account" => Function::new_typed_with_env(wasm_store, &env, wrap_tx::_7("namada_tx_init_account", host_env::tx_init_account)),
+            "namada_tx_insert_verifier" => Function::new_typed_with_env(wasm_store, &env, wrap_tx::_2("namada_tx_insert_verifier", host_env::tx_insert_verifier)),
+            "namada_tx_iter_next" => Function::new_typed_with_env(wasm_store, &env, wrap_tx::_1("namada_tx_iter_next", host_env::tx_iter_next)),
+            "namada_tx_iter_prefix" => Function::new_typed_with_env(wasm_store, &env, wrap_tx::_2("namada_tx_iter_prefix", host_env::tx_iter_prefix)),
+            "namada_tx_iter_prefix_range" => Function::new_typed_with_env(wasm_store, &env, wrap_tx::_4("namada_tx_iter_prefix_range", host_env::tx_iter_prefix_range)),
+            "namada_tx_log_string" => Function::new_typed_with_env(wasm_store, &env, wrap_tx::_2("namada_tx_log_string", host_env::tx_log_string)),
+            "namada_tx_push_savepoint" => Function::new_typed_with_env(wasm_store, &env, wrap_tx::_0("namada_tx_push_savepoint", host_env::tx_push_savepoint)),
+            "namada_tx_read" => Function::new_typed_with_env(wasm_store, &env, wrap_tx::_2("namada_tx_read", host_env::tx_read)),
+            "namada_tx_read_temp" => Function::new_typed_with_env(wasm_store, &env, wrap_tx::_2("namada_tx_read_temp", host_env::tx_read_temp)),
+            "namada_tx_release_savepoint" => Function::new_typed_with_env(wasm_store, &env, wrap_tx::_0("namada_tx_release_savepoint", host_env::tx_release_savepoint)),
+            "namada_tx_result_buffer" => Function::new_typed_with_env(wasm_store, &env, wrap_tx::_1("namada_tx_result_buffer", host_env::tx_result_buffer)),
+            "namada_tx_rollback_to_savepoint" => Function::new_typed_with_env(wasm_store, &env, wrap_tx::_0("namada_tx_rollback_to_savepoint", host_env::tx_rollback_to_savepoint)),
+            "namada_tx_set_commitment_sentinel" => Function::new_typed_with_env(wasm_store, &env, wrap_tx::_0("namada_tx_set_commitment_sentinel", host_env::tx_set_commitment_sentinel)),
+            "namada_tx_update_masp_note_commitment_tree" => Function::new_typed_with_env(wasm_store, &env, wrap_tx::_2("namada_tx_update_masp_note_commitment_tree", host_env::tx_update_masp_note_commitment_tree)),
+            "namada_tx_update_validity_predicate" => Function::new_typed_with_env(wasm_store, &env, wrap_tx::_6("namada_tx_update_validity_predicate", host_env::tx_update_validity_predicate)),
+            "namada_tx_verify_signature" => Function::new_typed_with_env(wasm_store, &env, wrap_tx::_6("namada_tx_verify_signature", host_env::tx_verify_signature)),
+            "namada_tx_verify_tx_section_signature" => Function::new_typed_with_env(wasm_store, &env, wrap_tx::_5("namada_tx_verify_tx_section_signature", host_env::tx_verify_tx_section_signature)),
+            "namada_tx_write" => Function::new_typed_with_env(wasm_store, &env, wrap_tx::_4("namada_tx_write", host_env::tx_write)),
+            "namada_tx_write_temp" => Function::new_typed_with_env(wasm_store, &env, wrap_tx::_4("namada_tx_write_temp", host_env::tx_write_temp)),
+            "namada_tx_yield_value" => Function::new_typed_with_env(wasm_store, &env, wrap_tx::_2("namada_tx_yield_value", host_env::tx_yield_value)),
         },
     }
 }
@@ -273,29 +289,35 @@ where
         // Default namespace
         "env" => {
             // Gas injection hook
-            "gas" => Function::new_typed_with_env(wasm_store, &env, wrap_vp::_1(host_env::vp_charge_gas)),
+            "gas" => Function::new_typed_with_env(wasm_store, &env, wrap_vp::_1("gas", host_env::vp_charge_gas)),
             // VP Host functions
-            "namada_vp_eval" => Function::new_typed_with_env(wasm_store, &env, wrap_vp::_4(host_env::vp_eval)),
-            "namada_vp_get_block_header" => Function::new_typed_with_env(wasm_store, &env, wrap_vp::_1(host_env::vp_get_block_header)),
-            "namada_vp_get_block_height" => Function::new_typed_with_env(wasm_store, &env, wrap_vp::_0(host_env::vp_get_block_height)),
-            "namada_vp_get_chain_id" => Function::new_typed_with_env(wasm_store, &env, wrap_vp::_1(host_env::vp_get_chain_id)),
-            "namada_vp_get_events" => Function::new_typed_with_env(wasm_store, &env, wrap_vp::_2(host_env::vp_get_events)),
-            "namada_vp_get_native_token" => Function::new_typed_with_env(wasm_store, &env, wrap_vp::_1(host_env::vp_get_native_token)),
-            "namada_vp_get_pred_epochs" => Function::new_typed_with_env(wasm_store, &env, wrap_vp::_0(host_env::vp_get_pred_epochs)),
-            "namada_vp_get_tx_code_hash" => Function::new_typed_with_env(wasm_store, &env, wrap_vp::_1(host_env::vp_get_tx_code_hash)),
-            "namada_vp_get_tx_index" => Function::new_typed_with_env(wasm_store, &env, wrap_vp::_0(host_env::vp_get_tx_index)),
-            "namada_vp_has_key_post" => Function::new_typed_with_env(wasm_store, &env, wrap_vp::_2(host_env::vp_has_key_post)),
-            "namada_vp_has_key_pre" => Function::new_typed_with_env(wasm_store, &env, wrap_vp::_2(host_env::vp_has_key_pre)),
-            "namada_vp_iter_next" => Function::new_typed_with_env(wasm_store, &env, wrap_vp::_1(host_env::vp_iter_next)),
-            "namada_vp_iter_prefix_post" => Function::new_typed_with_env(wasm_store, &env, wrap_vp::_2(host_env::vp_iter_prefix_post)),
-            "namada_vp_iter_prefix_pre" => Function::new_typed_with_env(wasm_store, &env, wrap_vp::_2(host_env::vp_iter_prefix_pre)),
-            "namada_vp_log_string" => Function::new_typed_with_env(wasm_store, &env, wrap_vp::_2(host_env::vp_log_string)),
-            "namada_vp_read_post" => Function::new_typed_with_env(wasm_store, &env, wrap_vp::_2(host_env::vp_read_post)),
-            "namada_vp_read_pre" => Function::new_typed_with_env(wasm_store, &env, wrap_vp::_2(host_env::vp_read_pre)),
-            "namada_vp_read_temp" => Function::new_typed_with_env(wasm_store, &env, wrap_vp::_2(host_env::vp_read_temp)),
-            "namada_vp_result_buffer" => Function::new_typed_with_env(wasm_store, &env, wrap_vp::_1(host_env::vp_result_buffer)),
-            "namada_vp_verify_tx_section_signature" => Function::new_typed_with_env(wasm_store, &env, wrap_vp::_7(host_env::vp_verify_tx_section_signature)),
-            "namada_vp_yield_value" => Function::new_typed_with_env(wasm_store, &env, wrap_vp::_2(host_env::vp_yield_value)),
+            "namada_vp_eval" => Function::new_typed_with_env(wasm_store, &env, wrap_vp::_4("namada_vp_eval", host_env::vp_eval)),
+            "namada_vp_get_block_header" => Function::new_typed_with_env(wasm_store, &env, wrap_vp::_1("namada_vp_get_block_header", host_env::vp_get_block_header)),
+            "namada_vp_get_block_height" => Function::new_typed_with_env(wasm_store, &env, wrap_vp::_0("namada_vp_get_block_height", host_env::vp_get_block_height)),
+            "namada_vp_get_chain_id" => Function::new_typed_with_env(wasm_store, &env, wrap_vp::_1("namada_vp_get_chain_id", host_env::vp_get_chain_id)),
+            "namada_vp_get_events" => Function::new_typed_with_env(wasm_store, &env, wrap_vp::_2("namada_vp_get_events", host_env::vp_get_events)),
+            "namada_vp_get_native_token" => Function::new_typed_with_env(wasm_store, &env, wrap_vp::_1("namada_vp_get_native_token", host_env::vp_get_native_token)),
+            "namada_vp_get_pred_epochs" => Function::new_typed_with_env(wasm_store, &env, wrap_vp::_0("namada_vp_get_pred_epochs", host_env::vp_get_pred_epochs)),
+            "namada_vp_get_tx_code_hash" => Function::new_typed_with_env(wasm_store, &env, wrap_vp::_1("namada_vp_get_tx_code_hash", host_env::vp_get_tx_code_hash)),
+            "namada_vp_get_tx_index" => Function::new_typed_with_env(wasm_store, &env, wrap_vp::_0("namada_vp_get_tx_index", host_env::vp_get_tx_index)),
+            "namada_vp_hash_blake2b" => Function::new_typed_with_env(wasm_store, &env, wrap_vp::_3("namada_vp_hash_blake2b", host_env::vp_hash_blake2b)),
+            "namada_vp_hash_keccak256" => Function::new_typed_with_env(wasm_store, &env, wrap_vp::_3("namada_vp_hash_keccak256", host_env::vp_hash_keccak256)),
+            "namada_vp_hash_sha256" => Function::new_typed_with_env(wasm_store, &env, wrap_vp::_3("namada_vp_hash_sha256", host_env::vp_hash_sha256)),
+            "namada_vp_has_key_post" => Function::new_typed_with_env(wasm_store, &env, wrap_vp::_2("namada_vp_has_key_post", host_env::vp_has_key_post)),
+            "namada_vp_has_key_pre" => Function::new_typed_with_env(wasm_store, &env, wrap_vp::_2("namada_vp_has_key_pre", host_env::vp_has_key_pre)),
+            "namada_vp_iter_next" => Function::new_typed_with_env(wasm_store, &env, wrap_vp::_1("namada_vp_iter_next", host_env::vp_iter_next)),
+            "namada_vp_iter_prefix_post" => Function::new_typed_with_env(wasm_store, &env, wrap_vp::_2("namada_vp_iter_prefix_post", host_env::vp_iter_prefix_post)),
+            "namada_vp_iter_prefix_pre" => Function::new_typed_with_env(wasm_store, &env, wrap_vp::_2("namada_vp_iter_prefix_pre", host_env::vp_iter_prefix_pre)),
+            "namada_vp_iter_prefix_range_post" => Function::new_typed_with_env(wasm_store, &env, wrap_vp::_4("namada_vp_iter_prefix_range_post", host_env::vp_iter_prefix_range_post)),
+            "namada_vp_iter_prefix_range_pre" => Function::new_typed_with_env(wasm_store, &env, wrap_vp::_4("namada_vp_iter_prefix_range_pre", host_env::vp_iter_prefix_range_pre)),
+            "namada_vp_log_string" => Function::new_typed_with_env(wasm_store, &env, wrap_vp::_2("namada_vp_log_string", host_env::vp_log_string)),
+            "namada_vp_read_post" => Function::new_typed_with_env(wasm_store, &env, wrap_vp::_2("namada_vp_read_post", host_env::vp_read_post)),
+            "namada_vp_read_pre" => Function::new_typed_with_env(wasm_store, &env, wrap_vp::_2("namada_vp_read_pre", host_env::vp_read_pre)),
+            "namada_vp_read_temp" => Function::new_typed_with_env(wasm_store, &env, wrap_vp::_2("namada_vp_read_temp", host_env::vp_read_temp)),
+            "namada_vp_result_buffer" => Function::new_typed_with_env(wasm_store, &env, wrap_vp::_1("namada_vp_result_buffer", host_env::vp_result_buffer)),
+            "namada_vp_verify_signature" => Function::new_typed_with_env(wasm_store, &env, wrap_vp::_6("namada_vp_verify_signature", host_env::vp_verify_signature)),
+            "namada_vp_verify_tx_section_signature" => Function::new_typed_with_env(wasm_store, &env, wrap_vp::_7("namada_vp_verify_tx_section_signature", host_env::vp_verify_tx_section_signature)),
+            "namada_vp_yield_value" => Function::new_typed_with_env(wasm_store, &env, wrap_vp::_2("namada_vp_yield_value", host_env::vp_yield_value)),
         },
     }
 }
@@ -317,6 +339,7 @@ mod wrap_tx {
     use crate::wasm::memory::WasmMemory;
 
     pub(super) fn _0<F, RET, D, H, CA>(
+        name: &'static str,
         f: F,
     ) -> impl Fn(FunctionEnvMut<'_, TxVmEnv<WasmMemory, D, H, CA>>) -> RET
     where
@@ -325,10 +348,11 @@ mod wrap_tx {
         CA: WasmCacheAccess + 'static,
         F: Fn(&mut TxVmEnv<WasmMemory, D, H, CA>) -> RET,
     {
-        move |mut env| f(env.data_mut())
+        move |mut env| env.data_mut().profile_host_fn(name, &f)
     }
 
     pub(super) fn _1<F, ARG0, RET, D, H, CA>(
+        name: &'static str,
         f: F,
     ) -> impl Fn(FunctionEnvMut<'_, TxVmEnv<WasmMemory, D, H, CA>>, ARG0) -> RET
     where
@@ -337,10 +361,13 @@ mod wrap_tx {
         CA: WasmCacheAccess + 'static,
         F: Fn(&mut TxVmEnv<WasmMemory, D, H, CA>, ARG0) -> RET,
     {
-        move |mut env, arg0| f(env.data_mut(), arg0)
+        move |mut env, arg0| {
+            env.data_mut().profile_host_fn(name, |env| f(env, arg0))
+        }
     }
 
     pub(super) fn _2<F, ARG0, ARG1, RET, D, H, CA>(
+        name: &'static str,
         f: F,
     ) -> impl Fn(FunctionEnvMut<'_, TxVmEnv<WasmMemory, D, H, CA>>, ARG0, ARG1) -> RET
     where
@@ -349,10 +376,35 @@ mod wrap_tx {
         CA: WasmCacheAccess + 'static,
         F: Fn(&mut TxVmEnv<WasmMemory, D, H, CA>, ARG0, ARG1) -> RET,
     {
-        move |mut env, arg0, arg1| f(env.data_mut(), arg0, arg1)
+        move |mut env, arg0, arg1| {
+            env.data_mut()
+                .profile_host_fn(name, |env| f(env, arg0, arg1))
+        }
+    }
+
+    pub(super) fn _3<F, ARG0, ARG1, ARG2, RET, D, H, CA>(
+        name: &'static str,
+        f: F,
+    ) -> impl Fn(
+        FunctionEnvMut<'_, TxVmEnv<WasmMemory, D, H, CA>>,
+        ARG0,
+        ARG1,
+        ARG2,
+    ) -> RET
+    where
+        D: DB + for<'iter> DBIter<'iter> + 'static,
+        H: StorageHasher + 'static,
+        CA: WasmCacheAccess + 'static,
+        F: Fn(&mut TxVmEnv<WasmMemory, D, H, CA>, ARG0, ARG1, ARG2) -> RET,
+    {
+        move |mut env, arg0, arg1, arg2| {
+            env.data_mut()
+                .profile_host_fn(name, |env| f(env, arg0, arg1, arg2))
+        }
     }
 
     pub(super) fn _4<F, ARG0, ARG1, ARG2, ARG3, RET, D, H, CA>(
+        name: &'static str,
         f: F,
     ) -> impl Fn(
         FunctionEnvMut<'_, TxVmEnv<WasmMemory, D, H, CA>>,
@@ -374,11 +426,13 @@ mod wrap_tx {
         ) -> RET,
     {
         move |mut env, arg0, arg1, arg2, arg3| {
-            f(env.data_mut(), arg0, arg1, arg2, arg3)
+            env.data_mut()
+                .profile_host_fn(name, |env| f(env, arg0, arg1, arg2, arg3))
         }
     }
 
     pub(super) fn _5<F, ARG0, ARG1, ARG2, ARG3, ARG4, RET, D, H, CA>(
+        name: &'static str,
         f: F,
     ) -> impl Fn(
         FunctionEnvMut<'_, TxVmEnv<WasmMemory, D, H, CA>>,
@@ -402,11 +456,14 @@ mod wrap_tx {
         ) -> RET,
     {
         move |mut env, arg0, arg1, arg2, arg3, arg4| {
-            f(env.data_mut(), arg0, arg1, arg2, arg3, arg4)
+            env.data_mut().profile_host_fn(name, |env| {
+                f(env, arg0, arg1, arg2, arg3, arg4)
+            })
         }
     }
 
     pub(super) fn _6<F, ARG0, ARG1, ARG2, ARG3, ARG4, ARG5, RET, D, H, CA>(
+        name: &'static str,
         f: F,
     ) -> impl Fn(
         FunctionEnvMut<'_, TxVmEnv<WasmMemory, D, H, CA>>,
@@ -432,7 +489,9 @@ mod wrap_tx {
         ) -> RET,
     {
         move |mut env, arg0, arg1, arg2, arg3, arg4, arg5| {
-            f(env.data_mut(), arg0, arg1, arg2, arg3, arg4, arg5)
+            env.data_mut().profile_host_fn(name, |env| {
+                f(env, arg0, arg1, arg2, arg3, arg4, arg5)
+            })
         }
     }
 
@@ -450,6 +509,7 @@ mod wrap_tx {
         H,
         CA,
     >(
+        name: &'static str,
         f: F,
     ) -> impl Fn(
         FunctionEnvMut<'_, TxVmEnv<WasmMemory, D, H, CA>>,
@@ -477,7 +537,9 @@ mod wrap_tx {
         ) -> RET,
     {
         move |mut env, arg0, arg1, arg2, arg3, arg4, arg5, arg6| {
-            f(env.data_mut(), arg0, arg1, arg2, arg3, arg4, arg5, arg6)
+            env.data_mut().profile_host_fn(name, |env| {
+                f(env, arg0, arg1, arg2, arg3, arg4, arg5, arg6)
+            })
         }
     }
 }
@@ -499,6 +561,7 @@ mod wrap_vp {
     use crate::wasm::memory::WasmMemory;
 
     pub(super) fn _0<F, RET, D, H, EVAL, CA>(
+        name: &'static str,
         f: F,
     ) -> impl Fn(FunctionEnvMut<'_, VpVmEnv<WasmMemory, D, H, EVAL, CA>>) -> RET
     where
@@ -508,10 +571,11 @@ mod wrap_vp {
         EVAL: VpEvaluator<Db = D, H = H, Eval = EVAL, CA = CA> + 'static,
         F: Fn(&mut VpVmEnv<WasmMemory, D, H, EVAL, CA>) -> RET,
     {
-        move |mut env| f(env.data_mut())
+        move |mut env| env.data_mut().profile_host_fn(name, &f)
     }
 
     pub(super) fn _1<F, ARG0, RET, D, H, EVAL, CA>(
+        name: &'static str,
         f: F,
     ) -> impl Fn(FunctionEnvMut<'_, VpVmEnv<WasmMemory, D, H, EVAL, CA>>, ARG0) -> RET
     where
@@ -521,10 +585,13 @@ mod wrap_vp {
         EVAL: VpEvaluator<Db = D, H = H, Eval = EVAL, CA = CA> + 'static,
         F: Fn(&mut VpVmEnv<WasmMemory, D, H, EVAL, CA>, ARG0) -> RET,
     {
-        move |mut env, arg0| f(env.data_mut(), arg0)
+        move |mut env, arg0| {
+            env.data_mut().profile_host_fn(name, |env| f(env, arg0))
+        }
     }
 
     pub(super) fn _2<F, ARG0, ARG1, RET, D, H, EVAL, CA>(
+        name: &'static str,
         f: F,
     ) -> impl Fn(
         FunctionEnvMut<'_, VpVmEnv<WasmMemory, D, H, EVAL, CA>>,
@@ -538,10 +605,41 @@ mod wrap_vp {
         EVAL: VpEvaluator<Db = D, H = H, Eval = EVAL, CA = CA> + 'static,
         F: Fn(&mut VpVmEnv<WasmMemory, D, H, EVAL, CA>, ARG0, ARG1) -> RET,
     {
-        move |mut env, arg0, arg1| f(env.data_mut(), arg0, arg1)
+        move |mut env, arg0, arg1| {
+            env.data_mut()
+                .profile_host_fn(name, |env| f(env, arg0, arg1))
+        }
+    }
+
+    pub(super) fn _3<F, ARG0, ARG1, ARG2, RET, D, H, EVAL, CA>(
+        name: &'static str,
+        f: F,
+    ) -> impl Fn(
+        FunctionEnvMut<'_, VpVmEnv<WasmMemory, D, H, EVAL, CA>>,
+        ARG0,
+        ARG1,
+        ARG2,
+    ) -> RET
+    where
+        D: DB + for<'iter> DBIter<'iter> + 'static,
+        H: StorageHasher + 'static,
+        CA: WasmCacheAccess + 'static,
+        EVAL: VpEvaluator<Db = D, H = H, Eval = EVAL, CA = CA> + 'static,
+        F: Fn(
+            &mut VpVmEnv<WasmMemory, D, H, EVAL, CA>,
+            ARG0,
+            ARG1,
+            ARG2,
+        ) -> RET,
+    {
+        move |mut env, arg0, arg1, arg2| {
+            env.data_mut()
+                .profile_host_fn(name, |env| f(env, arg0, arg1, arg2))
+        }
     }
 
     pub(super) fn _4<F, ARG0, ARG1, ARG2, ARG3, RET, D, H, EVAL, CA>(
+        name: &'static str,
         f: F,
     ) -> impl Fn(
         FunctionEnvMut<'_, VpVmEnv<WasmMemory, D, H, EVAL, CA>>,
@@ -564,7 +662,55 @@ mod wrap_vp {
         ) -> RET,
     {
         move |mut env, arg0, arg1, arg2, arg3| {
-            f(env.data_mut(), arg0, arg1, arg2, arg3)
+            env.data_mut()
+                .profile_host_fn(name, |env| f(env, arg0, arg1, arg2, arg3))
+        }
+    }
+
+    pub(super) fn _6<
+        F,
+        ARG0,
+        ARG1,
+        ARG2,
+        ARG3,
+        ARG4,
+        ARG5,
+        RET,
+        D,
+        H,
+        EVAL,
+        CA,
+    >(
+        name: &'static str,
+        f: F,
+    ) -> impl Fn(
+        FunctionEnvMut<'_, VpVmEnv<WasmMemory, D, H, EVAL, CA>>,
+        ARG0,
+        ARG1,
+        ARG2,
+        ARG3,
+        ARG4,
+        ARG5,
+    ) -> RET
+    where
+        D: DB + for<'iter> DBIter<'iter> + 'static,
+        H: StorageHasher + 'static,
+        CA: WasmCacheAccess + 'static,
+        EVAL: VpEvaluator<Db = D, H = H, Eval = EVAL, CA = CA> + 'static,
+        F: Fn(
+            &mut VpVmEnv<WasmMemory, D, H, EVAL, CA>,
+            ARG0,
+            ARG1,
+            ARG2,
+            ARG3,
+            ARG4,
+            ARG5,
+        ) -> RET,
+    {
+        move |mut env, arg0, arg1, arg2, arg3, arg4, arg5| {
+            env.data_mut().profile_host_fn(name, |env| {
+                f(env, arg0, arg1, arg2, arg3, arg4, arg5)
+            })
         }
     }
 
@@ -583,6 +729,7 @@ mod wrap_vp {
         EVAL,
         CA,
     >(
+        name: &'static str,
         f: F,
     ) -> impl Fn(
         FunctionEnvMut<'_, VpVmEnv<WasmMemory, D, H, EVAL, CA>>,
@@ -611,7 +758,9 @@ mod wrap_vp {
         ) -> RET,
     {
         move |mut env, arg0, arg1, arg2, arg3, arg4, arg5, arg6| {
-            f(env.data_mut(), arg0, arg1, arg2, arg3, arg4, arg5, arg6)
+            env.data_mut().profile_host_fn(name, |env| {
+                f(env, arg0, arg1, arg2, arg3, arg4, arg5, arg6)
+            })
         }
     }
 
@@ -632,6 +781,7 @@ mod wrap_vp {
         EVAL,
         CA,
     >(
+        name: &'static str,
         f: F,
     ) -> impl Fn(
         FunctionEnvMut<'_, VpVmEnv<WasmMemory, D, H, EVAL, CA>>,
@@ -664,18 +814,9 @@ mod wrap_vp {
         ) -> RET,
     {
         move |mut env, arg0, arg1, arg2, arg3, arg4, arg5, arg6, arg7, arg8| {
-            f(
-                env.data_mut(),
-                arg0,
-                arg1,
-                arg2,
-                arg3,
-                arg4,
-                arg5,
-                arg6,
-                arg7,
-                arg8,
-            )
+            env.data_mut().profile_host_fn(name, |env| {
+                f(env, arg0, arg1, arg2, arg3, arg4, arg5, arg6, arg7, arg8)
+            })
         }
     }
 }
@@ -19,7 +19,7 @@ use namada_gas::{GasMetering, TxGasMeter, VpGasMeter, WASM_MEMORY_PAGE_GAS};
 use namada_state::prefix_iter::PrefixIterators;
 use namada_state::{DB, DBIter, State, StateRead, StorageHasher, StorageRead};
 use namada_tx::data::{TxSentinel, TxType};
-use namada_tx::{BatchedTxRef, Commitment, Section, Tx, TxCommitments};
+use namada_tx::{BatchedTxRef, Code, Commitment, Section, Tx, TxCommitments};
 use namada_vp::vp_host_fns;
 use parity_wasm::elements::Instruction::*;
 use parity_wasm::elements::{self, SignExtInstruction};
@@ -42,9 +42,9 @@ use crate::{
 /// Allocator function name injected into a expored from wasm
 pub const ALLOC_FN_NAME: &str = "_injected_alloc";
 
-const GUEST_MEMORY: &str = "memory";
-const TX_ENTRYPOINT: &str = "_apply_tx";
-const VP_ENTRYPOINT: &str = "_validate_tx";
+pub(crate) const GUEST_MEMORY: &str = "memory";
+pub(crate) const TX_ENTRYPOINT: &str = "_apply_tx";
+pub(crate) const VP_ENTRYPOINT: &str = "_validate_tx";
 const MUT_GLOBAL_GAS_NAME: &str = "_namada_gas";
 const WASM_STACK_LIMIT: u32 = u16::MAX as u32;
 
@@ -113,6 +113,30 @@ pub enum Error {
     DisallowedTx,
     #[error("Invalid transaction section signature: {0}")]
     InvalidSectionSignature(String),
+    #[cfg(feature = "wasmtime-runtime")]
+    #[error("Wasmtime compilation error: {0}")]
+    WasmtimeCompileError(wasmtime::Error),
+    #[cfg(feature = "wasmtime-runtime")]
+    #[error("Failed instantiating wasm module with wasmtime: {0}")]
+    WasmtimeInstantiationError(wasmtime::Error),
+    #[cfg(feature = "wasmtime-runtime")]
+    #[error("Missing wasm export: {0}")]
+    WasmtimeMissingExport(&'static str),
+    #[cfg(feature = "wasmtime-runtime")]
+    #[error(
+        "Unexpected module entrypoint interface {entrypoint}, failed with: \
+         {error}"
+    )]
+    WasmtimeModuleEntrypointInterface {
+        entrypoint: &'static str,
+        error: wasmtime::Error,
+    },
+    #[cfg(feature = "wasmtime-runtime")]
+    #[error("Failed running wasm with wasmtime: {0}")]
+    WasmtimeRuntimeError(wasmtime::Error),
+    #[cfg(feature = "wasmtime-runtime")]
+    #[error("Wasmtime memory error: {0}")]
+    WasmtimeMemoryError(crate::wasmtime_runtime::memory::Error),
 }
 
 /// Result for functions that may fail
@@ -146,6 +170,10 @@ where
 
 /// Execute a transaction code. Returns the set verifiers addresses requested by
 /// the transaction.
+///
+/// The code is executed with wasmer, unless the `wasmtime-runtime` feature is
+/// enabled. With the `wasm-differential` feature, it's executed on both
+/// engines and their outcomes are asserted to be identical.
 #[allow(clippy::too_many_arguments)]
 pub fn tx<S, CA>(
     state: &mut S,
@@ -162,6 +190,67 @@ pub fn tx<S, CA>(
 where
     S: StateRead + State + StorageRead,
     CA: 'static + WasmCacheAccess,
+{
+    #[cfg(not(feature = "wasmtime-runtime"))]
+    {
+        wasmer_tx(
+            state,
+            gas_meter,
+            wrapper_hash,
+            tx_index,
+            tx,
+            cmt,
+            vp_wasm_cache,
+            tx_wasm_cache,
+            gas_meter_kind,
+            dry_run,
+        )
+    }
+    #[cfg(all(
+        feature = "wasmtime-runtime",
+        not(feature = "wasm-differential")
+    ))]
+    {
+        // NB: wasmtime always meters gas with the injected host fn
+        _ = gas_meter_kind;
+        crate::wasmtime_runtime::run::tx(
+            state,
+            gas_meter,
+            wrapper_hash,
+            tx_index,
+            tx,
+            cmt,
+            vp_wasm_cache,
+            tx_wasm_cache,
+            dry_run,
+        )
+    }
+    #[cfg(feature = "wasm-differential")]
+    {
+        crate::wasmtime_runtime::differential::tx(
+            state,
+            gas_meter,
+            wrapper_hash,
+            tx_index,
+            tx,
+            cmt,
+            vp_wasm_cache,
+            tx_wasm_cache,
+            gas_meter_kind,
+            dry_run,
+        )
+    }
+}
+
+/// Get the code section of a transaction, checking that it's allowed and that
+/// it matches its tag, if any.
+pub(crate) fn tx_code_section<S>(
+    state: &S,
+    tx: &Tx,
+    cmt: &TxCommitments,
+) -> Result<Code>
+where
+    S: StorageRead,
 {
     let tx_code = tx
         .get_section(cmt.code_sechash())
@@ -204,6 +293,31 @@ where
         }
     }
 
+    Ok(tx_code)
+}
+
+/// Execute a transaction code with wasmer. Returns the set verifiers addresses
+/// requested by the transaction.
+#[allow(clippy::too_many_arguments)]
+pub fn wasmer_tx<S, CA>(
+    state: &mut S,
+    gas_meter: &RefCell<TxGasMeter>,
+    wrapper_hash: Option<&Hash>,
+    tx_index: &TxIndex,
+    tx: &Tx,
+    cmt: &TxCommitments,
+    vp_wasm_cache: &mut VpCache<CA>,
+    tx_wasm_cache: &mut TxCache<CA>,
+    gas_meter_kind: GasMeterKind,
+    dry_run: bool,
+) -> Result<BTreeSet<Address>>
+where
+    S: StateRead + State + StorageRead,
+    CA: 'static + WasmCacheAccess,
+{
+    let tx_code = tx_code_section(state, tx, cmt)?;
+    let batched_tx = tx.batch_ref_tx(cmt);
+
     let (module, store) = fetch_or_compile(
         tx_wasm_cache,
         &tx_code.code,
@@ -341,7 +455,11 @@ where
     let ok = result.map_err(|err| {
         tracing::debug!("Tx WASM failed with {}", err);
 
-        extract_tx_error(&mut yielded_value, &sentinel, Some(err))
+        extract_tx_error(
+            &mut yielded_value,
+            &sentinel,
+            Some(Error::RuntimeError(err)),
+        )
     })?;
 
     // NB: early drop this data to avoid memory errors
@@ -357,15 +475,17 @@ where
     }
 }
 
-fn extract_tx_error(
+/// Extract the error of a failed tx from the value it yielded, the sentinel
+/// set by the host and the runtime error of the engine that executed it.
+pub(crate) fn extract_tx_error(
     yielded_value: &mut Option<Vec<u8>>,
     sentinel: &RefCell<TxSentinel>,
-    runtime_err: Option<wasmer::RuntimeError>,
+    runtime_err: Option<Error>,
 ) -> Error {
     #[inline]
     fn take(
         yielded_value: &mut Option<Vec<u8>>,
-        runtime_err: Option<wasmer::RuntimeError>,
+        runtime_err: Option<Error>,
     ) -> Error {
         const UNKNOWN: &str = "Runtime panic caused execution to end abruptly";
 
@@ -380,7 +500,7 @@ fn extract_tx_error(
         if let Some(result) = yielded_err {
             result.map_or_else(|err| err, Error::TxError)
         } else if let Some(err) = runtime_err {
-            Error::RuntimeError(err)
+            err
         } else {
             Error::TxError(UNKNOWN.to_owned())
         }
@@ -400,8 +520,87 @@ fn extract_tx_error(
 /// Execute a validity predicate code. Returns whether the validity
 /// predicate accepted storage modifications performed by the transaction
 /// that triggered the execution.
+///
+/// The code is executed with wasmer, unless the `wasmtime-runtime` feature is
+/// enabled. With the `wasm-differential` feature, it's executed on both
+/// engines and their outcomes are asserted to be identical.
 #[allow(clippy::too_many_arguments)]
 pub fn vp<S, CA>(
+    vp_code_hash: Hash,
+    batched_tx: &BatchedTxRef<'_>,
+    tx_index: &TxIndex,
+    address: &Address,
+    state: &S,
+    gas_meter: &RefCell<VpGasMeter>,
+    keys_changed: &BTreeSet<Key>,
+    verifiers: &BTreeSet<Address>,
+    vp_wasm_cache: VpCache<CA>,
+    gas_meter_kind: GasMeterKind,
+    dry_run: bool,
+) -> Result<()>
+where
+    S: StateRead,
+    CA: 'static + WasmCacheAccess,
+{
+    #[cfg(not(feature = "wasmtime-runtime"))]
+    {
+        wasmer_vp(
+            vp_code_hash,
+            batched_tx,
+            tx_index,
+            address,
+            state,
+            gas_meter,
+            keys_changed,
+            verifiers,
+            vp_wasm_cache,
+            gas_meter_kind,
+            dry_run,
+        )
+    }
+    #[cfg(all(
+        feature = "wasmtime-runtime",
+        not(feature = "wasm-differential")
+    ))]
+    {
+        // NB: wasmtime always meters gas with the injected host fn
+        _ = gas_meter_kind;
+        crate::wasmtime_runtime::run::vp(
+            vp_code_hash,
+            batched_tx,
+            tx_index,
+            address,
+            state,
+            gas_meter,
+            keys_changed,
+            verifiers,
+            vp_wasm_cache,
+            dry_run,
+        )
+    }
+    #[cfg(feature = "wasm-differential")]
+    {
+        crate::wasmtime_runtime::differential::vp(
+            vp_code_hash,
+            batched_tx,
+            tx_index,
+            address,
+            state,
+            gas_meter,
+            keys_changed,
+            verifiers,
+            vp_wasm_cache,
+            gas_meter_kind,
+            dry_run,
+        )
+    }
+}
+
+/// Execute a validity predicate code with wasmer. Returns whether the validity
+/// predicate accepted storage modifications performed by the transaction
+/// that triggered the execution.
+#[allow(clippy::too_many_arguments)]
+pub fn wasmer_vp<S, CA>(
     vp_code_hash: Hash,
     batched_tx: &BatchedTxRef<'_>,
     tx_index: &TxIndex,
@@ -611,27 +810,13 @@ where
             verifiers_len,
         )
         .map_err(|rt_error| {
-            let downcasted_err = || {
-                let source_err = rt_error.source()?;
-                let downcasted_vp_err =
-                    source_err.downcast_ref::<vp_host_fns::Error>()?;
-                let downcasted_vp_rt_err = downcasted_vp_err
-                    .downcast_ref::<vp_host_fns::RuntimeError>(
-                )?;
-
-                match downcasted_vp_rt_err {
-                    vp_host_fns::RuntimeError::OutOfGas(_) => {
-                        Some(Error::GasError(rt_error.to_string()))
-                    }
-                    vp_host_fns::RuntimeError::InvalidSectionSignature(_) => {
-                        Some(Error::InvalidSectionSignature(
-                            rt_error.to_string(),
-                        ))
-                    }
-                    _ => None,
-                }
-            };
-            downcasted_err().unwrap_or(Error::RuntimeError(rt_error))
+            rt_error
+                .source()
+                .and_then(|source_err| {
+                    source_err.downcast_ref::<vp_host_fns::Error>()
+                })
+                .and_then(|vp_err| downcast_vp_host_fn_error(vp_err, &rt_error))
+                .unwrap_or(Error::RuntimeError(rt_error))
         })?;
 
     finish_ctx()?;
@@ -651,17 +836,40 @@ where
         let _store = RefCell::into_inner(store);
         Ok(())
     } else {
-        unsafe { yielded_value.get_mut() }.take().map_or_else(
-            || Err(Error::VpError(VpError::Unspecified)),
-            |borsh_encoded_err| {
-                let vp_err = VpError::try_from_slice(&borsh_encoded_err)
-                    .map_err(|e| Error::ConversionError(e.to_string()))?;
-                Err(Error::VpError(vp_err))
-            },
-        )
+        Err(extract_vp_error(unsafe { yielded_value.get_mut() }))
+    }
+}
+
+/// Map the errors raised by VP host fns that must be reported to the
+/// protocol, given the runtime error of the engine that trapped on them.
+pub(crate) fn downcast_vp_host_fn_error(
+    vp_err: &vp_host_fns::Error,
+    rt_error: &impl std::fmt::Display,
+) -> Option<Error> {
+    match vp_err.downcast_ref::<vp_host_fns::RuntimeError>()? {
+        vp_host_fns::RuntimeError::OutOfGas(_) => {
+            Some(Error::GasError(rt_error.to_string()))
+        }
+        vp_host_fns::RuntimeError::InvalidSectionSignature(_) => {
+            Some(Error::InvalidSectionSignature(rt_error.to_string()))
+        }
+        _ => None,
     }
 }
 
+/// Extract the error of a rejecting VP from the value it yielded.
+pub(crate) fn extract_vp_error(yielded_value: &mut Option<Vec<u8>>) -> Error {
+    yielded_value.take().map_or(
+        Error::VpError(VpError::Unspecified),
+        |borsh_encoded_err| {
+            VpError::try_from_slice(&borsh_encoded_err).map_or_else(
+                |e| Error::ConversionError(e.to_string()),
+                Error::VpError,
+            )
+        },
+    )
+}
+
 /// Validity predicate wasm evaluator for `eval` host function calls.
 #[derive(Default, Debug)]
 pub struct VpEvalWasm<D, H, CA>
@@ -689,93 +897,121 @@ where
         vp_code_hash: Hash,
         input_data: BatchedTxRef<'_>,
     ) -> namada_state::Result<()> {
-        use namada_state::ResultExt;
+        #[cfg(not(feature = "wasmtime-runtime"))]
+        {
+            wasmer_eval_native(native_ctx, vp_code_hash, input_data)
+        }
+        #[cfg(feature = "wasmtime-runtime")]
+        {
+            crate::wasmtime_runtime::run::eval_native(
+                native_ctx,
+                vp_code_hash,
+                input_data,
+            )
+        }
+    }
+}
 
-        let eval_runner =
-            VpEvalWasm::<<S as StateRead>::D, <S as StateRead>::H, CA> {
-                db: PhantomData,
-                hasher: PhantomData,
-                cache_access: PhantomData,
-            };
-        let mut iterators: PrefixIterators<'_, <S as StateRead>::D> =
-            PrefixIterators::default();
-        let mut result_buffer: Option<Vec<u8>> = None;
-        let mut yielded_value: Option<Vec<u8>> = None;
-        let mut vp_wasm_cache = native_ctx.vp_wasm_cache.clone();
+/// Evaluate a VP with wasmer from a native VP.
+#[cfg(not(feature = "wasmtime-runtime"))]
+fn wasmer_eval_native<S, CA>(
+    native_ctx: &namada_vp::native_vp::Ctx<
+        '_,
+        S,
+        VpCache<CA>,
+        VpEvalWasm<<S as StateRead>::D, <S as StateRead>::H, CA>,
+    >,
+    vp_code_hash: Hash,
+    input_data: BatchedTxRef<'_>,
+) -> namada_state::Result<()>
+where
+    S: 'static + StateRead,
+    CA: WasmCacheAccess,
+{
+    use namada_state::ResultExt;
 
-        let wasm_gas_meter = RefCell::new(GasMeter::new(
-            GasMeterKind::MutGlobal,
-            || unsafe { VpGasMeter::placeholder() },
-            WasmGasMeter::uninit,
-        ));
+    let eval_runner =
+        VpEvalWasm::<<S as StateRead>::D, <S as StateRead>::H, CA> {
+            db: PhantomData,
+            hasher: PhantomData,
+            cache_access: PhantomData,
+        };
+    let mut iterators: PrefixIterators<'_, <S as StateRead>::D> =
+        PrefixIterators::default();
+    let mut result_buffer: Option<Vec<u8>> = None;
+    let mut yielded_value: Option<Vec<u8>> = None;
+    let mut vp_wasm_cache = native_ctx.vp_wasm_cache.clone();
 
-        let ctx = VpCtx::new(
-            native_ctx.address,
-            native_ctx.state.write_log(),
-            native_ctx.state.in_mem(),
-            native_ctx.state.db(),
-            &wasm_gas_meter,
-            native_ctx.tx,
-            native_ctx.cmt,
-            native_ctx.tx_index,
-            &mut iterators,
-            native_ctx.verifiers,
-            &mut result_buffer,
-            &mut yielded_value,
-            native_ctx.keys_changed,
-            &eval_runner,
-            &mut vp_wasm_cache,
-        );
+    let wasm_gas_meter = RefCell::new(GasMeter::new(
+        GasMeterKind::MutGlobal,
+        || unsafe { VpGasMeter::placeholder() },
+        WasmGasMeter::uninit,
+    ));
 
-        eval_runner
-            .eval_native_result(
-                ctx,
-                vp_code_hash,
-                input_data,
-                |instance, store| {
-                    wasm_gas_meter.borrow_mut().init(
-                        |meter| {
-                            *meter =
-                                native_ctx.gas_meter.replace_with(|_| unsafe {
-                                    VpGasMeter::placeholder()
-                                });
-
-                            Ok(())
-                        },
-                        |meter| {
-                            let global = instance
-                                .exports
-                                .get_global(MUT_GLOBAL_GAS_NAME)
-                                .map_err(Error::MissingGasMutGlobal)?;
-
-                            meter.init_from(
-                                &*native_ctx.gas_meter.borrow(),
-                                global.clone(),
-                                Rc::downgrade(store),
-                            );
-
-                            Ok(())
-                        },
-                    )
-                },
-                || {
-                    let wasm_gas_meter =
-                        wasm_gas_meter.replace_with(|_| unsafe {
-                            GasMeter::vp_placeholder()
-                        });
-
-                    wasm_gas_meter
-                        .flush_to_meter(&mut *native_ctx.gas_meter.borrow_mut())
-                        .map_err(|err| Error::GasError(err.to_string()))
-                },
-            )
-            .inspect_err(|err| {
-                tracing::warn!("VP eval from a native VP failed with: {err}");
-            })
-            .into_storage_result()?;
+    let ctx = VpCtx::new(
+        native_ctx.address,
+        native_ctx.state.write_log(),
+        native_ctx.state.in_mem(),
+        native_ctx.state.db(),
+        &wasm_gas_meter,
+        native_ctx.tx,
+        native_ctx.cmt,
+        native_ctx.tx_index,
+        &mut iterators,
+        native_ctx.verifiers,
+        &mut result_buffer,
+        &mut yielded_value,
+        native_ctx.keys_changed,
+        &eval_runner,
+        &mut vp_wasm_cache,
+    );
 
-        Ok(())
-    }
+    eval_runner
+        .eval_native_result(
+            ctx,
+            vp_code_hash,
+            input_data,
+            |instance, store| {
+                wasm_gas_meter.borrow_mut().init(
+                    |meter| {
+                        *meter =
+                            native_ctx.gas_meter.replace_with(|_| unsafe {
+                                VpGasMeter::placeholder()
+                            });
+
+                        Ok(())
+                    },
+                    |meter| {
+                        let global = instance
+                            .exports
+                            .get_global(MUT_GLOBAL_GAS_NAME)
+                            .map_err(Error::MissingGasMutGlobal)?;
+
+                        meter.init_from(
+                            &*native_ctx.gas_meter.borrow(),
+                            global.clone(),
+                            Rc::downgrade(store),
+                        );
+
+                        Ok(())
+                    },
+                )
+            },
+            || {
+                let wasm_gas_meter = wasm_gas_meter
+                    .replace_with(|_| unsafe { GasMeter::vp_placeholder() });
+
+                wasm_gas_meter
+                    .flush_to_meter(&mut *native_ctx.gas_meter.borrow_mut())
+                    .map_err(|err| Error::GasError(err.to_string()))
+            },
+        )
+        .inspect_err(|err| {
+            tracing::warn!("VP eval from a native VP failed with: {err}");
+        })
+        .into_storage_result()?;
+
+    Ok(())
 }
 
 impl<D, H, CA> VpEvaluator for VpEvalWasm<D, H, CA>
@@ -1454,6 +1690,8 @@ mod tests {
 
     use super::memory::{TX_MEMORY_INIT_PAGES, VP_MEMORY_INIT_PAGES};
     use super::*;
+    // NB: these tests cover the wasmer backend specifically
+    use super::{wasmer_tx as tx, wasmer_vp as vp};
     use crate::host_env::{self, TxRuntimeError};
     use crate::wasm;
 
@@ -0,0 +1,397 @@
+//! Differential execution of txs and VPs on wasmer and wasmtime.
+//!
+//! Both engines run from the same pre-state, and we assert that they produce
+//! identical storage writes, events, results and gas consumption. On
+//! success, the state is left as modified by wasmtime.
+//!
+//! The equivalence of the gas consumption relies on wasmtime's fuel never
+//! running out before the gas, see `FUEL_PER_GAS`. That holds as long as
+//! every wasm instruction costs at least one unit of gas and the work done in
+//! host functions consumes gas, but no fuel. A guest running out of fuel
+//! breaks these assumptions, which we assert.
+
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+use std::fmt::Debug;
+
+use namada_core::address::Address;
+use namada_core::hash::Hash;
+use namada_core::storage::{Key, TxIndex};
+use namada_gas::profile::{GasProfile, GasProfiler};
+use namada_gas::{GasMeterKind, GasMetering, TxGasMeter, VpGasMeter};
+use namada_state::{State, StateRead, StorageRead};
+use namada_tx::{BatchedTxRef, Tx, TxCommitments};
+
+use super::run::OUT_OF_FUEL;
+use crate::WasmCacheAccess;
+use crate::wasm::run::{Error, Result, wasmer_tx, wasmer_vp};
+use crate::wasm::{TxCache, VpCache};
+
+/// The engine agnostic outcome of an execution
+#[derive(Debug, PartialEq)]
+enum Outcome<'a, T> {
+    /// The execution succeeded
+    Accepted(&'a T),
+    /// The tx failed with the given error
+    TxRejected(&'a str),
+    /// The VP rejected the tx with the given error
+    VpRejected(String),
+    /// The execution ran out of gas
+    OutOfGas,
+    /// A tx section is missing or a commitment is invalid
+    MissingSection,
+    /// A tx section signature is invalid
+    InvalidSectionSignature,
+    /// The engine aborted the execution, e.g. the guest trapped
+    Aborted,
+    /// The execution failed before or after running the wasm
+    Failed(String),
+}
+
+impl<'a, T> Outcome<'a, T> {
+    fn of(result: &'a Result<T>) -> Self {
+        match result {
+            Ok(value) => Self::Accepted(value),
+            Err(Error::TxError(err)) => Self::TxRejected(err),
+            Err(Error::VpError(err)) => Self::VpRejected(err.to_string()),
+            Err(Error::GasError(_)) => Self::OutOfGas,
+            Err(Error::MissingSection(_)) => Self::MissingSection,
+            Err(Error::InvalidSectionSignature(_)) => {
+                Self::InvalidSectionSignature
+            }
+            // The errors of each engine carry messages that differ
+            Err(
+                Error::CompileError(_)
+                | Error::RuntimeError(_)
+                | Error::InstantiationError(_)
+                | Error::MissingModuleMemory(_)
+                | Error::MissingModuleEntrypoint(_)
+                | Error::UnexpectedModuleEntrypointInterface { .. }
+                | Error::MemoryError(_)
+                | Error::WasmtimeCompileError(_)
+                | Error::WasmtimeInstantiationError(_)
+                | Error::WasmtimeMissingExport(_)
+                | Error::WasmtimeModuleEntrypointInterface { .. }
+                | Error::WasmtimeRuntimeError(_)
+                | Error::WasmtimeMemoryError(_),
+            ) => Self::Aborted,
+            Err(err) => Self::Failed(err.to_string()),
+        }
+    }
+}
+
+/// Assert that both engines had the same outcome and consumed the same gas.
+fn assert_same_outcome<T: Debug + PartialEq>(
+    kind: &str,
+    code_hash: &Hash,
+    wasmer: (&Result<T>, &impl GasMetering),
+    wasmtime: (&Result<T>, &impl GasMetering),
+) {
+    let (wasmer_result, wasmer_gas_meter) = wasmer;
+    let (wasmtime_result, wasmtime_gas_meter) = wasmtime;
+    assert!(
+        !matches!(
+            wasmtime_result,
+            Err(Error::GasError(err)) if err.starts_with(OUT_OF_FUEL)
+        ),
+        "The {kind} {code_hash} ran out of fuel before running out of gas on \
+         wasmtime: {wasmtime_result:?}"
+    );
+    let outcome = Outcome::of(wasmer_result);
+    assert_eq!(
+        outcome,
+        Outcome::of(wasmtime_result),
+        "The outcome of {kind} {code_hash} differs between wasmer and \
+         wasmtime: {wasmer_result:?} != {wasmtime_result:?}"
+    );
+    // NB: when running out of gas, the gas meter may be left in a different
+    // state depending on the gas metering backend
+    if outcome == Outcome::OutOfGas {
+        return;
+    }
+    assert_eq!(
+        wasmer_gas_meter.get_consumed_gas(),
+        wasmtime_gas_meter.get_consumed_gas(),
+        "The gas consumed by {kind} {code_hash} differs between wasmer and \
+         wasmtime"
+    );
+}
+
+/// Get the gas profile collected before an execution, if any. The profiler is
+/// shared by the clones of a gas meter, so we must restore its profile after
+/// the execution on wasmer to only keep the one of wasmtime.
+fn pre_gas_profile(gas_meter: &impl GasMetering) -> Option<GasProfile> {
+    gas_meter.gas_profiler().map(GasProfiler::snapshot)
+}
+
+/// Restore the gas profile collected before an execution, if any
+fn restore_gas_profile(
+    gas_meter: &impl GasMetering,
+    profile: Option<GasProfile>,
+) {
+    if let Some((profiler, profile)) = gas_meter.gas_profiler().zip(profile) {
+        profiler.replace(profile);
+    }
+}
+
+/// Execute a transaction code on both engines. Returns the set verifiers
+/// addresses requested by the transaction.
+#[allow(clippy::too_many_arguments)]
+pub fn tx<S, CA>(
+    state: &mut S,
+    gas_meter: &RefCell<TxGasMeter>,
+    wrapper_hash: Option<&Hash>,
+    tx_index: &TxIndex,
+    tx: &Tx,
+    cmt: &TxCommitments,
+    vp_wasm_cache: &mut VpCache<CA>,
+    tx_wasm_cache: &mut TxCache<CA>,
+    gas_meter_kind: GasMeterKind,
+    dry_run: bool,
+) -> Result<BTreeSet<Address>>
+where
+    S: StateRead + State + StorageRead,
+    CA: 'static + WasmCacheAccess,
+{
+    let pre_write_log = state.write_log().clone();
+    let pre_gas_meter = gas_meter.borrow().clone();
+    let pre_gas_profile = pre_gas_profile(&pre_gas_meter);
+
+    let wasmer_result = wasmer_tx(
+        state,
+        gas_meter,
+        wrapper_hash,
+        tx_index,
+        tx,
+        cmt,
+        vp_wasm_cache,
+        tx_wasm_cache,
+        gas_meter_kind,
+        dry_run,
+    );
+    let wasmer_write_log =
+        std::mem::replace(state.write_log_mut(), pre_write_log);
+    let wasmer_gas_meter = gas_meter.replace(pre_gas_meter);
+    restore_gas_profile(&*gas_meter.borrow(), pre_gas_profile);
+
+    let wasmtime_result = super::run::tx(
+        state,
+        gas_meter,
+        wrapper_hash,
+        tx_index,
+        tx,
+        cmt,
+        vp_wasm_cache,
+        tx_wasm_cache,
+        dry_run,
+    );
+
+    let code_hash = cmt.code_sechash();
+    assert_same_outcome(
+        "tx",
+        code_hash,
+        (&wasmer_result, &wasmer_gas_meter),
+        (&wasmtime_result, &*gas_meter.borrow()),
+    );
+    // NB: the write log holds both the storage writes and the events
+    assert!(
+        &wasmer_write_log == state.write_log(),
+        "The storage writes or events of tx {code_hash} differ between wasmer \
+         and wasmtime"
+    );
+
+    wasmtime_result
+}
+
+/// Execute a validity predicate code on both engines. Returns whether the
+/// validity predicate accepted storage modifications performed by the
+/// transaction that triggered the execution.
+#[allow(clippy::too_many_arguments)]
+pub fn vp<S, CA>(
+    vp_code_hash: Hash,
+    batched_tx: &BatchedTxRef<'_>,
+    tx_index: &TxIndex,
+    address: &Address,
+    state: &S,
+    gas_meter: &RefCell<VpGasMeter>,
+    keys_changed: &BTreeSet<Key>,
+    verifiers: &BTreeSet<Address>,
+    vp_wasm_cache: VpCache<CA>,
+    gas_meter_kind: GasMeterKind,
+    dry_run: bool,
+) -> Result<()>
+where
+    S: StateRead,
+    CA: 'static + WasmCacheAccess,
+{
+    let pre_gas_meter = gas_meter.borrow().clone();
+    let pre_gas_profile = pre_gas_profile(&pre_gas_meter);
+
+    let wasmer_result = wasmer_vp(
+        vp_code_hash,
+        batched_tx,
+        tx_index,
+        address,
+        state,
+        gas_meter,
+        keys_changed,
+        verifiers,
+        vp_wasm_cache.clone(),
+        gas_meter_kind,
+        dry_run,
+    );
+    let wasmer_gas_meter = gas_meter.replace(pre_gas_meter);
+    restore_gas_profile(&*gas_meter.borrow(), pre_gas_profile);
+
+    let wasmtime_result = super::run::vp(
+        vp_code_hash,
+        batched_tx,
+        tx_index,
+        address,
+        state,
+        gas_meter,
+        keys_changed,
+        verifiers,
+        vp_wasm_cache,
+        dry_run,
+    );
+
+    assert_same_outcome(
+        "VP",
+        &vp_code_hash,
+        (&wasmer_result, &wasmer_gas_meter),
+        (&wasmtime_result, &*gas_meter.borrow()),
+    );
+
+    wasmtime_result
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use namada_state::StorageWrite;
+    use namada_state::testing::TestState;
+    use namada_test_utils::{WASM_FOR_TESTS_DIR, repo_root};
+    use namada_tx::data::TxType;
+    use namada_tx::{Code, Data};
+    use test_log::test;
+
+    use super::*;
+    use crate::wasm;
+
+    /// The gas limit is low enough for the wasms that never terminate to run
+    /// out of gas quickly
+    const GAS_LIMIT: u64 = 100_000_000;
+    const GAS_SCALE: u64 = 1;
+
+    /// Get the paths to the built wasms in the given directory of the repo
+    /// whose name starts with the given prefix
+    fn built_wasms(dir: &str, prefix: &str) -> Vec<PathBuf> {
+        let dir = repo_root().join(dir);
+        let mut wasms: Vec<_> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| {
+                path.extension().is_some_and(|ext| ext == "wasm")
+                    && path
+                        .file_name()
+                        .and_then(|name| name.to_str())
+                        .is_some_and(|name| name.starts_with(prefix))
+            })
+            .collect();
+        assert!(
+            !wasms.is_empty(),
+            "No built wasm found in {}, the wasms must be built first",
+            dir.to_string_lossy()
+        );
+        wasms.sort();
+        wasms
+    }
+
+    /// Execute the tx code on both engines with some empty data
+    fn run_tx(tx_code: &Path, gas_meter_kind: GasMeterKind) {
+        let (mut vp_cache, _) =
+            wasm::compilation_cache::common::testing::vp_cache();
+        let (mut tx_cache, _) =
+            wasm::compilation_cache::common::testing::tx_cache();
+        let mut state = TestState::default();
+        let gas_meter = RefCell::new(TxGasMeter::new(GAS_LIMIT, GAS_SCALE));
+        let mut outer_tx = Tx::from_type(TxType::Raw);
+        outer_tx.set_code(Code::new(std::fs::read(tx_code).unwrap(), None));
+        outer_tx.set_data(Data::new(vec![]));
+        let batched_tx = outer_tx.batch_ref_first_tx().unwrap();
+
+        // NB: the outcomes are asserted to be the same on both engines, but
+        // the txs are free to fail
+        let _ = tx(
+            &mut state,
+            &gas_meter,
+            None,
+            &TxIndex::default(),
+            batched_tx.tx,
+            batched_tx.cmt,
+            &mut vp_cache,
+            &mut tx_cache,
+            gas_meter_kind,
+            false,
+        );
+    }
+
+    /// Execute the VP code on both engines with an empty inner tx
+    fn run_vp(vp_code: &Path, gas_meter_kind: GasMeterKind) {
+        let (vp_cache, _) =
+            wasm::compilation_cache::common::testing::vp_cache();
+        let mut state = TestState::default();
+        let addr = state.in_mem_mut().address_gen.generate_address("rng seed");
+        let gas_meter = RefCell::new(VpGasMeter::new_from_tx_meter(
+            &TxGasMeter::new(GAS_LIMIT, GAS_SCALE),
+        ));
+        let mut outer_tx = Tx::from_type(TxType::Raw);
+        outer_tx.push_default_inner_tx();
+        // store the vp code
+        let vp_code = std::fs::read(vp_code).unwrap();
+        let code_hash = Hash::sha256(&vp_code);
+        let code_len = vp_code.len() as u64;
+        state.write(&Key::wasm_code(&code_hash), vp_code).unwrap();
+        state
+            .write(&Key::wasm_code_len(&code_hash), code_len)
+            .unwrap();
+
+        // NB: the outcomes are asserted to be the same on both engines, but
+        // the VPs are free to reject the tx
+        let _ = vp(
+            code_hash,
+            &outer_tx.batch_ref_first_tx().unwrap(),
+            &TxIndex::default(),
+            &addr,
+            &state,
+            &gas_meter,
+            &BTreeSet::new(),
+            &BTreeSet::new(),
+            vp_cache,
+            gas_meter_kind,
+            false,
+        );
+    }
+
+    /// Test that every built tx and VP wasm has the same outcome on both
+    /// engines
+    #[test]
+    fn test_built_wasms() {
+        for dir in ["wasm", WASM_FOR_TESTS_DIR] {
+            for gas_meter_kind in
+                [GasMeterKind::HostFn, GasMeterKind::MutGlobal]
+            {
+                for tx_code in built_wasms(dir, "tx_") {
+                    tracing::info!(?tx_code, ?gas_meter_kind, "Running tx");
+                    run_tx(&tx_code, gas_meter_kind);
+                }
+                for vp_code in built_wasms(dir, "vp_") {
+                    tracing::info!(?vp_code, ?gas_meter_kind, "Running VP");
+                    run_vp(&vp_code, gas_meter_kind);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,215 @@
+//! The wasmtime host environment.
+//!
+//! Here, we expose the host functions into wasmtime's linker, so they can be
+//! called from inside the wasm.
+
+use namada_state::{DB, DBIter, StorageHasher};
+use wasmtime::{Caller, Linker};
+
+use super::memory::{StoreData, WasmtimeMemory};
+use crate::host_env::{TxVmEnv, VpEvaluator, VpVmEnv};
+use crate::{WasmCacheAccess, host_env};
+
+/// Convert the result of a host function into a wasmtime result. An error
+/// traps the guest, and can be recovered by downcasting the trap.
+trait IntoWasmtimeResult {
+    type Output;
+
+    fn into_wasmtime_result(self) -> wasmtime::Result<Self::Output>;
+}
+
+impl<T> IntoWasmtimeResult for namada_state::Result<T> {
+    type Output = T;
+
+    fn into_wasmtime_result(self) -> wasmtime::Result<T> {
+        self.map_err(wasmtime::Error::new)
+    }
+}
+
+impl IntoWasmtimeResult for () {
+    type Output = ();
+
+    fn into_wasmtime_result(self) -> wasmtime::Result<()> {
+        Ok(())
+    }
+}
+
+/// Link a host function into the `env` namespace of a linker. The host env is
+/// given access to the guest memory through the caller for the duration of
+/// the call, and the gas of the call is profiled under the import name.
+macro_rules! link_host_fn {
+    ($linker:ident, $env:ident, $name:literal, $host_fn:path $(, $arg:ident: $ty:ty)* $(,)?) => {{
+        let env = $env.clone();
+        $linker.func_wrap(
+            "env",
+            $name,
+            move |mut caller: Caller<'_, StoreData>, $($arg: $ty),*| {
+                let mut env = env.clone();
+                // SAFETY: the memory is dropped with `env` before we return
+                // from the host function call
+                env.memory = unsafe { WasmtimeMemory::from_caller(&mut caller) };
+                env.profile_host_fn($name, |env| $host_fn(env, $($arg),*))
+                    .into_wasmtime_result()
+            },
+        )?;
+    }};
+}
+
+/// Link the host functions exposed to the vm guest running transaction code
+#[rustfmt::skip]
+pub fn tx_imports<D, H, CA>(
+    linker: &mut Linker<StoreData>,
+    env: TxVmEnv<WasmtimeMemory, D, H, CA>,
+) -> wasmtime::Result<()>
+where
+    D: DB + for<'iter> DBIter<'iter> + 'static,
+    H: StorageHasher + 'static,
+    CA: WasmCacheAccess + 'static,
+{
+    // Gas injection hook
+    link_host_fn!(linker, env, "gas", host_env::tx_charge_gas, a0: u64);
+    // Tx Host functions
+    link_host_fn!(linker, env, "namada_tx_delete", host_env::tx_delete, a0: u64, a1: u64);
+    link_host_fn!(linker, env, "namada_tx_emit_event", host_env::tx_emit_event, a0: u64, a1: u64);
+    link_host_fn!(linker, env, "namada_tx_get_block_epoch", host_env::tx_get_block_epoch);
+    link_host_fn!(linker, env, "namada_tx_get_block_header", host_env::tx_get_block_header, a0: u64);
+    link_host_fn!(linker, env, "namada_tx_get_block_height", host_env::tx_get_block_height);
+    link_host_fn!(linker, env, "namada_tx_get_chain_id", host_env::tx_get_chain_id, a0: u64);
+    link_host_fn!(linker, env, "namada_tx_get_events", host_env::tx_get_events, a0: u64, a1: u64);
+    link_host_fn!(linker, env, "namada_tx_get_native_token", host_env::tx_get_native_token, a0: u64);
+    link_host_fn!(linker, env, "namada_tx_get_pred_epochs", host_env::tx_get_pred_epochs);
+    link_host_fn!(linker, env, "namada_tx_get_tx_index", host_env::tx_get_tx_index);
+    link_host_fn!(linker, env, "namada_tx_hash_blake2b", host_env::tx_hash_blake2b, a0: u64, a1: u64, a2: u64);
+    link_host_fn!(linker, env, "namada_tx_hash_keccak256", host_env::tx_hash_keccak256, a0: u64, a1: u64, a2: u64);
+    link_host_fn!(linker, env, "namada_tx_hash_sha256", host_env::tx_hash_sha256, a0: u64, a1: u64, a2: u64);
+    link_host_fn!(linker, env, "namada_tx_has_key", host_env::tx_has_key, a0: u64, a1: u64);
+    link_host_fn!(linker, env, "namada_tx_init_account", host_env::tx_init_account, a0: u64, a1: u64, a2: u64, a3: u64, a4: u64, a5: u64, a6: u64);
+    link_host_fn!(linker, env, "namada_tx_insert_verifier", host_env::tx_insert_verifier, a0: u64, a1: u64);
+    link_host_fn!(linker, env, "namada_tx_iter_next", host_env::tx_iter_next, a0: u64);
+    link_host_fn!(linker, env, "namada_tx_iter_prefix", host_env::tx_iter_prefix, a0: u64, a1: u64);
+    link_host_fn!(linker, env, "namada_tx_iter_prefix_range", host_env::tx_iter_prefix_range, a0: u64, a1: u64, a2: u64, a3: u64);
+    link_host_fn!(linker, env, "namada_tx_log_string", host_env::tx_log_string, a0: u64, a1: u64);
+    link_host_fn!(linker, env, "namada_tx_push_savepoint", host_env::tx_push_savepoint);
+    link_host_fn!(linker, env, "namada_tx_read", host_env::tx_read, a0: u64, a1: u64);
+    link_host_fn!(linker, env, "namada_tx_read_temp", host_env::tx_read_temp, a0: u64, a1: u64);
+    link_host_fn!(linker, env, "namada_tx_release_savepoint", host_env::tx_release_savepoint);
+    link_host_fn!(linker, env, "namada_tx_result_buffer", host_env::tx_result_buffer, a0: u64);
+    link_host_fn!(linker, env, "namada_tx_rollback_to_savepoint", host_env::tx_rollback_to_savepoint);
+    link_host_fn!(linker, env, "namada_tx_set_commitment_sentinel", host_env::tx_set_commitment_sentinel);
+    link_host_fn!(linker, env, "namada_tx_update_masp_note_commitment_tree", host_env::tx_update_masp_note_commitment_tree, a0: u64, a1: u64);
+    link_host_fn!(linker, env, "namada_tx_update_validity_predicate", host_env::tx_update_validity_predicate, a0: u64, a1: u64, a2: u64, a3: u64, a4: u64, a5: u64);
+    link_host_fn!(linker, env, "namada_tx_verify_signature", host_env::tx_verify_signature, a0: u64, a1: u64, a2: u64, a3: u64, a4: u64, a5: u64);
+    link_host_fn!(linker, env, "namada_tx_verify_tx_section_signature", tx_verify_tx_section_signature, a0: u64, a1: u64, a2: u64, a3: u64, a4: i32);
+    link_host_fn!(linker, env, "namada_tx_write", host_env::tx_write, a0: u64, a1: u64, a2: u64, a3: u64);
+    link_host_fn!(linker, env, "namada_tx_write_temp", host_env::tx_write_temp, a0: u64, a1: u64, a2: u64, a3: u64);
+    link_host_fn!(linker, env, "namada_tx_yield_value", host_env::tx_yield_value, a0: u64, a1: u64);
+
+    Ok(())
+}
+
+/// Link the host functions exposed to the vm guest running validity predicate
+/// code
+#[rustfmt::skip]
+pub fn vp_imports<D, H, EVAL, CA>(
+    linker: &mut Linker<StoreData>,
+    env: VpVmEnv<WasmtimeMemory, D, H, EVAL, CA>,
+) -> wasmtime::Result<()>
+where
+    D: DB + for<'iter> DBIter<'iter> + 'static,
+    H: StorageHasher + 'static,
+    EVAL: VpEvaluator<Db = D, H = H, Eval = EVAL, CA = CA> + 'static,
+    CA: WasmCacheAccess + 'static,
+{
+    // Gas injection hook
+    link_host_fn!(linker, env, "gas", host_env::vp_charge_gas, a0: u64);
+    // VP Host functions
+    link_host_fn!(linker, env, "namada_vp_eval", host_env::vp_eval, a0: u64, a1: u64, a2: u64, a3: u64);
+    link_host_fn!(linker, env, "namada_vp_get_block_header", host_env::vp_get_block_header, a0: u64);
+    link_host_fn!(linker, env, "namada_vp_get_block_height", host_env::vp_get_block_height);
+    link_host_fn!(linker, env, "namada_vp_get_chain_id", host_env::vp_get_chain_id, a0: u64);
+    link_host_fn!(linker, env, "namada_vp_get_events", host_env::vp_get_events, a0: u64, a1: u64);
+    link_host_fn!(linker, env, "namada_vp_get_native_token", host_env::vp_get_native_token, a0: u64);
+    link_host_fn!(linker, env, "namada_vp_get_pred_epochs", host_env::vp_get_pred_epochs);
+    link_host_fn!(linker, env, "namada_vp_get_tx_code_hash", host_env::vp_get_tx_code_hash, a0: u64);
+    link_host_fn!(linker, env, "namada_vp_get_tx_index", host_env::vp_get_tx_index);
+    link_host_fn!(linker, env, "namada_vp_hash_blake2b", host_env::vp_hash_blake2b, a0: u64, a1: u64, a2: u64);
+    link_host_fn!(linker, env, "namada_vp_hash_keccak256", host_env::vp_hash_keccak256, a0: u64, a1: u64, a2: u64);
+    link_host_fn!(linker, env, "namada_vp_hash_sha256", host_env::vp_hash_sha256, a0: u64, a1: u64, a2: u64);
+    link_host_fn!(linker, env, "namada_vp_has_key_post", host_env::vp_has_key_post, a0: u64, a1: u64);
+    link_host_fn!(linker, env, "namada_vp_has_key_pre", host_env::vp_has_key_pre, a0: u64, a1: u64);
+    link_host_fn!(linker, env, "namada_vp_iter_next", host_env::vp_iter_next, a0: u64);
+    link_host_fn!(linker, env, "namada_vp_iter_prefix_post", host_env::vp_iter_prefix_post, a0: u64, a1: u64);
+    link_host_fn!(linker, env, "namada_vp_iter_prefix_pre", host_env::vp_iter_prefix_pre, a0: u64, a1: u64);
+    link_host_fn!(linker, env, "namada_vp_iter_prefix_range_post", host_env::vp_iter_prefix_range_post, a0: u64, a1: u64, a2: u64, a3: u64);
+    link_host_fn!(linker, env, "namada_vp_iter_prefix_range_pre", host_env::vp_iter_prefix_range_pre, a0: u64, a1: u64, a2: u64, a3: u64);
+    link_host_fn!(linker, env, "namada_vp_log_string", host_env::vp_log_string, a0: u64, a1: u64);
+    link_host_fn!(linker, env, "namada_vp_read_post", host_env::vp_read_post, a0: u64, a1: u64);
+    link_host_fn!(linker, env, "namada_vp_read_pre", host_env::vp_read_pre, a0: u64, a1: u64);
+    link_host_fn!(linker, env, "namada_vp_read_temp", host_env::vp_read_temp, a0: u64, a1: u64);
+    link_host_fn!(linker, env, "namada_vp_result_buffer", host_env::vp_result_buffer, a0: u64);
+    link_host_fn!(linker, env, "namada_vp_verify_signature", host_env::vp_verify_signature, a0: u64, a1: u64, a2: u64, a3: u64, a4: u64, a5: u64);
+    link_host_fn!(linker, env, "namada_vp_verify_tx_section_signature", vp_verify_tx_section_signature, a0: u64, a1: u64, a2: u64, a3: u64, a4: u64, a5: u64, a6: i32);
+    link_host_fn!(linker, env, "namada_vp_yield_value", host_env::vp_yield_value, a0: u64, a1: u64);
+
+    Ok(())
+}
+
+/// Wasm has no 8 bits integers, so the signature threshold is passed in as an
+/// `i32`. Like wasmer, we truncate it to the expected `u8`.
+fn tx_verify_tx_section_signature<D, H, CA>(
+    env: &mut TxVmEnv<WasmtimeMemory, D, H, CA>,
+    hash_list_ptr: u64,
+    hash_list_len: u64,
+    public_keys_map_ptr: u64,
+    public_keys_map_len: u64,
+    threshold: i32,
+) -> host_env::TxResult<i64>
+where
+    D: DB + for<'iter> DBIter<'iter> + 'static,
+    H: StorageHasher + 'static,
+    CA: WasmCacheAccess + 'static,
+{
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let threshold = threshold as u8;
+    host_env::tx_verify_tx_section_signature(
+        env,
+        hash_list_ptr,
+        hash_list_len,
+        public_keys_map_ptr,
+        public_keys_map_len,
+        threshold,
+    )
+}
+
+/// Wasm has no 8 bits integers, so the signature threshold is passed in as an
+/// `i32`. Like wasmer, we truncate it to the expected `u8`.
+#[allow(clippy::too_many_arguments)]
+fn vp_verify_tx_section_signature<D, H, EVAL, CA>(
+    env: &mut VpVmEnv<WasmtimeMemory, D, H, EVAL, CA>,
+    hash_list_ptr: u64,
+    hash_list_len: u64,
+    public_keys_map_ptr: u64,
+    public_keys_map_len: u64,
+    signer_ptr: u64,
+    signer_len: u64,
+    threshold: i32,
+) -> namada_state::Result<()>
+where
+    D: DB + for<'iter> DBIter<'iter> + 'static,
+    H: StorageHasher + 'static,
+    EVAL: VpEvaluator<Db = D, H = H, Eval = EVAL, CA = CA> + 'static,
+    CA: WasmCacheAccess + 'static,
+{
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let threshold = threshold as u8;
+    host_env::vp_verify_tx_section_signature(
+        env,
+        hash_list_ptr,
+        hash_list_len,
+        public_keys_map_ptr,
+        public_keys_map_len,
+        signer_ptr,
+        signer_len,
+        threshold,
+    )
+}
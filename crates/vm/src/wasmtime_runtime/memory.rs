@@ -0,0 +1,391 @@
+//! Wasmtime memory is used for bi-directionally passing data between the host
+//! and a wasm instance.
+
+use std::ptr::NonNull;
+use std::str::Utf8Error;
+
+use namada_core::arith::{self, checked};
+use namada_core::borsh::BorshSerializeExt;
+use namada_gas::{Gas, MEMORY_ACCESS_GAS_PER_BYTE};
+use namada_tx::BatchedTxRef;
+use thiserror::Error;
+use wasmtime::{
+    AsContextMut, Caller, Engine, ExternType, Instance, Memory, Module, Store,
+    StoreLimits, StoreLimitsBuilder,
+};
+
+use crate::memory::VmMemory;
+use crate::types::VpInput;
+use crate::wasm::memory::{TxCallInput, VpCallInput};
+use crate::wasm::run::{ALLOC_FN_NAME, GUEST_MEMORY};
+
+/// The size of a wasm page (64 KiB)
+const WASM_PAGE_SIZE: u64 = 0x1_0000;
+
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("Attempted to modify read-only memory")]
+    ReadOnly,
+    #[error("Offset {0}+{1} overflows 32 bits storage")]
+    OverflowingOffset(u64, usize),
+    #[error("Failed to grow memory: {0}")]
+    Grow(wasmtime::Error),
+    #[error("Wasm memory access error: {0}")]
+    Access(#[from] wasmtime::MemoryAccessError),
+    #[error("Memory is not initialized")]
+    UninitializedMemory,
+    #[error("Invalid utf8 string read from memory")]
+    InvalidUtf8String(Utf8Error),
+    #[error("Arithmetic {0}")]
+    Arith(#[from] arith::Error),
+    #[error("{0}")]
+    TryFromInt(#[from] std::num::TryFromIntError),
+    #[error("Failed to allocate memory: {0}")]
+    GuestAlloc(wasmtime::Error),
+    #[error("{0} exceeds the allowed memory limit")]
+    Limit(&'static str),
+}
+
+/// Result of a function that may fail
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl From<Error> for namada_state::Error {
+    fn from(value: Error) -> Self {
+        namada_state::Error::new(value)
+    }
+}
+
+/// The host data of a wasmtime store.
+#[derive(Debug)]
+pub struct StoreData {
+    /// Limits on the resources of the store
+    limits: StoreLimits,
+    /// The guest's main memory, set after instantiation
+    memory: Option<Memory>,
+}
+
+impl StoreData {
+    /// Store a handle to the guest's main memory.
+    pub fn init_memory(&mut self, memory: Memory) {
+        if self.memory.is_some() {
+            tracing::error!("wasm memory is already initialized");
+            return;
+        }
+        self.memory = Some(memory);
+    }
+}
+
+/// Build a new store whose linear memory can grow up to `max_pages` wasm
+/// pages.
+pub fn new_store(engine: &Engine, max_pages: u32) -> Store<StoreData> {
+    let memory_size = u64::from(max_pages).saturating_mul(WASM_PAGE_SIZE);
+    let limits = StoreLimitsBuilder::new()
+        .memory_size(usize::try_from(memory_size).unwrap_or(usize::MAX))
+        .build();
+    let mut store = Store::new(
+        engine,
+        StoreData {
+            limits,
+            memory: None,
+        },
+    );
+    store.limiter(|data| &mut data.limits);
+    store
+}
+
+/// Ensure that the memory exported by the given module does not exceed
+/// `max_pages` wasm pages. This mirrors the memory tunables of the wasmer
+/// runtime.
+pub fn check_memory_limit(module: &Module, max_pages: u32) -> Result<()> {
+    let max_pages = u64::from(max_pages);
+    for export in module.exports() {
+        if let ExternType::Memory(ty) = export.ty() {
+            if ty.minimum() > max_pages {
+                return Err(Error::Limit("Minimum"));
+            }
+            if ty.maximum().is_some_and(|max| max > max_pages) {
+                return Err(Error::Limit("Maximum"));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Write transaction inputs into wasm memory
+pub fn write_tx_inputs(
+    instance: &Instance,
+    store: &mut Store<StoreData>,
+    memory: Memory,
+    tx_data: &BatchedTxRef<'_>,
+) -> Result<TxCallInput> {
+    let tx_data_bytes = tx_data.serialize_to_vec();
+    let tx_data_len = tx_data_bytes.len() as u64;
+    let tx_data_ptr = wasm_alloc(instance, store, tx_data_len)?;
+
+    write_memory_bytes(store, memory, tx_data_ptr, &tx_data_bytes)?;
+
+    Ok(TxCallInput {
+        tx_data_ptr,
+        tx_data_len,
+    })
+}
+
+/// Write validity predicate inputs into wasm memory
+pub fn write_vp_inputs(
+    instance: &Instance,
+    store: &mut Store<StoreData>,
+    memory: Memory,
+    VpInput {
+        addr,
+        data,
+        keys_changed,
+        verifiers,
+    }: VpInput<'_>,
+) -> Result<VpCallInput> {
+    let addr_bytes = addr.serialize_to_vec();
+    let addr_len = addr_bytes.len() as _;
+
+    let data_bytes = data.serialize_to_vec();
+    let data_len = data_bytes.len() as _;
+
+    let keys_changed_bytes = keys_changed.serialize_to_vec();
+    let keys_changed_len = keys_changed_bytes.len() as _;
+
+    let verifiers_bytes = verifiers.serialize_to_vec();
+    let verifiers_len = verifiers_bytes.len() as _;
+
+    let bytes = [
+        &addr_bytes[..],
+        &data_bytes[..],
+        &keys_changed_bytes[..],
+        &verifiers_bytes[..],
+    ]
+    .concat();
+    let bytes_len = bytes.len() as u64;
+
+    let addr_ptr = wasm_alloc(instance, store, bytes_len)?;
+    let data_ptr = checked!(addr_ptr + addr_len)?;
+    let keys_changed_ptr = checked!(data_ptr + data_len)?;
+    let verifiers_ptr = checked!(keys_changed_ptr + keys_changed_len)?;
+
+    write_memory_bytes(store, memory, addr_ptr, bytes)?;
+
+    Ok(VpCallInput {
+        addr_ptr,
+        addr_len,
+        data_ptr,
+        data_len,
+        keys_changed_ptr,
+        keys_changed_len,
+        verifiers_ptr,
+        verifiers_len,
+    })
+}
+
+/// Allocated inside the wasm instance using the injected function and return a
+/// ptr to it
+fn wasm_alloc(
+    instance: &Instance,
+    store: &mut Store<StoreData>,
+    len: u64,
+) -> Result<u64> {
+    let alloc_fn = instance
+        .get_typed_func::<i32, i32>(&mut *store, ALLOC_FN_NAME)
+        .map_err(Error::GuestAlloc)?;
+
+    let len: i32 = len.try_into().map_err(Error::TryFromInt)?;
+    let ptr = alloc_fn.call(&mut *store, len).map_err(Error::GuestAlloc)?;
+    let ptr: u64 = ptr.try_into().map_err(Error::TryFromInt)?;
+    Ok(ptr)
+}
+
+/// Check that the given offset and length fits into the memory bounds. If not,
+/// it will try to grow the memory when `grow` is set.
+fn check_bounds(
+    store: &mut impl AsContextMut,
+    memory: Memory,
+    base_addr: u64,
+    offset: usize,
+    grow: bool,
+) -> Result<()> {
+    let data_size = u64::try_from(memory.data_size(&*store))?;
+
+    tracing::debug!(
+        "check_bounds pages {}, data_size {data_size}, base_addr {base_addr}, \
+         offset {offset}",
+        memory.size(&*store),
+    );
+    let desired_offset = base_addr
+        .checked_add(offset as u64)
+        .and_then(|off| {
+            if off < u64::from(u32::MAX) {
+                // wasm pointers are 32 bits wide, therefore we can't
+                // read from/write to offsets past `u32::MAX`
+                Some(off)
+            } else {
+                None
+            }
+        })
+        .ok_or(Error::OverflowingOffset(base_addr, offset))?;
+    if data_size < desired_offset {
+        if !grow {
+            return Err(Error::ReadOnly);
+        }
+
+        let capacity = checked!(memory.size(&*store) * WASM_PAGE_SIZE)?;
+        let missing = checked!(desired_offset - capacity)?;
+
+        // extrapolate the number of pages missing to allow addressing
+        // the desired memory offset
+        let req_pages =
+            checked!((missing + WASM_PAGE_SIZE - 1) / WASM_PAGE_SIZE)?;
+
+        tracing::debug!(req_pages, "Attempting to grow wasm memory");
+
+        memory.grow(&mut *store, req_pages).map_err(Error::Grow)?;
+        tracing::debug!(
+            mem_size = memory.data_size(&*store),
+            "Wasm memory size has been successfully extended"
+        );
+    }
+    Ok(())
+}
+
+/// Read bytes from memory at the given offset and length
+fn read_memory_bytes(
+    store: &mut impl AsContextMut,
+    memory: Memory,
+    offset: u64,
+    len: usize,
+) -> Result<Vec<u8>> {
+    check_bounds(store, memory, offset, len, false)?;
+    let mut buf = vec![0; len];
+    memory.read(&*store, usize::try_from(offset)?, &mut buf)?;
+    Ok(buf)
+}
+
+/// Write bytes into memory at the given offset
+fn write_memory_bytes(
+    store: &mut impl AsContextMut,
+    memory: Memory,
+    offset: u64,
+    bytes: impl AsRef<[u8]>,
+) -> Result<()> {
+    let buf = bytes.as_ref();
+    check_bounds(store, memory, offset, buf.len(), true)?;
+    memory.write(&mut *store, usize::try_from(offset)?, buf)?;
+    Ok(())
+}
+
+/// The wasmtime memory, accessed through the [`Caller`] of a host function.
+#[derive(Debug, Clone, Copy)]
+pub struct WasmtimeMemory {
+    caller: Option<NonNull<Caller<'static, StoreData>>>,
+}
+
+// The memory is only ever accessed from the thread running the wasm that
+// called into the host, for the duration of that host function call.
+unsafe impl Send for WasmtimeMemory {}
+unsafe impl Sync for WasmtimeMemory {}
+
+impl WasmtimeMemory {
+    /// Build a memory that is not yet accessible, to be replaced with
+    /// [`WasmtimeMemory::from_caller`] once the guest calls into the host.
+    pub const fn uninit() -> Self {
+        Self { caller: None }
+    }
+
+    /// Access the guest's memory through the caller of a host function.
+    ///
+    /// # Safety
+    ///
+    /// The returned memory must not be used after the host function call
+    /// that provided the `caller` returns.
+    pub unsafe fn from_caller(caller: &mut Caller<'_, StoreData>) -> Self {
+        let caller = NonNull::from(caller).cast();
+        Self {
+            caller: Some(caller),
+        }
+    }
+
+    /// Access the inner [`Memory`] and its store.
+    #[inline]
+    fn access<F, T>(&self, f: F) -> Result<T>
+    where
+        F: FnOnce(Memory, &mut Caller<'static, StoreData>) -> Result<T>,
+    {
+        let mut caller = self.caller.ok_or(Error::UninitializedMemory)?;
+        // SAFETY: The caller outlives the host function call that built this
+        // memory, and it is not otherwise accessed while the host function
+        // runs
+        let caller = unsafe { caller.as_mut() };
+        let memory = caller.data().memory.ok_or(Error::UninitializedMemory)?;
+        f(memory, caller)
+    }
+}
+
+impl VmMemory for WasmtimeMemory {
+    type Error = Error;
+
+    /// Read bytes from memory at the given offset and length, return the bytes
+    /// and the gas cost
+    fn read_bytes(
+        &mut self,
+        offset: u64,
+        len: usize,
+    ) -> Result<(Vec<u8>, Gas)> {
+        self.access(|memory, caller| {
+            let bytes = read_memory_bytes(caller, memory, offset, len)?;
+            let len = bytes.len() as u64;
+            let gas = checked!(len * MEMORY_ACCESS_GAS_PER_BYTE)?;
+            Ok((bytes, gas.into()))
+        })
+    }
+
+    /// Write bytes into memory at the given offset and return the gas cost
+    fn write_bytes(
+        &mut self,
+        offset: u64,
+        bytes: impl AsRef<[u8]>,
+    ) -> Result<Gas> {
+        self.access(|memory, caller| {
+            // No need for a separate gas multiplier for writes since we are
+            // only writing to memory and we already charge gas for
+            // every memory page allocated
+            let len = bytes.as_ref().len() as u64;
+            let gas = checked!(len * MEMORY_ACCESS_GAS_PER_BYTE)?;
+            write_memory_bytes(caller, memory, offset, bytes)?;
+            Ok(gas.into())
+        })
+    }
+
+    /// Read string from memory at the given offset and bytes length, and return
+    /// the gas cost
+    fn read_string(
+        &mut self,
+        offset: u64,
+        len: usize,
+    ) -> Result<(String, Gas)> {
+        let (bytes, gas) = self.read_bytes(offset, len)?;
+        let string = std::str::from_utf8(&bytes)
+            .map_err(Error::InvalidUtf8String)?
+            .to_string();
+        Ok((string, gas))
+    }
+
+    /// Write string into memory at the given offset and return the gas cost
+    fn write_string(&mut self, offset: u64, string: String) -> Result<Gas> {
+        self.write_bytes(offset, string.as_bytes())
+    }
+}
+
+/// Get the guest's main memory of an instance and store it in the host data.
+pub fn init_guest_memory(
+    instance: &Instance,
+    store: &mut Store<StoreData>,
+) -> Option<Memory> {
+    let memory = instance.get_memory(&mut *store, GUEST_MEMORY)?;
+    store.data_mut().init_memory(memory);
+    Some(memory)
+}
@@ -0,0 +1,15 @@
+//! Wasm execution backend built on wasmtime.
+//!
+//! With the `wasmtime-runtime` feature, txs and VPs are executed with
+//! wasmtime instead of wasmer. Gas is metered exactly like with wasmer, with
+//! a host function injected into the wasm code, while the engine's fuel only
+//! bounds the execution.
+//!
+//! With the `wasm-differential` feature, every tx and VP is executed on both
+//! engines, asserting that they produce identical outcomes.
+
+#[cfg(feature = "wasm-differential")]
+pub mod differential;
+pub mod host_env;
+pub mod memory;
+pub mod run;
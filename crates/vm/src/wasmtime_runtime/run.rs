@@ -0,0 +1,912 @@
+//! Wasmtime runners
+
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+use std::marker::PhantomData;
+use std::num::NonZeroUsize;
+use std::sync::{LazyLock, Mutex};
+
+use clru::CLruCache;
+use namada_core::address::Address;
+use namada_core::hash::Hash;
+use namada_core::internal::HostEnvResult;
+use namada_core::storage::{Key, TxIndex};
+use namada_gas::{GasMeterKind, GasMetering, TxGasMeter, VpGasMeter};
+use namada_state::prefix_iter::PrefixIterators;
+use namada_state::{DB, DBIter, State, StateRead, StorageHasher, StorageRead};
+use namada_tx::data::TxSentinel;
+use namada_tx::{BatchedTxRef, Commitment, Tx, TxCommitments};
+use namada_vp::vp_host_fns;
+use wasmtime::{Config, Engine, Linker, Module, Store};
+
+use super::host_env::{tx_imports, vp_imports};
+use super::memory::{self, StoreData, WasmtimeMemory};
+use crate::host_env::gas_meter::GasMeter;
+use crate::host_env::{TxVmEnv, VpCtx, VpEvaluator, VpVmEnv};
+use crate::types::VpInput;
+use crate::wasm::memory::{
+    TX_MEMORY_MAX_PAGES, TxCallInput, VP_MEMORY_MAX_PAGES, VpCallInput,
+};
+use crate::wasm::run::{
+    Error, GUEST_MEMORY, Result, TX_ENTRYPOINT, VP_ENTRYPOINT, VpEvalWasm,
+    downcast_vp_host_fn_error, extract_tx_error, extract_vp_error,
+    prepare_wasm_code, tx_code_section,
+};
+use crate::wasm::{TxCache, VpCache};
+use crate::{RoHostRef, WasmCacheAccess, validate_untrusted_wasm};
+
+/// The amount of fuel granted to the guest per unit of available gas.
+///
+/// Gas is metered by the host function injected into the wasm code, like with
+/// wasmer. Fuel only bounds the execution in the engine itself, and is set
+/// high enough for gas to always run out first. This assumes that every wasm
+/// instruction is charged at least one unit of gas and that the work done in
+/// host functions is only charged in gas, without consuming any fuel.
+const FUEL_PER_GAS: u64 = 16;
+
+/// The prefix of the gas error of a guest that ran out of fuel
+pub(crate) const OUT_OF_FUEL: &str = "Out of fuel";
+
+/// The number of compiled modules kept in memory
+const MODULE_CACHE_SIZE: usize = 512;
+
+/// The engine shared by all wasmtime executions
+static ENGINE: LazyLock<Engine> = LazyLock::new(|| {
+    let mut config = Config::new();
+    config.consume_fuel(true);
+    // Floating point NaNs must be deterministic across hosts
+    config.cranelift_nan_canonicalization(true);
+    Engine::new(&config).expect("The wasmtime engine config must be valid")
+});
+
+/// Compiled modules, keyed by the hash of their wasm code
+static MODULE_CACHE: LazyLock<Mutex<CLruCache<Hash, Module>>> =
+    LazyLock::new(|| {
+        Mutex::new(CLruCache::new(
+            NonZeroUsize::new(MODULE_CACHE_SIZE)
+                .expect("The module cache size must be non-zero"),
+        ))
+    });
+
+/// Execute a transaction code with wasmtime. Returns the set verifiers
+/// addresses requested by the transaction.
+#[allow(clippy::too_many_arguments)]
+pub fn tx<S, CA>(
+    state: &mut S,
+    gas_meter: &RefCell<TxGasMeter>,
+    wrapper_hash: Option<&Hash>,
+    tx_index: &TxIndex,
+    tx: &Tx,
+    cmt: &TxCommitments,
+    vp_wasm_cache: &mut VpCache<CA>,
+    tx_wasm_cache: &mut TxCache<CA>,
+    dry_run: bool,
+) -> Result<BTreeSet<Address>>
+where
+    S: StateRead + State + StorageRead,
+    CA: 'static + WasmCacheAccess,
+{
+    let tx_code = tx_code_section(state, tx, cmt)?;
+    let batched_tx = tx.batch_ref_tx(cmt);
+
+    let module = fetch_or_compile(&tx_code.code, state, gas_meter)?;
+    memory::check_memory_limit(&module, TX_MEMORY_MAX_PAGES)
+        .map_err(Error::WasmtimeMemoryError)?;
+
+    let mut iterators: PrefixIterators<'_, <S as StateRead>::D> =
+        PrefixIterators::default();
+    let mut verifiers = BTreeSet::new();
+    let mut result_buffer: Option<Vec<u8>> = None;
+    let mut yielded_value: Option<Vec<u8>> = None;
+
+    let sentinel = RefCell::new(TxSentinel::default());
+    let (write_log, in_mem, db) = state.split_borrow();
+    const ZERO_HASH: Hash = Hash::zero();
+    let wrapper_hash = wrapper_hash.unwrap_or(&ZERO_HASH);
+
+    // NB: the gas meter is taken from the caller while the wasm runs, and
+    // restored afterwards
+    let wasm_gas_meter =
+        RefCell::new(GasMeter::Native(unsafe { TxGasMeter::placeholder() }));
+
+    let env = TxVmEnv::new(
+        WasmtimeMemory::uninit(),
+        dry_run,
+        write_log,
+        in_mem,
+        db,
+        &mut iterators,
+        &wasm_gas_meter,
+        &sentinel,
+        wrapper_hash,
+        tx,
+        cmt,
+        tx_index,
+        &mut verifiers,
+        &mut result_buffer,
+        &mut yielded_value,
+        vp_wasm_cache,
+        tx_wasm_cache,
+    );
+
+    // Instantiate the wasm module
+    let mut store = memory::new_store(&ENGINE, TX_MEMORY_MAX_PAGES);
+    let mut linker = Linker::new(&ENGINE);
+    tx_imports(&mut linker, env).map_err(Error::WasmtimeInstantiationError)?;
+    let instance = linker
+        .instantiate(&mut store, &module)
+        .map_err(Error::WasmtimeInstantiationError)?;
+
+    // Fetch guest's main memory
+    let guest_memory = memory::init_guest_memory(&instance, &mut store)
+        .ok_or(Error::WasmtimeMissingExport(GUEST_MEMORY))?;
+
+    *wasm_gas_meter.borrow_mut() = GasMeter::Native(
+        gas_meter.replace_with(|_| unsafe { TxGasMeter::placeholder() }),
+    );
+
+    let result = (|| {
+        set_fuel(&mut store, &*wasm_gas_meter.borrow())?;
+
+        let TxCallInput {
+            tx_data_ptr,
+            tx_data_len,
+        } = memory::write_tx_inputs(
+            &instance,
+            &mut store,
+            guest_memory,
+            &batched_tx,
+        )
+        .map_err(Error::WasmtimeMemoryError)?;
+
+        // Get the module's entrypoint to be called
+        let apply_tx = instance
+            .get_typed_func::<(u64, u64), u64>(&mut store, TX_ENTRYPOINT)
+            .map_err(|error| Error::WasmtimeModuleEntrypointInterface {
+                entrypoint: TX_ENTRYPOINT,
+                error,
+            })?;
+
+        apply_tx
+            .call(&mut store, (tx_data_ptr, tx_data_len))
+            .map_err(|err| {
+                tracing::debug!("Tx WASM failed with {}", err);
+
+                extract_tx_error(
+                    &mut yielded_value,
+                    &sentinel,
+                    Some(runtime_error(err)),
+                )
+            })
+    })();
+
+    wasm_gas_meter
+        .replace(GasMeter::Native(unsafe { TxGasMeter::placeholder() }))
+        .flush_to_meter(&mut *gas_meter.borrow_mut())
+        .map_err(|err| Error::GasError(err.to_string()))?;
+
+    // NB: early drop this data to avoid memory errors
+    _ = (linker, store);
+
+    if result? == 1 {
+        Ok(verifiers)
+    } else {
+        Err(extract_tx_error(&mut yielded_value, &sentinel, None))
+    }
+}
+
+/// Execute a validity predicate code with wasmtime. Returns whether the
+/// validity predicate accepted storage modifications performed by the
+/// transaction that triggered the execution.
+#[allow(clippy::too_many_arguments)]
+pub fn vp<S, CA>(
+    vp_code_hash: Hash,
+    batched_tx: &BatchedTxRef<'_>,
+    tx_index: &TxIndex,
+    address: &Address,
+    state: &S,
+    gas_meter: &RefCell<VpGasMeter>,
+    keys_changed: &BTreeSet<Key>,
+    verifiers: &BTreeSet<Address>,
+    mut vp_wasm_cache: VpCache<CA>,
+    dry_run: bool,
+) -> Result<()>
+where
+    S: StateRead,
+    CA: 'static + WasmCacheAccess,
+{
+    let module =
+        fetch_or_compile(&Commitment::Hash(vp_code_hash), state, gas_meter)?;
+
+    let mut iterators: PrefixIterators<'_, <S as StateRead>::D> =
+        PrefixIterators::default();
+    let mut result_buffer: Option<Vec<u8>> = None;
+    let mut yielded_value: Option<Vec<u8>> = None;
+    let eval_runner =
+        VpEvalWasmtime::<<S as StateRead>::D, <S as StateRead>::H, CA> {
+            db: PhantomData,
+            hasher: PhantomData,
+            cache_access: PhantomData,
+        };
+    let BatchedTxRef { tx, cmt } = batched_tx;
+
+    let wasm_gas_meter =
+        RefCell::new(GasMeter::Native(unsafe { VpGasMeter::placeholder() }));
+
+    let env = VpVmEnv::new(
+        WasmtimeMemory::uninit(),
+        dry_run,
+        address,
+        state.write_log(),
+        state.in_mem(),
+        state.db(),
+        &wasm_gas_meter,
+        tx,
+        cmt,
+        tx_index,
+        &mut iterators,
+        verifiers,
+        &mut result_buffer,
+        &mut yielded_value,
+        keys_changed,
+        &eval_runner,
+        &mut vp_wasm_cache,
+    );
+
+    run_vp(
+        module,
+        env,
+        &vp_code_hash,
+        batched_tx,
+        address,
+        keys_changed,
+        verifiers,
+        || {
+            *wasm_gas_meter.borrow_mut() = GasMeter::Native(
+                gas_meter
+                    .replace_with(|_| unsafe { VpGasMeter::placeholder() }),
+            );
+        },
+        || {
+            wasm_gas_meter
+                .replace_with(|_| unsafe { GasMeter::vp_placeholder() })
+                .flush_to_meter(&mut *gas_meter.borrow_mut())
+                .map_err(|err| Error::GasError(err.to_string()))
+        },
+    )
+}
+
+/// Evaluate a VP with wasmtime from a native VP.
+pub fn eval_native<S, CA>(
+    native_ctx: &namada_vp::native_vp::Ctx<
+        '_,
+        S,
+        VpCache<CA>,
+        VpEvalWasm<<S as StateRead>::D, <S as StateRead>::H, CA>,
+    >,
+    vp_code_hash: Hash,
+    input_data: BatchedTxRef<'_>,
+) -> namada_state::Result<()>
+where
+    S: 'static + StateRead,
+    CA: WasmCacheAccess,
+{
+    use namada_state::ResultExt;
+
+    let eval_runner =
+        VpEvalWasmtime::<<S as StateRead>::D, <S as StateRead>::H, CA> {
+            db: PhantomData,
+            hasher: PhantomData,
+            cache_access: PhantomData,
+        };
+    let mut iterators: PrefixIterators<'_, <S as StateRead>::D> =
+        PrefixIterators::default();
+    let mut result_buffer: Option<Vec<u8>> = None;
+    let mut yielded_value: Option<Vec<u8>> = None;
+    let mut vp_wasm_cache = native_ctx.vp_wasm_cache.clone();
+
+    let wasm_gas_meter = RefCell::new(GasMeter::Native(
+        native_ctx
+            .gas_meter
+            .replace_with(|_| unsafe { VpGasMeter::placeholder() }),
+    ));
+
+    let ctx = VpCtx::new(
+        native_ctx.address,
+        native_ctx.state.write_log(),
+        native_ctx.state.in_mem(),
+        native_ctx.state.db(),
+        &wasm_gas_meter,
+        native_ctx.tx,
+        native_ctx.cmt,
+        native_ctx.tx_index,
+        &mut iterators,
+        native_ctx.verifiers,
+        &mut result_buffer,
+        &mut yielded_value,
+        native_ctx.keys_changed,
+        &eval_runner,
+        &mut vp_wasm_cache,
+    );
+
+    let result = eval_runner.eval_native_result(
+        ctx,
+        vp_code_hash,
+        input_data,
+        || {},
+        || Ok(()),
+    );
+
+    // NB: the gas meter is restored even if the VP couldn't be compiled
+    wasm_gas_meter
+        .into_inner()
+        .flush_to_meter(&mut *native_ctx.gas_meter.borrow_mut())
+        .into_storage_result()?;
+
+    result
+        .inspect_err(|err| {
+            tracing::warn!("VP eval from a native VP failed with: {err}");
+        })
+        .into_storage_result()
+}
+
+#[allow(clippy::too_many_arguments)]
+fn run_vp<D, H, EVAL, CA, Init, Fini>(
+    module: Module,
+    env: VpVmEnv<WasmtimeMemory, D, H, EVAL, CA>,
+    vp_code_hash: &Hash,
+    input_data: &BatchedTxRef<'_>,
+    address: &Address,
+    keys_changed: &BTreeSet<Key>,
+    verifiers: &BTreeSet<Address>,
+    init_gas_meter: Init,
+    fini_gas_meter: Fini,
+) -> Result<()>
+where
+    D: DB + for<'iter> DBIter<'iter> + 'static,
+    H: StorageHasher + 'static,
+    EVAL: VpEvaluator<Db = D, H = H, Eval = EVAL, CA = CA> + 'static,
+    CA: WasmCacheAccess + 'static,
+    Init: FnOnce(),
+    Fini: FnOnce() -> Result<()>,
+{
+    let input: VpInput<'_> = VpInput {
+        addr: address,
+        data: input_data,
+        keys_changed,
+        verifiers,
+    };
+
+    memory::check_memory_limit(&module, VP_MEMORY_MAX_PAGES)
+        .map_err(Error::WasmtimeMemoryError)?;
+
+    let yielded_value = env.ctx.yielded_value;
+    let gas_meter = env.ctx.gas_meter;
+
+    // Instantiate the wasm module
+    let mut store = memory::new_store(&ENGINE, VP_MEMORY_MAX_PAGES);
+    let mut linker = Linker::new(&ENGINE);
+    vp_imports(&mut linker, env).map_err(Error::WasmtimeInstantiationError)?;
+    let instance = linker
+        .instantiate(&mut store, &module)
+        .map_err(Error::WasmtimeInstantiationError)?;
+
+    // Store ref to guest memory in host data structure
+    let guest_memory = memory::init_guest_memory(&instance, &mut store)
+        .ok_or(Error::WasmtimeMissingExport(GUEST_MEMORY))?;
+
+    init_gas_meter();
+
+    let result = (|| {
+        set_fuel(&mut store, &*unsafe { gas_meter.get() }.borrow())?;
+
+        // Write the inputs in the memory exported from the wasm
+        // module
+        let VpCallInput {
+            addr_ptr,
+            addr_len,
+            data_ptr,
+            data_len,
+            keys_changed_ptr,
+            keys_changed_len,
+            verifiers_ptr,
+            verifiers_len,
+        } = memory::write_vp_inputs(&instance, &mut store, guest_memory, input)
+            .map_err(Error::WasmtimeMemoryError)?;
+
+        // Get the module's entrypoint to be called
+        let validate_tx = instance
+            .get_typed_func::<(u64, u64, u64, u64, u64, u64, u64, u64), u64>(
+                &mut store,
+                VP_ENTRYPOINT,
+            )
+            .map_err(|error| Error::WasmtimeModuleEntrypointInterface {
+                entrypoint: VP_ENTRYPOINT,
+                error,
+            })?;
+
+        validate_tx
+            .call(
+                &mut store,
+                (
+                    addr_ptr,
+                    addr_len,
+                    data_ptr,
+                    data_len,
+                    keys_changed_ptr,
+                    keys_changed_len,
+                    verifiers_ptr,
+                    verifiers_len,
+                ),
+            )
+            .map_err(|rt_error| {
+                rt_error
+                    .downcast_ref::<vp_host_fns::Error>()
+                    .and_then(|vp_err| {
+                        downcast_vp_host_fn_error(vp_err, &rt_error)
+                    })
+                    .unwrap_or_else(|| runtime_error(rt_error))
+            })
+    })();
+
+    fini_gas_meter()?;
+
+    let is_valid = result?;
+
+    tracing::debug!(
+        is_valid,
+        %vp_code_hash,
+        "wasmtime vp"
+    );
+
+    // NB: early drop this data to avoid memory errors
+    _ = (linker, store);
+
+    if is_valid == 1 {
+        Ok(())
+    } else {
+        Err(extract_vp_error(unsafe { yielded_value.get_mut() }))
+    }
+}
+
+/// Validity predicate wasmtime evaluator for `eval` host function calls.
+#[derive(Debug)]
+pub struct VpEvalWasmtime<D, H, CA>
+where
+    D: DB + for<'iter> DBIter<'iter> + 'static,
+    H: StorageHasher + 'static,
+    CA: WasmCacheAccess + 'static,
+{
+    /// Phantom type for DB
+    pub db: PhantomData<*const D>,
+    /// Phantom type for hasher
+    pub hasher: PhantomData<*const H>,
+    /// Phantom type for WASM compilation cache access
+    pub cache_access: PhantomData<*const CA>,
+}
+
+impl<D, H, CA> VpEvaluator for VpEvalWasmtime<D, H, CA>
+where
+    D: DB + for<'iter> DBIter<'iter> + 'static,
+    H: StorageHasher + 'static,
+    CA: WasmCacheAccess + 'static,
+{
+    type CA = CA;
+    type Db = D;
+    type Eval = Self;
+    type H = H;
+
+    fn eval(
+        &self,
+        ctx: VpCtx<D, H, Self, CA>,
+        vp_code_hash: Hash,
+        input_data: BatchedTxRef<'_>,
+    ) -> HostEnvResult {
+        let mut new_ctx = ctx.clone();
+
+        let wasm_gas_meter =
+            RefCell::new(GasMeter::Native(VpGasMeter::new_from_meter(
+                &*unsafe { ctx.gas_meter.get() }.borrow(),
+            )));
+
+        new_ctx.gas_meter = unsafe { RoHostRef::new(&wasm_gas_meter) };
+
+        self.eval_native_result(
+            new_ctx,
+            vp_code_hash,
+            input_data,
+            || {},
+            || {
+                let wasm_gas_meter = wasm_gas_meter
+                    .replace_with(|_| unsafe { GasMeter::vp_placeholder() });
+
+                unsafe { ctx.gas_meter.get() }
+                    .borrow_mut()
+                    .consume(
+                        wasm_gas_meter.native().unwrap().get_vp_consumed_gas(),
+                    )
+                    .map_err(|err| Error::GasError(err.to_string()))
+            },
+        )
+        .map_or_else(
+            |err| {
+                tracing::info!("VP eval error {err}");
+                HostEnvResult::Fail
+            },
+            |()| HostEnvResult::Success,
+        )
+    }
+}
+
+impl<D, H, CA> VpEvalWasmtime<D, H, CA>
+where
+    D: DB + for<'iter> DBIter<'iter> + 'static,
+    H: StorageHasher + 'static,
+    CA: WasmCacheAccess + 'static,
+{
+    /// Evaluate the given VP.
+    pub fn eval_native_result<Init, Fini>(
+        &self,
+        ctx: VpCtx<D, H, Self, CA>,
+        vp_code_hash: Hash,
+        input_data: BatchedTxRef<'_>,
+        init_gas_meter: Init,
+        fini_gas_meter: Fini,
+    ) -> Result<()>
+    where
+        Init: FnOnce(),
+        Fini: FnOnce() -> Result<()>,
+    {
+        let address = unsafe { ctx.address.get() };
+        let keys_changed = unsafe { ctx.keys_changed.get() };
+        let verifiers = unsafe { ctx.verifiers.get() };
+        let gas_meter = unsafe { ctx.gas_meter.get() };
+        // Compile the wasm module
+        let module = fetch_or_compile(
+            &Commitment::Hash(vp_code_hash),
+            &ctx.state(),
+            gas_meter,
+        )?;
+
+        let env = VpVmEnv {
+            memory: WasmtimeMemory::uninit(),
+            ctx,
+            dry_run: false,
+        };
+
+        run_vp(
+            module,
+            env,
+            &vp_code_hash,
+            &input_data,
+            address,
+            keys_changed,
+            verifiers,
+            init_gas_meter,
+            fini_gas_meter,
+        )
+    }
+}
+
+/// Set the fuel of the store from the gas still available to the guest.
+fn set_fuel(
+    store: &mut Store<StoreData>,
+    gas_meter: &impl GasMetering,
+) -> Result<()> {
+    let fuel =
+        u64::from(gas_meter.get_available_gas()).saturating_mul(FUEL_PER_GAS);
+    store.set_fuel(fuel).map_err(Error::WasmtimeRuntimeError)
+}
+
+/// Map a trap of the guest to an error.
+fn runtime_error(err: wasmtime::Error) -> Error {
+    match err.downcast_ref::<wasmtime::Trap>() {
+        Some(wasmtime::Trap::OutOfFuel) => {
+            Error::GasError(format!("{OUT_OF_FUEL}: {err}"))
+        }
+        _ => Error::WasmtimeRuntimeError(err),
+    }
+}
+
+// Fetch or compile a WASM code from the cache or storage. Account for the
+// loading and code compilation gas costs, exactly like the wasmer runner.
+fn fetch_or_compile<S>(
+    code_or_hash: &Commitment,
+    state: &S,
+    gas_meter: &RefCell<impl GasMetering>,
+) -> Result<Module>
+where
+    S: StateRead,
+{
+    match code_or_hash {
+        Commitment::Hash(code_hash) => {
+            let code_len_key = Key::wasm_code_len(code_hash);
+            let tx_len = state
+                .read::<u64>(&code_len_key)
+                .map_err(|e| {
+                    Error::LoadWasmCode(format!(
+                        "Read wasm code length failed: key {code_len_key}, \
+                         error {e}"
+                    ))
+                })?
+                .ok_or_else(|| {
+                    Error::LoadWasmCode(format!(
+                        "No wasm code length in storage: key {code_len_key}"
+                    ))
+                })?;
+
+            // Gas accounting in any case, even if the compiled module is in
+            // cache
+            gas_meter
+                .borrow_mut()
+                .add_wasm_load_from_storage_gas(tx_len)
+                .map_err(|e| Error::GasError(e.to_string()))?;
+            gas_meter
+                .borrow_mut()
+                .add_compiling_gas(tx_len)
+                .map_err(|e| Error::GasError(e.to_string()))?;
+
+            if let Some(module) = fetch(code_hash) {
+                return Ok(module);
+            }
+
+            let key = Key::wasm_code(code_hash);
+            let code = state
+                .read::<Vec<u8>>(&key)
+                .map_err(|e| {
+                    Error::LoadWasmCode(format!(
+                        "Read wasm code failed: key {key}, error {e}"
+                    ))
+                })?
+                .ok_or_else(|| {
+                    Error::LoadWasmCode(format!(
+                        "No wasm code in storage: key {key}"
+                    ))
+                })?;
+
+            compile(*code_hash, code)
+        }
+        Commitment::Id(code) => {
+            let tx_len = code.len() as u64;
+            gas_meter
+                .borrow_mut()
+                .add_wasm_validation_gas(tx_len)
+                .map_err(|e| Error::GasError(e.to_string()))?;
+            // Validation is only needed for governance proposals. The other
+            // transactions are subject to the allowlist and are guaranteed to
+            // not contain invalid opcodes.
+            validate_untrusted_wasm(code).map_err(Error::ValidationError)?;
+
+            gas_meter
+                .borrow_mut()
+                .add_compiling_gas(tx_len)
+                .map_err(|e| Error::GasError(e.to_string()))?;
+
+            let code_hash = code_or_hash.hash();
+            match fetch(&code_hash) {
+                Some(module) => Ok(module),
+                None => compile(code_hash, code),
+            }
+        }
+    }
+}
+
+/// Fetch a compiled module from the cache.
+fn fetch(code_hash: &Hash) -> Option<Module> {
+    MODULE_CACHE
+        .lock()
+        .expect("The module cache lock must not be poisoned")
+        .get(code_hash)
+        .cloned()
+}
+
+/// Instrument and compile the given wasm code, and add the module to the
+/// cache.
+fn compile(code_hash: Hash, code: impl AsRef<[u8]>) -> Result<Module> {
+    let code = prepare_wasm_code(code, GasMeterKind::HostFn)?;
+    let module =
+        Module::new(&ENGINE, code).map_err(Error::WasmtimeCompileError)?;
+    MODULE_CACHE
+        .lock()
+        .expect("The module cache lock must not be poisoned")
+        .put(code_hash, module.clone());
+    Ok(module)
+}
+
+#[cfg(test)]
+mod tests {
+    use namada_core::borsh::BorshSerializeExt;
+    use namada_state::testing::TestState;
+    use namada_test_utils::{TestWasms, tx_data};
+    use namada_tx::data::TxType;
+    use namada_tx::{Code, Data};
+    use test_log::test;
+
+    use super::*;
+    use crate::wasm;
+    use crate::wasm::run::wasmer_tx;
+
+    const TX_GAS_LIMIT: u64 = 10_000_000_000_000;
+    const OUT_OF_GAS_LIMIT: u64 = 10_000;
+    const GAS_SCALE: u64 = 1;
+
+    /// Execute the given tx code and data with wasmtime, or with wasmer when
+    /// `gas_meter_kind` is provided.
+    fn execute_tx(
+        state: &mut TestState,
+        gas_meter: &RefCell<TxGasMeter>,
+        tx_code: Vec<u8>,
+        tx_data: Vec<u8>,
+        gas_meter_kind: Option<GasMeterKind>,
+    ) -> Result<BTreeSet<Address>> {
+        let (mut vp_cache, _) =
+            wasm::compilation_cache::common::testing::vp_cache();
+        let (mut tx_cache, _) =
+            wasm::compilation_cache::common::testing::tx_cache();
+        let mut outer_tx = Tx::from_type(TxType::Raw);
+        outer_tx.set_code(Code::new(tx_code, None));
+        outer_tx.set_data(Data::new(tx_data));
+        let batched_tx = outer_tx.batch_ref_first_tx().unwrap();
+        match gas_meter_kind {
+            Some(gas_meter_kind) => wasmer_tx(
+                state,
+                gas_meter,
+                None,
+                &TxIndex::default(),
+                batched_tx.tx,
+                batched_tx.cmt,
+                &mut vp_cache,
+                &mut tx_cache,
+                gas_meter_kind,
+                false,
+            ),
+            None => tx(
+                state,
+                gas_meter,
+                None,
+                &TxIndex::default(),
+                batched_tx.tx,
+                batched_tx.cmt,
+                &mut vp_cache,
+                &mut tx_cache,
+                false,
+            ),
+        }
+    }
+
+    /// Test that a tx executed with wasmtime consumes the same gas and
+    /// performs the same writes as with wasmer.
+    #[test]
+    fn test_tx_same_as_wasmer() {
+        let tx_code = TestWasms::TxWriteStorageKey.read_bytes();
+        let key = Key::parse("key").unwrap();
+        let value = vec![6_u8; 1024];
+        let tx_data = tx_data::TxWriteData {
+            key: key.clone(),
+            value: value.clone(),
+        }
+        .serialize_to_vec();
+
+        let mut wasmer_state = TestState::default();
+        let wasmer_gas_meter =
+            RefCell::new(TxGasMeter::new(TX_GAS_LIMIT, GAS_SCALE));
+        execute_tx(
+            &mut wasmer_state,
+            &wasmer_gas_meter,
+            tx_code.clone(),
+            tx_data.clone(),
+            Some(GasMeterKind::HostFn),
+        )
+        .unwrap();
+
+        let mut state = TestState::default();
+        let gas_meter = RefCell::new(TxGasMeter::new(TX_GAS_LIMIT, GAS_SCALE));
+        execute_tx(&mut state, &gas_meter, tx_code, tx_data, None).unwrap();
+
+        assert_eq!(state.read_bytes(&key).unwrap().unwrap(), value);
+        assert_eq!(state.write_log(), wasmer_state.write_log());
+        assert_eq!(
+            gas_meter.borrow().get_consumed_gas(),
+            wasmer_gas_meter.borrow().get_consumed_gas()
+        );
+    }
+
+    /// Test that a tx which is larger than the initial WASM memory size gets
+    /// executed without issues (the injected alloc fn should be invoked from
+    /// host)
+    #[test]
+    fn test_tx_alloc() {
+        let mut state = TestState::default();
+        let gas_meter = RefCell::new(TxGasMeter::new(TX_GAS_LIMIT, GAS_SCALE));
+
+        // Using `2^21` (2 MiB) for the input should be above the initial memory
+        // size and require allocation
+        let key = Key::parse("key").unwrap();
+        let value: Vec<u8> = vec![6_u8; 2_usize.pow(21)];
+        let tx_data = tx_data::TxWriteData {
+            key: key.clone(),
+            value: value.clone(),
+        }
+        .serialize_to_vec();
+
+        execute_tx(
+            &mut state,
+            &gas_meter,
+            TestWasms::TxWriteStorageKey.read_bytes(),
+            tx_data,
+            None,
+        )
+        .unwrap();
+
+        let written_value = state.read_bytes(&key).unwrap().unwrap();
+        assert_eq!(value, written_value);
+    }
+
+    /// Test that the guest memory is limited like with wasmer.
+    #[test]
+    fn test_tx_memory_limiter_in_guest() {
+        let mut state = TestState::default();
+        let tx_code = TestWasms::TxMemoryLimit.read_bytes();
+
+        // Allocating `2^24` (16 MiB) should be below the memory limit and
+        // shouldn't fail
+        let gas_meter = RefCell::new(TxGasMeter::new(TX_GAS_LIMIT, GAS_SCALE));
+        let result = execute_tx(
+            &mut state,
+            &gas_meter,
+            tx_code.clone(),
+            2_usize.pow(24).serialize_to_vec(),
+            None,
+        );
+        assert!(result.is_ok(), "Expected success, got {:?}", result);
+
+        // Allocating `2^25` (32 MiB) should be above the memory limit and
+        // should fail
+        let gas_meter = RefCell::new(TxGasMeter::new(TX_GAS_LIMIT, GAS_SCALE));
+        let result = execute_tx(
+            &mut state,
+            &gas_meter,
+            tx_code,
+            2_usize.pow(25).serialize_to_vec(),
+            None,
+        );
+        assert!(result.is_err(), "Expected to run out of memory");
+    }
+
+    /// Test that when a tx runs out of gas in guest, the execution is aborted
+    #[test]
+    fn test_tx_out_of_gas_in_guest() {
+        let mut state = TestState::default();
+        let gas_meter =
+            RefCell::new(TxGasMeter::new(OUT_OF_GAS_LIMIT, GAS_SCALE));
+
+        let result = execute_tx(
+            &mut state,
+            &gas_meter,
+            TestWasms::TxInfiniteGuestGas.read_bytes(),
+            vec![],
+            None,
+        );
+
+        assert!(matches!(result.unwrap_err(), Error::GasError(_)));
+    }
+
+    /// Test that when a tx runs out of gas in host, the execution is aborted
+    /// from the host env (no cooperation required by the guest).
+    #[test]
+    fn test_tx_out_of_gas_in_host() {
+        let mut state = TestState::default();
+        let gas_meter =
+            RefCell::new(TxGasMeter::new(OUT_OF_GAS_LIMIT, GAS_SCALE));
+
+        let result = execute_tx(
+            &mut state,
+            &gas_meter,
+            TestWasms::TxInfiniteHostGas.read_bytes(),
+            vec![],
+            None,
+        );
+
+        assert!(matches!(result.unwrap_err(), Error::GasError(_)));
+    }
+}
@@ -78,6 +78,15 @@ pub mod tx {
         /// keys.
         pub fn namada_tx_iter_prefix(prefix_ptr: u64, prefix_len: u64) -> u64;
 
+        /// Get an ID of a data iterator with key prefix, restricted to the
+        /// borsh encoded key range.
+        pub fn namada_tx_iter_prefix_range(
+            prefix_ptr: u64,
+            prefix_len: u64,
+            range_ptr: u64,
+            range_len: u64,
+        ) -> u64;
+
         /// Returns the size of the value (can be 0), or -1 if there's no next
         /// value. If a value is found, it will be placed in the read
         /// cache, because we cannot allocate a buffer for it before we know
@@ -147,6 +156,15 @@ pub mod tx {
         /// Set the sentinel for a wrong tx section commitment
         pub fn namada_tx_set_commitment_sentinel();
 
+        /// Push a savepoint of the current tx's write log
+        pub fn namada_tx_push_savepoint();
+
+        /// Undo the changes since the innermost savepoint and remove it
+        pub fn namada_tx_rollback_to_savepoint();
+
+        /// Remove the innermost savepoint, keeping its changes
+        pub fn namada_tx_release_savepoint();
+
         /// Verify the signatures of a tx
         pub fn namada_tx_verify_tx_section_signature(
             hash_list_ptr: u64,
@@ -156,6 +174,37 @@ pub mod tx {
             threshold: u8,
         ) -> i64;
 
+        /// Compute the SHA-256 hash of the given data
+        pub fn namada_tx_hash_sha256(
+            data_ptr: u64,
+            data_len: u64,
+            result_ptr: u64,
+        );
+
+        /// Compute the Keccak-256 hash of the given data
+        pub fn namada_tx_hash_keccak256(
+            data_ptr: u64,
+            data_len: u64,
+            result_ptr: u64,
+        );
+
+        /// Compute the 256-bit Blake2b hash of the given data
+        pub fn namada_tx_hash_blake2b(
+            data_ptr: u64,
+            data_len: u64,
+            result_ptr: u64,
+        );
+
+        /// Verify a signature of an arbitrary message
+        pub fn namada_tx_verify_signature(
+            pk_ptr: u64,
+            pk_len: u64,
+            sig_ptr: u64,
+            sig_len: u64,
+            msg_ptr: u64,
+            msg_len: u64,
+        ) -> i64;
+
         /// Update the masp note commitment tree with the new notes
         pub fn namada_tx_update_masp_note_commitment_tree(
             transaction_ptr: u64,
@@ -216,6 +265,24 @@ pub mod vp {
             prefix_len: u64,
         ) -> u64;
 
+        /// Get an ID of a data iterator with key prefix in prior state,
+        /// restricted to the borsh encoded key range.
+        pub fn namada_vp_iter_prefix_range_pre(
+            prefix_ptr: u64,
+            prefix_len: u64,
+            range_ptr: u64,
+            range_len: u64,
+        ) -> u64;
+
+        /// Get an ID of a data iterator with key prefix in posterior state,
+        /// restricted to the borsh encoded key range.
+        pub fn namada_vp_iter_prefix_range_post(
+            prefix_ptr: u64,
+            prefix_len: u64,
+            range_ptr: u64,
+            range_len: u64,
+        ) -> u64;
+
         /// Read variable-length iterator's next value when we don't know the
         /// size up-front, returns the size of the value (can be 0), or
         /// -1 if the key is not present. If a value is found, it will be
@@ -270,6 +337,37 @@ pub mod vp {
             threshold: u8,
         );
 
+        /// Compute the SHA-256 hash of the given data
+        pub fn namada_vp_hash_sha256(
+            data_ptr: u64,
+            data_len: u64,
+            result_ptr: u64,
+        );
+
+        /// Compute the Keccak-256 hash of the given data
+        pub fn namada_vp_hash_keccak256(
+            data_ptr: u64,
+            data_len: u64,
+            result_ptr: u64,
+        );
+
+        /// Compute the 256-bit Blake2b hash of the given data
+        pub fn namada_vp_hash_blake2b(
+            data_ptr: u64,
+            data_len: u64,
+            result_ptr: u64,
+        );
+
+        /// Verify a signature of an arbitrary message
+        pub fn namada_vp_verify_signature(
+            pk_ptr: u64,
+            pk_len: u64,
+            sig_ptr: u64,
+            sig_len: u64,
+            msg_ptr: u64,
+            msg_len: u64,
+        ) -> i64;
+
         /// Evaluate a validity-predicate
         pub fn namada_vp_eval(
             vp_code_hash_ptr: u64,
@@ -17,8 +17,8 @@ use namada_tx::{BatchedTxRef, Tx, TxCommitments};
 use super::vp_host_fns;
 use crate::state::prefix_iter::PrefixIterators;
 use crate::state::{
-    BlockHeader, BlockHeight, Epoch, Key, PrefixIter, StateRead, StorageRead,
-    TxIndex,
+    BlockHeader, BlockHeight, Epoch, Key, KeyRange, PrefixIter, StateRead,
+    StorageRead, TxIndex,
 };
 pub use crate::state::{Error, Result, ResultExt};
 use crate::{Address, Event, EventType, Hash, VpEnv};
@@ -199,6 +199,21 @@ where
         .into_storage_result()
     }
 
+    fn iter_prefix_range<'iter>(
+        &'iter self,
+        prefix: &Key,
+        range: &KeyRange,
+    ) -> Result<Self::PrefixIter<'iter>> {
+        vp_host_fns::iter_prefix_range_pre(
+            self.ctx.gas_meter,
+            self.ctx.state.write_log(),
+            self.ctx.state.db(),
+            prefix,
+            range,
+        )
+        .into_storage_result()
+    }
+
     // ---- Methods below are implemented in `self.ctx`, because they are
     //      the same in `pre/post` ----
 
@@ -277,6 +292,21 @@ where
         .into_storage_result()
     }
 
+    fn iter_prefix_range<'iter>(
+        &'iter self,
+        prefix: &Key,
+        range: &KeyRange,
+    ) -> Result<Self::PrefixIter<'iter>> {
+        vp_host_fns::iter_prefix_range_post(
+            self.ctx.gas_meter,
+            self.ctx.state.write_log(),
+            self.ctx.state.db(),
+            prefix,
+            range,
+        )
+        .into_storage_result()
+    }
+
     // ---- Methods below are implemented in `self.ctx`, because they are
     //      the same in `pre/post` ----
 
@@ -14,7 +14,9 @@ use namada_tx::{BatchedTxRef, Section};
 use thiserror::Error;
 
 use crate::state::write_log::WriteLog;
-use crate::state::{DB, DBIter, PrefixIter, ResultExt, StateRead, write_log};
+use crate::state::{
+    DB, DBIter, KeyRange, PrefixIter, ResultExt, StateRead, write_log,
+};
 pub use crate::state::{Error, Result};
 
 /// These runtime errors will abort VP execution immediately
@@ -381,6 +383,50 @@ where
     Ok(iter)
 }
 
+/// Storage prefix iterator for prior state (before tx execution) over the keys
+/// within the given range, ordered by storage keys in the range's direction.
+/// It will try to get an iterator from the storage.
+pub fn iter_prefix_range_pre<'a, D>(
+    gas_meter: &RefCell<impl GasMetering>,
+    // We cannot use e.g. `&'a State`, because it doesn't live long
+    // enough - the lifetime of the `PrefixIter` must depend on the lifetime of
+    // references to the `WriteLog` and `DB`.
+    write_log: &'a WriteLog,
+    db: &'a D,
+    prefix: &Key,
+    range: &KeyRange,
+) -> Result<PrefixIter<'a, D>>
+where
+    D: DB + for<'iter> DBIter<'iter>,
+{
+    let (iter, gas) =
+        namada_state::iter_prefix_range_pre(write_log, db, prefix, range)?;
+    add_gas(gas_meter, gas)?;
+    Ok(iter)
+}
+
+/// Storage prefix iterator for posterior state (after tx execution) over the
+/// keys within the given range, ordered by storage keys in the range's
+/// direction. It will try to get an iterator from the storage.
+pub fn iter_prefix_range_post<'a, D>(
+    gas_meter: &RefCell<impl GasMetering>,
+    // We cannot use e.g. `&'a State`, because it doesn't live long
+    // enough - the lifetime of the `PrefixIter` must depend on the lifetime of
+    // references to the `WriteLog` and `DB`.
+    write_log: &'a WriteLog,
+    db: &'a D,
+    prefix: &Key,
+    range: &KeyRange,
+) -> Result<PrefixIter<'a, D>>
+where
+    D: DB + for<'iter> DBIter<'iter>,
+{
+    let (iter, gas) =
+        namada_state::iter_prefix_range_post(write_log, db, prefix, range)?;
+    add_gas(gas_meter, gas)?;
+    Ok(iter)
+}
+
 /// Get the next item in a storage prefix iterator (pre or post).
 pub fn iter_next<DB>(
     gas_meter: &RefCell<impl GasMetering>,
@@ -0,0 +1,205 @@
+//! LazyDeque validation helpers
+
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+
+use namada_core::borsh::{BorshDeserialize, BorshSerialize};
+use namada_core::storage;
+use namada_storage::ResultExt;
+use namada_storage::collections::lazy_deque::{
+    Bounds, Index, LazyDeque, SubKey, ValidationError,
+};
+
+use super::{Data, LazyCollectionExt, read_data};
+use crate::VpEnv;
+
+/// Possible sub-keys of a [`LazyDeque`], together with their [`Data`]
+/// that contains prior and posterior state.
+#[derive(Debug)]
+pub enum SubKeyWithData<T> {
+    /// Bounds sub-key
+    Bounds(Data<Bounds>),
+    /// Data sub-key, further sub-keyed by its storage index
+    Data(Index, Data<T>),
+}
+
+/// Possible actions that can modify a [`LazyDeque`]. This roughly corresponds
+/// to the methods that have `StorageWrite` access.
+#[derive(Clone, Debug)]
+pub enum Action<T> {
+    /// Push a value `T` to the front of a [`LazyDeque<T>`]
+    PushFront(T),
+    /// Push a value `T` to the back of a [`LazyDeque<T>`]
+    PushBack(T),
+    /// Pop a value `T` from the front of a [`LazyDeque<T>`]
+    PopFront(T),
+    /// Pop a value `T` from the back of a [`LazyDeque<T>`]
+    PopBack(T),
+    /// Update a value `T` at storage index from pre to post state in a
+    /// [`LazyDeque<T>`]
+    Update {
+        /// storage index at which the value is updated
+        index: Index,
+        /// value before the update
+        pre: T,
+        /// value after the update
+        post: T,
+    },
+}
+
+impl<T> LazyCollectionExt for LazyDeque<T>
+where
+    T: BorshSerialize + BorshDeserialize + 'static + Debug,
+{
+    type Action = Action<T>;
+    type SubKeyWithData = SubKeyWithData<T>;
+
+    fn read_sub_key_data<ENV>(
+        env: &ENV,
+        storage_key: &storage::Key,
+        sub_key: Self::SubKey,
+    ) -> namada_storage::Result<Option<Self::SubKeyWithData>>
+    where
+        ENV: for<'a> VpEnv<'a>,
+    {
+        let change = match sub_key {
+            SubKey::Bounds => {
+                let data = read_data(env, storage_key)?;
+                data.map(SubKeyWithData::Bounds)
+            }
+            SubKey::Data(index) => {
+                let data = read_data(env, storage_key)?;
+                data.map(|data| SubKeyWithData::Data(index, data))
+            }
+        };
+        Ok(change)
+    }
+
+    /// The validation rules for a [`LazyDeque`] are:
+    ///   - An empty deque must be deleted from storage
+    ///   - Elements can only be added at the storage indices that are within
+    ///     the posterior bounds, but not the prior bounds, and all of these
+    ///     indices must be added
+    ///   - Elements can only be deleted at the storage indices that are within
+    ///     the prior bounds, but not the posterior bounds, and all of these
+    ///     indices must be deleted
+    ///   - Elements can only be updated at the storage indices within both the
+    ///     prior and the posterior bounds
+    ///
+    /// The actions are returned in the order of the elements' storage indices.
+    fn validate_changed_sub_keys(
+        keys: Vec<Self::SubKeyWithData>,
+    ) -> namada_storage::Result<Vec<Self::Action>> {
+        // The prior and posterior bounds, if changed
+        let mut bounds: Option<(Bounds, Bounds)> = None;
+        let mut changes = BTreeMap::<Index, Data<T>>::new();
+
+        for key in keys {
+            match key {
+                SubKeyWithData::Bounds(data) => {
+                    let (pre, post) = match data {
+                        Data::Add { post } | Data::Update { post, .. }
+                            if post.is_empty() =>
+                        {
+                            return Err(
+                                ValidationError::EmptyDequeShouldBeDeleted,
+                            )
+                            .into_storage_result();
+                        }
+                        Data::Add { post } => (Bounds::EMPTY, post),
+                        Data::Update { pre, post } => (pre, post),
+                        Data::Delete { pre } => (pre, Bounds::EMPTY),
+                    };
+                    for bounds in [&pre, &post] {
+                        if bounds.front > bounds.end {
+                            return Err(ValidationError::InvalidBounds {
+                                front: bounds.front,
+                                end: bounds.end,
+                            })
+                            .into_storage_result();
+                        }
+                    }
+                    bounds = Some((pre, post));
+                }
+                SubKeyWithData::Data(index, data) => {
+                    changes.insert(index, data);
+                }
+            }
+        }
+
+        let mut actions = Vec::with_capacity(changes.len());
+        let mut added: u64 = 0;
+        let mut deleted: u64 = 0;
+        for (index, data) in changes {
+            match data {
+                Data::Add { post } => {
+                    let Some((pre_bounds, post_bounds)) = &bounds else {
+                        return Err(ValidationError::UnexpectedPushIndex(
+                            index,
+                        ))
+                        .into_storage_result();
+                    };
+                    if !post_bounds.contains(index)
+                        || pre_bounds.contains(index)
+                    {
+                        return Err(ValidationError::UnexpectedPushIndex(
+                            index,
+                        ))
+                        .into_storage_result();
+                    }
+                    added = added.saturating_add(1);
+                    if !pre_bounds.is_empty() && index < pre_bounds.front {
+                        actions.push(Action::PushFront(post));
+                    } else {
+                        actions.push(Action::PushBack(post));
+                    }
+                }
+                Data::Update { pre, post } => {
+                    if let Some((pre_bounds, post_bounds)) = &bounds {
+                        if !pre_bounds.contains(index)
+                            || !post_bounds.contains(index)
+                        {
+                            return Err(
+                                ValidationError::UnexpectedUpdateIndex(index),
+                            )
+                            .into_storage_result();
+                        }
+                    }
+                    actions.push(Action::Update { index, pre, post });
+                }
+                Data::Delete { pre } => {
+                    let Some((pre_bounds, post_bounds)) = &bounds else {
+                        return Err(ValidationError::UnexpectedPopIndex(index))
+                            .into_storage_result();
+                    };
+                    if !pre_bounds.contains(index)
+                        || post_bounds.contains(index)
+                    {
+                        return Err(ValidationError::UnexpectedPopIndex(index))
+                            .into_storage_result();
+                    }
+                    deleted = deleted.saturating_add(1);
+                    if !post_bounds.is_empty() && index >= post_bounds.end {
+                        actions.push(Action::PopBack(pre));
+                    } else {
+                        actions.push(Action::PopFront(pre));
+                    }
+                }
+            }
+        }
+
+        if let Some((pre_bounds, post_bounds)) = &bounds {
+            // All the indices that entered or left the bounds must have been
+            // added or deleted, respectively
+            let overlap = pre_bounds.overlap(post_bounds);
+            if added != post_bounds.len().saturating_sub(overlap)
+                || deleted != pre_bounds.len().saturating_sub(overlap)
+            {
+                return Err(ValidationError::InvalidBoundsDiff)
+                    .into_storage_result();
+            }
+        }
+
+        Ok(actions)
+    }
+}
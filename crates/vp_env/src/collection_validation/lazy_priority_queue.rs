@@ -0,0 +1,97 @@
+//! LazyPriorityQueue validation helpers
+
+use std::fmt::Debug;
+
+use namada_core::borsh::{BorshDeserialize, BorshSerialize};
+use namada_core::storage::{self, KeySeg};
+use namada_storage::collections::lazy_priority_queue::{
+    LazyPriorityQueue, Seq, SubKey,
+};
+
+use super::{Data, LazyCollectionExt, read_data};
+use crate::VpEnv;
+
+/// Possible sub-keys of a [`LazyPriorityQueue`], together with their [`Data`]
+/// that contains prior and posterior state.
+#[derive(Clone, Debug)]
+pub enum SubKeyWithData<K, T> {
+    /// Data sub-key, further sub-keyed by its priority and sequence number
+    Data {
+        /// The priority of the element
+        priority: K,
+        /// The sequence number of the element within its priority
+        seq: Seq,
+        /// The element's prior and posterior state
+        data: Data<T>,
+    },
+}
+
+/// Possible actions that can modify a [`LazyPriorityQueue`]. This roughly
+/// corresponds to the methods that have `StorageWrite` access.
+#[derive(Clone, Debug)]
+pub enum Action<K, T> {
+    /// Push a value `T` with priority `K` into a [`LazyPriorityQueue<K, T>`]
+    Push(K, T),
+    /// Pop a value `T` with priority `K` from a [`LazyPriorityQueue<K, T>`]
+    Pop(K, T),
+    /// Replace a value `T` with priority `K` in a [`LazyPriorityQueue<K, T>`].
+    /// This happens when the last element with some priority is popped and
+    /// another one with the same priority is pushed in the same transaction.
+    Update {
+        /// priority of the replaced value
+        priority: K,
+        /// value before the update
+        pre: T,
+        /// value after the update
+        post: T,
+    },
+}
+
+impl<K, T> LazyCollectionExt for LazyPriorityQueue<K, T>
+where
+    K: storage::KeySeg + Debug,
+    T: BorshSerialize + BorshDeserialize + 'static + Debug,
+{
+    type Action = Action<K, T>;
+    type SubKeyWithData = SubKeyWithData<K, T>;
+
+    fn read_sub_key_data<ENV>(
+        env: &ENV,
+        storage_key: &storage::Key,
+        sub_key: Self::SubKey,
+    ) -> namada_storage::Result<Option<Self::SubKeyWithData>>
+    where
+        ENV: for<'a> VpEnv<'a>,
+    {
+        let SubKey::Data { priority, seq } = sub_key;
+        let data = read_data(env, storage_key)?;
+        Ok(data.map(|data| SubKeyWithData::Data {
+            priority,
+            seq,
+            data,
+        }))
+    }
+
+    /// There are no additional validation rules for a [`LazyPriorityQueue`],
+    /// as any of its sub-keys can be validly added or deleted. The changes are
+    /// turned into actions in the order of the elements in the queue.
+    fn validate_changed_sub_keys(
+        mut keys: Vec<Self::SubKeyWithData>,
+    ) -> namada_storage::Result<Vec<Self::Action>> {
+        keys.sort_by_cached_key(
+            |SubKeyWithData::Data { priority, seq, .. }| (priority.raw(), *seq),
+        );
+        Ok(keys
+            .into_iter()
+            .map(|SubKeyWithData::Data { priority, data, .. }| match data {
+                Data::Add { post } => Action::Push(priority, post),
+                Data::Update { pre, post } => Action::Update {
+                    priority,
+                    pre,
+                    post,
+                },
+                Data::Delete { pre } => Action::Pop(priority, pre),
+            })
+            .collect())
+    }
+}
@@ -1,6 +1,8 @@
 //! Storage change validation helpers
 
+pub mod lazy_deque;
 pub mod lazy_map;
+pub mod lazy_priority_queue;
 pub mod lazy_set;
 pub mod lazy_vec;
 
@@ -26,6 +26,7 @@ use std::marker::PhantomData;
 use std::str::FromStr;
 
 use chain::ChainId;
+pub use namada_account as account;
 pub use namada_core::address::Address;
 pub use namada_core::borsh::{
     BorshDeserialize, BorshSerialize, BorshSerializeExt,
@@ -36,6 +37,7 @@ use namada_core::chain::{
 pub use namada_core::collections::HashSet;
 use namada_core::hash::{HASH_LENGTH, Hash};
 use namada_core::internal::HostEnvResult;
+use namada_core::key::common;
 use namada_core::storage::TxIndex;
 pub use namada_core::validity_predicate::{VpError, VpErrorExtResult};
 pub use namada_core::*;
@@ -44,20 +46,20 @@ use namada_gas::Gas;
 pub use namada_governance::pgf::storage as pgf_storage;
 pub use namada_governance::storage as gov_storage;
 pub use namada_macros::validity_predicate;
+pub use namada_parameters as parameters;
+pub use namada_proof_of_stake as proof_of_stake;
 pub use namada_storage::{
-    Error, OptionExt, ResultExt, StorageRead, iter_prefix, iter_prefix_bytes,
+    Error, KeyRange, OptionExt, ResultExt, StorageRead, iter_prefix,
+    iter_prefix_bytes, iter_prefix_range, iter_prefix_range_bytes,
 };
+pub use namada_token as token;
+pub use namada_tx as tx;
 pub use namada_tx::{BatchedTx, Section, Tx};
 use namada_vm_env::vp::*;
 use namada_vm_env::{read_from_buffer, read_key_val_bytes_from_buffer};
 pub use namada_vp_env::{VpEnv, collection_validation};
 pub use sha2::{Digest, Sha256, Sha384, Sha512};
 use tx::{BatchedTxRef, TxCommitments};
-pub use {
-    namada_account as account, namada_parameters as parameters,
-    namada_proof_of_stake as proof_of_stake, namada_token as token,
-    namada_tx as tx,
-};
 
 /// SHA-256 hash of given bytes
 pub fn sha256(bytes: &[u8]) -> Hash {
@@ -65,6 +67,68 @@ pub fn sha256(bytes: &[u8]) -> Hash {
     Hash(*digest.as_ref())
 }
 
+/// SHA-256 hash of the given bytes, computed and gas metered by the host
+pub fn hash_sha256(bytes: &[u8]) -> Hash {
+    let mut result = [0; 32];
+    unsafe {
+        namada_vp_hash_sha256(
+            bytes.as_ptr() as _,
+            bytes.len() as _,
+            result.as_mut_ptr() as _,
+        );
+    }
+    Hash(result)
+}
+
+/// Keccak-256 hash of the given bytes, computed and gas metered by the host
+pub fn hash_keccak256(bytes: &[u8]) -> keccak::KeccakHash {
+    let mut result = [0; 32];
+    unsafe {
+        namada_vp_hash_keccak256(
+            bytes.as_ptr() as _,
+            bytes.len() as _,
+            result.as_mut_ptr() as _,
+        );
+    }
+    keccak::KeccakHash(result)
+}
+
+/// 256-bit Blake2b hash of the given bytes, computed and gas metered by the
+/// host
+pub fn hash_blake2b(bytes: &[u8]) -> [u8; 32] {
+    let mut result = [0; 32];
+    unsafe {
+        namada_vp_hash_blake2b(
+            bytes.as_ptr() as _,
+            bytes.len() as _,
+            result.as_mut_ptr() as _,
+        );
+    }
+    result
+}
+
+/// Verify a signature over the raw bytes of an arbitrary message with the
+/// given public key
+pub fn verify_signature(
+    pk: &common::PublicKey,
+    sig: &common::Signature,
+    msg: &[u8],
+) -> bool {
+    let pk = pk.serialize_to_vec();
+    let sig = sig.serialize_to_vec();
+    let valid = unsafe {
+        namada_vp_verify_signature(
+            pk.as_ptr() as _,
+            pk.len() as _,
+            sig.as_ptr() as _,
+            sig.len() as _,
+            msg.as_ptr() as _,
+            msg.len() as _,
+        )
+    };
+    HostEnvResult::is_success(valid)
+}
+
 /// Log a string. The message will be printed at the `tracing::Level::Info`.
 pub fn log_string<T: AsRef<str>>(msg: T) {
     let msg = msg.as_ref();
@@ -454,6 +518,14 @@ impl StorageRead for CtxPreStorageRead<'_> {
         iter_prefix_pre_impl(prefix)
     }
 
+    fn iter_prefix_range<'iter>(
+        &'iter self,
+        prefix: &storage::Key,
+        range: &KeyRange,
+    ) -> Result<Self::PrefixIter<'iter>, Error> {
+        iter_prefix_range_pre_impl(prefix, range)
+    }
+
     // ---- Methods below share the same implementation in `pre/post` ----
 
     fn iter_next<'iter>(
@@ -527,6 +599,14 @@ impl StorageRead for CtxPostStorageRead<'_> {
         iter_prefix_post_impl(prefix)
     }
 
+    fn iter_prefix_range<'iter>(
+        &'iter self,
+        prefix: &storage::Key,
+        range: &KeyRange,
+    ) -> Result<Self::PrefixIter<'iter>, Error> {
+        iter_prefix_range_post_impl(prefix, range)
+    }
+
     // ---- Methods below share the same implementation in `pre/post` ----
 
     fn iter_next<'iter>(
@@ -582,6 +662,23 @@ fn iter_prefix_pre_impl(
     Ok(KeyValIterator(iter_id, PhantomData))
 }
 
+fn iter_prefix_range_pre_impl(
+    prefix: &storage::Key,
+    range: &KeyRange,
+) -> Result<KeyValIterator<(String, Vec<u8>)>, Error> {
+    let prefix = prefix.to_string();
+    let range = range.serialize_to_vec();
+    let iter_id = unsafe {
+        namada_vp_iter_prefix_range_pre(
+            prefix.as_ptr() as _,
+            prefix.len() as _,
+            range.as_ptr() as _,
+            range.len() as _,
+        )
+    };
+    Ok(KeyValIterator(iter_id, PhantomData))
+}
+
 fn iter_prefix_post_impl(
     prefix: &storage::Key,
 ) -> Result<KeyValIterator<(String, Vec<u8>)>, Error> {
@@ -592,6 +689,23 @@ fn iter_prefix_post_impl(
     Ok(KeyValIterator(iter_id, PhantomData))
 }
 
+fn iter_prefix_range_post_impl(
+    prefix: &storage::Key,
+    range: &KeyRange,
+) -> Result<KeyValIterator<(String, Vec<u8>)>, Error> {
+    let prefix = prefix.to_string();
+    let range = range.serialize_to_vec();
+    let iter_id = unsafe {
+        namada_vp_iter_prefix_range_post(
+            prefix.as_ptr() as _,
+            prefix.len() as _,
+            range.as_ptr() as _,
+            range.len() as _,
+        )
+    };
+    Ok(KeyValIterator(iter_id, PhantomData))
+}
+
 fn get_chain_id() -> Result<ChainId, Error> {
     let result = Vec::with_capacity(CHAIN_ID_LENGTH);
     unsafe {
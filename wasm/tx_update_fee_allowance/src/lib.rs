@@ -0,0 +1,27 @@
+//! A tx for granting or revoking a fee allowance.
+//! This tx uses `account::UpdateFeeAllowance` as its input.
+
+use namada_tx_prelude::*;
+
+#[transaction]
+fn apply_tx(ctx: &mut Ctx, batched_tx: BatchedTx) -> TxResult {
+    let data = ctx.get_tx_data(&batched_tx)?;
+    let tx_data = account::UpdateFeeAllowance::try_from_slice(&data[..])
+        .wrap_err("Failed to decode UpdateFeeAllowance tx data")?;
+
+    let granter = &tx_data.granter;
+    let grantee = &tx_data.grantee;
+    debug_log!("update fee allowance of {granter} for {grantee}");
+
+    // The tx must be authorized by the granter
+    ctx.insert_verifier(granter)?;
+
+    match tx_data.allowance {
+        Some(allowance) => {
+            account::grant_fee_allowance(ctx, granter, grantee, allowance)
+                .wrap_err("Failed to grant the fee allowance")
+        }
+        None => account::revoke_fee_allowance(ctx, granter, grantee)
+            .wrap_err("Failed to revoke the fee allowance"),
+    }
+}
@@ -9,6 +9,9 @@
 //! It allows to bond, unbond and withdraw tokens to and from PoS system with a
 //! valid signature.
 //!
+//! A fee allowance can be granted or revoked only with a valid signature of
+//! the granter.
+//!
 //! Any other storage key changes are allowed only with a valid signature.
 
 use booleans::BoolResultUnitExt;
@@ -214,6 +217,17 @@ fn validate_tx(
                 cmt,
                 &addr,
             ),
+            KeyType::FeeAllowance { granter } => {
+                // Only the granter has to authorize its fee allowances, the
+                // grantee doesn't
+                gadget.verify_signatures_when(
+                    || granter == &addr,
+                    ctx,
+                    &tx,
+                    cmt,
+                    &addr,
+                )
+            }
             KeyType::Masp | KeyType::Ibc => Ok(()),
             KeyType::Unknown => {
                 // Unknown changes require a valid signature
@@ -236,6 +250,9 @@ enum KeyType<'a> {
     },
     TokenMinted,
     TokenMinter(&'a Address),
+    FeeAllowance {
+        granter: &'a Address,
+    },
     Masp,
     Ibc,
     Unknown,
@@ -254,6 +271,8 @@ impl<'a> From<&'a storage::Key> for KeyType<'a> {
         } else if let Some(minter) = token::storage_key::is_any_minter_key(key)
         {
             Self::TokenMinter(minter)
+        } else if let Some([granter, _]) = account::is_fee_allowance_key(key) {
+            Self::FeeAllowance { granter }
         } else if token::storage_key::is_masp_key(key) {
             Self::Masp
         } else if ibc::is_ibc_key(key) {
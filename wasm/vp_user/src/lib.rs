@@ -9,6 +9,9 @@
 //! For validator a tx to change a validator's commission rate or metadata
 //! requires a valid signature(s) only from the validator.
 //!
+//! A fee allowance can be granted or revoked only with a valid signature of
+//! the granter.
+//!
 //! Any other storage key changes are allowed only with a valid signature.
 
 use booleans::BoolResultUnitExt;
@@ -185,6 +188,17 @@ fn validate_tx(
                     &addr,
                 )
             }
+            KeyType::FeeAllowance { granter } => {
+                // Only the granter has to authorize its fee allowances, the
+                // grantee doesn't
+                gadget.verify_signatures_when(
+                    || granter == &addr,
+                    ctx,
+                    &tx,
+                    cmt,
+                    &addr,
+                )
+            }
             KeyType::Masp | KeyType::Ibc => Ok(()),
             KeyType::Unknown => {
                 // Unknown changes require a valid signature
@@ -204,6 +218,7 @@ enum KeyType<'a> {
     TokenMinted,
     TokenMinter(&'a Address),
     Vp(&'a Address),
+    FeeAllowance { granter: &'a Address },
     Masp,
     Ibc,
     Unknown,
@@ -222,6 +237,8 @@ impl<'a> From<&'a storage::Key> for KeyType<'a> {
             Self::TokenMinter(minter)
         } else if let Some(address) = key.is_validity_predicate() {
             Self::Vp(address)
+        } else if let Some([granter, _]) = account::is_fee_allowance_key(key) {
+            Self::FeeAllowance { granter }
         } else if token::storage_key::is_masp_key(key) {
             Self::Masp
         } else if ibc::is_ibc_key(key) {